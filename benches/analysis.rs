@@ -0,0 +1,33 @@
+//! Analysis latency of representative npm packages
+//!
+//! Run with `cargo bench`; see the Benchmarks section of the README.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use tempfile::TempDir;
+use threatflux_package_security::benchmark::{self, Scenario};
+use threatflux_package_security::core::PackageAnalyzer;
+use threatflux_package_security::NpmAnalyzer;
+
+fn analysis(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let options = benchmark::options();
+
+    let mut group = c.benchmark_group("npm_analysis");
+    for scenario in Scenario::ALL {
+        let dir = TempDir::new().unwrap();
+        scenario.write(dir.path()).unwrap();
+        group.bench_function(scenario.name(), |b| {
+            b.to_async(&runtime).iter(|| async {
+                analyzer
+                    .analyze_with_options(dir.path(), &options)
+                    .await
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, analysis);
+criterion_main!(benches);
@@ -0,0 +1,650 @@
+//! Cargo (Rust crate) analyzer
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::context::AnalyzerContext;
+use crate::core::{
+    custom_rules, deadline, progress, transport, AnalysisOptions, AnalysisResult, Dependency,
+    DependencyAnalysis, DependencyType, Finding, InstallHook, MaliciousPattern, MemoryBudget,
+    PackageAnalyzer, PackageInfo, PartialParse, PatternMatcher, PhaseRecord, PinningReport,
+    QualityMetrics, RiskAssessment, RiskCalculator, ScanContext, ScanReason, Vulnerability,
+};
+use crate::ecosystem::Ecosystem;
+use crate::manifest::cargo::parse_manifest;
+use crate::vulnerability_db::{self, VulnerabilityDatabase};
+
+const MANIFEST: &str = "Cargo.toml";
+const LOCK_FILE: &str = "Cargo.lock";
+
+pub use crate::manifest::cargo::{CargoPackage, DeclaredDependency};
+
+/// Cargo analysis result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CargoAnalysisResult {
+    pub package: CargoPackage,
+    pub risk_assessment: RiskAssessment,
+    pub dependency_analysis: DependencyAnalysis,
+    pub vulnerabilities: Vec<Vulnerability>,
+    pub malicious_patterns: Vec<MaliciousPattern>,
+    /// Signs the package is tested, documented and maintained
+    #[serde(default)]
+    pub quality_metrics: QualityMetrics,
+}
+
+impl AnalysisResult for CargoAnalysisResult {
+    fn package_info(&self) -> &dyn PackageInfo {
+        &self.package
+    }
+
+    fn risk_assessment(&self) -> &RiskAssessment {
+        &self.risk_assessment
+    }
+
+    fn risk_assessment_mut(&mut self) -> &mut RiskAssessment {
+        &mut self.risk_assessment
+    }
+
+    fn dependency_analysis(&self) -> &DependencyAnalysis {
+        &self.dependency_analysis
+    }
+
+    fn vulnerabilities(&self) -> &[Vulnerability] {
+        &self.vulnerabilities
+    }
+
+    fn malicious_patterns(&self) -> &[MaliciousPattern] {
+        &self.malicious_patterns
+    }
+
+    fn quality_metrics(&self) -> QualityMetrics {
+        self.quality_metrics.clone()
+    }
+
+    fn install_hooks(&self) -> Vec<InstallHook> {
+        self.package.install_hooks()
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+}
+
+/// `[[package]]` entry from `Cargo.lock`
+#[derive(Debug, Clone, Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    /// `registry+…`, `sparse+…` or `git+…`; absent for workspace and path crates
+    #[serde(default)]
+    source: Option<String>,
+    /// `name`, `name version` or `name version (source)`
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+impl LockedPackage {
+    /// URL the crate is fetched from, without Cargo's source kind prefix
+    fn source_url(&self) -> Option<&str> {
+        let source = self.source.as_deref()?;
+        Some(source.split_once('+').map_or(source, |(_, url)| url))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Lockfile {
+    #[serde(default, rename = "package")]
+    packages: Vec<LockedPackage>,
+}
+
+/// Cargo crate analyzer
+pub struct CargoAnalyzer {
+    vuln_db: Option<Arc<dyn VulnerabilityDatabase>>,
+    pattern_matcher: PatternMatcher,
+}
+
+impl CargoAnalyzer {
+    /// Create a new Cargo analyzer
+    ///
+    /// No crates.io advisories are bundled; vulnerabilities are only looked
+    /// up once a database is set with [`Self::with_vulnerability_db`].
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            vuln_db: None,
+            pattern_matcher: PatternMatcher::new()?,
+        })
+    }
+
+    /// Create analyzer sharing the compiled rules and database of `context`
+    pub fn from_context(context: &AnalyzerContext) -> Self {
+        Self {
+            vuln_db: context.vulnerability_db(Ecosystem::Cargo),
+            pattern_matcher: context.pattern_matcher().clone(),
+        }
+    }
+
+    /// Look up vulnerabilities in a database, such as a loaded snapshot
+    pub fn with_vulnerability_db(mut self, db: impl Into<Arc<dyn VulnerabilityDatabase>>) -> Self {
+        self.vuln_db = Some(db.into());
+        self
+    }
+
+    /// Vulnerability database for an analysis with `options`, if any
+    fn database(
+        &self,
+        options: &AnalysisOptions,
+    ) -> Result<Option<Arc<dyn VulnerabilityDatabase>>> {
+        Ok(vulnerability_db::database_override(options)?.or_else(|| self.vuln_db.clone()))
+    }
+
+    /// Whether `path` is a crate or workspace directory or its `Cargo.toml`
+    pub(crate) fn is_crate(path: &Path) -> bool {
+        if path.is_dir() {
+            path.join(MANIFEST).exists()
+        } else {
+            path.file_name().and_then(|n| n.to_str()) == Some(MANIFEST)
+        }
+    }
+
+    /// Crate root for a crate directory or its `Cargo.toml`
+    fn crate_root(path: &Path) -> PathBuf {
+        if path.is_dir() {
+            path.to_path_buf()
+        } else {
+            path.parent().unwrap_or(Path::new(".")).to_path_buf()
+        }
+    }
+
+    /// Read `Cargo.lock`, if there is one
+    async fn read_lockfile(root: &Path, package: &mut CargoPackage) -> Vec<LockedPackage> {
+        let Ok(content) = tokio::fs::read_to_string(root.join(LOCK_FILE)).await else {
+            return vec![];
+        };
+        match toml::from_str::<Lockfile>(&content) {
+            Ok(lockfile) => {
+                package.has_lock_file = true;
+                lockfile.packages
+            }
+            Err(error) => {
+                package
+                    .skipped_files
+                    .push(PartialParse::new(LOCK_FILE, error));
+                vec![]
+            }
+        }
+    }
+
+    /// Build the dependency tree from locked versions, or from the manifest
+    /// when there is no lock file
+    ///
+    /// Crates without a source in the lock file are the workspace's own;
+    /// what they depend on is direct, the rest transitive. Each locked crate
+    /// appears once, under the first crate reaching it breadth-first, so the
+    /// tree covers the whole graph without repeating shared subtrees.
+    async fn analyze_dependencies(
+        &self,
+        package: &CargoPackage,
+        locked: &[LockedPackage],
+        options: &AnalysisOptions,
+    ) -> Result<DependencyAnalysis> {
+        let mut analysis = DependencyAnalysis::default();
+        if locked.is_empty() {
+            for declared in &package.declared_dependencies {
+                if declared.version_spec == "*" && declared.source.is_some() {
+                    continue;
+                }
+                let vulnerabilities = self
+                    .check(&declared.name, &declared.version_spec, options)
+                    .await?;
+                progress::dependency_resolved(&declared.name);
+                analysis.dependency_tree.push(Dependency {
+                    name: declared.name.clone(),
+                    version_spec: declared.version_spec.clone(),
+                    resolved_version: None,
+                    dependency_type: declared.dependency_type.clone(),
+                    is_direct: true,
+                    is_dev: declared.dependency_type == DependencyType::Development,
+                    vulnerabilities,
+                    license: None,
+                    dependencies: vec![],
+                    alias_target: None,
+                    range_vulnerabilities: vec![],
+                    shadowed_declarations: vec![],
+                    source: None,
+                    platform: None,
+                    hashes: vec![],
+                    marker: None,
+                });
+            }
+            analysis.summarize();
+            return Ok(analysis);
+        }
+
+        let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, locked) in locked.iter().enumerate() {
+            by_name.entry(&locked.name).or_default().push(index);
+        }
+        let resolve = |reference: &str| -> Option<usize> {
+            let mut parts = reference.split_whitespace();
+            let candidates = by_name.get(parts.next()?)?;
+            match parts.next() {
+                Some(version) => candidates
+                    .iter()
+                    .copied()
+                    .find(|&i| locked[i].version == version),
+                None => candidates.first().copied(),
+            }
+        };
+        let edges: Vec<Vec<usize>> = locked
+            .iter()
+            .map(|package| {
+                package
+                    .dependencies
+                    .iter()
+                    .filter_map(|dep| resolve(dep))
+                    .filter(|&dep| locked[dep].source.is_some())
+                    .collect()
+            })
+            .collect();
+
+        // Breadth-first from the direct dependencies of the workspace crates
+        let mut visited = HashSet::new();
+        let mut order = Vec::new();
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        for index in (0..locked.len()).filter(|&i| locked[i].source.is_none()) {
+            for &dep in &edges[index] {
+                if visited.insert(dep) {
+                    queue.push_back(dep);
+                }
+            }
+        }
+        let direct: HashSet<usize> = visited.clone();
+        let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+        while let Some(index) = queue.pop_front() {
+            order.push(index);
+            for &dep in &edges[index] {
+                if visited.insert(dep) {
+                    children.entry(index).or_default().push(dep);
+                    queue.push_back(dep);
+                }
+            }
+        }
+
+        // Transitive crates inherit the kind of the direct dependency above them
+        let declared = |name: &str| {
+            package
+                .declared_dependencies
+                .iter()
+                .filter(|d| d.name == name)
+                .min_by_key(|d| d.dependency_type.declaration_rank())
+        };
+        let mut kinds: HashMap<usize, DependencyType> = HashMap::new();
+        for &index in &order {
+            let kind = kinds.get(&index).cloned().unwrap_or_else(|| {
+                declared(&locked[index].name)
+                    .map(|d| d.dependency_type.clone())
+                    .unwrap_or(DependencyType::Runtime)
+            });
+            for &child in children.get(&index).into_iter().flatten() {
+                kinds.insert(child, kind.clone());
+            }
+            kinds.insert(index, kind);
+        }
+
+        // Children come after their parent in breadth-first order, so
+        // building in reverse always finds them complete
+        let mut built: HashMap<usize, Dependency> = HashMap::new();
+        for &index in order.iter().rev() {
+            let crate_ = &locked[index];
+            let dependency_type = kinds[&index].clone();
+            let version_spec = match declared(&crate_.name).filter(|_| direct.contains(&index)) {
+                Some(declared) => declared.version_spec.clone(),
+                None => crate_.version.clone(),
+            };
+            let dependencies = children
+                .get(&index)
+                .into_iter()
+                .flatten()
+                .filter_map(|child| built.remove(child))
+                .collect();
+            progress::dependency_resolved(&crate_.name);
+            built.insert(
+                index,
+                Dependency {
+                    name: crate_.name.clone(),
+                    version_spec,
+                    resolved_version: Some(crate_.version.clone()),
+                    is_direct: direct.contains(&index),
+                    is_dev: dependency_type == DependencyType::Development,
+                    dependency_type,
+                    vulnerabilities: self.check(&crate_.name, &crate_.version, options).await?,
+                    license: None,
+                    dependencies,
+                    alias_target: None,
+                    range_vulnerabilities: vec![],
+                    shadowed_declarations: vec![],
+                    source: None,
+                    platform: None,
+                    hashes: vec![],
+                    marker: None,
+                },
+            );
+        }
+        analysis.dependency_tree = order
+            .iter()
+            .filter(|index| direct.contains(index))
+            .filter_map(|index| built.remove(index))
+            .collect();
+        analysis.summarize();
+        Ok(analysis)
+    }
+
+    /// Advisories for a crate at `version`, when a database is set
+    async fn check(
+        &self,
+        name: &str,
+        version: &str,
+        options: &AnalysisOptions,
+    ) -> Result<Vec<Vulnerability>> {
+        match self.database(options)? {
+            Some(db) if options.check_vulnerabilities => {
+                db.check_package_with_policy(
+                    name,
+                    version,
+                    "cargo",
+                    options.prerelease_policy("cargo"),
+                )
+                .await
+            }
+            _ => Ok(vec![]),
+        }
+    }
+
+    /// Git and registry sources fetched without TLS or from hosts the source
+    /// policy does not allow, and a lock file that could not be read
+    fn source_findings(
+        package: &CargoPackage,
+        locked: &[LockedPackage],
+        options: &AnalysisOptions,
+    ) -> Vec<Finding> {
+        let declared: Vec<(&str, &str)> = package
+            .declared_dependencies
+            .iter()
+            .filter_map(|dep| Some((dep.name.as_str(), dep.source.as_deref()?)))
+            .filter(|(_, source)| source.contains("://"))
+            .collect();
+        let resolved: Vec<(&str, &str)> = locked
+            .iter()
+            .filter_map(|crate_| Some((crate_.name.as_str(), crate_.source_url()?)))
+            .collect();
+        let mut findings = transport::insecure_transport_findings(declared.clone(), MANIFEST);
+        findings.extend(transport::insecure_transport_findings(
+            resolved.clone(),
+            LOCK_FILE,
+        ));
+        findings.extend(options.source_policy.findings(declared, MANIFEST));
+        findings.extend(options.source_policy.findings(resolved, LOCK_FILE));
+        findings.extend(package.skipped_files.iter().map(|skipped| {
+            skipped
+                .to_finding()
+                .with_scan_reason(ScanReason::DependencyAnalysis)
+        }));
+        findings
+    }
+
+    /// Analysis of `path` without the deadline `options.timeout_seconds` sets
+    async fn run_analysis(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<CargoAnalysisResult> {
+        let budget = MemoryBudget::new(options.max_memory_bytes);
+        let root = Self::crate_root(path);
+        let content = tokio::fs::read_to_string(root.join(MANIFEST))
+            .await
+            .with_context(|| format!("No Cargo.toml found in {}", root.display()))?;
+        budget.charge(MANIFEST, content.len())?;
+        let mut package = parse_manifest(Some(&root), &content)?;
+        // Only the first `max_dependencies` are analyzed
+        let dependency_limit = options
+            .limits
+            .truncate_dependencies(MANIFEST, &mut package.declared_dependencies);
+        let locked = Self::read_lockfile(&root, &mut package).await;
+        progress::manifest_parsed(package.metadata());
+        progress::vuln_lookup_started(options);
+        let mut dependency_analysis = self
+            .analyze_dependencies(&package, &locked, options)
+            .await?;
+        deadline::dependencies_analyzed(&dependency_analysis);
+        budget.charge_for("dependencies", &dependency_analysis.dependency_tree)?;
+        dependency_analysis.license_findings =
+            options.license_policy.findings(&dependency_analysis);
+
+        let mut findings = if options.analyze_dependencies {
+            Self::source_findings(&package, &locked, options)
+        } else {
+            vec![]
+        };
+        findings.extend(dependency_limit);
+
+        // Build scripts run on the machine of everyone who compiles the crate
+        let mut malicious_patterns = if options.scan_malicious_patterns {
+            self.pattern_matcher.scan(&content, Some(MANIFEST))
+        } else {
+            vec![]
+        };
+        if let Some(build_script) = package
+            .build_script
+            .as_ref()
+            .filter(|_| options.scan_malicious_patterns)
+        {
+            if let Ok(source) = tokio::fs::read_to_string(root.join(build_script)).await {
+                for pattern in self.pattern_matcher.scan(&source, Some(build_script)) {
+                    if !malicious_patterns
+                        .iter()
+                        .any(|existing| existing.pattern_id == pattern.pattern_id)
+                    {
+                        malicious_patterns.push(pattern);
+                    }
+                }
+            }
+        }
+        malicious_patterns.extend(custom_rules::scan_package(&root, options)?);
+        progress::pattern_scan_complete(&malicious_patterns);
+        budget.charge_for("malicious patterns", &malicious_patterns)?;
+
+        let mut vulnerabilities = vec![];
+        let mut nodes: Vec<&Dependency> = dependency_analysis.dependency_tree.iter().collect();
+        while let Some(dep) = nodes.pop() {
+            vulnerabilities.extend(dep.vulnerabilities.clone());
+            nodes.extend(dep.dependencies.iter());
+        }
+
+        let risk_calculator = RiskCalculator::from_options(options);
+        let compile_time_code = package.build_script.is_some() || package.is_proc_macro;
+        let supply_chain_score = if compile_time_code { 20.0 } else { 0.0 };
+
+        let mut risk_score = risk_calculator.calculate(
+            &vulnerabilities,
+            &malicious_patterns,
+            false,
+            supply_chain_score,
+            50.0, // Default maintenance score
+        );
+        risk_calculator.apply_findings(&mut risk_score, &findings);
+        risk_calculator.apply_pinning(
+            &mut risk_score,
+            &PinningReport::from_analysis(&dependency_analysis, "cargo"),
+        );
+        risk_calculator.apply_install_hooks(&mut risk_score, &package.install_hooks());
+        let scoped_risk = risk_calculator.scoped_risk(
+            &risk_score,
+            &dependency_analysis,
+            &findings,
+            &options.production_dependency_types,
+        );
+
+        let scan_phases = vec![
+            PhaseRecord::new(
+                ScanReason::DependencyAnalysis,
+                options.analyze_dependencies,
+                findings.len(),
+            ),
+            PhaseRecord::new(
+                ScanReason::VulnerabilityCheck,
+                options.check_vulnerabilities && self.database(options)?.is_some(),
+                vulnerabilities.len(),
+            ),
+            PhaseRecord::new(
+                ScanReason::MaliciousPatterns,
+                options.scan_malicious_patterns,
+                malicious_patterns.len(),
+            ),
+        ];
+
+        let risk_assessment = RiskAssessment {
+            risk_score: risk_score.clone(),
+            summary: format!(
+                "Cargo crate '{}' has {} risk with {} dependencies and {} findings; {}",
+                package.metadata.name,
+                risk_score.risk_level,
+                dependency_analysis.total_dependencies,
+                findings.len(),
+                scoped_risk
+            ),
+            detailed_findings: findings,
+            recommendations: dependency_analysis
+                .upgrade_recommendations_with("cargo", options.recommend_prerelease_fixes),
+            security_posture: crate::core::SecurityPosture {
+                vulnerabilities_present: !vulnerabilities.is_empty(),
+                malicious_code_detected: !malicious_patterns.is_empty(),
+                supply_chain_risks: compile_time_code
+                    || locked.iter().any(|crate_| {
+                        crate_
+                            .source
+                            .as_deref()
+                            .is_some_and(|s| s.starts_with("git+"))
+                    }),
+                actively_maintained: true,
+                trusted_publisher: false,
+                security_practices_score: if package.has_lock_file { 70.0 } else { 40.0 },
+            },
+            scan_phases,
+            scoped_risk: Some(scoped_risk),
+            scan_context: Some(ScanContext::current(
+                self.database(options)?.and_then(|db| db.last_updated()),
+            )),
+        };
+
+        let quality_metrics =
+            QualityMetrics::assess(&root, package.metadata(), false, options.now());
+        Ok(CargoAnalysisResult {
+            package,
+            risk_assessment,
+            dependency_analysis,
+            vulnerabilities,
+            malicious_patterns,
+            quality_metrics,
+        })
+    }
+}
+
+#[async_trait]
+impl PackageAnalyzer for CargoAnalyzer {
+    type Package = CargoPackage;
+    type Analysis = CargoAnalysisResult;
+
+    async fn analyze(&self, path: &Path) -> Result<Self::Analysis> {
+        self.analyze_with_options(path, &AnalysisOptions::default())
+            .await
+    }
+
+    async fn analyze_with_options(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Self::Analysis> {
+        deadline::enforce(path, options, self.run_analysis(path, options)).await
+    }
+
+    fn can_analyze(&self, path: &Path) -> bool {
+        Self::is_crate(path)
+    }
+
+    fn name(&self) -> &str {
+        "Cargo Crate Analyzer"
+    }
+
+    fn supported_extensions(&self) -> Vec<&str> {
+        vec!["toml"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let manifest = r#"
+[workspace]
+members = ["cli"]
+
+[workspace.package]
+version = "0.4.2"
+license = "Apache-2.0"
+
+[workspace.dependencies]
+serde = { version = "1.0.190", features = ["derive"] }
+
+[package]
+name = "ledger-core"
+version.workspace = true
+license.workspace = true
+authors = ["Ana <ana@example.com>", "Bo <bo@example.com>"]
+repository = "https://github.com/example/ledger"
+build = false
+
+[dependencies]
+serde = { workspace = true }
+json = { package = "serde_json", version = "1" }
+
+[target.'cfg(windows)'.dependencies]
+winapi = "0.3"
+
+[dev-dependencies]
+fixtures = { git = "https://github.com/example/fixtures" }
+"#;
+        let package = parse_manifest(Some(Path::new(".")), manifest).unwrap();
+        assert_eq!(package.metadata.version, "0.4.2");
+        assert_eq!(package.metadata.license.as_deref(), Some("Apache-2.0"));
+        assert_eq!(
+            package.metadata.author.as_deref(),
+            Some("Ana <ana@example.com>, Bo <bo@example.com>")
+        );
+        assert_eq!(package.build_script, None);
+
+        let declared: Vec<(&str, &str)> = package
+            .declared_dependencies
+            .iter()
+            .map(|d| (d.name.as_str(), d.version_spec.as_str()))
+            .collect();
+        assert_eq!(
+            declared,
+            [
+                ("serde_json", "1"),
+                ("serde", "1.0.190"),
+                ("fixtures", "*"),
+                ("winapi", "0.3")
+            ]
+        );
+        assert_eq!(
+            package.declared_dependencies[2].source.as_deref(),
+            Some("https://github.com/example/fixtures")
+        );
+    }
+}
@@ -0,0 +1,959 @@
+//! Composer (PHP) package analyzer
+//!
+//! Metadata and declared requirements come from `composer.json`; pinned
+//! versions, where each package was downloaded from, and the dependencies
+//! pulled in transitively come from `composer.lock`.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::context::AnalyzerContext;
+use crate::core::{
+    custom_rules, deadline, progress, rules, transport, AnalysisOptions, AnalysisResult,
+    Dependency, DependencyAnalysis, DependencySource, DependencyType, Finding, FindingType,
+    InstallHook, MaliciousPattern, MemoryBudget, PackageAnalyzer, PackageInfo, PackageMetadata,
+    PartialParse, PatternMatcher, PhaseRecord, PinningReport, QualityMetrics, RiskAssessment,
+    RiskCalculator, RiskLevel, ScanContext, ScanReason, Vulnerability,
+};
+use crate::ecosystem::Ecosystem;
+use crate::vulnerability_db::{self, VulnerabilityDatabase};
+
+const MANIFEST: &str = "composer.json";
+const LOCK_FILE: &str = "composer.lock";
+
+/// Script events Composer runs for the root package on install or update
+const INSTALL_EVENTS: &[&str] = &[
+    "pre-install-cmd",
+    "post-install-cmd",
+    "pre-update-cmd",
+    "post-update-cmd",
+    "post-autoload-dump",
+    "post-root-package-install",
+    "post-create-project-cmd",
+];
+
+/// Composer package information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposerPackage {
+    /// A project without a `name` is named after its directory
+    pub metadata: PackageMetadata,
+    /// Package `type`, `library` unless set
+    pub package_kind: String,
+    /// Packages in `require` and `require-dev`; platform requirements such
+    /// as `php` and `ext-json` are left out
+    pub requirements: Vec<ComposerRequirement>,
+    /// Custom repositories, such as `vcs` and `path` ones
+    pub repositories: Vec<ComposerRepository>,
+    /// Script events run on install or update
+    pub install_scripts: Vec<String>,
+    /// Commands of the `install_scripts` events, in manifest order
+    #[serde(default)]
+    pub install_hooks: Vec<InstallHook>,
+    pub minimum_stability: Option<String>,
+    pub has_lock_file: bool,
+    /// Lockfile left out because it could not be parsed
+    #[serde(default)]
+    pub skipped_files: Vec<PartialParse>,
+}
+
+impl ComposerPackage {
+    /// Number of `require` or, with `dev`, `require-dev` packages
+    pub fn requirement_count(&self, dev: bool) -> usize {
+        self.requirements.iter().filter(|r| r.dev == dev).count()
+    }
+}
+
+/// A package `composer.json` requires
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComposerRequirement {
+    pub name: String,
+    pub constraint: String,
+    /// Declared in `require-dev`
+    pub dev: bool,
+}
+
+/// A repository declared in `composer.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComposerRepository {
+    /// Repository `type`, such as `vcs`, `composer` or `path`
+    pub kind: String,
+    pub url: String,
+}
+
+impl ComposerRepository {
+    /// Whether packages from it are checked out of version control
+    fn is_vcs(&self) -> bool {
+        matches!(
+            self.kind.as_str(),
+            "vcs" | "git" | "github" | "gitlab" | "bitbucket" | "hg" | "svn" | "fossil"
+        )
+    }
+}
+
+/// `composer.lock`, reduced to the fields used here
+#[derive(Debug, Default, Deserialize)]
+struct ComposerLock {
+    #[serde(default)]
+    packages: Vec<LockedPackage>,
+    #[serde(default, rename = "packages-dev")]
+    packages_dev: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    #[serde(default)]
+    source: Option<PackageOrigin>,
+    #[serde(default)]
+    dist: Option<PackageOrigin>,
+    #[serde(default, rename = "type")]
+    kind: Option<String>,
+    /// SPDX identifiers, any one of which the package may be used under
+    #[serde(default)]
+    license: Vec<String>,
+}
+
+/// `source` (a VCS checkout) or `dist` (an archive) of a locked package
+#[derive(Debug, Clone, Deserialize)]
+struct PackageOrigin {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    reference: Option<String>,
+}
+
+impl LockedPackage {
+    /// Whether the version is a branch, such as `dev-main` or `2.x-dev`,
+    /// rather than a tagged release
+    fn is_dev_version(&self) -> bool {
+        self.version.starts_with("dev-") || self.version.ends_with("-dev")
+    }
+
+    /// Where Composer installs the package from
+    ///
+    /// A branch version, a package without an archive, or one from a VCS
+    /// repository `composer.json` declares is checked out of version
+    /// control; anything else is downloaded from a registry.
+    fn origin(&self, vcs_urls: &[&str]) -> Option<DependencySource> {
+        if let Some(dist) = self.dist.as_ref().filter(|dist| dist.kind == "path") {
+            return Some(DependencySource::Path {
+                path: dist.url.clone(),
+            });
+        }
+        let from_vcs = |source: &&PackageOrigin| {
+            self.is_dev_version() || self.dist.is_none() || vcs_urls.contains(&source.url.as_str())
+        };
+        if let Some(source) = self.source.as_ref().filter(from_vcs) {
+            return Some(DependencySource::Git {
+                url: source.url.clone(),
+                revision: source.reference.clone(),
+            });
+        }
+        self.dist.as_ref().map(|dist| DependencySource::Registry {
+            url: dist.url.clone(),
+        })
+    }
+}
+
+impl PackageInfo for ComposerPackage {
+    fn metadata(&self) -> &PackageMetadata {
+        &self.metadata
+    }
+
+    fn package_type(&self) -> &str {
+        "composer"
+    }
+
+    fn custom_attributes(&self) -> HashMap<String, serde_json::Value> {
+        let mut attrs = HashMap::new();
+        attrs.insert("type".to_string(), serde_json::json!(self.package_kind));
+        attrs.insert(
+            "require_count".to_string(),
+            serde_json::json!(self.requirement_count(false)),
+        );
+        attrs.insert(
+            "require_dev_count".to_string(),
+            serde_json::json!(self.requirement_count(true)),
+        );
+        attrs.insert(
+            "install_scripts".to_string(),
+            serde_json::json!(self.install_scripts),
+        );
+        attrs.insert(
+            "minimum_stability".to_string(),
+            serde_json::json!(self.minimum_stability),
+        );
+        attrs.insert(
+            "has_lock_file".to_string(),
+            serde_json::json!(self.has_lock_file),
+        );
+        attrs
+    }
+}
+
+/// Composer analysis result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposerAnalysisResult {
+    pub package: ComposerPackage,
+    pub risk_assessment: RiskAssessment,
+    pub dependency_analysis: DependencyAnalysis,
+    pub vulnerabilities: Vec<Vulnerability>,
+    pub malicious_patterns: Vec<MaliciousPattern>,
+    /// Signs the package is tested, documented and maintained
+    #[serde(default)]
+    pub quality_metrics: QualityMetrics,
+}
+
+impl AnalysisResult for ComposerAnalysisResult {
+    fn package_info(&self) -> &dyn PackageInfo {
+        &self.package
+    }
+
+    fn risk_assessment(&self) -> &RiskAssessment {
+        &self.risk_assessment
+    }
+
+    fn risk_assessment_mut(&mut self) -> &mut RiskAssessment {
+        &mut self.risk_assessment
+    }
+
+    fn dependency_analysis(&self) -> &DependencyAnalysis {
+        &self.dependency_analysis
+    }
+
+    fn vulnerabilities(&self) -> &[Vulnerability] {
+        &self.vulnerabilities
+    }
+
+    fn malicious_patterns(&self) -> &[MaliciousPattern] {
+        &self.malicious_patterns
+    }
+
+    fn quality_metrics(&self) -> QualityMetrics {
+        self.quality_metrics.clone()
+    }
+
+    fn install_hooks(&self) -> Vec<InstallHook> {
+        self.package.install_hooks.clone()
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+}
+
+/// Composer package analyzer
+pub struct ComposerAnalyzer {
+    vuln_db: Option<Arc<dyn VulnerabilityDatabase>>,
+    pattern_matcher: PatternMatcher,
+}
+
+impl ComposerAnalyzer {
+    /// Create a new Composer analyzer
+    ///
+    /// No Packagist advisories are bundled; vulnerabilities are only looked
+    /// up once a database is set with [`Self::with_vulnerability_db`].
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            vuln_db: None,
+            pattern_matcher: PatternMatcher::new()?,
+        })
+    }
+
+    /// Create analyzer sharing the compiled rules and database of `context`
+    pub fn from_context(context: &AnalyzerContext) -> Self {
+        Self {
+            vuln_db: context.vulnerability_db(Ecosystem::Composer),
+            pattern_matcher: context.pattern_matcher().clone(),
+        }
+    }
+
+    /// Look up vulnerabilities in a database, such as a loaded snapshot
+    pub fn with_vulnerability_db(mut self, db: impl Into<Arc<dyn VulnerabilityDatabase>>) -> Self {
+        self.vuln_db = Some(db.into());
+        self
+    }
+
+    /// Vulnerability database for an analysis with `options`, if any
+    fn database(
+        &self,
+        options: &AnalysisOptions,
+    ) -> Result<Option<Arc<dyn VulnerabilityDatabase>>> {
+        Ok(vulnerability_db::database_override(options)?.or_else(|| self.vuln_db.clone()))
+    }
+
+    /// Whether `path` is a project directory, its `composer.json` or its lockfile
+    pub(crate) fn is_project(path: &Path) -> bool {
+        if path.is_dir() {
+            path.join(MANIFEST).exists()
+        } else {
+            matches!(
+                path.file_name().and_then(|n| n.to_str()),
+                Some(MANIFEST | LOCK_FILE)
+            )
+        }
+    }
+
+    /// Project root for a project directory or one of its files
+    fn project_root(path: &Path) -> PathBuf {
+        if path.is_dir() {
+            path.to_path_buf()
+        } else {
+            path.parent().unwrap_or(Path::new(".")).to_path_buf()
+        }
+    }
+
+    /// Whether a requirement is on the platform, such as `php` or
+    /// `ext-mbstring`, rather than a package
+    fn is_platform_requirement(name: &str) -> bool {
+        !name.contains('/')
+    }
+
+    /// Parse `composer.json`
+    fn parse_manifest(root: &Path, content: &str) -> Result<ComposerPackage> {
+        let manifest: Value =
+            serde_json::from_str(content).context("Failed to parse composer.json")?;
+        let string = |key: &str| manifest.get(key).and_then(Value::as_str).map(String::from);
+        let strings = |value: Option<&Value>| -> Vec<String> {
+            match value {
+                Some(Value::String(value)) => vec![value.clone()],
+                Some(Value::Array(values)) => values
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(String::from)
+                    .collect(),
+                _ => vec![],
+            }
+        };
+
+        let requirements = [("require", false), ("require-dev", true)]
+            .iter()
+            .flat_map(|&(key, dev)| {
+                manifest
+                    .get(key)
+                    .and_then(Value::as_object)
+                    .into_iter()
+                    .flatten()
+                    .filter(|(name, _)| !Self::is_platform_requirement(name))
+                    .map(move |(name, constraint)| ComposerRequirement {
+                        name: name.clone(),
+                        constraint: constraint.as_str().unwrap_or("*").to_string(),
+                        dev,
+                    })
+            })
+            .collect();
+
+        // `repositories` is a list, or an object keyed by repository name
+        let repositories = match manifest.get("repositories") {
+            Some(Value::Array(repositories)) => repositories.iter().collect(),
+            Some(Value::Object(repositories)) => repositories.values().collect(),
+            _ => vec![],
+        }
+        .into_iter()
+        .filter_map(|repository| {
+            Some(ComposerRepository {
+                kind: repository.get("type")?.as_str()?.to_string(),
+                url: repository.get("url")?.as_str()?.to_string(),
+            })
+        })
+        .collect();
+
+        let install_scripts: Vec<String> = manifest
+            .get("scripts")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .map(|(event, _)| event.clone())
+            .filter(|event| INSTALL_EVENTS.contains(&event.as_str()))
+            .collect();
+        // A script is one command or a list of them
+        let install_hooks = install_scripts
+            .iter()
+            .flat_map(|event| {
+                let commands = match &manifest["scripts"][event.as_str()] {
+                    Value::Array(commands) => commands.iter().collect(),
+                    command => vec![command],
+                };
+                commands
+                    .into_iter()
+                    .filter_map(Value::as_str)
+                    .map(|command| InstallHook::new(event, command, MANIFEST))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let authors: Vec<String> = manifest
+            .get("authors")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|author| author.get("name")?.as_str().map(String::from))
+            .collect();
+        let licenses = strings(manifest.get("license"));
+        let name = string("name").unwrap_or_else(|| {
+            root.canonicalize()
+                .ok()
+                .and_then(|root| root.file_name()?.to_str().map(String::from))
+                .unwrap_or_else(|| "project".to_string())
+        });
+
+        Ok(ComposerPackage {
+            metadata: PackageMetadata {
+                name,
+                version: string("version").unwrap_or_else(|| "unknown".to_string()),
+                description: string("description"),
+                author: (!authors.is_empty()).then(|| authors.join(", ")),
+                license: (!licenses.is_empty()).then(|| licenses.join(" OR ")),
+                homepage: string("homepage"),
+                repository: manifest
+                    .get("support")
+                    .and_then(|support| support.get("source"))
+                    .and_then(Value::as_str)
+                    .map(String::from),
+                keywords: strings(manifest.get("keywords")),
+                publish_date: None,
+            },
+            package_kind: string("type").unwrap_or_else(|| "library".to_string()),
+            requirements,
+            repositories,
+            install_scripts,
+            install_hooks,
+            minimum_stability: string("minimum-stability"),
+            has_lock_file: false,
+            skipped_files: vec![],
+        })
+    }
+
+    /// Read `composer.lock`, if there is one
+    ///
+    /// A lockfile past the limits of `options` pins nothing.
+    async fn read_lockfile(
+        root: &Path,
+        package: &mut ComposerPackage,
+        options: &AnalysisOptions,
+    ) -> ComposerLock {
+        let Ok(content) = tokio::fs::read_to_string(root.join(LOCK_FILE)).await else {
+            return ComposerLock::default();
+        };
+        if options.limits.check_json(LOCK_FILE, &content).is_err() {
+            return ComposerLock::default();
+        }
+        match serde_json::from_str(&content) {
+            Ok(lock) => {
+                package.has_lock_file = true;
+                lock
+            }
+            Err(error) => {
+                package
+                    .skipped_files
+                    .push(PartialParse::new(LOCK_FILE, error));
+                ComposerLock::default()
+            }
+        }
+    }
+
+    /// Build the dependency list from the lockfile, or from the requirements
+    /// when there is none
+    ///
+    /// Required packages are direct and the rest of the lockfile transitive.
+    /// Packages from `packages-dev` are development dependencies.
+    async fn analyze_dependencies(
+        &self,
+        package: &ComposerPackage,
+        lock: &ComposerLock,
+        options: &AnalysisOptions,
+    ) -> Result<DependencyAnalysis> {
+        let mut analysis = DependencyAnalysis::default();
+        let dependency_type = |dev: bool| {
+            if dev {
+                DependencyType::Development
+            } else {
+                DependencyType::Runtime
+            }
+        };
+        let constraint = |name: &str| {
+            package
+                .requirements
+                .iter()
+                .find(|r| r.name.eq_ignore_ascii_case(name))
+                .map(|r| r.constraint.clone())
+        };
+
+        if !package.has_lock_file {
+            for requirement in &package.requirements {
+                progress::dependency_resolved(&requirement.name);
+                analysis.dependency_tree.push(Dependency {
+                    name: requirement.name.clone(),
+                    version_spec: requirement.constraint.clone(),
+                    resolved_version: None,
+                    dependency_type: dependency_type(requirement.dev),
+                    is_direct: true,
+                    is_dev: requirement.dev,
+                    vulnerabilities: vec![],
+                    license: None,
+                    dependencies: vec![],
+                    alias_target: None,
+                    range_vulnerabilities: vec![],
+                    shadowed_declarations: vec![],
+                    source: None,
+                    platform: None,
+                    hashes: vec![],
+                    marker: None,
+                });
+            }
+            analysis.summarize();
+            return Ok(analysis);
+        }
+
+        let vcs_urls: Vec<&str> = package
+            .repositories
+            .iter()
+            .filter(|repository| repository.is_vcs())
+            .map(|repository| repository.url.as_str())
+            .collect();
+        let locked = lock
+            .packages
+            .iter()
+            .map(|locked| (locked, false))
+            .chain(lock.packages_dev.iter().map(|locked| (locked, true)));
+        let mut direct = Vec::new();
+        let mut transitive = Vec::new();
+        for (locked, dev) in locked {
+            let declared = constraint(&locked.name);
+            // Branches have no release to look advisories up for
+            let vulnerabilities = if locked.is_dev_version() {
+                vec![]
+            } else {
+                self.check(&locked.name, &locked.version, options).await?
+            };
+            let dependency = Dependency {
+                name: locked.name.clone(),
+                version_spec: declared.clone().unwrap_or_else(|| locked.version.clone()),
+                resolved_version: Some(locked.version.clone()),
+                dependency_type: dependency_type(dev),
+                is_direct: declared.is_some(),
+                is_dev: dev,
+                vulnerabilities,
+                license: (!locked.license.is_empty()).then(|| locked.license.join(" OR ")),
+                dependencies: vec![],
+                alias_target: None,
+                range_vulnerabilities: vec![],
+                shadowed_declarations: vec![],
+                source: locked.origin(&vcs_urls),
+                platform: None,
+                hashes: vec![],
+                marker: None,
+            };
+            progress::dependency_resolved(&dependency.name);
+            if dependency.is_direct {
+                direct.push(dependency);
+            } else {
+                transitive.push(dependency);
+            }
+        }
+        analysis.dependency_tree = direct.into_iter().chain(transitive).collect();
+        analysis.summarize();
+        Ok(analysis)
+    }
+
+    /// Advisories for a package at `version`, when a database is set
+    ///
+    /// Tags such as `v5.4.21` are looked up without their `v`.
+    async fn check(
+        &self,
+        name: &str,
+        version: &str,
+        options: &AnalysisOptions,
+    ) -> Result<Vec<Vulnerability>> {
+        match self.database(options)? {
+            Some(db) if options.check_vulnerabilities => {
+                db.check_package_with_policy(
+                    name,
+                    version.strip_prefix('v').unwrap_or(version),
+                    "composer",
+                    options.prerelease_policy("composer"),
+                )
+                .await
+            }
+            _ => Ok(vec![]),
+        }
+    }
+
+    /// Packages installed from version control or a local path instead of a
+    /// registry release
+    ///
+    /// Branch checkouts move with every update; development dependencies
+    /// only reach developer machines, so they rank lower.
+    fn non_registry_findings(analysis: &DependencyAnalysis) -> Vec<Finding> {
+        analysis
+            .dependency_tree
+            .iter()
+            .filter_map(|dep| {
+                let (kind, location) = match dep.source.as_ref()? {
+                    DependencySource::Registry { .. } => return None,
+                    DependencySource::Git { url, revision } => (
+                        "version control",
+                        match revision {
+                            Some(revision) => format!("{} @ {}", url, revision),
+                            None => url.clone(),
+                        },
+                    ),
+                    DependencySource::Path { path } => ("a local path", path.clone()),
+                    DependencySource::Url { url } => ("a URL", url.clone()),
+                };
+                let severity = if dep.is_dev {
+                    RiskLevel::Low
+                } else {
+                    RiskLevel::Medium
+                };
+                Some(
+                    Finding::new(
+                        FindingType::NonRegistrySource,
+                        severity,
+                        format!("Package '{}' is installed from {}", dep.name, kind),
+                        "The package is not a registry release, so it bypasses the registry's versioning and advisories, and a branch checkout changes with every update",
+                    )
+                    .with_evidence(format!(
+                        "version {}",
+                        dep.resolved_version.as_deref().unwrap_or(&dep.version_spec)
+                    ))
+                    .with_evidence(location)
+                    .with_affected_component(dep.name.clone())
+                    .with_scan_reason(ScanReason::DependencyAnalysis)
+                    .with_rule_id(rules::COMPOSER_NON_REGISTRY_SOURCE),
+                )
+            })
+            .collect()
+    }
+
+    /// Repositories and downloads without TLS or from hosts the source policy
+    /// does not allow, and a lockfile that could not be read
+    fn source_findings(
+        package: &ComposerPackage,
+        analysis: &DependencyAnalysis,
+        options: &AnalysisOptions,
+    ) -> Vec<Finding> {
+        let declared: Vec<(&str, &str)> = package
+            .repositories
+            .iter()
+            .filter(|repository| repository.kind != "path")
+            .map(|repository| (repository.kind.as_str(), repository.url.as_str()))
+            .collect();
+        let resolved: Vec<(&str, &str)> = analysis
+            .dependency_tree
+            .iter()
+            .filter_map(|dep| Some((dep.name.as_str(), dep.source.as_ref()?.url()?)))
+            .collect();
+        let mut findings = transport::insecure_transport_findings(declared.clone(), MANIFEST);
+        findings.extend(transport::insecure_transport_findings(
+            resolved.clone(),
+            LOCK_FILE,
+        ));
+        findings.extend(options.source_policy.findings(declared, MANIFEST));
+        findings.extend(options.source_policy.findings(resolved, LOCK_FILE));
+        findings.extend(package.skipped_files.iter().map(|skipped| {
+            skipped
+                .to_finding()
+                .with_scan_reason(ScanReason::DependencyAnalysis)
+        }));
+        findings
+    }
+
+    /// Supply-chain score from packages outside the registry, install-time
+    /// code and a missing lockfile
+    ///
+    /// `require` packages weigh more than `require-dev` ones, which are not
+    /// installed in production.
+    fn supply_chain_score(
+        package: &ComposerPackage,
+        analysis: &DependencyAnalysis,
+        install_time_code: bool,
+    ) -> f32 {
+        let non_registry = |dev: bool| {
+            analysis
+                .dependency_tree
+                .iter()
+                .filter(|dep| dep.is_dev == dev)
+                .filter(|dep| !matches!(dep.source, None | Some(DependencySource::Registry { .. })))
+                .count() as f32
+        };
+        let mut score = 20.0 * non_registry(false) + 5.0 * non_registry(true);
+        if install_time_code {
+            score += 20.0;
+        }
+        if !package.has_lock_file && !package.requirements.is_empty() {
+            score += 20.0;
+        }
+        score.min(100.0)
+    }
+
+    /// Analysis of `path` without the deadline `options.timeout_seconds` sets
+    async fn run_analysis(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<ComposerAnalysisResult> {
+        let budget = MemoryBudget::new(options.max_memory_bytes);
+        let root = Self::project_root(path);
+        let content = tokio::fs::read_to_string(root.join(MANIFEST))
+            .await
+            .with_context(|| format!("Failed to read {}", MANIFEST))?;
+        budget.charge(MANIFEST, content.len())?;
+        options.limits.check_json(MANIFEST, &content)?;
+        let mut package = Self::parse_manifest(&root, &content)?;
+        // Only the first `max_dependencies` are analyzed
+        let dependency_limit = options
+            .limits
+            .truncate_dependencies(MANIFEST, &mut package.requirements);
+        let lock = Self::read_lockfile(&root, &mut package, options).await;
+        progress::manifest_parsed(package.metadata());
+
+        progress::vuln_lookup_started(options);
+        let mut dependency_analysis = self.analyze_dependencies(&package, &lock, options).await?;
+        deadline::dependencies_analyzed(&dependency_analysis);
+        budget.charge_for("dependencies", &dependency_analysis.dependency_tree)?;
+        dependency_analysis.license_findings =
+            options.license_policy.findings(&dependency_analysis);
+
+        let mut findings = if options.analyze_dependencies {
+            let mut findings = Self::non_registry_findings(&dependency_analysis);
+            findings.extend(Self::source_findings(
+                &package,
+                &dependency_analysis,
+                options,
+            ));
+            findings
+        } else {
+            vec![]
+        };
+        findings.extend(dependency_limit);
+
+        // Scripts run on install; `composer.json` holds them inline
+        let mut malicious_patterns = if options.scan_malicious_patterns {
+            self.pattern_matcher.scan(&content, Some(MANIFEST))
+        } else {
+            vec![]
+        };
+        malicious_patterns.extend(custom_rules::scan_package(&root, options)?);
+        progress::pattern_scan_complete(&malicious_patterns);
+        budget.charge_for("malicious patterns", &malicious_patterns)?;
+
+        let vulnerabilities: Vec<Vulnerability> = dependency_analysis
+            .dependency_tree
+            .iter()
+            .flat_map(|dep| dep.vulnerabilities.iter().cloned())
+            .collect();
+
+        // Plugins run inside Composer whenever it installs packages
+        let plugins: HashSet<&str> = lock
+            .packages
+            .iter()
+            .chain(&lock.packages_dev)
+            .filter(|locked| locked.kind.as_deref() == Some("composer-plugin"))
+            .map(|locked| locked.name.as_str())
+            .collect();
+        let install_time_code = !package.install_scripts.is_empty() || !plugins.is_empty();
+
+        let risk_calculator = RiskCalculator::from_options(options);
+        let mut risk_score = risk_calculator.calculate(
+            &vulnerabilities,
+            &malicious_patterns,
+            false,
+            Self::supply_chain_score(&package, &dependency_analysis, install_time_code),
+            50.0, // Default maintenance score
+        );
+        risk_calculator.apply_findings(&mut risk_score, &findings);
+        risk_calculator.apply_pinning(
+            &mut risk_score,
+            &PinningReport::from_analysis(&dependency_analysis, "composer"),
+        );
+        risk_calculator.apply_install_hooks(&mut risk_score, &package.install_hooks);
+        let scoped_risk = risk_calculator.scoped_risk(
+            &risk_score,
+            &dependency_analysis,
+            &findings,
+            &options.production_dependency_types,
+        );
+
+        let scan_phases = vec![
+            PhaseRecord::new(
+                ScanReason::DependencyAnalysis,
+                options.analyze_dependencies,
+                findings.len(),
+            ),
+            PhaseRecord::new(
+                ScanReason::VulnerabilityCheck,
+                options.check_vulnerabilities && self.database(options)?.is_some(),
+                vulnerabilities.len(),
+            ),
+            PhaseRecord::new(
+                ScanReason::MaliciousPatterns,
+                options.scan_malicious_patterns,
+                malicious_patterns.len(),
+            ),
+        ];
+
+        let risk_assessment = RiskAssessment {
+            risk_score: risk_score.clone(),
+            summary: format!(
+                "Composer package '{}' has {} risk with {} dependencies ({} required, {} for development) and {} findings; {}",
+                package.metadata.name,
+                risk_score.risk_level,
+                dependency_analysis.total_dependencies,
+                package.requirement_count(false),
+                package.requirement_count(true),
+                findings.len(),
+                scoped_risk
+            ),
+            security_posture: crate::core::SecurityPosture {
+                vulnerabilities_present: !vulnerabilities.is_empty(),
+                malicious_code_detected: !malicious_patterns.is_empty(),
+                supply_chain_risks: install_time_code
+                    || findings
+                        .iter()
+                        .any(|f| f.finding_type == FindingType::NonRegistrySource),
+                actively_maintained: true,
+                trusted_publisher: false,
+                security_practices_score: if package.has_lock_file { 70.0 } else { 40.0 },
+            },
+            detailed_findings: findings,
+            recommendations: dependency_analysis
+                .upgrade_recommendations_with("composer", options.recommend_prerelease_fixes),
+            scan_phases,
+            scoped_risk: Some(scoped_risk),
+            scan_context: Some(ScanContext::current(
+                self.database(options)?.and_then(|db| db.last_updated()),
+            )),
+        };
+
+        let quality_metrics =
+            QualityMetrics::assess(&root, package.metadata(), false, options.now());
+        Ok(ComposerAnalysisResult {
+            package,
+            risk_assessment,
+            dependency_analysis,
+            vulnerabilities,
+            malicious_patterns,
+            quality_metrics,
+        })
+    }
+}
+
+#[async_trait]
+impl PackageAnalyzer for ComposerAnalyzer {
+    type Package = ComposerPackage;
+    type Analysis = ComposerAnalysisResult;
+
+    async fn analyze(&self, path: &Path) -> Result<Self::Analysis> {
+        self.analyze_with_options(path, &AnalysisOptions::default())
+            .await
+    }
+
+    async fn analyze_with_options(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Self::Analysis> {
+        deadline::enforce(path, options, self.run_analysis(path, options)).await
+    }
+
+    fn can_analyze(&self, path: &Path) -> bool {
+        Self::is_project(path)
+    }
+
+    fn name(&self) -> &str {
+        "Composer Analyzer"
+    }
+
+    fn supported_extensions(&self) -> Vec<&str> {
+        vec!["json", "lock"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest_and_origins() {
+        let manifest = r#"{
+            "name": "acme/shop",
+            "type": "project",
+            "license": ["MIT", "GPL-3.0-or-later"],
+            "require": {
+                "php": ">=8.1",
+                "ext-json": "*",
+                "symfony/http-kernel": "^5.4",
+                "acme/payments": "dev-main"
+            },
+            "require-dev": { "phpunit/phpunit": "^10.0" },
+            "repositories": [
+                { "type": "vcs", "url": "https://git.acme.test/payments.git" }
+            ],
+            "scripts": { "post-install-cmd": "php bin/setup", "test": "phpunit" }
+        }"#;
+        let package = ComposerAnalyzer::parse_manifest(Path::new("."), manifest).unwrap();
+        assert_eq!(package.metadata.name, "acme/shop");
+        assert_eq!(
+            package.metadata.license.as_deref(),
+            Some("MIT OR GPL-3.0-or-later")
+        );
+        assert_eq!(package.requirement_count(false), 2);
+        assert_eq!(package.requirement_count(true), 1);
+        assert_eq!(package.install_scripts, ["post-install-cmd"]);
+        assert_eq!(
+            package.install_hooks,
+            [InstallHook::new(
+                "post-install-cmd",
+                "php bin/setup",
+                MANIFEST
+            )]
+        );
+
+        let lock: ComposerLock = serde_json::from_str(
+            r#"{
+            "packages": [
+                {
+                    "name": "acme/payments",
+                    "version": "dev-main",
+                    "source": { "type": "git", "url": "https://git.acme.test/payments.git", "reference": "9f1c2e7" },
+                    "dist": { "type": "zip", "url": "https://git.acme.test/payments.zip", "reference": "9f1c2e7" }
+                },
+                {
+                    "name": "symfony/http-kernel",
+                    "version": "v5.4.20",
+                    "source": { "type": "git", "url": "https://github.com/symfony/http-kernel.git", "reference": "aaf3" },
+                    "dist": { "type": "zip", "url": "https://api.github.com/repos/symfony/http-kernel/zipball/aaf3", "reference": "aaf3" }
+                },
+                {
+                    "name": "acme/tools",
+                    "version": "1.0.0",
+                    "dist": { "type": "path", "url": "../tools" }
+                }
+            ]
+        }"#,
+        )
+        .unwrap();
+        let vcs = ["https://git.acme.test/payments.git"];
+        assert!(matches!(
+            lock.packages[0].origin(&vcs),
+            Some(DependencySource::Git { revision: Some(ref r), .. }) if r == "9f1c2e7"
+        ));
+        assert!(matches!(
+            lock.packages[1].origin(&vcs),
+            Some(DependencySource::Registry { .. })
+        ));
+        assert!(matches!(
+            lock.packages[2].origin(&vcs),
+            Some(DependencySource::Path { ref path }) if path == "../tools"
+        ));
+    }
+}
@@ -0,0 +1,755 @@
+//! VS Code and browser extension analyzer
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::analyzers::npm::NpmAnalyzer;
+use crate::context::AnalyzerContext;
+use crate::core::{
+    custom_rules, deadline, progress, rules, AnalysisOptions, AnalysisResult, DependencyAnalysis,
+    Finding, FindingType, MaliciousPattern, MemoryBudget, MetadataInjection, PackageAnalyzer,
+    PackageInfo, PackageMetadata, PatternMatcher, PhaseRecord, QualityMetrics, RiskAssessment,
+    RiskCalculator, RiskLevel, ScanReason, Vulnerability, VulnerabilityDatabase,
+};
+use crate::ecosystem::is_vscode_manifest;
+
+use crate::utils::typosquatting::TyposquattingTargetProvider;
+
+/// Host patterns granting access to every site
+const ALL_HOSTS: &[&str] = &["<all_urls>", "*://*/*", "http://*/*", "https://*/*"];
+
+/// Browser permissions exposing browsing data or control, with what they grant
+const SENSITIVE_PERMISSIONS: &[(&str, &str)] = &[
+    ("cookies", "read and write cookies"),
+    ("tabs", "see the URL and title of every tab"),
+    ("history", "read browsing history"),
+    ("webRequest", "observe all network requests"),
+    ("webRequestBlocking", "modify network requests"),
+    ("declarativeNetRequest", "rewrite network requests"),
+    ("debugger", "attach the debugger to pages"),
+    ("nativeMessaging", "talk to native programs"),
+    ("proxy", "route traffic through a proxy"),
+    ("management", "manage other extensions"),
+    ("clipboardRead", "read the clipboard"),
+    ("downloads", "download files"),
+    ("scripting", "inject scripts into pages"),
+];
+
+/// VS Code activation events that activate the extension for every workspace
+const EAGER_ACTIVATION: &[&str] = &["*", "onStartupFinished", "workspaceContains:**/*"];
+
+/// Source files larger than this are not scanned
+const MAX_SOURCE_SIZE: u64 = 2 * 1024 * 1024;
+
+/// Directories never scanned for extension source
+const SKIPPED_DIRS: &[&str] = &["node_modules", ".git"];
+
+/// Code loaded from the network and executed at runtime
+static REMOTE_CODE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"importScripts\(\s*['"`]https?://|\.src\s*=\s*['"`]https?://[^'"`]+\.js|fetch\([^;]*?\)[\s\S]{0,200}?(?:\beval|new\s+Function|\bFunction)\s*\(|executeScript\([^)]*\bcode\s*:"#,
+    )
+    .unwrap()
+});
+
+/// Kind of extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExtensionKind {
+    VsCode,
+    Browser,
+}
+
+/// A browser `content_scripts` entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentScript {
+    pub matches: Vec<String>,
+    pub js: Vec<String>,
+}
+
+/// Editor or browser extension information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtensionPackage {
+    pub metadata: PackageMetadata,
+    pub kind: ExtensionKind,
+    /// API permissions requested by a browser extension
+    pub permissions: Vec<String>,
+    pub host_permissions: Vec<String>,
+    pub content_scripts: Vec<ContentScript>,
+    pub background_scripts: Vec<String>,
+    pub content_security_policy: Option<String>,
+    pub activation_events: Vec<String>,
+    pub main: Option<String>,
+    pub contributes: Vec<String>,
+}
+
+impl PackageInfo for ExtensionPackage {
+    fn metadata(&self) -> &PackageMetadata {
+        &self.metadata
+    }
+
+    fn package_type(&self) -> &str {
+        match self.kind {
+            ExtensionKind::VsCode => "vscode-extension",
+            ExtensionKind::Browser => "browser-extension",
+        }
+    }
+
+    fn custom_attributes(&self) -> HashMap<String, serde_json::Value> {
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "permissions".to_string(),
+            serde_json::json!(self.permissions),
+        );
+        attrs.insert(
+            "host_permissions".to_string(),
+            serde_json::json!(self.host_permissions),
+        );
+        attrs.insert(
+            "activation_events".to_string(),
+            serde_json::json!(self.activation_events),
+        );
+        attrs
+    }
+}
+
+/// Extension analysis result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtensionAnalysisResult {
+    pub package: ExtensionPackage,
+    pub risk_assessment: RiskAssessment,
+    pub dependency_analysis: DependencyAnalysis,
+    pub vulnerabilities: Vec<Vulnerability>,
+    pub malicious_patterns: Vec<MaliciousPattern>,
+    pub scanned_sources: Vec<String>,
+    /// Signs the package is tested, documented and maintained
+    #[serde(default)]
+    pub quality_metrics: QualityMetrics,
+}
+
+impl AnalysisResult for ExtensionAnalysisResult {
+    fn package_info(&self) -> &dyn PackageInfo {
+        &self.package
+    }
+
+    fn risk_assessment(&self) -> &RiskAssessment {
+        &self.risk_assessment
+    }
+
+    fn risk_assessment_mut(&mut self) -> &mut RiskAssessment {
+        &mut self.risk_assessment
+    }
+
+    fn dependency_analysis(&self) -> &DependencyAnalysis {
+        &self.dependency_analysis
+    }
+
+    fn vulnerabilities(&self) -> &[Vulnerability] {
+        &self.vulnerabilities
+    }
+
+    fn malicious_patterns(&self) -> &[MaliciousPattern] {
+        &self.malicious_patterns
+    }
+
+    fn quality_metrics(&self) -> QualityMetrics {
+        self.quality_metrics.clone()
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+}
+
+/// VS Code and browser extension analyzer
+pub struct ExtensionAnalyzer {
+    npm_analyzer: NpmAnalyzer,
+    pattern_matcher: PatternMatcher,
+}
+
+impl ExtensionAnalyzer {
+    /// Create a new extension analyzer
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            npm_analyzer: NpmAnalyzer::new()?,
+            pattern_matcher: PatternMatcher::new()?,
+        })
+    }
+
+    /// Create analyzer with custom database path
+    pub fn with_db_path(db_path: &Path) -> Result<Self> {
+        Ok(Self {
+            npm_analyzer: NpmAnalyzer::with_db_path(db_path)?,
+            pattern_matcher: PatternMatcher::new()?,
+        })
+    }
+
+    /// Create analyzer sharing the compiled rules and database of `context`
+    pub fn from_context(context: &AnalyzerContext) -> Result<Self> {
+        Ok(Self {
+            npm_analyzer: NpmAnalyzer::from_context(context)?,
+            pattern_matcher: context.pattern_matcher().clone(),
+        })
+    }
+
+    /// Look up vulnerabilities in another database, such as a loaded snapshot
+    pub fn with_vulnerability_db(mut self, db: impl Into<Arc<dyn VulnerabilityDatabase>>) -> Self {
+        self.npm_analyzer = self.npm_analyzer.with_vulnerability_db(db);
+        self
+    }
+
+    /// Also compare package names against candidates from `provider`
+    pub fn with_typosquatting_provider(
+        mut self,
+        provider: Arc<dyn TyposquattingTargetProvider>,
+    ) -> Self {
+        self.npm_analyzer = self.npm_analyzer.with_typosquatting_provider(provider);
+        self
+    }
+
+    /// Locate the manifest of an extension directory
+    pub(crate) fn manifest_path(path: &Path) -> Option<(PathBuf, ExtensionKind)> {
+        if !path.is_dir() {
+            return None;
+        }
+        let manifest = path.join("manifest.json");
+        if let Some(json) = read_json(&manifest) {
+            if json.get("manifest_version").is_some() {
+                return Some((manifest, ExtensionKind::Browser));
+            }
+        }
+        let package_json = path.join("package.json");
+        let json = read_json(&package_json)?;
+        is_vscode_manifest(&json).then_some((package_json, ExtensionKind::VsCode))
+    }
+
+    /// Parse an extension manifest
+    fn parse_manifest(&self, json: &Value, kind: ExtensionKind) -> Result<ExtensionPackage> {
+        let obj = json
+            .as_object()
+            .ok_or_else(|| anyhow::anyhow!("extension manifest is not an object"))?;
+        let text = |key: &str| obj.get(key).and_then(|v| v.as_str()).map(String::from);
+
+        let name = match kind {
+            ExtensionKind::VsCode => match (text("publisher"), text("name")) {
+                (Some(publisher), Some(name)) => format!("{}.{}", publisher, name),
+                (None, Some(name)) => name,
+                _ => "unknown".to_string(),
+            },
+            ExtensionKind::Browser => text("name").unwrap_or_else(|| "unknown".to_string()),
+        };
+        let metadata = PackageMetadata {
+            name,
+            version: text("version").unwrap_or_else(|| "0.0.0".to_string()),
+            description: text("description"),
+            author: text("author").or_else(|| text("publisher")),
+            license: text("license"),
+            homepage: text("homepage").or_else(|| text("homepage_url")),
+            repository: obj.get("repository").and_then(|v| {
+                v.as_str()
+                    .map(String::from)
+                    .or_else(|| v.get("url").and_then(|u| u.as_str()).map(String::from))
+            }),
+            keywords: strings(obj.get("keywords")),
+            publish_date: None,
+        };
+
+        let content_scripts = obj
+            .get("content_scripts")
+            .and_then(|v| v.as_array())
+            .map(|scripts| {
+                scripts
+                    .iter()
+                    .map(|script| ContentScript {
+                        matches: strings(script.get("matches")),
+                        js: strings(script.get("js")),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let background = obj.get("background");
+        let mut background_scripts = strings(background.and_then(|b| b.get("scripts")));
+        if let Some(worker) = background
+            .and_then(|b| b.get("service_worker"))
+            .and_then(|w| w.as_str())
+        {
+            background_scripts.push(worker.to_string());
+        }
+
+        // Manifest V2 uses a string, V3 an object keyed by context
+        let content_security_policy = obj.get("content_security_policy").map(|csp| match csp {
+            Value::String(policy) => policy.clone(),
+            Value::Object(policies) => policies
+                .values()
+                .filter_map(|p| p.as_str())
+                .collect::<Vec<_>>()
+                .join("; "),
+            _ => String::new(),
+        });
+
+        // Manifest V2 mixes host patterns into `permissions`
+        let mut permissions = Vec::new();
+        let mut host_permissions = strings(obj.get("host_permissions"));
+        for permission in strings(obj.get("permissions"))
+            .into_iter()
+            .chain(strings(obj.get("optional_permissions")))
+        {
+            if permission.contains("://") || permission == "<all_urls>" {
+                host_permissions.push(permission);
+            } else {
+                permissions.push(permission);
+            }
+        }
+
+        Ok(ExtensionPackage {
+            metadata,
+            kind,
+            permissions,
+            host_permissions,
+            content_scripts,
+            background_scripts,
+            content_security_policy,
+            activation_events: strings(obj.get("activationEvents")),
+            main: text("main").or_else(|| text("browser")),
+            contributes: obj
+                .get("contributes")
+                .and_then(|c| c.as_object())
+                .map(|c| c.keys().cloned().collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Findings for capabilities broader than an extension should need
+    pub fn capability_findings(&self, package: &ExtensionPackage) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        let all_hosts: Vec<&String> = package
+            .host_permissions
+            .iter()
+            .filter(|p| ALL_HOSTS.contains(&p.as_str()))
+            .collect();
+        let sensitive: Vec<(&str, &str)> = SENSITIVE_PERMISSIONS
+            .iter()
+            .copied()
+            .filter(|(name, _)| package.permissions.iter().any(|p| p == name))
+            .collect();
+
+        if !all_hosts.is_empty() {
+            // Cookie access on every site is enough to steal every session
+            let severity = if sensitive
+                .iter()
+                .any(|(name, _)| matches!(*name, "cookies" | "webRequest" | "debugger"))
+            {
+                RiskLevel::Critical
+            } else {
+                RiskLevel::High
+            };
+            let mut finding = Finding::new(
+                FindingType::OverbroadCapability,
+                severity,
+                "Extension can access every website",
+                "Access to all hosts lets the extension read and modify every page the user visits",
+            )
+            .with_affected_component("host_permissions")
+            .with_scan_reason(ScanReason::Manifest)
+            .with_rule_id(rules::EXTENSION_ALL_HOSTS);
+            for host in all_hosts {
+                finding = finding.with_evidence(format!("Host permission: {}", host));
+            }
+            for (name, _) in &sensitive {
+                finding = finding.with_evidence(format!("Combined with permission: {}", name));
+            }
+            findings.push(finding);
+        }
+
+        if !sensitive.is_empty() {
+            let mut finding = Finding::new(
+                FindingType::OverbroadCapability,
+                if sensitive.len() >= 3 {
+                    RiskLevel::High
+                } else {
+                    RiskLevel::Medium
+                },
+                format!(
+                    "Extension requests {} sensitive permissions",
+                    sensitive.len()
+                ),
+                format!(
+                    "The extension can {}",
+                    sensitive
+                        .iter()
+                        .map(|(_, grants)| *grants)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            )
+            .with_affected_component("permissions")
+            .with_scan_reason(ScanReason::Manifest)
+            .with_rule_id(rules::EXTENSION_SENSITIVE_PERMISSIONS);
+            for (name, _) in &sensitive {
+                finding = finding.with_evidence(format!("Permission: {}", name));
+            }
+            findings.push(finding);
+        }
+
+        for script in &package.content_scripts {
+            let remote: Vec<&String> = script
+                .js
+                .iter()
+                .filter(|js| js.starts_with("http://") || js.starts_with("https://"))
+                .collect();
+            if !remote.is_empty() {
+                let mut finding = Finding::new(
+                    FindingType::RuntimeRemoteCode,
+                    RiskLevel::Critical,
+                    "Content script is loaded from a remote URL",
+                    "Remote content scripts can be swapped for malicious code at any time without an update",
+                )
+                .with_scan_reason(ScanReason::Manifest)
+                .with_rule_id(rules::EXTENSION_REMOTE_CONTENT_SCRIPT);
+                for url in remote {
+                    finding = finding
+                        .with_evidence(format!("Content script: {}", url))
+                        .with_affected_component(url.clone());
+                }
+                findings.push(finding);
+            }
+            if script
+                .matches
+                .iter()
+                .any(|m| ALL_HOSTS.contains(&m.as_str()))
+            {
+                let mut finding = Finding::new(
+                    FindingType::OverbroadCapability,
+                    RiskLevel::Medium,
+                    "Content script runs on every website",
+                    "The script is injected into every page the user visits",
+                )
+                .with_scan_reason(ScanReason::Manifest)
+                .with_rule_id(rules::EXTENSION_CONTENT_SCRIPT_ALL_HOSTS);
+                for js in &script.js {
+                    finding = finding.with_affected_component(js.clone());
+                }
+                findings
+                    .push(finding.with_evidence(format!("matches: {}", script.matches.join(", "))));
+            }
+        }
+
+        if let Some(policy) = &package.content_security_policy {
+            if policy.contains("'unsafe-eval'")
+                || policy
+                    .split(';')
+                    .filter(|directive| directive.trim_start().starts_with("script-src"))
+                    .any(|directive| directive.contains("http:") || directive.contains("https:"))
+            {
+                findings.push(
+                    Finding::new(
+                        FindingType::RuntimeRemoteCode,
+                        RiskLevel::High,
+                        "Content security policy allows remote or evaluated code",
+                        "The extension's policy permits running code that is not shipped in the package",
+                    )
+                    .with_evidence(format!("content_security_policy: {}", policy))
+                    .with_affected_component("content_security_policy")
+                    .with_scan_reason(ScanReason::Manifest)
+                    .with_rule_id(rules::EXTENSION_PERMISSIVE_CSP),
+                );
+            }
+        }
+
+        let eager: Vec<&String> = package
+            .activation_events
+            .iter()
+            .filter(|event| EAGER_ACTIVATION.contains(&event.as_str()))
+            .collect();
+        if !eager.is_empty() {
+            let mut finding = Finding::new(
+                FindingType::OverbroadCapability,
+                if eager.iter().any(|e| e.as_str() == "*") {
+                    RiskLevel::Medium
+                } else {
+                    RiskLevel::Low
+                },
+                "Extension activates in every workspace",
+                "The extension runs on startup regardless of the files being edited",
+            )
+            .with_affected_component("activationEvents")
+            .with_scan_reason(ScanReason::Manifest)
+            .with_rule_id(rules::EXTENSION_EAGER_ACTIVATION);
+            for event in eager {
+                finding = finding.with_evidence(format!("Activation event: {}", event));
+            }
+            findings.push(finding);
+        }
+
+        findings
+    }
+
+    /// Collect JavaScript sources of an extension
+    fn source_files(root: &Path) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let mut stack = vec![root.to_path_buf()];
+        while let Some(dir) = stack.pop() {
+            for entry in std::fs::read_dir(&dir)? {
+                let path = entry?.path();
+                let file_name = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or_default();
+                if path.is_dir() {
+                    if !SKIPPED_DIRS.contains(&file_name) {
+                        stack.push(path);
+                    }
+                } else if matches!(
+                    path.extension().and_then(|e| e.to_str()),
+                    Some("js" | "mjs" | "cjs")
+                ) && std::fs::metadata(&path)?.len() <= MAX_SOURCE_SIZE
+                {
+                    files.push(path);
+                }
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Analysis of `path` without the deadline `options.timeout_seconds` sets
+    async fn run_analysis(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<ExtensionAnalysisResult> {
+        let budget = MemoryBudget::new(options.max_memory_bytes);
+        let (manifest_path, kind) = Self::manifest_path(path)
+            .ok_or_else(|| anyhow::anyhow!("No extension manifest in {}", path.display()))?;
+        let content = tokio::fs::read_to_string(&manifest_path)
+            .await
+            .context("Failed to read extension manifest")?;
+        let manifest_name = match kind {
+            ExtensionKind::VsCode => "package.json",
+            ExtensionKind::Browser => "manifest.json",
+        };
+        budget.charge(manifest_name, content.len())?;
+        options.limits.check_json(manifest_name, &content)?;
+        let json: Value =
+            serde_json::from_str(&content).context("Failed to parse extension manifest")?;
+        let mut package = self.parse_manifest(&json, kind)?;
+        progress::manifest_parsed(package.metadata());
+
+        // VS Code extensions are npm packages and bundle their npm dependencies
+        let mut dependency_analysis = if kind == ExtensionKind::VsCode {
+            progress::vuln_lookup_started(options);
+            self.npm_analyzer
+                .analyze_dependencies(&json, options)
+                .await?
+        } else {
+            DependencyAnalysis::default()
+        };
+        deadline::dependencies_analyzed(&dependency_analysis);
+        budget.charge_for("dependencies", &dependency_analysis.dependency_tree)?;
+        dependency_analysis.license_findings =
+            options.license_policy.findings(&dependency_analysis);
+
+        let mut findings = self.capability_findings(&package);
+        findings.extend(MetadataInjection::apply(
+            &mut package.metadata,
+            options.sanitize_metadata,
+        ));
+        if let Err(exceeded) = options
+            .limits
+            .check_dependency_count(manifest_name, NpmAnalyzer::declared_dependency_count(&json))
+        {
+            findings.push(exceeded.to_finding());
+        }
+        let manifest_findings = findings.len();
+        if options.analyze_dependencies {
+            findings.extend(dependency_analysis.unpinned_findings());
+        }
+        let dependency_findings = findings.len() - manifest_findings;
+
+        let mut malicious_patterns = Vec::new();
+        let mut scanned_sources = Vec::new();
+        if options.scan_malicious_patterns {
+            for file in Self::source_files(path)? {
+                let relative = file
+                    .strip_prefix(path)
+                    .unwrap_or(&file)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                let Ok(source) = tokio::fs::read_to_string(&file).await else {
+                    continue;
+                };
+                for pattern in self.pattern_matcher.scan(&source, Some(&relative)) {
+                    if !malicious_patterns
+                        .iter()
+                        .any(|p: &MaliciousPattern| p.pattern_id == pattern.pattern_id)
+                    {
+                        malicious_patterns.push(pattern);
+                    }
+                }
+                if let Some(m) = REMOTE_CODE.find(&source) {
+                    findings.push(
+                        Finding::new(
+                            FindingType::RuntimeRemoteCode,
+                            RiskLevel::High,
+                            format!("{} executes code fetched at runtime", relative),
+                            "Code downloaded after installation bypasses store review and can change at any time",
+                        )
+                        .with_evidence(m.as_str().chars().take(200).collect::<String>())
+                        .with_affected_component(relative.clone())
+                        .with_scan_reason(ScanReason::MaliciousPatterns)
+                        .with_rule_id(rules::EXTENSION_RUNTIME_REMOTE_CODE),
+                    );
+                }
+                scanned_sources.push(relative);
+            }
+        }
+
+        malicious_patterns.extend(custom_rules::scan_package(path, options)?);
+        progress::pattern_scan_complete(&malicious_patterns);
+        budget.charge_for("malicious patterns", &malicious_patterns)?;
+
+        let mut vulnerabilities = vec![];
+        for dep in &dependency_analysis.dependency_tree {
+            vulnerabilities.extend(dep.vulnerabilities.clone());
+        }
+
+        let risk_calculator = RiskCalculator::from_options(options);
+        let mut risk_score = risk_calculator.calculate(
+            &vulnerabilities,
+            &malicious_patterns,
+            false,
+            0.0,
+            50.0, // Default maintenance score
+        );
+        risk_calculator.apply_findings(&mut risk_score, &findings);
+        let scoped_risk = risk_calculator.scoped_risk(
+            &risk_score,
+            &dependency_analysis,
+            &findings,
+            &options.production_dependency_types,
+        );
+
+        let scan_phases = vec![
+            PhaseRecord::new(ScanReason::Manifest, true, manifest_findings),
+            PhaseRecord::new(
+                ScanReason::DependencyAnalysis,
+                options.analyze_dependencies,
+                dependency_findings,
+            ),
+            PhaseRecord::new(
+                ScanReason::VulnerabilityCheck,
+                options.check_vulnerabilities,
+                vulnerabilities.len(),
+            ),
+            PhaseRecord::new(
+                ScanReason::MaliciousPatterns,
+                options.scan_malicious_patterns,
+                malicious_patterns.len() + findings.len() - manifest_findings - dependency_findings,
+            ),
+        ];
+
+        let remote_code = findings
+            .iter()
+            .any(|f| f.finding_type == FindingType::RuntimeRemoteCode);
+        let risk_assessment = RiskAssessment {
+            risk_score: risk_score.clone(),
+            summary: format!(
+                "{} '{}' has {} risk with {} findings; {}",
+                package.package_type(),
+                package.metadata.name,
+                risk_score.risk_level,
+                findings.len(),
+                scoped_risk
+            ),
+            detailed_findings: findings,
+            recommendations: dependency_analysis
+                .upgrade_recommendations_with("npm", options.recommend_prerelease_fixes),
+            security_posture: crate::core::SecurityPosture {
+                vulnerabilities_present: !vulnerabilities.is_empty(),
+                malicious_code_detected: !malicious_patterns.is_empty(),
+                supply_chain_risks: remote_code,
+                actively_maintained: true,
+                trusted_publisher: false,
+                security_practices_score: if remote_code { 20.0 } else { 50.0 },
+            },
+            scan_phases,
+            scoped_risk: Some(scoped_risk),
+            scan_context: Some(self.npm_analyzer.scan_context(options)?),
+        };
+
+        // VS Code extensions run their tests through npm scripts
+        let scripts = json
+            .get("scripts")
+            .and_then(|scripts| serde_json::from_value(scripts.clone()).ok())
+            .unwrap_or_default();
+        let quality_metrics = QualityMetrics::assess(
+            path,
+            package.metadata(),
+            NpmAnalyzer::declares_tests(&scripts),
+            options.now(),
+        );
+        Ok(ExtensionAnalysisResult {
+            package,
+            risk_assessment,
+            dependency_analysis,
+            vulnerabilities,
+            malicious_patterns,
+            quality_metrics,
+            scanned_sources,
+        })
+    }
+}
+
+#[async_trait]
+impl PackageAnalyzer for ExtensionAnalyzer {
+    type Package = ExtensionPackage;
+    type Analysis = ExtensionAnalysisResult;
+
+    async fn analyze(&self, path: &Path) -> Result<Self::Analysis> {
+        self.analyze_with_options(path, &AnalysisOptions::default())
+            .await
+    }
+
+    async fn analyze_with_options(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Self::Analysis> {
+        deadline::enforce(path, options, self.run_analysis(path, options)).await
+    }
+
+    fn can_analyze(&self, path: &Path) -> bool {
+        Self::manifest_path(path).is_some()
+    }
+
+    fn name(&self) -> &str {
+        "Extension Analyzer"
+    }
+
+    fn supported_extensions(&self) -> Vec<&str> {
+        vec![]
+    }
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+fn strings(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
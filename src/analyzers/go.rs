@@ -0,0 +1,727 @@
+//! Go module analyzer
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::context::AnalyzerContext;
+use crate::core::{
+    custom_rules, deadline, progress, rules, AnalysisOptions, AnalysisResult, Dependency,
+    DependencyAnalysis, DependencyType, Finding, FindingType, MaliciousPattern, MemoryBudget,
+    PackageAnalyzer, PackageInfo, PackageMetadata, PatternMatcher, PhaseRecord, PinningReport,
+    QualityMetrics, RiskAssessment, RiskCalculator, RiskLevel, ScanContext, ScanReason,
+    Vulnerability,
+};
+use crate::ecosystem::Ecosystem;
+use crate::versioning::VersionSchemeRegistry;
+use crate::vulnerability_db::{self, VulnerabilityDatabase};
+
+const MOD_FILE: &str = "go.mod";
+const SUM_FILE: &str = "go.sum";
+
+/// Code hosts whose module paths name the repository in their first three segments
+const REPOSITORY_HOSTS: &[&str] = &["github.com", "gitlab.com", "bitbucket.org"];
+
+/// Go module information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoModule {
+    /// Name is the full module path, such as `github.com/foo/bar`
+    pub metadata: PackageMetadata,
+    pub go_version: Option<String>,
+    pub toolchain: Option<String>,
+    pub requirements: Vec<GoRequirement>,
+    pub replacements: Vec<GoReplacement>,
+    pub exclusions: Vec<GoExclusion>,
+    pub has_sum_file: bool,
+    /// `(module path, version)` pairs with a hash in `go.sum`
+    #[serde(skip)]
+    pub sums: HashSet<(String, String)>,
+}
+
+/// A `require` directive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoRequirement {
+    pub path: String,
+    pub version: String,
+    /// Marked `// indirect`: needed by a dependency, not imported by the module
+    pub indirect: bool,
+}
+
+/// A `replace` directive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoReplacement {
+    pub path: String,
+    /// Only this version is replaced; every version when `None`
+    pub version: Option<String>,
+    /// Replacement module path, or a local directory
+    pub new_path: String,
+    /// Version of the replacement module, `None` for a local directory
+    pub new_version: Option<String>,
+}
+
+impl GoReplacement {
+    /// Whether the replacement is a directory rather than a module
+    pub fn is_local(&self) -> bool {
+        self.new_version.is_none()
+            && (self.new_path.starts_with("./")
+                || self.new_path.starts_with("../")
+                || self.new_path.starts_with('/'))
+    }
+}
+
+/// An `exclude` directive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoExclusion {
+    pub path: String,
+    pub version: String,
+}
+
+impl PackageInfo for GoModule {
+    fn metadata(&self) -> &PackageMetadata {
+        &self.metadata
+    }
+
+    fn package_type(&self) -> &str {
+        "go"
+    }
+
+    fn custom_attributes(&self) -> HashMap<String, serde_json::Value> {
+        let mut attrs = HashMap::new();
+        attrs.insert("go_version".to_string(), serde_json::json!(self.go_version));
+        attrs.insert("toolchain".to_string(), serde_json::json!(self.toolchain));
+        attrs.insert(
+            "replacements".to_string(),
+            serde_json::json!(self.replacements),
+        );
+        attrs.insert("exclusions".to_string(), serde_json::json!(self.exclusions));
+        attrs.insert(
+            "has_sum_file".to_string(),
+            serde_json::json!(self.has_sum_file),
+        );
+        attrs
+    }
+}
+
+/// Go analysis result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoAnalysisResult {
+    pub package: GoModule,
+    pub risk_assessment: RiskAssessment,
+    pub dependency_analysis: DependencyAnalysis,
+    pub vulnerabilities: Vec<Vulnerability>,
+    pub malicious_patterns: Vec<MaliciousPattern>,
+    /// Signs the package is tested, documented and maintained
+    #[serde(default)]
+    pub quality_metrics: QualityMetrics,
+}
+
+impl AnalysisResult for GoAnalysisResult {
+    fn package_info(&self) -> &dyn PackageInfo {
+        &self.package
+    }
+
+    fn risk_assessment(&self) -> &RiskAssessment {
+        &self.risk_assessment
+    }
+
+    fn risk_assessment_mut(&mut self) -> &mut RiskAssessment {
+        &mut self.risk_assessment
+    }
+
+    fn dependency_analysis(&self) -> &DependencyAnalysis {
+        &self.dependency_analysis
+    }
+
+    fn vulnerabilities(&self) -> &[Vulnerability] {
+        &self.vulnerabilities
+    }
+
+    fn malicious_patterns(&self) -> &[MaliciousPattern] {
+        &self.malicious_patterns
+    }
+
+    fn quality_metrics(&self) -> QualityMetrics {
+        self.quality_metrics.clone()
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+}
+
+/// Go module analyzer
+pub struct GoModuleAnalyzer {
+    vuln_db: Option<Arc<dyn VulnerabilityDatabase>>,
+    pattern_matcher: PatternMatcher,
+}
+
+impl GoModuleAnalyzer {
+    /// Create a new Go module analyzer
+    ///
+    /// No Go advisories are bundled; vulnerabilities are only looked up once
+    /// a database is set with [`Self::with_vulnerability_db`].
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            vuln_db: None,
+            pattern_matcher: PatternMatcher::new()?,
+        })
+    }
+
+    /// Create analyzer sharing the compiled rules and database of `context`
+    pub fn from_context(context: &AnalyzerContext) -> Self {
+        Self {
+            vuln_db: context.vulnerability_db(Ecosystem::Go),
+            pattern_matcher: context.pattern_matcher().clone(),
+        }
+    }
+
+    /// Look up vulnerabilities in a database, such as a loaded snapshot
+    pub fn with_vulnerability_db(mut self, db: impl Into<Arc<dyn VulnerabilityDatabase>>) -> Self {
+        self.vuln_db = Some(db.into());
+        self
+    }
+
+    /// Vulnerability database for an analysis with `options`, if any
+    fn database(
+        &self,
+        options: &AnalysisOptions,
+    ) -> Result<Option<Arc<dyn VulnerabilityDatabase>>> {
+        Ok(vulnerability_db::database_override(options)?.or_else(|| self.vuln_db.clone()))
+    }
+
+    /// Whether `path` is a module directory or its `go.mod`
+    pub(crate) fn is_module(path: &Path) -> bool {
+        if path.is_dir() {
+            path.join(MOD_FILE).exists()
+        } else {
+            path.file_name().and_then(|n| n.to_str()) == Some(MOD_FILE)
+        }
+    }
+
+    /// Module root for a module directory or its `go.mod`
+    fn module_root(path: &Path) -> PathBuf {
+        if path.is_dir() {
+            path.to_path_buf()
+        } else {
+            path.parent().unwrap_or(Path::new(".")).to_path_buf()
+        }
+    }
+
+    /// Parse `go.mod`
+    ///
+    /// Handles single-line directives and parenthesized blocks; `retract`,
+    /// `godebug` and `tool` directives are skipped.
+    fn parse_mod_file(content: &str) -> Result<GoModule> {
+        let mut module_path = None;
+        let mut go_version = None;
+        let mut toolchain = None;
+        let mut requirements = Vec::new();
+        let mut replacements = Vec::new();
+        let mut exclusions = Vec::new();
+
+        let mut block: Option<String> = None;
+        for (number, raw) in content.lines().enumerate() {
+            let (code, comment) = match raw.split_once("//") {
+                Some((code, comment)) => (code.trim(), Some(comment.trim())),
+                None => (raw.trim(), None),
+            };
+            if code.is_empty() {
+                continue;
+            }
+            let (directive, args) = match &block {
+                Some(_) if code == ")" => {
+                    block = None;
+                    continue;
+                }
+                Some(directive) => (directive.clone(), code),
+                None => {
+                    let (directive, rest) =
+                        code.split_once(char::is_whitespace).unwrap_or((code, ""));
+                    let rest = rest.trim();
+                    if rest == "(" {
+                        block = Some(directive.to_string());
+                        continue;
+                    }
+                    (directive.to_string(), rest)
+                }
+            };
+            let tokens: Vec<String> = args
+                .split_whitespace()
+                .map(|t| t.trim_matches(|c| c == '"' || c == '`').to_string())
+                .collect();
+            let malformed =
+                || anyhow::anyhow!("Malformed {} directive on line {}", directive, number + 1);
+            match directive.as_str() {
+                "module" => module_path = Some(tokens.first().ok_or_else(malformed)?.clone()),
+                "go" => go_version = tokens.first().cloned(),
+                "toolchain" => toolchain = tokens.first().cloned(),
+                "require" => {
+                    let [path, version] = &tokens[..] else {
+                        return Err(malformed());
+                    };
+                    requirements.push(GoRequirement {
+                        path: path.clone(),
+                        version: version.clone(),
+                        indirect: comment
+                            .is_some_and(|c| c.split(';').any(|c| c.trim() == "indirect")),
+                    });
+                }
+                "replace" => {
+                    let arrow = tokens
+                        .iter()
+                        .position(|t| t == "=>")
+                        .ok_or_else(malformed)?;
+                    let (old, new) = (&tokens[..arrow], &tokens[arrow + 1..]);
+                    let (Some(path), Some(new_path)) = (old.first(), new.first()) else {
+                        return Err(malformed());
+                    };
+                    replacements.push(GoReplacement {
+                        path: path.clone(),
+                        version: old.get(1).cloned(),
+                        new_path: new_path.clone(),
+                        new_version: new.get(1).cloned(),
+                    });
+                }
+                "exclude" => {
+                    let [path, version] = &tokens[..] else {
+                        return Err(malformed());
+                    };
+                    exclusions.push(GoExclusion {
+                        path: path.clone(),
+                        version: version.clone(),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let name = module_path.ok_or_else(|| anyhow::anyhow!("go.mod has no module directive"))?;
+        let repository = REPOSITORY_HOSTS
+            .iter()
+            .find(|host| name.starts_with(&format!("{}/", host)))
+            .and_then(|_| {
+                let segments: Vec<&str> = name.splitn(4, '/').take(3).collect();
+                (segments.len() == 3).then(|| format!("https://{}", segments.join("/")))
+            });
+        Ok(GoModule {
+            metadata: PackageMetadata {
+                version: "unknown".to_string(),
+                description: None,
+                author: None,
+                license: None,
+                homepage: repository.clone(),
+                repository,
+                keywords: vec![],
+                publish_date: None,
+                name,
+            },
+            go_version,
+            toolchain,
+            requirements,
+            replacements,
+            exclusions,
+            has_sum_file: false,
+            sums: HashSet::new(),
+        })
+    }
+
+    /// Module versions with a hash in `go.sum`
+    ///
+    /// `/go.mod` lines only cover a module's `go.mod`; both kinds count as
+    /// pinning the version.
+    fn parse_sum_file(content: &str) -> HashSet<(String, String)> {
+        content
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let (path, version, hash) = (fields.next()?, fields.next()?, fields.next()?);
+                hash.starts_with("h1:").then(|| {
+                    let version = version.strip_suffix("/go.mod").unwrap_or(version);
+                    (path.to_string(), version.to_string())
+                })
+            })
+            .collect()
+    }
+
+    /// Whether the module root holds `_test.go` files, which Go keeps next
+    /// to the code they test
+    fn has_test_files(root: &Path) -> bool {
+        std::fs::read_dir(root).is_ok_and(|entries| {
+            entries
+                .flatten()
+                .any(|entry| entry.file_name().to_string_lossy().ends_with("_test.go"))
+        })
+    }
+
+    /// Replacement applying to `path` at `version`; one naming the version
+    /// takes precedence over one for every version
+    fn replacement<'a>(
+        module: &'a GoModule,
+        path: &str,
+        version: &str,
+    ) -> Option<&'a GoReplacement> {
+        let matching = module.replacements.iter().filter(|r| r.path == path);
+        matching
+            .clone()
+            .find(|r| r.version.as_deref() == Some(version))
+            .or_else(|| matching.into_iter().find(|r| r.version.is_none()))
+    }
+
+    /// Version Go selects when `version` is excluded: the lowest later
+    /// version recorded in `go.sum` that is not excluded too
+    fn next_allowed(module: &GoModule, path: &str, version: &str) -> Option<String> {
+        let scheme = VersionSchemeRegistry::global().get_or_default("go");
+        let excluded = |v: &str| {
+            module
+                .exclusions
+                .iter()
+                .any(|e| e.path == path && e.version == v)
+        };
+        module
+            .sums
+            .iter()
+            .filter(|(sum_path, _)| sum_path == path)
+            .map(|(_, v)| v.as_str())
+            .filter(|v| !excluded(v))
+            .filter(|v| scheme.compare(v, version).ok() == Some(Ordering::Greater))
+            .min_by(|a, b| scheme.compare(a, b).unwrap_or(Ordering::Equal))
+            .map(String::from)
+    }
+
+    /// Build dependency analysis from the requirements
+    ///
+    /// `replace` directives redirect a requirement to another module, whose
+    /// advisories are checked instead, or to a local directory, which has
+    /// none. A requirement on an excluded version resolves to the version
+    /// Go would select instead, as far as `go.sum` shows it.
+    async fn analyze_dependencies(
+        &self,
+        module: &GoModule,
+        options: &AnalysisOptions,
+    ) -> Result<DependencyAnalysis> {
+        let mut analysis = DependencyAnalysis::default();
+        let db = self.database(options)?;
+        for requirement in &module.requirements {
+            let excluded = module
+                .exclusions
+                .iter()
+                .any(|e| e.path == requirement.path && e.version == requirement.version);
+            let selected = if excluded {
+                Self::next_allowed(module, &requirement.path, &requirement.version)
+            } else {
+                Some(requirement.version.clone())
+            };
+            let replacement = selected
+                .as_deref()
+                .and_then(|version| Self::replacement(module, &requirement.path, version));
+
+            let (lookup, resolved_version) = match (replacement, selected) {
+                (Some(replacement), _) if replacement.is_local() => (None, None),
+                (Some(replacement), _) => (
+                    Some(replacement.new_path.as_str()).zip(replacement.new_version.clone()),
+                    replacement.new_version.clone(),
+                ),
+                (None, Some(version)) => (
+                    Some((requirement.path.as_str(), version.clone())),
+                    Some(version),
+                ),
+                (None, None) => (None, None),
+            };
+            let vulnerabilities = match (lookup, &db) {
+                (Some((name, version)), Some(db)) if options.check_vulnerabilities => {
+                    db.check_package_with_policy(
+                        name,
+                        &version,
+                        "go",
+                        options.prerelease_policy("go"),
+                    )
+                    .await?
+                }
+                _ => vec![],
+            };
+
+            progress::dependency_resolved(&requirement.path);
+            analysis.dependency_tree.push(Dependency {
+                name: requirement.path.clone(),
+                version_spec: requirement.version.clone(),
+                resolved_version,
+                dependency_type: DependencyType::Runtime,
+                is_direct: !requirement.indirect,
+                is_dev: false,
+                vulnerabilities,
+                license: None,
+                dependencies: vec![],
+                alias_target: replacement.map(|r| r.new_path.clone()),
+                range_vulnerabilities: vec![],
+                shadowed_declarations: vec![],
+                source: None,
+                platform: None,
+                hashes: vec![],
+                marker: None,
+            });
+        }
+        analysis.summarize();
+        Ok(analysis)
+    }
+
+    /// Requirements `go.sum` does not pin, or every requirement when it is missing
+    fn integrity_findings(module: &GoModule, analysis: &DependencyAnalysis) -> Vec<Finding> {
+        let remote: Vec<(&str, &str)> = analysis
+            .dependency_tree
+            .iter()
+            .filter_map(|dep| {
+                let version = dep.resolved_version.as_deref()?;
+                Some((dep.alias_target.as_deref().unwrap_or(&dep.name), version))
+            })
+            .collect();
+        if remote.is_empty() {
+            return vec![];
+        }
+
+        if !module.has_sum_file {
+            let mut finding = Finding::new(
+                FindingType::MissingIntegrity,
+                RiskLevel::Medium,
+                "No go.sum file",
+                "go.sum is missing, so the content of required modules is not pinned by hash",
+            )
+            .with_scan_reason(ScanReason::Integrity)
+            .with_rule_id(rules::GO_MISSING_SUM_FILE);
+            for (path, _) in remote {
+                finding = finding.with_affected_component(path.to_string());
+            }
+            return vec![finding];
+        }
+
+        remote
+            .into_iter()
+            .filter(|(path, version)| {
+                !module
+                    .sums
+                    .contains(&(path.to_string(), version.to_string()))
+            })
+            .map(|(path, version)| {
+                Finding::new(
+                    FindingType::MissingIntegrity,
+                    RiskLevel::Medium,
+                    format!("Module '{}' is not in go.sum", path),
+                    format!(
+                        "{}@{} is required but go.sum records no hash for it",
+                        path, version
+                    ),
+                )
+                .with_affected_component(path.to_string())
+                .with_scan_reason(ScanReason::Integrity)
+                .with_rule_id(rules::GO_MODULE_NOT_IN_SUM)
+            })
+            .collect()
+    }
+
+    /// Analysis of `path` without the deadline `options.timeout_seconds` sets
+    async fn run_analysis(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<GoAnalysisResult> {
+        let budget = MemoryBudget::new(options.max_memory_bytes);
+        let root = Self::module_root(path);
+        let content = tokio::fs::read_to_string(root.join(MOD_FILE))
+            .await
+            .with_context(|| format!("No go.mod found in {}", root.display()))?;
+        budget.charge(MOD_FILE, content.len())?;
+        let mut package = Self::parse_mod_file(&content)?;
+        // Only the first `max_dependencies` are analyzed
+        let dependency_limit = options
+            .limits
+            .truncate_dependencies(MOD_FILE, &mut package.requirements);
+        if let Ok(sums) = tokio::fs::read_to_string(root.join(SUM_FILE)).await {
+            budget.charge(SUM_FILE, sums.len())?;
+            package.has_sum_file = true;
+            package.sums = Self::parse_sum_file(&sums);
+        }
+        progress::manifest_parsed(package.metadata());
+        progress::vuln_lookup_started(options);
+        let mut dependency_analysis = self.analyze_dependencies(&package, options).await?;
+        deadline::dependencies_analyzed(&dependency_analysis);
+        budget.charge_for("dependencies", &dependency_analysis.dependency_tree)?;
+        dependency_analysis.license_findings =
+            options.license_policy.findings(&dependency_analysis);
+
+        let mut findings = Self::integrity_findings(&package, &dependency_analysis);
+        findings.extend(dependency_limit);
+        let mut malicious_patterns = if options.scan_malicious_patterns {
+            self.pattern_matcher.scan(&content, Some(MOD_FILE))
+        } else {
+            vec![]
+        };
+        malicious_patterns.extend(custom_rules::scan_package(&root, options)?);
+        progress::pattern_scan_complete(&malicious_patterns);
+        budget.charge_for("malicious patterns", &malicious_patterns)?;
+
+        let mut vulnerabilities = vec![];
+        for dep in &dependency_analysis.dependency_tree {
+            vulnerabilities.extend(dep.vulnerabilities.clone());
+        }
+
+        // Without go.sum nothing ties the modules fetched to the ones reviewed
+        let risk_calculator = RiskCalculator::from_options(options);
+        let supply_chain_score = if package.has_sum_file || package.requirements.is_empty() {
+            0.0
+        } else {
+            30.0
+        };
+
+        let mut risk_score = risk_calculator.calculate(
+            &vulnerabilities,
+            &malicious_patterns,
+            false,
+            supply_chain_score,
+            50.0, // Default maintenance score
+        );
+        risk_calculator.apply_findings(&mut risk_score, &findings);
+        risk_calculator.apply_pinning(
+            &mut risk_score,
+            &PinningReport::from_analysis(&dependency_analysis, "go"),
+        );
+        let scoped_risk = risk_calculator.scoped_risk(
+            &risk_score,
+            &dependency_analysis,
+            &findings,
+            &options.production_dependency_types,
+        );
+
+        let scan_phases = vec![
+            PhaseRecord::new(ScanReason::Integrity, true, findings.len()),
+            PhaseRecord::new(
+                ScanReason::VulnerabilityCheck,
+                options.check_vulnerabilities && self.database(options)?.is_some(),
+                vulnerabilities.len(),
+            ),
+            PhaseRecord::new(
+                ScanReason::MaliciousPatterns,
+                options.scan_malicious_patterns,
+                malicious_patterns.len(),
+            ),
+        ];
+
+        let risk_assessment = RiskAssessment {
+            risk_score: risk_score.clone(),
+            summary: format!(
+                "Go module '{}' has {} risk with {} requirements and {} findings; {}",
+                package.metadata.name,
+                risk_score.risk_level,
+                package.requirements.len(),
+                findings.len(),
+                scoped_risk
+            ),
+            detailed_findings: findings,
+            recommendations: dependency_analysis
+                .upgrade_recommendations_with("go", options.recommend_prerelease_fixes),
+            security_posture: crate::core::SecurityPosture {
+                vulnerabilities_present: !vulnerabilities.is_empty(),
+                malicious_code_detected: !malicious_patterns.is_empty(),
+                supply_chain_risks: supply_chain_score > 0.0
+                    || package.replacements.iter().any(GoReplacement::is_local),
+                actively_maintained: true,
+                trusted_publisher: false,
+                security_practices_score: if package.has_sum_file { 70.0 } else { 40.0 },
+            },
+            scan_phases,
+            scoped_risk: Some(scoped_risk),
+            scan_context: Some(ScanContext::current(
+                self.database(options)?.and_then(|db| db.last_updated()),
+            )),
+        };
+
+        let quality_metrics = QualityMetrics::assess(
+            &root,
+            package.metadata(),
+            Self::has_test_files(&root),
+            options.now(),
+        );
+        Ok(GoAnalysisResult {
+            package,
+            risk_assessment,
+            dependency_analysis,
+            vulnerabilities,
+            malicious_patterns,
+            quality_metrics,
+        })
+    }
+}
+
+#[async_trait]
+impl PackageAnalyzer for GoModuleAnalyzer {
+    type Package = GoModule;
+    type Analysis = GoAnalysisResult;
+
+    async fn analyze(&self, path: &Path) -> Result<Self::Analysis> {
+        self.analyze_with_options(path, &AnalysisOptions::default())
+            .await
+    }
+
+    async fn analyze_with_options(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Self::Analysis> {
+        deadline::enforce(path, options, self.run_analysis(path, options)).await
+    }
+
+    fn can_analyze(&self, path: &Path) -> bool {
+        Self::is_module(path)
+    }
+
+    fn name(&self) -> &str {
+        "Go Module Analyzer"
+    }
+
+    fn supported_extensions(&self) -> Vec<&str> {
+        vec!["mod", "sum"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mod_file() {
+        let go_mod = r#"
+module github.com/acme/billing/v2
+
+go 1.22
+toolchain go1.22.3
+
+require github.com/google/uuid v1.6.0
+
+require (
+	golang.org/x/net v0.23.0 // indirect
+	github.com/sirupsen/logrus v1.9.0
+)
+
+replace github.com/sirupsen/logrus => github.com/acme/logrus v1.9.3-acme
+replace golang.org/x/net v0.23.0 => ../net
+
+exclude github.com/google/uuid v1.5.0
+"#;
+        let module = GoModuleAnalyzer::parse_mod_file(go_mod).unwrap();
+        assert_eq!(module.metadata.name, "github.com/acme/billing/v2");
+        assert_eq!(
+            module.metadata.repository.as_deref(),
+            Some("https://github.com/acme/billing")
+        );
+        assert_eq!(module.go_version.as_deref(), Some("1.22"));
+        assert_eq!(module.requirements.len(), 3);
+        assert!(module.requirements[1].indirect);
+        assert!(!module.replacements[0].is_local());
+        assert!(module.replacements[1].is_local());
+        assert_eq!(module.exclusions[0].version, "v1.5.0");
+
+        assert!(GoModuleAnalyzer::parse_mod_file("require (\n)").is_err());
+    }
+}
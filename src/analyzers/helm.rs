@@ -0,0 +1,1112 @@
+//! Helm chart analyzer
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::context::AnalyzerContext;
+use crate::core::{
+    custom_rules, deadline, progress, rules, AnalysisOptions, AnalysisResult, Dependency,
+    DependencyAnalysis, DependencyType, Finding, FindingType, InsecureSource, InstallHook,
+    MaliciousPattern, MemoryBudget, PackageAnalyzer, PackageInfo, PackageMetadata, PatternMatcher,
+    PhaseRecord, PinningReport, QualityMetrics, RiskAssessment, RiskCalculator, RiskLevel,
+    ScanContext, ScanReason, Vulnerability,
+};
+
+const CHART_FILE: &str = "Chart.yaml";
+const VALUES_FILE: &str = "values.yaml";
+
+/// Dependency manifests and their lock files; `requirements.*` is the apiVersion v1 layout
+const DEPENDENCY_FILES: &[(&str, &str)] = &[
+    (CHART_FILE, "Chart.lock"),
+    ("requirements.yaml", "requirements.lock"),
+];
+
+/// Hook events that run while a release is installed or upgraded
+const INSTALL_HOOK_EVENTS: &[&str] =
+    &["pre-install", "post-install", "pre-upgrade", "post-upgrade"];
+
+/// `helm.sh/hook` annotation of a template resource
+static HOOK_ANNOTATION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?m)^\s*["']?helm\.sh/hook["']?\s*:\s*["']?([a-z\-, ]+)"#).unwrap());
+
+/// Top-level `kind` of a template resource
+static RESOURCE_KIND: Lazy<Regex> = Lazy::new(|| Regex::new(r#"(?m)^kind:\s*["']?(\w+)"#).unwrap());
+
+/// Container `command:` or `args:` key, with any inline value
+static COMMAND_KEY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\s*)(?:-\s+)?(?:command|args)\s*:\s*(.*)$").unwrap());
+
+/// Content downloaded and handed straight to a shell
+static REMOTE_EXEC: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\b(?:curl|wget)\b[^\n;&|]*\|\s*(?:sudo\s+)?(?:ba|z|da)?sh\b|\$\(\s*(?:curl|wget)\b|`\s*(?:curl|wget)\b|\b(?:curl|wget)\b[^\n]*(?:&&|;)\s*(?:ba|z|da)?sh\s",
+    )
+    .unwrap()
+});
+
+/// Helm chart information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HelmChart {
+    pub metadata: PackageMetadata,
+    pub api_version: Option<String>,
+    pub app_version: Option<String>,
+    /// `application` or `library`
+    pub chart_type: Option<String>,
+    pub dependencies: Vec<ChartDependency>,
+    /// Template resources that are hooks or run commands
+    pub templates: Vec<TemplateResource>,
+    /// Container images referenced in `values.yaml`
+    pub images: Vec<ContainerImage>,
+    pub has_lock_file: bool,
+}
+
+impl HelmChart {
+    /// Hook resources Helm runs while installing or upgrading a release, with
+    /// the commands their containers run, or their kind when they name none
+    pub fn install_hooks(&self) -> Vec<InstallHook> {
+        self.templates
+            .iter()
+            .filter(|template| template.is_install_hook())
+            .map(|template| {
+                let command = if template.commands.is_empty() {
+                    template.kind.clone().unwrap_or_default()
+                } else {
+                    template.commands.join("; ")
+                };
+                InstallHook::new(template.hook_events.join(","), command, &template.template)
+            })
+            .collect()
+    }
+}
+
+/// A chart listed under `dependencies`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartDependency {
+    pub name: String,
+    pub version: String,
+    pub repository: String,
+    pub repository_kind: ChartRepositoryKind,
+    /// Enabled by a `condition` or `tags`
+    pub optional: bool,
+    pub locked_version: Option<String>,
+}
+
+/// Where a chart dependency is fetched from
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ChartRepositoryKind {
+    /// Vendored under `charts/` or referenced with `file://`
+    Local,
+    Https,
+    Oci,
+    Http,
+    /// `@name` or `alias:name`, resolved through the local `helm repo` configuration
+    Alias,
+    Other,
+}
+
+impl ChartRepositoryKind {
+    /// Classify a dependency `repository` value
+    pub fn classify(repository: &str) -> Self {
+        let lower = repository.trim().to_ascii_lowercase();
+        if lower.is_empty() || lower.starts_with("file://") {
+            Self::Local
+        } else if lower.starts_with("https://") {
+            Self::Https
+        } else if lower.starts_with("oci://") {
+            Self::Oci
+        } else if lower.starts_with("http://") {
+            Self::Http
+        } else if lower.starts_with('@') || lower.starts_with("alias:") {
+            Self::Alias
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// A resource in `templates/` that is a hook or runs a command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateResource {
+    /// Path relative to the chart root
+    pub template: String,
+    pub kind: Option<String>,
+    /// Events from the `helm.sh/hook` annotation; empty for ordinary resources
+    pub hook_events: Vec<String>,
+    pub commands: Vec<String>,
+}
+
+impl TemplateResource {
+    /// Whether Helm runs this resource while installing or upgrading a release
+    pub fn is_install_hook(&self) -> bool {
+        self.hook_events
+            .iter()
+            .any(|event| INSTALL_HOOK_EVENTS.contains(&event.as_str()))
+    }
+}
+
+/// A container image referenced in `values.yaml`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContainerImage {
+    /// Dotted path of the value, such as `controller.image`
+    pub values_path: String,
+    pub reference: String,
+    pub tag: Option<String>,
+    pub digest: Option<String>,
+}
+
+impl ContainerImage {
+    /// Whether the reference can point at different content over time
+    ///
+    /// An empty tag is left alone: by convention charts then use `appVersion`.
+    pub fn is_mutable(&self) -> bool {
+        self.digest.is_none() && matches!(self.tag.as_deref(), None | Some("latest"))
+    }
+}
+
+impl PackageInfo for HelmChart {
+    fn metadata(&self) -> &PackageMetadata {
+        &self.metadata
+    }
+
+    fn package_type(&self) -> &str {
+        "helm"
+    }
+
+    fn custom_attributes(&self) -> HashMap<String, serde_json::Value> {
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "api_version".to_string(),
+            serde_json::json!(self.api_version),
+        );
+        attrs.insert(
+            "app_version".to_string(),
+            serde_json::json!(self.app_version),
+        );
+        attrs.insert(
+            "dependencies".to_string(),
+            serde_json::json!(self.dependencies),
+        );
+        attrs.insert(
+            "hooks".to_string(),
+            serde_json::json!(self
+                .templates
+                .iter()
+                .filter(|t| !t.hook_events.is_empty())
+                .collect::<Vec<_>>()),
+        );
+        attrs.insert("images".to_string(), serde_json::json!(self.images));
+        attrs.insert(
+            "has_lock_file".to_string(),
+            serde_json::json!(self.has_lock_file),
+        );
+        attrs
+    }
+}
+
+/// Helm analysis result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HelmAnalysisResult {
+    pub package: HelmChart,
+    pub risk_assessment: RiskAssessment,
+    pub dependency_analysis: DependencyAnalysis,
+    pub vulnerabilities: Vec<Vulnerability>,
+    pub malicious_patterns: Vec<MaliciousPattern>,
+    /// Signs the package is tested, documented and maintained
+    #[serde(default)]
+    pub quality_metrics: QualityMetrics,
+}
+
+impl AnalysisResult for HelmAnalysisResult {
+    fn package_info(&self) -> &dyn PackageInfo {
+        &self.package
+    }
+
+    fn risk_assessment(&self) -> &RiskAssessment {
+        &self.risk_assessment
+    }
+
+    fn risk_assessment_mut(&mut self) -> &mut RiskAssessment {
+        &mut self.risk_assessment
+    }
+
+    fn dependency_analysis(&self) -> &DependencyAnalysis {
+        &self.dependency_analysis
+    }
+
+    fn vulnerabilities(&self) -> &[Vulnerability] {
+        &self.vulnerabilities
+    }
+
+    fn malicious_patterns(&self) -> &[MaliciousPattern] {
+        &self.malicious_patterns
+    }
+
+    fn quality_metrics(&self) -> QualityMetrics {
+        self.quality_metrics.clone()
+    }
+
+    fn install_hooks(&self) -> Vec<InstallHook> {
+        self.package.install_hooks()
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+}
+
+/// Helm chart analyzer
+pub struct HelmAnalyzer {
+    pattern_matcher: PatternMatcher,
+}
+
+impl HelmAnalyzer {
+    /// Create a new Helm analyzer
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            pattern_matcher: PatternMatcher::new()?,
+        })
+    }
+
+    /// Create analyzer sharing the compiled rules of `context`
+    pub fn from_context(context: &AnalyzerContext) -> Self {
+        Self {
+            pattern_matcher: context.pattern_matcher().clone(),
+        }
+    }
+
+    /// Whether `path` is a chart directory or its `Chart.yaml`
+    pub(crate) fn is_chart(path: &Path) -> bool {
+        if path.is_dir() {
+            path.join(CHART_FILE).exists()
+        } else {
+            path.file_name().and_then(|n| n.to_str()) == Some(CHART_FILE)
+        }
+    }
+
+    /// Chart root for a chart directory or its `Chart.yaml`
+    fn chart_root(path: &Path) -> PathBuf {
+        if path.is_dir() {
+            path.to_path_buf()
+        } else {
+            path.parent().unwrap_or(Path::new(".")).to_path_buf()
+        }
+    }
+
+    /// Parse chart metadata, dependencies, templates and values
+    async fn parse_chart(
+        &self,
+        root: &Path,
+        content: &str,
+        options: &AnalysisOptions,
+    ) -> Result<HelmChart> {
+        let chart: Value = serde_yaml::from_str(content).context("Failed to parse Chart.yaml")?;
+        let text = |key: &str| chart.get(key).and_then(yaml_text);
+
+        let metadata = PackageMetadata {
+            name: text("name").unwrap_or_else(|| "unknown".to_string()),
+            version: text("version").unwrap_or_else(|| "unknown".to_string()),
+            description: text("description"),
+            author: chart
+                .get("maintainers")
+                .and_then(|m| m.get(0))
+                .and_then(|m| m.get("name"))
+                .and_then(yaml_text),
+            license: chart
+                .get("annotations")
+                .and_then(|a| a.get("licenses"))
+                .and_then(yaml_text),
+            homepage: text("home"),
+            repository: chart
+                .get("sources")
+                .and_then(|s| s.get(0))
+                .and_then(yaml_text),
+            keywords: chart
+                .get("keywords")
+                .and_then(|k| k.as_sequence())
+                .map(|items| items.iter().filter_map(yaml_text).collect())
+                .unwrap_or_default(),
+            publish_date: None,
+        };
+
+        let (dependencies, has_lock_file) = Self::read_dependencies(root, &chart, options).await?;
+
+        Ok(HelmChart {
+            metadata,
+            api_version: text("apiVersion"),
+            app_version: text("appVersion"),
+            chart_type: text("type"),
+            dependencies,
+            templates: Self::read_templates(root)?,
+            images: Self::read_images(root, options).await,
+            has_lock_file,
+        })
+    }
+
+    /// Read declared dependencies and their locked versions
+    async fn read_dependencies(
+        root: &Path,
+        chart: &Value,
+        options: &AnalysisOptions,
+    ) -> Result<(Vec<ChartDependency>, bool)> {
+        let mut declared = chart.get("dependencies").cloned();
+        let mut lock_file = DEPENDENCY_FILES[0].1;
+        if declared.is_none() {
+            let (manifest, lock) = DEPENDENCY_FILES[1];
+            if let Ok(content) = tokio::fs::read_to_string(root.join(manifest)).await {
+                options.limits.check_yaml(manifest, &content)?;
+                let requirements: Value = serde_yaml::from_str(&content)
+                    .with_context(|| format!("Failed to parse {}", manifest))?;
+                declared = requirements.get("dependencies").cloned();
+                lock_file = lock;
+            }
+        }
+
+        let mut dependencies = Vec::new();
+        for entry in declared
+            .as_ref()
+            .and_then(|d| d.as_sequence())
+            .into_iter()
+            .flatten()
+            .take(options.limits.max_dependencies)
+        {
+            let Some(name) = entry.get("name").and_then(yaml_text) else {
+                continue;
+            };
+            let repository = entry
+                .get("repository")
+                .and_then(yaml_text)
+                .unwrap_or_default();
+            dependencies.push(ChartDependency {
+                name,
+                version: entry
+                    .get("version")
+                    .and_then(yaml_text)
+                    .unwrap_or_else(|| "*".to_string()),
+                repository_kind: ChartRepositoryKind::classify(&repository),
+                repository,
+                optional: entry.get("condition").is_some() || entry.get("tags").is_some(),
+                locked_version: None,
+            });
+        }
+
+        // An oversized or unreadable lock file locks nothing
+        let lock = tokio::fs::read_to_string(root.join(lock_file))
+            .await
+            .ok()
+            .filter(|content| options.limits.check_yaml(lock_file, content).is_ok())
+            .and_then(|content| serde_yaml::from_str::<Value>(&content).ok());
+        let has_lock_file = lock.is_some();
+        let locked: Vec<&Value> = lock
+            .as_ref()
+            .and_then(|l| l.get("dependencies"))
+            .and_then(|d| d.as_sequence())
+            .map(|items| items.iter().collect())
+            .unwrap_or_default();
+        for dependency in &mut dependencies {
+            dependency.locked_version = locked
+                .iter()
+                .find(|entry| {
+                    entry.get("name").and_then(yaml_text).as_deref() == Some(&dependency.name)
+                })
+                .and_then(|entry| entry.get("version"))
+                .and_then(yaml_text);
+        }
+
+        Ok((dependencies, has_lock_file))
+    }
+
+    /// Find hooks and commands in `templates/`
+    ///
+    /// Templates are Go templates rather than YAML, so resources are split on
+    /// document markers and read line by line.
+    fn read_templates(root: &Path) -> Result<Vec<TemplateResource>> {
+        let dir = root.join("templates");
+        let mut files = Vec::new();
+        if dir.is_dir() {
+            collect_templates(&dir, &mut files)?;
+        }
+        files.sort();
+
+        let mut resources = Vec::new();
+        for file in files {
+            let Ok(content) = std::fs::read_to_string(&file) else {
+                continue;
+            };
+            let template = file
+                .strip_prefix(root)?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/");
+            for document in split_documents(&content) {
+                let hook_events: Vec<String> = HOOK_ANNOTATION
+                    .captures(&document)
+                    .map(|c| {
+                        c[1].split(',')
+                            .map(|event| event.trim().to_string())
+                            .filter(|event| !event.is_empty())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let commands = extract_commands(&document);
+                if hook_events.is_empty() && commands.is_empty() {
+                    continue;
+                }
+                resources.push(TemplateResource {
+                    template: template.clone(),
+                    kind: RESOURCE_KIND.captures(&document).map(|c| c[1].to_string()),
+                    hook_events,
+                    commands,
+                });
+            }
+        }
+        Ok(resources)
+    }
+
+    /// Map container images referenced in `values.yaml`
+    async fn read_images(root: &Path, options: &AnalysisOptions) -> Vec<ContainerImage> {
+        let Ok(content) = tokio::fs::read_to_string(root.join(VALUES_FILE)).await else {
+            return vec![];
+        };
+        if options.limits.check_yaml(VALUES_FILE, &content).is_err() {
+            return vec![];
+        }
+        let Ok(values) = serde_yaml::from_str::<Value>(&content) else {
+            return vec![];
+        };
+        let mut images = Vec::new();
+        collect_images(&values, "", &mut images);
+        images
+    }
+
+    /// Flag chart dependencies fetched over plain HTTP or through local repository aliases
+    fn check_repositories(chart: &HelmChart) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for dependency in &chart.dependencies {
+            match dependency.repository_kind {
+                ChartRepositoryKind::Http => {
+                    findings.extend(
+                        InsecureSource::check(&dependency.name, &dependency.repository, CHART_FILE)
+                            .map(|source| source.to_finding()),
+                    );
+                }
+                ChartRepositoryKind::Alias | ChartRepositoryKind::Other => {
+                    findings.push(
+                        Finding::new(
+                            FindingType::NonRegistrySource,
+                            RiskLevel::Medium,
+                            format!("Chart dependency '{}' uses an untrusted repository", dependency.name),
+                            format!(
+                                "'{}' is not an HTTPS or OCI repository URL, so where the chart comes from depends on the machine running helm",
+                                dependency.repository
+                            ),
+                        )
+                        .with_evidence(format!("repository: {}", dependency.repository))
+                        .with_affected_component(dependency.name.clone())
+                        .with_scan_reason(ScanReason::DependencyAnalysis)
+                        .with_rule_id(rules::HELM_UNTRUSTED_REPOSITORY),
+                    );
+                }
+                _ => {}
+            }
+        }
+        findings
+    }
+
+    /// Check remote dependencies are recorded in the lock file
+    fn check_lock(chart: &HelmChart) -> Vec<Finding> {
+        let remote: Vec<&ChartDependency> = chart
+            .dependencies
+            .iter()
+            .filter(|d| d.repository_kind != ChartRepositoryKind::Local)
+            .collect();
+        if remote.is_empty() {
+            return vec![];
+        }
+
+        if !chart.has_lock_file {
+            let mut finding = Finding::new(
+                FindingType::MissingIntegrity,
+                RiskLevel::Medium,
+                "No dependency lock file",
+                "Chart.lock is missing, so chart dependency versions and digests are not pinned",
+            )
+            .with_scan_reason(ScanReason::Integrity)
+            .with_rule_id(rules::HELM_MISSING_LOCK_FILE);
+            for dependency in remote {
+                finding = finding.with_affected_component(dependency.name.clone());
+            }
+            return vec![finding];
+        }
+
+        remote
+            .into_iter()
+            .filter(|d| d.locked_version.is_none())
+            .map(|dependency| {
+                Finding::new(
+                    FindingType::MissingIntegrity,
+                    RiskLevel::Medium,
+                    format!("Chart dependency '{}' is not locked", dependency.name),
+                    format!(
+                        "'{}' is declared but has no entry in the lock file",
+                        dependency.name
+                    ),
+                )
+                .with_affected_component(dependency.name.clone())
+                .with_scan_reason(ScanReason::Integrity)
+                .with_rule_id(rules::HELM_DEPENDENCY_NOT_LOCKED)
+            })
+            .collect()
+    }
+
+    /// Scan template commands, reporting hooks that download and run code
+    fn scan_templates(&self, chart: &HelmChart) -> (Vec<MaliciousPattern>, Vec<Finding>) {
+        let mut patterns: Vec<MaliciousPattern> = Vec::new();
+        let mut findings = Vec::new();
+
+        for resource in &chart.templates {
+            let commands = resource.commands.join("\n");
+            if commands.is_empty() {
+                continue;
+            }
+            let context = if resource.hook_events.is_empty() {
+                resource.template.clone()
+            } else {
+                format!(
+                    "{} hook ({})",
+                    resource.hook_events.join(","),
+                    resource.template
+                )
+            };
+
+            for pattern in self
+                .pattern_matcher
+                .scan(&commands, Some(&resource.template))
+            {
+                // Offsets are into the extracted commands, not the template
+                let mut pattern = pattern;
+                pattern.forget_positions();
+                let evidence = format!("In {}", context);
+                match patterns
+                    .iter_mut()
+                    .find(|existing| existing.pattern_id == pattern.pattern_id)
+                {
+                    Some(existing) => {
+                        if !existing.evidence.contains(&evidence) {
+                            existing.evidence.push(evidence);
+                        }
+                    }
+                    None => {
+                        pattern.evidence.insert(0, evidence);
+                        patterns.push(pattern);
+                    }
+                }
+            }
+
+            if resource.is_install_hook() {
+                if let Some(command) = resource.commands.iter().find(|c| REMOTE_EXEC.is_match(c)) {
+                    findings.push(
+                        Finding::new(
+                            FindingType::SuspiciousActivity,
+                            RiskLevel::High,
+                            format!("Install hook in {} runs downloaded code", resource.template),
+                            "A hook downloads a script and runs it in the cluster while the release is installed, so what runs is never part of the chart",
+                        )
+                        .with_evidence(format!("{}: {}", context, command))
+                        .with_affected_component(resource.template.clone())
+                        .with_scan_reason(ScanReason::MaliciousPatterns)
+                        .with_rule_id(rules::HELM_HOOK_REMOTE_EXEC),
+                    );
+                }
+            }
+        }
+
+        (patterns, findings)
+    }
+
+    /// Note images that can change without the chart changing
+    fn check_images(chart: &HelmChart) -> Vec<Finding> {
+        chart
+            .images
+            .iter()
+            .filter(|image| image.is_mutable())
+            .map(|image| {
+                Finding::new(
+                    FindingType::UnpinnedDependency,
+                    RiskLevel::Low,
+                    format!("Image '{}' uses a mutable tag", image.reference),
+                    "The image is neither pinned by digest nor to a release tag, so the deployed content can change without the chart changing",
+                )
+                .with_evidence(format!("{}: {}", image.values_path, image.reference))
+                .with_affected_component(image.reference.clone())
+                .with_scan_reason(ScanReason::Manifest)
+                .with_rule_id(rules::HELM_MUTABLE_IMAGE)
+            })
+            .collect()
+    }
+
+    /// Build dependency analysis from chart dependencies
+    fn analyze_dependencies(chart: &HelmChart) -> DependencyAnalysis {
+        let mut analysis = DependencyAnalysis::default();
+        for dependency in &chart.dependencies {
+            progress::dependency_resolved(&dependency.name);
+            analysis.dependency_tree.push(Dependency {
+                name: dependency.name.clone(),
+                version_spec: dependency.version.clone(),
+                resolved_version: dependency.locked_version.clone(),
+                dependency_type: if dependency.optional {
+                    DependencyType::Optional
+                } else {
+                    DependencyType::Runtime
+                },
+                is_direct: true,
+                is_dev: false,
+                vulnerabilities: vec![],
+                license: None,
+                dependencies: vec![],
+                alias_target: None,
+                range_vulnerabilities: vec![],
+                shadowed_declarations: vec![],
+                source: None,
+                platform: None,
+                hashes: vec![],
+                marker: None,
+            });
+        }
+        analysis.summarize();
+        analysis
+    }
+
+    /// Analysis of `path` without the deadline `options.timeout_seconds` sets
+    async fn run_analysis(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<HelmAnalysisResult> {
+        let budget = MemoryBudget::new(options.max_memory_bytes);
+        let root = Self::chart_root(path);
+        let content = tokio::fs::read_to_string(root.join(CHART_FILE))
+            .await
+            .with_context(|| format!("No {} found in {}", CHART_FILE, root.display()))?;
+        budget.charge(CHART_FILE, content.len())?;
+        options.limits.check_yaml(CHART_FILE, &content)?;
+
+        let package = self.parse_chart(&root, &content, options).await?;
+        progress::manifest_parsed(package.metadata());
+        let mut dependency_analysis = Self::analyze_dependencies(&package);
+        deadline::dependencies_analyzed(&dependency_analysis);
+        budget.charge_for("dependencies", &dependency_analysis.dependency_tree)?;
+        dependency_analysis.license_findings =
+            options.license_policy.findings(&dependency_analysis);
+
+        let mut findings = Vec::new();
+        if let Some(dependencies) = serde_yaml::from_str::<Value>(&content)
+            .ok()
+            .and_then(|chart| chart.get("dependencies")?.as_sequence().map(Vec::len))
+        {
+            // Only the first `max_dependencies` were analyzed
+            if let Err(exceeded) = options
+                .limits
+                .check_dependency_count(CHART_FILE, dependencies)
+            {
+                findings.push(exceeded.to_finding());
+            }
+        }
+        if options.flag_mutable_images {
+            findings.extend(Self::check_images(&package));
+        }
+        let manifest_findings = findings.len();
+
+        if options.analyze_dependencies {
+            findings.extend(Self::check_repositories(&package));
+            findings.extend(dependency_analysis.unpinned_findings());
+        }
+        let dependency_findings = findings.len() - manifest_findings;
+        findings.extend(Self::check_lock(&package));
+        let integrity_findings = findings.len() - manifest_findings - dependency_findings;
+
+        // Hooks run with the release's service account before anyone reviews the result
+        let (mut malicious_patterns, hook_findings) = if options.scan_malicious_patterns {
+            self.scan_templates(&package)
+        } else {
+            (vec![], vec![])
+        };
+        let hook_count = hook_findings.len();
+        findings.extend(hook_findings);
+        malicious_patterns.extend(custom_rules::scan_package(&root, options)?);
+        progress::pattern_scan_complete(&malicious_patterns);
+        budget.charge_for("malicious patterns", &malicious_patterns)?;
+
+        let vulnerabilities: Vec<Vulnerability> = vec![];
+        let has_hooks = package.templates.iter().any(|t| t.is_install_hook());
+        let risk_calculator = RiskCalculator::from_options(options);
+        let supply_chain_score = if has_hooks { 40.0 } else { 0.0 };
+
+        let mut risk_score = risk_calculator.calculate(
+            &vulnerabilities,
+            &malicious_patterns,
+            false,
+            supply_chain_score,
+            50.0, // Default maintenance score
+        );
+        risk_calculator.apply_findings(&mut risk_score, &findings);
+        risk_calculator.apply_pinning(
+            &mut risk_score,
+            &PinningReport::from_analysis(&dependency_analysis, "helm"),
+        );
+        risk_calculator.apply_install_hooks(&mut risk_score, &package.install_hooks());
+        let scoped_risk = risk_calculator.scoped_risk(
+            &risk_score,
+            &dependency_analysis,
+            &findings,
+            &options.production_dependency_types,
+        );
+
+        let scan_phases = vec![
+            PhaseRecord::new(ScanReason::Manifest, true, manifest_findings),
+            PhaseRecord::new(
+                ScanReason::DependencyAnalysis,
+                options.analyze_dependencies,
+                dependency_findings,
+            ),
+            PhaseRecord::new(ScanReason::Integrity, true, integrity_findings),
+            PhaseRecord::new(
+                ScanReason::MaliciousPatterns,
+                options.scan_malicious_patterns,
+                malicious_patterns.len() + hook_count,
+            ),
+        ];
+
+        let risk_assessment = RiskAssessment {
+            risk_score: risk_score.clone(),
+            summary: format!(
+                "Helm chart '{}' has {} risk with {} dependencies, {} images and {} findings; {}",
+                package.metadata.name,
+                risk_score.risk_level,
+                package.dependencies.len(),
+                package.images.len(),
+                findings.len(),
+                scoped_risk
+            ),
+            detailed_findings: findings,
+            recommendations: vec![],
+            security_posture: crate::core::SecurityPosture {
+                vulnerabilities_present: false,
+                malicious_code_detected: !malicious_patterns.is_empty(),
+                supply_chain_risks: has_hooks
+                    || package.dependencies.iter().any(|d| {
+                        matches!(
+                            d.repository_kind,
+                            ChartRepositoryKind::Http
+                                | ChartRepositoryKind::Alias
+                                | ChartRepositoryKind::Other
+                        )
+                    }),
+                actively_maintained: true,
+                trusted_publisher: false,
+                security_practices_score: if package.has_lock_file { 70.0 } else { 40.0 },
+            },
+            scan_phases,
+            scoped_risk: Some(scoped_risk),
+            scan_context: Some(ScanContext::current(None)),
+        };
+
+        let quality_metrics =
+            QualityMetrics::assess(&root, package.metadata(), false, options.now());
+        Ok(HelmAnalysisResult {
+            package,
+            risk_assessment,
+            dependency_analysis,
+            vulnerabilities,
+            malicious_patterns,
+            quality_metrics,
+        })
+    }
+}
+
+/// Render a scalar YAML value as text
+fn yaml_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Recursively collect template files under `dir`
+fn collect_templates(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_templates(&path, files)?;
+        } else if path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| matches!(ext, "yaml" | "yml" | "tpl"))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Split a template into its YAML documents
+fn split_documents(content: &str) -> Vec<String> {
+    let mut documents = vec![String::new()];
+    for line in content.lines() {
+        if line.trim_end() == "---" {
+            documents.push(String::new());
+        } else if let Some(current) = documents.last_mut() {
+            current.push_str(line);
+            current.push('\n');
+        }
+    }
+    documents.retain(|d| !d.trim().is_empty());
+    documents
+}
+
+/// Collect container `command` and `args` values, one command line per key
+fn extract_commands(document: &str) -> Vec<String> {
+    let lines: Vec<&str> = document.lines().collect();
+    let mut commands = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let Some(captures) = COMMAND_KEY.captures(lines[i]) else {
+            i += 1;
+            continue;
+        };
+        let indent = captures[1].len();
+        let mut parts = vec![captures[2].to_string()];
+        i += 1;
+
+        // A block list may sit at the key's own indentation
+        while i < lines.len() {
+            let line = lines[i];
+            let trimmed = line.trim_start();
+            let line_indent = line.len() - trimmed.len();
+            if !trimmed.is_empty()
+                && (line_indent < indent || (line_indent == indent && !trimmed.starts_with("- ")))
+            {
+                break;
+            }
+            parts.push(trimmed.to_string());
+            i += 1;
+        }
+
+        let command = parts
+            .iter()
+            .map(|part| {
+                part.trim_start_matches("- ")
+                    .trim_matches(|c| matches!(c, '[' | ']' | '|' | '>'))
+                    .split("\", \"")
+                    .map(|arg| arg.trim().trim_matches(['"', '\'', ',']))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .filter(|part| !part.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !command.is_empty() {
+            commands.push(command);
+        }
+    }
+    commands
+}
+
+/// Walk values for `image` entries, either a reference string or a
+/// `registry`/`repository`/`tag`/`digest` mapping
+fn collect_images(value: &Value, path: &str, images: &mut Vec<ContainerImage>) {
+    let Some(mapping) = value.as_mapping() else {
+        return;
+    };
+    for (key, child) in mapping {
+        let Some(key) = key.as_str() else {
+            continue;
+        };
+        let child_path = if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        };
+        let is_image_key = key == "image" || key.ends_with("Image");
+
+        match child {
+            Value::String(reference) if is_image_key && !reference.is_empty() => {
+                let (name, digest) = match reference.split_once('@') {
+                    Some((name, digest)) => (name, Some(digest.to_string())),
+                    None => (reference.as_str(), None),
+                };
+                // A colon after the last slash separates the tag, not a registry port
+                let tag = name
+                    .rsplit('/')
+                    .next()
+                    .and_then(|last| last.split_once(':'))
+                    .map(|(_, tag)| tag.to_string());
+                images.push(ContainerImage {
+                    values_path: child_path,
+                    reference: reference.clone(),
+                    tag,
+                    digest,
+                });
+            }
+            Value::Mapping(_) if is_image_key && child.get("repository").is_some() => {
+                let field = |name: &str| {
+                    child
+                        .get(name)
+                        .and_then(yaml_text)
+                        .filter(|s| !s.is_empty())
+                };
+                let repository = field("repository").unwrap_or_default();
+                let tag = child.get("tag").and_then(yaml_text);
+                let digest = field("digest");
+                let mut reference = match field("registry") {
+                    Some(registry) => format!("{}/{}", registry, repository),
+                    None => repository,
+                };
+                if let Some(digest) = &digest {
+                    reference = format!("{}@{}", reference, digest);
+                } else if let Some(tag) = tag.as_deref().filter(|t| !t.is_empty()) {
+                    reference = format!("{}:{}", reference, tag);
+                }
+                images.push(ContainerImage {
+                    values_path: child_path,
+                    reference,
+                    tag,
+                    digest,
+                });
+            }
+            _ => collect_images(child, &child_path, images),
+        }
+    }
+}
+
+#[async_trait]
+impl PackageAnalyzer for HelmAnalyzer {
+    type Package = HelmChart;
+    type Analysis = HelmAnalysisResult;
+
+    async fn analyze(&self, path: &Path) -> Result<Self::Analysis> {
+        self.analyze_with_options(path, &AnalysisOptions::default())
+            .await
+    }
+
+    async fn analyze_with_options(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Self::Analysis> {
+        deadline::enforce(path, options, self.run_analysis(path, options)).await
+    }
+
+    fn can_analyze(&self, path: &Path) -> bool {
+        Self::is_chart(path)
+    }
+
+    fn name(&self) -> &str {
+        "Helm Chart Analyzer"
+    }
+
+    fn supported_extensions(&self) -> Vec<&str> {
+        vec!["yaml"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repository_kinds() {
+        for (repository, kind) in [
+            ("", ChartRepositoryKind::Local),
+            ("file://../common", ChartRepositoryKind::Local),
+            (
+                "https://charts.bitnami.com/bitnami",
+                ChartRepositoryKind::Https,
+            ),
+            (
+                "oci://registry-1.docker.io/bitnamicharts",
+                ChartRepositoryKind::Oci,
+            ),
+            ("http://charts.example.com", ChartRepositoryKind::Http),
+            ("@stable", ChartRepositoryKind::Alias),
+            ("alias:stable", ChartRepositoryKind::Alias),
+        ] {
+            assert_eq!(
+                ChartRepositoryKind::classify(repository),
+                kind,
+                "{}",
+                repository
+            );
+        }
+    }
+
+    #[test]
+    fn test_extract_commands() {
+        let document = r#"
+spec:
+  containers:
+    - name: setup
+      command: ["/bin/sh", "-c"]
+      args:
+        - |
+          curl -sSL https://get.example.com/install.sh | sh
+    - name: other
+      command:
+      - echo
+      - "{{ .Values.message }}"
+"#;
+        assert_eq!(
+            extract_commands(document),
+            [
+                "/bin/sh -c",
+                "curl -sSL https://get.example.com/install.sh | sh",
+                "echo {{ .Values.message }}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_images() {
+        let values: Value = serde_yaml::from_str(
+            r#"
+image:
+  repository: nginx
+  tag: ""
+sidecar:
+  image: busybox
+initImage: registry.example.com:5000/tools/init:latest
+exporter:
+  image:
+    registry: ghcr.io
+    repository: example/exporter
+    digest: sha256:abc
+"#,
+        )
+        .unwrap();
+        let mut images = Vec::new();
+        collect_images(&values, "", &mut images);
+
+        let references: Vec<(&str, bool)> = images
+            .iter()
+            .map(|i| (i.reference.as_str(), i.is_mutable()))
+            .collect();
+        assert_eq!(
+            references,
+            [
+                ("nginx", false),
+                ("busybox", true),
+                ("registry.example.com:5000/tools/init:latest", true),
+                ("ghcr.io/example/exporter@sha256:abc", false),
+            ]
+        );
+        assert_eq!(images[3].values_path, "exporter.image");
+    }
+}
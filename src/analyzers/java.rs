@@ -5,14 +5,19 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 use zip::ZipArchive;
 
+use crate::context::AnalyzerContext;
 use crate::core::{
-    AnalysisResult, DependencyAnalysis, MaliciousPattern, PackageAnalyzer, PackageInfo,
-    PackageMetadata, PatternMatcher, RiskAssessment, RiskCalculator, Vulnerability,
+    archive, custom_rules, deadline, progress, AnalysisOptions, AnalysisResult, DecompressionGuard,
+    DependencyAnalysis, Finding, MaliciousPattern, MemoryBudget, PackageAnalyzer, PackageInfo,
+    PackageMetadata, PatternMatcher, PhaseRecord, QualityMetrics, RiskAssessment, RiskCalculator,
+    ScanContext, ScanReason, Vulnerability,
 };
+use crate::ecosystem::Ecosystem;
 use crate::utils::typosquatting::TyposquattingDetector;
-use crate::vulnerability_db::VulnerabilityDatabase;
+use crate::vulnerability_db::{self, VulnerabilityDatabase};
 
 /// Java package information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -76,6 +81,9 @@ pub struct JavaAnalysisResult {
     pub vulnerabilities: Vec<Vulnerability>,
     pub malicious_patterns: Vec<MaliciousPattern>,
     pub security_analysis: JavaSecurityAnalysis,
+    /// Signs the package is tested, documented and maintained
+    #[serde(default)]
+    pub quality_metrics: QualityMetrics,
 }
 
 impl AnalysisResult for JavaAnalysisResult {
@@ -87,6 +95,10 @@ impl AnalysisResult for JavaAnalysisResult {
         &self.risk_assessment
     }
 
+    fn risk_assessment_mut(&mut self) -> &mut RiskAssessment {
+        &mut self.risk_assessment
+    }
+
     fn dependency_analysis(&self) -> &DependencyAnalysis {
         &self.dependency_analysis
     }
@@ -99,6 +111,10 @@ impl AnalysisResult for JavaAnalysisResult {
         &self.malicious_patterns
     }
 
+    fn quality_metrics(&self) -> QualityMetrics {
+        self.quality_metrics.clone()
+    }
+
     fn to_json(&self) -> Result<serde_json::Value> {
         Ok(serde_json::to_value(self)?)
     }
@@ -113,12 +129,28 @@ pub struct JavaSecurityAnalysis {
     pub dangerous_permissions: Vec<String>,
     pub suspicious_apis: Vec<String>,
     pub certificate_issues: Vec<String>,
+    /// Entries that are encrypted or password-protected and could not be read
+    #[serde(default)]
+    pub encrypted_entries: Vec<String>,
+    /// Total number of entries in the archive
+    #[serde(default)]
+    pub total_entries: usize,
+}
+
+impl JavaSecurityAnalysis {
+    /// Report encrypted entries, which a public package has no reason to ship
+    ///
+    /// An archive whose entries are all encrypted cannot be inspected at all and
+    /// is critical; otherwise the unreadable entries are high.
+    pub fn encrypted_archive_finding(&self) -> Option<Finding> {
+        archive::encrypted_entries_finding(&self.encrypted_entries, self.total_entries)
+    }
 }
 
 /// Java package analyzer
 pub struct JavaAnalyzer {
     #[allow(dead_code)]
-    vuln_db: Box<dyn VulnerabilityDatabase>,
+    vuln_db: Arc<dyn VulnerabilityDatabase>,
     pattern_matcher: PatternMatcher,
     #[allow(dead_code)]
     typo_detector: TyposquattingDetector,
@@ -128,21 +160,43 @@ impl JavaAnalyzer {
     /// Create a new Java analyzer
     pub fn new() -> Result<Self> {
         Ok(Self {
-            vuln_db: crate::vulnerability_db::create_java_database()?,
+            vuln_db: crate::vulnerability_db::create_java_database()?.into(),
             pattern_matcher: PatternMatcher::new()?,
-            typo_detector: TyposquattingDetector::new(),
+            typo_detector: TyposquattingDetector::for_ecosystem("java"),
         })
     }
 
     /// Create analyzer with custom database path
     pub fn with_db_path(db_path: &Path) -> Result<Self> {
         Ok(Self {
-            vuln_db: crate::vulnerability_db::create_java_database_with_path(db_path)?,
+            vuln_db: crate::vulnerability_db::create_java_database_with_path(db_path)?.into(),
             pattern_matcher: PatternMatcher::new()?,
-            typo_detector: TyposquattingDetector::new(),
+            typo_detector: TyposquattingDetector::for_ecosystem("java"),
         })
     }
 
+    /// Create analyzer sharing the compiled rules and database of `context`
+    pub fn from_context(context: &AnalyzerContext) -> Self {
+        Self {
+            vuln_db: context
+                .vulnerability_db(Ecosystem::Java)
+                .expect("Java has a vulnerability database"),
+            pattern_matcher: context.pattern_matcher().clone(),
+            typo_detector: TyposquattingDetector::for_ecosystem("java"),
+        }
+    }
+
+    /// Look up vulnerabilities in another database, such as a loaded snapshot
+    pub fn with_vulnerability_db(mut self, db: impl Into<Arc<dyn VulnerabilityDatabase>>) -> Self {
+        self.vuln_db = db.into();
+        self
+    }
+
+    /// Vulnerability database for an analysis with `options`
+    fn database(&self, options: &AnalysisOptions) -> Result<Arc<dyn VulnerabilityDatabase>> {
+        Ok(vulnerability_db::database_override(options)?.unwrap_or_else(|| self.vuln_db.clone()))
+    }
+
     /// Detect archive type from file extension
     fn detect_archive_type(&self, path: &Path) -> JavaArchiveType {
         match path.extension().and_then(|e| e.to_str()) {
@@ -156,7 +210,7 @@ impl JavaAnalyzer {
     }
 
     /// Parse Java archive
-    async fn parse_archive(&self, path: &Path) -> Result<JavaPackage> {
+    async fn parse_archive(&self, path: &Path, options: &AnalysisOptions) -> Result<JavaPackage> {
         let file = tokio::fs::File::open(path).await?;
         let file = file.into_std().await;
         let mut archive = ZipArchive::new(file)?;
@@ -167,10 +221,16 @@ impl JavaAnalyzer {
         let mut is_signed = false;
         let android_info = None;
 
-        // Read manifest
+        // Read manifest; an encrypted manifest is reported by the security analysis
         if let Ok(mut manifest_file) = archive.by_name("META-INF/MANIFEST.MF") {
+            let guard = DecompressionGuard::new(path.display().to_string(), options);
+            guard.add_compressed(manifest_file.compressed_size());
             let mut content = String::new();
-            std::io::Read::read_to_string(&mut manifest_file, &mut content)?;
+            std::io::Read::read_to_string(
+                &mut guard.decompressed(&mut manifest_file),
+                &mut content,
+            )
+            .map_err(|e| guard.explain(e))?;
 
             for line in content.lines() {
                 if let Some((key, value)) = line.split_once(':') {
@@ -185,9 +245,9 @@ impl JavaAnalyzer {
             }
         }
 
-        // Check if signed
+        // Check if signed, without decrypting entries
         for i in 0..archive.len() {
-            let file = archive.by_index(i)?;
+            let file = archive.by_index_raw(i)?;
             let name = file.name();
             if name.starts_with("META-INF/")
                 && (name.ends_with(".RSA") || name.ends_with(".DSA") || name.ends_with(".EC"))
@@ -257,13 +317,19 @@ impl JavaAnalyzer {
             dangerous_permissions: vec![],
             suspicious_apis: vec![],
             certificate_issues: vec![],
+            encrypted_entries: vec![],
+            total_entries: archive.len(),
         };
 
-        // Check for native libraries
+        // Check for native libraries; raw access lists entries without decrypting them
         for i in 0..archive.len() {
-            let file = archive.by_index(i)?;
+            let file = archive.by_index_raw(i)?;
             let name = file.name();
 
+            if file.encrypted() {
+                analysis.encrypted_entries.push(name.to_string());
+            }
+
             if name.ends_with(".so") || name.ends_with(".dll") || name.ends_with(".dylib") {
                 analysis.has_native_libraries = true;
             }
@@ -273,16 +339,22 @@ impl JavaAnalyzer {
 
         Ok(analysis)
     }
-}
 
-#[async_trait]
-impl PackageAnalyzer for JavaAnalyzer {
-    type Package = JavaPackage;
-    type Analysis = JavaAnalysisResult;
-
-    async fn analyze(&self, path: &Path) -> Result<Self::Analysis> {
-        let package = self.parse_archive(path).await?;
-        let dependency_analysis = self.analyze_dependencies(path).await?;
+    /// Analysis of `path` without the deadline `options.timeout_seconds` sets
+    async fn run_analysis(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<JavaAnalysisResult> {
+        let budget = MemoryBudget::new(options.max_memory_bytes);
+        let package = self.parse_archive(path, options).await?;
+        progress::manifest_parsed(package.metadata());
+        progress::vuln_lookup_started(options);
+        let mut dependency_analysis = self.analyze_dependencies(path).await?;
+        deadline::dependencies_analyzed(&dependency_analysis);
+        budget.charge_for("dependencies", &dependency_analysis.dependency_tree)?;
+        dependency_analysis.license_findings =
+            options.license_policy.findings(&dependency_analysis);
 
         // Open archive for security analysis
         let file = std::fs::File::open(path)?;
@@ -291,9 +363,24 @@ impl PackageAnalyzer for JavaAnalyzer {
 
         // Check for malicious patterns in manifest
         let manifest_content = serde_json::to_string(&package.manifest_attributes)?;
-        let malicious_patterns = self
-            .pattern_matcher
-            .scan(&manifest_content, Some("MANIFEST.MF"));
+        let mut malicious_patterns = if options.scan_malicious_patterns {
+            // The attributes are scanned re-serialized, so offsets would not
+            // match the manifest
+            let mut patterns = self
+                .pattern_matcher
+                .scan(&manifest_content, Some("MANIFEST.MF"));
+            for pattern in &mut patterns {
+                pattern.forget_positions();
+            }
+            patterns
+        } else {
+            vec![]
+        };
+        // A jar has no directory of sources for custom rules to scan, but an
+        // invalid ruleset still fails the analysis
+        malicious_patterns.extend(custom_rules::scan_package(path, options)?);
+        progress::pattern_scan_complete(&malicious_patterns);
+        budget.charge_for("malicious patterns", &malicious_patterns)?;
 
         // Collect all vulnerabilities
         let mut vulnerabilities = vec![];
@@ -302,14 +389,14 @@ impl PackageAnalyzer for JavaAnalyzer {
         }
 
         // Calculate risk assessment
-        let risk_calculator = RiskCalculator::new();
+        let risk_calculator = RiskCalculator::from_options(options);
         let supply_chain_score = if security_analysis.has_native_libraries {
             30.0
         } else {
             0.0
         };
 
-        let risk_score = risk_calculator.calculate(
+        let mut risk_score = risk_calculator.calculate(
             &vulnerabilities,
             &malicious_patterns,
             false, // TODO: Check typosquatting for Java packages
@@ -317,33 +404,79 @@ impl PackageAnalyzer for JavaAnalyzer {
             50.0, // Default maintenance score
         );
 
+        let detailed_findings: Vec<Finding> = security_analysis
+            .encrypted_archive_finding()
+            .into_iter()
+            .collect();
+        risk_calculator.apply_findings(&mut risk_score, &detailed_findings);
+        let scoped_risk = risk_calculator.scoped_risk(
+            &risk_score,
+            &dependency_analysis,
+            &detailed_findings,
+            &options.production_dependency_types,
+        );
+
         let risk_assessment = RiskAssessment {
             risk_score: risk_score.clone(),
             summary: format!(
-                "Java archive '{}' has {} risk",
-                package.metadata.name, risk_score.risk_level
+                "Java archive '{}' has {} risk; {}",
+                package.metadata.name, risk_score.risk_level, scoped_risk
             ),
-            detailed_findings: vec![],
-            recommendations: vec![],
+            recommendations: dependency_analysis
+                .upgrade_recommendations_with("java", options.recommend_prerelease_fixes),
             security_posture: crate::core::SecurityPosture {
                 vulnerabilities_present: !vulnerabilities.is_empty(),
                 malicious_code_detected: !malicious_patterns.is_empty(),
-                supply_chain_risks: security_analysis.has_native_libraries,
+                supply_chain_risks: security_analysis.has_native_libraries
+                    || !security_analysis.encrypted_entries.is_empty(),
                 actively_maintained: true,
                 trusted_publisher: package.is_signed,
                 security_practices_score: if package.is_signed { 70.0 } else { 30.0 },
             },
+            scan_phases: vec![
+                PhaseRecord::new(ScanReason::Integrity, true, detailed_findings.len()),
+                PhaseRecord::new(
+                    ScanReason::MaliciousPatterns,
+                    options.scan_malicious_patterns,
+                    malicious_patterns.len(),
+                ),
+            ],
+            scoped_risk: Some(scoped_risk),
+            scan_context: Some(ScanContext::current(self.database(options)?.last_updated())),
+            detailed_findings,
         };
 
+        let quality_metrics =
+            QualityMetrics::assess(path, package.metadata(), false, options.now());
         Ok(JavaAnalysisResult {
             package,
             risk_assessment,
             dependency_analysis,
             vulnerabilities,
             malicious_patterns,
+            quality_metrics,
             security_analysis,
         })
     }
+}
+
+#[async_trait]
+impl PackageAnalyzer for JavaAnalyzer {
+    type Package = JavaPackage;
+    type Analysis = JavaAnalysisResult;
+
+    async fn analyze(&self, path: &Path) -> Result<Self::Analysis> {
+        self.analyze_with_options(path, &AnalysisOptions::default())
+            .await
+    }
+
+    async fn analyze_with_options(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Self::Analysis> {
+        deadline::enforce(path, options, self.run_analysis(path, options)).await
+    }
 
     fn can_analyze(&self, path: &Path) -> bool {
         path.extension()
@@ -0,0 +1,1284 @@
+//! Maven and Gradle project analyzer
+//!
+//! Declared dependencies come from `pom.xml`, with `<properties>` substituted
+//! and versions missing from a dependency taken from
+//! `<dependencyManagement>`, and from `build.gradle` or `build.gradle.kts`.
+//! A `gradle.lockfile` supplies resolved versions and the transitive
+//! dependencies; without one, only what is declared is checked, as resolving
+//! parents, BOMs and transitive POMs needs a Maven repository.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::context::AnalyzerContext;
+use crate::core::{
+    custom_rules, deadline, progress, transport, AnalysisOptions, AnalysisResult, Dependency,
+    DependencyAnalysis, DependencyType, Finding, MaliciousPattern, ManifestLimits, MemoryBudget,
+    PackageAnalyzer, PackageInfo, PackageMetadata, PartialParse, PatternMatcher, PhaseRecord,
+    PinningReport, QualityMetrics, RiskAssessment, RiskCalculator, ScanContext, ScanReason,
+    Vulnerability,
+};
+use crate::ecosystem::Ecosystem;
+use crate::vulnerability_db::{self, VulnerabilityDatabase};
+
+const POM: &str = "pom.xml";
+const GRADLE_BUILD_FILES: &[&str] = &["build.gradle", "build.gradle.kts"];
+const GRADLE_SETTINGS_FILES: &[&str] = &["settings.gradle", "settings.gradle.kts"];
+const GRADLE_PROPERTIES: &str = "gradle.properties";
+const GRADLE_LOCK_FILE: &str = "gradle.lockfile";
+
+/// Maximum rounds of `${…}` substitution, for properties defined by others
+const MAX_INTERPOLATION_ROUNDS: usize = 8;
+
+/// `${name}` reference in a POM
+static POM_PROPERTY: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$\{([\w.-]+)\}").unwrap());
+
+/// `$name` or `${name}` reference in a Gradle string
+static GRADLE_PROPERTY: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$\{?(\w+)\}?").unwrap());
+
+/// `configuration "group:artifact:version"` in a Gradle build script
+static GRADLE_DEPENDENCY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?m)^\s*(\w+)\s*\(?\s*["']([^"':\s]+):([^"':\s]+)(?::([^"':@\s]+))?[^"']*["']"#)
+        .unwrap()
+});
+
+/// `configuration group: "…", name: "…", version: "…"` in a Gradle build script
+static GRADLE_MAP_DEPENDENCY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?m)^\s*(\w+)\s*\(?\s*group\s*[:=]\s*["']([^"']+)["']\s*,\s*name\s*[:=]\s*["']([^"']+)["'](?:\s*,\s*version\s*[:=]\s*["']([^"']+)["'])?"#,
+    )
+    .unwrap()
+});
+
+/// `name = "value"`, optionally as `ext.name`, `def name` or `val name`
+static GRADLE_ASSIGNMENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?m)^\s*(?:ext\.|def\s+|val\s+)?([\w.]+)\s*=\s*["']([^"'$]*)["']\s*$"#).unwrap()
+});
+
+/// URL of a `maven { … }` repository in a Gradle build script
+static GRADLE_REPOSITORY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"maven\s*\{[^}]*?\burl\s*(?:=\s*)?(?:uri\s*\(\s*)?["']([^"']+)["']"#).unwrap()
+});
+
+/// Build tool a project is declared for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuildTool {
+    Maven,
+    Gradle,
+}
+
+/// Maven or Gradle project information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MavenPackage {
+    /// Named by its `groupId:artifactId` coordinate
+    pub metadata: PackageMetadata,
+    pub group_id: String,
+    pub artifact_id: String,
+    /// `jar` unless the POM says otherwise
+    pub packaging: String,
+    /// `groupId:artifactId:version` of the parent POM, which is not resolved
+    pub parent: Option<String>,
+    pub build_tool: BuildTool,
+    /// Dependencies the POM or build script declares, versions substituted
+    pub declared_dependencies: Vec<MavenDependency>,
+    /// Versions `<dependencyManagement>` pins
+    pub managed_dependencies: Vec<MavenDependency>,
+    pub repositories: Vec<MavenRepository>,
+    pub has_lock_file: bool,
+    /// Lockfile left out because it could not be parsed
+    #[serde(default)]
+    pub skipped_files: Vec<PartialParse>,
+}
+
+/// A dependency declared in a POM or Gradle build script
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MavenDependency {
+    pub group_id: String,
+    pub artifact_id: String,
+    /// Version or range as declared after substitution; `None` when neither
+    /// the dependency nor `<dependencyManagement>` gives one
+    pub version: Option<String>,
+    /// Maven scope, or the Gradle configuration
+    pub scope: String,
+    pub optional: bool,
+    /// File declaring it
+    pub declared_in: String,
+}
+
+impl MavenDependency {
+    /// `groupId:artifactId`, the name vulnerabilities are looked up by
+    pub fn coordinate(&self) -> String {
+        format!("{}:{}", self.group_id, self.artifact_id)
+    }
+
+    /// Dependency type for the scope or configuration
+    pub fn dependency_type(&self) -> DependencyType {
+        if self.optional {
+            return DependencyType::Optional;
+        }
+        let scope = self.scope.to_ascii_lowercase();
+        if scope.starts_with("test") {
+            DependencyType::Test
+        } else if matches!(
+            scope.as_str(),
+            "provided" | "system" | "compileonly" | "annotationprocessor" | "kapt" | "classpath"
+        ) {
+            DependencyType::Build
+        } else {
+            DependencyType::Runtime
+        }
+    }
+}
+
+/// A repository dependencies are downloaded from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MavenRepository {
+    /// `<id>` from the POM, or the URL for Gradle repositories
+    pub id: String,
+    pub url: String,
+    pub declared_in: String,
+}
+
+/// A dependency pinned in `gradle.lockfile`
+#[derive(Debug, Clone, PartialEq)]
+struct LockedDependency {
+    group_id: String,
+    artifact_id: String,
+    version: String,
+    /// Configurations resolving it, such as `runtimeClasspath`
+    configurations: Vec<String>,
+}
+
+impl LockedDependency {
+    fn coordinate(&self) -> String {
+        format!("{}:{}", self.group_id, self.artifact_id)
+    }
+
+    /// Dependency type from the configurations resolving it
+    fn dependency_type(&self) -> DependencyType {
+        let configurations = || self.configurations.iter().map(|c| c.to_ascii_lowercase());
+        if configurations().all(|c| c.starts_with("test")) {
+            DependencyType::Test
+        } else if configurations().any(|c| c.contains("runtime")) {
+            DependencyType::Runtime
+        } else {
+            DependencyType::Build
+        }
+    }
+}
+
+impl PackageInfo for MavenPackage {
+    fn metadata(&self) -> &PackageMetadata {
+        &self.metadata
+    }
+
+    fn package_type(&self) -> &str {
+        "maven"
+    }
+
+    fn custom_attributes(&self) -> HashMap<String, serde_json::Value> {
+        let mut attrs = HashMap::new();
+        attrs.insert("packaging".to_string(), serde_json::json!(self.packaging));
+        attrs.insert("parent".to_string(), serde_json::json!(self.parent));
+        attrs.insert("build_tool".to_string(), serde_json::json!(self.build_tool));
+        attrs.insert(
+            "repositories".to_string(),
+            serde_json::json!(self
+                .repositories
+                .iter()
+                .map(|r| r.url.as_str())
+                .collect::<Vec<_>>()),
+        );
+        attrs.insert(
+            "has_lock_file".to_string(),
+            serde_json::json!(self.has_lock_file),
+        );
+        attrs
+    }
+}
+
+/// Maven analysis result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MavenAnalysisResult {
+    pub package: MavenPackage,
+    pub risk_assessment: RiskAssessment,
+    pub dependency_analysis: DependencyAnalysis,
+    pub vulnerabilities: Vec<Vulnerability>,
+    pub malicious_patterns: Vec<MaliciousPattern>,
+    /// Signs the package is tested, documented and maintained
+    #[serde(default)]
+    pub quality_metrics: QualityMetrics,
+}
+
+impl AnalysisResult for MavenAnalysisResult {
+    fn package_info(&self) -> &dyn PackageInfo {
+        &self.package
+    }
+
+    fn risk_assessment(&self) -> &RiskAssessment {
+        &self.risk_assessment
+    }
+
+    fn risk_assessment_mut(&mut self) -> &mut RiskAssessment {
+        &mut self.risk_assessment
+    }
+
+    fn dependency_analysis(&self) -> &DependencyAnalysis {
+        &self.dependency_analysis
+    }
+
+    fn vulnerabilities(&self) -> &[Vulnerability] {
+        &self.vulnerabilities
+    }
+
+    fn malicious_patterns(&self) -> &[MaliciousPattern] {
+        &self.malicious_patterns
+    }
+
+    fn quality_metrics(&self) -> QualityMetrics {
+        self.quality_metrics.clone()
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+}
+
+/// Element of a parsed XML document; attributes are not kept
+#[derive(Debug, Default)]
+struct Element {
+    name: String,
+    text: String,
+    children: Vec<Element>,
+}
+
+impl Element {
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// Element reached through `path` of child names
+    fn descendant(&self, path: &[&str]) -> Option<&Element> {
+        path.iter()
+            .try_fold(self, |element, name| element.child(name))
+    }
+
+    /// Trimmed text of a child, `None` when missing or empty
+    fn text_of(&self, name: &str) -> Option<String> {
+        let text = self.child(name)?.text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+}
+
+/// Parse an XML document into its root element
+///
+/// Enough of XML for build files: comments, CDATA, processing instructions
+/// and doctypes are handled, and namespace prefixes dropped.
+fn parse_xml(content: &str) -> Result<Element> {
+    let mut stack = vec![Element::default()];
+    let mut rest = content;
+    while let Some(start) = rest.find('<') {
+        let text = decode_entities(&rest[..start]);
+        stack.last_mut().unwrap().text.push_str(&text);
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let end = comment.find("-->").context("Unterminated XML comment")?;
+            rest = &comment[end + 3..];
+            continue;
+        }
+        if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").context("Unterminated CDATA section")?;
+            stack.last_mut().unwrap().text.push_str(&cdata[..end]);
+            rest = &cdata[end + 3..];
+            continue;
+        }
+        let end = rest.find('>').context("Unterminated XML tag")?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+        if tag.starts_with(['?', '!']) {
+            continue;
+        }
+        let local_name = |name: &str| name.rsplit(':').next().unwrap_or(name).to_string();
+        if let Some(name) = tag.strip_prefix('/') {
+            if stack.len() < 2 {
+                anyhow::bail!("Unexpected closing tag </{}>", name.trim());
+            }
+            let element = stack.pop().unwrap();
+            if element.name != local_name(name.trim()) {
+                anyhow::bail!(
+                    "Closing tag </{}> does not match <{}>",
+                    name.trim(),
+                    element.name
+                );
+            }
+            stack.last_mut().unwrap().children.push(element);
+        } else {
+            let name = tag
+                .trim_end_matches('/')
+                .split_whitespace()
+                .next()
+                .context("Empty XML tag")?;
+            let element = Element {
+                name: local_name(name),
+                ..Element::default()
+            };
+            if tag.ends_with('/') {
+                stack.last_mut().unwrap().children.push(element);
+            } else {
+                stack.push(element);
+            }
+        }
+    }
+    if stack.len() != 1 {
+        anyhow::bail!("Unclosed tag <{}>", stack.last().unwrap().name);
+    }
+    stack
+        .pop()
+        .and_then(|document| document.children.into_iter().next())
+        .context("No root element")
+}
+
+/// Replace the predefined XML entities
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Maven and Gradle project analyzer
+pub struct MavenAnalyzer {
+    vuln_db: Arc<dyn VulnerabilityDatabase>,
+    pattern_matcher: PatternMatcher,
+}
+
+impl MavenAnalyzer {
+    /// Create a new Maven analyzer using the bundled Java database
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            vuln_db: vulnerability_db::create_java_database()?.into(),
+            pattern_matcher: PatternMatcher::new()?,
+        })
+    }
+
+    /// Create analyzer sharing the compiled rules and database of `context`
+    pub fn from_context(context: &AnalyzerContext) -> Self {
+        Self {
+            vuln_db: context
+                .vulnerability_db(Ecosystem::Maven)
+                .expect("Maven has a vulnerability database"),
+            pattern_matcher: context.pattern_matcher().clone(),
+        }
+    }
+
+    /// Look up vulnerabilities in another database, such as a loaded snapshot
+    pub fn with_vulnerability_db(mut self, db: impl Into<Arc<dyn VulnerabilityDatabase>>) -> Self {
+        self.vuln_db = db.into();
+        self
+    }
+
+    /// Vulnerability database for an analysis with `options`
+    fn database(&self, options: &AnalysisOptions) -> Result<Arc<dyn VulnerabilityDatabase>> {
+        Ok(vulnerability_db::database_override(options)?.unwrap_or_else(|| self.vuln_db.clone()))
+    }
+
+    /// Whether `path` is a project directory or one of its build files
+    pub(crate) fn is_project(path: &Path) -> bool {
+        if path.is_dir() {
+            path.join(POM).exists()
+                || GRADLE_BUILD_FILES
+                    .iter()
+                    .any(|file| path.join(file).exists())
+        } else {
+            let name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            name == POM || name == GRADLE_LOCK_FILE || GRADLE_BUILD_FILES.contains(&name)
+        }
+    }
+
+    /// Project root for a project directory or one of its files
+    fn project_root(path: &Path) -> PathBuf {
+        if path.is_dir() {
+            path.to_path_buf()
+        } else {
+            path.parent().unwrap_or(Path::new(".")).to_path_buf()
+        }
+    }
+
+    /// Substitute `${…}` references until none resolve further
+    fn interpolate(value: &str, properties: &HashMap<String, String>) -> String {
+        let mut value = value.trim().to_string();
+        for _ in 0..MAX_INTERPOLATION_ROUNDS {
+            let next = POM_PROPERTY
+                .replace_all(&value, |caps: &regex::Captures| {
+                    properties
+                        .get(&caps[1])
+                        .cloned()
+                        .unwrap_or_else(|| caps[0].to_string())
+                })
+                .into_owned();
+            if next == value {
+                break;
+            }
+            value = next;
+        }
+        value
+    }
+
+    /// Dependencies listed under `element`, versions substituted
+    fn pom_dependencies(
+        element: Option<&Element>,
+        properties: &HashMap<String, String>,
+    ) -> Vec<MavenDependency> {
+        element
+            .into_iter()
+            .flat_map(|dependencies| dependencies.children("dependency"))
+            .filter_map(|dependency| {
+                let text = |name: &str| {
+                    dependency
+                        .text_of(name)
+                        .map(|value| Self::interpolate(&value, properties))
+                };
+                Some(MavenDependency {
+                    group_id: text("groupId")?,
+                    artifact_id: text("artifactId")?,
+                    version: text("version"),
+                    scope: text("scope").unwrap_or_else(|| "compile".to_string()),
+                    optional: text("optional").is_some_and(|optional| optional == "true"),
+                    declared_in: POM.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Parse a `pom.xml`
+    ///
+    /// Properties are the POM's own `<properties>` and the `project.*`
+    /// coordinates; a missing `groupId` or `version` is inherited from the
+    /// `<parent>` element, though the parent POM itself is not read.
+    fn parse_pom(content: &str) -> Result<MavenPackage> {
+        let project = parse_xml(content).context("Failed to parse pom.xml")?;
+        if project.name != "project" {
+            anyhow::bail!("pom.xml has no <project> element");
+        }
+        let parent = project.child("parent");
+        let parent_text = |name: &str| parent.and_then(|parent| parent.text_of(name));
+
+        let mut properties: HashMap<String, String> = project
+            .child("properties")
+            .into_iter()
+            .flat_map(|properties| properties.children.iter())
+            .map(|property| (property.name.clone(), property.text.trim().to_string()))
+            .collect();
+        let artifact_id = project
+            .text_of("artifactId")
+            .context("pom.xml has no artifactId")?;
+        let group_id = project
+            .text_of("groupId")
+            .or_else(|| parent_text("groupId"))
+            .context("pom.xml has no groupId")?;
+        let version = project
+            .text_of("version")
+            .or_else(|| parent_text("version"));
+        for prefix in ["project", "pom"] {
+            properties.insert(format!("{}.groupId", prefix), group_id.clone());
+            properties.insert(format!("{}.artifactId", prefix), artifact_id.clone());
+            if let Some(version) = &version {
+                properties.insert(format!("{}.version", prefix), version.clone());
+            }
+        }
+        for name in ["groupId", "artifactId", "version"] {
+            if let Some(value) = parent_text(name) {
+                properties.insert(format!("project.parent.{}", name), value);
+            }
+        }
+        let text = |name: &str| {
+            project
+                .text_of(name)
+                .map(|value| Self::interpolate(&value, &properties))
+        };
+
+        let managed_dependencies = Self::pom_dependencies(
+            project.descendant(&["dependencyManagement", "dependencies"]),
+            &properties,
+        );
+        let mut declared_dependencies =
+            Self::pom_dependencies(project.child("dependencies"), &properties);
+        for dependency in &mut declared_dependencies {
+            if dependency.version.is_none() {
+                dependency.version = managed_dependencies
+                    .iter()
+                    .find(|managed| managed.coordinate() == dependency.coordinate())
+                    .and_then(|managed| managed.version.clone());
+            }
+        }
+
+        let repositories = ["repositories", "pluginRepositories"]
+            .iter()
+            .filter_map(|section| project.child(section))
+            .flat_map(|section| section.children.iter())
+            .filter_map(|repository| {
+                let url = repository.text_of("url")?;
+                Some(MavenRepository {
+                    id: repository.text_of("id").unwrap_or_else(|| url.clone()),
+                    url: Self::interpolate(&url, &properties),
+                    declared_in: POM.to_string(),
+                })
+            })
+            .collect();
+
+        let developers: Vec<String> = project
+            .child("developers")
+            .into_iter()
+            .flat_map(|developers| developers.children("developer"))
+            .filter_map(|developer| developer.text_of("name"))
+            .collect();
+        let licenses: Vec<String> = project
+            .child("licenses")
+            .into_iter()
+            .flat_map(|licenses| licenses.children("license"))
+            .filter_map(|license| license.text_of("name"))
+            .collect();
+
+        Ok(MavenPackage {
+            metadata: PackageMetadata {
+                name: format!("{}:{}", group_id, artifact_id),
+                version: version
+                    .map(|version| Self::interpolate(&version, &properties))
+                    .unwrap_or_else(|| "unknown".to_string()),
+                description: text("description").or_else(|| text("name")),
+                author: (!developers.is_empty()).then(|| developers.join(", ")),
+                license: (!licenses.is_empty()).then(|| licenses.join(" OR ")),
+                homepage: text("url"),
+                repository: project
+                    .child("scm")
+                    .and_then(|scm| scm.text_of("url"))
+                    .map(|url| Self::interpolate(&url, &properties)),
+                keywords: vec![],
+                publish_date: None,
+            },
+            packaging: text("packaging").unwrap_or_else(|| "jar".to_string()),
+            parent: parent.map(|_| {
+                ["groupId", "artifactId", "version"]
+                    .map(|name| parent_text(name).unwrap_or_default())
+                    .join(":")
+            }),
+            group_id,
+            artifact_id,
+            build_tool: BuildTool::Maven,
+            declared_dependencies,
+            managed_dependencies,
+            repositories,
+            has_lock_file: false,
+            skipped_files: vec![],
+        })
+    }
+
+    /// Substitute `$name` and `${name}` references in a Gradle string
+    fn interpolate_gradle(value: &str, properties: &HashMap<String, String>) -> String {
+        GRADLE_PROPERTY
+            .replace_all(value, |caps: &regex::Captures| {
+                properties
+                    .get(&caps[1])
+                    .cloned()
+                    .unwrap_or_else(|| caps[0].to_string())
+            })
+            .into_owned()
+    }
+
+    /// String properties a Gradle build script and `gradle.properties` set
+    fn gradle_properties(script: &str, properties_file: &str) -> HashMap<String, String> {
+        let mut properties: HashMap<String, String> = properties_file
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.starts_with(['#', '!']))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect();
+        for caps in GRADLE_ASSIGNMENT.captures_iter(script) {
+            properties.insert(caps[1].to_string(), caps[2].to_string());
+        }
+        properties
+    }
+
+    /// Dependencies and repositories a Gradle build script declares
+    fn parse_gradle(
+        file: &str,
+        script: &str,
+        properties: &HashMap<String, String>,
+    ) -> (Vec<MavenDependency>, Vec<MavenRepository>) {
+        let dependency = |caps: regex::Captures| {
+            let configuration = &caps[1];
+            if !Self::is_gradle_configuration(configuration) {
+                return None;
+            }
+            Some(MavenDependency {
+                group_id: Self::interpolate_gradle(&caps[2], properties),
+                artifact_id: Self::interpolate_gradle(&caps[3], properties),
+                version: caps
+                    .get(4)
+                    .map(|version| Self::interpolate_gradle(version.as_str(), properties)),
+                scope: configuration.to_string(),
+                optional: false,
+                declared_in: file.to_string(),
+            })
+        };
+        let dependencies = GRADLE_DEPENDENCY
+            .captures_iter(script)
+            .chain(GRADLE_MAP_DEPENDENCY.captures_iter(script))
+            .filter_map(dependency)
+            .collect();
+        let repositories = GRADLE_REPOSITORY
+            .captures_iter(script)
+            .map(|caps| {
+                let url = Self::interpolate_gradle(&caps[1], properties);
+                MavenRepository {
+                    id: url.clone(),
+                    url,
+                    declared_in: file.to_string(),
+                }
+            })
+            .collect();
+        (dependencies, repositories)
+    }
+
+    /// Whether `name` is a Gradle configuration dependencies are declared in
+    fn is_gradle_configuration(name: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        [
+            "implementation",
+            "api",
+            "compile",
+            "compileonly",
+            "runtime",
+            "runtimeonly",
+            "annotationprocessor",
+            "kapt",
+            "classpath",
+        ]
+        .iter()
+        .any(|configuration| {
+            name == *configuration
+                || name
+                    .strip_prefix("test")
+                    .is_some_and(|rest| rest == *configuration)
+        })
+    }
+
+    /// Parse `gradle.lockfile`, one `group:artifact:version=configurations`
+    /// line per dependency
+    fn parse_lockfile(content: &str) -> Result<Vec<LockedDependency>> {
+        let mut locked = Vec::new();
+        for (number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with("empty=") {
+                continue;
+            }
+            let malformed = || anyhow::anyhow!("Malformed entry on line {}", number + 1);
+            let (coordinate, configurations) = line.split_once('=').ok_or_else(malformed)?;
+            let mut parts = coordinate.split(':');
+            let (Some(group_id), Some(artifact_id), Some(version), None) =
+                (parts.next(), parts.next(), parts.next(), parts.next())
+            else {
+                return Err(malformed());
+            };
+            locked.push(LockedDependency {
+                group_id: group_id.to_string(),
+                artifact_id: artifact_id.to_string(),
+                version: version.to_string(),
+                configurations: configurations
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|c| !c.is_empty())
+                    .map(String::from)
+                    .collect(),
+            });
+        }
+        Ok(locked)
+    }
+
+    /// Whether `version` names exactly one version
+    ///
+    /// Maven ranges such as `[1.0,2.0)`, Gradle dynamic versions such as
+    /// `1.+` and `latest.release`, and unresolved properties do not.
+    fn is_exact_version(version: &str) -> bool {
+        !version.is_empty()
+            && !version.contains(['[', ']', '(', ')', ',', '+', '$'])
+            && !matches!(version, "LATEST" | "RELEASE")
+            && !version.starts_with("latest.")
+    }
+
+    /// Build the dependency list from the declared dependencies and the lockfile
+    ///
+    /// Declared dependencies are direct; dependencies only the lockfile
+    /// names are transitive. A locked version takes precedence over the
+    /// declared one, which is otherwise used when it is exact.
+    async fn analyze_dependencies(
+        &self,
+        package: &MavenPackage,
+        locked: &[LockedDependency],
+        options: &AnalysisOptions,
+    ) -> Result<DependencyAnalysis> {
+        let mut analysis = DependencyAnalysis::default();
+        let locked_versions: HashMap<String, &LockedDependency> = locked
+            .iter()
+            .map(|dependency| (dependency.coordinate(), dependency))
+            .collect();
+
+        let mut seen = HashSet::new();
+        let declared = package
+            .declared_dependencies
+            .iter()
+            .filter(|dependency| seen.insert(dependency.coordinate()))
+            .map(|dependency| {
+                let coordinate = dependency.coordinate();
+                let resolved = match locked_versions.get(&coordinate) {
+                    Some(locked) => Some(locked.version.clone()),
+                    None => dependency
+                        .version
+                        .clone()
+                        .filter(|version| Self::is_exact_version(version)),
+                };
+                (
+                    coordinate,
+                    dependency.version.clone().unwrap_or_default(),
+                    resolved,
+                    dependency.dependency_type(),
+                    true,
+                )
+            })
+            .collect::<Vec<_>>();
+        let transitive = locked
+            .iter()
+            .filter(|dependency| !seen.contains(&dependency.coordinate()))
+            .map(|dependency| {
+                (
+                    dependency.coordinate(),
+                    dependency.version.clone(),
+                    Some(dependency.version.clone()),
+                    dependency.dependency_type(),
+                    false,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let db = self.database(options)?;
+        for (name, version_spec, resolved_version, dependency_type, is_direct) in
+            declared.into_iter().chain(transitive)
+        {
+            let vulnerabilities = match &resolved_version {
+                Some(version) if options.check_vulnerabilities => {
+                    db.check_package_with_policy(
+                        &name,
+                        version,
+                        "maven",
+                        options.prerelease_policy("maven"),
+                    )
+                    .await?
+                }
+                _ => vec![],
+            };
+            progress::dependency_resolved(&name);
+            analysis.dependency_tree.push(Dependency {
+                name,
+                version_spec,
+                resolved_version,
+                is_dev: matches!(dependency_type, DependencyType::Test),
+                dependency_type,
+                is_direct,
+                vulnerabilities,
+                license: None,
+                dependencies: vec![],
+                alias_target: None,
+                range_vulnerabilities: vec![],
+                shadowed_declarations: vec![],
+                source: None,
+                platform: None,
+                hashes: vec![],
+                marker: None,
+            });
+        }
+        analysis.summarize();
+        Ok(analysis)
+    }
+
+    /// Repositories fetched without TLS or from hosts the source policy does
+    /// not allow, and a lockfile that could not be read
+    fn source_findings(package: &MavenPackage, options: &AnalysisOptions) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let mut files: Vec<&str> = package
+            .repositories
+            .iter()
+            .map(|repository| repository.declared_in.as_str())
+            .collect();
+        files.dedup();
+        for file in files {
+            let sources: Vec<(&str, &str)> = package
+                .repositories
+                .iter()
+                .filter(|repository| repository.declared_in == file)
+                .map(|repository| (repository.id.as_str(), repository.url.as_str()))
+                .collect();
+            findings.extend(transport::insecure_transport_findings(
+                sources.clone(),
+                file,
+            ));
+            findings.extend(options.source_policy.findings(sources, file));
+        }
+        findings.extend(package.skipped_files.iter().map(|skipped| {
+            skipped
+                .to_finding()
+                .with_scan_reason(ScanReason::DependencyAnalysis)
+        }));
+        findings
+    }
+
+    /// Read the project's build files into a package and the files to scan
+    ///
+    /// `pom.xml` is checked against `limits` before it is parsed.
+    async fn read_project(
+        root: &Path,
+        limits: &ManifestLimits,
+    ) -> Result<(MavenPackage, Vec<(String, String)>)> {
+        let mut sources = Vec::new();
+        let mut package = match tokio::fs::read_to_string(root.join(POM)).await {
+            Ok(content) => {
+                limits.check_xml(POM, &content)?;
+                let package = Self::parse_pom(&content)?;
+                sources.push((POM.to_string(), content));
+                Some(package)
+            }
+            Err(_) => None,
+        };
+
+        let properties_file = tokio::fs::read_to_string(root.join(GRADLE_PROPERTIES))
+            .await
+            .unwrap_or_default();
+        for file in GRADLE_BUILD_FILES {
+            let Ok(script) = tokio::fs::read_to_string(root.join(file)).await else {
+                continue;
+            };
+            let properties = Self::gradle_properties(&script, &properties_file);
+            let (dependencies, repositories) = Self::parse_gradle(file, &script, &properties);
+            let package = package.get_or_insert_with(|| Self::gradle_package(root, &properties));
+            package.declared_dependencies.extend(dependencies);
+            package.repositories.extend(repositories);
+            sources.push((file.to_string(), script));
+        }
+
+        let package = package.with_context(|| {
+            format!(
+                "No pom.xml or Gradle build script found in {}",
+                root.display()
+            )
+        })?;
+        Ok((package, sources))
+    }
+
+    /// Package for a Gradle project, named `group:rootProject.name`
+    ///
+    /// The project name falls back to the directory name, and the group to
+    /// none when the build script does not set one.
+    fn gradle_package(root: &Path, properties: &HashMap<String, String>) -> MavenPackage {
+        let settings_name = GRADLE_SETTINGS_FILES
+            .iter()
+            .filter_map(|file| std::fs::read_to_string(root.join(file)).ok())
+            .find_map(|settings| Self::gradle_properties(&settings, "").remove("rootProject.name"));
+        let artifact_id = settings_name.unwrap_or_else(|| {
+            root.canonicalize()
+                .ok()
+                .and_then(|root| root.file_name()?.to_str().map(String::from))
+                .unwrap_or_else(|| "project".to_string())
+        });
+        let group_id = properties.get("group").cloned().unwrap_or_default();
+        MavenPackage {
+            metadata: PackageMetadata {
+                name: if group_id.is_empty() {
+                    artifact_id.clone()
+                } else {
+                    format!("{}:{}", group_id, artifact_id)
+                },
+                version: properties
+                    .get("version")
+                    .cloned()
+                    .unwrap_or_else(|| "unknown".to_string()),
+                description: properties.get("description").cloned(),
+                author: None,
+                license: None,
+                homepage: None,
+                repository: None,
+                keywords: vec![],
+                publish_date: None,
+            },
+            group_id,
+            artifact_id,
+            packaging: "jar".to_string(),
+            parent: None,
+            build_tool: BuildTool::Gradle,
+            declared_dependencies: vec![],
+            managed_dependencies: vec![],
+            repositories: vec![],
+            has_lock_file: false,
+            skipped_files: vec![],
+        }
+    }
+
+    /// Analysis of `path` without the deadline `options.timeout_seconds` sets
+    async fn run_analysis(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<MavenAnalysisResult> {
+        let budget = MemoryBudget::new(options.max_memory_bytes);
+        let root = Self::project_root(path);
+        let (mut package, sources) = Self::read_project(&root, &options.limits).await?;
+        budget.charge(
+            "build files",
+            sources.iter().map(|(_, source)| source.len()).sum(),
+        )?;
+        // Only the first `max_dependencies` are analyzed
+        let dependency_limit = options.limits.truncate_dependencies(
+            sources.first().map_or(POM, |(file, _)| file.as_str()),
+            &mut package.declared_dependencies,
+        );
+
+        let locked = match tokio::fs::read_to_string(root.join(GRADLE_LOCK_FILE)).await {
+            Ok(content) => match Self::parse_lockfile(&content) {
+                Ok(locked) => {
+                    package.has_lock_file = true;
+                    locked
+                }
+                Err(error) => {
+                    package
+                        .skipped_files
+                        .push(PartialParse::new(GRADLE_LOCK_FILE, error));
+                    vec![]
+                }
+            },
+            Err(_) => vec![],
+        };
+        progress::manifest_parsed(package.metadata());
+
+        progress::vuln_lookup_started(options);
+        let mut dependency_analysis = self
+            .analyze_dependencies(&package, &locked, options)
+            .await?;
+        deadline::dependencies_analyzed(&dependency_analysis);
+        budget.charge_for("dependencies", &dependency_analysis.dependency_tree)?;
+        dependency_analysis.license_findings =
+            options.license_policy.findings(&dependency_analysis);
+
+        let mut findings = if options.analyze_dependencies {
+            Self::source_findings(&package, options)
+        } else {
+            vec![]
+        };
+        findings.extend(dependency_limit);
+
+        // Gradle build scripts are code run by everyone building the project
+        let mut malicious_patterns = if options.scan_malicious_patterns {
+            let mut patterns: Vec<MaliciousPattern> = Vec::new();
+            for (file, source) in &sources {
+                for pattern in self.pattern_matcher.scan(source, Some(file)) {
+                    if !patterns.iter().any(|p| p.pattern_id == pattern.pattern_id) {
+                        patterns.push(pattern);
+                    }
+                }
+            }
+            patterns
+        } else {
+            vec![]
+        };
+        malicious_patterns.extend(custom_rules::scan_package(&root, options)?);
+        progress::pattern_scan_complete(&malicious_patterns);
+        budget.charge_for("malicious patterns", &malicious_patterns)?;
+
+        let vulnerabilities: Vec<Vulnerability> = dependency_analysis
+            .dependency_tree
+            .iter()
+            .flat_map(|dep| dep.vulnerabilities.iter().cloned())
+            .collect();
+
+        // Ranges and dynamic versions drift to new releases unless locked
+        let pinned = package.has_lock_file
+            || package
+                .declared_dependencies
+                .iter()
+                .all(|dep| dep.version.as_deref().is_some_and(Self::is_exact_version));
+
+        let risk_calculator = RiskCalculator::from_options(options);
+        let mut risk_score = risk_calculator.calculate(
+            &vulnerabilities,
+            &malicious_patterns,
+            false,
+            if pinned { 0.0 } else { 20.0 },
+            50.0, // Default maintenance score
+        );
+        risk_calculator.apply_findings(&mut risk_score, &findings);
+        risk_calculator.apply_pinning(
+            &mut risk_score,
+            &PinningReport::from_analysis(&dependency_analysis, "maven"),
+        );
+        let scoped_risk = risk_calculator.scoped_risk(
+            &risk_score,
+            &dependency_analysis,
+            &findings,
+            &options.production_dependency_types,
+        );
+
+        let scan_phases = vec![
+            PhaseRecord::new(
+                ScanReason::DependencyAnalysis,
+                options.analyze_dependencies,
+                findings.len(),
+            ),
+            PhaseRecord::new(
+                ScanReason::VulnerabilityCheck,
+                options.check_vulnerabilities,
+                vulnerabilities.len(),
+            ),
+            PhaseRecord::new(
+                ScanReason::MaliciousPatterns,
+                options.scan_malicious_patterns,
+                malicious_patterns.len(),
+            ),
+        ];
+
+        let risk_assessment = RiskAssessment {
+            risk_score: risk_score.clone(),
+            summary: format!(
+                "Maven package '{}' has {} risk with {} dependencies and {} findings; {}",
+                package.metadata.name,
+                risk_score.risk_level,
+                dependency_analysis.total_dependencies,
+                findings.len(),
+                scoped_risk
+            ),
+            detailed_findings: findings,
+            recommendations: dependency_analysis
+                .upgrade_recommendations_with("maven", options.recommend_prerelease_fixes),
+            security_posture: crate::core::SecurityPosture {
+                vulnerabilities_present: !vulnerabilities.is_empty(),
+                malicious_code_detected: !malicious_patterns.is_empty(),
+                supply_chain_risks: !pinned,
+                actively_maintained: true,
+                trusted_publisher: false,
+                security_practices_score: if pinned { 70.0 } else { 40.0 },
+            },
+            scan_phases,
+            scoped_risk: Some(scoped_risk),
+            scan_context: Some(ScanContext::current(self.database(options)?.last_updated())),
+        };
+
+        let quality_metrics =
+            QualityMetrics::assess(&root, package.metadata(), false, options.now());
+        Ok(MavenAnalysisResult {
+            package,
+            risk_assessment,
+            dependency_analysis,
+            vulnerabilities,
+            malicious_patterns,
+            quality_metrics,
+        })
+    }
+}
+
+#[async_trait]
+impl PackageAnalyzer for MavenAnalyzer {
+    type Package = MavenPackage;
+    type Analysis = MavenAnalysisResult;
+
+    async fn analyze(&self, path: &Path) -> Result<Self::Analysis> {
+        self.analyze_with_options(path, &AnalysisOptions::default())
+            .await
+    }
+
+    async fn analyze_with_options(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Self::Analysis> {
+        deadline::enforce(path, options, self.run_analysis(path, options)).await
+    }
+
+    fn can_analyze(&self, path: &Path) -> bool {
+        Self::is_project(path)
+    }
+
+    fn name(&self) -> &str {
+        "Maven Analyzer"
+    }
+
+    fn supported_extensions(&self) -> Vec<&str> {
+        vec!["xml", "gradle", "kts", "lockfile"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pom_properties_and_management() {
+        let pom = r#"<?xml version="1.0" encoding="UTF-8"?>
+<project xmlns="http://maven.apache.org/POM/4.0.0">
+  <modelVersion>4.0.0</modelVersion>
+  <parent>
+    <groupId>com.acme</groupId>
+    <artifactId>acme-parent</artifactId>
+    <version>3.0.0</version>
+  </parent>
+  <artifactId>billing</artifactId>
+  <!-- <version>ignored</version> -->
+  <properties>
+    <jackson.version>2.15.2</jackson.version>
+    <jackson.databind.version>${jackson.version}</jackson.databind.version>
+  </properties>
+  <dependencyManagement>
+    <dependencies>
+      <dependency>
+        <groupId>org.slf4j</groupId>
+        <artifactId>slf4j-api</artifactId>
+        <version>2.0.9</version>
+      </dependency>
+    </dependencies>
+  </dependencyManagement>
+  <dependencies>
+    <dependency>
+      <groupId>com.fasterxml.jackson.core</groupId>
+      <artifactId>jackson-databind</artifactId>
+      <version>${jackson.databind.version}</version>
+    </dependency>
+    <dependency>
+      <groupId>org.slf4j</groupId>
+      <artifactId>slf4j-api</artifactId>
+    </dependency>
+    <dependency>
+      <groupId>${project.groupId}</groupId>
+      <artifactId>billing-test-kit</artifactId>
+      <version>${project.version}</version>
+      <scope>test</scope>
+    </dependency>
+  </dependencies>
+</project>
+"#;
+        let package = MavenAnalyzer::parse_pom(pom).unwrap();
+        assert_eq!(package.metadata.name, "com.acme:billing");
+        assert_eq!(package.metadata.version, "3.0.0");
+        assert_eq!(
+            package.parent.as_deref(),
+            Some("com.acme:acme-parent:3.0.0")
+        );
+        let declared: Vec<(String, Option<&str>, DependencyType)> = package
+            .declared_dependencies
+            .iter()
+            .map(|d| (d.coordinate(), d.version.as_deref(), d.dependency_type()))
+            .collect();
+        assert_eq!(
+            declared,
+            [
+                (
+                    "com.fasterxml.jackson.core:jackson-databind".to_string(),
+                    Some("2.15.2"),
+                    DependencyType::Runtime
+                ),
+                (
+                    "org.slf4j:slf4j-api".to_string(),
+                    Some("2.0.9"),
+                    DependencyType::Runtime
+                ),
+                (
+                    "com.acme:billing-test-kit".to_string(),
+                    Some("3.0.0"),
+                    DependencyType::Test
+                ),
+            ]
+        );
+
+        assert!(MavenAnalyzer::parse_pom("<project><artifactId>x</project>").is_err());
+    }
+
+    #[test]
+    fn test_parse_gradle_and_lockfile() {
+        let script = r#"
+plugins { id 'java' }
+
+group = 'com.acme'
+version = '1.2.0'
+def guavaVersion = '32.1.2-jre'
+
+repositories {
+    mavenCentral()
+    maven { url 'http://repo.acme.internal/maven' }
+}
+
+dependencies {
+    implementation "com.google.guava:guava:$guavaVersion"
+    compileOnly 'org.projectlombok:lombok:1.18.30'
+    testImplementation group: 'junit', name: 'junit', version: '4.13.2'
+    implementation 'org.apache.commons:commons-lang3:3.+'
+}
+"#;
+        let properties = MavenAnalyzer::gradle_properties(script, "");
+        let (dependencies, repositories) =
+            MavenAnalyzer::parse_gradle("build.gradle", script, &properties);
+        let declared: Vec<(String, Option<&str>, DependencyType)> = dependencies
+            .iter()
+            .map(|d| (d.coordinate(), d.version.as_deref(), d.dependency_type()))
+            .collect();
+        assert_eq!(
+            declared,
+            [
+                (
+                    "com.google.guava:guava".to_string(),
+                    Some("32.1.2-jre"),
+                    DependencyType::Runtime
+                ),
+                (
+                    "org.projectlombok:lombok".to_string(),
+                    Some("1.18.30"),
+                    DependencyType::Build
+                ),
+                (
+                    "org.apache.commons:commons-lang3".to_string(),
+                    Some("3.+"),
+                    DependencyType::Runtime
+                ),
+                (
+                    "junit:junit".to_string(),
+                    Some("4.13.2"),
+                    DependencyType::Test
+                ),
+            ]
+        );
+        assert_eq!(repositories[0].url, "http://repo.acme.internal/maven");
+        assert!(!MavenAnalyzer::is_exact_version("3.+"));
+        assert!(!MavenAnalyzer::is_exact_version("[1.0,2.0)"));
+
+        let lockfile = "\
+# This is a Gradle generated file for dependency locking.
+com.google.guava:failureaccess:1.0.1=compileClasspath,runtimeClasspath
+junit:junit:4.13.2=testCompileClasspath,testRuntimeClasspath
+empty=annotationProcessor
+";
+        let locked = MavenAnalyzer::parse_lockfile(lockfile).unwrap();
+        assert_eq!(locked.len(), 2);
+        assert_eq!(locked[0].dependency_type(), DependencyType::Runtime);
+        assert_eq!(locked[1].dependency_type(), DependencyType::Test);
+        assert!(MavenAnalyzer::parse_lockfile("junit:junit=test").is_err());
+    }
+}
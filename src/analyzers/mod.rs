@@ -1,10 +1,26 @@
 //! Package-specific analyzers
 
+pub mod cargo;
+pub mod composer;
+pub mod extension;
+pub mod go;
+pub mod helm;
 pub mod java;
+pub mod maven;
 pub mod npm;
 pub mod python;
+pub mod ruby;
+pub mod terraform;
 
 // Re-export analyzers
+pub use cargo::CargoAnalyzer;
+pub use composer::ComposerAnalyzer;
+pub use extension::ExtensionAnalyzer;
+pub use go::GoModuleAnalyzer;
+pub use helm::HelmAnalyzer;
 pub use java::JavaAnalyzer;
+pub use maven::MavenAnalyzer;
 pub use npm::NpmAnalyzer;
 pub use python::PythonAnalyzer;
+pub use ruby::RubyGemAnalyzer;
+pub use terraform::TerraformAnalyzer;
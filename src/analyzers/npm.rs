@@ -2,48 +2,70 @@
 
 use anyhow::{Context, Result};
 use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::Path;
+use std::sync::Arc;
 
+use crate::context::AnalyzerContext;
 use crate::core::{
-    AnalysisResult, Dependency, DependencyAnalysis, DependencyType, MaliciousPattern,
-    PackageAnalyzer, PackageInfo, PackageMetadata, PatternMatcher, RiskAssessment, RiskCalculator,
-    Vulnerability,
+    custom_rules, deadline, progress, rules, transport, AnalysisOptions, AnalysisResult,
+    CrossLanguageExecution, DelayedExecution, Dependency, DependencyAlias, DependencyAnalysis,
+    DependencyConfusionRisk, DependencyGraph, DependencyType, EnvironmentFingerprint, Finding,
+    FindingType, ImportHijack, InstallHook, MaliciousPattern, MemoryBudget, MemoryFootprint,
+    MetadataInjection, PackageAnalyzer, PackageInfo, PartialParse, PatternCategory, PatternMatcher,
+    PhaseRecord, PinningReport, PlatformTargetedPayload, Provenance, ProvenanceVerifier,
+    PublicRegistry, PurposeMismatch, QualityMetrics, RangeVulnerability, RegistryExfiltration,
+    RepositoryValidator, RiskAssessment, RiskCalculator, RiskLevel, ScanContext, ScanReason,
+    ScriptLanguage, ShadowedDeclaration, SymbolUsage, Vulnerability,
 };
-use crate::utils::typosquatting::TyposquattingDetector;
-use crate::vulnerability_db::VulnerabilityDatabase;
+use crate::ecosystem::Ecosystem;
+use crate::manifest::npm::{
+    install_hooks, parse_package_json, INSTALL_HOOKS, SCOPE_CONFUSION_CONFIDENCE,
+};
+use crate::utils::binary::{self, BinaryKind, BundledBinary};
+use crate::utils::digest;
+use crate::utils::generated_names::GeneratedNameDetector;
+use crate::utils::npm_tarball::{self, ExtractedTarball};
+use crate::utils::typosquatting::{
+    SimilarPackage, TyposquattingDetector, TyposquattingReason, TyposquattingTargetProvider,
+    TyposquattingTechnique,
+};
+use crate::utils::yarn_lock;
+use crate::versioning::VersionSchemeRegistry;
+use crate::vulnerability_db::{self, VulnerabilityDatabase};
 
-/// NPM package information
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NpmPackage {
-    pub metadata: PackageMetadata,
-    pub main: Option<String>,
-    pub scripts: HashMap<String, String>,
-    pub engines: HashMap<String, String>,
-    pub files: Vec<String>,
-    pub private: bool,
-}
+/// `package.json` fields declaring dependencies, with the type of each
+const DEPENDENCY_FIELDS: &[(&str, DependencyType)] = &[
+    ("dependencies", DependencyType::Runtime),
+    ("devDependencies", DependencyType::Development),
+    ("peerDependencies", DependencyType::Peer),
+    ("optionalDependencies", DependencyType::Optional),
+];
 
-impl PackageInfo for NpmPackage {
-    fn metadata(&self) -> &PackageMetadata {
-        &self.metadata
-    }
+/// Lockfiles npm installs from, in order of precedence
+const LOCK_FILES: &[&str] = &["npm-shrinkwrap.json", "package-lock.json", YARN_LOCK];
 
-    fn package_type(&self) -> &str {
-        "npm"
-    }
+/// Lockfile written by Yarn, read when npm has none
+const YARN_LOCK: &str = "yarn.lock";
 
-    fn custom_attributes(&self) -> HashMap<String, serde_json::Value> {
-        let mut attrs = HashMap::new();
-        attrs.insert("main".to_string(), serde_json::json!(self.main));
-        attrs.insert("scripts".to_string(), serde_json::json!(self.scripts));
-        attrs.insert("engines".to_string(), serde_json::json!(self.engines));
-        attrs.insert("private".to_string(), serde_json::json!(self.private));
-        attrs
-    }
-}
+/// Local script files run by an install command, e.g. `node scripts/setup.js`
+static SCRIPT_INVOCATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"\b(?:node|sh|bash|python3?|pwsh|powershell(?:\.exe)?(?:\s+-File)?)\s+((?:\./)?[\w./-]+\.(?:c?js|mjs|sh|py|ps1))\b",
+    )
+    .unwrap()
+});
+
+/// Binary file names mentioned in a script
+static BINARY_REFERENCE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"([\w@./-]+\.(?:wasm|node))(?:$|[\s"';&|>)])"#).unwrap());
+
+pub use crate::manifest::npm::NpmPackage;
 
 /// NPM analysis result
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +77,13 @@ pub struct NpmAnalysisResult {
     pub malicious_patterns: Vec<MaliciousPattern>,
     pub scripts_analysis: ScriptsAnalysis,
     pub typosquatting_risk: Option<TyposquattingRisk>,
+    pub bundled_binaries: Vec<BundledBinary>,
+    /// Digest of the analysis options and every package file except the lockfile
+    #[serde(default)]
+    pub input_digest: Option<String>,
+    /// Signs the package is tested, documented and maintained
+    #[serde(default)]
+    pub quality_metrics: QualityMetrics,
 }
 
 impl AnalysisResult for NpmAnalysisResult {
@@ -66,6 +95,10 @@ impl AnalysisResult for NpmAnalysisResult {
         &self.risk_assessment
     }
 
+    fn risk_assessment_mut(&mut self) -> &mut RiskAssessment {
+        &mut self.risk_assessment
+    }
+
     fn dependency_analysis(&self) -> &DependencyAnalysis {
         &self.dependency_analysis
     }
@@ -78,6 +111,10 @@ impl AnalysisResult for NpmAnalysisResult {
         &self.malicious_patterns
     }
 
+    fn quality_metrics(&self) -> QualityMetrics {
+        self.quality_metrics.clone()
+    }
+
     fn to_json(&self) -> Result<serde_json::Value> {
         Ok(serde_json::to_value(self)?)
     }
@@ -89,8 +126,256 @@ impl AnalysisResult for NpmAnalysisResult {
                 is_potential_typosquatting: risk.is_likely_typosquatting,
                 similar_packages: risk.similar_packages.clone(),
                 confidence_score: risk.confidence,
+                techniques: risk
+                    .similar_packages
+                    .first()
+                    .map(|best| best.techniques.clone())
+                    .unwrap_or_default(),
+                reason: risk.reason,
             })
     }
+
+    fn install_hooks(&self) -> Vec<InstallHook> {
+        install_hooks(&self.package.scripts)
+    }
+}
+
+/// How much of a previous result a lockfile re-scan reused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RescanStats {
+    /// Files other than the lockfile changed, so everything was re-scanned
+    pub full_scan: bool,
+    /// Dependencies looked up again because they are new or their version changed
+    pub reanalyzed: usize,
+    /// Dependencies whose previous results were reused
+    pub reused: usize,
+}
+
+/// Contents of the lockfile npm installs from
+#[derive(Default)]
+struct Lockfile {
+    file: &'static str,
+    /// Locked version of each direct dependency
+    versions: HashMap<String, String>,
+    /// Every locked package, keyed by an id unique within the lockfile
+    packages: HashMap<String, LockedPackage>,
+    /// Id of the locked package each direct dependency resolves to
+    roots: HashMap<String, String>,
+    /// `(name, resolved URL)` of every locked package, nested copies included
+    sources: Vec<(String, String)>,
+    /// Lockfiles passed over because they could not be parsed
+    skipped: Vec<PartialParse>,
+}
+
+/// A locked package and the ids its own dependencies resolve to
+struct LockedPackage {
+    /// Name of the installed package, the target for an alias
+    name: String,
+    version: String,
+    dependencies: Vec<String>,
+    /// SPDX license expression, recorded by lockfile v2 and later
+    license: Option<String>,
+}
+
+impl MemoryFootprint for Lockfile {
+    fn footprint(&self) -> usize {
+        let versions: usize = self
+            .versions
+            .iter()
+            .chain(&self.roots)
+            .map(|(name, version)| name.footprint() + version.footprint())
+            .sum();
+        let packages: usize = self
+            .packages
+            .iter()
+            .map(|(id, package)| {
+                id.footprint()
+                    + package.name.footprint()
+                    + package.version.footprint()
+                    + package.dependencies.footprint()
+                    + package
+                        .license
+                        .as_ref()
+                        .map_or(0, MemoryFootprint::footprint)
+            })
+            .sum();
+        let sources: usize = self
+            .sources
+            .iter()
+            .map(|(name, url)| name.footprint() + url.footprint())
+            .sum();
+        versions + packages + sources
+    }
+}
+
+impl Lockfile {
+    /// Locked dependencies of each direct dependency, up to `max_depth` levels
+    ///
+    /// Walks breadth-first from the direct dependencies in `direct` order, so
+    /// a package shared by several dependents is listed once, beneath the
+    /// closest. Transitive packages inherit the dependency type of the direct
+    /// dependency above them and are not yet checked for vulnerabilities.
+    /// At most `max_packages` are added in total.
+    fn transitive_trees(
+        &self,
+        direct: &[(&str, &DependencyType)],
+        max_depth: usize,
+        max_packages: usize,
+    ) -> HashMap<String, Vec<Dependency>> {
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut queue: VecDeque<(&str, usize)> = VecDeque::new();
+        let mut owners: HashMap<&str, usize> = HashMap::new();
+        for (index, (name, _)) in direct.iter().enumerate() {
+            if let Some(id) = self.roots.get(*name) {
+                if visited.insert(id) {
+                    owners.insert(id, index);
+                    queue.push_back((id, 1));
+                }
+            }
+        }
+
+        let mut order = Vec::new();
+        let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut added = 0;
+        while let Some((id, depth)) = queue.pop_front() {
+            order.push(id);
+            if depth >= max_depth {
+                continue;
+            }
+            for child in &self.packages[id].dependencies {
+                if added >= max_packages || !visited.insert(child) {
+                    continue;
+                }
+                added += 1;
+                owners.insert(child, owners[id]);
+                children.entry(id).or_default().push(child);
+                queue.push_back((child, depth + 1));
+            }
+        }
+
+        // Children come after their parent in breadth-first order, so
+        // building in reverse always finds them complete
+        let mut built: HashMap<&str, Dependency> = HashMap::new();
+        let mut trees: HashMap<String, Vec<Dependency>> = HashMap::new();
+        for &id in order.iter().rev() {
+            let dependencies: Vec<Dependency> = children
+                .get(id)
+                .into_iter()
+                .flatten()
+                .filter_map(|child| built.remove(child))
+                .collect();
+            let (direct_name, dependency_type) = direct[owners[id]];
+            if self.roots.get(direct_name).map(String::as_str) == Some(id) {
+                trees.insert(direct_name.to_string(), dependencies);
+                continue;
+            }
+            let package = &self.packages[id];
+            built.insert(
+                id,
+                Dependency {
+                    name: package.name.clone(),
+                    version_spec: package.version.clone(),
+                    resolved_version: Some(package.version.clone()),
+                    dependency_type: dependency_type.clone(),
+                    is_direct: false,
+                    is_dev: matches!(dependency_type, DependencyType::Development),
+                    vulnerabilities: vec![],
+                    license: package.license.clone(),
+                    dependencies,
+                    alias_target: None,
+                    range_vulnerabilities: vec![],
+                    shadowed_declarations: vec![],
+                    source: None,
+                    platform: None,
+                    hashes: vec![],
+                    marker: None,
+                },
+            );
+        }
+        trees
+    }
+}
+
+/// Whether two locked subtrees hold the same packages at the same versions
+fn same_locked_tree(previous: &[Dependency], current: &[Dependency]) -> bool {
+    let mut pairs = vec![(previous, current)];
+    while let Some((previous, current)) = pairs.pop() {
+        if previous.len() != current.len() {
+            return false;
+        }
+        for (old, new) in previous.iter().zip(current) {
+            if old.name != new.name
+                || old.resolved_version != new.resolved_version
+                || old.dependency_type != new.dependency_type
+            {
+                return false;
+            }
+            pairs.push((&old.dependencies, &new.dependencies));
+        }
+    }
+    true
+}
+
+/// Results of scanning package files, independent of dependency versions
+struct FileScan {
+    scripts_analysis: ScriptsAnalysis,
+    malicious_patterns: Vec<MaliciousPattern>,
+    typosquatting_risk: Option<TyposquattingRisk>,
+    bundled_binaries: Vec<BundledBinary>,
+    /// Findings of every phase but dependency analysis, in report order
+    findings: Vec<Finding>,
+    quality_metrics: QualityMetrics,
+}
+
+impl FileScan {
+    /// Recover the file scan from an earlier result
+    fn from_result(result: &NpmAnalysisResult) -> Self {
+        Self {
+            scripts_analysis: result.scripts_analysis.clone(),
+            malicious_patterns: result.malicious_patterns.clone(),
+            typosquatting_risk: result.typosquatting_risk.clone(),
+            bundled_binaries: result.bundled_binaries.clone(),
+            findings: result
+                .risk_assessment
+                .detailed_findings
+                .iter()
+                .filter(|f| f.scan_reason != Some(ScanReason::DependencyAnalysis))
+                .cloned()
+                .collect(),
+            quality_metrics: result.quality_metrics.clone(),
+        }
+    }
+}
+
+/// An install command followed by the local scripts it runs, scanned as one text
+struct InstallScriptSource {
+    text: String,
+    /// Scripts appended to `text`, with the offset each starts at
+    scripts: Vec<(String, usize)>,
+}
+
+impl InstallScriptSource {
+    /// Point pattern locations at the script they fall in
+    ///
+    /// Matches in the command itself are left without a file, since the
+    /// command is only part of `package.json`.
+    fn locate(&self, pattern: &mut MaliciousPattern) {
+        for location in &mut pattern.locations {
+            location.file = None;
+            if let Some((script, start)) = self
+                .scripts
+                .iter()
+                .rev()
+                .find(|(_, start)| location.offset >= *start)
+            {
+                // Scripts start on a line of their own, so columns carry over
+                location.file = Some(script.clone());
+                location.line -= self.text[..*start].matches('\n').count();
+                location.offset -= start;
+            }
+        }
+        pattern.update_position();
+    }
 }
 
 /// NPM-specific scripts analysis
@@ -112,207 +397,705 @@ pub struct SuspiciousScript {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TyposquattingRisk {
     pub is_likely_typosquatting: bool,
-    pub similar_packages: Vec<String>,
+    pub similar_packages: Vec<SimilarPackage>,
     pub confidence: f32,
+    #[serde(default)]
+    pub reason: TyposquattingReason,
 }
 
 /// NPM package analyzer
 pub struct NpmAnalyzer {
-    vuln_db: Box<dyn VulnerabilityDatabase>,
+    vuln_db: Arc<dyn VulnerabilityDatabase>,
     pattern_matcher: PatternMatcher,
     typo_detector: TyposquattingDetector,
+    name_detector: GeneratedNameDetector,
+    repository_validator: RepositoryValidator,
+    provenance_verifier: ProvenanceVerifier,
+    public_registry: PublicRegistry,
 }
 
 impl NpmAnalyzer {
     /// Create a new NPM analyzer
     pub fn new() -> Result<Self> {
         Ok(Self {
-            vuln_db: crate::vulnerability_db::create_npm_database()?,
+            vuln_db: crate::vulnerability_db::create_npm_database()?.into(),
             pattern_matcher: PatternMatcher::new()?,
-            typo_detector: TyposquattingDetector::new(),
+            typo_detector: TyposquattingDetector::for_ecosystem("npm"),
+            name_detector: GeneratedNameDetector::new(),
+            repository_validator: RepositoryValidator::new()?,
+            provenance_verifier: ProvenanceVerifier::new()?,
+            public_registry: PublicRegistry::new()?,
         })
     }
 
     /// Create analyzer with custom database path
     pub fn with_db_path(db_path: &Path) -> Result<Self> {
         Ok(Self {
-            vuln_db: crate::vulnerability_db::create_npm_database_with_path(db_path)?,
+            vuln_db: crate::vulnerability_db::create_npm_database_with_path(db_path)?.into(),
             pattern_matcher: PatternMatcher::new()?,
-            typo_detector: TyposquattingDetector::new(),
+            typo_detector: TyposquattingDetector::for_ecosystem("npm"),
+            name_detector: GeneratedNameDetector::new(),
+            repository_validator: RepositoryValidator::new()?,
+            provenance_verifier: ProvenanceVerifier::new()?,
+            public_registry: PublicRegistry::new()?,
         })
     }
 
-    /// Parse package.json file
-    async fn parse_package_json(&self, content: &str) -> Result<NpmPackage> {
-        let json: Value = serde_json::from_str(content).context("Failed to parse package.json")?;
-
-        let obj = json
-            .as_object()
-            .ok_or_else(|| anyhow::anyhow!("package.json is not an object"))?;
-
-        let metadata = PackageMetadata {
-            name: obj
-                .get("name")
-                .and_then(|v| v.as_str())
-                .unwrap_or("unknown")
-                .to_string(),
-            version: obj
-                .get("version")
-                .and_then(|v| v.as_str())
-                .unwrap_or("0.0.0")
-                .to_string(),
-            description: obj
-                .get("description")
-                .and_then(|v| v.as_str())
-                .map(String::from),
-            author: obj.get("author").and_then(|v| v.as_str()).map(String::from),
-            license: obj
-                .get("license")
-                .and_then(|v| v.as_str())
-                .map(String::from),
-            homepage: obj
-                .get("homepage")
-                .and_then(|v| v.as_str())
-                .map(String::from),
-            repository: obj.get("repository").and_then(|v| {
-                if let Some(s) = v.as_str() {
-                    Some(s.to_string())
-                } else if let Some(obj) = v.as_object() {
-                    obj.get("url").and_then(|u| u.as_str()).map(String::from)
-                } else {
-                    None
-                }
-            }),
-            keywords: obj
-                .get("keywords")
-                .and_then(|v| v.as_array())
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| v.as_str().map(String::from))
-                        .collect()
-                })
-                .unwrap_or_default(),
-            publish_date: None,
-        };
-
-        let scripts = obj
-            .get("scripts")
-            .and_then(|v| v.as_object())
-            .map(|obj| {
-                obj.iter()
-                    .map(|(k, v)| (k.clone(), v.as_str().unwrap_or("").to_string()))
-                    .collect()
-            })
-            .unwrap_or_default();
-
-        let engines = obj
-            .get("engines")
-            .and_then(|v| v.as_object())
-            .map(|obj| {
-                obj.iter()
-                    .map(|(k, v)| (k.clone(), v.as_str().unwrap_or("").to_string()))
-                    .collect()
-            })
-            .unwrap_or_default();
-
-        let files = obj
-            .get("files")
-            .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.as_str().map(String::from))
-                    .collect()
-            })
-            .unwrap_or_default();
-
-        Ok(NpmPackage {
-            metadata,
-            main: obj.get("main").and_then(|v| v.as_str()).map(String::from),
-            scripts,
-            engines,
-            files,
-            private: obj
-                .get("private")
-                .and_then(|v| v.as_bool())
-                .unwrap_or(false),
+    /// Create analyzer sharing the compiled rules and database of `context`
+    pub fn from_context(context: &AnalyzerContext) -> Result<Self> {
+        Ok(Self {
+            vuln_db: context
+                .vulnerability_db(Ecosystem::Npm)
+                .expect("npm has a vulnerability database"),
+            pattern_matcher: context.pattern_matcher().clone(),
+            typo_detector: TyposquattingDetector::for_ecosystem("npm"),
+            name_detector: GeneratedNameDetector::new(),
+            repository_validator: RepositoryValidator::new()?,
+            provenance_verifier: ProvenanceVerifier::new()?,
+            public_registry: PublicRegistry::new()?,
         })
     }
 
+    /// Look up vulnerabilities in another database, such as a loaded snapshot
+    pub fn with_vulnerability_db(mut self, db: impl Into<Arc<dyn VulnerabilityDatabase>>) -> Self {
+        self.vuln_db = db.into();
+        self
+    }
+
+    /// Also compare package names against candidates from `provider`
+    pub fn with_typosquatting_provider(
+        mut self,
+        provider: Arc<dyn TyposquattingTargetProvider>,
+    ) -> Self {
+        self.typo_detector = self.typo_detector.with_target_provider(provider);
+        self
+    }
+
+    /// Look up provenance attestations in a registry mirror instead of npmjs.org
+    pub fn with_registry(mut self, registry: &str) -> Result<Self> {
+        self.provenance_verifier = ProvenanceVerifier::with_registry(registry)?;
+        Ok(self)
+    }
+
+    /// Fetch repository pages from a forge mirror instead of the forge itself
+    pub fn with_repository_base_url(mut self, base_url: &str) -> Result<Self> {
+        self.repository_validator = RepositoryValidator::with_base_url(base_url)?;
+        Ok(self)
+    }
+
     /// Analyze dependencies
-    async fn analyze_dependencies(&self, package_json: &Value) -> Result<DependencyAnalysis> {
+    pub(crate) async fn analyze_dependencies(
+        &self,
+        package_json: &Value,
+        options: &AnalysisOptions,
+    ) -> Result<DependencyAnalysis> {
+        let budget = MemoryBudget::new(options.max_memory_bytes);
+        let (analysis, _) = self
+            .resolve_dependencies(package_json, &Lockfile::default(), options, None, &budget)
+            .await?;
+        Ok(analysis)
+    }
+
+    /// Analyze dependencies at their locked versions, reusing unchanged ones from `previous`
+    ///
+    /// With a lockfile, each direct dependency carries the locked packages
+    /// beneath it up to `max_dependency_depth`, and those are checked for
+    /// vulnerabilities at their locked versions too. A direct dependency is
+    /// reused when its name, type, declared range, locked version and locked
+    /// subtree all match. Each dependency is charged to `budget` as it is
+    /// added. Returns the analysis and the number of reused dependencies.
+    async fn resolve_dependencies(
+        &self,
+        package_json: &Value,
+        lockfile: &Lockfile,
+        options: &AnalysisOptions,
+        previous: Option<&DependencyAnalysis>,
+        budget: &MemoryBudget,
+    ) -> Result<(DependencyAnalysis, usize)> {
         let mut analysis = DependencyAnalysis::default();
+        let mut reused = 0;
         let obj = package_json.as_object().unwrap();
+        let db = self.database(options)?;
 
-        // Parse different dependency types
-        let dep_types = [
-            ("dependencies", DependencyType::Runtime),
-            ("devDependencies", DependencyType::Development),
-            ("peerDependencies", DependencyType::Peer),
-            ("optionalDependencies", DependencyType::Optional),
-        ];
+        // Every section declaring each package, so that a package declared
+        // twice is analyzed once, as the section npm installs it from
+        let mut declarations: HashMap<&str, Vec<(&DependencyType, &str)>> = HashMap::new();
+        for (field, dep_type) in DEPENDENCY_FIELDS {
+            for (name, version_spec) in obj
+                .get(*field)
+                .and_then(|v| v.as_object())
+                .into_iter()
+                .flatten()
+            {
+                declarations
+                    .entry(name.as_str())
+                    .or_default()
+                    .push((dep_type, version_spec.as_str().unwrap_or("*")));
+            }
+        }
 
-        for (field, dep_type) in &dep_types {
+        // Parse different dependency types
+        let mut direct = Vec::new();
+        'declared: for (field, dep_type) in DEPENDENCY_FIELDS {
             if let Some(deps) = obj.get(*field).and_then(|v| v.as_object()) {
                 for (name, version_spec) in deps {
-                    let version_str = version_spec.as_str().unwrap_or("*");
-
-                    // Check for vulnerabilities
-                    let vulns = self.vuln_db.check_package(name, version_str, "npm").await?;
-
-                    let dependency = Dependency {
-                        name: name.clone(),
-                        version_spec: version_str.to_string(),
-                        resolved_version: None,
-                        dependency_type: dep_type.clone(),
-                        is_direct: true,
-                        is_dev: matches!(dep_type, DependencyType::Development),
-                        vulnerabilities: vulns,
-                        license: None,
-                        dependencies: vec![], // TODO: Parse lock file for transitive deps
-                    };
-
-                    analysis.dependency_tree.push(dependency);
-                    analysis.direct_dependencies += 1;
+                    let declared = &declarations[name.as_str()];
+                    if declared
+                        .iter()
+                        .any(|(other, _)| other.declaration_rank() < dep_type.declaration_rank())
+                    {
+                        continue;
+                    }
+                    if direct.len() >= options.limits.max_dependencies {
+                        break 'declared;
+                    }
+                    direct.push((name, dep_type, version_spec.as_str().unwrap_or("*")));
                 }
             }
         }
+        let mut trees = lockfile.transitive_trees(
+            &direct
+                .iter()
+                .map(|(name, dep_type, _)| (name.as_str(), *dep_type))
+                .collect::<Vec<_>>(),
+            options.max_dependency_depth,
+            options.limits.max_dependencies - direct.len(),
+        );
 
-        analysis.total_dependencies = analysis.dependency_tree.len();
+        for (name, dep_type, version_str) in direct {
+            let shadowed_declarations: Vec<ShadowedDeclaration> = declarations[name.as_str()]
+                .iter()
+                .filter(|(other, _)| *other != dep_type)
+                .map(|(other, spec)| ShadowedDeclaration {
+                    dependency_type: (*other).clone(),
+                    version_spec: spec.to_string(),
+                })
+                .collect();
+            let resolved_version = lockfile.versions.get(name).cloned();
+            let mut dependencies = trees.remove(name.as_str()).unwrap_or_default();
+            // An alias installs its target, so that is what gets looked up
+            let alias = DependencyAlias::parse(name, version_str);
+            let (package, range) = alias
+                .as_ref()
+                .map_or((name.as_str(), version_str), |alias| {
+                    (alias.target.as_str(), alias.range.as_str())
+                });
 
-        // Calculate vulnerability summary
-        for dep in &analysis.dependency_tree {
-            for vuln in &dep.vulnerabilities {
-                analysis.vulnerability_summary.total_vulnerabilities += 1;
-                match vuln.severity {
-                    crate::core::VulnerabilitySeverity::Critical => {
-                        analysis.vulnerability_summary.critical_count += 1;
-                    }
-                    crate::core::VulnerabilitySeverity::High => {
-                        analysis.vulnerability_summary.high_count += 1;
-                    }
-                    crate::core::VulnerabilitySeverity::Medium => {
-                        analysis.vulnerability_summary.medium_count += 1;
-                    }
-                    crate::core::VulnerabilitySeverity::Low => {
-                        analysis.vulnerability_summary.low_count += 1;
-                    }
-                    _ => {}
+            let unchanged = previous.and_then(|previous| {
+                previous.dependency_tree.iter().find(|dep| {
+                    &dep.name == name
+                        && dep.dependency_type == *dep_type
+                        && dep.version_spec == version_str
+                        && dep.resolved_version == resolved_version
+                        && dep.shadowed_declarations == shadowed_declarations
+                        && same_locked_tree(&dep.dependencies, &dependencies)
+                })
+            });
+            if let Some(dependency) = unchanged {
+                budget.charge_for("dependencies", dependency)?;
+                progress::dependency_resolved(&dependency.name);
+                analysis.dependency_tree.push(dependency.clone());
+                reused += 1;
+                continue;
+            }
+
+            // Check for vulnerabilities, at the locked version when known
+            let vulns = if options.check_vulnerabilities {
+                let version = resolved_version.as_deref().unwrap_or(range);
+                db.check_package_with_policy(
+                    package,
+                    version,
+                    "npm",
+                    options.prerelease_policy("npm"),
+                )
+                .await?
+            } else {
+                vec![]
+            };
+            // Other advisories the range could drift into; only known
+            // once a lockfile pins what is installed now
+            let range_vulnerabilities =
+                if options.check_vulnerabilities && resolved_version.is_some() {
+                    self.range_vulnerabilities(package, range, &vulns, options)
+                        .await?
+                } else {
+                    vec![]
+                };
+            if options.check_vulnerabilities {
+                let mut nodes: Vec<&mut Dependency> = dependencies.iter_mut().collect();
+                while let Some(node) = nodes.pop() {
+                    node.vulnerabilities = db
+                        .check_package_with_policy(
+                            &node.name,
+                            &node.version_spec,
+                            "npm",
+                            options.prerelease_policy("npm"),
+                        )
+                        .await?;
+                    nodes.extend(node.dependencies.iter_mut());
                 }
+            }
 
-                if !analysis
-                    .vulnerability_summary
-                    .vulnerable_dependencies
-                    .contains(&dep.name)
-                {
-                    analysis
-                        .vulnerability_summary
-                        .vulnerable_dependencies
-                        .push(dep.name.clone());
+            let dependency = Dependency {
+                name: name.clone(),
+                version_spec: version_str.to_string(),
+                resolved_version,
+                dependency_type: dep_type.clone(),
+                is_direct: true,
+                is_dev: matches!(dep_type, DependencyType::Development),
+                vulnerabilities: vulns,
+                license: lockfile
+                    .roots
+                    .get(name)
+                    .and_then(|id| lockfile.packages[id].license.clone()),
+                dependencies,
+                alias_target: alias.map(|alias| alias.target),
+                range_vulnerabilities,
+                shadowed_declarations,
+                source: None,
+                platform: None,
+                hashes: vec![],
+                marker: None,
+            };
+            budget.charge_for("dependencies", &dependency)?;
+            progress::dependency_resolved(&dependency.name);
+
+            analysis.dependency_tree.push(dependency);
+        }
+
+        analysis.summarize();
+
+        Ok((analysis, reused))
+    }
+
+    /// Advisories for `package` outside `current` that affect a version `range` permits
+    async fn range_vulnerabilities(
+        &self,
+        package: &str,
+        range: &str,
+        current: &[Vulnerability],
+        options: &AnalysisOptions,
+    ) -> Result<Vec<RangeVulnerability>> {
+        let scheme = VersionSchemeRegistry::global().get_or_default("npm");
+        let policy = options.prerelease_policy("npm");
+        Ok(self
+            .database(options)?
+            .get_all_for_package(package, "npm")
+            .await?
+            .into_iter()
+            .filter(|vuln| !current.iter().any(|found| found.id == vuln.id))
+            .filter_map(|vulnerability| {
+                let version =
+                    vulnerability.affected_version_in_range(range, scheme.as_ref(), policy)?;
+                Some(RangeVulnerability {
+                    version,
+                    vulnerability,
+                })
+            })
+            .collect())
+    }
+
+    /// Number of dependencies `package.json` declares across all dependency fields
+    pub(crate) fn declared_dependency_count(package_json: &Value) -> usize {
+        DEPENDENCY_FIELDS
+            .iter()
+            .filter_map(|(field, _)| package_json.get(*field).and_then(|v| v.as_object()))
+            .map(|deps| deps.len())
+            .sum()
+    }
+
+    /// Read the lockfile the package would be installed from
+    ///
+    /// Tries npm's lockfiles, then yarn.lock. A missing, unreadable or
+    /// oversized lockfile pins nothing; one that does not parse is recorded
+    /// in `skipped` and the next one is tried.
+    async fn read_lockfile(
+        root: &Path,
+        package_json: &Value,
+        options: &AnalysisOptions,
+    ) -> Lockfile {
+        let mut lockfile = Lockfile::default();
+        for lock_file in LOCK_FILES {
+            let Ok(content) = tokio::fs::read_to_string(root.join(lock_file)).await else {
+                continue;
+            };
+            let parsed = if *lock_file == YARN_LOCK {
+                Self::read_yarn_lock(&content, package_json, &mut lockfile)
+            } else if options.limits.check_json(lock_file, &content).is_err() {
+                continue;
+            } else {
+                Self::read_npm_lock(&content, &mut lockfile)
+            };
+            match parsed {
+                Ok(()) => {
+                    lockfile.file = lock_file;
+                    break;
                 }
+                Err(error) => lockfile.skipped.push(PartialParse::new(*lock_file, error)),
             }
         }
+        lockfile
+    }
 
-        Ok(analysis)
+    /// Read package-lock.json or npm-shrinkwrap.json
+    ///
+    /// Reads the `packages` map of lockfile v2/v3 and falls back to the
+    /// `dependencies` map of v1, whose nesting is read into the same
+    /// `node_modules/a/node_modules/b` paths. Dependencies resolve the way
+    /// Node does, to the closest copy up the path.
+    fn read_npm_lock(content: &str, lockfile: &mut Lockfile) -> Result<()> {
+        let lock: Value = serde_json::from_str(content)?;
+        let mut entries: HashMap<String, (Option<String>, String, Vec<String>)> = HashMap::new();
+        let mut licenses: HashMap<String, String> = HashMap::new();
+        if let Some(packages) = lock.get("packages").and_then(|p| p.as_object()) {
+            for (key, entry) in packages {
+                let Some((_, name)) = key.rsplit_once("node_modules/") else {
+                    continue;
+                };
+                if let Some(resolved) = entry.get("resolved").and_then(|v| v.as_str()) {
+                    lockfile
+                        .sources
+                        .push((name.to_string(), resolved.to_string()));
+                }
+                let Some(version) = entry.get("version").and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                let dependencies = ["dependencies", "optionalDependencies", "peerDependencies"]
+                    .iter()
+                    .filter_map(|field| entry.get(*field).and_then(|d| d.as_object()))
+                    .flat_map(|deps| deps.keys().cloned())
+                    .collect();
+                // An alias records the package it installs as `name`
+                let target = entry.get("name").and_then(|v| v.as_str()).map(String::from);
+                if let Some(license) = entry.get("license").and_then(|v| v.as_str()) {
+                    licenses.insert(key.clone(), license.to_string());
+                }
+                entries.insert(key.clone(), (target, version.to_string(), dependencies));
+            }
+        } else if let Some(deps) = lock.get("dependencies").and_then(|d| d.as_object()) {
+            Self::collect_v1_entries(deps, "", &mut entries);
+            Self::collect_v1_sources(deps, &mut lockfile.sources);
+        }
+
+        let resolve = |from: &str, name: &str| {
+            let mut base = from;
+            loop {
+                let key = match base {
+                    "" => format!("node_modules/{}", name),
+                    base => format!("{}/node_modules/{}", base, name),
+                };
+                if entries.contains_key(&key) {
+                    return Some(key);
+                }
+                if base.is_empty() {
+                    return None;
+                }
+                base = base
+                    .rfind("node_modules/")
+                    .map_or("", |at| base[..at].trim_end_matches('/'));
+            }
+        };
+        for (key, (target, version, dependencies)) in &entries {
+            let name = key
+                .rsplit_once("node_modules/")
+                .map_or("", |(_, name)| name);
+            // Nested copies live under `node_modules/a/node_modules/b`
+            if key.matches("node_modules/").count() == 1 {
+                lockfile.versions.insert(name.to_string(), version.clone());
+                lockfile.roots.insert(name.to_string(), key.clone());
+            }
+            lockfile.packages.insert(
+                key.clone(),
+                LockedPackage {
+                    name: target.clone().unwrap_or_else(|| name.to_string()),
+                    version: version.clone(),
+                    dependencies: dependencies
+                        .iter()
+                        .filter_map(|dependency| resolve(key, dependency))
+                        .collect(),
+                    license: licenses.get(key).cloned(),
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Read the versions yarn.lock resolves the declared dependencies to
+    fn read_yarn_lock(content: &str, package_json: &Value, lockfile: &mut Lockfile) -> Result<()> {
+        let entries = yarn_lock::parse(content)?;
+        let id = |entry: &yarn_lock::YarnEntry| format!("{}@{}", entry.name(), entry.version);
+        let by_spec: HashMap<&str, String> = entries
+            .iter()
+            .flat_map(|entry| {
+                entry
+                    .specs
+                    .iter()
+                    .map(move |spec| (spec.as_str(), id(entry)))
+            })
+            .collect();
+        let resolve = |name: &str, range: &str| {
+            by_spec
+                .get(format!("{}@{}", name, range).as_str())
+                .or_else(|| by_spec.get(format!("{}@npm:{}", name, range).as_str()))
+                .cloned()
+        };
+
+        let declared = DEPENDENCY_FIELDS
+            .iter()
+            .filter_map(|(field, _)| package_json.get(*field).and_then(|v| v.as_object()))
+            .flatten()
+            .filter_map(|(name, spec)| Some((name, spec.as_str()?)));
+        for (name, range) in declared {
+            if let Some(entry) = entries.iter().find(|entry| entry.satisfies(name, range)) {
+                lockfile
+                    .versions
+                    .insert(name.clone(), entry.version.clone());
+                lockfile.roots.insert(name.clone(), id(entry));
+            }
+        }
+        for entry in &entries {
+            lockfile.packages.insert(
+                id(entry),
+                LockedPackage {
+                    name: entry.name().to_string(),
+                    version: entry.version.clone(),
+                    dependencies: entry
+                        .dependencies
+                        .iter()
+                        .filter_map(|(name, range)| resolve(name, range))
+                        .collect(),
+                    license: None,
+                },
+            );
+        }
+        lockfile.sources.extend(
+            entries
+                .iter()
+                .filter_map(|entry| Some((entry.name().to_string(), entry.resolved.clone()?))),
+        );
+        Ok(())
+    }
+
+    /// Entries of a v1 lockfile `dependencies` map, keyed by the path they
+    /// are installed at
+    ///
+    /// Aliases lock `npm:target@version` as their version.
+    fn collect_v1_entries(
+        deps: &serde_json::Map<String, Value>,
+        parent: &str,
+        entries: &mut HashMap<String, (Option<String>, String, Vec<String>)>,
+    ) {
+        for (name, entry) in deps {
+            let key = match parent {
+                "" => format!("node_modules/{}", name),
+                parent => format!("{}/node_modules/{}", parent, name),
+            };
+            if let Some(version) = entry.get("version").and_then(|v| v.as_str()) {
+                let (target, version) = match DependencyAlias::parse(name, version) {
+                    Some(alias) => (Some(alias.target), alias.range),
+                    None => (None, version.to_string()),
+                };
+                let requires = entry
+                    .get("requires")
+                    .and_then(|r| r.as_object())
+                    .map(|requires| requires.keys().cloned().collect())
+                    .unwrap_or_default();
+                entries.insert(key.clone(), (target, version, requires));
+            }
+            if let Some(nested) = entry.get("dependencies").and_then(|d| d.as_object()) {
+                Self::collect_v1_entries(nested, &key, entries);
+            }
+        }
+    }
+
+    /// Resolved URLs of a v1 lockfile `dependencies` map, including nested ones
+    fn collect_v1_sources(
+        deps: &serde_json::Map<String, Value>,
+        sources: &mut Vec<(String, String)>,
+    ) {
+        for (name, entry) in deps {
+            if let Some(resolved) = entry.get("resolved").and_then(|v| v.as_str()) {
+                sources.push((name.clone(), resolved.to_string()));
+            }
+            if let Some(nested) = entry.get("dependencies").and_then(|d| d.as_object()) {
+                Self::collect_v1_sources(nested, sources);
+            }
+        }
+    }
+
+    /// Dependencies declared or resolved with a URL that is fetched without
+    /// TLS or from a host the source policy does not allow, internal
+    /// dependencies resolved from a public registry, declared dependencies
+    /// the lockfile does not match, and lockfiles that could not be read
+    fn source_findings(
+        package_json: &Value,
+        lockfile: &Lockfile,
+        options: &AnalysisOptions,
+    ) -> Vec<Finding> {
+        let declared: Vec<(&str, &str)> = DEPENDENCY_FIELDS
+            .iter()
+            .filter_map(|(field, _)| package_json.get(*field).and_then(|v| v.as_object()))
+            .flatten()
+            .filter_map(|(name, spec)| Some((name.as_str(), spec.as_str()?)))
+            .collect();
+        let mut findings = transport::insecure_transport_findings(declared.clone(), "package.json");
+        // A URL declared in package.json is resolved to itself; report it once
+        let resolved = lockfile
+            .sources
+            .iter()
+            .map(|(name, url)| (name.as_str(), url.as_str()))
+            .filter(|source| !declared.contains(source))
+            .collect::<Vec<_>>();
+        findings.extend(transport::insecure_transport_findings(
+            resolved.clone(),
+            lockfile.file,
+        ));
+        findings.extend(Self::unmatched_findings(&declared, lockfile));
+        findings.extend(options.source_policy.findings(declared, "package.json"));
+        findings.extend(
+            options
+                .source_policy
+                .findings(resolved.clone(), lockfile.file),
+        );
+        findings.extend(resolved.iter().filter_map(|(name, url)| {
+            DependencyConfusionRisk::from_source(name, url, lockfile.file, &options.internal_scopes)
+                .map(|risk| risk.to_finding())
+        }));
+        // Recomputed with the lockfile on every scan, so not a file scan finding
+        findings.extend(lockfile.skipped.iter().map(|skipped| {
+            skipped
+                .to_finding()
+                .with_scan_reason(ScanReason::DependencyAnalysis)
+        }));
+        findings
+    }
+
+    /// Declared dependencies the lockfile has no entry for, or locks at a
+    /// version outside the declared range
+    ///
+    /// npm installs these from the registry instead of the lockfile, or
+    /// rewrites the lockfile, so what was reviewed is not what gets
+    /// installed. Specs that are not semver ranges, such as URLs and tags,
+    /// are only checked for an entry.
+    fn unmatched_findings(declared: &[(&str, &str)], lockfile: &Lockfile) -> Vec<Finding> {
+        if lockfile.file.is_empty() {
+            return vec![];
+        }
+        let scheme = VersionSchemeRegistry::global().get_or_default("npm");
+        declared
+            .iter()
+            .filter_map(|&(name, spec)| {
+                let description = match lockfile.versions.get(name) {
+                    None => format!(
+                        "{} declares {}@{} but has no entry for it",
+                        lockfile.file, name, spec
+                    ),
+                    Some(version) => {
+                        let range = DependencyAlias::parse(name, spec)
+                            .map_or(spec.to_string(), |alias| alias.range);
+                        if scheme.satisfies(version, &range).unwrap_or(true) {
+                            return None;
+                        }
+                        format!(
+                            "{} locks {} at {}, outside the declared range {}",
+                            lockfile.file, name, version, range
+                        )
+                    }
+                };
+                Some(
+                    Finding::new(
+                        FindingType::UnpinnedDependency,
+                        RiskLevel::Medium,
+                        format!("Dependency '{}' does not match {}", name, lockfile.file),
+                        description,
+                    )
+                    .with_evidence(format!("package.json: {}@{}", name, spec))
+                    .with_affected_component(name.to_string())
+                    .with_scan_reason(ScanReason::DependencyAnalysis)
+                    .with_rule_id(rules::NPM_LOCKFILE_UNMATCHED),
+                )
+            })
+            .collect()
+    }
+
+    /// Digest of the options and every package file except the lockfiles
+    fn input_digest(root: &Path, options: &AnalysisOptions) -> Result<String> {
+        digest::tree_digest(root, LOCK_FILES, &serde_json::to_vec(options)?)
+    }
+
+    /// Re-analyze only the dependencies whose locked version changed
+    ///
+    /// When nothing but the lockfile changed since `previous` was produced
+    /// with the same options, file scans and typosquatting results are reused
+    /// and only new or re-versioned dependencies are looked up again. Any
+    /// other change falls back to a full scan. The result matches a full scan
+    /// as long as the vulnerability database has not changed in between.
+    pub async fn rescan_lockfile(
+        &self,
+        path: &Path,
+        previous: &NpmAnalysisResult,
+        options: &AnalysisOptions,
+    ) -> Result<(NpmAnalysisResult, RescanStats)> {
+        let digest = Self::input_digest(path, options)?;
+        if previous.input_digest.as_deref() != Some(digest.as_str()) {
+            let result = self.analyze_with_options(path, options).await?;
+            let stats = RescanStats {
+                full_scan: true,
+                reanalyzed: result.dependency_analysis.dependency_tree.len(),
+                reused: 0,
+            };
+            return Ok((result, stats));
+        }
+
+        let content = tokio::fs::read_to_string(path.join("package.json"))
+            .await
+            .context("Failed to read package.json")?;
+        let budget = MemoryBudget::new(options.max_memory_bytes);
+        budget.charge("package.json", content.len())?;
+        let json_value: Value = serde_json::from_str(&content)?;
+        budget.charge_for("package.json", &json_value)?;
+        let lockfile = Self::read_lockfile(path, &json_value, options).await;
+        budget.charge_for(lockfile.file, &lockfile)?;
+        let (mut dependency_analysis, reused) = self
+            .resolve_dependencies(
+                &json_value,
+                &lockfile,
+                options,
+                Some(&previous.dependency_analysis),
+                &budget,
+            )
+            .await?;
+        dependency_analysis.license_findings =
+            options.license_policy.findings(&dependency_analysis);
+        if options.analyze_reachability {
+            SymbolUsage::scan(path, ScriptLanguage::JavaScript).annotate(&mut dependency_analysis);
+        }
+        let stats = RescanStats {
+            full_scan: false,
+            reanalyzed: dependency_analysis.dependency_tree.len() - reused,
+            reused,
+        };
+
+        let mut result = Self::assemble(
+            previous.package.clone(),
+            dependency_analysis,
+            Self::source_findings(&json_value, &lockfile, options),
+            FileScan::from_result(previous),
+            self.scan_context(options)?,
+            options,
+        );
+        result.input_digest = Some(digest);
+        Ok((result, stats))
+    }
+
+    /// Tool version and vulnerability database snapshot for results
+    pub(crate) fn scan_context(&self, options: &AnalysisOptions) -> Result<ScanContext> {
+        Ok(ScanContext::current(self.database(options)?.last_updated()))
+    }
+
+    /// Vulnerability database for an analysis with `options`
+    fn database(&self, options: &AnalysisOptions) -> Result<Arc<dyn VulnerabilityDatabase>> {
+        Ok(vulnerability_db::database_override(options)?.unwrap_or_else(|| self.vuln_db.clone()))
     }
 
     /// Analyze scripts for suspicious patterns
@@ -324,11 +1107,12 @@ impl NpmAnalyzer {
             shell_commands: vec![],
         };
 
-        let install_hooks = ["preinstall", "install", "postinstall", "prepare"];
-
+        // In name order, so results do not depend on hash map iteration
+        let mut scripts: Vec<_> = scripts.iter().collect();
+        scripts.sort();
         for (name, content) in scripts {
             // Check for install scripts
-            if install_hooks.contains(&name.as_str()) {
+            if INSTALL_HOOKS.contains(&name.as_str()) {
                 analysis.has_install_scripts = true;
             }
 
@@ -355,94 +1139,689 @@ impl NpmAnalyzer {
                     risk_level: "Critical".to_string(),
                 });
             }
+
+            // Check for tampering with security tooling
+            let evasion: Vec<_> = self
+                .pattern_matcher
+                .scan(content, None)
+                .into_iter()
+                .filter(|p| p.category == PatternCategory::DefenseEvasion)
+                .collect();
+            if !evasion.is_empty() {
+                let targets: Vec<_> = evasion
+                    .iter()
+                    .flat_map(|p| p.evidence.iter())
+                    .filter_map(|e| e.strip_prefix("Target: "))
+                    .collect();
+                analysis.suspicious_scripts.push(SuspiciousScript {
+                    script_name: name.clone(),
+                    reason: format!("Tampers with security tooling ({})", targets.join(", ")),
+                    risk_level: "Critical".to_string(),
+                });
+            }
+
+            // Check for autostart and scheduled execution
+            if INSTALL_HOOKS.contains(&name.as_str()) {
+                for pattern in Self::persistence_patterns(&self.pattern_matcher.scan(content, None))
+                {
+                    analysis.suspicious_scripts.push(SuspiciousScript {
+                        script_name: name.clone(),
+                        reason: format!(
+                            "Establishes persistence via {}",
+                            Self::persistence_summary(pattern)
+                        ),
+                        risk_level: "Critical".to_string(),
+                    });
+                }
+            }
         }
 
         analysis
     }
-}
 
-#[async_trait]
-impl PackageAnalyzer for NpmAnalyzer {
-    type Package = NpmPackage;
-    type Analysis = NpmAnalysisResult;
+    /// Persistence patterns that name a specific mechanism and target
+    ///
+    /// The generic `PERS_001` keyword pattern is left out since it carries no
+    /// target and matches words like "startup" in ordinary code.
+    fn persistence_patterns(patterns: &[MaliciousPattern]) -> Vec<&MaliciousPattern> {
+        patterns
+            .iter()
+            .filter(|p| p.category == PatternCategory::Persistence)
+            .filter(|p| !p.attack_techniques.is_empty())
+            .collect()
+    }
 
-    async fn analyze(&self, path: &Path) -> Result<Self::Analysis> {
-        let package_json_path = if path.is_dir() {
-            path.join("package.json")
-        } else {
-            // TODO: Handle .tgz archives
-            return Err(anyhow::anyhow!("Archive extraction not yet implemented"));
+    /// Exfiltration patterns that tie what is read to where it is sent
+    ///
+    /// The generic `EXFIL_001` read is left out since configuration code
+    /// reads the environment too.
+    fn exfiltration_patterns(patterns: &[MaliciousPattern]) -> Vec<&MaliciousPattern> {
+        patterns
+            .iter()
+            .filter(|p| p.category == PatternCategory::DataExfiltration)
+            .filter(|p| !p.attack_techniques.is_empty())
+            .collect()
+    }
+
+    /// Mechanism and targets of a persistence pattern, e.g. `systemd unit persistence (foo.service)`
+    fn persistence_summary(pattern: &MaliciousPattern) -> String {
+        let targets: Vec<_> = pattern
+            .evidence
+            .iter()
+            .filter_map(|e| e.strip_prefix("Target: "))
+            .collect();
+        format!("{} ({})", pattern.pattern_name, targets.join(", "))
+    }
+
+    /// An install command together with the local scripts it runs
+    async fn install_script_source(&self, root: &Path, command: &str) -> InstallScriptSource {
+        let mut source = InstallScriptSource {
+            text: command.to_string(),
+            scripts: Vec::new(),
         };
+        let Ok(root) = root.canonicalize() else {
+            return source;
+        };
+        for file in SCRIPT_INVOCATION
+            .captures_iter(command)
+            .filter_map(|caps| caps.get(1))
+        {
+            // Only follow files inside the package
+            let Ok(script) = root.join(file.as_str()).canonicalize() else {
+                continue;
+            };
+            if !script.starts_with(&root) {
+                continue;
+            }
+            if let Ok(content) = tokio::fs::read_to_string(&script).await {
+                source.text.push('\n');
+                source.scripts.push((
+                    file.as_str().trim_start_matches("./").to_string(),
+                    source.text.len(),
+                ));
+                source.text.push_str(&content);
+            }
+        }
+        source
+    }
 
-        let content = tokio::fs::read_to_string(&package_json_path)
-            .await
-            .context("Failed to read package.json")?;
+    /// Report bundled binaries, scaled by how well their provenance is explained
+    fn binary_findings(
+        &self,
+        binaries: &[BundledBinary],
+        scripts: &HashMap<String, String>,
+    ) -> Vec<Finding> {
+        let mut findings = Vec::new();
 
-        let package = self.parse_package_json(&content).await?;
-        let json_value: Value = serde_json::from_str(&content)?;
-        let dependency_analysis = self.analyze_dependencies(&json_value).await?;
+        for binary in binaries {
+            let run_on_install = !binary.referenced_by_scripts.is_empty();
+            let mut severity = match (binary.has_build_config, run_on_install) {
+                (true, _) => RiskLevel::Low,
+                (false, false) => RiskLevel::Medium,
+                (false, true) => RiskLevel::High,
+            };
+            let dangerous = binary.dangerous_imports();
+            if !dangerous.is_empty() {
+                severity = match severity {
+                    RiskLevel::Low => RiskLevel::Medium,
+                    RiskLevel::Medium => RiskLevel::High,
+                    _ => RiskLevel::Critical,
+                };
+            }
 
+            let (finding_type, rule_id, label) = match binary.kind {
+                BinaryKind::Wasm => (
+                    FindingType::BundledWasm,
+                    rules::NPM_BUNDLED_WASM,
+                    "WebAssembly module",
+                ),
+                _ => (
+                    FindingType::PrebuiltBinary,
+                    rules::NPM_PREBUILT_BINARY,
+                    "prebuilt native binary",
+                ),
+            };
+            let provenance = if binary.has_build_config {
+                "build configuration or source ships alongside it"
+            } else {
+                "no source or build configuration explains where it came from"
+            };
+            let mut finding = Finding::new(
+                finding_type,
+                severity,
+                format!("Bundled {} '{}'", label, binary.path),
+                format!(
+                    "Package ships a {} ({} bytes) that source scanning cannot inspect; {}",
+                    label, binary.size, provenance
+                ),
+            )
+            .with_affected_component(binary.path.clone())
+            .with_scan_reason(ScanReason::BinaryInventory)
+            .with_rule_id(rule_id);
+            for hook in &binary.referenced_by_scripts {
+                finding = finding.with_evidence(format!("Referenced by {} script", hook));
+            }
+            for import in dangerous {
+                finding =
+                    finding.with_evidence(format!("Imports {}.{}", import.module, import.name));
+            }
+            findings.push(finding);
+        }
+
+        // Install scripts that download binaries not shipped in the package
+        let mut hooks: Vec<_> = scripts
+            .iter()
+            .filter(|(name, _)| INSTALL_HOOKS.contains(&name.as_str()))
+            .collect();
+        hooks.sort();
+        for (hook, command) in hooks {
+            let downloads = ["curl", "wget", "http://", "https://"]
+                .iter()
+                .any(|marker| command.contains(marker));
+            if !downloads {
+                continue;
+            }
+            for reference in BINARY_REFERENCE.captures_iter(command) {
+                let name = reference[1].rsplit('/').next().unwrap_or_default();
+                if binaries.iter().any(|b| b.path.ends_with(name)) {
+                    continue;
+                }
+                let finding_type = if name.ends_with(".wasm") {
+                    FindingType::BundledWasm
+                } else {
+                    FindingType::PrebuiltBinary
+                };
+                findings.push(
+                    Finding::new(
+                        finding_type,
+                        RiskLevel::High,
+                        format!("{} script fetches binary '{}'", hook, name),
+                        "An install script downloads a binary at install time, so its contents are never part of the published package".to_string(),
+                    )
+                    .with_evidence(command.clone())
+                    .with_affected_component(name.to_string())
+                    .with_scan_reason(ScanReason::BinaryInventory)
+                    .with_rule_id(rules::NPM_INSTALL_FETCHES_BINARY),
+                );
+            }
+        }
+
+        findings
+    }
+    /// Scan scripts, sources, binaries and metadata; everything that does not
+    /// depend on resolved dependency versions
+    async fn scan_files(
+        &self,
+        path: &Path,
+        package: &mut NpmPackage,
+        content: &str,
+        options: &AnalysisOptions,
+    ) -> Result<FileScan> {
         // Analyze scripts
         let scripts_analysis = self.analyze_scripts(&package.scripts);
 
         // Check for malicious patterns
-        let malicious_patterns = self.pattern_matcher.scan(&content, Some("package.json"));
+        let mut malicious_patterns = if options.scan_malicious_patterns {
+            let mut patterns = self.pattern_matcher.scan(content, Some("package.json"));
+            for pattern in self
+                .pattern_matcher
+                .scan_encoded(content, Some("package.json"))
+            {
+                pattern.merge_into(&mut patterns);
+            }
+            patterns
+        } else {
+            vec![]
+        };
 
-        // Check typosquatting
-        let typosquatting_risk = if self.typo_detector.is_typosquatting(&package.metadata.name) {
+        // Check typosquatting, scope confusion first since it names the exact target
+        let typo_detector = if options.detect_typosquatting {
+            self.typo_detector.for_options(options)?
+        } else {
+            Cow::Borrowed(&self.typo_detector)
+        };
+        let typosquatting_risk = if !options.detect_typosquatting {
+            None
+        } else if let Some((canonical, reason)) = typo_detector
+            .scope_confusion(&package.metadata.name)
+            .filter(|_| SCOPE_CONFUSION_CONFIDENCE >= options.typosquatting_min_confidence)
+        {
             Some(TyposquattingRisk {
                 is_likely_typosquatting: true,
-                similar_packages: self.typo_detector.find_similar(&package.metadata.name),
-                confidence: 0.8,
+                similar_packages: vec![SimilarPackage::new(
+                    &package.metadata.name,
+                    &canonical,
+                    SCOPE_CONFUSION_CONFIDENCE,
+                    vec![TyposquattingTechnique::ScopeConfusion],
+                )],
+                confidence: SCOPE_CONFUSION_CONFIDENCE,
+                reason,
             })
         } else {
-            None
+            let similar = typo_detector.similar_within(
+                &package.metadata.name,
+                options.typosquatting_max_distance,
+                options.typosquatting_min_confidence,
+            );
+            let confidence = similar.first().map(|best| best.confidence);
+            confidence.map(|confidence| TyposquattingRisk {
+                is_likely_typosquatting: true,
+                similar_packages: similar,
+                confidence,
+                reason: TyposquattingReason::SimilarName,
+            })
         };
 
-        // Collect all vulnerabilities
+        // Opaque WebAssembly and native binaries; imports are only inspected when
+        // pattern scanning is enabled
+        let install_scripts: Vec<(&str, &str)> = package
+            .scripts
+            .iter()
+            .filter(|(name, _)| INSTALL_HOOKS.contains(&name.as_str()))
+            .map(|(name, command)| (name.as_str(), command.as_str()))
+            .collect();
+        let binary::Inventory {
+            binaries: bundled_binaries,
+            unreadable,
+        } = binary::inventory(path, &install_scripts, options.scan_malicious_patterns);
+        let mut findings = self.binary_findings(&bundled_binaries, &package.scripts);
+        findings.extend(unreadable.iter().map(|skipped| {
+            skipped
+                .to_finding()
+                .with_scan_reason(ScanReason::BinaryInventory)
+        }));
+
+        // Random-looking names, more telling when the package also runs at install
+        let first_hook = install_scripts.iter().map(|(hook, _)| *hook).min();
+        findings.extend(
+            self.name_detector
+                .check(&package.metadata, first_hook, options),
+        );
+
+        // Install scripts that check where they run before acting, only act on
+        // some platforms, register themselves to run again at login, boot or
+        // on a schedule, or send secrets away
+        if options.scan_malicious_patterns {
+            let mut hooks = install_scripts.clone();
+            hooks.sort();
+            let mut hook_sources = Vec::new();
+            for (hook, command) in hooks {
+                let script_source = self.install_script_source(path, command).await;
+                let mut scanned = self.pattern_matcher.scan(&script_source.text, None);
+                for pattern in &mut scanned {
+                    script_source.locate(pattern);
+                }
+
+                // Payloads kept encoded until the script runs, whether or not
+                // they are decoded where the scan can see it
+                for mut pattern in self.pattern_matcher.scan_encoded(&script_source.text, None) {
+                    script_source.locate(&mut pattern);
+                    pattern.evidence.insert(0, format!("In {} script", hook));
+                    pattern.merge_into(&mut malicious_patterns);
+                }
+                let source = script_source.text;
+                let specific = Self::persistence_patterns(&scanned)
+                    .into_iter()
+                    .chain(Self::exfiltration_patterns(&scanned));
+                for pattern in specific {
+                    match malicious_patterns
+                        .iter_mut()
+                        .find(|existing| existing.pattern_id == pattern.pattern_id)
+                    {
+                        Some(existing) => {
+                            for evidence in &pattern.evidence {
+                                if !existing.evidence.contains(evidence) {
+                                    existing.evidence.push(evidence.clone());
+                                }
+                            }
+                            for location in &pattern.locations {
+                                if !existing.locations.contains(location) {
+                                    existing.locations.push(location.clone());
+                                }
+                            }
+                        }
+                        None => {
+                            let mut pattern = pattern.clone();
+                            pattern.evidence.insert(0, format!("In {} script", hook));
+                            malicious_patterns.push(pattern);
+                        }
+                    }
+                }
+                if let Some(finding) = EnvironmentFingerprint::detect(&source)
+                    .and_then(|fingerprint| fingerprint.to_finding_with(hook, options))
+                {
+                    findings.push(finding);
+                }
+                if let Some(delayed) = DelayedExecution::detect(&source) {
+                    findings.push(delayed.to_finding(hook));
+                }
+                if let Some(gated) = PlatformTargetedPayload::detect(&source) {
+                    findings.push(gated.to_finding(hook));
+                }
+                if options.detect_registry_exfiltration {
+                    findings.extend(
+                        RegistryExfiltration::detect(&source)
+                            .map(|exfiltration| exfiltration.to_finding(hook)),
+                    );
+                }
+
+                // Payloads handed to another language's interpreter, which
+                // are scanned here since nothing else looks at them
+                if let Some(cross) =
+                    CrossLanguageExecution::detect(&source, ScriptLanguage::JavaScript)
+                {
+                    for pattern in cross.scan_foreign_sources(path, &self.pattern_matcher) {
+                        match malicious_patterns
+                            .iter_mut()
+                            .find(|existing| existing.pattern_id == pattern.pattern_id)
+                        {
+                            Some(existing) => {
+                                existing.evidence.extend(pattern.evidence);
+                                existing.locations.extend(pattern.locations);
+                            }
+                            None => malicious_patterns.push(pattern),
+                        }
+                    }
+                    findings.push(cross.to_finding(hook));
+                }
+                hook_sources.push((hook, source));
+            }
+
+            // Code doing what the package's stated purpose has no need for
+            let hook_sources: Vec<(&str, &str)> = hook_sources
+                .iter()
+                .map(|(hook, source)| (*hook, source.as_str()))
+                .collect();
+            if let Some(mismatch) = PurposeMismatch::check(&package.metadata, path, &hook_sources) {
+                findings.push(mismatch.to_finding(&package.metadata.name));
+            }
+
+            // Patches to the module loader, which run inside every later require
+            findings.extend(
+                ImportHijack::scan(path, ScriptLanguage::JavaScript)
+                    .and_then(|hijack| hijack.to_finding(&package.metadata.name, options)),
+            );
+        }
+
+        malicious_patterns.extend(custom_rules::scan_package(path, options)?);
+
+        // Repository link that is dead or belongs to another project
+        if options.validate_repository && options.network_allowed() {
+            if let Some(declared) = &package.metadata.repository {
+                let validation = self
+                    .repository_validator
+                    .validate(&package.metadata.name, declared)
+                    .await;
+                findings.extend(validation.to_finding(&package.metadata.name));
+                package.repository_validation = Some(validation);
+            }
+        }
+
+        // Attested build provenance; a claim that does not verify is a finding
+        if options.verify_provenance && options.network_allowed() {
+            let claims_provenance = serde_json::from_str::<Value>(content)
+                .ok()
+                .and_then(|json| json["publishConfig"]["provenance"].as_bool())
+                .unwrap_or(false);
+            let provenance = self
+                .provenance_verifier
+                .verify_npm(
+                    &package.metadata.name,
+                    &package.metadata.version,
+                    package.metadata.repository.as_deref(),
+                    claims_provenance,
+                )
+                .await;
+            findings.extend(provenance.to_finding(&package.metadata.name));
+            package.provenance = Some(provenance);
+        }
+
+        // Internal dependencies whose names are taken on the public registry
+        if !options.internal_scopes.is_empty() && options.network_allowed() {
+            let json: Value = serde_json::from_str(content)?;
+            let mut declared: Vec<&str> = DEPENDENCY_FIELDS
+                .iter()
+                .filter_map(|(field, _)| json.get(*field).and_then(|v| v.as_object()))
+                .flat_map(|deps| deps.keys().map(String::as_str))
+                .collect();
+            declared.sort_unstable();
+            declared.dedup();
+            for name in declared {
+                if let Some(risk) = self
+                    .public_registry
+                    .check(name, &options.internal_scopes)
+                    .await
+                {
+                    findings.push(risk.to_finding());
+                }
+            }
+        }
+
+        // Every install hook runs arbitrary code, suspicious or not
+        if options.flag_install_scripts {
+            let mut hooks = install_scripts.clone();
+            hooks.sort();
+            for (hook, command) in hooks {
+                findings.push(
+                    Finding::new(
+                        FindingType::SuspiciousActivity,
+                        RiskLevel::Low,
+                        format!("Package runs a {} script", hook),
+                        "Install scripts run arbitrary code with the installing user's permissions",
+                    )
+                    .with_evidence(command.to_string())
+                    .with_affected_component(hook.to_string())
+                    .with_scan_reason(ScanReason::Manifest)
+                    .with_rule_id(rules::NPM_INSTALL_SCRIPT),
+                );
+            }
+        }
+
+        let mut quality_metrics = QualityMetrics::assess(
+            path,
+            &package.metadata,
+            Self::declares_tests(&package.scripts),
+            options.now(),
+        );
+        if let Some(validation) = &package.repository_validation {
+            quality_metrics = quality_metrics.with_repository_validation(validation);
+        }
+
+        Ok(FileScan {
+            scripts_analysis,
+            malicious_patterns,
+            typosquatting_risk,
+            bundled_binaries,
+            findings,
+            quality_metrics,
+        })
+    }
+
+    /// Whether `scripts` runs tests, other than the placeholder `npm init` writes
+    pub(crate) fn declares_tests(scripts: &HashMap<String, String>) -> bool {
+        scripts
+            .get("test")
+            .is_some_and(|test| !test.contains("no test specified"))
+    }
+
+    /// Combine dependency and file scan results into the final analysis
+    fn assemble(
+        mut package: NpmPackage,
+        dependency_analysis: DependencyAnalysis,
+        source_findings: Vec<Finding>,
+        file_scan: FileScan,
+        scan_context: ScanContext,
+        options: &AnalysisOptions,
+    ) -> NpmAnalysisResult {
+        let FileScan {
+            scripts_analysis,
+            malicious_patterns,
+            typosquatting_risk,
+            bundled_binaries,
+            findings: file_findings,
+            quality_metrics,
+        } = file_scan;
+
+        // Collect all vulnerabilities, transitive ones included
         let mut vulnerabilities = vec![];
-        for dep in &dependency_analysis.dependency_tree {
+        let mut nodes: Vec<&Dependency> = dependency_analysis.dependency_tree.iter().collect();
+        while let Some(dep) = nodes.pop() {
             vulnerabilities.extend(dep.vulnerabilities.clone());
+            nodes.extend(dep.dependencies.iter());
         }
 
         // Calculate risk assessment
-        let risk_calculator = RiskCalculator::new();
+        let risk_calculator = RiskCalculator::from_options(options);
         let supply_chain_score = if scripts_analysis.has_install_scripts {
             40.0
         } else {
             0.0
         };
 
-        let risk_score = risk_calculator.calculate(
+        // Cycles, unpinned ranges, aliases and insecure sources in the declared dependencies
+        let mut findings = if options.analyze_dependencies {
+            let mut findings =
+                DependencyGraph::from_analysis(&package.metadata.name, &dependency_analysis)
+                    .cycle_findings();
+            findings.extend(dependency_analysis.unpinned_findings());
+            findings.extend(dependency_analysis.alias_findings());
+            findings.extend(dependency_analysis.range_findings());
+            findings.extend(dependency_analysis.duplicate_findings());
+            findings.extend(source_findings);
+            findings
+        } else {
+            vec![]
+        };
+        let dependency_findings = findings.len();
+        findings.extend(file_findings);
+        findings.extend(MetadataInjection::apply(
+            &mut package.metadata,
+            options.sanitize_metadata,
+        ));
+
+        let count = |reason: ScanReason| {
+            findings
+                .iter()
+                .filter(|f| f.scan_reason.as_ref() == Some(&reason))
+                .count()
+        };
+        let binary_findings = count(ScanReason::BinaryInventory);
+        let repository_findings = count(ScanReason::RepositoryCheck);
+        let provenance_findings = count(ScanReason::ProvenanceCheck);
+        let name_findings = count(ScanReason::Typosquatting);
+        let evasion_findings: Vec<&Finding> = findings
+            .iter()
+            .filter(|f| {
+                matches!(
+                    f.finding_type,
+                    FindingType::SandboxEvasion
+                        | FindingType::DelayedExecution
+                        | FindingType::PlatformTargetedPayload
+                )
+            })
+            .collect();
+        let sandbox_evasion = evasion_findings
+            .iter()
+            .any(|f| f.severity == RiskLevel::Critical);
+        let evasion_count = evasion_findings.len();
+        let cross_language_count = findings
+            .iter()
+            .filter(|f| {
+                matches!(
+                    f.finding_type,
+                    FindingType::CrossLanguageExecution
+                        | FindingType::PurposeMismatch
+                        | FindingType::RegistryExfiltration
+                        | FindingType::ImportHijack
+                )
+            })
+            .count();
+
+        let scan_phases = vec![
+            PhaseRecord::new(
+                ScanReason::DependencyAnalysis,
+                options.analyze_dependencies,
+                dependency_findings,
+            ),
+            PhaseRecord::new(ScanReason::BinaryInventory, true, binary_findings),
+            PhaseRecord::new(
+                ScanReason::RepositoryCheck,
+                options.validate_repository && options.network_allowed(),
+                repository_findings,
+            ),
+            PhaseRecord::new(
+                ScanReason::ProvenanceCheck,
+                options.verify_provenance && options.network_allowed(),
+                provenance_findings,
+            ),
+            PhaseRecord::new(
+                ScanReason::VulnerabilityCheck,
+                options.check_vulnerabilities,
+                vulnerabilities.len(),
+            ),
+            PhaseRecord::new(
+                ScanReason::MaliciousPatterns,
+                options.scan_malicious_patterns,
+                malicious_patterns.len() + evasion_count + cross_language_count,
+            ),
+            PhaseRecord::new(
+                ScanReason::Typosquatting,
+                options.detect_typosquatting,
+                usize::from(typosquatting_risk.is_some()) + name_findings,
+            ),
+        ];
+
+        let mut risk_score = risk_calculator.calculate(
             &vulnerabilities,
             &malicious_patterns,
             typosquatting_risk.is_some(),
             supply_chain_score,
             50.0, // Default maintenance score
         );
+        risk_calculator.apply_findings(&mut risk_score, &findings);
+        risk_calculator.apply_pinning(
+            &mut risk_score,
+            &PinningReport::from_analysis(&dependency_analysis, "npm"),
+        );
+        risk_calculator.apply_install_hooks(&mut risk_score, &install_hooks(&package.scripts));
+        let scoped_risk = risk_calculator.scoped_risk(
+            &risk_score,
+            &dependency_analysis,
+            &findings,
+            &options.production_dependency_types,
+        );
 
         let risk_assessment = RiskAssessment {
             risk_score: risk_score.clone(),
             summary: format!(
-                "NPM package '{}' has {} risk with {} vulnerabilities and {} malicious patterns",
+                "NPM package '{}' has {} risk with {} vulnerabilities and {} malicious patterns; {}",
                 package.metadata.name,
                 risk_score.risk_level,
                 vulnerabilities.len(),
-                malicious_patterns.len()
+                malicious_patterns.len(),
+                scoped_risk
             ),
-            detailed_findings: vec![],
-            recommendations: vec![],
+            detailed_findings: findings,
+            recommendations: dependency_analysis
+                .upgrade_recommendations_with("npm", options.recommend_prerelease_fixes),
             security_posture: crate::core::SecurityPosture {
                 vulnerabilities_present: !vulnerabilities.is_empty(),
-                malicious_code_detected: !malicious_patterns.is_empty(),
-                supply_chain_risks: scripts_analysis.has_install_scripts,
+                malicious_code_detected: !malicious_patterns.is_empty() || sandbox_evasion,
+                supply_chain_risks: scripts_analysis.has_install_scripts
+                    || bundled_binaries.iter().any(|b| !b.has_build_config),
                 actively_maintained: true, // TODO: Check actual maintenance
-                trusted_publisher: false,  // TODO: Check publisher trust
+                trusted_publisher: package
+                    .provenance
+                    .as_ref()
+                    .is_some_and(Provenance::attestation_matched),
                 security_practices_score: 50.0,
             },
+            scan_phases,
+            scoped_risk: Some(scoped_risk),
+            scan_context: Some(scan_context),
         };
 
-        Ok(NpmAnalysisResult {
+        NpmAnalysisResult {
             package,
             risk_assessment,
             dependency_analysis,
@@ -450,17 +1829,113 @@ impl PackageAnalyzer for NpmAnalyzer {
             malicious_patterns,
             scripts_analysis,
             typosquatting_risk,
-        })
+            bundled_binaries,
+            input_digest: None,
+            quality_metrics,
+        }
+    }
+
+    /// Analysis of `path` without the deadline `options.timeout_seconds` sets
+    async fn run_analysis(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<NpmAnalysisResult> {
+        // A tarball is unpacked and its canonical root analyzed in its place
+        let extracted = if path.is_dir() {
+            None
+        } else {
+            Some(npm_tarball::extract_with_options(path, options)?)
+        };
+        let root = extracted.as_ref().map(ExtractedTarball::root);
+        let path = root.as_deref().unwrap_or(path);
+        let package_json_path = path.join("package.json");
+
+        let content = tokio::fs::read_to_string(&package_json_path)
+            .await
+            .context("Failed to read package.json")?;
+
+        options.limits.check_json("package.json", &content)?;
+        let budget = MemoryBudget::new(options.max_memory_bytes);
+        budget.charge("package.json", content.len())?;
+        let mut package = parse_package_json(&content)?;
+        progress::manifest_parsed(package.metadata());
+        let json_value: Value = serde_json::from_str(&content)?;
+        budget.charge_for("package.json", &json_value)?;
+        let lockfile = Self::read_lockfile(path, &json_value, options).await;
+        budget.charge_for(lockfile.file, &lockfile)?;
+        progress::vuln_lookup_started(options);
+        let (mut dependency_analysis, _) = self
+            .resolve_dependencies(&json_value, &lockfile, options, None, &budget)
+            .await?;
+        deadline::dependencies_analyzed(&dependency_analysis);
+        dependency_analysis.license_findings =
+            options.license_policy.findings(&dependency_analysis);
+        if options.analyze_reachability {
+            SymbolUsage::scan(path, ScriptLanguage::JavaScript).annotate(&mut dependency_analysis);
+        }
+        let mut file_scan = self
+            .scan_files(path, &mut package, &content, options)
+            .await?;
+        deadline::progress(|| {
+            format!(
+                "scanned package files, {} malicious patterns found",
+                file_scan.malicious_patterns.len()
+            )
+        });
+        progress::pattern_scan_complete(&file_scan.malicious_patterns);
+        budget.charge_for("malicious patterns", &file_scan.malicious_patterns)?;
+        budget.charge_for("findings", &file_scan.findings)?;
+
+        // Only the first `max_dependencies` were analyzed
+        if let Err(exceeded) = options
+            .limits
+            .check_dependency_count("package.json", Self::declared_dependency_count(&json_value))
+        {
+            file_scan.findings.insert(0, exceeded.to_finding());
+        }
+        if let Some(layout) = extracted.as_ref().and_then(|e| e.layout.to_finding()) {
+            file_scan.findings.insert(0, layout);
+        }
+
+        let source_findings = Self::source_findings(&json_value, &lockfile, options);
+        let mut result = Self::assemble(
+            package,
+            dependency_analysis,
+            source_findings,
+            file_scan,
+            self.scan_context(options)?,
+            options,
+        );
+        result.input_digest = Some(Self::input_digest(path, options)?);
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl PackageAnalyzer for NpmAnalyzer {
+    type Package = NpmPackage;
+    type Analysis = NpmAnalysisResult;
+
+    async fn analyze(&self, path: &Path) -> Result<Self::Analysis> {
+        self.analyze_with_options(path, &AnalysisOptions::default())
+            .await
+    }
+
+    async fn analyze_with_options(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Self::Analysis> {
+        deadline::enforce(path, options, self.run_analysis(path, options)).await
     }
 
     fn can_analyze(&self, path: &Path) -> bool {
         if path.is_dir() {
             path.join("package.json").exists()
         } else {
-            path.extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| ext == "tgz" || ext == "gz")
-                .unwrap_or(false)
+            let name = path.to_string_lossy();
+            name.ends_with(".tgz") || name.ends_with(".tar.gz")
         }
     }
 
@@ -1,18 +1,43 @@
 //! Python package analyzer
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
+use std::sync::Arc;
 
+use crate::context::AnalyzerContext;
 use crate::core::{
-    AnalysisResult, Dependency, DependencyAnalysis, DependencyType, MaliciousPattern,
-    PackageAnalyzer, PackageInfo, PackageMetadata, PatternMatcher, RiskAssessment, RiskCalculator,
-    Vulnerability,
+    custom_rules, deadline, progress, purpose, rules, transport, AnalysisOptions, AnalysisResult,
+    CrossLanguageExecution, DelayedExecution, Dependency, DependencyAnalysis, DependencyGraph,
+    DependencySource, DependencyType, EnvironmentFingerprint, Finding, FindingType, ImportHijack,
+    InstallHook, MaliciousPattern, MemoryBudget, MetadataInjection, PackageAnalyzer, PackageInfo,
+    PackageMetadata, PatternMatcher, PhaseRecord, PinningReport, PlatformTargetedPayload,
+    QualityMetrics, RegistryExfiltration, RiskAssessment, RiskCalculator, RiskLevel, ScanContext,
+    ScanReason, ScriptLanguage, SymbolUsage, Vulnerability,
 };
-use crate::utils::typosquatting::TyposquattingDetector;
-use crate::vulnerability_db::VulnerabilityDatabase;
+use crate::ecosystem::Ecosystem;
+use crate::manifest::python::{analyze_setup, parse_setup_py};
+use crate::utils::generated_names::GeneratedNameDetector;
+use crate::utils::names::NameNormalization;
+use crate::utils::pep508;
+use crate::utils::python_archive::{self, DistributionKind, ExtractedDistribution};
+use crate::utils::requirements_txt::{self, Requirements};
+use crate::utils::typosquatting::{
+    SimilarPackage, TyposquattingDetector, TyposquattingTargetProvider,
+};
+use crate::vulnerability_db::{self, VulnerabilityDatabase};
+
+/// Lockfiles pinning the versions a project resolved to, in order of preference
+const LOCK_FILES: &[&str] = &["poetry.lock", "pdm.lock"];
+
+/// Labels of project URLs pointing at the home page
+const HOMEPAGE_LABELS: &[&str] = &["homepage", "home"];
+
+/// Labels of project URLs pointing at the source repository
+const REPOSITORY_LABELS: &[&str] = &["source", "source code", "repository", "code"];
 
 /// Python package information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +51,21 @@ pub struct PythonPackage {
     pub maintainer_email: Option<String>,
 }
 
+impl PythonPackage {
+    /// Package described only by `metadata`
+    fn from_metadata(metadata: PackageMetadata) -> Self {
+        Self {
+            metadata,
+            package_format: PackageFormat::Directory,
+            python_requires: None,
+            classifiers: vec![],
+            project_urls: HashMap::new(),
+            maintainer: None,
+            maintainer_email: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PackageFormat {
     Wheel,
@@ -65,6 +105,46 @@ impl PackageInfo for PythonPackage {
     }
 }
 
+/// A requirement declared in pyproject.toml or requirements.txt
+#[derive(Debug, Clone)]
+struct Requirement {
+    name: String,
+    version_spec: String,
+    dependency_type: DependencyType,
+    /// Environment marker limiting where it is installed
+    marker: Option<String>,
+    /// `--hash` digests it is pinned to
+    hashes: Vec<String>,
+    /// Where it is installed from when that is not a package index
+    source: Option<DependencySource>,
+}
+
+impl Requirement {
+    fn new(name: String, version_spec: String, dependency_type: DependencyType) -> Self {
+        Self {
+            name,
+            version_spec,
+            dependency_type,
+            marker: None,
+            hashes: vec![],
+            source: None,
+        }
+    }
+}
+
+/// `poetry.lock` or `pdm.lock`, reduced to the fields used here
+#[derive(Debug, Default, Deserialize)]
+struct PythonLock {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+}
+
 /// Python analysis result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PythonAnalysisResult {
@@ -75,6 +155,9 @@ pub struct PythonAnalysisResult {
     pub malicious_patterns: Vec<MaliciousPattern>,
     pub setup_analysis: SetupAnalysis,
     pub typosquatting_risk: Option<TyposquattingRisk>,
+    /// Signs the package is tested, documented and maintained
+    #[serde(default)]
+    pub quality_metrics: QualityMetrics,
 }
 
 impl AnalysisResult for PythonAnalysisResult {
@@ -86,6 +169,10 @@ impl AnalysisResult for PythonAnalysisResult {
         &self.risk_assessment
     }
 
+    fn risk_assessment_mut(&mut self) -> &mut RiskAssessment {
+        &mut self.risk_assessment
+    }
+
     fn dependency_analysis(&self) -> &DependencyAnalysis {
         &self.dependency_analysis
     }
@@ -98,154 +185,486 @@ impl AnalysisResult for PythonAnalysisResult {
         &self.malicious_patterns
     }
 
+    fn quality_metrics(&self) -> QualityMetrics {
+        self.quality_metrics.clone()
+    }
+
     fn to_json(&self) -> Result<serde_json::Value> {
         Ok(serde_json::to_value(self)?)
     }
-}
 
-/// Python setup.py analysis
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SetupAnalysis {
-    pub has_setup_py: bool,
-    pub has_custom_commands: bool,
-    pub dangerous_operations: Vec<String>,
-    pub external_downloads: Vec<String>,
-    pub code_execution_risk: bool,
+    fn typosquatting_risk(&self) -> Option<crate::core::TyposquattingRisk> {
+        self.typosquatting_risk
+            .as_ref()
+            .map(|risk| crate::core::TyposquattingRisk {
+                is_potential_typosquatting: risk.is_likely_typosquatting,
+                similar_packages: risk.similar_packages.clone(),
+                confidence_score: risk.confidence,
+                techniques: risk
+                    .similar_packages
+                    .first()
+                    .map(|best| best.techniques.clone())
+                    .unwrap_or_default(),
+                reason: crate::utils::typosquatting::TyposquattingReason::SimilarName,
+            })
+    }
+
+    fn install_hooks(&self) -> Vec<InstallHook> {
+        self.setup_analysis.install_hooks()
+    }
 }
 
+pub use crate::manifest::python::SetupAnalysis;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TyposquattingRisk {
     pub is_likely_typosquatting: bool,
-    pub similar_packages: Vec<String>,
+    pub similar_packages: Vec<SimilarPackage>,
     pub confidence: f32,
 }
 
 /// Python package analyzer
 pub struct PythonAnalyzer {
-    vuln_db: Box<dyn VulnerabilityDatabase>,
+    vuln_db: Arc<dyn VulnerabilityDatabase>,
     pattern_matcher: PatternMatcher,
     typo_detector: TyposquattingDetector,
+    name_detector: GeneratedNameDetector,
 }
 
 impl PythonAnalyzer {
     /// Create a new Python analyzer
     pub fn new() -> Result<Self> {
         Ok(Self {
-            vuln_db: crate::vulnerability_db::create_python_database()?,
+            vuln_db: crate::vulnerability_db::create_python_database()?.into(),
             pattern_matcher: PatternMatcher::new()?,
-            typo_detector: TyposquattingDetector::new(),
+            typo_detector: TyposquattingDetector::for_ecosystem("python"),
+            name_detector: GeneratedNameDetector::new(),
         })
     }
 
     /// Create analyzer with custom database path
     pub fn with_db_path(db_path: &Path) -> Result<Self> {
         Ok(Self {
-            vuln_db: crate::vulnerability_db::create_python_database_with_path(db_path)?,
+            vuln_db: crate::vulnerability_db::create_python_database_with_path(db_path)?.into(),
             pattern_matcher: PatternMatcher::new()?,
-            typo_detector: TyposquattingDetector::new(),
+            typo_detector: TyposquattingDetector::for_ecosystem("python"),
+            name_detector: GeneratedNameDetector::new(),
         })
     }
 
-    /// Parse setup.py or pyproject.toml
+    /// Create analyzer sharing the compiled rules and database of `context`
+    pub fn from_context(context: &AnalyzerContext) -> Self {
+        Self {
+            vuln_db: context
+                .vulnerability_db(Ecosystem::Python)
+                .expect("Python has a vulnerability database"),
+            pattern_matcher: context.pattern_matcher().clone(),
+            typo_detector: TyposquattingDetector::for_ecosystem("python"),
+            name_detector: GeneratedNameDetector::new(),
+        }
+    }
+
+    /// Look up vulnerabilities in another database, such as a loaded snapshot
+    pub fn with_vulnerability_db(mut self, db: impl Into<Arc<dyn VulnerabilityDatabase>>) -> Self {
+        self.vuln_db = db.into();
+        self
+    }
+
+    /// Vulnerability database for an analysis with `options`
+    fn database(&self, options: &AnalysisOptions) -> Result<Arc<dyn VulnerabilityDatabase>> {
+        Ok(vulnerability_db::database_override(options)?.unwrap_or_else(|| self.vuln_db.clone()))
+    }
+
+    /// Also compare package names against candidates from `provider`
+    pub fn with_typosquatting_provider(
+        mut self,
+        provider: Arc<dyn TyposquattingTargetProvider>,
+    ) -> Self {
+        self.typo_detector = self.typo_detector.with_target_provider(provider);
+        self
+    }
+
+    /// Parse pyproject.toml, setup.py or setup.cfg
+    ///
+    /// pyproject.toml describes the project when it has a `[project]` or
+    /// `[tool.poetry]` table, even with a setup.py kept for older tooling. A
+    /// version the build backend fills in is taken from setup.py or
+    /// setup.cfg, or reported as `unknown`.
     async fn parse_package_metadata(&self, path: &Path) -> Result<PythonPackage> {
-        let (metadata, format) = if path.is_dir() {
-            // Check for different Python project files
-            if path.join("setup.py").exists() {
-                let content = tokio::fs::read_to_string(path.join("setup.py")).await?;
-                (self.parse_setup_py(&content)?, PackageFormat::Directory)
-            } else if path.join("pyproject.toml").exists() {
-                let content = tokio::fs::read_to_string(path.join("pyproject.toml")).await?;
-                (
-                    self.parse_pyproject_toml(&content)?,
-                    PackageFormat::Directory,
-                )
-            } else if path.join("setup.cfg").exists() {
-                let content = tokio::fs::read_to_string(path.join("setup.cfg")).await?;
-                (self.parse_setup_cfg(&content)?, PackageFormat::Directory)
-            } else {
-                return Err(anyhow::anyhow!("No Python package files found"));
-            }
+        if !path.is_dir() {
+            return Err(anyhow::anyhow!(
+                "{} is not a Python package directory",
+                path.display()
+            ));
+        }
+
+        let pyproject = if path.join("pyproject.toml").exists() {
+            let content = tokio::fs::read_to_string(path.join("pyproject.toml")).await?;
+            Some(self.parse_pyproject_toml(&content))
         } else {
-            // TODO: Handle .whl, .tar.gz archives
-            return Err(anyhow::anyhow!("Archive extraction not yet implemented"));
+            None
+        };
+        let mut package = match pyproject {
+            Some(Ok(package)) => package,
+            // Only build settings; the metadata is in setup.py or setup.cfg
+            Some(Err(e)) => match self.parse_legacy_metadata(path).await? {
+                Some(metadata) => PythonPackage::from_metadata(metadata),
+                None => return Err(e),
+            },
+            None => self
+                .parse_legacy_metadata(path)
+                .await?
+                .map(PythonPackage::from_metadata)
+                .ok_or_else(|| anyhow::anyhow!("No Python package files found"))?,
         };
 
-        Ok(PythonPackage {
-            metadata,
-            package_format: format,
-            python_requires: None, // TODO: Extract from metadata
-            classifiers: vec![],
-            project_urls: HashMap::new(),
-            maintainer: None,
-            maintainer_email: None,
-        })
+        if package.metadata.version.is_empty() {
+            package.metadata.version = match self.parse_legacy_metadata(path).await {
+                Ok(Some(metadata)) => metadata.version,
+                _ => "unknown".to_string(),
+            };
+        }
+        Ok(package)
     }
 
-    /// Parse setup.py file
-    fn parse_setup_py(&self, content: &str) -> Result<PackageMetadata> {
-        // Simple regex-based extraction
-        let name = self.extract_setup_field(content, "name")?;
-        let version = self.extract_setup_field(content, "version")?;
+    /// Metadata from setup.py, or else setup.cfg, `None` without either
+    async fn parse_legacy_metadata(&self, path: &Path) -> Result<Option<PackageMetadata>> {
+        if path.join("setup.py").exists() {
+            let content = tokio::fs::read_to_string(path.join("setup.py")).await?;
+            Ok(Some(parse_setup_py(&content)?))
+        } else if path.join("setup.cfg").exists() {
+            let content = tokio::fs::read_to_string(path.join("setup.cfg")).await?;
+            Ok(Some(self.parse_setup_cfg(&content)?))
+        } else {
+            Ok(None)
+        }
+    }
 
-        Ok(PackageMetadata {
-            name,
-            version,
-            description: self.extract_setup_field(content, "description").ok(),
-            author: self.extract_setup_field(content, "author").ok(),
-            license: self.extract_setup_field(content, "license").ok(),
-            homepage: self.extract_setup_field(content, "url").ok(),
-            repository: None,
-            keywords: vec![], // TODO: Parse keywords list
+    /// Metadata of an unpacked wheel or sdist from its `METADATA` or `PKG-INFO`
+    ///
+    /// An sdist without `PKG-INFO` falls back to its setup.py, pyproject.toml
+    /// or setup.cfg.
+    async fn parse_distribution(
+        &self,
+        distribution: &ExtractedDistribution,
+    ) -> Result<PythonPackage> {
+        let package_format = match distribution.kind {
+            DistributionKind::Wheel => PackageFormat::Wheel,
+            DistributionKind::Sdist => PackageFormat::SourceDistribution,
+        };
+        let Some(core) = distribution.metadata()? else {
+            let mut package = self.parse_package_metadata(&distribution.root()).await?;
+            package.package_format = package_format;
+            return Ok(package);
+        };
+
+        let metadata = PackageMetadata {
+            name: core.name.clone().context("Package metadata has no Name")?,
+            version: core
+                .version
+                .clone()
+                .context("Package metadata has no Version")?,
+            description: core.summary.clone(),
+            author: core.author.clone().or_else(|| core.author_email.clone()),
+            license: core.license.clone(),
+            homepage: core
+                .home_page
+                .clone()
+                .or_else(|| project_url(&core.project_urls, HOMEPAGE_LABELS)),
+            repository: project_url(&core.project_urls, REPOSITORY_LABELS),
+            keywords: core.keywords.clone(),
             publish_date: None,
+        };
+
+        Ok(PythonPackage {
+            metadata,
+            package_format,
+            python_requires: core.requires_python,
+            classifiers: core.classifiers,
+            project_urls: core.project_urls,
+            maintainer: core.maintainer,
+            maintainer_email: core.maintainer_email,
         })
     }
 
-    /// Parse pyproject.toml file
-    fn parse_pyproject_toml(&self, content: &str) -> Result<PackageMetadata> {
-        let toml_value: toml::Value = toml::from_str(content)?;
-
-        let project = toml_value
-            .get("project")
-            .ok_or_else(|| anyhow::anyhow!("No [project] section in pyproject.toml"))?;
+    /// Specific patterns in the Python modules under `root` other than setup.py
+    ///
+    /// Generic patterns such as network access match most libraries, so only
+    /// those naming a mechanism, and encoded payloads, are kept.
+    fn bundled_patterns(&self, root: &Path) -> Vec<MaliciousPattern> {
+        let mut detected = Vec::new();
+        let modules =
+            purpose::package_files(root, |path| path.ends_with(".py") && path != "setup.py");
+        for (file, content) in modules {
+            let specific = self
+                .pattern_matcher
+                .scan(&content, Some(&file))
+                .into_iter()
+                .filter(|pattern| !pattern.attack_techniques.is_empty());
+            for pattern in specific.chain(self.pattern_matcher.scan_encoded(&content, Some(&file)))
+            {
+                pattern.merge_into(&mut detected);
+            }
+        }
+        detected
+    }
 
-        Ok(PackageMetadata {
-            name: project
-                .get("name")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| anyhow::anyhow!("Missing project name"))?
-                .to_string(),
-            version: project
-                .get("version")
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| anyhow::anyhow!("Missing project version"))?
-                .to_string(),
-            description: project
-                .get("description")
-                .and_then(|v| v.as_str())
-                .map(String::from),
-            author: None, // TODO: Parse authors array
-            license: project
-                .get("license")
-                .and_then(|v| v.as_str())
-                .map(String::from),
-            homepage: project
-                .get("homepage")
-                .and_then(|v| v.as_str())
-                .map(String::from),
-            repository: None,
-            keywords: project
-                .get("keywords")
+    /// Parse the `[project]` (PEP 621) or `[tool.poetry]` table of pyproject.toml
+    ///
+    /// The version is empty when the build backend fills it in, as with
+    /// `dynamic = ["version"]`.
+    fn parse_pyproject_toml(&self, content: &str) -> Result<PythonPackage> {
+        let toml_value: toml::Value = toml::from_str(content)?;
+        let project = toml_value.get("project");
+        let poetry = toml_value.get("tool").and_then(|tool| tool.get("poetry"));
+        let (table, is_poetry) = match (project, poetry) {
+            (Some(project), _) if project.get("name").is_some() => (project, false),
+            (_, Some(poetry)) if poetry.get("name").is_some() => (poetry, true),
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "No [project] or [tool.poetry] name in pyproject.toml"
+                ))
+            }
+        };
+        let text = |key: &str| table.get(key).and_then(|v| v.as_str()).map(String::from);
+        let list = |key: &str| -> Vec<String> {
+            table
+                .get(key)
                 .and_then(|v| v.as_array())
                 .map(|arr| {
                     arr.iter()
                         .filter_map(|v| v.as_str().map(String::from))
                         .collect()
                 })
-                .unwrap_or_default(),
+                .unwrap_or_default()
+        };
+
+        let mut project_urls: HashMap<String, String> = table
+            .get("urls")
+            .and_then(|v| v.as_table())
+            .map(|urls| {
+                urls.iter()
+                    .filter_map(|(label, url)| Some((label.clone(), url.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        if is_poetry {
+            for (key, label) in [
+                ("homepage", "Homepage"),
+                ("repository", "Repository"),
+                ("documentation", "Documentation"),
+            ] {
+                if let Some(url) = text(key) {
+                    project_urls.entry(label.to_string()).or_insert(url);
+                }
+            }
+        }
+
+        // PEP 621 authors are `{ name, email }` tables, Poetry's
+        // `Name <email>` strings
+        let authors: Vec<String> = table
+            .get("authors")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|author| match author {
+                        toml::Value::String(author) => Some(author.clone()),
+                        author => author
+                            .get("name")
+                            .or_else(|| author.get("email"))
+                            .and_then(|v| v.as_str())
+                            .map(String::from),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let license = match table.get("license") {
+            Some(toml::Value::String(license)) => Some(license.clone()),
+            Some(license) => license
+                .get("text")
+                .and_then(|v| v.as_str())
+                .map(String::from),
+            None => None,
+        };
+        let python_requires = if is_poetry {
+            table
+                .get("dependencies")
+                .and_then(|deps| deps.get("python"))
+                .map(Self::poetry_constraint)
+        } else {
+            text("requires-python")
+        };
+
+        let metadata = PackageMetadata {
+            name: text("name").unwrap_or_default(),
+            version: text("version").unwrap_or_default(),
+            description: text("description"),
+            author: (!authors.is_empty()).then(|| authors.join(", ")),
+            license,
+            homepage: project_url(&project_urls, HOMEPAGE_LABELS),
+            repository: project_url(&project_urls, REPOSITORY_LABELS),
+            keywords: list("keywords"),
             publish_date: None,
+        };
+        Ok(PythonPackage {
+            metadata,
+            package_format: PackageFormat::Directory,
+            python_requires,
+            classifiers: list("classifiers"),
+            project_urls,
+            maintainer: None,
+            maintainer_email: None,
         })
     }
 
+    /// Requirements declared in pyproject.toml
+    ///
+    /// PEP 621 `dependencies` are runtime and `optional-dependencies` optional
+    /// requirements. Poetry `dependencies` are runtime, or optional when marked
+    /// so, and `dev-dependencies` and dependency groups development ones.
+    fn pyproject_requirements(content: &str) -> Vec<Requirement> {
+        let Ok(toml_value) = toml::from_str::<toml::Value>(content) else {
+            return vec![];
+        };
+        let mut requirements = Vec::new();
+
+        if let Some(project) = toml_value.get("project") {
+            let pep508 = |list: &toml::Value, dependency_type: DependencyType| {
+                list.as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|v| v.as_str())
+                    .filter_map(pep508::parse)
+                    .map(move |parsed| Requirement {
+                        marker: parsed.marker,
+                        source: parsed.url.as_deref().map(location_source),
+                        ..Requirement::new(
+                            parsed.name,
+                            parsed.url.clone().unwrap_or(parsed.version_spec),
+                            dependency_type.clone(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            };
+            if let Some(dependencies) = project.get("dependencies") {
+                requirements.extend(pep508(dependencies, DependencyType::Runtime));
+            }
+            if let Some(extras) = project
+                .get("optional-dependencies")
+                .and_then(|v| v.as_table())
+            {
+                for list in extras.values() {
+                    requirements.extend(pep508(list, DependencyType::Optional));
+                }
+            }
+        }
+
+        if let Some(poetry) = toml_value.get("tool").and_then(|tool| tool.get("poetry")) {
+            let groups = poetry
+                .get("group")
+                .and_then(|v| v.as_table())
+                .into_iter()
+                .flat_map(|groups| groups.values())
+                .filter_map(|group| group.get("dependencies"));
+            let tables = poetry
+                .get("dependencies")
+                .map(|deps| (deps, false))
+                .into_iter()
+                .chain(poetry.get("dev-dependencies").map(|deps| (deps, true)))
+                .chain(groups.map(|deps| (deps, true)));
+            for (dependencies, dev) in tables {
+                let Some(dependencies) = dependencies.as_table() else {
+                    continue;
+                };
+                // `python` constrains the interpreter, not a package
+                for (name, constraint) in dependencies.iter().filter(|(name, _)| *name != "python")
+                {
+                    let optional = constraint
+                        .get("optional")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false);
+                    let dependency_type = if dev {
+                        DependencyType::Development
+                    } else if optional {
+                        DependencyType::Optional
+                    } else {
+                        DependencyType::Runtime
+                    };
+                    let source = Self::poetry_source(constraint);
+                    let version_spec = match &source {
+                        Some(DependencySource::Path { path }) => path.clone(),
+                        Some(source) => source.url().unwrap_or_default().to_string(),
+                        None => Self::poetry_constraint(constraint),
+                    };
+                    requirements.push(Requirement {
+                        marker: constraint
+                            .get("markers")
+                            .and_then(|v| v.as_str())
+                            .map(String::from),
+                        source,
+                        ..Requirement::new(name.clone(), version_spec, dependency_type)
+                    });
+                }
+            }
+        }
+
+        requirements
+    }
+
+    /// Source of a Poetry dependency given by `git`, `path` or `url`
+    fn poetry_source(constraint: &toml::Value) -> Option<DependencySource> {
+        let text = |key: &str| constraint.get(key).and_then(|v| v.as_str());
+        if let Some(url) = text("git") {
+            return Some(DependencySource::Git {
+                url: url.to_string(),
+                revision: text("rev")
+                    .or_else(|| text("tag"))
+                    .or_else(|| text("branch"))
+                    .map(String::from),
+            });
+        }
+        if let Some(path) = text("path") {
+            return Some(DependencySource::Path {
+                path: path.to_string(),
+            });
+        }
+        text("url").map(|url| DependencySource::Url {
+            url: url.to_string(),
+        })
+    }
+
+    /// Version constraint of a Poetry dependency: a string, a table with a
+    /// `version`, or a list of those for different environments
+    fn poetry_constraint(constraint: &toml::Value) -> String {
+        match constraint {
+            toml::Value::String(version) => version.clone(),
+            toml::Value::Array(alternatives) => alternatives
+                .first()
+                .map_or_else(|| "*".to_string(), Self::poetry_constraint),
+            table => table
+                .get("version")
+                .and_then(|v| v.as_str())
+                .unwrap_or("*")
+                .to_string(),
+        }
+    }
+
+    /// Read the first of [`LOCK_FILES`] there is, charging it to `budget`
+    ///
+    /// A lockfile that fails to parse is passed over.
+    async fn read_lockfile(path: &Path, budget: &MemoryBudget) -> Result<Option<PythonLock>> {
+        for file in LOCK_FILES {
+            let Ok(content) = tokio::fs::read_to_string(path.join(file)).await else {
+                continue;
+            };
+            budget.charge(file, content.len())?;
+            if let Ok(lock) = toml::from_str(&content) {
+                return Ok(Some(lock));
+            }
+        }
+        Ok(None)
+    }
+
     /// Parse setup.cfg file
     fn parse_setup_cfg(&self, content: &str) -> Result<PackageMetadata> {
         // Simple INI-style parsing
@@ -297,160 +716,296 @@ impl PythonAnalyzer {
         Ok(metadata)
     }
 
-    /// Extract field from setup.py using regex
-    fn extract_setup_field(&self, content: &str, field: &str) -> Result<String> {
-        let pattern = format!(r#"{}\s*=\s*["']([^"']+)["']"#, field);
-        let re = regex::Regex::new(&pattern)?;
-
-        re.captures(content)
-            .and_then(|cap| cap.get(1))
-            .map(|m| m.as_str().to_string())
-            .ok_or_else(|| anyhow::anyhow!("Field '{}' not found", field))
-    }
-
-    /// Analyze setup.py for dangerous operations
-    fn analyze_setup(&self, content: &str) -> SetupAnalysis {
-        let mut analysis = SetupAnalysis {
-            has_setup_py: true,
-            has_custom_commands: false,
-            dangerous_operations: vec![],
-            external_downloads: vec![],
-            code_execution_risk: false,
+    /// requirements.txt and the files it includes, charged to `budget`
+    fn read_requirements(path: &Path, budget: &MemoryBudget) -> Result<Requirements> {
+        let requirements = if path.join("requirements.txt").is_file() {
+            requirements_txt::read(path, "requirements.txt")?
+        } else {
+            Requirements::default()
         };
+        budget.charge("requirements.txt", requirements.bytes)?;
+        Ok(requirements)
+    }
 
-        // Check for custom commands
-        if content.contains("cmdclass") {
-            analysis.has_custom_commands = true;
-            analysis
-                .dangerous_operations
-                .push("Custom setup commands detected".to_string());
+    /// Requirements pyproject.toml declares, charged to `budget`
+    async fn read_pyproject(path: &Path, budget: &MemoryBudget) -> Result<Vec<Requirement>> {
+        if !path.join("pyproject.toml").exists() {
+            return Ok(vec![]);
         }
+        let content = tokio::fs::read_to_string(path.join("pyproject.toml")).await?;
+        budget.charge("pyproject.toml", content.len())?;
+        Ok(Self::pyproject_requirements(&content))
+    }
 
-        // Check for dangerous operations
-        let dangerous_patterns = [
-            ("subprocess", "Process execution"),
-            ("os.system", "System command execution"),
-            ("exec", "Dynamic code execution"),
-            ("eval", "Code evaluation"),
-            ("__import__", "Dynamic imports"),
-            ("urllib", "Network access"),
-            ("requests", "HTTP requests"),
-        ];
+    /// Analyze dependencies, charging the lockfile and each dependency to
+    /// `budget`
+    ///
+    /// Requirements come from `pyproject` and `requirements`. With a
+    /// Poetry or PDM lockfile they are checked at the version they resolved
+    /// to, and the rest of the lockfile is listed as transitive dependencies.
+    /// Requirements whose marker rules out `options.python_env_markers` are
+    /// left out.
+    async fn analyze_dependencies(
+        &self,
+        path: &Path,
+        pyproject: Vec<Requirement>,
+        requirements: &Requirements,
+        options: &AnalysisOptions,
+        budget: &MemoryBudget,
+    ) -> Result<DependencyAnalysis> {
+        let mut analysis = DependencyAnalysis::default();
 
-        for (pattern, description) in &dangerous_patterns {
-            if content.contains(pattern) {
-                analysis.dangerous_operations.push(description.to_string());
-                analysis.code_execution_risk = true;
+        let mut declared = pyproject;
+        declared.extend(requirements.requirements.iter().map(|line| {
+            Requirement {
+                marker: line.marker.clone(),
+                hashes: line.hashes.clone(),
+                source: line.location.as_deref().map(location_source),
+                ..Requirement::new(
+                    line.name.clone(),
+                    line.location
+                        .clone()
+                        .unwrap_or_else(|| line.version_spec.clone()),
+                    DependencyType::Runtime,
+                )
             }
+        }));
+
+        // `Flask` and `flask[async]` are the same distribution
+        let mut seen = HashSet::new();
+        declared.retain(|requirement| {
+            requirement
+                .marker
+                .as_deref()
+                .is_none_or(|marker| pep508::marker_matches(marker, &options.python_env_markers))
+                && seen.insert(NameNormalization::Pep503.normalize(&requirement.name))
+        });
+
+        let lock = Self::read_lockfile(path, budget).await?.unwrap_or_default();
+        let mut locked: HashMap<String, &LockedPackage> = lock
+            .package
+            .iter()
+            .map(|locked| (NameNormalization::Pep503.normalize(&locked.name), locked))
+            .collect();
+
+        for requirement in declared {
+            let resolved = locked
+                .remove(&NameNormalization::Pep503.normalize(&requirement.name))
+                .map(|locked| locked.version.clone());
+            // A URL or path names no release to look advisories up for
+            let vulns = match (&resolved, &requirement.source) {
+                (Some(version), _) => self.check(&requirement.name, version, options).await?,
+                (None, None) => {
+                    self.check(&requirement.name, &requirement.version_spec, options)
+                        .await?
+                }
+                (None, Some(_)) => vec![],
+            };
+            let dependency = Self::dependency(requirement, resolved, true, vulns);
+            budget.charge_for("dependencies", &dependency)?;
+            progress::dependency_resolved(&dependency.name);
+            analysis.dependency_tree.push(dependency);
         }
 
-        // Check for external downloads
-        if content.contains("urlopen") || content.contains("requests.get") {
-            analysis
-                .external_downloads
-                .push("External download detected".to_string());
+        // Locked packages nothing declares came in through other packages
+        let mut transitive: Vec<&LockedPackage> = locked.into_values().collect();
+        transitive.sort_by(|a, b| a.name.cmp(&b.name));
+        for locked in transitive {
+            let vulns = self.check(&locked.name, &locked.version, options).await?;
+            let requirement = Requirement::new(
+                locked.name.clone(),
+                locked.version.clone(),
+                DependencyType::Runtime,
+            );
+            let dependency =
+                Self::dependency(requirement, Some(locked.version.clone()), false, vulns);
+            budget.charge_for("dependencies", &dependency)?;
+            progress::dependency_resolved(&dependency.name);
+            analysis.dependency_tree.push(dependency);
         }
 
-        analysis
+        analysis.summarize();
+
+        Ok(analysis)
     }
 
-    /// Analyze dependencies
-    async fn analyze_dependencies(&self, path: &Path) -> Result<DependencyAnalysis> {
-        let mut analysis = DependencyAnalysis::default();
+    /// Dependency entry for a requirement, or a locked package as one
+    fn dependency(
+        requirement: Requirement,
+        resolved_version: Option<String>,
+        is_direct: bool,
+        vulnerabilities: Vec<Vulnerability>,
+    ) -> Dependency {
+        Dependency {
+            name: requirement.name,
+            version_spec: requirement.version_spec,
+            resolved_version,
+            is_dev: requirement.dependency_type == DependencyType::Development,
+            dependency_type: requirement.dependency_type,
+            is_direct,
+            vulnerabilities,
+            license: None,
+            dependencies: vec![],
+            alias_target: None,
+            range_vulnerabilities: vec![],
+            shadowed_declarations: vec![],
+            source: requirement.source,
+            platform: None,
+            hashes: requirement.hashes,
+            marker: requirement.marker,
+        }
+    }
+
+    /// Requirements installed editable or from a URL or path instead of an
+    /// index
+    ///
+    /// Neither has a release the index vouches for or advisories can name,
+    /// and an editable install runs from a working tree that can change after
+    /// review, so it ranks higher. Development dependencies rank lower.
+    fn non_index_findings(
+        analysis: &DependencyAnalysis,
+        requirements: &Requirements,
+    ) -> Vec<Finding> {
+        analysis
+            .dependency_tree
+            .iter()
+            .filter(|dep| dep.is_direct)
+            .filter_map(|dep| {
+                let (kind, location) = match dep.source.as_ref()? {
+                    DependencySource::Registry { .. } => return None,
+                    DependencySource::Git { url, revision } => (
+                        "version control",
+                        match revision {
+                            Some(revision) => format!("{} @ {}", url, revision),
+                            None => url.clone(),
+                        },
+                    ),
+                    DependencySource::Path { path } => ("a local path", path.clone()),
+                    DependencySource::Url { url } => ("a URL", url.clone()),
+                };
+                let line = requirements.requirements.iter().find(|line| {
+                    NameNormalization::Pep503.normalize(&line.name)
+                        == NameNormalization::Pep503.normalize(&dep.name)
+                });
+                let editable = line.is_some_and(|line| line.editable);
+                let severity = match (editable, dep.is_dev) {
+                    (_, true) => RiskLevel::Low,
+                    (true, false) => RiskLevel::High,
+                    (false, false) => RiskLevel::Medium,
+                };
+                let title = if editable {
+                    format!("Package '{}' is installed editable from {}", dep.name, kind)
+                } else {
+                    format!("Package '{}' is installed from {}", dep.name, kind)
+                };
+                let mut finding = Finding::new(
+                    FindingType::NonRegistrySource,
+                    severity,
+                    title,
+                    "The package is not an index release, so it bypasses the index's versioning and advisories, and what it installs can change without a new version",
+                )
+                .with_evidence(location)
+                .with_affected_component(dep.name.clone())
+                .with_scan_reason(ScanReason::DependencyAnalysis)
+                .with_rule_id(rules::PYTHON_NON_INDEX_SOURCE);
+                if let Some(line) = line {
+                    finding = finding.with_evidence(format!("{}:{}", line.file, line.line));
+                }
+                Some(finding)
+            })
+            .collect()
+    }
 
-        // Try to find requirements
-        let requirements = if path.join("requirements.txt").exists() {
-            tokio::fs::read_to_string(path.join("requirements.txt")).await?
-        } else if path.join("setup.py").exists() {
-            // TODO: Extract from setup.py install_requires
-            String::new()
-        } else if path.join("pyproject.toml").exists() {
-            // TODO: Extract from pyproject.toml dependencies
-            String::new()
+    /// Supply-chain score from install-time code in setup.py and runtime
+    /// requirements installed editable or from a URL or path
+    fn supply_chain_score(
+        setup_analysis: &SetupAnalysis,
+        analysis: &DependencyAnalysis,
+        requirements: &Requirements,
+    ) -> f32 {
+        let mut score: f32 = if setup_analysis.code_execution_risk {
+            50.0
         } else {
-            String::new()
+            0.0
         };
-
-        // Parse requirements
-        for line in requirements.lines() {
-            let line = line.trim();
-            if line.is_empty() || line.starts_with('#') {
+        for dep in analysis.dependency_tree.iter().filter(|dep| !dep.is_dev) {
+            if matches!(dep.source, None | Some(DependencySource::Registry { .. })) {
                 continue;
             }
-
-            // Simple parsing - split on operators
-            let (name, version_spec) = if let Some(pos) = line.find("==") {
-                (&line[..pos], &line[pos..])
-            } else if let Some(pos) = line.find(">=") {
-                (&line[..pos], &line[pos..])
-            } else if let Some(pos) = line.find("~=") {
-                (&line[..pos], &line[pos..])
-            } else {
-                (line, "*")
-            };
-
-            let vulns = self
-                .vuln_db
-                .check_package(name, version_spec, "python")
-                .await?;
-
-            let dependency = Dependency {
-                name: name.to_string(),
-                version_spec: version_spec.to_string(),
-                resolved_version: None,
-                dependency_type: DependencyType::Runtime,
-                is_direct: true,
-                is_dev: false,
-                vulnerabilities: vulns,
-                license: None,
-                dependencies: vec![],
-            };
-
-            analysis.dependency_tree.push(dependency);
-            analysis.direct_dependencies += 1;
+            let editable = requirements.requirements.iter().any(|line| {
+                line.editable
+                    && NameNormalization::Pep503.normalize(&line.name)
+                        == NameNormalization::Pep503.normalize(&dep.name)
+            });
+            score += if editable { 30.0 } else { 20.0 };
         }
+        score.min(100.0)
+    }
 
-        analysis.total_dependencies = analysis.dependency_tree.len();
-
-        // Calculate vulnerability summary
-        for dep in &analysis.dependency_tree {
-            for vuln in &dep.vulnerabilities {
-                analysis.vulnerability_summary.total_vulnerabilities += 1;
-                match vuln.severity {
-                    crate::core::VulnerabilitySeverity::Critical => {
-                        analysis.vulnerability_summary.critical_count += 1;
-                    }
-                    crate::core::VulnerabilitySeverity::High => {
-                        analysis.vulnerability_summary.high_count += 1;
-                    }
-                    crate::core::VulnerabilitySeverity::Medium => {
-                        analysis.vulnerability_summary.medium_count += 1;
-                    }
-                    crate::core::VulnerabilitySeverity::Low => {
-                        analysis.vulnerability_summary.low_count += 1;
-                    }
-                    _ => {}
-                }
-            }
+    /// Advisories for a package at `version`, a version or a specifier
+    async fn check(
+        &self,
+        name: &str,
+        version: &str,
+        options: &AnalysisOptions,
+    ) -> Result<Vec<Vulnerability>> {
+        if !options.check_vulnerabilities {
+            return Ok(vec![]);
         }
-
-        Ok(analysis)
+        self.database(options)?
+            .check_package_with_policy(name, version, "python", options.prerelease_policy("python"))
+            .await
     }
-}
 
-#[async_trait]
-impl PackageAnalyzer for PythonAnalyzer {
-    type Package = PythonPackage;
-    type Analysis = PythonAnalysisResult;
+    /// Analysis of `path` without the deadline `options.timeout_seconds` sets
+    async fn run_analysis(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<PythonAnalysisResult> {
+        // Wheels and sdists are unpacked and their package root analyzed in their place
+        let extracted = if path.is_dir() {
+            None
+        } else {
+            Some(python_archive::extract_with_options(path, options)?)
+        };
+        let root = extracted.as_ref().map(ExtractedDistribution::root);
+        let path = root.as_deref().unwrap_or(path);
 
-    async fn analyze(&self, path: &Path) -> Result<Self::Analysis> {
-        let package = self.parse_package_metadata(path).await?;
-        let dependency_analysis = self.analyze_dependencies(path).await?;
+        let budget = MemoryBudget::new(options.max_memory_bytes);
+        let mut package = match &extracted {
+            Some(distribution) => self.parse_distribution(distribution).await?,
+            None => self.parse_package_metadata(path).await?,
+        };
+        progress::manifest_parsed(package.metadata());
+        let mut requirements = Self::read_requirements(path, &budget)?;
+        let mut pyproject = Self::read_pyproject(path, &budget).await?;
+        // Only the first `max_dependencies` of each manifest are analyzed
+        let dependency_limits: Vec<Finding> = options
+            .limits
+            .truncate_dependencies("pyproject.toml", &mut pyproject)
+            .into_iter()
+            .chain(
+                options
+                    .limits
+                    .truncate_dependencies("requirements.txt", &mut requirements.requirements),
+            )
+            .collect();
+        progress::vuln_lookup_started(options);
+        let mut dependency_analysis = self
+            .analyze_dependencies(path, pyproject, &requirements, options, &budget)
+            .await?;
+        deadline::dependencies_analyzed(&dependency_analysis);
+        dependency_analysis.license_findings =
+            options.license_policy.findings(&dependency_analysis);
+        if options.analyze_reachability && path.is_dir() {
+            SymbolUsage::scan(path, ScriptLanguage::Python).annotate(&mut dependency_analysis);
+        }
 
         // Analyze setup.py if present
         let setup_analysis = if path.join("setup.py").exists() {
             let content = tokio::fs::read_to_string(path.join("setup.py")).await?;
-            self.analyze_setup(&content)
+            analyze_setup(&content)
         } else {
             SetupAnalysis {
                 has_setup_py: false,
@@ -458,6 +1013,7 @@ impl PackageAnalyzer for PythonAnalyzer {
                 dangerous_operations: vec![],
                 external_downloads: vec![],
                 code_execution_risk: false,
+                custom_commands: BTreeMap::new(),
             }
         };
 
@@ -466,14 +1022,63 @@ impl PackageAnalyzer for PythonAnalyzer {
         if path.join("setup.py").exists() {
             all_content.push_str(&tokio::fs::read_to_string(path.join("setup.py")).await?);
         }
-        let malicious_patterns = self.pattern_matcher.scan(&all_content, Some("setup.py"));
+        budget.charge("setup.py", all_content.len())?;
+        let mut malicious_patterns = if options.scan_malicious_patterns {
+            let mut patterns = self.pattern_matcher.scan(&all_content, Some("setup.py"));
+            for pattern in self
+                .pattern_matcher
+                .scan_encoded(&all_content, Some("setup.py"))
+            {
+                pattern.merge_into(&mut patterns);
+            }
+            patterns
+        } else {
+            vec![]
+        };
+
+        // setup.py handing code to another interpreter; that code is scanned too
+        let cross_language = if options.scan_malicious_patterns {
+            CrossLanguageExecution::detect(&all_content, ScriptLanguage::Python)
+        } else {
+            None
+        };
+        if let Some(cross) = &cross_language {
+            for pattern in cross.scan_foreign_sources(path, &self.pattern_matcher) {
+                match malicious_patterns
+                    .iter_mut()
+                    .find(|existing| existing.pattern_id == pattern.pattern_id)
+                {
+                    Some(existing) => existing.evidence.extend(pattern.evidence),
+                    None => malicious_patterns.push(pattern),
+                }
+            }
+        }
+        malicious_patterns.extend(custom_rules::scan_package(path, options)?);
+        // Everything a distribution ships is installed, not only setup.py
+        if extracted.is_some() && options.scan_malicious_patterns {
+            for pattern in self.bundled_patterns(path) {
+                pattern.merge_into(&mut malicious_patterns);
+            }
+        }
+        progress::pattern_scan_complete(&malicious_patterns);
 
         // Check typosquatting
-        let typosquatting_risk = if self.typo_detector.is_typosquatting(&package.metadata.name) {
-            Some(TyposquattingRisk {
+        let typo_detector = if options.detect_typosquatting {
+            self.typo_detector.for_options(options)?
+        } else {
+            Cow::Borrowed(&self.typo_detector)
+        };
+        let typosquatting_risk = if options.detect_typosquatting {
+            let similar = typo_detector.similar_within(
+                &package.metadata.name,
+                options.typosquatting_max_distance,
+                options.typosquatting_min_confidence,
+            );
+            let confidence = similar.first().map(|best| best.confidence);
+            confidence.map(|confidence| TyposquattingRisk {
                 is_likely_typosquatting: true,
-                similar_packages: self.typo_detector.find_similar(&package.metadata.name),
-                confidence: 0.8,
+                similar_packages: similar,
+                confidence,
             })
         } else {
             None
@@ -486,51 +1091,261 @@ impl PackageAnalyzer for PythonAnalyzer {
         }
 
         // Calculate risk assessment
-        let risk_calculator = RiskCalculator::new();
-        let supply_chain_score = if setup_analysis.code_execution_risk {
-            50.0
+        let risk_calculator = RiskCalculator::from_options(options);
+        let supply_chain_score =
+            Self::supply_chain_score(&setup_analysis, &dependency_analysis, &requirements);
+
+        // Cycles, unpinned ranges, and insecure or disallowed sources in the
+        // declared dependencies
+        let mut findings = if options.analyze_dependencies {
+            let mut findings = DependencyGraph::from_analysis_normalized(
+                &package.metadata.name,
+                &dependency_analysis,
+                NameNormalization::Pep503,
+            )
+            .cycle_findings();
+            findings.extend(dependency_analysis.unpinned_findings());
+            findings.extend(Self::non_index_findings(
+                &dependency_analysis,
+                &requirements,
+            ));
+            for file in &requirements.files {
+                let sources: Vec<(&str, &str)> = requirements
+                    .requirements
+                    .iter()
+                    .filter(|line| &line.file == file)
+                    .filter_map(|line| Some((line.name.as_str(), line.location.as_deref()?)))
+                    .filter(|(_, location)| location.contains("://"))
+                    .collect();
+                let indexes = requirements
+                    .indexes
+                    .iter()
+                    .filter(|index| &index.file == file)
+                    .map(|index| (index.option.as_str(), index.url.as_str()));
+                findings.extend(transport::insecure_transport_findings(
+                    sources.clone(),
+                    file,
+                ));
+                findings.extend(
+                    options
+                        .source_policy
+                        .findings(sources.into_iter().chain(indexes), file),
+                );
+            }
+            findings
         } else {
-            0.0
+            vec![]
+        };
+        findings.extend(dependency_limits);
+
+        let dependency_findings = findings.len();
+
+        // setup.py runs at install time; check whether it fingerprints its
+        // environment, waits before acting, only acts on some platforms or
+        // publishes what it collects
+        let evasion_findings: Vec<_> = if options.scan_malicious_patterns {
+            EnvironmentFingerprint::detect(&all_content)
+                .and_then(|fingerprint| fingerprint.to_finding_with("setup.py", options))
+                .into_iter()
+                .chain(
+                    DelayedExecution::detect(&all_content)
+                        .map(|delayed| delayed.to_finding("setup.py")),
+                )
+                .chain(
+                    PlatformTargetedPayload::detect(&all_content)
+                        .map(|gated| gated.to_finding("setup.py")),
+                )
+                .chain(
+                    RegistryExfiltration::detect(&all_content)
+                        .filter(|_| options.detect_registry_exfiltration)
+                        .map(|exfiltration| exfiltration.to_finding("setup.py")),
+                )
+                .collect()
+        } else {
+            vec![]
         };
+        let sandbox_evasion = evasion_findings
+            .iter()
+            .any(|f| f.severity == RiskLevel::Critical);
+        let evasion_count = evasion_findings.len();
+        findings.extend(evasion_findings);
+        let cross_language_count = usize::from(cross_language.is_some());
+        findings.extend(cross_language.map(|cross| cross.to_finding("setup.py")));
+
+        // Import hooks anywhere in the package, which run inside every later import
+        let import_hijack = if options.scan_malicious_patterns && path.is_dir() {
+            ImportHijack::scan(path, ScriptLanguage::Python)
+                .and_then(|hijack| hijack.to_finding(&package.metadata.name, options))
+        } else {
+            None
+        };
+        let import_hijack_count = usize::from(import_hijack.is_some());
+        findings.extend(import_hijack);
+
+        // Random-looking names, more telling when setup.py runs custom commands
+        let install_hook = setup_analysis.has_custom_commands.then_some("setup.py");
+        let name_finding = self
+            .name_detector
+            .check(&package.metadata, install_hook, options);
+        let name_findings = usize::from(name_finding.is_some());
+        findings.extend(name_finding);
+        findings.extend(MetadataInjection::apply(
+            &mut package.metadata,
+            options.sanitize_metadata,
+        ));
+        // Wheel entries behind a password were neither unpacked nor scanned
+        findings.extend(
+            extracted
+                .as_ref()
+                .and_then(ExtractedDistribution::encrypted_archive_finding),
+        );
 
-        let risk_score = risk_calculator.calculate(
+        let scan_phases = vec![
+            PhaseRecord::new(
+                ScanReason::DependencyAnalysis,
+                options.analyze_dependencies,
+                dependency_findings,
+            ),
+            PhaseRecord::new(
+                ScanReason::VulnerabilityCheck,
+                options.check_vulnerabilities,
+                vulnerabilities.len(),
+            ),
+            PhaseRecord::new(
+                ScanReason::MaliciousPatterns,
+                options.scan_malicious_patterns,
+                malicious_patterns.len()
+                    + evasion_count
+                    + cross_language_count
+                    + import_hijack_count,
+            ),
+            PhaseRecord::new(
+                ScanReason::Typosquatting,
+                options.detect_typosquatting,
+                usize::from(typosquatting_risk.is_some()) + name_findings,
+            ),
+        ];
+
+        let mut risk_score = risk_calculator.calculate(
             &vulnerabilities,
             &malicious_patterns,
             typosquatting_risk.is_some(),
             supply_chain_score,
             50.0, // Default maintenance score
         );
+        risk_calculator.apply_findings(&mut risk_score, &findings);
+        risk_calculator.apply_pinning(
+            &mut risk_score,
+            &PinningReport::from_analysis(&dependency_analysis, "python"),
+        );
+        risk_calculator.apply_install_hooks(&mut risk_score, &setup_analysis.install_hooks());
+        let scoped_risk = risk_calculator.scoped_risk(
+            &risk_score,
+            &dependency_analysis,
+            &findings,
+            &options.production_dependency_types,
+        );
 
         let risk_assessment = RiskAssessment {
             risk_score: risk_score.clone(),
             summary: format!(
-                "Python package '{}' has {} risk with {} vulnerabilities",
+                "Python package '{}' has {} risk with {} vulnerabilities; {}",
                 package.metadata.name,
                 risk_score.risk_level,
-                vulnerabilities.len()
+                vulnerabilities.len(),
+                scoped_risk
             ),
-            detailed_findings: vec![],
-            recommendations: vec![],
+            detailed_findings: findings,
+            recommendations: dependency_analysis
+                .upgrade_recommendations_with("python", options.recommend_prerelease_fixes),
             security_posture: crate::core::SecurityPosture {
                 vulnerabilities_present: !vulnerabilities.is_empty(),
-                malicious_code_detected: !malicious_patterns.is_empty(),
+                malicious_code_detected: !malicious_patterns.is_empty() || sandbox_evasion,
                 supply_chain_risks: setup_analysis.code_execution_risk,
                 actively_maintained: true,
                 trusted_publisher: false,
                 security_practices_score: 50.0,
             },
+            scan_phases,
+            scoped_risk: Some(scoped_risk),
+            scan_context: Some(ScanContext::current(self.database(options)?.last_updated())),
         };
 
+        let quality_metrics =
+            QualityMetrics::assess(path, package.metadata(), false, options.now());
         Ok(PythonAnalysisResult {
             package,
             risk_assessment,
             dependency_analysis,
             vulnerabilities,
             malicious_patterns,
+            quality_metrics,
             setup_analysis,
             typosquatting_risk,
         })
     }
+}
+
+/// First project URL with one of `labels`, compared case-insensitively
+fn project_url(urls: &HashMap<String, String>, labels: &[&str]) -> Option<String> {
+    urls.iter()
+        .find(|(label, _)| labels.contains(&label.to_ascii_lowercase().as_str()))
+        .map(|(_, url)| url.clone())
+}
+
+/// Where a requirement installed from `location`, a URL or path, comes from
+///
+/// `git+https://host/repo.git@v1.0#egg=name` is a git checkout of `v1.0`;
+/// other VCS URLs are treated alike.
+fn location_source(location: &str) -> DependencySource {
+    let location = location.split('#').next().unwrap_or(location);
+    let vcs = ["git+", "hg+", "svn+", "bzr+"]
+        .iter()
+        .find_map(|prefix| location.strip_prefix(prefix));
+    if let Some(url) = vcs {
+        // `@rev` after the path, not the `user@` of the host
+        let path_start = url.split_once("://").map_or(0, |(scheme, rest)| {
+            scheme.len() + 3 + rest.find('/').unwrap_or(rest.len())
+        });
+        let (url, revision) = match url[path_start..].rsplit_once('@') {
+            Some((path, revision)) => (
+                format!("{}{}", &url[..path_start], path),
+                Some(revision.to_string()),
+            ),
+            None => (url.to_string(), None),
+        };
+        return DependencySource::Git { url, revision };
+    }
+    match location.strip_prefix("file://") {
+        Some(path) => DependencySource::Path {
+            path: path.to_string(),
+        },
+        None if location.contains("://") => DependencySource::Url {
+            url: location.to_string(),
+        },
+        None => DependencySource::Path {
+            path: location.to_string(),
+        },
+    }
+}
+
+#[async_trait]
+impl PackageAnalyzer for PythonAnalyzer {
+    type Package = PythonPackage;
+    type Analysis = PythonAnalysisResult;
+
+    async fn analyze(&self, path: &Path) -> Result<Self::Analysis> {
+        self.analyze_with_options(path, &AnalysisOptions::default())
+            .await
+    }
+
+    async fn analyze_with_options(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Self::Analysis> {
+        deadline::enforce(path, options, self.run_analysis(path, options)).await
+    }
 
     fn can_analyze(&self, path: &Path) -> bool {
         if path.is_dir() {
@@ -538,10 +1353,7 @@ impl PackageAnalyzer for PythonAnalyzer {
                 || path.join("pyproject.toml").exists()
                 || path.join("setup.cfg").exists()
         } else {
-            path.extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| matches!(ext, "whl" | "egg" | "gz" | "zip"))
-                .unwrap_or(false)
+            DistributionKind::of(path).is_some()
         }
     }
 
@@ -550,6 +1362,71 @@ impl PackageAnalyzer for PythonAnalyzer {
     }
 
     fn supported_extensions(&self) -> Vec<&str> {
-        vec!["whl", "egg", "tar.gz", "zip"]
+        vec!["whl", "tar.gz"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location_source() {
+        assert_eq!(
+            location_source("git+https://git@github.com/o/tool.git@v1.0#egg=tool"),
+            DependencySource::Git {
+                url: "https://git@github.com/o/tool.git".to_string(),
+                revision: Some("v1.0".to_string()),
+            }
+        );
+        assert_eq!(
+            location_source("https://files.example.com/helper-1.0.tar.gz"),
+            DependencySource::Url {
+                url: "https://files.example.com/helper-1.0.tar.gz".to_string(),
+            }
+        );
+        assert_eq!(
+            location_source("./plugins/local"),
+            DependencySource::Path {
+                path: "./plugins/local".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_pyproject_requirements() {
+        let requirements = PythonAnalyzer::pyproject_requirements(
+            r#"
+            [tool.poetry]
+            name = "acme"
+            version = "1.0.0"
+
+            [tool.poetry.dependencies]
+            python = "^3.9"
+            requests = "^2.25"
+            uvloop = { version = ">=0.17", optional = true }
+
+            [tool.poetry.group.test.dependencies]
+            pytest = "*"
+            "#,
+        );
+        let found: Vec<(&str, &str, DependencyType)> = requirements
+            .iter()
+            .map(|r| {
+                (
+                    r.name.as_str(),
+                    r.version_spec.as_str(),
+                    r.dependency_type.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("requests", "^2.25", DependencyType::Runtime),
+                ("uvloop", ">=0.17", DependencyType::Optional),
+                ("pytest", "*", DependencyType::Development),
+            ]
+        );
     }
 }
@@ -0,0 +1,1263 @@
+//! Ruby gem analyzer
+//!
+//! Metadata comes from the project's `*.gemspec` and the resolved dependency
+//! graph from `Gemfile.lock`; without a lockfile, the gems the `Gemfile` or
+//! gemspec declare are checked unresolved. Gemspecs and Gemfiles are Ruby
+//! code, of which only the literal strings in the usual assignments and calls
+//! are read.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::context::AnalyzerContext;
+use crate::core::{
+    custom_rules, deadline, progress, transport, AnalysisOptions, AnalysisResult, Dependency,
+    DependencyAnalysis, DependencySource, DependencyType, Finding, InstallHook, MaliciousPattern,
+    MemoryBudget, PackageAnalyzer, PackageInfo, PackageMetadata, PartialParse, PatternMatcher,
+    PhaseRecord, PinningReport, QualityMetrics, RiskAssessment, RiskCalculator, ScanContext,
+    ScanReason, Vulnerability,
+};
+use crate::ecosystem::Ecosystem;
+use crate::vulnerability_db::{self, VulnerabilityDatabase};
+
+const GEMFILE: &str = "Gemfile";
+const LOCK_FILE: &str = "Gemfile.lock";
+const GEMSPEC_EXTENSION: &str = "gemspec";
+
+/// Registry Bundler installs from unless a Gemfile names another
+pub const RUBYGEMS_REGISTRY: &str = "https://rubygems.org/";
+
+/// `receiver.attribute = value` in a gemspec
+static ASSIGNMENT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^\s*\w+\.(\w+)\s*=\s*(.+?)\s*$").unwrap());
+
+/// `receiver.metadata["key"] = "value"` in a gemspec
+static METADATA_ASSIGNMENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?m)^\s*\w+\.metadata\[\s*["'](\w+)["']\s*\]\s*=\s*["']([^"']*)["']"#).unwrap()
+});
+
+/// `receiver.add_dependency "name", "requirement"` and its variants
+static DEPENDENCY_CALL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^\s*\w+\.add_(runtime_|development_)?dependency\b\s*\(?(.+?)\)?\s*$").unwrap()
+});
+
+/// `gem "name", …` in a Gemfile
+static GEM_CALL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^gem\s*\(?\s*["']([^"']+)["'](.*?)\)?$"#).unwrap());
+
+/// Block opening a Gemfile scope, such as `group :test do`
+static BLOCK_START: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(group|platforms?|source|git|github|path)\b(.*)\bdo\b").unwrap());
+
+/// Quoted string literal
+static STRING_LITERAL: Lazy<Regex> = Lazy::new(|| Regex::new(r#"["']([^"']*)["']"#).unwrap());
+
+/// Symbol literal such as `:test`
+static SYMBOL_LITERAL: Lazy<Regex> = Lazy::new(|| Regex::new(r":(\w+)").unwrap());
+
+/// Ruby project or gem information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RubyGem {
+    /// From the gemspec; an application without one is named after its directory
+    pub metadata: PackageMetadata,
+    /// Gemspec read, relative to the project root
+    pub gemspec: Option<String>,
+    pub required_ruby_version: Option<String>,
+    /// Native extension build scripts, run when the gem is installed
+    pub extensions: Vec<String>,
+    /// Gems the gemspec and `Gemfile` declare
+    pub declared_dependencies: Vec<DeclaredGem>,
+    /// Platforms `Gemfile.lock` resolves gems for
+    pub platforms: Vec<String>,
+    /// Bundler version that wrote `Gemfile.lock`
+    pub bundler_version: Option<String>,
+    pub has_lock_file: bool,
+    /// Lockfile left out because it could not be parsed
+    #[serde(default)]
+    pub skipped_files: Vec<PartialParse>,
+}
+
+impl RubyGem {
+    /// Native extension build scripts, run by `gem install`
+    pub fn install_hooks(&self) -> Vec<InstallHook> {
+        let gemspec = self.gemspec.as_deref().unwrap_or_default();
+        self.extensions
+            .iter()
+            .map(|extension| InstallHook::new("extension", extension, gemspec))
+            .collect()
+    }
+}
+
+/// A gem declared in a gemspec or `Gemfile`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeclaredGem {
+    pub name: String,
+    /// Requirements joined with `, `, `>= 0` when none are given
+    pub version_spec: String,
+    pub dependency_type: DependencyType,
+    /// Bundler groups, such as `development` and `test`
+    pub groups: Vec<String>,
+    /// Git repository, local path or other registry the gem is taken from
+    pub source: Option<DependencySource>,
+    /// File declaring the gem
+    pub declared_in: String,
+}
+
+/// A gem resolved in `Gemfile.lock`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedGem {
+    pub name: String,
+    pub version: String,
+    /// Platform of a prebuilt gem, such as `x86_64-linux`; `None` for pure Ruby
+    pub platform: Option<String>,
+    pub source: DependencySource,
+    /// Names of the gems it depends on
+    pub dependencies: Vec<String>,
+}
+
+/// The parts of `Gemfile.lock` the analysis uses
+#[derive(Debug, Default)]
+struct Lockfile {
+    gems: Vec<LockedGem>,
+    /// Gems the Gemfile declares; `!` marks those from a git or path source
+    dependencies: Vec<String>,
+    platforms: Vec<String>,
+    bundler_version: Option<String>,
+}
+
+impl PackageInfo for RubyGem {
+    fn metadata(&self) -> &PackageMetadata {
+        &self.metadata
+    }
+
+    fn package_type(&self) -> &str {
+        "rubygems"
+    }
+
+    fn custom_attributes(&self) -> HashMap<String, serde_json::Value> {
+        let mut attrs = HashMap::new();
+        attrs.insert("gemspec".to_string(), serde_json::json!(self.gemspec));
+        attrs.insert(
+            "required_ruby_version".to_string(),
+            serde_json::json!(self.required_ruby_version),
+        );
+        attrs.insert("extensions".to_string(), serde_json::json!(self.extensions));
+        attrs.insert("platforms".to_string(), serde_json::json!(self.platforms));
+        attrs.insert(
+            "bundler_version".to_string(),
+            serde_json::json!(self.bundler_version),
+        );
+        attrs.insert(
+            "has_lock_file".to_string(),
+            serde_json::json!(self.has_lock_file),
+        );
+        attrs
+    }
+}
+
+/// Ruby analysis result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RubyAnalysisResult {
+    pub package: RubyGem,
+    pub risk_assessment: RiskAssessment,
+    pub dependency_analysis: DependencyAnalysis,
+    pub vulnerabilities: Vec<Vulnerability>,
+    pub malicious_patterns: Vec<MaliciousPattern>,
+    /// Signs the package is tested, documented and maintained
+    #[serde(default)]
+    pub quality_metrics: QualityMetrics,
+}
+
+impl AnalysisResult for RubyAnalysisResult {
+    fn package_info(&self) -> &dyn PackageInfo {
+        &self.package
+    }
+
+    fn risk_assessment(&self) -> &RiskAssessment {
+        &self.risk_assessment
+    }
+
+    fn risk_assessment_mut(&mut self) -> &mut RiskAssessment {
+        &mut self.risk_assessment
+    }
+
+    fn dependency_analysis(&self) -> &DependencyAnalysis {
+        &self.dependency_analysis
+    }
+
+    fn vulnerabilities(&self) -> &[Vulnerability] {
+        &self.vulnerabilities
+    }
+
+    fn malicious_patterns(&self) -> &[MaliciousPattern] {
+        &self.malicious_patterns
+    }
+
+    fn quality_metrics(&self) -> QualityMetrics {
+        self.quality_metrics.clone()
+    }
+
+    fn install_hooks(&self) -> Vec<InstallHook> {
+        self.package.install_hooks()
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+}
+
+/// Ruby gem and Bundler project analyzer
+pub struct RubyGemAnalyzer {
+    vuln_db: Option<Arc<dyn VulnerabilityDatabase>>,
+    pattern_matcher: PatternMatcher,
+}
+
+impl RubyGemAnalyzer {
+    /// Create a new Ruby gem analyzer
+    ///
+    /// No RubyGems advisories are bundled; vulnerabilities are only looked
+    /// up once a database is set with [`Self::with_vulnerability_db`].
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            vuln_db: None,
+            pattern_matcher: PatternMatcher::new()?,
+        })
+    }
+
+    /// Create analyzer sharing the compiled rules and database of `context`
+    pub fn from_context(context: &AnalyzerContext) -> Self {
+        Self {
+            vuln_db: context.vulnerability_db(Ecosystem::Ruby),
+            pattern_matcher: context.pattern_matcher().clone(),
+        }
+    }
+
+    /// Look up vulnerabilities in a database, such as a loaded snapshot
+    pub fn with_vulnerability_db(mut self, db: impl Into<Arc<dyn VulnerabilityDatabase>>) -> Self {
+        self.vuln_db = Some(db.into());
+        self
+    }
+
+    /// Vulnerability database for an analysis with `options`, if any
+    fn database(
+        &self,
+        options: &AnalysisOptions,
+    ) -> Result<Option<Arc<dyn VulnerabilityDatabase>>> {
+        Ok(vulnerability_db::database_override(options)?.or_else(|| self.vuln_db.clone()))
+    }
+
+    /// Whether `path` is a project directory, its `Gemfile` or lockfile, or a
+    /// gemspec
+    pub(crate) fn is_project(path: &Path) -> bool {
+        if path.is_dir() {
+            path.join(GEMFILE).exists()
+                || path.join(LOCK_FILE).exists()
+                || Self::find_gemspec(path).is_some()
+        } else {
+            let name = path.file_name().and_then(|n| n.to_str());
+            matches!(name, Some(GEMFILE | LOCK_FILE))
+                || path.extension().is_some_and(|ext| ext == GEMSPEC_EXTENSION)
+        }
+    }
+
+    /// Project root for a project directory or one of its files
+    fn project_root(path: &Path) -> PathBuf {
+        if path.is_dir() {
+            path.to_path_buf()
+        } else {
+            path.parent().unwrap_or(Path::new(".")).to_path_buf()
+        }
+    }
+
+    /// Gemspec in `root`, the first by name when there are several
+    fn find_gemspec(root: &Path) -> Option<String> {
+        let mut gemspecs: Vec<String> = std::fs::read_dir(root)
+            .ok()?
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| name.ends_with(&format!(".{}", GEMSPEC_EXTENSION)))
+            .collect();
+        gemspecs.sort();
+        gemspecs.into_iter().next()
+    }
+
+    /// String literals in a Ruby expression, including `%w[…]` word arrays
+    fn strings(value: &str) -> Vec<String> {
+        let value = value.trim();
+        if let Some(words) = value
+            .strip_prefix("%w")
+            .or_else(|| value.strip_prefix("%W"))
+        {
+            return words
+                .trim_matches(|c| matches!(c, '[' | ']' | '(' | ')' | '{' | '}'))
+                .split_whitespace()
+                .map(String::from)
+                .collect();
+        }
+        STRING_LITERAL
+            .captures_iter(value)
+            .map(|caps| caps[1].to_string())
+            .collect()
+    }
+
+    /// Parse a gemspec
+    ///
+    /// Attributes set to anything but string literals, such as
+    /// `Acme::VERSION`, are left unknown.
+    fn parse_gemspec(file: &str, content: &str) -> Result<RubyGem> {
+        if !content.contains("Gem::Specification.new") {
+            anyhow::bail!("{} does not define a Gem::Specification", file);
+        }
+        let mut attributes: HashMap<&str, Vec<String>> = HashMap::new();
+        for caps in ASSIGNMENT.captures_iter(content) {
+            let (attribute, value) = (caps.get(1).unwrap(), caps.get(2).unwrap());
+            attributes
+                .entry(attribute.as_str())
+                .or_insert_with(|| Self::strings(value.as_str()));
+        }
+        let metadata_fields: HashMap<String, String> = METADATA_ASSIGNMENT
+            .captures_iter(content)
+            .map(|caps| (caps[1].to_string(), caps[2].to_string()))
+            .collect();
+        let first = |attribute: &str| {
+            attributes
+                .get(attribute)
+                .and_then(|values| values.first())
+                .cloned()
+        };
+        let all = |attribute: &str| attributes.get(attribute).cloned().unwrap_or_default();
+
+        let name = first("name").ok_or_else(|| anyhow::anyhow!("{} has no name", file))?;
+        let authors = [all("authors"), all("author")].concat();
+        let licenses = [all("licenses"), all("license")].concat();
+        let metadata = PackageMetadata {
+            name,
+            version: first("version").unwrap_or_else(|| "unknown".to_string()),
+            description: first("summary").or_else(|| first("description")),
+            author: (!authors.is_empty()).then(|| authors.join(", ")),
+            license: (!licenses.is_empty()).then(|| licenses.join(" OR ")),
+            homepage: first("homepage").or_else(|| metadata_fields.get("homepage_uri").cloned()),
+            repository: metadata_fields.get("source_code_uri").cloned(),
+            keywords: vec![],
+            publish_date: None,
+        };
+
+        let declared_dependencies = DEPENDENCY_CALL
+            .captures_iter(content)
+            .filter_map(|caps| {
+                let mut strings = Self::strings(&caps[2]).into_iter();
+                let name = strings.next()?;
+                let requirements: Vec<String> = strings.collect();
+                let dependency_type = match caps.get(1).map(|kind| kind.as_str()) {
+                    Some("development_") => DependencyType::Development,
+                    _ => DependencyType::Runtime,
+                };
+                Some(DeclaredGem {
+                    name,
+                    version_spec: Self::version_spec(requirements),
+                    dependency_type,
+                    groups: vec![],
+                    source: None,
+                    declared_in: file.to_string(),
+                })
+            })
+            .collect();
+
+        Ok(RubyGem {
+            metadata,
+            gemspec: Some(file.to_string()),
+            required_ruby_version: first("required_ruby_version"),
+            extensions: all("extensions"),
+            declared_dependencies,
+            platforms: vec![],
+            bundler_version: None,
+            has_lock_file: false,
+            skipped_files: vec![],
+        })
+    }
+
+    /// Requirements as one spec, `>= 0` (any version) when there are none
+    fn version_spec(requirements: Vec<String>) -> String {
+        if requirements.is_empty() {
+            ">= 0".to_string()
+        } else {
+            requirements.join(", ")
+        }
+    }
+
+    /// Value of a `key: value` or `:key => value` option among `args`
+    fn option<'a>(args: &'a [&'a str], key: &str) -> Option<&'a str> {
+        args.iter().find_map(|arg| {
+            arg.strip_prefix(&format!("{}:", key))
+                .or_else(|| {
+                    arg.strip_prefix(&format!(":{}", key))
+                        .and_then(|rest| rest.trim_start().strip_prefix("=>"))
+                })
+                .map(str::trim)
+        })
+    }
+
+    /// Source a `git:`, `github:`, `path:` or `source:` option names
+    fn source_option(args: &[&str]) -> Option<DependencySource> {
+        let string = |key: &str| {
+            Self::option(args, key).and_then(|value| Self::strings(value).into_iter().next())
+        };
+        let revision = string("ref")
+            .or_else(|| string("tag"))
+            .or_else(|| string("branch"));
+        if let Some(url) = string("git") {
+            return Some(DependencySource::Git { url, revision });
+        }
+        if let Some(repository) = string("github") {
+            return Some(DependencySource::Git {
+                url: format!("https://github.com/{}.git", repository),
+                revision,
+            });
+        }
+        if let Some(path) = string("path") {
+            return Some(DependencySource::Path { path });
+        }
+        string("source").map(|url| DependencySource::Registry { url })
+    }
+
+    /// Gems a `Gemfile` declares, with the groups and sources of the blocks
+    /// around them
+    fn parse_gemfile(content: &str) -> Vec<DeclaredGem> {
+        // Each open block: its groups or source, if any
+        let mut blocks: Vec<(Vec<String>, Option<DependencySource>)> = Vec::new();
+        let mut gems = Vec::new();
+        for line in content.lines() {
+            let line = line.split(" #").next().unwrap_or_default().trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(caps) = BLOCK_START.captures(line) {
+                let args = &caps[2];
+                let block = match &caps[1] {
+                    "group" => (
+                        SYMBOL_LITERAL
+                            .captures_iter(args)
+                            .map(|caps| caps[1].to_string())
+                            .collect(),
+                        None,
+                    ),
+                    "source" => (
+                        vec![],
+                        Self::strings(args)
+                            .into_iter()
+                            .next()
+                            .map(|url| DependencySource::Registry { url }),
+                    ),
+                    "git" => (
+                        vec![],
+                        Self::strings(args)
+                            .into_iter()
+                            .next()
+                            .map(|url| DependencySource::Git {
+                                url,
+                                revision: None,
+                            }),
+                    ),
+                    "path" => (
+                        vec![],
+                        Self::strings(args)
+                            .into_iter()
+                            .next()
+                            .map(|path| DependencySource::Path { path }),
+                    ),
+                    _ => (vec![], None),
+                };
+                blocks.push(block);
+                continue;
+            }
+            if line == "end" || line.starts_with("end ") {
+                blocks.pop();
+                continue;
+            }
+            let Some(caps) = GEM_CALL.captures(line) else {
+                continue;
+            };
+
+            let args: Vec<&str> = caps[2]
+                .split(',')
+                .map(str::trim)
+                .filter(|arg| !arg.is_empty())
+                .collect();
+            let requirements: Vec<String> = args
+                .iter()
+                .take_while(|arg| arg.starts_with(['"', '\'']))
+                .flat_map(|arg| Self::strings(arg))
+                .collect();
+            let mut groups: Vec<String> = blocks
+                .iter()
+                .flat_map(|(groups, _)| groups.iter().cloned())
+                .collect();
+            if let Some(value) =
+                Self::option(&args, "group").or_else(|| Self::option(&args, "groups"))
+            {
+                groups.extend(
+                    SYMBOL_LITERAL
+                        .captures_iter(value)
+                        .map(|caps| caps[1].to_string()),
+                );
+            }
+            // `groups: [:development, :test]` spans several arguments
+            if let Some(start) = args.iter().position(|arg| arg.starts_with("groups:")) {
+                for arg in args.iter().skip(start + 1) {
+                    if arg.contains(':') && !arg.starts_with(':') {
+                        break;
+                    }
+                    groups.extend(
+                        SYMBOL_LITERAL
+                            .captures_iter(arg)
+                            .map(|caps| caps[1].to_string()),
+                    );
+                }
+            }
+            groups.dedup();
+            let source = Self::source_option(&args)
+                .or_else(|| blocks.iter().rev().find_map(|(_, source)| source.clone()));
+            gems.push(DeclaredGem {
+                name: caps[1].to_string(),
+                version_spec: Self::version_spec(requirements),
+                dependency_type: Self::group_type(&groups),
+                groups,
+                source,
+                declared_in: GEMFILE.to_string(),
+            });
+        }
+        gems
+    }
+
+    /// Dependency type of gems in Bundler `groups`
+    fn group_type(groups: &[String]) -> DependencyType {
+        if groups.iter().any(|group| group == "development") {
+            DependencyType::Development
+        } else if !groups.is_empty() && groups.iter().all(|group| group == "test") {
+            DependencyType::Test
+        } else {
+            DependencyType::Runtime
+        }
+    }
+
+    /// Global `source` lines of a `Gemfile`
+    fn gemfile_sources(content: &str) -> Vec<String> {
+        content
+            .lines()
+            .map(str::trim)
+            .filter(|line| line.starts_with("source ") || line.starts_with("source("))
+            .filter(|line| !BLOCK_START.is_match(line))
+            .filter_map(|line| Self::strings(line).into_iter().next())
+            .collect()
+    }
+
+    /// Parse `Gemfile.lock`
+    ///
+    /// Gems come from the `GEM`, `GIT` and `PATH` sections, each resolved
+    /// from the section's `remote`. A gem resolved for several platforms is
+    /// listed once per platform.
+    fn parse_lockfile(content: &str) -> Result<Lockfile> {
+        let mut lockfile = Lockfile::default();
+        let mut section = "";
+        let mut remote: Option<String> = None;
+        let mut revision: Option<String> = None;
+        for (number, line) in content.lines().enumerate() {
+            let text = line.trim();
+            if text.is_empty() {
+                continue;
+            }
+            let indent = line.len() - line.trim_start().len();
+            if indent == 0 {
+                section = text;
+                remote = None;
+                revision = None;
+                continue;
+            }
+            let malformed =
+                || anyhow::anyhow!("Malformed {} entry on line {}", section, number + 1);
+            match (section, indent) {
+                ("GEM" | "GIT" | "PATH", 2) => match text.split_once(':') {
+                    Some(("remote", value)) => remote = Some(value.trim().to_string()),
+                    Some(("revision", value)) => revision = Some(value.trim().to_string()),
+                    _ => {}
+                },
+                ("GEM" | "GIT" | "PATH", 4) => {
+                    let (name, version) = text
+                        .strip_suffix(')')
+                        .and_then(|spec| spec.split_once(" ("))
+                        .ok_or_else(malformed)?;
+                    let (version, platform) = match version.split_once('-') {
+                        Some((version, platform)) => (version, Some(platform.to_string())),
+                        None => (version, None),
+                    };
+                    let remote = remote.clone().ok_or_else(malformed)?;
+                    let source = match section {
+                        "GEM" => DependencySource::Registry { url: remote },
+                        "GIT" => DependencySource::Git {
+                            url: remote,
+                            revision: revision.clone(),
+                        },
+                        _ => DependencySource::Path { path: remote },
+                    };
+                    lockfile.gems.push(LockedGem {
+                        name: name.to_string(),
+                        version: version.to_string(),
+                        platform,
+                        source,
+                        dependencies: vec![],
+                    });
+                }
+                ("GEM" | "GIT" | "PATH", 6) => {
+                    let gem = lockfile.gems.last_mut().ok_or_else(malformed)?;
+                    let name = text.split_once(" (").map_or(text, |(name, _)| name);
+                    gem.dependencies.push(name.to_string());
+                }
+                ("DEPENDENCIES", 2) => {
+                    let name = text.split_once(" (").map_or(text, |(name, _)| name);
+                    lockfile.dependencies.push(name.to_string());
+                }
+                ("PLATFORMS", 2) => lockfile.platforms.push(text.to_string()),
+                ("BUNDLED WITH", _) => lockfile.bundler_version = Some(text.to_string()),
+                _ => {}
+            }
+        }
+        Ok(lockfile)
+    }
+
+    /// Read `Gemfile.lock`, if there is one
+    async fn read_lockfile(root: &Path, package: &mut RubyGem) -> Lockfile {
+        let Ok(content) = tokio::fs::read_to_string(root.join(LOCK_FILE)).await else {
+            return Lockfile::default();
+        };
+        match Self::parse_lockfile(&content) {
+            Ok(lockfile) => {
+                package.has_lock_file = true;
+                package.platforms = lockfile.platforms.clone();
+                package.bundler_version = lockfile.bundler_version.clone();
+                lockfile
+            }
+            Err(error) => {
+                package
+                    .skipped_files
+                    .push(PartialParse::new(LOCK_FILE, error));
+                Lockfile::default()
+            }
+        }
+    }
+
+    /// Build the dependency tree from locked versions, or from the declared
+    /// gems when there is no lockfile
+    ///
+    /// Gems the lockfile lists under `DEPENDENCIES` are direct, as are those
+    /// of the project's own gem when the Gemfile loads its gemspec. Each
+    /// locked gem appears once, under the first gem reaching it
+    /// breadth-first, and each platform-specific build of a gem is its own
+    /// dependency.
+    async fn analyze_dependencies(
+        &self,
+        package: &RubyGem,
+        lockfile: &Lockfile,
+        options: &AnalysisOptions,
+    ) -> Result<DependencyAnalysis> {
+        let mut analysis = DependencyAnalysis::default();
+        let declared = |name: &str| {
+            package
+                .declared_dependencies
+                .iter()
+                .filter(|d| d.name == name)
+                .min_by_key(|d| d.dependency_type.declaration_rank())
+        };
+        if !package.has_lock_file {
+            let mut seen = HashSet::new();
+            for gem in &package.declared_dependencies {
+                if !seen.insert(gem.name.as_str()) {
+                    continue;
+                }
+                let gem = declared(&gem.name).unwrap_or(gem);
+                progress::dependency_resolved(&gem.name);
+                analysis.dependency_tree.push(Dependency {
+                    name: gem.name.clone(),
+                    version_spec: gem.version_spec.clone(),
+                    resolved_version: None,
+                    dependency_type: gem.dependency_type.clone(),
+                    is_direct: true,
+                    is_dev: gem.dependency_type == DependencyType::Development,
+                    vulnerabilities: vec![],
+                    license: None,
+                    dependencies: vec![],
+                    alias_target: None,
+                    range_vulnerabilities: vec![],
+                    shadowed_declarations: vec![],
+                    source: gem.source.clone(),
+                    platform: None,
+                    hashes: vec![],
+                    marker: None,
+                });
+            }
+            analysis.summarize();
+            return Ok(analysis);
+        }
+
+        // The project's own gem, when the Gemfile calls `gemspec`
+        let gems = &lockfile.gems;
+        let is_root = |gem: &LockedGem| {
+            gem.name == package.metadata.name && matches!(gem.source, DependencySource::Path { .. })
+        };
+        let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, gem) in gems.iter().enumerate() {
+            if !is_root(gem) {
+                by_name.entry(&gem.name).or_default().push(index);
+            }
+        }
+        let resolve = |name: &str| by_name.get(name).cloned().unwrap_or_default();
+        let edges: Vec<Vec<usize>> = gems
+            .iter()
+            .map(|gem| {
+                gem.dependencies
+                    .iter()
+                    .flat_map(|dep| resolve(dep))
+                    .collect()
+            })
+            .collect();
+
+        let mut visited = HashSet::new();
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        let roots = lockfile
+            .dependencies
+            .iter()
+            .map(|name| name.trim_end_matches('!').to_string())
+            .chain(
+                gems.iter()
+                    .filter(|gem| is_root(gem))
+                    .flat_map(|gem| gem.dependencies.clone()),
+            );
+        for name in roots {
+            for index in resolve(&name) {
+                if visited.insert(index) {
+                    queue.push_back(index);
+                }
+            }
+        }
+        let direct: HashSet<usize> = visited.clone();
+        let mut order = Vec::new();
+        let mut children: HashMap<usize, Vec<usize>> = HashMap::new();
+        while let Some(index) = queue.pop_front() {
+            order.push(index);
+            for &dep in &edges[index] {
+                if visited.insert(dep) {
+                    children.entry(index).or_default().push(dep);
+                    queue.push_back(dep);
+                }
+            }
+        }
+
+        // Transitive gems inherit the kind of the direct gem above them
+        let mut kinds: HashMap<usize, DependencyType> = HashMap::new();
+        for &index in &order {
+            let kind = kinds.get(&index).cloned().unwrap_or_else(|| {
+                declared(&gems[index].name)
+                    .map(|d| d.dependency_type.clone())
+                    .unwrap_or(DependencyType::Runtime)
+            });
+            for &child in children.get(&index).into_iter().flatten() {
+                kinds.insert(child, kind.clone());
+            }
+            kinds.insert(index, kind);
+        }
+
+        // Children come after their parent in breadth-first order, so
+        // building in reverse always finds them complete
+        let mut built: HashMap<usize, Dependency> = HashMap::new();
+        for &index in order.iter().rev() {
+            let gem = &gems[index];
+            let dependency_type = kinds[&index].clone();
+            let version_spec = match declared(&gem.name).filter(|_| direct.contains(&index)) {
+                Some(declared) => declared.version_spec.clone(),
+                None => gem.version.clone(),
+            };
+            let dependencies = children
+                .get(&index)
+                .into_iter()
+                .flatten()
+                .filter_map(|child| built.remove(child))
+                .collect();
+            progress::dependency_resolved(&gem.name);
+            built.insert(
+                index,
+                Dependency {
+                    name: gem.name.clone(),
+                    version_spec,
+                    resolved_version: Some(gem.version.clone()),
+                    is_direct: direct.contains(&index),
+                    is_dev: dependency_type == DependencyType::Development,
+                    dependency_type,
+                    vulnerabilities: self.check(&gem.name, &gem.version, options).await?,
+                    license: None,
+                    dependencies,
+                    alias_target: None,
+                    range_vulnerabilities: vec![],
+                    shadowed_declarations: vec![],
+                    source: Some(gem.source.clone()),
+                    platform: gem.platform.clone(),
+                    hashes: vec![],
+                    marker: None,
+                },
+            );
+        }
+        analysis.dependency_tree = order
+            .iter()
+            .filter(|index| direct.contains(index))
+            .filter_map(|index| built.remove(index))
+            .collect();
+        analysis.summarize();
+        Ok(analysis)
+    }
+
+    /// Advisories for a gem at `version`, when a database is set
+    async fn check(
+        &self,
+        name: &str,
+        version: &str,
+        options: &AnalysisOptions,
+    ) -> Result<Vec<Vulnerability>> {
+        match self.database(options)? {
+            Some(db) if options.check_vulnerabilities => {
+                db.check_package_with_policy(
+                    name,
+                    version,
+                    "rubygems",
+                    options.prerelease_policy("rubygems"),
+                )
+                .await
+            }
+            _ => Ok(vec![]),
+        }
+    }
+
+    /// Registries and repositories fetched without TLS or from hosts the
+    /// source policy does not allow, and a lockfile that could not be read
+    fn source_findings(
+        package: &RubyGem,
+        gemfile_sources: &[String],
+        lockfile: &Lockfile,
+        options: &AnalysisOptions,
+    ) -> Vec<Finding> {
+        let declared: Vec<(&str, &str)> = gemfile_sources
+            .iter()
+            .map(|url| ("source", url.as_str()))
+            .chain(
+                package
+                    .declared_dependencies
+                    .iter()
+                    .filter_map(|gem| Some((gem.name.as_str(), gem.source.as_ref()?.url()?))),
+            )
+            .collect();
+        let resolved: Vec<(&str, &str)> = lockfile
+            .gems
+            .iter()
+            .filter_map(|gem| Some((gem.name.as_str(), gem.source.url()?)))
+            .collect();
+        let mut findings = transport::insecure_transport_findings(declared.clone(), GEMFILE);
+        findings.extend(transport::insecure_transport_findings(
+            resolved.clone(),
+            LOCK_FILE,
+        ));
+        findings.extend(options.source_policy.findings(declared, GEMFILE));
+        findings.extend(options.source_policy.findings(resolved, LOCK_FILE));
+        findings.extend(package.skipped_files.iter().map(|skipped| {
+            skipped
+                .to_finding()
+                .with_scan_reason(ScanReason::DependencyAnalysis)
+        }));
+        findings
+    }
+
+    /// Analysis of `path` without the deadline `options.timeout_seconds` sets
+    async fn run_analysis(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<RubyAnalysisResult> {
+        let budget = MemoryBudget::new(options.max_memory_bytes);
+        let root = Self::project_root(path);
+        let gemspec_file = match path.extension() {
+            Some(ext) if ext == GEMSPEC_EXTENSION => path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(String::from),
+            _ => Self::find_gemspec(&root),
+        };
+        let mut sources: Vec<(String, String)> = Vec::new();
+        let mut package = match &gemspec_file {
+            Some(file) => {
+                let content = tokio::fs::read_to_string(root.join(file))
+                    .await
+                    .with_context(|| format!("Failed to read {}", file))?;
+                budget.charge(file, content.len())?;
+                let package = Self::parse_gemspec(file, &content)?;
+                sources.push((file.clone(), content));
+                package
+            }
+            None => RubyGem {
+                metadata: PackageMetadata {
+                    name: root
+                        .canonicalize()
+                        .ok()
+                        .and_then(|root| root.file_name()?.to_str().map(String::from))
+                        .unwrap_or_else(|| "application".to_string()),
+                    version: "unknown".to_string(),
+                    description: None,
+                    author: None,
+                    license: None,
+                    homepage: None,
+                    repository: None,
+                    keywords: vec![],
+                    publish_date: None,
+                },
+                gemspec: None,
+                required_ruby_version: None,
+                extensions: vec![],
+                declared_dependencies: vec![],
+                platforms: vec![],
+                bundler_version: None,
+                has_lock_file: false,
+                skipped_files: vec![],
+            },
+        };
+        let gemfile = tokio::fs::read_to_string(root.join(GEMFILE)).await.ok();
+        if gemfile.is_none() && gemspec_file.is_none() && !root.join(LOCK_FILE).exists() {
+            anyhow::bail!("No Gemfile or gemspec found in {}", root.display());
+        }
+        let gemfile_sources = gemfile
+            .as_deref()
+            .map(Self::gemfile_sources)
+            .unwrap_or_default();
+        if let Some(gemfile) = gemfile {
+            budget.charge(GEMFILE, gemfile.len())?;
+            package
+                .declared_dependencies
+                .extend(Self::parse_gemfile(&gemfile));
+            sources.push((GEMFILE.to_string(), gemfile));
+        }
+
+        // Only the first `max_dependencies` are analyzed
+        let dependency_limit = options.limits.truncate_dependencies(
+            gemspec_file.as_deref().unwrap_or(GEMFILE),
+            &mut package.declared_dependencies,
+        );
+        let lockfile = Self::read_lockfile(&root, &mut package).await;
+        progress::manifest_parsed(package.metadata());
+        progress::vuln_lookup_started(options);
+        let mut dependency_analysis = self
+            .analyze_dependencies(&package, &lockfile, options)
+            .await?;
+        deadline::dependencies_analyzed(&dependency_analysis);
+        budget.charge_for("dependencies", &dependency_analysis.dependency_tree)?;
+        dependency_analysis.license_findings =
+            options.license_policy.findings(&dependency_analysis);
+
+        let mut findings = if options.analyze_dependencies {
+            Self::source_findings(&package, &gemfile_sources, &lockfile, options)
+        } else {
+            vec![]
+        };
+        findings.extend(dependency_limit);
+
+        // Native extensions are built on the machine of everyone installing the gem
+        let mut malicious_patterns = Vec::new();
+        if options.scan_malicious_patterns {
+            for extension in &package.extensions {
+                if let Ok(source) = tokio::fs::read_to_string(root.join(extension)).await {
+                    sources.push((extension.clone(), source));
+                }
+            }
+            for (file, source) in &sources {
+                for pattern in self.pattern_matcher.scan(source, Some(file)) {
+                    if !malicious_patterns
+                        .iter()
+                        .any(|existing: &MaliciousPattern| {
+                            existing.pattern_id == pattern.pattern_id
+                        })
+                    {
+                        malicious_patterns.push(pattern);
+                    }
+                }
+            }
+        }
+        malicious_patterns.extend(custom_rules::scan_package(&root, options)?);
+        progress::pattern_scan_complete(&malicious_patterns);
+        budget.charge_for("malicious patterns", &malicious_patterns)?;
+
+        let mut vulnerabilities: Vec<Vulnerability> = vec![];
+        let mut nodes: Vec<&Dependency> = dependency_analysis.dependency_tree.iter().collect();
+        while let Some(dep) = nodes.pop() {
+            // Platform builds of one gem version share its advisories
+            for vulnerability in &dep.vulnerabilities {
+                if !vulnerabilities.iter().any(|v| v.id == vulnerability.id) {
+                    vulnerabilities.push(vulnerability.clone());
+                }
+            }
+            nodes.extend(dep.dependencies.iter());
+        }
+
+        let risk_calculator = RiskCalculator::from_options(options);
+        let install_time_code = !package.extensions.is_empty();
+        let supply_chain_score = if install_time_code { 20.0 } else { 0.0 };
+
+        let mut risk_score = risk_calculator.calculate(
+            &vulnerabilities,
+            &malicious_patterns,
+            false,
+            supply_chain_score,
+            50.0, // Default maintenance score
+        );
+        risk_calculator.apply_findings(&mut risk_score, &findings);
+        risk_calculator.apply_pinning(
+            &mut risk_score,
+            &PinningReport::from_analysis(&dependency_analysis, "rubygems"),
+        );
+        risk_calculator.apply_install_hooks(&mut risk_score, &package.install_hooks());
+        let scoped_risk = risk_calculator.scoped_risk(
+            &risk_score,
+            &dependency_analysis,
+            &findings,
+            &options.production_dependency_types,
+        );
+
+        let scan_phases = vec![
+            PhaseRecord::new(
+                ScanReason::DependencyAnalysis,
+                options.analyze_dependencies,
+                findings.len(),
+            ),
+            PhaseRecord::new(
+                ScanReason::VulnerabilityCheck,
+                options.check_vulnerabilities && self.database(options)?.is_some(),
+                vulnerabilities.len(),
+            ),
+            PhaseRecord::new(
+                ScanReason::MaliciousPatterns,
+                options.scan_malicious_patterns,
+                malicious_patterns.len(),
+            ),
+        ];
+
+        let risk_assessment = RiskAssessment {
+            risk_score: risk_score.clone(),
+            summary: format!(
+                "Ruby gem '{}' has {} risk with {} dependencies and {} findings; {}",
+                package.metadata.name,
+                risk_score.risk_level,
+                dependency_analysis.total_dependencies,
+                findings.len(),
+                scoped_risk
+            ),
+            detailed_findings: findings,
+            recommendations: dependency_analysis
+                .upgrade_recommendations_with("rubygems", options.recommend_prerelease_fixes),
+            security_posture: crate::core::SecurityPosture {
+                vulnerabilities_present: !vulnerabilities.is_empty(),
+                malicious_code_detected: !malicious_patterns.is_empty(),
+                supply_chain_risks: install_time_code
+                    || lockfile
+                        .gems
+                        .iter()
+                        .any(|gem| matches!(gem.source, DependencySource::Git { .. })),
+                actively_maintained: true,
+                trusted_publisher: false,
+                security_practices_score: if package.has_lock_file { 70.0 } else { 40.0 },
+            },
+            scan_phases,
+            scoped_risk: Some(scoped_risk),
+            scan_context: Some(ScanContext::current(
+                self.database(options)?.and_then(|db| db.last_updated()),
+            )),
+        };
+
+        let quality_metrics =
+            QualityMetrics::assess(&root, package.metadata(), false, options.now());
+        Ok(RubyAnalysisResult {
+            package,
+            risk_assessment,
+            dependency_analysis,
+            vulnerabilities,
+            malicious_patterns,
+            quality_metrics,
+        })
+    }
+}
+
+#[async_trait]
+impl PackageAnalyzer for RubyGemAnalyzer {
+    type Package = RubyGem;
+    type Analysis = RubyAnalysisResult;
+
+    async fn analyze(&self, path: &Path) -> Result<Self::Analysis> {
+        self.analyze_with_options(path, &AnalysisOptions::default())
+            .await
+    }
+
+    async fn analyze_with_options(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Self::Analysis> {
+        deadline::enforce(path, options, self.run_analysis(path, options)).await
+    }
+
+    fn can_analyze(&self, path: &Path) -> bool {
+        Self::is_project(path)
+    }
+
+    fn name(&self) -> &str {
+        "Ruby Gem Analyzer"
+    }
+
+    fn supported_extensions(&self) -> Vec<&str> {
+        vec!["gemspec", "lock"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gemspec_and_gemfile() {
+        let gemspec = r#"
+require_relative "lib/ledger/version"
+
+Gem::Specification.new do |spec|
+  spec.name          = "ledger"
+  spec.version       = Ledger::VERSION
+  spec.authors       = ["Ana", "Bo"]
+  spec.summary       = "Double-entry bookkeeping"
+  spec.license       = "MIT"
+  spec.metadata["source_code_uri"] = "https://github.com/example/ledger"
+  spec.extensions    = ["ext/ledger/extconf.rb"]
+
+  spec.add_dependency "bigdecimal", ">= 3.1", "< 4"
+  spec.add_development_dependency("rspec", "~> 3.12")
+end
+"#;
+        let gem = RubyGemAnalyzer::parse_gemspec("ledger.gemspec", gemspec).unwrap();
+        assert_eq!(gem.metadata.name, "ledger");
+        assert_eq!(gem.metadata.version, "unknown");
+        assert_eq!(gem.metadata.author.as_deref(), Some("Ana, Bo"));
+        assert_eq!(
+            gem.metadata.repository.as_deref(),
+            Some("https://github.com/example/ledger")
+        );
+        assert_eq!(gem.extensions, ["ext/ledger/extconf.rb"]);
+        let declared: Vec<(&str, &str, DependencyType)> = gem
+            .declared_dependencies
+            .iter()
+            .map(|d| {
+                (
+                    d.name.as_str(),
+                    d.version_spec.as_str(),
+                    d.dependency_type.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            declared,
+            [
+                ("bigdecimal", ">= 3.1, < 4", DependencyType::Runtime),
+                ("rspec", "~> 3.12", DependencyType::Development)
+            ]
+        );
+
+        let gemfile = r#"
+source "https://rubygems.org"
+
+gem "rails", "~> 7.1.0"
+gem "auth", git: "https://git.example.com/auth.git", tag: "v2.0"
+gem "billing", path: "../billing"
+
+group :development, :test do
+  gem "rspec-rails"
+end
+
+gem "capybara", group: :test
+"#;
+        let gems = RubyGemAnalyzer::parse_gemfile(gemfile);
+        assert_eq!(gems.len(), 5);
+        assert_eq!(gems[0].version_spec, "~> 7.1.0");
+        assert_eq!(
+            gems[1].source,
+            Some(DependencySource::Git {
+                url: "https://git.example.com/auth.git".to_string(),
+                revision: Some("v2.0".to_string()),
+            })
+        );
+        assert_eq!(
+            gems[2].source,
+            Some(DependencySource::Path {
+                path: "../billing".to_string()
+            })
+        );
+        assert_eq!(gems[3].groups, ["development", "test"]);
+        assert_eq!(gems[3].dependency_type, DependencyType::Development);
+        assert_eq!(gems[4].dependency_type, DependencyType::Test);
+        assert_eq!(
+            RubyGemAnalyzer::gemfile_sources(gemfile),
+            ["https://rubygems.org"]
+        );
+    }
+
+    #[test]
+    fn test_parse_lockfile() {
+        let lockfile = r#"GIT
+  remote: https://git.example.com/auth.git
+  revision: 3f2a9c1d
+  tag: v2.0
+  specs:
+    auth (2.0.0)
+      jwt (>= 2.0)
+
+GEM
+  remote: https://rubygems.org/
+  specs:
+    jwt (2.7.1)
+    nokogiri (1.15.4-arm64-darwin)
+      racc (~> 1.4)
+    nokogiri (1.15.4-x86_64-linux)
+      racc (~> 1.4)
+    racc (1.7.3)
+
+PLATFORMS
+  arm64-darwin
+  x86_64-linux
+
+DEPENDENCIES
+  auth!
+  nokogiri (~> 1.15)
+
+BUNDLED WITH
+   2.5.3
+"#;
+        let lockfile = RubyGemAnalyzer::parse_lockfile(lockfile).unwrap();
+        assert_eq!(lockfile.gems.len(), 5);
+        assert_eq!(
+            lockfile.gems[0].source,
+            DependencySource::Git {
+                url: "https://git.example.com/auth.git".to_string(),
+                revision: Some("3f2a9c1d".to_string()),
+            }
+        );
+        assert_eq!(lockfile.gems[0].dependencies, ["jwt"]);
+        assert_eq!(lockfile.gems[2].version, "1.15.4");
+        assert_eq!(lockfile.gems[2].platform.as_deref(), Some("arm64-darwin"));
+        assert_eq!(lockfile.dependencies, ["auth!", "nokogiri"]);
+        assert_eq!(lockfile.platforms, ["arm64-darwin", "x86_64-linux"]);
+        assert_eq!(lockfile.bundler_version.as_deref(), Some("2.5.3"));
+
+        assert!(RubyGemAnalyzer::parse_lockfile("GEM\n  specs:\n    rake\n").is_err());
+    }
+}
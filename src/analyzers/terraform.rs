@@ -0,0 +1,1093 @@
+//! Terraform/OpenTofu module analyzer
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::context::AnalyzerContext;
+use crate::core::{
+    custom_rules, deadline, progress, rules, AnalysisOptions, AnalysisResult, Dependency,
+    DependencyAnalysis, DependencyType, Finding, FindingType, InsecureSource, MaliciousPattern,
+    ManifestLimits, MemoryBudget, PackageAnalyzer, PackageInfo, PackageMetadata, PartialParse,
+    PatternMatcher, PhaseRecord, PinningReport, QualityMetrics, ResourceLimitExceeded,
+    RiskAssessment, RiskCalculator, RiskLevel, ScanContext, ScanReason, Vulnerability,
+};
+use crate::ecosystem::Ecosystem;
+use crate::utils::hcl::{self, HclBody, HclValue};
+use crate::vulnerability_db::terraform_db::TerraformVulnerabilityDb;
+use crate::vulnerability_db::VulnerabilityDatabase;
+
+const LOCK_FILE: &str = ".terraform.lock.hcl";
+const DEFAULT_REGISTRY: &str = "registry.terraform.io";
+
+/// Terraform root module information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerraformModule {
+    pub metadata: PackageMetadata,
+    pub required_version: Option<String>,
+    pub module_calls: Vec<ModuleCall>,
+    pub providers: Vec<ProviderRequirement>,
+    pub provisioner_commands: Vec<ProvisionerCommand>,
+    pub has_lock_file: bool,
+    /// Configuration and lock files left out because they could not be parsed
+    #[serde(default)]
+    pub skipped_files: Vec<PartialParse>,
+}
+
+/// A `module` block referencing another module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleCall {
+    pub name: String,
+    pub source: String,
+    pub version: Option<String>,
+    pub source_kind: ModuleSourceKind,
+}
+
+/// Where a module is fetched from
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ModuleSourceKind {
+    Local,
+    Registry,
+    Git,
+    Mercurial,
+    Http,
+    ObjectStorage,
+    Other,
+}
+
+impl ModuleSourceKind {
+    /// Classify a module `source` string using Terraform's source address rules
+    pub fn classify(source: &str) -> Self {
+        if source.starts_with("./") || source.starts_with("../") {
+            return Self::Local;
+        }
+        if source.starts_with("git::")
+            || source.starts_with("git@")
+            || source.starts_with("github.com/")
+            || source.starts_with("bitbucket.org/")
+        {
+            return Self::Git;
+        }
+        if source.starts_with("hg::") {
+            return Self::Mercurial;
+        }
+        if source.starts_with("s3::") || source.starts_with("gcs::") {
+            return Self::ObjectStorage;
+        }
+        if source.starts_with("http://") || source.starts_with("https://") {
+            return Self::Http;
+        }
+
+        let address = source.split("//").next().unwrap_or(source);
+        let parts: Vec<&str> = address.split('/').collect();
+        let is_registry = match parts.len() {
+            3 => parts.iter().all(|p| !p.is_empty() && !p.contains(':')),
+            4 => parts[0].contains('.') && parts[1..].iter().all(|p| !p.is_empty()),
+            _ => false,
+        };
+        if is_registry {
+            Self::Registry
+        } else {
+            Self::Other
+        }
+    }
+}
+
+/// A provider listed in `required_providers` or the lock file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderRequirement {
+    pub local_name: String,
+    pub source: String,
+    pub version_constraint: Option<String>,
+    pub locked_version: Option<String>,
+    pub lock_hashes: Vec<String>,
+}
+
+/// A command executed by a provisioner or external data source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvisionerCommand {
+    pub resource: String,
+    pub provisioner: String,
+    pub command: String,
+    pub file: String,
+}
+
+impl PackageInfo for TerraformModule {
+    fn metadata(&self) -> &PackageMetadata {
+        &self.metadata
+    }
+
+    fn package_type(&self) -> &str {
+        "terraform"
+    }
+
+    fn custom_attributes(&self) -> HashMap<String, serde_json::Value> {
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "required_version".to_string(),
+            serde_json::json!(self.required_version),
+        );
+        attrs.insert(
+            "module_calls".to_string(),
+            serde_json::json!(self.module_calls),
+        );
+        attrs.insert("providers".to_string(), serde_json::json!(self.providers));
+        attrs.insert(
+            "has_lock_file".to_string(),
+            serde_json::json!(self.has_lock_file),
+        );
+        attrs
+    }
+}
+
+/// Terraform analysis result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerraformAnalysisResult {
+    pub package: TerraformModule,
+    pub risk_assessment: RiskAssessment,
+    pub dependency_analysis: DependencyAnalysis,
+    pub vulnerabilities: Vec<Vulnerability>,
+    pub malicious_patterns: Vec<MaliciousPattern>,
+    /// Signs the package is tested, documented and maintained
+    #[serde(default)]
+    pub quality_metrics: QualityMetrics,
+}
+
+impl AnalysisResult for TerraformAnalysisResult {
+    fn package_info(&self) -> &dyn PackageInfo {
+        &self.package
+    }
+
+    fn risk_assessment(&self) -> &RiskAssessment {
+        &self.risk_assessment
+    }
+
+    fn risk_assessment_mut(&mut self) -> &mut RiskAssessment {
+        &mut self.risk_assessment
+    }
+
+    fn dependency_analysis(&self) -> &DependencyAnalysis {
+        &self.dependency_analysis
+    }
+
+    fn vulnerabilities(&self) -> &[Vulnerability] {
+        &self.vulnerabilities
+    }
+
+    fn malicious_patterns(&self) -> &[MaliciousPattern] {
+        &self.malicious_patterns
+    }
+
+    fn quality_metrics(&self) -> QualityMetrics {
+        self.quality_metrics.clone()
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+}
+
+/// Provider entry from `.terraform.lock.hcl`
+#[derive(Debug, Clone)]
+struct LockedProvider {
+    version: Option<String>,
+    hashes: Vec<String>,
+}
+
+/// Terraform/OpenTofu analyzer
+pub struct TerraformAnalyzer {
+    vuln_db: Arc<dyn VulnerabilityDatabase>,
+    pattern_matcher: PatternMatcher,
+}
+
+impl TerraformAnalyzer {
+    /// Create a new Terraform analyzer
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            vuln_db: crate::vulnerability_db::create_terraform_database()?.into(),
+            pattern_matcher: PatternMatcher::new()?,
+        })
+    }
+
+    /// Create analyzer with custom database path
+    pub fn with_db_path(db_path: &Path) -> Result<Self> {
+        Ok(Self {
+            vuln_db: crate::vulnerability_db::create_terraform_database_with_path(db_path)?.into(),
+            pattern_matcher: PatternMatcher::new()?,
+        })
+    }
+
+    /// Create analyzer sharing the compiled rules and database of `context`
+    pub fn from_context(context: &AnalyzerContext) -> Self {
+        Self {
+            vuln_db: context
+                .vulnerability_db(Ecosystem::Terraform)
+                .expect("Terraform has a vulnerability database"),
+            pattern_matcher: context.pattern_matcher().clone(),
+        }
+    }
+
+    /// Whether `path` is a module directory or a configuration file
+    ///
+    /// A directory needs a configuration file; a lock file alone is not a module.
+    pub(crate) fn is_module(path: &Path) -> bool {
+        if path.is_dir() {
+            std::fs::read_dir(path)
+                .map(|entries| {
+                    entries
+                        .filter_map(|e| e.ok())
+                        .any(|e| is_config_file(&e.path()))
+                })
+                .unwrap_or(false)
+        } else {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| matches!(ext, "tf" | "tofu"))
+                .unwrap_or(false)
+        }
+    }
+
+    /// Look up vulnerabilities in another database, such as a loaded snapshot
+    pub fn with_vulnerability_db(mut self, db: impl Into<Arc<dyn VulnerabilityDatabase>>) -> Self {
+        self.vuln_db = db.into();
+        self
+    }
+
+    /// Compute the `h1:` hash Terraform records for an unpacked provider directory
+    ///
+    /// Symlinks inside the directory are hashed by their target rather than
+    /// followed, so a link back up the tree cannot loop the walk.
+    pub fn provider_hash(dir: &Path) -> Result<String> {
+        let mut files = Vec::new();
+        collect_files(dir, dir, &mut files)?;
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut summary = Sha256::new();
+        for (name, path) in files {
+            let content = if std::fs::symlink_metadata(&path)?.is_symlink() {
+                std::fs::read_link(&path)?
+                    .to_string_lossy()
+                    .into_owned()
+                    .into_bytes()
+            } else {
+                std::fs::read(&path)?
+            };
+            summary.update(format!(
+                "{}  {}\n",
+                hex::encode(Sha256::digest(&content)),
+                name
+            ));
+        }
+
+        Ok(format!(
+            "h1:{}",
+            base64::engine::general_purpose::STANDARD.encode(summary.finalize())
+        ))
+    }
+
+    /// Normalize a provider source address to `hostname/namespace/type`
+    fn normalize_provider_source(local_name: &str, source: Option<&str>) -> String {
+        let source = source
+            .map(|s| s.to_lowercase())
+            .unwrap_or_else(|| format!("hashicorp/{}", local_name.to_lowercase()));
+        if source.split('/').count() == 2 {
+            format!("{}/{}", DEFAULT_REGISTRY, source)
+        } else {
+            source
+        }
+    }
+
+    /// Find configuration files for the module rooted at `path`
+    async fn config_files(&self, path: &Path) -> Result<(PathBuf, Vec<PathBuf>)> {
+        if !path.is_dir() {
+            let root = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+            return Ok((root, vec![path.to_path_buf()]));
+        }
+
+        let mut files = Vec::new();
+        let mut entries = tokio::fs::read_dir(path).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file = entry.path();
+            if is_config_file(&file) {
+                files.push(file);
+            }
+        }
+        files.sort();
+        Ok((path.to_path_buf(), files))
+    }
+
+    /// Parse the configuration files into a module description
+    ///
+    /// Each file read is charged to `budget`. Files that fail to parse are
+    /// skipped and listed in `skipped_files`, as is a lock file that fails to
+    /// parse; it then pins no provider. When no configuration file parses, the
+    /// first error is returned. A configuration file nesting deeper than
+    /// `limits` allow fails the analysis.
+    async fn parse_module(
+        &self,
+        root: &Path,
+        files: &[PathBuf],
+        budget: &MemoryBudget,
+        limits: &ManifestLimits,
+    ) -> Result<TerraformModule> {
+        let mut required_version = None;
+        let mut module_calls = Vec::new();
+        let mut providers: Vec<ProviderRequirement> = Vec::new();
+        let mut provisioner_commands = Vec::new();
+        let mut skipped_files = Vec::new();
+        let mut first_error = None;
+
+        for file in files {
+            let content = tokio::fs::read_to_string(file)
+                .await
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let file_name = file
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default()
+                .to_string();
+            budget.charge(&file_name, content.len())?;
+            let body = match hcl::parse_with_limits(&content, &file_name, limits) {
+                Ok(body) => body,
+                Err(error) if error.is::<ResourceLimitExceeded>() => return Err(error),
+                Err(error) => {
+                    skipped_files.push(PartialParse::new(file_name, format!("{:#}", error)));
+                    first_error.get_or_insert(
+                        error.context(format!("Failed to parse {}", file.display())),
+                    );
+                    continue;
+                }
+            };
+
+            for module in body.blocks_of("module") {
+                let Some(source) = module.body.attribute("source").and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                module_calls.push(ModuleCall {
+                    name: module.labels.first().cloned().unwrap_or_default(),
+                    source: source.to_string(),
+                    version: module
+                        .body
+                        .attribute("version")
+                        .and_then(|v| v.as_str())
+                        .map(String::from),
+                    source_kind: ModuleSourceKind::classify(source),
+                });
+            }
+
+            for terraform in body.blocks_of("terraform") {
+                if let Some(version) = terraform
+                    .body
+                    .attribute("required_version")
+                    .and_then(|v| v.as_str())
+                {
+                    required_version = Some(version.to_string());
+                }
+
+                for required in terraform.body.blocks_of("required_providers") {
+                    for (local_name, value) in &required.body.attributes {
+                        // Legacy shorthand: `aws = "~> 4.0"`
+                        let (source, constraint) = match value {
+                            HclValue::String(version) => (None, Some(version.clone())),
+                            _ => (
+                                value.get("source").and_then(|v| v.as_str()),
+                                value
+                                    .get("version")
+                                    .and_then(|v| v.as_str())
+                                    .map(String::from),
+                            ),
+                        };
+                        providers.push(ProviderRequirement {
+                            local_name: local_name.clone(),
+                            source: Self::normalize_provider_source(local_name, source),
+                            version_constraint: constraint,
+                            locked_version: None,
+                            lock_hashes: vec![],
+                        });
+                    }
+                }
+            }
+
+            provisioner_commands.extend(Self::extract_commands(&body, &file_name));
+        }
+
+        if let Some(error) = first_error.filter(|_| skipped_files.len() == files.len()) {
+            return Err(error);
+        }
+
+        let lock_path = root.join(LOCK_FILE);
+        let has_lock_file = lock_path.exists();
+        if has_lock_file {
+            let content = tokio::fs::read_to_string(&lock_path).await?;
+            let locked = Self::parse_lock_file(&content, limits).unwrap_or_else(|error| {
+                skipped_files.push(PartialParse::new(LOCK_FILE, format!("{:#}", error)));
+                HashMap::new()
+            });
+
+            for provider in &mut providers {
+                let entry = locked.get(&provider.source).or_else(|| {
+                    let key = TerraformVulnerabilityDb::registry_key(&provider.source);
+                    locked
+                        .iter()
+                        .find(|(address, _)| TerraformVulnerabilityDb::registry_key(address) == key)
+                        .map(|(_, entry)| entry)
+                });
+                if let Some(entry) = entry {
+                    provider.locked_version = entry.version.clone();
+                    provider.lock_hashes = entry.hashes.clone();
+                }
+            }
+
+            // Providers used implicitly (no required_providers entry) still appear in the lock file
+            let mut implicit: Vec<_> = locked
+                .iter()
+                .filter(|(address, _)| {
+                    let key = TerraformVulnerabilityDb::registry_key(address);
+                    !providers
+                        .iter()
+                        .any(|p| TerraformVulnerabilityDb::registry_key(&p.source) == key)
+                })
+                .collect();
+            implicit.sort_by(|a, b| a.0.cmp(b.0));
+            for (address, entry) in implicit {
+                providers.push(ProviderRequirement {
+                    local_name: address.rsplit('/').next().unwrap_or(address).to_string(),
+                    source: address.clone(),
+                    version_constraint: None,
+                    locked_version: entry.version.clone(),
+                    lock_hashes: entry.hashes.clone(),
+                });
+            }
+        }
+
+        let metadata = PackageMetadata {
+            name: root
+                .canonicalize()
+                .ok()
+                .as_deref()
+                .unwrap_or(root)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown")
+                .to_string(),
+            version: "unknown".to_string(),
+            description: None,
+            author: None,
+            license: None,
+            homepage: None,
+            repository: None,
+            keywords: vec![],
+            publish_date: None,
+        };
+
+        Ok(TerraformModule {
+            metadata,
+            required_version,
+            module_calls,
+            providers,
+            provisioner_commands,
+            has_lock_file,
+            skipped_files,
+        })
+    }
+
+    /// Parse `.terraform.lock.hcl` into provider entries keyed by address
+    fn parse_lock_file(
+        content: &str,
+        limits: &ManifestLimits,
+    ) -> Result<HashMap<String, LockedProvider>> {
+        let body = hcl::parse_with_limits(content, LOCK_FILE, limits)
+            .context("Failed to parse .terraform.lock.hcl")?;
+        let mut locked = HashMap::new();
+
+        for provider in body.blocks_of("provider") {
+            let Some(address) = provider.labels.first() else {
+                continue;
+            };
+            let hashes = provider
+                .body
+                .attribute("hashes")
+                .and_then(|v| v.as_list())
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|v| v.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default();
+            locked.insert(
+                address.to_lowercase(),
+                LockedProvider {
+                    version: provider
+                        .body
+                        .attribute("version")
+                        .and_then(|v| v.as_str())
+                        .map(String::from),
+                    hashes,
+                },
+            );
+        }
+
+        Ok(locked)
+    }
+
+    /// Extract commands run by provisioners and `external` data sources
+    fn extract_commands(body: &HclBody, file: &str) -> Vec<ProvisionerCommand> {
+        let mut commands = Vec::new();
+
+        for resource in body.blocks_of("resource") {
+            let resource_name = resource.labels.join(".");
+            for provisioner in resource.body.blocks_of("provisioner") {
+                let kind = provisioner.labels.first().cloned().unwrap_or_default();
+                let mut texts = Vec::new();
+                if let Some(command) = provisioner.body.attribute("command") {
+                    texts.extend(command.as_text().map(String::from));
+                }
+                if let Some(inline) = provisioner.body.attribute("inline") {
+                    match inline.as_list() {
+                        Some(items) => {
+                            texts.extend(items.iter().filter_map(|v| v.as_text().map(String::from)))
+                        }
+                        None => texts.extend(inline.as_text().map(String::from)),
+                    }
+                }
+                for command in texts {
+                    commands.push(ProvisionerCommand {
+                        resource: resource_name.clone(),
+                        provisioner: kind.clone(),
+                        command,
+                        file: file.to_string(),
+                    });
+                }
+            }
+        }
+
+        for data in body.blocks_of("data") {
+            if data.labels.first().map(String::as_str) != Some("external") {
+                continue;
+            }
+            if let Some(program) = data.body.attribute("program").and_then(|v| v.as_list()) {
+                let command = program
+                    .iter()
+                    .filter_map(|v| v.as_text())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                commands.push(ProvisionerCommand {
+                    resource: format!("data.{}", data.labels.join(".")),
+                    provisioner: "external".to_string(),
+                    command,
+                    file: file.to_string(),
+                });
+            }
+        }
+
+        commands
+    }
+
+    /// Flag modules fetched from outside a registry
+    fn check_module_sources(&self, module: &TerraformModule) -> Vec<Finding> {
+        let mut findings = Vec::new();
+
+        for call in &module.module_calls {
+            let kind = &call.source_kind;
+            if matches!(kind, ModuleSourceKind::Local | ModuleSourceKind::Registry) {
+                continue;
+            }
+
+            let pinned = call.source.contains("ref=") || call.source.contains("checksum=");
+            let (severity, detail) = if !pinned {
+                (RiskLevel::Medium, "without a pinned ref or checksum")
+            } else {
+                (RiskLevel::Low, "pinned to a specific ref")
+            };
+
+            findings.push(
+                Finding::new(
+                    FindingType::NonRegistrySource,
+                    severity,
+                    format!("Module '{}' uses a non-registry source", call.name),
+                    format!(
+                        "Module '{}' is fetched from a {:?} source {}, bypassing registry versioning",
+                        call.name, kind, detail
+                    ),
+                )
+                .with_evidence(format!("source = \"{}\"", call.source))
+                .with_affected_component(call.name.clone())
+                .with_scan_reason(ScanReason::DependencyAnalysis)
+                .with_rule_id(rules::TERRAFORM_NON_REGISTRY_SOURCE),
+            );
+
+            // Go-getter forced schemes such as `git::http://` wrap the URL
+            let url = call.source.rsplit("::").next().unwrap_or(&call.source);
+            let declared_in = format!("module \"{}\"", call.name);
+            if let Some(source) = InsecureSource::check(&call.name, url, &declared_in) {
+                findings.push(source.to_finding());
+            }
+        }
+
+        findings
+    }
+
+    /// Check providers against the lock file and any installed provider packages
+    fn verify_providers(&self, root: &Path, module: &TerraformModule) -> Result<Vec<Finding>> {
+        let mut findings = Vec::new();
+
+        if !module.has_lock_file {
+            if !module.providers.is_empty() {
+                let mut finding = Finding::new(
+                    FindingType::MissingIntegrity,
+                    RiskLevel::Medium,
+                    "No dependency lock file",
+                    format!(
+                        "{} is missing, so provider versions and checksums are not pinned",
+                        LOCK_FILE
+                    ),
+                )
+                .with_rule_id(rules::TERRAFORM_MISSING_LOCK_FILE);
+                for provider in &module.providers {
+                    finding = finding.with_affected_component(provider.source.clone());
+                }
+                findings.push(finding);
+            }
+            return Ok(findings);
+        }
+
+        for provider in &module.providers {
+            if provider.locked_version.is_none() {
+                findings.push(
+                    Finding::new(
+                        FindingType::MissingIntegrity,
+                        RiskLevel::Medium,
+                        format!("Provider '{}' is not locked", provider.source),
+                        format!(
+                            "Provider '{}' is required but has no entry in {}",
+                            provider.source, LOCK_FILE
+                        ),
+                    )
+                    .with_affected_component(provider.source.clone())
+                    .with_rule_id(rules::TERRAFORM_PROVIDER_NOT_LOCKED),
+                );
+                continue;
+            }
+
+            if provider.lock_hashes.is_empty() {
+                findings.push(
+                    Finding::new(
+                        FindingType::MissingIntegrity,
+                        RiskLevel::Medium,
+                        format!("Provider '{}' has no recorded checksums", provider.source),
+                        format!(
+                            "The {} entry for '{}' lists no hashes, so the downloaded binary cannot be verified",
+                            LOCK_FILE, provider.source
+                        ),
+                    )
+                    .with_affected_component(provider.source.clone())
+                    .with_rule_id(rules::TERRAFORM_PROVIDER_NO_CHECKSUMS),
+                );
+                continue;
+            }
+
+            findings.extend(self.verify_installed_provider(root, provider)?);
+        }
+
+        Ok(findings)
+    }
+
+    /// Compare installed provider packages in `.terraform/providers` with the lock file
+    fn verify_installed_provider(
+        &self,
+        root: &Path,
+        provider: &ProviderRequirement,
+    ) -> Result<Vec<Finding>> {
+        let mut findings = Vec::new();
+        let Some(version) = &provider.locked_version else {
+            return Ok(findings);
+        };
+
+        let install_dir = root
+            .join(".terraform")
+            .join("providers")
+            .join(&provider.source)
+            .join(version);
+        if !install_dir.is_dir() {
+            return Ok(findings);
+        }
+
+        let has_h1 = provider.lock_hashes.iter().any(|h| h.starts_with("h1:"));
+        if !has_h1 {
+            return Ok(findings);
+        }
+
+        let mut platforms: Vec<_> = std::fs::read_dir(&install_dir)?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .collect();
+        platforms.sort();
+
+        for platform_dir in platforms {
+            let hash = Self::provider_hash(&platform_dir)?;
+            if !provider.lock_hashes.contains(&hash) {
+                let platform = platform_dir
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or_default()
+                    .to_string();
+                findings.push(
+                    Finding::new(
+                        FindingType::IntegrityMismatch,
+                        RiskLevel::Critical,
+                        format!("Provider '{}' checksum mismatch", provider.source),
+                        format!(
+                            "Installed provider '{}' {} ({}) does not match any hash in {}",
+                            provider.source, version, platform, LOCK_FILE
+                        ),
+                    )
+                    .with_evidence(format!("computed {}", hash))
+                    .with_affected_component(provider.source.clone())
+                    .with_rule_id(rules::TERRAFORM_PROVIDER_CHECKSUM_MISMATCH),
+                );
+            }
+        }
+
+        Ok(findings)
+    }
+
+    /// Build dependency analysis from modules and providers
+    async fn analyze_dependencies(
+        &self,
+        module: &TerraformModule,
+        options: &AnalysisOptions,
+    ) -> Result<DependencyAnalysis> {
+        let mut analysis = DependencyAnalysis::default();
+
+        for call in &module.module_calls {
+            if call.source_kind == ModuleSourceKind::Local {
+                continue;
+            }
+
+            let name = if call.source_kind == ModuleSourceKind::Registry {
+                TerraformVulnerabilityDb::registry_key(
+                    call.source.split("//").next().unwrap_or(&call.source),
+                )
+            } else {
+                call.source.clone()
+            };
+            let version_spec = call.version.clone().unwrap_or_else(|| "*".to_string());
+            let vulns = if options.check_vulnerabilities {
+                self.vuln_db
+                    .check_package_with_policy(
+                        &name,
+                        &version_spec,
+                        "terraform",
+                        options.prerelease_policy("terraform"),
+                    )
+                    .await?
+            } else {
+                vec![]
+            };
+
+            progress::dependency_resolved(&name);
+            analysis.dependency_tree.push(Dependency {
+                name,
+                version_spec,
+                resolved_version: None,
+                dependency_type: DependencyType::Runtime,
+                is_direct: true,
+                is_dev: false,
+                vulnerabilities: vulns,
+                license: None,
+                dependencies: vec![],
+                alias_target: None,
+                range_vulnerabilities: vec![],
+                shadowed_declarations: vec![],
+                source: None,
+                platform: None,
+                hashes: vec![],
+                marker: None,
+            });
+        }
+
+        for provider in &module.providers {
+            let name = TerraformVulnerabilityDb::registry_key(&provider.source);
+            let version_spec = provider
+                .version_constraint
+                .clone()
+                .unwrap_or_else(|| "*".to_string());
+            let lookup_version = provider.locked_version.as_deref().unwrap_or(&version_spec);
+            let vulns = if options.check_vulnerabilities {
+                self.vuln_db
+                    .check_package_with_policy(
+                        &name,
+                        lookup_version,
+                        "terraform",
+                        options.prerelease_policy("terraform"),
+                    )
+                    .await?
+            } else {
+                vec![]
+            };
+
+            progress::dependency_resolved(&name);
+            analysis.dependency_tree.push(Dependency {
+                name,
+                version_spec,
+                resolved_version: provider.locked_version.clone(),
+                dependency_type: DependencyType::Runtime,
+                is_direct: true,
+                is_dev: false,
+                vulnerabilities: vulns,
+                license: None,
+                dependencies: vec![],
+                alias_target: None,
+                range_vulnerabilities: vec![],
+                shadowed_declarations: vec![],
+                source: None,
+                platform: None,
+                hashes: vec![],
+                marker: None,
+            });
+        }
+
+        analysis.summarize();
+        Ok(analysis)
+    }
+
+    /// Analysis of `path` without the deadline `options.timeout_seconds` sets
+    async fn run_analysis(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<TerraformAnalysisResult> {
+        let budget = MemoryBudget::new(options.max_memory_bytes);
+        let (root, files) = self.config_files(path).await?;
+        if files.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No Terraform configuration files found in {}",
+                path.display()
+            ));
+        }
+
+        let package = self
+            .parse_module(&root, &files, &budget, &options.limits)
+            .await?;
+        progress::manifest_parsed(package.metadata());
+        progress::vuln_lookup_started(options);
+        let mut dependency_analysis = self.analyze_dependencies(&package, options).await?;
+        deadline::dependencies_analyzed(&dependency_analysis);
+        budget.charge_for("dependencies", &dependency_analysis.dependency_tree)?;
+        dependency_analysis.license_findings =
+            options.license_policy.findings(&dependency_analysis);
+
+        let mut findings = if options.analyze_dependencies {
+            // Non-registry modules are reported by source, not by version range
+            let mut findings = self.check_module_sources(&package);
+            findings.extend(
+                dependency_analysis
+                    .unpinned_findings()
+                    .into_iter()
+                    .filter(|f| {
+                        !package.module_calls.iter().any(|call| {
+                            call.source_kind != ModuleSourceKind::Registry
+                                && f.affected_components.contains(&call.source)
+                        })
+                    }),
+            );
+            findings
+        } else {
+            vec![]
+        };
+        let source_findings = findings.len();
+        findings.extend(
+            self.verify_providers(&root, &package)?
+                .into_iter()
+                .map(|f| f.with_scan_reason(ScanReason::Integrity)),
+        );
+        let integrity_findings = findings.len() - source_findings;
+        findings.extend(package.skipped_files.iter().map(PartialParse::to_finding));
+
+        // Provisioners and external data sources run arbitrary commands at apply time
+        let commands = package
+            .provisioner_commands
+            .iter()
+            .map(|c| c.command.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let mut malicious_patterns = if commands.is_empty() || !options.scan_malicious_patterns {
+            vec![]
+        } else {
+            // Provisioner commands are gathered from several files, so
+            // offsets into them point nowhere
+            let mut patterns = self.pattern_matcher.scan(&commands, Some("provisioner"));
+            for pattern in &mut patterns {
+                pattern.forget_positions();
+            }
+            patterns
+        };
+        malicious_patterns.extend(custom_rules::scan_package(&root, options)?);
+        progress::pattern_scan_complete(&malicious_patterns);
+        budget.charge_for("malicious patterns", &malicious_patterns)?;
+
+        let mut vulnerabilities = vec![];
+        for dep in &dependency_analysis.dependency_tree {
+            vulnerabilities.extend(dep.vulnerabilities.clone());
+        }
+
+        let risk_calculator = RiskCalculator::from_options(options);
+        let supply_chain_score = if package.provisioner_commands.is_empty() {
+            0.0
+        } else {
+            40.0
+        };
+
+        let mut risk_score = risk_calculator.calculate(
+            &vulnerabilities,
+            &malicious_patterns,
+            false,
+            supply_chain_score,
+            50.0, // Default maintenance score
+        );
+        risk_calculator.apply_findings(&mut risk_score, &findings);
+        risk_calculator.apply_pinning(
+            &mut risk_score,
+            &PinningReport::from_analysis(&dependency_analysis, "terraform"),
+        );
+        let scoped_risk = risk_calculator.scoped_risk(
+            &risk_score,
+            &dependency_analysis,
+            &findings,
+            &options.production_dependency_types,
+        );
+
+        let scan_phases = vec![
+            PhaseRecord::new(
+                ScanReason::DependencyAnalysis,
+                options.analyze_dependencies,
+                source_findings,
+            ),
+            PhaseRecord::new(ScanReason::Integrity, true, integrity_findings),
+            PhaseRecord::new(
+                ScanReason::VulnerabilityCheck,
+                options.check_vulnerabilities,
+                vulnerabilities.len(),
+            ),
+            PhaseRecord::new(
+                ScanReason::MaliciousPatterns,
+                options.scan_malicious_patterns,
+                malicious_patterns.len(),
+            ),
+        ];
+
+        let risk_assessment = RiskAssessment {
+            risk_score: risk_score.clone(),
+            summary: format!(
+                "Terraform configuration '{}' has {} risk with {} modules, {} providers and {} findings; {}",
+                package.metadata.name,
+                risk_score.risk_level,
+                package.module_calls.len(),
+                package.providers.len(),
+                findings.len(),
+                scoped_risk
+            ),
+            detailed_findings: findings,
+            recommendations: dependency_analysis
+                .upgrade_recommendations_with("terraform", options.recommend_prerelease_fixes),
+            security_posture: crate::core::SecurityPosture {
+                vulnerabilities_present: !vulnerabilities.is_empty(),
+                malicious_code_detected: !malicious_patterns.is_empty(),
+                supply_chain_risks: supply_chain_score > 0.0
+                    || package
+                        .module_calls
+                        .iter()
+                        .any(|m| !matches!(m.source_kind, ModuleSourceKind::Local | ModuleSourceKind::Registry)),
+                actively_maintained: true,
+                trusted_publisher: false,
+                security_practices_score: if package.has_lock_file { 70.0 } else { 40.0 },
+            },
+            scan_phases,
+            scoped_risk: Some(scoped_risk),
+            scan_context: Some(ScanContext::current(self.vuln_db.last_updated())),
+        };
+
+        let quality_metrics =
+            QualityMetrics::assess(&root, package.metadata(), false, options.now());
+        Ok(TerraformAnalysisResult {
+            package,
+            risk_assessment,
+            dependency_analysis,
+            vulnerabilities,
+            malicious_patterns,
+            quality_metrics,
+        })
+    }
+}
+
+/// Check whether a path is a Terraform/OpenTofu configuration file
+fn is_config_file(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|ext| matches!(ext, "tf" | "tofu"))
+            .unwrap_or(false)
+}
+
+/// Recursively collect files under `dir` with slash-separated paths relative to `base`
+///
+/// Symlinks are collected as entries and never descended into.
+fn collect_files(dir: &Path, base: &Path, files: &mut Vec<(String, PathBuf)>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        // The entry itself, not what a symlink points to
+        let file_type = entry.file_type()?;
+        let path = entry.path();
+        if file_type.is_dir() {
+            collect_files(&path, base, files)?;
+        } else {
+            let relative = path
+                .strip_prefix(base)?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push((relative, path));
+        }
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl PackageAnalyzer for TerraformAnalyzer {
+    type Package = TerraformModule;
+    type Analysis = TerraformAnalysisResult;
+
+    async fn analyze(&self, path: &Path) -> Result<Self::Analysis> {
+        self.analyze_with_options(path, &AnalysisOptions::default())
+            .await
+    }
+
+    async fn analyze_with_options(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Self::Analysis> {
+        deadline::enforce(path, options, self.run_analysis(path, options)).await
+    }
+
+    fn can_analyze(&self, path: &Path) -> bool {
+        Self::is_module(path)
+    }
+
+    fn name(&self) -> &str {
+        "Terraform Module Analyzer"
+    }
+
+    fn supported_extensions(&self) -> Vec<&str> {
+        vec!["tf", "tofu"]
+    }
+}
@@ -0,0 +1,275 @@
+//! Benchmark scenarios and a performance-regression guard
+//!
+//! Each [`Scenario`] writes a fixed npm package to a directory, generated
+//! without randomness, so every run analyzes identical input. Analyze it with
+//! [`AnalysisOptions::reproducible`] at [`REFERENCE_TIME`] to keep the network
+//! and the clock out of the measurement. The criterion suite in `benches/`
+//! runs the scenarios; [`RegressionGuard`] checks timings against recorded
+//! baselines, for use in CI.
+
+use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::analyzers::npm::NpmAnalyzer;
+use crate::core::{AnalysisOptions, PackageAnalyzer};
+
+/// Direct dependencies of the large lockfile scenario
+const LOCKFILE_DIRECT: usize = 200;
+/// Packages recorded in the large lockfile, direct and transitive
+const LOCKFILE_PACKAGES: usize = 2_000;
+/// Nesting of `node_modules` in the deep tree scenario
+const TREE_DEPTH: usize = 64;
+/// Size of the minified file scenario's script
+const MINIFIED_SIZE: usize = 2 * 1024 * 1024;
+
+/// Time every benchmark analysis treats as now: 2025-01-01T00:00:00Z
+pub const REFERENCE_TIME: i64 = 1_735_689_600;
+
+/// A representative analysis workload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Scenario {
+    /// A `package.json` with a handful of dependencies and one source file
+    SmallManifest,
+    /// Hundreds of direct dependencies pinned by a lockfile of thousands
+    LargeLockfile,
+    /// A lockfile whose packages nest `node_modules` dozens of levels deep
+    DeepTree,
+    /// A single-line, multi-megabyte script run at install
+    MinifiedFile,
+}
+
+impl Scenario {
+    pub const ALL: [Scenario; 4] = [
+        Scenario::SmallManifest,
+        Scenario::LargeLockfile,
+        Scenario::DeepTree,
+        Scenario::MinifiedFile,
+    ];
+
+    /// Identifier used as the benchmark name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SmallManifest => "small_manifest",
+            Self::LargeLockfile => "large_lockfile",
+            Self::DeepTree => "deep_tree",
+            Self::MinifiedFile => "minified_file",
+        }
+    }
+
+    /// Write the scenario's package into `dir`
+    pub fn write(&self, dir: &Path) -> Result<()> {
+        let mut package = json!({
+            "name": format!("bench-{}", self.name().replace('_', "-")),
+            "version": "1.0.0",
+            "description": "Benchmark fixture",
+        });
+        match self {
+            Self::SmallManifest => {
+                package["dependencies"] = json!({
+                    "express": "^4.18.2",
+                    "lodash": "4.17.21",
+                    "chalk": "^5.3.0",
+                });
+                std::fs::write(
+                    dir.join("index.js"),
+                    "const _ = require('lodash');\nmodule.exports = (a) => _.merge({}, a);\n",
+                )?;
+            }
+            Self::LargeLockfile => {
+                let direct: Map<String, Value> = (0..LOCKFILE_DIRECT)
+                    .map(|i| (fixture_name(i), json!(format!("^1.{}.0", i % 10))))
+                    .collect();
+                package["dependencies"] = Value::Object(direct);
+                let packages = (0..LOCKFILE_PACKAGES).map(|i| {
+                    let key = match i {
+                        i if i < LOCKFILE_DIRECT => format!("node_modules/{}", fixture_name(i)),
+                        i => format!(
+                            "node_modules/{}/node_modules/{}",
+                            fixture_name(i % LOCKFILE_DIRECT),
+                            fixture_name(i)
+                        ),
+                    };
+                    (
+                        key,
+                        lock_entry(&fixture_name(i), &format!("1.{}.3", i % 10)),
+                    )
+                });
+                write_lockfile(dir, &package, packages)?;
+            }
+            Self::DeepTree => {
+                package["dependencies"] = json!({ fixture_name(0): "^1.0.0" });
+                let packages = (0..TREE_DEPTH).map(|depth| {
+                    let key = (0..=depth)
+                        .map(|i| format!("node_modules/{}", fixture_name(i)))
+                        .collect::<Vec<_>>()
+                        .join("/");
+                    (key, lock_entry(&fixture_name(depth), "1.0.0"))
+                });
+                write_lockfile(dir, &package, packages)?;
+            }
+            Self::MinifiedFile => {
+                package["scripts"] = json!({ "postinstall": "node install.js" });
+                let statement = "var a=function(b,c){return b.map(function(d){return d*c})};";
+                let script = statement.repeat(MINIFIED_SIZE / statement.len());
+                std::fs::write(dir.join("install.js"), script)?;
+            }
+        }
+        std::fs::write(
+            dir.join("package.json"),
+            serde_json::to_string_pretty(&package)?,
+        )?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Scenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// [`REFERENCE_TIME`] as a timestamp
+pub fn reference_time() -> DateTime<Utc> {
+    Utc.timestamp_opt(REFERENCE_TIME, 0).unwrap()
+}
+
+/// Options every benchmark analyzes with
+pub fn options() -> AnalysisOptions {
+    AnalysisOptions::reproducible(reference_time())
+}
+
+/// Median time of `iterations` analyses of the package in `root`
+pub async fn measure(analyzer: &NpmAnalyzer, root: &Path, iterations: usize) -> Result<Duration> {
+    let options = options();
+    let mut timings = Vec::with_capacity(iterations.max(1));
+    for _ in 0..iterations.max(1) {
+        let start = Instant::now();
+        analyzer.analyze_with_options(root, &options).await?;
+        timings.push(start.elapsed());
+    }
+    timings.sort();
+    Ok(timings[timings.len() / 2])
+}
+
+/// A scenario measured slower than its baseline allows
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{scenario} took {measured:?}, over the {allowed:?} allowed by its {baseline:?} baseline")]
+pub struct PerformanceRegression {
+    pub scenario: Scenario,
+    pub baseline: Duration,
+    pub measured: Duration,
+    pub allowed: Duration,
+}
+
+/// Baseline timings with a tolerance for noise
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegressionGuard {
+    baselines: BTreeMap<Scenario, Duration>,
+    tolerance: f64,
+}
+
+impl RegressionGuard {
+    /// Guard allowing each scenario to run `tolerance` slower than its
+    /// baseline, as a fraction: `0.25` allows 25%
+    pub fn new(tolerance: f64) -> Self {
+        Self {
+            baselines: BTreeMap::new(),
+            tolerance: tolerance.max(0.0),
+        }
+    }
+
+    /// Record the expected time of a scenario
+    pub fn with_baseline(mut self, scenario: Scenario, baseline: Duration) -> Self {
+        self.baselines.insert(scenario, baseline);
+        self
+    }
+
+    /// Compare a measurement with its baseline; scenarios without one pass
+    pub fn check(
+        &self,
+        scenario: Scenario,
+        measured: Duration,
+    ) -> Result<(), PerformanceRegression> {
+        let Some(&baseline) = self.baselines.get(&scenario) else {
+            return Ok(());
+        };
+        let allowed = baseline.mul_f64(1.0 + self.tolerance);
+        if measured <= allowed {
+            return Ok(());
+        }
+        Err(PerformanceRegression {
+            scenario,
+            baseline,
+            measured,
+            allowed,
+        })
+    }
+}
+
+/// Deterministic package name for fixture index `i`
+fn fixture_name(i: usize) -> String {
+    format!("bench-dep-{:04}", i)
+}
+
+fn lock_entry(name: &str, version: &str) -> Value {
+    json!({
+        "version": version,
+        "resolved": format!("https://registry.npmjs.org/{0}/-/{0}-{1}.tgz", name, version),
+        "integrity": "sha512-AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA==",
+    })
+}
+
+fn write_lockfile(
+    dir: &Path,
+    package: &Value,
+    packages: impl Iterator<Item = (String, Value)>,
+) -> Result<()> {
+    let mut entries = Map::new();
+    entries.insert(
+        String::new(),
+        json!({
+            "name": package["name"],
+            "version": package["version"],
+            "dependencies": package["dependencies"],
+        }),
+    );
+    entries.extend(packages);
+    let lock = json!({
+        "name": package["name"],
+        "version": package["version"],
+        "lockfileVersion": 3,
+        "requires": true,
+        "packages": entries,
+    });
+    std::fs::write(
+        dir.join("package-lock.json"),
+        serde_json::to_string_pretty(&lock)?,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_regression_guard() {
+        let guard = RegressionGuard::new(0.25)
+            .with_baseline(Scenario::SmallManifest, Duration::from_millis(100));
+        assert!(guard
+            .check(Scenario::SmallManifest, Duration::from_millis(120))
+            .is_ok());
+        let regression = guard
+            .check(Scenario::SmallManifest, Duration::from_millis(130))
+            .unwrap_err();
+        assert_eq!(regression.allowed, Duration::from_millis(125));
+        assert!(guard
+            .check(Scenario::DeepTree, Duration::from_secs(60))
+            .is_ok());
+    }
+}
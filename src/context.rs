@@ -0,0 +1,163 @@
+//! Compiled rules and vulnerability databases shared between analyzers
+//!
+//! Creating an analyzer compiles every malicious-code pattern and loads the
+//! vulnerability databases. Services that create analyzers per request can
+//! build an [`AnalyzerContext`] once instead and create each analyzer from it
+//! with [`PackageSecurityAnalyzer::from_context`](crate::PackageSecurityAnalyzer::from_context),
+//! which only clones reference-counted handles.
+//!
+//! A context is immutable once built. Its `with_*` methods consume it and
+//! return a new one, and clones share the same compiled rules and databases.
+//! Every part is `Send + Sync`, so one context can serve analyzers on any
+//! number of threads.
+
+use anyhow::Result;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::core::{MaliciousPattern, PatternMatcher, VulnerabilityDatabase};
+use crate::ecosystem::Ecosystem;
+use crate::vulnerability_db::{
+    self, CachedDb, DatabaseMetadata, DatabaseSource, LookupCache, SnapshotDb,
+};
+
+/// Compiled rule set and vulnerability database handles, cheap to clone
+#[derive(Clone)]
+pub struct AnalyzerContext {
+    pattern_matcher: PatternMatcher,
+    npm_db: Arc<dyn VulnerabilityDatabase>,
+    python_db: Arc<dyn VulnerabilityDatabase>,
+    java_db: Arc<dyn VulnerabilityDatabase>,
+    terraform_db: Arc<dyn VulnerabilityDatabase>,
+    /// Only a loaded snapshot covers crates; none is bundled
+    cargo_db: Option<Arc<dyn VulnerabilityDatabase>>,
+    /// Likewise for Go modules
+    go_db: Option<Arc<dyn VulnerabilityDatabase>>,
+    /// And for Ruby gems
+    ruby_db: Option<Arc<dyn VulnerabilityDatabase>>,
+    /// And for Composer packages
+    composer_db: Option<Arc<dyn VulnerabilityDatabase>>,
+    database: Arc<DatabaseMetadata>,
+}
+
+impl AnalyzerContext {
+    /// Context with the built-in patterns and the bundled databases
+    pub fn new() -> Result<Self> {
+        Self::with_databases([
+            vulnerability_db::create_npm_database()?.into(),
+            vulnerability_db::create_python_database()?.into(),
+            vulnerability_db::create_java_database()?.into(),
+            vulnerability_db::create_terraform_database()?.into(),
+        ])
+    }
+
+    /// Context with the built-in patterns and databases loaded from `db_path`
+    pub fn with_db_path(db_path: impl AsRef<Path>) -> Result<Self> {
+        let db_path = db_path.as_ref();
+        Self::with_databases([
+            vulnerability_db::create_npm_database_with_path(db_path)?.into(),
+            vulnerability_db::create_python_database_with_path(db_path)?.into(),
+            vulnerability_db::create_java_database_with_path(db_path)?.into(),
+            vulnerability_db::create_terraform_database_with_path(db_path)?.into(),
+        ])
+    }
+
+    /// Context with the built-in patterns and a database loaded from `source`
+    ///
+    /// One snapshot serves every ecosystem; see
+    /// [`PackageSecurityAnalyzer::with_database`](crate::PackageSecurityAnalyzer::with_database).
+    pub async fn with_database(source: DatabaseSource) -> Result<Self> {
+        let Some(snapshot) = source.load().await? else {
+            return Self::new();
+        };
+        let database = Arc::new(snapshot.metadata().clone());
+        let db: Arc<dyn VulnerabilityDatabase> = Arc::new(SnapshotDb::new(Arc::new(snapshot)));
+        Ok(Self {
+            pattern_matcher: PatternMatcher::new()?,
+            npm_db: db.clone(),
+            python_db: db.clone(),
+            java_db: db.clone(),
+            terraform_db: db.clone(),
+            cargo_db: Some(db.clone()),
+            go_db: Some(db.clone()),
+            ruby_db: Some(db.clone()),
+            composer_db: Some(db),
+            database,
+        })
+    }
+
+    /// Also scan for `patterns`, after the built-in ones
+    ///
+    /// Replaces any custom patterns added before.
+    pub fn with_custom_patterns(mut self, patterns: Vec<MaliciousPattern>) -> Result<Self> {
+        self.pattern_matcher = PatternMatcher::with_custom_patterns(patterns)?;
+        Ok(self)
+    }
+
+    /// Answer vulnerability lookups from `cache` first for every analyzer
+    /// built from this context
+    ///
+    /// Answers are kept for the cache's TTL; see [`CachedDb`].
+    pub fn with_lookup_cache(mut self, cache: Arc<LookupCache>) -> Self {
+        let cached = |db: Arc<dyn VulnerabilityDatabase>| -> Arc<dyn VulnerabilityDatabase> {
+            Arc::new(CachedDb::new(db, cache.clone()))
+        };
+        self.npm_db = cached(self.npm_db);
+        self.python_db = cached(self.python_db);
+        self.java_db = cached(self.java_db);
+        self.terraform_db = cached(self.terraform_db);
+        self.cargo_db = self.cargo_db.map(cached);
+        self.go_db = self.go_db.map(cached);
+        self.ruby_db = self.ruby_db.map(cached);
+        self.composer_db = self.composer_db.map(cached);
+        self
+    }
+
+    /// Compiled malicious-code patterns
+    pub fn pattern_matcher(&self) -> &PatternMatcher {
+        &self.pattern_matcher
+    }
+
+    /// Vulnerability database for an ecosystem, `None` for ecosystems without one
+    ///
+    /// Extensions use the npm database, and Maven projects the Java one.
+    /// Crates, Go modules, Ruby gems and Composer packages only have one when
+    /// the context was built from a snapshot.
+    pub fn vulnerability_db(&self, ecosystem: Ecosystem) -> Option<Arc<dyn VulnerabilityDatabase>> {
+        match ecosystem {
+            Ecosystem::Npm | Ecosystem::Extension => Some(self.npm_db.clone()),
+            Ecosystem::Python => Some(self.python_db.clone()),
+            Ecosystem::Java | Ecosystem::Maven => Some(self.java_db.clone()),
+            Ecosystem::Terraform => Some(self.terraform_db.clone()),
+            Ecosystem::Cargo => self.cargo_db.clone(),
+            Ecosystem::Go => self.go_db.clone(),
+            Ecosystem::Ruby => self.ruby_db.clone(),
+            Ecosystem::Composer => self.composer_db.clone(),
+            Ecosystem::Helm | Ecosystem::Unknown => None,
+        }
+    }
+
+    /// Version and size of the vulnerability database in use
+    pub fn database(&self) -> &DatabaseMetadata {
+        &self.database
+    }
+
+    fn with_databases(databases: [Arc<dyn VulnerabilityDatabase>; 4]) -> Result<Self> {
+        let database = Arc::new(DatabaseMetadata::bundled_from(
+            databases.iter().map(|db| db.as_ref()),
+        ));
+        let [npm_db, python_db, java_db, terraform_db] = databases;
+        Ok(Self {
+            pattern_matcher: PatternMatcher::new()?,
+            npm_db,
+            python_db,
+            java_db,
+            terraform_db,
+            cargo_db: None,
+            go_db: None,
+            ruby_db: None,
+            composer_db: None,
+            database,
+        })
+    }
+}
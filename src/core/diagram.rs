@@ -0,0 +1,223 @@
+//! Graphviz DOT and Mermaid renderings of a dependency tree
+//!
+//! Nodes are colored by the worst vulnerability affecting the package and
+//! every edge on a path from the root to a vulnerable package is highlighted,
+//! so reviewers can see how a vulnerable transitive dependency enters the
+//! tree. Direct edges are solid and transitive edges dashed. Output is sorted
+//! by depth and name, so the same tree always renders to the same text.
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+use super::{Dependency, DependencyAnalysis, DependencyGraph, RiskLevel};
+
+/// Color of highlighted edges leading to vulnerable packages
+const VULNERABLE_PATH_COLOR: &str = "#FF0000";
+
+/// A node of a [`DependencyDiagram`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagramNode {
+    pub name: String,
+    /// Shortest distance from the root package
+    pub depth: usize,
+    /// Worst vulnerability severity of the package, `Safe` when there is none
+    pub risk: RiskLevel,
+    /// Dependencies left out by the depth limit
+    pub hidden_dependencies: usize,
+}
+
+/// An edge of a [`DependencyDiagram`], `from` depending on `to`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagramEdge {
+    pub from: usize,
+    pub to: usize,
+    /// Declared by the root package itself
+    pub direct: bool,
+    /// On a path from the root to a vulnerable package
+    pub vulnerable_path: bool,
+}
+
+/// Dependency tree prepared for rendering
+#[derive(Debug, Clone, Default)]
+pub struct DependencyDiagram {
+    /// Nodes ordered by depth, then name; the root comes first
+    pub nodes: Vec<DiagramNode>,
+    /// Edges ordered by source node, then target node
+    pub edges: Vec<DiagramEdge>,
+}
+
+impl DependencyDiagram {
+    /// Lay out the tree of `root`, keeping packages at most `max_depth` edges away
+    pub fn build(root: &str, analysis: &DependencyAnalysis, max_depth: Option<usize>) -> Self {
+        let graph = DependencyGraph::from_analysis(root, analysis);
+
+        // Worst severity per package across every place it appears
+        let mut risk: HashMap<&str, RiskLevel> = HashMap::new();
+        let mut stack: Vec<&Dependency> = analysis.dependency_tree.iter().collect();
+        while let Some(dep) = stack.pop() {
+            let worst = dep
+                .vulnerabilities
+                .iter()
+                .map(|v| RiskLevel::from(v.severity.clone()))
+                .max()
+                .unwrap_or(RiskLevel::Safe);
+            let entry = risk.entry(dep.name.as_str()).or_insert(RiskLevel::Safe);
+            *entry = (*entry).max(worst);
+            stack.extend(&dep.dependencies);
+        }
+
+        // Breadth-first depths from the root
+        let mut depth: HashMap<&str, usize> = HashMap::from([(root, 0)]);
+        let mut queue = VecDeque::from([root]);
+        while let Some(name) = queue.pop_front() {
+            let next_depth = depth[name] + 1;
+            for dep in graph.dependencies_of(name) {
+                if !depth.contains_key(dep) {
+                    depth.insert(dep, next_depth);
+                    queue.push_back(dep);
+                }
+            }
+        }
+
+        let visible = |name: &str| max_depth.is_none_or(|max| depth[name] <= max);
+        let mut names: Vec<&str> = graph
+            .nodes()
+            .iter()
+            .map(String::as_str)
+            .filter(|name| depth.contains_key(name) && visible(name))
+            .collect();
+        names.sort_by_key(|name| (depth[name], *name));
+        // One node per package, whichever versions the graph holds
+        names.dedup();
+        let index: HashMap<&str, usize> = names.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+        // Packages that are vulnerable or lead to one
+        let mut leads_to_vulnerable: HashSet<&str> = risk
+            .iter()
+            .filter(|(_, level)| **level > RiskLevel::Safe)
+            .map(|(name, _)| *name)
+            .collect();
+        let mut frontier: Vec<&str> = leads_to_vulnerable.iter().copied().collect();
+        while let Some(name) = frontier.pop() {
+            for dependent in graph.dependents_of(name) {
+                if leads_to_vulnerable.insert(dependent) {
+                    frontier.push(dependent);
+                }
+            }
+        }
+
+        let mut diagram = Self::default();
+        let mut edges = BTreeSet::new();
+        for name in &names {
+            let dependencies = graph.dependencies_of(name);
+            let mut hidden = 0;
+            for dep in dependencies {
+                match index.get(dep) {
+                    Some(&to) => {
+                        edges.insert((index[name], to));
+                    }
+                    None => hidden += 1,
+                }
+            }
+            diagram.nodes.push(DiagramNode {
+                name: name.to_string(),
+                depth: depth[name],
+                risk: risk.get(name).copied().unwrap_or(RiskLevel::Safe),
+                hidden_dependencies: hidden,
+            });
+        }
+        diagram.edges = edges
+            .into_iter()
+            .map(|(from, to)| DiagramEdge {
+                from,
+                to,
+                direct: from == 0,
+                vulnerable_path: leads_to_vulnerable.contains(names[to]),
+            })
+            .collect();
+        diagram
+    }
+
+    fn label(node: &DiagramNode) -> String {
+        if node.hidden_dependencies > 0 {
+            format!("{} (+{})", node.name, node.hidden_dependencies)
+        } else {
+            node.name.clone()
+        }
+    }
+
+    /// Render as a Graphviz DOT digraph
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph dependencies {\n    rankdir=LR;\n");
+        out.push_str("    node [shape=box, style=\"rounded,filled\", fontname=\"Helvetica\"];\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let label = Self::label(node).replace('\\', "\\\\").replace('"', "\\\"");
+            let _ = write!(
+                out,
+                "    n{} [label=\"{}\", fillcolor=\"{}\"",
+                i,
+                label,
+                node.risk.color()
+            );
+            if i == 0 {
+                out.push_str(", penwidth=2");
+            }
+            out.push_str("];\n");
+        }
+        for edge in &self.edges {
+            let _ = write!(out, "    n{} -> n{}", edge.from, edge.to);
+            let mut attrs = Vec::new();
+            if !edge.direct {
+                attrs.push("style=dashed".to_string());
+            }
+            if edge.vulnerable_path {
+                attrs.push(format!("color=\"{}\", penwidth=2", VULNERABLE_PATH_COLOR));
+            }
+            if !attrs.is_empty() {
+                let _ = write!(out, " [{}]", attrs.join(", "));
+            }
+            out.push_str(";\n");
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render as a Mermaid flowchart
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart LR\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let label = Self::label(node).replace('"', "#quot;");
+            let _ = writeln!(out, "    n{}[\"{}\"]", i, label);
+        }
+        for edge in &self.edges {
+            let arrow = if edge.direct { "-->" } else { "-.->" };
+            let _ = writeln!(out, "    n{} {} n{}", edge.from, arrow, edge.to);
+        }
+
+        let mut levels: Vec<RiskLevel> = self.nodes.iter().map(|n| n.risk).collect();
+        levels.sort();
+        levels.dedup();
+        for level in levels {
+            let class = level.to_string().to_lowercase();
+            let _ = writeln!(out, "    classDef {} fill:{}", class, level.color());
+            let members: Vec<String> = self
+                .nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, n)| n.risk == level)
+                .map(|(i, _)| format!("n{}", i))
+                .collect();
+            let _ = writeln!(out, "    class {} {}", members.join(","), class);
+        }
+        for (i, edge) in self.edges.iter().enumerate() {
+            if edge.vulnerable_path {
+                let _ = writeln!(
+                    out,
+                    "    linkStyle {} stroke:{},stroke-width:2px",
+                    i, VULNERABLE_PATH_COLOR
+                );
+            }
+        }
+        out
+    }
+}
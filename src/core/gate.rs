@@ -0,0 +1,116 @@
+//! Pass/fail decisions for CI pipelines
+//!
+//! [`FailConditions`] lists what fails a build; evaluating it against a result
+//! gives a [`GateDecision`] naming every condition that was met, so a pipeline
+//! can print why it failed instead of only that it did.
+
+use serde::{Deserialize, Serialize};
+
+use super::{AnalysisResult, Dependency, RiskLevel};
+
+/// What fails the gate; the default fails nothing
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FailConditions {
+    /// Fail when the overall risk level is above this one
+    #[serde(default)]
+    pub max_risk_level: Option<RiskLevel>,
+    /// Fail on any vulnerability, in the package or a dependency, with a CVSS
+    /// score at least this; vulnerabilities without a score never match
+    #[serde(default)]
+    pub min_cvss: Option<f32>,
+    /// Fail when any malicious indicator is present
+    #[serde(default)]
+    pub malicious_indicators: bool,
+}
+
+/// Outcome of a gate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GateDecision {
+    pub passed: bool,
+    /// One line per condition met, empty when the gate passed
+    pub reasons: Vec<String>,
+}
+
+impl FailConditions {
+    /// Fail when the overall risk level is above `level`
+    pub fn risk_level_above(mut self, level: RiskLevel) -> Self {
+        self.max_risk_level = Some(level);
+        self
+    }
+
+    /// Fail on any vulnerability with a CVSS score of `score` or more
+    pub fn cvss_at_least(mut self, score: f32) -> Self {
+        self.min_cvss = Some(score);
+        self
+    }
+
+    /// Fail when any malicious indicator is present
+    pub fn any_malicious_indicator(mut self) -> Self {
+        self.malicious_indicators = true;
+        self
+    }
+
+    /// Decide whether `result` passes
+    pub fn evaluate<R: AnalysisResult + ?Sized>(&self, result: &R) -> GateDecision {
+        let mut reasons = Vec::new();
+
+        if let Some(max) = self.max_risk_level {
+            let level = result.overall_risk_level();
+            if level > max {
+                reasons.push(format!("Risk level {} is above {}", level, max));
+            }
+        }
+
+        if let Some(min) = self.min_cvss {
+            let package = result.package_info().name().to_string();
+            let mut vulnerable: Vec<(&str, _)> = result
+                .vulnerabilities()
+                .iter()
+                .map(|vulnerability| (package.as_str(), vulnerability))
+                .collect();
+            let mut stack: Vec<&Dependency> = result
+                .dependency_analysis()
+                .dependency_tree
+                .iter()
+                .rev()
+                .collect();
+            while let Some(dependency) = stack.pop() {
+                vulnerable.extend(
+                    dependency
+                        .vulnerabilities
+                        .iter()
+                        .map(|vulnerability| (dependency.name.as_str(), vulnerability)),
+                );
+                stack.extend(dependency.dependencies.iter().rev());
+            }
+            let mut reported = Vec::new();
+            for (name, vulnerability) in vulnerable {
+                let Some(score) = vulnerability.cvss_score.filter(|score| *score >= min) else {
+                    continue;
+                };
+                if reported.contains(&(name, &vulnerability.id)) {
+                    continue;
+                }
+                reported.push((name, &vulnerability.id));
+                reasons.push(format!(
+                    "{}: {} has CVSS {:.1}, at least {:.1}",
+                    name, vulnerability.id, score, min
+                ));
+            }
+        }
+
+        if self.malicious_indicators {
+            for pattern in result.malicious_indicators() {
+                reasons.push(format!(
+                    "Malicious indicator: {} ({:?})",
+                    pattern.pattern_name, pattern.severity
+                ));
+            }
+        }
+
+        GateDecision {
+            passed: reasons.is_empty(),
+            reasons,
+        }
+    }
+}
@@ -0,0 +1,355 @@
+//! Dependency graph built from a package's dependency tree
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::{rules, Dependency, DependencyAnalysis, Finding, FindingType, RiskLevel, ScanReason};
+use crate::utils::names::NameNormalization;
+
+/// Directed graph of packages
+///
+/// Nodes built from a dependency tree are keyed by package name and version
+/// (the resolved version, or the declared spec when nothing was resolved), so
+/// a package that appears on several paths collapses into a single node and
+/// cycles become visible, while two versions of a package stay apart. Queries
+/// take a package name and cover every version of it. All traversals are
+/// iterative and track visited nodes, so cyclic input is safe.
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+    nodes: Vec<String>,
+    versions: Vec<Option<String>>,
+    index: HashMap<(String, Option<String>), usize>,
+    by_name: HashMap<String, Vec<usize>>,
+    edges: Vec<Vec<usize>>,
+}
+
+impl DependencyGraph {
+    /// Create an empty graph
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a graph rooted at `root` from a dependency analysis
+    pub fn from_analysis(root: &str, analysis: &DependencyAnalysis) -> Self {
+        Self::from_analysis_normalized(root, analysis, NameNormalization::Exact)
+    }
+
+    /// Build a graph whose nodes are keyed by names normalized under `normalization`
+    ///
+    /// Spellings the ecosystem treats as the same package (`Foo_Bar`, `foo-bar`)
+    /// collapse into one node. A direct dependency named like `root` is the
+    /// root itself; deeper ones are other copies of the package.
+    pub fn from_analysis_normalized(
+        root: &str,
+        analysis: &DependencyAnalysis,
+        normalization: NameNormalization,
+    ) -> Self {
+        let mut graph = Self::new();
+        let root_name = normalization.normalize(root);
+        let root = graph.add_node(&root_name);
+
+        let mut stack: Vec<(usize, &Dependency)> = analysis
+            .dependency_tree
+            .iter()
+            .map(|dep| (root, dep))
+            .collect();
+        while let Some((parent, dep)) = stack.pop() {
+            let name = normalization.normalize(&dep.name);
+            let node = if parent == root && name == root_name {
+                root
+            } else {
+                let version = dep.resolved_version.as_ref().unwrap_or(&dep.version_spec);
+                graph.add_versioned_node(&name, Some(version))
+            };
+            graph.add_edge_by_index(parent, node);
+            stack.extend(dep.dependencies.iter().map(|child| (node, child)));
+        }
+
+        graph
+    }
+
+    /// Add a node, returning its index
+    pub fn add_node(&mut self, name: &str) -> usize {
+        self.add_versioned_node(name, None)
+    }
+
+    fn add_versioned_node(&mut self, name: &str, version: Option<&String>) -> usize {
+        let key = (name.to_string(), version.cloned());
+        if let Some(&index) = self.index.get(&key) {
+            return index;
+        }
+        let index = self.nodes.len();
+        self.nodes.push(name.to_string());
+        self.versions.push(version.cloned());
+        self.edges.push(Vec::new());
+        self.by_name
+            .entry(name.to_string())
+            .or_default()
+            .push(index);
+        self.index.insert(key, index);
+        index
+    }
+
+    /// Add an edge meaning `from` depends on `to`
+    pub fn add_edge(&mut self, from: &str, to: &str) {
+        let from = self.add_node(from);
+        let to = self.add_node(to);
+        self.add_edge_by_index(from, to);
+    }
+
+    fn add_edge_by_index(&mut self, from: usize, to: usize) {
+        if !self.edges[from].contains(&to) {
+            self.edges[from].push(to);
+        }
+    }
+
+    /// Package names in the graph, once per version
+    pub fn nodes(&self) -> &[String] {
+        &self.nodes
+    }
+
+    /// Nodes for every version of `name`
+    fn indices_of(&self, name: &str) -> &[usize] {
+        self.by_name
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Names of `nodes` in order, each listed once
+    fn names(&self, nodes: impl IntoIterator<Item = usize>) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        nodes
+            .into_iter()
+            .map(|i| self.nodes[i].as_str())
+            .filter(|name| seen.insert(*name))
+            .collect()
+    }
+
+    /// `name@version` for nodes with a version, the name otherwise
+    fn label(&self, node: usize) -> String {
+        match &self.versions[node] {
+            Some(version) => format!("{}@{}", self.nodes[node], version),
+            None => self.nodes[node].clone(),
+        }
+    }
+
+    /// Direct dependencies of a package
+    pub fn dependencies_of(&self, name: &str) -> Vec<&str> {
+        self.names(
+            self.indices_of(name)
+                .iter()
+                .flat_map(|&i| self.edges[i].iter().copied()),
+        )
+    }
+
+    /// Packages that directly depend on `name`
+    pub fn dependents_of(&self, name: &str) -> Vec<&str> {
+        let targets = self.indices_of(name);
+        self.names(
+            self.edges
+                .iter()
+                .enumerate()
+                .filter(|(_, edges)| edges.iter().any(|to| targets.contains(to)))
+                .map(|(i, _)| i),
+        )
+    }
+
+    /// Every package reachable from `name`, excluding `name` unless some
+    /// version of it is reachable, as on a cycle
+    pub fn transitive_dependencies(&self, name: &str) -> Vec<&str> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut queue: VecDeque<usize> = self
+            .indices_of(name)
+            .iter()
+            .flat_map(|&i| self.edges[i].iter().copied())
+            .collect();
+        let mut reached = Vec::new();
+        while let Some(node) = queue.pop_front() {
+            if std::mem::replace(&mut seen[node], true) {
+                continue;
+            }
+            reached.push(node);
+            queue.extend(self.edges[node].iter().copied());
+        }
+        self.names(reached)
+    }
+
+    /// Length of the longest shortest-path from `name` to any reachable package
+    pub fn depth_from(&self, name: &str) -> usize {
+        let mut distance = vec![usize::MAX; self.nodes.len()];
+        let mut queue = VecDeque::new();
+        for &start in self.indices_of(name) {
+            distance[start] = 0;
+            queue.push_back(start);
+        }
+        let mut deepest = 0;
+        while let Some(node) = queue.pop_front() {
+            for &next in &self.edges[node] {
+                if distance[next] == usize::MAX {
+                    distance[next] = distance[node] + 1;
+                    deepest = deepest.max(distance[next]);
+                    queue.push_back(next);
+                }
+            }
+        }
+        deepest
+    }
+
+    /// Find dependency cycles, each listed as the path that returns to its first node
+    ///
+    /// Uses Tarjan's strongly connected components algorithm; every component with
+    /// more than one node, or a node depending on itself, yields one cycle.
+    pub fn find_cycles(&self) -> Vec<Vec<String>> {
+        self.cycle_nodes()
+            .into_iter()
+            .map(|cycle| cycle.into_iter().map(|i| self.nodes[i].clone()).collect())
+            .collect()
+    }
+
+    /// Node indices of each cycle
+    fn cycle_nodes(&self) -> Vec<Vec<usize>> {
+        let mut cycles = Vec::new();
+        for component in self.strongly_connected_components() {
+            let self_loop =
+                component.len() == 1 && self.edges[component[0]].contains(&component[0]);
+            if component.len() > 1 || self_loop {
+                let start = *component.iter().min().expect("component is non-empty");
+                let members: HashSet<usize> = component.iter().copied().collect();
+                cycles.push(self.cycle_through(start, &members));
+            }
+        }
+        cycles
+    }
+
+    /// Report each cycle as a `DependencyCycle` finding
+    ///
+    /// The evidence spells out the versions along the cycle.
+    pub fn cycle_findings(&self) -> Vec<Finding> {
+        self.cycle_nodes()
+            .into_iter()
+            .map(|cycle| {
+                let path = cycle
+                    .iter()
+                    .chain(cycle.first())
+                    .map(|&i| self.label(i))
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                let cycle = self.names(cycle);
+                let (title, description) = if cycle.len() == 1 {
+                    (
+                        format!("Package '{}' depends on itself", cycle[0]),
+                        "A self-referential dependency is usually a mistake and can be used to confuse resolvers"
+                            .to_string(),
+                    )
+                } else {
+                    (
+                        format!("Dependency cycle between {} packages", cycle.len()),
+                        "Packages that depend on each other form a cycle that can confuse resolvers and tooling"
+                            .to_string(),
+                    )
+                };
+                let mut finding = Finding::new(
+                    FindingType::DependencyCycle,
+                    RiskLevel::Medium,
+                    title,
+                    description,
+                )
+                .with_evidence(path)
+                .with_scan_reason(ScanReason::DependencyAnalysis)
+                .with_rule_id(rules::DEPENDENCY_CYCLE);
+                for node in cycle {
+                    finding = finding.with_affected_component(node);
+                }
+                finding
+            })
+            .collect()
+    }
+
+    /// Shortest cycle from `start` back to itself, staying within `members`
+    fn cycle_through(&self, start: usize, members: &HashSet<usize>) -> Vec<usize> {
+        if self.edges[start].contains(&start) {
+            return vec![start];
+        }
+        let mut parent: HashMap<usize, usize> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            for &next in &self.edges[node] {
+                if next == start {
+                    let mut path = vec![node];
+                    let mut current = node;
+                    while let Some(&p) = parent.get(&current) {
+                        path.push(p);
+                        current = p;
+                    }
+                    path.reverse();
+                    return path;
+                }
+                if members.contains(&next) && next != start && !parent.contains_key(&next) {
+                    parent.insert(next, node);
+                    queue.push_back(next);
+                }
+            }
+        }
+        vec![start]
+    }
+
+    /// Iterative Tarjan's algorithm
+    fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        let n = self.nodes.len();
+        let mut index = vec![usize::MAX; n];
+        let mut lowlink = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut counter = 0;
+
+        for root in 0..n {
+            if index[root] != usize::MAX {
+                continue;
+            }
+            // Each frame is (node, next edge to visit)
+            let mut frames = vec![(root, 0usize)];
+            index[root] = counter;
+            lowlink[root] = counter;
+            counter += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some(frame) = frames.last_mut() {
+                let node = frame.0;
+                if let Some(&next) = self.edges[node].get(frame.1) {
+                    frame.1 += 1;
+                    if index[next] == usize::MAX {
+                        index[next] = counter;
+                        lowlink[next] = counter;
+                        counter += 1;
+                        stack.push(next);
+                        on_stack[next] = true;
+                        frames.push((next, 0));
+                    } else if on_stack[next] {
+                        lowlink[node] = lowlink[node].min(index[next]);
+                    }
+                    continue;
+                }
+
+                frames.pop();
+                if let Some(&(parent, _)) = frames.last() {
+                    lowlink[parent] = lowlink[parent].min(lowlink[node]);
+                }
+                if lowlink[node] == index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+}
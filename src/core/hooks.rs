@@ -0,0 +1,36 @@
+//! Code a package runs while it is being installed
+//!
+//! npm lifecycle scripts, `setup.py` command classes, Cargo build scripts,
+//! native gem extensions, Composer scripts and Helm hooks all run on the
+//! installing machine before anyone has used the package, which makes them
+//! the first place an attack goes. Results list them whether or not a
+//! malicious pattern matched, so reviewers can read them for themselves.
+
+use serde::{Deserialize, Serialize};
+
+/// Code run while a package is installed, built or deployed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallHook {
+    /// Event the code runs on, such as npm's `postinstall` or Helm's
+    /// `pre-install`
+    pub hook: String,
+    /// Command as written; for hooks that run code rather than a command line,
+    /// the class or file holding it
+    pub command: String,
+    /// File declaring the hook, relative to the package root
+    pub source: String,
+}
+
+impl InstallHook {
+    pub fn new(
+        hook: impl Into<String>,
+        command: impl Into<String>,
+        source: impl Into<String>,
+    ) -> Self {
+        Self {
+            hook: hook.into(),
+            command: command.into(),
+            source: source.into(),
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Detection and removal of hidden characters in package metadata
+//!
+//! Registry metadata is shown to reviewers in terminals and web UIs. Control
+//! characters and ANSI escape sequences in a description can rewrite what a
+//! terminal displays, bidirectional overrides reorder text so it reads
+//! differently from what it is, and zero-width characters make two names that
+//! look identical compare differently. Each is reported, and the fields can be
+//! sanitized so reports built from the result cannot carry them further.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fmt;
+
+use super::{rules, Finding, FindingType, PackageMetadata, RiskLevel, ScanReason};
+
+/// CSI and OSC escape sequences, and any other escape followed by one character
+static ANSI_ESCAPE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\x1b(?:\[[0-?]*[ -/]*[@-~]|\][^\x07\x1b]*(?:\x07|\x1b\\)?|[@-_])").unwrap()
+});
+
+/// Kind of hidden character found in a field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HiddenCharacter {
+    /// Invisible characters such as zero-width spaces and joiners
+    ZeroWidth,
+    /// C0 and C1 control characters other than tab and line breaks
+    Control,
+    /// Bidirectional embeddings, overrides, isolates and marks
+    Bidi,
+    /// Terminal escape sequences
+    AnsiEscape,
+}
+
+impl HiddenCharacter {
+    /// Classify a single character; escape sequences are matched separately
+    pub fn of(c: char) -> Option<Self> {
+        match c {
+            '\u{200b}'..='\u{200d}' | '\u{2060}' | '\u{feff}' | '\u{180e}' => Some(Self::ZeroWidth),
+            '\u{200e}'
+            | '\u{200f}'
+            | '\u{061c}'
+            | '\u{202a}'..='\u{202e}'
+            | '\u{2066}'..='\u{2069}' => Some(Self::Bidi),
+            '\t' | '\n' | '\r' => None,
+            c if c.is_control() => Some(Self::Control),
+            _ => None,
+        }
+    }
+
+    fn severity(&self) -> RiskLevel {
+        match self {
+            Self::AnsiEscape | Self::Bidi => RiskLevel::High,
+            Self::Control => RiskLevel::Medium,
+            Self::ZeroWidth => RiskLevel::Low,
+        }
+    }
+}
+
+impl fmt::Display for HiddenCharacter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroWidth => write!(f, "zero-width characters"),
+            Self::Control => write!(f, "control characters"),
+            Self::Bidi => write!(f, "bidirectional text controls"),
+            Self::AnsiEscape => write!(f, "ANSI escape sequences"),
+        }
+    }
+}
+
+/// Hidden characters in one metadata field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InjectedField {
+    /// Field name, such as `description` or `keywords[2]`
+    pub field: String,
+    pub kinds: Vec<HiddenCharacter>,
+    /// The value with every hidden character shown as a `\u{..}` escape
+    pub escaped: String,
+}
+
+/// Metadata fields containing hidden characters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataInjection {
+    pub fields: Vec<InjectedField>,
+}
+
+impl MetadataInjection {
+    /// Check every string field of `metadata`
+    pub fn check(metadata: &PackageMetadata) -> Option<Self> {
+        let fields: Vec<InjectedField> = string_fields(metadata)
+            .into_iter()
+            .filter_map(|(field, value)| {
+                let kinds = hidden_characters(value);
+                (!kinds.is_empty()).then(|| InjectedField {
+                    field,
+                    kinds,
+                    escaped: escape(value),
+                })
+            })
+            .collect();
+        (!fields.is_empty()).then_some(Self { fields })
+    }
+
+    /// Report as a `MetadataInjection` finding
+    ///
+    /// High for escape sequences and bidirectional controls, which change
+    /// what a reviewer sees; medium for other control characters; low for
+    /// zero-width characters alone.
+    pub fn to_finding(&self, package: &str) -> Finding {
+        let kinds: Vec<HiddenCharacter> = self
+            .fields
+            .iter()
+            .flat_map(|field| field.kinds.iter().copied())
+            .fold(Vec::new(), |mut unique, kind| {
+                if !unique.contains(&kind) {
+                    unique.push(kind);
+                }
+                unique
+            });
+        let severity = kinds
+            .iter()
+            .map(HiddenCharacter::severity)
+            .max()
+            .unwrap_or(RiskLevel::Low);
+        let names: Vec<&str> = self.fields.iter().map(|f| f.field.as_str()).collect();
+
+        let mut finding = Finding::new(
+            FindingType::MetadataInjection,
+            severity,
+            format!("Hidden characters in {} metadata", sanitize(package)),
+            format!(
+                "{} contain {}, which can alter how the package appears in terminals and registry pages",
+                names.join(", "),
+                kinds
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        )
+        .with_affected_component(sanitize(package))
+        .with_scan_reason(ScanReason::Manifest)
+        .with_rule_id(rules::METADATA_INJECTION);
+        for field in &self.fields {
+            finding = finding.with_evidence(format!("{}: {}", field.field, field.escaped));
+        }
+        finding
+    }
+
+    /// Check `metadata`, then sanitize it when `sanitize_metadata` is set
+    ///
+    /// Returns the finding for what was found, computed before sanitizing.
+    pub fn apply(metadata: &mut PackageMetadata, sanitize_metadata: bool) -> Option<Finding> {
+        let finding = Self::check(metadata).map(|injection| injection.to_finding(&metadata.name));
+        if sanitize_metadata && finding.is_some() {
+            sanitize_metadata_fields(metadata);
+        }
+        finding
+    }
+}
+
+/// Remove escape sequences and hidden characters from `text`
+///
+/// Tabs and line breaks are kept.
+pub fn sanitize(text: &str) -> String {
+    ANSI_ESCAPE
+        .replace_all(text, "")
+        .chars()
+        .filter(|c| HiddenCharacter::of(*c).is_none())
+        .collect()
+}
+
+/// Show every hidden character in `text` as a `\u{..}` escape
+pub fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match HiddenCharacter::of(c) {
+            Some(_) => c.escape_unicode().to_string(),
+            None => c.to_string(),
+        })
+        .collect()
+}
+
+/// Kinds of hidden characters in `text`, most severe last
+fn hidden_characters(text: &str) -> Vec<HiddenCharacter> {
+    let mut kinds: Vec<HiddenCharacter> = text.chars().filter_map(HiddenCharacter::of).collect();
+    if ANSI_ESCAPE.is_match(text) {
+        kinds.retain(|kind| *kind != HiddenCharacter::Control || has_bare_control(text));
+        kinds.push(HiddenCharacter::AnsiEscape);
+    }
+    kinds.sort();
+    kinds.dedup();
+    kinds
+}
+
+/// Whether control characters remain once escape sequences are removed
+fn has_bare_control(text: &str) -> bool {
+    ANSI_ESCAPE
+        .replace_all(text, "")
+        .chars()
+        .any(|c| HiddenCharacter::of(c) == Some(HiddenCharacter::Control))
+}
+
+fn string_fields(metadata: &PackageMetadata) -> Vec<(String, &str)> {
+    let optional = [
+        ("description", &metadata.description),
+        ("author", &metadata.author),
+        ("license", &metadata.license),
+        ("homepage", &metadata.homepage),
+        ("repository", &metadata.repository),
+        ("publish_date", &metadata.publish_date),
+    ];
+    [
+        ("name".to_string(), metadata.name.as_str()),
+        ("version".to_string(), metadata.version.as_str()),
+    ]
+    .into_iter()
+    .chain(
+        optional
+            .into_iter()
+            .filter_map(|(field, value)| Some((field.to_string(), value.as_deref()?))),
+    )
+    .chain(
+        metadata
+            .keywords
+            .iter()
+            .enumerate()
+            .map(|(i, keyword)| (format!("keywords[{}]", i), keyword.as_str())),
+    )
+    .collect()
+}
+
+fn sanitize_metadata_fields(metadata: &mut PackageMetadata) {
+    for value in [&mut metadata.name, &mut metadata.version]
+        .into_iter()
+        .chain(metadata.keywords.iter_mut())
+        .chain(
+            [
+                &mut metadata.description,
+                &mut metadata.author,
+                &mut metadata.license,
+                &mut metadata.homepage,
+                &mut metadata.repository,
+                &mut metadata.publish_date,
+            ]
+            .into_iter()
+            .filter_map(Option::as_mut),
+        )
+    {
+        *value = sanitize(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hidden_characters() {
+        assert_eq!(
+            hidden_characters("Fast \x1b[2J\x1b[1;32mtrusted\x1b[0m logger"),
+            [HiddenCharacter::AnsiEscape]
+        );
+        assert_eq!(
+            sanitize("Fast \x1b[2J\x1b[1;32mtrusted\x1b[0m logger"),
+            "Fast trusted logger"
+        );
+        assert_eq!(
+            hidden_characters("left\u{202e}thgir\u{200b}"),
+            [HiddenCharacter::ZeroWidth, HiddenCharacter::Bidi]
+        );
+        assert_eq!(escape("a\u{202e}b"), "a\\u{202e}b");
+        assert_eq!(
+            hidden_characters("bell\x07 and\tline\nbreaks"),
+            [HiddenCharacter::Control]
+        );
+        // Non-Latin scripts and ordinary whitespace are left alone
+        assert!(hidden_characters("高速なロガー\tfor\r\nNode").is_empty());
+        assert_eq!(sanitize("שלום world"), "שלום world");
+    }
+}
@@ -0,0 +1,535 @@
+//! Organization policy on which dependency licenses are acceptable
+//!
+//! Like [`super::SourcePolicy`], this enforces an explicit rule rather than a
+//! heuristic: legal review decides which SPDX licenses may ship, and every
+//! dependency whose declared license falls outside that is reported. Licenses
+//! are SPDX expressions such as `MIT OR Apache-2.0` or
+//! `GPL-2.0-only WITH Classpath-exception-2.0`. A choice (`OR`) takes the
+//! best alternative and a combination (`AND`) the worst part.
+//!
+//! Separately, [`LicenseCategories`] sorts licenses into strong and weak
+//! copyleft, to find copyleft code a permissively licensed package pulls in
+//! through its dependencies.
+//!
+//! Dependencies only carry a license where the lockfile records one: npm's
+//! `package-lock.json` (v2 and later) and `composer.lock`. Elsewhere every
+//! dependency counts as unknown.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+
+use super::{Dependency, DependencyAnalysis};
+
+/// Licenses that say nothing about the terms
+const UNKNOWN_LICENSES: &[&str] = &["NOASSERTION", "UNKNOWN"];
+
+/// Copyleft SPDX license families, named by the identifier up to the first
+/// `-` (`GPL-3.0-or-later` is `GPL`)
+const COPYLEFT_FAMILIES: &[(&str, CopyleftStrength)] = &[
+    ("AGPL", CopyleftStrength::Strong),
+    ("GPL", CopyleftStrength::Strong),
+    ("OSL", CopyleftStrength::Strong),
+    ("SSPL", CopyleftStrength::Strong),
+    ("LGPL", CopyleftStrength::Weak),
+    ("MPL", CopyleftStrength::Weak),
+    ("EPL", CopyleftStrength::Weak),
+    ("CDDL", CopyleftStrength::Weak),
+];
+
+/// Exceptions that let code link against a strong copyleft library without
+/// taking on its license
+const LINKING_EXCEPTIONS: &[&str] = &[
+    "Classpath-exception-2.0",
+    "GCC-exception-2.0",
+    "GCC-exception-3.1",
+];
+
+/// What the policy makes of a license, from worst to best
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum LicenseDisposition {
+    /// The license may not ship
+    Deny,
+    /// The license needs legal review before it ships
+    #[default]
+    Review,
+    /// The license may ship
+    Allow,
+}
+
+/// SPDX licenses dependencies may or may not use
+///
+/// Identifiers compare without regard to case. An identifier with an
+/// exception, such as `GPL-2.0-only WITH Classpath-exception-2.0`, is looked
+/// up with its exception first, then on its own. The denylist wins over the
+/// allowlist.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LicensePolicy {
+    #[serde(default)]
+    pub allowed: Vec<String>,
+    #[serde(default)]
+    pub denied: Vec<String>,
+    /// Disposition of a license on neither list; `None` reviews it when the
+    /// allowlist is non-empty and allows it otherwise
+    #[serde(default)]
+    pub default_disposition: Option<LicenseDisposition>,
+    /// Disposition of a missing license or one that is not an SPDX
+    /// expression, such as `SEE LICENSE IN LICENSE.txt`
+    #[serde(default)]
+    pub unknown_disposition: LicenseDisposition,
+}
+
+/// A dependency whose license the policy does not allow
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LicenseFinding {
+    pub package: String,
+    pub version: Option<String>,
+    /// License as declared, `None` when there is none
+    pub license: Option<String>,
+    pub disposition: LicenseDisposition,
+    /// Licenses the disposition rests on: the denied part of a combination,
+    /// or every alternative of a choice
+    pub licenses: Vec<String>,
+}
+
+/// Obligations a copyleft license puts on code that uses it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyleftStrength {
+    /// Changes to the licensed files themselves must be shared (LGPL, MPL)
+    Weak,
+    /// Anything distributed with the licensed code must be shared under it
+    /// (GPL; AGPL also when offered over a network)
+    Strong,
+}
+
+/// Which licenses are copyleft, and how strongly
+///
+/// The bundled table covers the GPL, AGPL, LGPL, MPL, EPL, CDDL, OSL and SSPL
+/// families; a strong copyleft license with a linking exception such as
+/// `Classpath-exception-2.0` counts as weak. Overrides take precedence, keyed
+/// by SPDX identifier (with its exception, if any) or by family such as
+/// `MPL`, the identifier winning over its family.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LicenseCategories {
+    /// Strength per identifier or family; `None` marks it not copyleft
+    #[serde(default)]
+    pub overrides: BTreeMap<String, Option<CopyleftStrength>>,
+}
+
+/// A copyleft dependency and how the package comes to depend on it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CopyleftDependency {
+    pub package: String,
+    pub version: Option<String>,
+    pub license: String,
+    pub strength: CopyleftStrength,
+    /// Names from the direct dependency down to this package
+    pub path: Vec<String>,
+}
+
+/// A parsed SPDX license expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expression {
+    License {
+        id: String,
+        exception: Option<String>,
+    },
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+}
+
+impl LicensePolicy {
+    /// Allow an SPDX identifier
+    pub fn allow(mut self, license: impl Into<String>) -> Self {
+        self.allowed.push(license.into());
+        self
+    }
+
+    /// Deny an SPDX identifier
+    pub fn deny(mut self, license: impl Into<String>) -> Self {
+        self.denied.push(license.into());
+        self
+    }
+
+    /// Whether the policy restricts anything
+    pub fn is_empty(&self) -> bool {
+        self.allowed.is_empty()
+            && self.denied.is_empty()
+            && self.default_disposition.is_none()
+            && self.unknown_disposition == LicenseDisposition::default()
+    }
+
+    /// Disposition of `license`, with the licenses it rests on
+    pub fn evaluate(&self, license: Option<&str>) -> (LicenseDisposition, Vec<String>) {
+        let expression = license
+            .map(str::trim)
+            .filter(|license| {
+                !UNKNOWN_LICENSES
+                    .iter()
+                    .any(|u| u.eq_ignore_ascii_case(license))
+            })
+            .and_then(Expression::parse);
+        match expression {
+            Some(expression) => self.disposition(&expression),
+            None => (
+                self.unknown_disposition,
+                license.map(str::to_string).into_iter().collect(),
+            ),
+        }
+    }
+
+    /// Findings for every dependency in `analysis`, transitive ones
+    /// included, whose license is not allowed
+    pub fn findings(&self, analysis: &DependencyAnalysis) -> Vec<LicenseFinding> {
+        if self.is_empty() {
+            return vec![];
+        }
+        let mut findings = Vec::new();
+        let mut nodes: Vec<&Dependency> = analysis.dependency_tree.iter().rev().collect();
+        while let Some(dependency) = nodes.pop() {
+            let (disposition, licenses) = self.evaluate(dependency.license.as_deref());
+            if disposition != LicenseDisposition::Allow {
+                findings.push(LicenseFinding {
+                    package: dependency.name.clone(),
+                    version: dependency.resolved_version.clone(),
+                    license: dependency.license.clone(),
+                    disposition,
+                    licenses,
+                });
+            }
+            nodes.extend(dependency.dependencies.iter().rev());
+        }
+        findings
+    }
+
+    fn disposition(&self, expression: &Expression) -> (LicenseDisposition, Vec<String>) {
+        match expression {
+            Expression::License { id, exception } => {
+                let full = exception
+                    .as_ref()
+                    .map(|exception| format!("{} WITH {}", id, exception));
+                let listed = |list: &[String]| {
+                    list.iter().any(|entry| {
+                        let entry = entry.split_whitespace().collect::<Vec<_>>().join(" ");
+                        full.as_ref()
+                            .is_some_and(|full| entry.eq_ignore_ascii_case(full))
+                            || entry.eq_ignore_ascii_case(id)
+                    })
+                };
+                let disposition = if listed(&self.denied) {
+                    LicenseDisposition::Deny
+                } else if listed(&self.allowed) {
+                    LicenseDisposition::Allow
+                } else {
+                    self.default_disposition
+                        .unwrap_or(if self.allowed.is_empty() {
+                            LicenseDisposition::Allow
+                        } else {
+                            LicenseDisposition::Review
+                        })
+                };
+                (disposition, vec![full.unwrap_or_else(|| id.clone())])
+            }
+            Expression::And(left, right) | Expression::Or(left, right) => {
+                let parts = [self.disposition(left), self.disposition(right)];
+                let picked = if matches!(expression, Expression::Or(..)) {
+                    parts[0].0.max(parts[1].0)
+                } else {
+                    parts[0].0.min(parts[1].0)
+                };
+                let licenses = parts
+                    .into_iter()
+                    .filter(|(disposition, _)| *disposition == picked)
+                    .flat_map(|(_, licenses)| licenses)
+                    .collect();
+                (picked, licenses)
+            }
+        }
+    }
+}
+
+impl LicenseCategories {
+    /// Override the strength of an identifier or family; `None` marks it not
+    /// copyleft
+    pub fn with(mut self, license: impl Into<String>, strength: Option<CopyleftStrength>) -> Self {
+        self.overrides.insert(license.into(), strength);
+        self
+    }
+
+    /// Copyleft strength of a license expression; `None` when it is not
+    /// copyleft or not an SPDX expression
+    ///
+    /// A choice (`OR`) takes the least restrictive alternative and a
+    /// combination (`AND`) the most restrictive part.
+    pub fn classify(&self, license: &str) -> Option<CopyleftStrength> {
+        Expression::parse(license).and_then(|expression| self.strength(&expression))
+    }
+
+    /// Copyleft dependencies in `analysis`, transitive ones included, each
+    /// with the shortest path to it
+    ///
+    /// A package installed at the same version along several paths is
+    /// reported once.
+    pub fn copyleft_dependencies(&self, analysis: &DependencyAnalysis) -> Vec<CopyleftDependency> {
+        let mut found = Vec::new();
+        let mut seen = HashSet::new();
+        let mut queue: VecDeque<(&Dependency, Vec<String>)> = analysis
+            .dependency_tree
+            .iter()
+            .map(|dependency| (dependency, vec![dependency.name.clone()]))
+            .collect();
+        while let Some((dependency, path)) = queue.pop_front() {
+            for child in &dependency.dependencies {
+                let mut child_path = path.clone();
+                child_path.push(child.name.clone());
+                queue.push_back((child, child_path));
+            }
+            let Some(license) = dependency.license.as_deref() else {
+                continue;
+            };
+            let Some(strength) = self.classify(license) else {
+                continue;
+            };
+            if seen.insert((&dependency.name, &dependency.resolved_version)) {
+                found.push(CopyleftDependency {
+                    package: dependency.name.clone(),
+                    version: dependency.resolved_version.clone(),
+                    license: license.to_string(),
+                    strength,
+                    path,
+                });
+            }
+        }
+        found
+    }
+
+    fn strength(&self, expression: &Expression) -> Option<CopyleftStrength> {
+        match expression {
+            Expression::License { id, exception } => {
+                let family = id.split('-').next().unwrap_or(id);
+                let full = exception
+                    .as_ref()
+                    .map(|exception| format!("{} WITH {}", id, exception));
+                let overridden = |key: &str| {
+                    self.overrides
+                        .iter()
+                        .find(|(entry, _)| {
+                            entry
+                                .split_whitespace()
+                                .collect::<Vec<_>>()
+                                .join(" ")
+                                .eq_ignore_ascii_case(key)
+                        })
+                        .map(|(_, strength)| *strength)
+                };
+                if let Some(strength) = full.as_deref().and_then(overridden) {
+                    return strength;
+                }
+                let strength = match overridden(id).or_else(|| overridden(family)) {
+                    Some(strength) => strength,
+                    None => COPYLEFT_FAMILIES
+                        .iter()
+                        .find(|(name, _)| name.eq_ignore_ascii_case(family))
+                        .map(|(_, strength)| *strength),
+                };
+                let linking = exception.as_ref().is_some_and(|exception| {
+                    LINKING_EXCEPTIONS
+                        .iter()
+                        .any(|e| e.eq_ignore_ascii_case(exception))
+                });
+                strength.map(|strength| {
+                    if linking {
+                        CopyleftStrength::Weak
+                    } else {
+                        strength
+                    }
+                })
+            }
+            Expression::And(left, right) => self.strength(left).max(self.strength(right)),
+            Expression::Or(left, right) => self.strength(left).min(self.strength(right)),
+        }
+    }
+}
+
+impl Expression {
+    /// Parse an SPDX expression; `None` when it is not one
+    fn parse(expression: &str) -> Option<Self> {
+        let spaced = expression.replace('(', " ( ").replace(')', " ) ");
+        let tokens: Vec<&str> = spaced.split_whitespace().collect();
+        let mut pos = 0;
+        let parsed = Self::or_expr(&tokens, &mut pos)?;
+        (pos == tokens.len()).then_some(parsed)
+    }
+
+    fn or_expr(tokens: &[&str], pos: &mut usize) -> Option<Self> {
+        let mut expression = Self::and_expr(tokens, pos)?;
+        while tokens
+            .get(*pos)
+            .is_some_and(|t| t.eq_ignore_ascii_case("OR"))
+        {
+            *pos += 1;
+            expression = Self::Or(Box::new(expression), Box::new(Self::and_expr(tokens, pos)?));
+        }
+        Some(expression)
+    }
+
+    fn and_expr(tokens: &[&str], pos: &mut usize) -> Option<Self> {
+        let mut expression = Self::atom(tokens, pos)?;
+        while tokens
+            .get(*pos)
+            .is_some_and(|t| t.eq_ignore_ascii_case("AND"))
+        {
+            *pos += 1;
+            expression = Self::And(Box::new(expression), Box::new(Self::atom(tokens, pos)?));
+        }
+        Some(expression)
+    }
+
+    fn atom(tokens: &[&str], pos: &mut usize) -> Option<Self> {
+        let token = *tokens.get(*pos)?;
+        *pos += 1;
+        if token == "(" {
+            let expression = Self::or_expr(tokens, pos)?;
+            (*tokens.get(*pos)? == ")").then(|| *pos += 1)?;
+            return Some(expression);
+        }
+        if !is_identifier(token) {
+            return None;
+        }
+        let exception = if tokens
+            .get(*pos)
+            .is_some_and(|t| t.eq_ignore_ascii_case("WITH"))
+        {
+            let exception = *tokens.get(*pos + 1)?;
+            if !is_identifier(exception) {
+                return None;
+            }
+            *pos += 2;
+            Some(exception.to_string())
+        } else {
+            None
+        };
+        Some(Self::License {
+            id: token.to_string(),
+            exception,
+        })
+    }
+}
+
+/// Whether `token` can be an SPDX license or exception identifier
+fn is_identifier(token: &str) -> bool {
+    !["AND", "OR", "WITH"]
+        .iter()
+        .any(|op| op.eq_ignore_ascii_case(token))
+        && token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '+' | ':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> LicensePolicy {
+        LicensePolicy::default()
+            .allow("MIT")
+            .allow("Apache-2.0")
+            .allow("GPL-2.0-only WITH Classpath-exception-2.0")
+            .deny("GPL-3.0-only")
+            .deny("AGPL-3.0-only")
+    }
+
+    #[test]
+    fn test_evaluate_expressions() {
+        let policy = policy();
+        let disposition = |license: &str| policy.evaluate(Some(license));
+
+        assert_eq!(disposition("mit").0, LicenseDisposition::Allow);
+        assert_eq!(
+            disposition("MIT OR GPL-3.0-only").0,
+            LicenseDisposition::Allow
+        );
+        assert_eq!(
+            disposition("MIT AND (GPL-3.0-only OR Apache-2.0)").0,
+            LicenseDisposition::Allow
+        );
+        assert_eq!(
+            disposition("MIT AND GPL-3.0-only"),
+            (LicenseDisposition::Deny, vec!["GPL-3.0-only".to_string()])
+        );
+        assert_eq!(
+            disposition("GPL-3.0-only OR AGPL-3.0-only"),
+            (
+                LicenseDisposition::Deny,
+                vec!["GPL-3.0-only".to_string(), "AGPL-3.0-only".to_string()]
+            )
+        );
+        // The exception is what makes it acceptable
+        assert_eq!(
+            disposition("GPL-2.0-only WITH Classpath-exception-2.0").0,
+            LicenseDisposition::Allow
+        );
+        assert_eq!(disposition("GPL-2.0-only").0, LicenseDisposition::Review);
+    }
+
+    #[test]
+    fn test_unknown_licenses() {
+        let mut policy = policy();
+        assert_eq!(policy.evaluate(None).0, LicenseDisposition::Review);
+        assert_eq!(
+            policy.evaluate(Some("SEE LICENSE IN LICENSE.txt")),
+            (
+                LicenseDisposition::Review,
+                vec!["SEE LICENSE IN LICENSE.txt".to_string()]
+            )
+        );
+        assert_eq!(
+            policy.evaluate(Some("MIT AND (Apache-2.0")).0,
+            LicenseDisposition::Review
+        );
+        policy.unknown_disposition = LicenseDisposition::Deny;
+        assert_eq!(
+            policy.evaluate(Some("NOASSERTION")).0,
+            LicenseDisposition::Deny
+        );
+
+        // With only a denylist, other licenses pass unless told otherwise
+        let mut denylist = LicensePolicy::default().deny("GPL-3.0-only");
+        assert_eq!(denylist.evaluate(Some("ISC")).0, LicenseDisposition::Allow);
+        denylist.default_disposition = Some(LicenseDisposition::Review);
+        assert_eq!(denylist.evaluate(Some("ISC")).0, LicenseDisposition::Review);
+    }
+
+    #[test]
+    fn test_classify_copyleft() {
+        let categories = LicenseCategories::default();
+        let classify = |license: &str| categories.classify(license);
+
+        assert_eq!(classify("MIT"), None);
+        assert_eq!(classify("GPL-3.0-or-later"), Some(CopyleftStrength::Strong));
+        assert_eq!(classify("agpl-3.0-only"), Some(CopyleftStrength::Strong));
+        assert_eq!(classify("LGPL-2.1-only"), Some(CopyleftStrength::Weak));
+        assert_eq!(classify("MPL-2.0"), Some(CopyleftStrength::Weak));
+        assert_eq!(
+            classify("GPL-2.0-only WITH Classpath-exception-2.0"),
+            Some(CopyleftStrength::Weak)
+        );
+        assert_eq!(classify("MIT OR GPL-3.0-only"), None);
+        assert_eq!(
+            classify("MPL-2.0 AND GPL-3.0-only"),
+            Some(CopyleftStrength::Strong)
+        );
+
+        let overridden = LicenseCategories::default()
+            .with("MPL", None)
+            .with("MPL-1.1", Some(CopyleftStrength::Strong));
+        assert_eq!(overridden.classify("MPL-2.0"), None);
+        assert_eq!(
+            overridden.classify("MPL-1.1"),
+            Some(CopyleftStrength::Strong)
+        );
+    }
+}
@@ -0,0 +1,441 @@
+//! Resource limits for untrusted manifests
+//!
+//! Packages under analysis are adversarial input. A manifest can be crafted
+//! to exhaust the analyzer instead of hiding anything: structures nested deep
+//! enough to overflow a recursive parser, YAML aliases that expand
+//! exponentially ("billion laughs"), or tens of thousands of declared
+//! dependencies that each trigger a lookup. The checks here scan the raw text
+//! in a single linear pass, before any parser builds a tree from it.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{rules, Finding, FindingType, RiskLevel, ScanReason};
+
+/// YAML anchor definitions, e.g. `&lol`
+static YAML_ANCHOR: Lazy<Regex> = Lazy::new(|| Regex::new(r"&([\w.-]+)").unwrap());
+
+/// YAML alias references, e.g. `*lol`
+static YAML_ALIAS: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:^|[\s,\[{:-])\*([\w.-]+)").unwrap());
+
+/// Limits applied to manifests before they are parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestLimits {
+    /// Deepest nesting of objects, arrays or YAML blocks
+    pub max_depth: usize,
+    /// Most dependencies declared by a single manifest
+    pub max_dependencies: usize,
+    /// Most scalars and collections, counting every expansion of a YAML alias
+    pub max_tokens: usize,
+}
+
+impl Default for ManifestLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: 64,
+            max_dependencies: 10_000,
+            max_tokens: 1_000_000,
+        }
+    }
+}
+
+/// Limit a manifest ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LimitKind {
+    NestingDepth,
+    DependencyCount,
+    TokenCount,
+}
+
+impl fmt::Display for LimitKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitKind::NestingDepth => write!(f, "nesting depth"),
+            LimitKind::DependencyCount => write!(f, "dependency count"),
+            LimitKind::TokenCount => write!(f, "token count"),
+        }
+    }
+}
+
+/// A manifest exceeded one of the [`ManifestLimits`]
+///
+/// Returned as the analysis error when the manifest cannot be parsed safely;
+/// callers can recover it with `anyhow::Error::downcast_ref`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[error("{manifest} exceeds the {kind} limit of {limit} (reached {observed})")]
+pub struct ResourceLimitExceeded {
+    pub manifest: String,
+    pub kind: LimitKind,
+    pub limit: usize,
+    /// Value reached when scanning stopped; at least `limit + 1`
+    pub observed: usize,
+}
+
+impl ResourceLimitExceeded {
+    /// Report as a `ResourceExhaustionAttempt` finding
+    pub fn to_finding(&self) -> Finding {
+        let (rule_id, description) = match self.kind {
+            LimitKind::NestingDepth => (
+                rules::RESOURCE_NESTING_DEPTH,
+                "Nesting this deep has no legitimate use and can overflow the stack of recursive parsers",
+            ),
+            LimitKind::DependencyCount => (
+                rules::RESOURCE_DEPENDENCY_COUNT,
+                "Declaring this many dependencies stalls tools that look each one up; only the first ones were analyzed",
+            ),
+            LimitKind::TokenCount => (
+                rules::RESOURCE_TOKEN_COUNT,
+                "The manifest expands to more content than any real package needs, as in a billion-laughs attack",
+            ),
+        };
+
+        Finding::new(
+            FindingType::ResourceExhaustionAttempt,
+            RiskLevel::High,
+            format!("{} exceeds the {} limit", self.manifest, self.kind),
+            description,
+        )
+        .with_evidence(format!("Limit {}, reached {}", self.limit, self.observed))
+        .with_affected_component(self.manifest.clone())
+        .with_scan_reason(ScanReason::Manifest)
+        .with_rule_id(rule_id)
+    }
+}
+
+impl ManifestLimits {
+    fn exceeded(&self, manifest: &str, kind: LimitKind, observed: usize) -> ResourceLimitExceeded {
+        let limit = match kind {
+            LimitKind::NestingDepth => self.max_depth,
+            LimitKind::DependencyCount => self.max_dependencies,
+            LimitKind::TokenCount => self.max_tokens,
+        };
+        ResourceLimitExceeded {
+            manifest: manifest.to_string(),
+            kind,
+            limit,
+            observed,
+        }
+    }
+
+    /// Check the number of dependencies `manifest` declares
+    pub fn check_dependency_count(
+        &self,
+        manifest: &str,
+        count: usize,
+    ) -> Result<(), ResourceLimitExceeded> {
+        if count > self.max_dependencies {
+            return Err(self.exceeded(manifest, LimitKind::DependencyCount, count));
+        }
+        Ok(())
+    }
+
+    /// Keep the first `max_dependencies` of the dependencies `manifest`
+    /// declares, reporting the rest as a `ResourceExhaustionAttempt` finding
+    pub fn truncate_dependencies<T>(
+        &self,
+        manifest: &str,
+        declared: &mut Vec<T>,
+    ) -> Option<Finding> {
+        let exceeded = self
+            .check_dependency_count(manifest, declared.len())
+            .err()?;
+        declared.truncate(self.max_dependencies);
+        Some(exceeded.to_finding())
+    }
+
+    /// Check JSON nesting depth and token count without parsing
+    pub fn check_json(&self, manifest: &str, content: &str) -> Result<(), ResourceLimitExceeded> {
+        let mut depth = 0usize;
+        let mut tokens = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        let mut in_literal = false;
+
+        for byte in content.bytes() {
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if byte == b'\\' {
+                    escaped = true;
+                } else if byte == b'"' {
+                    in_string = false;
+                }
+                continue;
+            }
+
+            let starts_token = match byte {
+                b'{' | b'[' => {
+                    depth += 1;
+                    if depth > self.max_depth {
+                        return Err(self.exceeded(manifest, LimitKind::NestingDepth, depth));
+                    }
+                    in_literal = false;
+                    true
+                }
+                b'}' | b']' | b',' | b':' => {
+                    if byte == b'}' || byte == b']' {
+                        depth = depth.saturating_sub(1);
+                    }
+                    in_literal = false;
+                    false
+                }
+                b'"' => {
+                    in_string = true;
+                    in_literal = false;
+                    true
+                }
+                b if b.is_ascii_whitespace() => {
+                    in_literal = false;
+                    false
+                }
+                _ => !std::mem::replace(&mut in_literal, true),
+            };
+
+            if starts_token {
+                tokens += 1;
+                if tokens > self.max_tokens {
+                    return Err(self.exceeded(manifest, LimitKind::TokenCount, tokens));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check XML element nesting depth and element count without parsing
+    ///
+    /// Comments, CDATA sections, declarations and processing instructions are
+    /// skipped; every other tag opens an element.
+    pub fn check_xml(&self, manifest: &str, content: &str) -> Result<(), ResourceLimitExceeded> {
+        let mut depth = 0usize;
+        let mut tokens = 0usize;
+        let mut rest = content;
+
+        while let Some(start) = rest.find('<') {
+            rest = &rest[start..];
+            let skip_to = if rest.starts_with("<!--") {
+                "-->"
+            } else if rest.starts_with("<![CDATA[") {
+                "]]>"
+            } else {
+                ">"
+            };
+            let Some(end) = rest.find(skip_to) else {
+                break;
+            };
+            let tag = &rest[1..end];
+            rest = &rest[end + skip_to.len()..];
+            if skip_to != ">" || tag.starts_with(['?', '!']) {
+                continue;
+            }
+            if tag.starts_with('/') {
+                depth = depth.saturating_sub(1);
+                continue;
+            }
+
+            tokens += 1;
+            if tokens > self.max_tokens {
+                return Err(self.exceeded(manifest, LimitKind::TokenCount, tokens));
+            }
+            if !tag.ends_with('/') {
+                depth += 1;
+                if depth > self.max_depth {
+                    return Err(self.exceeded(manifest, LimitKind::NestingDepth, depth));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check YAML nesting depth and token count without parsing
+    ///
+    /// Aliases count as every token of the anchored node, so alias chains are
+    /// measured at their expanded size. Block structure is inferred from
+    /// indentation, which is enough to bound what a parser will build.
+    pub fn check_yaml(&self, manifest: &str, content: &str) -> Result<(), ResourceLimitExceeded> {
+        let mut anchors: HashMap<String, u64> = HashMap::new();
+        // (indent, name, expanded size so far) of anchors whose node is still open
+        let mut open: Vec<(usize, String, u64)> = Vec::new();
+        let mut indents: Vec<usize> = Vec::new();
+        let mut flow_depth = 0usize;
+        let mut total = 0u64;
+
+        for line in content.lines() {
+            let text = strip_quoted(line);
+            let trimmed = text.trim();
+            if trimmed.is_empty() || trimmed == "---" || trimmed == "..." {
+                continue;
+            }
+            let indent = text.len() - text.trim_start().len();
+
+            // A line at or left of an anchor's indentation ends its node
+            if flow_depth == 0 {
+                while open.last().is_some_and(|(at, _, _)| *at >= indent) {
+                    let (_, name, size) = open.pop().unwrap();
+                    anchors.insert(name, size);
+                }
+                while indents.last().is_some_and(|top| *top > indent) {
+                    indents.pop();
+                }
+                if indents.last() != Some(&indent) {
+                    indents.push(indent);
+                }
+            }
+
+            let dashes = trimmed
+                .split_inclusive(' ')
+                .take_while(|part| part.trim() == "-")
+                .count();
+            let mut line_depth = flow_depth;
+            for c in trimmed.chars() {
+                match c {
+                    '[' | '{' => {
+                        flow_depth += 1;
+                        line_depth = line_depth.max(flow_depth);
+                    }
+                    ']' | '}' => flow_depth = flow_depth.saturating_sub(1),
+                    _ => {}
+                }
+            }
+            let depth = indents.len() + dashes + line_depth;
+            if depth > self.max_depth {
+                return Err(self.exceeded(manifest, LimitKind::NestingDepth, depth));
+            }
+
+            let mut size = 1 + trimmed.matches(',').count() as u64;
+            for alias in YAML_ALIAS.captures_iter(trimmed) {
+                let expanded = anchors.get(&alias[1]).copied().unwrap_or(1);
+                size = size.saturating_add(expanded);
+            }
+            for anchor in YAML_ANCHOR.captures_iter(trimmed) {
+                open.push((indent, anchor[1].to_string(), 0));
+            }
+            for (_, _, anchored) in &mut open {
+                *anchored = anchored.saturating_add(size);
+            }
+
+            total = total.saturating_add(size);
+            if total > self.max_tokens as u64 {
+                let observed = usize::try_from(total).unwrap_or(usize::MAX);
+                return Err(self.exceeded(manifest, LimitKind::TokenCount, observed));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Drop quoted strings and comments from a YAML line, keeping indentation
+fn strip_quoted(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut quote = None;
+    let mut previous = ' ';
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                out.push('_');
+            }
+            None if c == '#' && previous.is_whitespace() => break,
+            None => out.push(c),
+        }
+        previous = c;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_within_limits() {
+        let limits = ManifestLimits::default();
+        let manifest =
+            r#"{"name": "ok", "dependencies": {"a": "^1.0.0"}, "tags": [1, true, null]}"#;
+        assert!(limits.check_json("package.json", manifest).is_ok());
+    }
+
+    #[test]
+    fn test_json_nesting_depth() {
+        let limits = ManifestLimits::default();
+        let manifest = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        let err = limits.check_json("package.json", &manifest).unwrap_err();
+        assert_eq!(err.kind, LimitKind::NestingDepth);
+        assert_eq!(err.observed, limits.max_depth + 1);
+
+        // Brackets inside strings are data
+        let quoted = format!("{{\"a\": \"{}\"}}", "[".repeat(1000));
+        assert!(limits.check_json("package.json", &quoted).is_ok());
+    }
+
+    #[test]
+    fn test_json_token_count() {
+        let limits = ManifestLimits {
+            max_tokens: 10,
+            ..ManifestLimits::default()
+        };
+        let err = limits
+            .check_json("package.json", "[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]")
+            .unwrap_err();
+        assert_eq!(err.kind, LimitKind::TokenCount);
+    }
+
+    #[test]
+    fn test_xml_nesting_depth() {
+        let limits = ManifestLimits::default();
+        let pom = "<project><dependencies><dependency/><!-- <a><b> --></dependencies></project>";
+        assert!(limits.check_xml("pom.xml", pom).is_ok());
+
+        let nested = format!("<project>{}", "<a>".repeat(100_000));
+        let err = limits.check_xml("pom.xml", &nested).unwrap_err();
+        assert_eq!(err.kind, LimitKind::NestingDepth);
+        assert_eq!(err.observed, limits.max_depth + 1);
+
+        // Self-closing elements count but don't nest
+        let limits = ManifestLimits {
+            max_tokens: 10,
+            ..ManifestLimits::default()
+        };
+        let flat = format!("<project>{}</project>", "<dependency/>".repeat(20));
+        let err = limits.check_xml("pom.xml", &flat).unwrap_err();
+        assert_eq!(err.kind, LimitKind::TokenCount);
+    }
+
+    #[test]
+    fn test_yaml_alias_expansion() {
+        let limits = ManifestLimits::default();
+        let benign = "defaults: &defaults\n  image: nginx\n  replicas: 2\nprod:\n  <<: *defaults\n  replicas: 5\n";
+        assert!(limits.check_yaml("values.yaml", benign).is_ok());
+
+        let mut laughs = String::from(
+            "a: &a [\"lol\",\"lol\",\"lol\",\"lol\",\"lol\",\"lol\",\"lol\",\"lol\",\"lol\"]\n",
+        );
+        for (previous, name) in ["a", "b", "c", "d", "e", "f", "g", "h"]
+            .iter()
+            .zip(["b", "c", "d", "e", "f", "g", "h", "i"])
+        {
+            let aliases = vec![format!("*{}", previous); 9].join(",");
+            laughs.push_str(&format!("{}: &{} [{}]\n", name, name, aliases));
+        }
+        let err = limits.check_yaml("values.yaml", &laughs).unwrap_err();
+        assert_eq!(err.kind, LimitKind::TokenCount);
+        assert_eq!(
+            err.to_finding().finding_type,
+            FindingType::ResourceExhaustionAttempt
+        );
+    }
+
+    #[test]
+    fn test_yaml_nesting_depth() {
+        let limits = ManifestLimits::default();
+        let nested: String = (0..100)
+            .map(|level| format!("{}k{}:\n", "  ".repeat(level), level))
+            .collect();
+        let err = limits.check_yaml("values.yaml", &nested).unwrap_err();
+        assert_eq!(err.kind, LimitKind::NestingDepth);
+    }
+}
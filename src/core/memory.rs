@@ -0,0 +1,244 @@
+//! Accounting of the memory an analysis holds
+//!
+//! A multi-tenant service scanning untrusted packages needs one package to
+//! fail on its own rather than take the host down with it. Analyzers charge
+//! their large allocations (file contents, parsed manifests, dependency trees,
+//! findings) to a [`MemoryBudget`] and stop with [`MemoryLimitExceeded`] once
+//! the total passes `AnalysisOptions::max_memory_bytes`.
+//!
+//! This is best-effort accounting, not an allocator-level guarantee: sizes
+//! are estimated from the data's shape, transient buffers and allocator
+//! overhead are not counted, and memory is only checked where it is charged.
+//! Bound the process itself (cgroups, `ulimit`) when a hard ceiling matters.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::mem::size_of;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::{
+    Dependency, DependencySource, Finding, MaliciousPattern, PatternLocation, ShadowedDeclaration,
+    Vulnerability,
+};
+
+/// An analysis held more memory than `AnalysisOptions::max_memory_bytes` allows
+///
+/// Returned as the analysis error; callers can recover it with
+/// `anyhow::Error::downcast_ref`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[error("analysis exceeds the memory limit of {limit} bytes while holding {holding} (reached {observed})")]
+pub struct MemoryLimitExceeded {
+    pub limit: usize,
+    /// Estimated bytes held when the limit was passed
+    pub observed: usize,
+    /// What was being charged when the limit was passed
+    pub holding: String,
+}
+
+/// Running total of the memory an analysis holds, checked against a limit
+#[derive(Debug, Default)]
+pub struct MemoryBudget {
+    limit: Option<usize>,
+    used: AtomicUsize,
+}
+
+impl MemoryBudget {
+    /// Budget allowing up to `limit` bytes; `None` only keeps count
+    pub fn new(limit: Option<usize>) -> Self {
+        Self {
+            limit,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    /// Charge `bytes` held for `holding`, failing once the total passes the limit
+    pub fn charge(&self, holding: &str, bytes: usize) -> Result<(), MemoryLimitExceeded> {
+        let (Ok(previous) | Err(previous)) =
+            self.used
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                    Some(used.saturating_add(bytes))
+                });
+        let used = previous.saturating_add(bytes);
+        match self.limit {
+            Some(limit) if used > limit => Err(MemoryLimitExceeded {
+                limit,
+                observed: used,
+                holding: holding.to_string(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Charge the estimated footprint of a value
+    pub fn charge_for<T: MemoryFootprint + ?Sized>(
+        &self,
+        holding: &str,
+        value: &T,
+    ) -> Result<(), MemoryLimitExceeded> {
+        self.charge(holding, value.footprint())
+    }
+
+    /// Estimated bytes charged so far
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+}
+
+/// Estimated heap and inline size of a value
+pub trait MemoryFootprint {
+    fn footprint(&self) -> usize;
+}
+
+impl MemoryFootprint for str {
+    fn footprint(&self) -> usize {
+        self.len()
+    }
+}
+
+impl MemoryFootprint for String {
+    fn footprint(&self) -> usize {
+        size_of::<String>() + self.capacity()
+    }
+}
+
+impl<T: MemoryFootprint> MemoryFootprint for [T] {
+    fn footprint(&self) -> usize {
+        self.iter().map(MemoryFootprint::footprint).sum()
+    }
+}
+
+impl<T: MemoryFootprint> MemoryFootprint for Vec<T> {
+    fn footprint(&self) -> usize {
+        size_of::<Vec<T>>() + self.as_slice().footprint()
+    }
+}
+
+impl MemoryFootprint for Value {
+    fn footprint(&self) -> usize {
+        // Walk iteratively; parsed manifests can be deeply nested
+        let mut total = 0;
+        let mut stack = vec![self];
+        while let Some(value) = stack.pop() {
+            total += size_of::<Value>();
+            match value {
+                Value::String(s) => total += s.capacity(),
+                Value::Array(items) => stack.extend(items),
+                Value::Object(map) => {
+                    for (key, item) in map {
+                        total += key.footprint();
+                        stack.push(item);
+                    }
+                }
+                Value::Null | Value::Bool(_) | Value::Number(_) => {}
+            }
+        }
+        total
+    }
+}
+
+impl MemoryFootprint for Vulnerability {
+    fn footprint(&self) -> usize {
+        size_of::<Self>()
+            + self.id.capacity()
+            + self.title.capacity()
+            + self.description.capacity()
+            + self.affected_versions.footprint()
+            + self.fixed_versions.footprint()
+            + self.references.footprint()
+            + self.cwe_ids.footprint()
+    }
+}
+
+impl MemoryFootprint for Dependency {
+    fn footprint(&self) -> usize {
+        let mut total = 0;
+        let mut stack = vec![self];
+        while let Some(dep) = stack.pop() {
+            total += size_of::<Self>()
+                + dep.name.capacity()
+                + dep.version_spec.capacity()
+                + dep.resolved_version.as_ref().map_or(0, String::capacity)
+                + dep.alias_target.as_ref().map_or(0, String::capacity)
+                + dep.platform.as_ref().map_or(0, String::capacity)
+                + dep
+                    .source
+                    .as_ref()
+                    .and_then(DependencySource::url)
+                    .map_or(0, str::len)
+                + dep.vulnerabilities.footprint()
+                + dep
+                    .range_vulnerabilities
+                    .iter()
+                    .map(|permitted| {
+                        permitted.version.capacity() + permitted.vulnerability.footprint()
+                    })
+                    .sum::<usize>()
+                + dep
+                    .shadowed_declarations
+                    .iter()
+                    .map(|shadowed| {
+                        size_of::<ShadowedDeclaration>() + shadowed.version_spec.capacity()
+                    })
+                    .sum::<usize>();
+            stack.extend(&dep.dependencies);
+        }
+        total
+    }
+}
+
+impl MemoryFootprint for Finding {
+    fn footprint(&self) -> usize {
+        size_of::<Self>()
+            + self.title.capacity()
+            + self.description.capacity()
+            + self.evidence.footprint()
+            + self.affected_components.footprint()
+            + self.rule_id.as_ref().map_or(0, String::capacity)
+    }
+}
+
+impl MemoryFootprint for MaliciousPattern {
+    fn footprint(&self) -> usize {
+        size_of::<Self>()
+            + self.pattern_id.capacity()
+            + self.pattern_name.capacity()
+            + self.description.capacity()
+            + self.indicators.footprint()
+            + self.regex_patterns.footprint()
+            + self.file_patterns.footprint()
+            + self.attack_techniques.footprint()
+            + self.evidence.footprint()
+            + self.file.capacity()
+            + self
+                .locations
+                .iter()
+                .map(|location| {
+                    size_of::<PatternLocation>()
+                        + location.file.as_ref().map_or(0, String::capacity)
+                })
+                .sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_stops_past_limit() {
+        let budget = MemoryBudget::new(Some(1_000));
+        budget.charge("manifest", 600).unwrap();
+        let err = budget.charge("dependencies", 600).unwrap_err();
+        assert_eq!(err.limit, 1_000);
+        assert_eq!(err.observed, 1_200);
+        assert_eq!(err.holding, "dependencies");
+
+        let unbounded = MemoryBudget::new(None);
+        unbounded.charge("manifest", usize::MAX).unwrap();
+        unbounded.charge("manifest", 1).unwrap();
+        assert_eq!(unbounded.used(), usize::MAX);
+
+        let json: Value = serde_json::from_str(r#"{"dependencies": {"a": "1.0.0"}}"#).unwrap();
+        assert!(json.footprint() >= 3 * size_of::<Value>() + "dependencies".len());
+    }
+}
@@ -1,18 +1,110 @@
 //! Core traits and structures for package security analysis
 
+pub mod alias;
+pub mod archive;
+pub mod confusion;
+pub mod cross_language;
+pub mod custom_rules;
+pub mod deadline;
+pub mod delay;
 pub mod dependency;
+pub mod diagram;
+pub mod diff;
+pub mod encoding;
+pub mod evasion;
+pub mod exfiltration;
+pub mod external;
+pub mod features;
+pub mod gate;
+pub mod graph;
+pub mod hijack;
+pub mod hooks;
+pub mod injection;
+pub mod license;
+pub mod limits;
+pub mod memory;
 pub mod package;
+pub mod partial;
 pub mod patterns;
+pub mod pinning;
+pub mod platform;
+pub mod progress;
+pub mod provenance;
+pub mod purpose;
+pub mod quality;
+pub mod query;
+pub mod reachability;
+pub mod repository;
 pub mod risk;
+pub mod rules;
+pub mod source_policy;
+pub mod transport;
 pub mod vulnerability;
 
-pub use dependency::{Dependency, DependencyAnalysis, DependencyType};
+pub use alias::DependencyAlias;
+pub use archive::DecompressionGuard;
+#[cfg(feature = "runtime")]
+pub use confusion::PublicRegistry;
+pub use confusion::{ConfusionEvidence, DependencyConfusionRisk};
+pub use cross_language::{CrossLanguageExecution, ForeignCode, ForeignExecution, ScriptLanguage};
+pub use custom_rules::{CustomRule, CustomRuleSet};
+pub use deadline::AnalysisTimeout;
+pub use delay::{Delay, DelayedExecution};
+pub use dependency::{
+    Dependency, DependencyAnalysis, DependencySource, DependencyType, RangeVulnerability,
+    ShadowedDeclaration,
+};
+pub use diagram::{DependencyDiagram, DiagramEdge, DiagramNode};
+pub use diff::{diff, AnalysisDiff, DependencyChange, ResultDiff, ScanContext};
+pub use encoding::{DecodedPayload, Encoding};
+pub use evasion::EnvironmentFingerprint;
+pub use exfiltration::{RegistryChannel, RegistryExfiltration};
+pub use external::ExternalFinding;
+pub use features::{FeatureVector, FEATURE_SCHEMA_VERSION};
+pub use gate::{FailConditions, GateDecision};
+pub use graph::DependencyGraph;
+pub use hijack::{ImportHijack, LoaderHook};
+pub use hooks::InstallHook;
+pub use injection::{HiddenCharacter, InjectedField, MetadataInjection};
+pub use license::{
+    CopyleftDependency, CopyleftStrength, LicenseCategories, LicenseDisposition, LicenseFinding,
+    LicensePolicy,
+};
+pub use limits::{LimitKind, ManifestLimits, ResourceLimitExceeded};
+pub use memory::{MemoryBudget, MemoryFootprint, MemoryLimitExceeded};
+#[cfg(feature = "runtime")]
+pub(crate) use package::ErasedAnalyzer;
 pub use package::{
-    AnalysisResult, PackageAnalyzer, PackageInfo, PackageMetadata, QualityMetrics,
-    TyposquattingRisk,
+    AnalysisOptions, AnalysisResult, BadgeData, DynPackageAnalyzer, PackageAnalyzer, PackageInfo,
+    PackageMetadata, TyposquattingRisk,
+};
+pub use partial::PartialParse;
+pub use patterns::{
+    MaliciousPattern, PatternCategory, PatternDatabase, PatternLocation, PatternMatcher,
+    PatternSeverity,
+};
+pub use pinning::{LooseDependency, PinningReport, PinningStyle};
+pub use platform::PlatformTargetedPayload;
+pub use progress::ProgressEvent;
+#[cfg(feature = "runtime")]
+pub use provenance::ProvenanceVerifier;
+pub use provenance::{Provenance, ProvenanceStatus};
+pub use purpose::{DeclaredPurpose, ObservedOperation, Operation, PurposeMismatch};
+pub use quality::QualityMetrics;
+pub use query::FindingQuery;
+pub use reachability::SymbolUsage;
+#[cfg(feature = "runtime")]
+pub use repository::RepositoryValidator;
+pub use repository::{RepositoryLink, RepositoryStatus, RepositoryValidation};
+pub use risk::{
+    ComponentWeights, EffortLevel, Finding, FindingType, ImpactLevel, PhaseRecord, Priority,
+    Recommendation, RiskAssessment, RiskCalculator, RiskCategory, RiskFactor, RiskLevel,
+    RiskOrderingKey, RiskScore, RiskScoringConfig, RiskThresholds, ScanReason, SecurityPosture,
 };
-pub use patterns::{MaliciousPattern, PatternDatabase, PatternMatcher};
-pub use risk::{RiskAssessment, RiskCalculator, RiskLevel, RiskScore, SecurityPosture};
+pub use rules::{RuleDescriptor, RuleRegistry, RULE_NAMESPACE_VERSION};
+pub use source_policy::{SourcePolicy, SourceViolation};
+pub use transport::InsecureSource;
 pub use vulnerability::{
-    DatabaseStatistics, UpdateResult, Vulnerability, VulnerabilityDatabase, VulnerabilitySeverity,
+    DatabaseStatistics, SourceSeverity, UpdateResult, Vulnerability, VulnerabilityDatabase,
+    VulnerabilitySeverity,
 };
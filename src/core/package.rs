@@ -2,11 +2,24 @@
 
 use anyhow::Result;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::Path;
-
-use super::{DependencyAnalysis, MaliciousPattern, RiskAssessment, Vulnerability};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::ecosystem::Ecosystem;
+use crate::utils::typosquatting::{
+    PopularPackages, SimilarPackage, TyposquattingDetector, TyposquattingReason,
+    TyposquattingTechnique,
+};
+use crate::versioning::PrereleasePolicy;
+
+use super::{
+    CopyleftDependency, DependencyAnalysis, DependencyDiagram, DependencyType, ExternalFinding,
+    FailConditions, FeatureVector, FindingQuery, GateDecision, InstallHook, LicenseCategories,
+    LicenseFinding, LicensePolicy, MaliciousPattern, ManifestLimits, PinningReport, ResultDiff,
+    RiskAssessment, RiskOrderingKey, RiskScoringConfig, SourcePolicy, Vulnerability,
+};
 
 /// Basic package information common to all package types
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,46 +35,19 @@ pub struct PackageMetadata {
     pub publish_date: Option<String>,
 }
 
-/// Package quality metrics
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct QualityMetrics {
-    pub documentation_score: f32,
-    pub has_tests: bool,
-    pub has_ci_cd: bool,
-    pub maintenance_score: f32,
-}
-
-impl Default for QualityMetrics {
-    fn default() -> Self {
-        Self {
-            documentation_score: 0.5,
-            has_tests: false,
-            has_ci_cd: false,
-            maintenance_score: 0.5,
-        }
-    }
-}
-
-impl QualityMetrics {
-    pub fn documentation_score(&self) -> f32 {
-        self.documentation_score
-    }
-
-    pub fn has_tests(&self) -> bool {
-        self.has_tests
-    }
-
-    pub fn has_ci_cd(&self) -> bool {
-        self.has_ci_cd
-    }
-}
-
 /// Typosquatting risk assessment
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TyposquattingRisk {
     pub is_potential_typosquatting: bool,
-    pub similar_packages: Vec<String>,
+    /// Names the package may impersonate, most likely first
+    pub similar_packages: Vec<SimilarPackage>,
+    /// Confidence of the most likely candidate
     pub confidence_score: f32,
+    #[serde(default)]
+    pub reason: TyposquattingReason,
+    /// How the name imitates the most likely candidate
+    #[serde(default)]
+    pub techniques: Vec<TyposquattingTechnique>,
 }
 
 impl TyposquattingRisk {
@@ -69,9 +55,17 @@ impl TyposquattingRisk {
         self.is_potential_typosquatting
     }
 
-    pub fn similar_packages(&self) -> &[String] {
+    pub fn similar_packages(&self) -> &[SimilarPackage] {
         &self.similar_packages
     }
+
+    pub fn reason(&self) -> TyposquattingReason {
+        self.reason
+    }
+
+    pub fn techniques(&self) -> &[TyposquattingTechnique] {
+        &self.techniques
+    }
 }
 
 /// Package-specific information trait
@@ -99,6 +93,9 @@ pub trait AnalysisResult: Send + Sync {
     /// Get risk assessment
     fn risk_assessment(&self) -> &RiskAssessment;
 
+    /// Get risk assessment for updating, such as with external findings
+    fn risk_assessment_mut(&mut self) -> &mut RiskAssessment;
+
     /// Get dependency analysis
     fn dependency_analysis(&self) -> &DependencyAnalysis;
 
@@ -116,11 +113,47 @@ pub trait AnalysisResult: Send + Sync {
         self.risk_assessment().risk_score.risk_level
     }
 
+    /// Why the overall risk level is what it is
+    ///
+    /// One [`super::RiskFactor`] per score component, largest contribution
+    /// first; each displays as a reason such as "1 high vulnerability
+    /// contributed +7". Contributions add up to the total score before it
+    /// is capped at 100.
+    fn risk_explanation(&self) -> Vec<super::RiskFactor> {
+        self.risk_assessment().risk_score.explanation()
+    }
+
+    /// Merge findings reported by other tools, deduped against every native
+    /// finding; see [`RiskAssessment::merge_external`]
+    fn merge_external(&mut self, external: &[ExternalFinding]) -> usize {
+        let reported = self.query().iter().map(|f| f.fingerprint()).collect();
+        self.risk_assessment_mut()
+            .merge_external(external, &reported)
+    }
+
+    /// Whether the overall risk level is above `threshold`
+    fn exceeds(&self, threshold: super::RiskLevel) -> bool {
+        self.overall_risk_level() > threshold
+    }
+
+    /// Pass/fail decision for CI, with the reason for each condition met
+    fn gate(&self, conditions: &FailConditions) -> GateDecision {
+        conditions.evaluate(self)
+    }
+
     /// Get malicious indicators (convenience method)
     fn malicious_indicators(&self) -> &[super::MaliciousPattern] {
         self.malicious_patterns()
     }
 
+    /// How tightly the direct dependencies pin their versions
+    fn pinning_report(&self) -> PinningReport {
+        PinningReport::from_analysis(
+            self.dependency_analysis(),
+            self.package_info().package_type(),
+        )
+    }
+
     /// Get supply chain risk score (convenience method)
     fn supply_chain_risk_score(&self) -> f32 {
         self.risk_assessment()
@@ -131,7 +164,8 @@ pub trait AnalysisResult: Send + Sync {
             .unwrap_or(0.0)
     }
 
-    /// Get quality metrics (placeholder)
+    /// Signs the package is tested, documented and maintained; see
+    /// [`super::QualityMetrics::assess`]
     fn quality_metrics(&self) -> super::QualityMetrics {
         super::QualityMetrics::default()
     }
@@ -140,6 +174,152 @@ pub trait AnalysisResult: Send + Sync {
     fn typosquatting_risk(&self) -> Option<TyposquattingRisk> {
         None
     }
+
+    /// Dependencies, transitive ones included, whose license
+    /// `AnalysisOptions::license_policy` does not allow
+    fn license_findings(&self) -> &[LicenseFinding] {
+        &self.dependency_analysis().license_findings
+    }
+
+    /// Copyleft dependencies, transitive ones included, with the path that
+    /// brings each in; see [`LicenseCategories::copyleft_dependencies`]
+    fn copyleft_dependencies(&self, categories: &LicenseCategories) -> Vec<CopyleftDependency> {
+        categories.copyleft_dependencies(self.dependency_analysis())
+    }
+
+    /// Scripts, commands and hooks run while the package is installed,
+    /// whether or not anything suspicious was found in them
+    fn install_hooks(&self) -> Vec<InstallHook> {
+        Vec::new()
+    }
+
+    /// Number of scripts or hooks run at install time
+    fn install_script_count(&self) -> usize {
+        self.install_hooks().len()
+    }
+
+    /// Normalized numeric features for machine-learning pipelines
+    fn feature_vector(&self) -> FeatureVector {
+        FeatureVector::from_result(self)
+    }
+
+    /// Query findings, vulnerabilities and malicious patterns as unified findings
+    fn query(&self) -> FindingQuery<'_> {
+        FindingQuery::new(self)
+    }
+
+    /// Dependency tree laid out for rendering, cut off `max_depth` edges from the root
+    fn dependency_diagram(&self, max_depth: Option<usize>) -> DependencyDiagram {
+        DependencyDiagram::build(
+            &self.package_info().metadata().name,
+            self.dependency_analysis(),
+            max_depth,
+        )
+    }
+
+    /// CycloneDX 1.5 JSON SBOM of the package, its dependencies and their
+    /// vulnerabilities; see [`crate::report::cyclonedx`]
+    fn to_cyclonedx(&self) -> Result<serde_json::Value> {
+        crate::report::cyclonedx::to_cyclonedx(self)
+    }
+
+    /// SPDX 2.3 JSON document describing the package and its dependencies;
+    /// see [`crate::report::spdx`]
+    fn to_spdx(&self) -> Result<serde_json::Value> {
+        crate::report::spdx::to_spdx(self)
+    }
+
+    /// SARIF 2.1.0 log of the package's malicious patterns and vulnerabilities
+    /// for GitHub code scanning; see [`crate::report::sarif`]
+    fn to_sarif(&self) -> Result<serde_json::Value> {
+        crate::report::sarif::to_sarif(self)
+    }
+
+    /// Summary for terminals and CI logs: the package, its risk level, its
+    /// vulnerabilities, the top malicious indicators and the supply chain
+    /// score; see [`crate::report::text`]
+    ///
+    /// With `color` off the report is plain ASCII. Pass
+    /// [`crate::report::text::color_supported`] to color it only on a
+    /// terminal.
+    fn to_text_report(&self, color: bool) -> String {
+        crate::report::text::to_text_report(self, color)
+    }
+
+    /// Full dependency tree as a Graphviz DOT digraph
+    fn to_dot(&self) -> String {
+        self.dependency_diagram(None).to_dot()
+    }
+
+    /// Full dependency tree as a Mermaid flowchart
+    fn to_mermaid(&self) -> String {
+        self.dependency_diagram(None).to_mermaid()
+    }
+
+    /// Key sorting results worst-first; see [`RiskOrderingKey`]
+    fn risk_ordering_key(&self) -> RiskOrderingKey {
+        let metadata = self.package_info().metadata();
+        RiskOrderingKey::new(self.risk_assessment(), &metadata.name, &metadata.version)
+    }
+
+    /// Changes from this result to a newer one, matched by finding fingerprint
+    ///
+    /// Needs only the stored results, not the packages; see [`ResultDiff`].
+    fn diff(&self, other: &dyn AnalysisResult) -> ResultDiff {
+        ResultDiff::between(self, other)
+    }
+
+    /// Badge summarizing the overall risk level
+    fn risk_badge(&self) -> BadgeData {
+        let metadata = self.package_info().metadata();
+        let level = self.overall_risk_level();
+        BadgeData {
+            label: format!("{}@{} risk", metadata.name, metadata.version),
+            message: level.to_string().to_lowercase(),
+            color: level.shields_color().to_string(),
+        }
+    }
+}
+
+// Results compare by `risk_ordering_key`, so sorting a batch puts the riskiest first
+impl PartialEq for dyn AnalysisResult + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.risk_ordering_key() == other.risk_ordering_key()
+    }
+}
+
+impl Eq for dyn AnalysisResult + '_ {}
+
+impl PartialOrd for dyn AnalysisResult + '_ {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for dyn AnalysisResult + '_ {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.risk_ordering_key().cmp(&other.risk_ordering_key())
+    }
+}
+
+/// Badge label, message and color
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BadgeData {
+    pub label: String,
+    pub message: String,
+    pub color: String,
+}
+
+impl BadgeData {
+    /// shields.io endpoint badge JSON
+    pub fn to_shields_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "schemaVersion": 1,
+            "label": self.label,
+            "message": self.message,
+            "color": self.color,
+        })
+    }
 }
 
 /// Package analyzer trait
@@ -154,6 +334,18 @@ pub trait PackageAnalyzer: Send + Sync {
     /// Analyze a package from the given path
     async fn analyze(&self, path: &Path) -> Result<Self::Analysis>;
 
+    /// Analyze a package with explicit options
+    ///
+    /// Analyzers that don't support options fall back to [`PackageAnalyzer::analyze`].
+    async fn analyze_with_options(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Self::Analysis> {
+        let _ = options;
+        self.analyze(path).await
+    }
+
     /// Check if this analyzer can handle the given path
     fn can_analyze(&self, path: &Path) -> bool;
 
@@ -164,6 +356,58 @@ pub trait PackageAnalyzer: Send + Sync {
     fn supported_extensions(&self) -> Vec<&str>;
 }
 
+/// Object-safe form of [`PackageAnalyzer`], returning boxed results
+///
+/// [`PackageAnalyzer`]'s associated types keep analyzers of different
+/// package types out of one collection; this trait erases them, so custom
+/// analyzers can be registered with
+/// [`crate::PackageSecurityAnalyzerBuilder`]. Implement it directly, or
+/// implement [`PackageAnalyzer`] and let the builder wrap it.
+#[async_trait]
+pub trait DynPackageAnalyzer: Send + Sync {
+    /// Analyze a package with explicit options
+    async fn analyze_with_options(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Box<dyn AnalysisResult>>;
+
+    /// Check if this analyzer can handle the given path
+    fn can_analyze(&self, path: &Path) -> bool;
+
+    /// Get analyzer name
+    fn name(&self) -> &str;
+}
+
+/// A [`PackageAnalyzer`] behind the [`DynPackageAnalyzer`] interface
+#[cfg(feature = "runtime")]
+pub(crate) struct ErasedAnalyzer<A>(pub A);
+
+#[cfg(feature = "runtime")]
+#[async_trait]
+impl<A> DynPackageAnalyzer for ErasedAnalyzer<A>
+where
+    A: PackageAnalyzer,
+    A::Analysis: 'static,
+{
+    async fn analyze_with_options(
+        &self,
+        path: &Path,
+        options: &AnalysisOptions,
+    ) -> Result<Box<dyn AnalysisResult>> {
+        let analysis = self.0.analyze_with_options(path, options).await?;
+        Ok(Box::new(analysis))
+    }
+
+    fn can_analyze(&self, path: &Path) -> bool {
+        self.0.can_analyze(path)
+    }
+
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+}
+
 /// Common package analysis options
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnalysisOptions {
@@ -183,7 +427,334 @@ pub struct AnalysisOptions {
     pub max_dependency_depth: usize,
 
     /// Timeout for analysis in seconds
+    ///
+    /// Covers the whole analysis of one package; when it passes, the analysis
+    /// is cancelled and fails with [`super::AnalysisTimeout`]. 0 means no limit.
     pub timeout_seconds: u64,
+
+    /// Largest edit distance to a popular package name that counts as typosquatting
+    #[serde(default = "default_typosquatting_distance")]
+    pub typosquatting_max_distance: usize,
+
+    /// Lowest confidence at which a similar name is reported
+    ///
+    /// One edit to a two-letter name (`ms` and `mz`) scores 0.5, one edit to
+    /// a seven-letter name about 0.86; see
+    /// [`TyposquattingDetector::similar_within`]. 0 reports every name within
+    /// `typosquatting_max_distance`.
+    #[serde(default)]
+    pub typosquatting_min_confidence: f32,
+
+    /// Legitimate names to compare package names against, per ecosystem
+    ///
+    /// Replaces the bundled popularity lists for the ecosystems given; the
+    /// others keep them. See [`PopularPackages`].
+    #[serde(default)]
+    pub popular_packages: BTreeMap<Ecosystem, PopularPackages>,
+
+    /// Report every install-time script, not only suspicious ones
+    #[serde(default)]
+    pub flag_install_scripts: bool,
+
+    /// Report container images referenced by `latest` or no tag at all
+    #[serde(default)]
+    pub flag_mutable_images: bool,
+
+    /// Report single weak heuristic signals at low severity instead of dropping them
+    #[serde(default)]
+    pub report_weak_signals: bool,
+
+    /// Fetch the declared repository link and check it belongs to the package
+    ///
+    /// Needs network access; never runs in builds with the `offline` feature.
+    /// Only links to GitHub, GitLab and Bitbucket are fetched.
+    #[serde(default)]
+    pub validate_repository: bool,
+
+    /// Look up and verify published provenance attestations
+    ///
+    /// Needs network access; never runs in builds with the `offline` feature.
+    #[serde(default)]
+    pub verify_provenance: bool,
+
+    /// Report install code that publishes packages or writes to a registry
+    ///
+    /// Off by default: publishing tooling run from lifecycle scripts is rare
+    /// but legitimate in monorepos, and the request heuristics are coarse.
+    #[serde(default)]
+    pub detect_registry_exfiltration: bool,
+
+    /// Check whether the package's own source uses the code a vulnerability is in
+    ///
+    /// A best-effort textual heuristic (see [`super::reachability`]) that
+    /// reads every source file, so it is off by default. Only advisories that
+    /// name affected symbols get an answer.
+    #[serde(default)]
+    pub analyze_reachability: bool,
+
+    /// Never use the network, as in builds with the `offline` feature
+    ///
+    /// Repository validation and provenance checks are skipped even when
+    /// requested.
+    #[serde(default)]
+    pub offline: bool,
+
+    /// Directory of OSV advisories to use as the vulnerability database
+    ///
+    /// Replaces every analyzer's own database with the dump (see
+    /// [`crate::vulnerability_db::OsvDumpDb`]) and, like `offline`, rules out
+    /// any network access. Results record the dump's date.
+    #[serde(default)]
+    pub offline_vuln_db: Option<PathBuf>,
+
+    /// Time treated as now, such as when judging whether a package was
+    /// published recently
+    ///
+    /// `None` uses the system clock; fix it for reproducible results.
+    #[serde(default)]
+    pub reference_time: Option<DateTime<Utc>>,
+
+    /// Dependency types that ship with the package, for the production risk level
+    #[serde(default = "default_production_types")]
+    pub production_dependency_types: Vec<DependencyType>,
+
+    /// Size limits checked before manifests are parsed
+    ///
+    /// Every analyzer applies `max_dependencies`; nesting and token limits
+    /// apply to the JSON, YAML and XML manifests, and the nesting limit to
+    /// Terraform's HCL.
+    #[serde(default)]
+    pub limits: ManifestLimits,
+
+    /// Most memory, in estimated bytes, an analysis may hold before it is aborted
+    ///
+    /// Every analyzer charges the manifests it reads, its dependency tree and
+    /// its pattern matches. Best-effort accounting of large allocations, not
+    /// an OS-enforced ceiling; see [`super::memory`]. `None` leaves memory
+    /// unbounded.
+    #[serde(default)]
+    pub max_memory_bytes: Option<usize>,
+
+    /// Most bytes an archive, such as a packed tarball, may unpack to
+    ///
+    /// Extraction stops with [`crate::PackageSecurityError::DecompressionBomb`]
+    /// once passed; see [`super::archive`].
+    #[serde(default = "default_max_decompressed_bytes")]
+    pub max_decompressed_bytes: u64,
+
+    /// Largest ratio of unpacked to packed size an archive may reach
+    ///
+    /// Only judged once an archive has unpacked past
+    /// [`super::archive::RATIO_GRACE_BYTES`].
+    #[serde(default = "default_max_compression_ratio")]
+    pub max_compression_ratio: f64,
+
+    /// Package names never reported as machine-generated
+    #[serde(default)]
+    pub name_allowlist: Vec<String>,
+
+    /// Packages allowed to hook module loading, in addition to known
+    /// instrumentation and transpiler packages
+    #[serde(default)]
+    pub loader_hook_allowlist: Vec<String>,
+
+    /// Strip escape sequences and hidden characters from package metadata in
+    /// the result, after reporting them, so reports cannot replay them into a
+    /// reviewer's terminal
+    #[serde(default = "default_sanitize_metadata")]
+    pub sanitize_metadata: bool,
+
+    /// How pre-release versions are matched against affected ranges
+    ///
+    /// `None` uses each ecosystem's own rules; see
+    /// [`PrereleasePolicy::for_ecosystem`].
+    #[serde(default)]
+    pub prerelease_matching: Option<PrereleasePolicy>,
+
+    /// Recommend pre-release fixes even when the installed version is stable
+    #[serde(default)]
+    pub recommend_prerelease_fixes: bool,
+
+    /// Hosts dependencies may and may not be fetched from
+    ///
+    /// Empty by default, which allows every source.
+    #[serde(default)]
+    pub source_policy: SourcePolicy,
+
+    /// SPDX licenses dependencies may and may not use
+    ///
+    /// Empty by default, which checks no licenses; see
+    /// [`AnalysisResult::license_findings`].
+    #[serde(default)]
+    pub license_policy: LicensePolicy,
+
+    /// Namespaces of packages published only to internal registries
+    ///
+    /// npm scopes (`@acme/`), name prefixes (`acme-*`) or exact names.
+    /// Dependencies under them that resolve from, or are published on, a
+    /// public registry are reported as dependency confusion; see
+    /// [`super::confusion`]. Empty by default.
+    #[serde(default)]
+    pub internal_scopes: Vec<String>,
+
+    /// Seconds vulnerability lookups are cached for across analyses
+    ///
+    /// An analyzer answers repeated lookups of the same package version from
+    /// its cache until they are this old; see
+    /// [`crate::vulnerability_db::LookupCache`]. Zero disables the cache.
+    #[serde(default = "default_vuln_cache_ttl_secs")]
+    pub vuln_cache_ttl_secs: u64,
+
+    /// Score bands and component weights to score results with
+    ///
+    /// `None` uses the defaults; see [`RiskScoringConfig`]. The weights
+    /// applied show in each result's `risk_score.components`.
+    #[serde(default)]
+    pub scoring: Option<RiskScoringConfig>,
+
+    /// YAML or JSON file of extra regex rules to scan package files with
+    ///
+    /// Matches are reported alongside the built-in malicious patterns; see
+    /// [`super::custom_rules`] for the format. A ruleset that fails to load
+    /// fails the analysis. Ignored when `scan_malicious_patterns` is off.
+    #[serde(default)]
+    pub custom_pattern_rules: Option<PathBuf>,
+
+    /// PEP 508 marker variables of the environment Python packages are
+    /// installed in, such as `python_version = "3.11"` or
+    /// `sys_platform = "linux"`
+    ///
+    /// Python requirements whose environment marker rules this environment
+    /// out are left out of the analysis. Markers naming a variable not given
+    /// hold, so an empty map, the default, keeps every requirement.
+    #[serde(default)]
+    pub python_env_markers: HashMap<String, String>,
+}
+
+fn default_typosquatting_distance() -> usize {
+    TyposquattingDetector::DEFAULT_MAX_DISTANCE
+}
+
+fn default_max_decompressed_bytes() -> u64 {
+    512 * 1024 * 1024
+}
+
+fn default_max_compression_ratio() -> f64 {
+    100.0
+}
+
+#[cfg(feature = "runtime")]
+fn default_vuln_cache_ttl_secs() -> u64 {
+    crate::vulnerability_db::cached::DEFAULT_TTL_SECS
+}
+
+/// Nothing is looked up without the runtime; an hour, as with it
+#[cfg(not(feature = "runtime"))]
+fn default_vuln_cache_ttl_secs() -> u64 {
+    60 * 60
+}
+
+fn default_sanitize_metadata() -> bool {
+    true
+}
+
+fn default_production_types() -> Vec<DependencyType> {
+    vec![
+        DependencyType::Runtime,
+        DependencyType::Optional,
+        DependencyType::Peer,
+    ]
+}
+
+impl AnalysisOptions {
+    /// Maximum sensitivity, accepting more false positives
+    ///
+    /// Enables every phase and sets:
+    /// - `typosquatting_max_distance`: 3
+    /// - `flag_install_scripts`: true
+    /// - `flag_mutable_images`: true
+    /// - `prerelease_matching`: `Include`, so pre-releases match by precedence
+    /// - `report_weak_signals`: true
+    /// - `validate_repository`: true
+    /// - `verify_provenance`: true
+    /// - `detect_registry_exfiltration`: true
+    /// - `analyze_reachability`: true
+    /// - `max_dependency_depth`: 20
+    /// - `timeout_seconds`: 900
+    ///
+    /// Binary import inspection and payload decoding run whenever
+    /// `scan_malicious_patterns` is on, always to their full depth.
+    pub fn paranoid() -> Self {
+        Self {
+            analyze_dependencies: true,
+            check_vulnerabilities: true,
+            scan_malicious_patterns: true,
+            detect_typosquatting: true,
+            max_dependency_depth: 20,
+            timeout_seconds: 900,
+            typosquatting_max_distance: 3,
+            flag_install_scripts: true,
+            flag_mutable_images: true,
+            prerelease_matching: Some(PrereleasePolicy::Include),
+            report_weak_signals: true,
+            validate_repository: true,
+            verify_provenance: true,
+            detect_registry_exfiltration: true,
+            analyze_reachability: true,
+            ..Self::default()
+        }
+    }
+
+    /// Fewer false positives, at the cost of missing subtler issues
+    ///
+    /// Enables every phase and sets:
+    /// - `typosquatting_max_distance`: 1
+    /// - `flag_install_scripts`: false
+    /// - `flag_mutable_images`: false
+    /// - `prerelease_matching`: the ecosystem default
+    /// - `report_weak_signals`: false
+    /// - `validate_repository`: false
+    /// - `verify_provenance`: false
+    /// - `detect_registry_exfiltration`: false
+    /// - `analyze_reachability`: false
+    /// - `max_dependency_depth`: 3
+    /// - `timeout_seconds`: 300
+    pub fn relaxed() -> Self {
+        Self {
+            max_dependency_depth: 3,
+            typosquatting_max_distance: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Default settings that give the same result on every run
+    ///
+    /// Sets `offline` and fixes `reference_time` to `now`, so results depend
+    /// only on the input files and the vulnerability database. Used by the
+    /// benchmarks in [`crate::benchmark`].
+    pub fn reproducible(now: DateTime<Utc>) -> Self {
+        Self {
+            offline: true,
+            reference_time: Some(now),
+            ..Self::default()
+        }
+    }
+
+    /// Current time as the analysis sees it
+    pub fn now(&self) -> DateTime<Utc> {
+        self.reference_time.unwrap_or_else(Utc::now)
+    }
+
+    /// Whether network checks may run
+    pub fn network_allowed(&self) -> bool {
+        !self.offline && self.offline_vuln_db.is_none() && !cfg!(feature = "offline")
+    }
+
+    /// Pre-release matching policy for an ecosystem
+    pub fn prerelease_policy(&self, ecosystem: &str) -> PrereleasePolicy {
+        self.prerelease_matching
+            .unwrap_or_else(|| PrereleasePolicy::for_ecosystem(ecosystem))
+    }
 }
 
 impl Default for AnalysisOptions {
@@ -195,6 +766,36 @@ impl Default for AnalysisOptions {
             detect_typosquatting: true,
             max_dependency_depth: 5,
             timeout_seconds: 300,
+            typosquatting_max_distance: TyposquattingDetector::DEFAULT_MAX_DISTANCE,
+            typosquatting_min_confidence: 0.0,
+            popular_packages: BTreeMap::new(),
+            flag_install_scripts: false,
+            flag_mutable_images: false,
+            report_weak_signals: false,
+            validate_repository: false,
+            verify_provenance: false,
+            detect_registry_exfiltration: false,
+            analyze_reachability: false,
+            offline: false,
+            offline_vuln_db: None,
+            reference_time: None,
+            production_dependency_types: default_production_types(),
+            limits: ManifestLimits::default(),
+            max_memory_bytes: None,
+            max_decompressed_bytes: default_max_decompressed_bytes(),
+            max_compression_ratio: default_max_compression_ratio(),
+            name_allowlist: Vec::new(),
+            loader_hook_allowlist: Vec::new(),
+            sanitize_metadata: default_sanitize_metadata(),
+            prerelease_matching: None,
+            recommend_prerelease_fixes: false,
+            source_policy: SourcePolicy::default(),
+            license_policy: LicensePolicy::default(),
+            internal_scopes: Vec::new(),
+            vuln_cache_ttl_secs: default_vuln_cache_ttl_secs(),
+            scoring: None,
+            custom_pattern_rules: None,
+            python_env_markers: HashMap::new(),
         }
     }
 }
@@ -0,0 +1,45 @@
+//! Inputs skipped because they could not be parsed
+//!
+//! A package with several manifests or lockfiles is still analyzed when some
+//! of them are broken, using whatever parsed. Each skipped file is reported so
+//! the result is known to be incomplete. A package none of whose manifests
+//! parse is still an error.
+
+use serde::{Deserialize, Serialize};
+
+use super::{rules, Finding, FindingType, RiskLevel, ScanReason};
+
+/// A manifest or lockfile left out of the analysis because it failed to parse
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialParse {
+    /// File that was skipped, relative to the package root
+    pub file: String,
+    /// Why it could not be parsed
+    pub reason: String,
+}
+
+impl PartialParse {
+    pub fn new(file: impl Into<String>, reason: impl std::fmt::Display) -> Self {
+        Self {
+            file: file.into(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Report as an informational `PartialParse` finding
+    ///
+    /// The finding carries no risk of its own; it marks the result as
+    /// incomplete.
+    pub fn to_finding(&self) -> Finding {
+        Finding::new(
+            FindingType::PartialParse,
+            RiskLevel::Safe,
+            format!("{} could not be parsed", self.file),
+            "The file was skipped and analysis continued with the remaining inputs, so results may be incomplete",
+        )
+        .with_evidence(self.reason.clone())
+        .with_affected_component(self.file.clone())
+        .with_scan_reason(ScanReason::Manifest)
+        .with_rule_id(rules::PARTIAL_PARSE)
+    }
+}
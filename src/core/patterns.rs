@@ -4,6 +4,25 @@ use anyhow::Result;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::encoding::{decode_payloads, find_encoded_literals, Encoding};
+
+/// Security products targeted by defense-evasion patterns
+const SECURITY_TOOLS: &str = "falcon-sensor|falcond|crowdstrike|cbagentd|carbonblack|sentinelone|sentinelagent|osqueryd|wazuh-agent|ossec|sysmon|clamd|clamav|freshclam|MsMpEng|WinDefend|MsSense|sophos|mcafee|elastic-agent|auditbeat|falco|tracee|rkhunter|chkrootkit|snort|suricata|cylance|tanium|splunkd|nessusd";
+
+/// Reads of the whole environment or of variables named like secrets
+const SENSITIVE_ENV_READ: &str = r#"JSON\.stringify\(\s*process\.env\b|Object\.(?:keys|entries|values|assign)\([^)]*process\.env\b|\.\.\.process\.env\b|process\.env\.(?:\w*(?:TOKEN|SECRET|PASSW|API_?KEY|ACCESS_KEY|PRIVATE_KEY|CREDENTIAL)\w*|(?:AWS|GITHUB|NPM|GCP|AZURE)_\w+)|os\.environ\.(?:copy|items|keys|values)\(|(?:dict|json\.dumps|str)\(\s*(?:dict\(\s*)?os\.environ\b|os\.(?:environ\.get\(|environ\[|getenv\()\s*['"]\w*(?:TOKEN|SECRET|PASSW|API_?KEY|ACCESS_KEY|PRIVATE_KEY|CREDENTIAL)\w*|\bprintenv\b|\benv\s*\|"#;
+
+/// Calls that send data off the machine
+const OUTBOUND_CALL: &str = r#"\bhttps?\.(?:request|get)\s*\(|\bfetch\s*\(|\baxios\b|XMLHttpRequest|\bnet\.(?:connect|createConnection)\s*\(|new\s+WebSocket\s*\(|\brequests\.(?:post|get|put)\s*\(|urllib\.request|\burlopen\s*\(|http\.client|\bcurl\s|\bwget\s|\bdns\.(?:lookup|resolve\w*)\s*\(|\bsocket\.gethostbyname"#;
+
+/// Data hex-encoded, as DNS labels only allow letters, digits and hyphens
+const HEX_ENCODE: &str = r#"\.toString\(\s*['"]hex['"]\s*\)|\bhexlify\s*\(|\.hex\(\)|\bxxd\s+-p\b|\bod\s+-A\s*n\s+-t\s*x1"#;
+
+/// Name lookups that reach an attacker's authoritative DNS server
+const DNS_LOOKUP: &str = r#"\bdns\.(?:promises\.)?(?:lookup|resolve\w*)\s*\(|\bsocket\.(?:gethostbyname|getaddrinfo)\s*\(|\bdns\.resolver\.(?:resolve|query)\s*\(|\bnslookup\s|\bdig\s"#;
 
 /// Malicious pattern definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,7 +35,106 @@ pub struct MaliciousPattern {
     pub indicators: Vec<String>,
     pub regex_patterns: Vec<String>,
     pub file_patterns: Vec<String>,
+    /// MITRE ATT&CK technique IDs this pattern maps to
+    #[serde(default)]
+    pub attack_techniques: Vec<String>,
     pub evidence: Vec<String>,
+    /// File the pattern matched in, empty when the content came from no file
+    #[serde(default)]
+    pub file: PathBuf,
+    /// 1-based line in `file` of the first match
+    #[serde(default)]
+    pub line: Option<usize>,
+    /// 1-based column, in characters, of the first match
+    #[serde(default)]
+    pub column: Option<usize>,
+    /// Where the pattern matched, one entry per matching regex in match order
+    #[serde(default)]
+    pub locations: Vec<PatternLocation>,
+}
+
+impl MaliciousPattern {
+    /// Point `file`, `line` and `column` at the first location in a file
+    pub(crate) fn update_position(&mut self) {
+        let first = self.locations.iter().find_map(|location| {
+            Some((
+                PathBuf::from(location.file.as_ref()?),
+                location.line,
+                location.column,
+            ))
+        });
+        match first {
+            Some((file, line, column)) => {
+                self.file = file;
+                self.line = Some(line);
+                self.column = Some(column);
+            }
+            None => {
+                self.file = PathBuf::new();
+                self.line = None;
+                self.column = None;
+            }
+        }
+    }
+
+    /// Add `pattern` to `detected`, folding it into an earlier match of the
+    /// same pattern; severity is the higher of the two
+    pub(crate) fn merge_into(self, detected: &mut Vec<MaliciousPattern>) {
+        match detected
+            .iter_mut()
+            .find(|existing| existing.pattern_id == self.pattern_id)
+        {
+            Some(existing) => {
+                existing.severity = existing.severity.clone().max(self.severity);
+                for evidence in self.evidence {
+                    if !existing.evidence.contains(&evidence) {
+                        existing.evidence.push(evidence);
+                    }
+                }
+                for location in self.locations {
+                    if !existing.locations.contains(&location) {
+                        existing.locations.push(location);
+                    }
+                }
+            }
+            None => detected.push(self),
+        }
+    }
+
+    /// Drop match positions, keeping `file`, when the scanned content was
+    /// extracted or decoded rather than the file's own text
+    pub(crate) fn forget_positions(&mut self) {
+        self.locations.clear();
+        self.line = None;
+        self.column = None;
+    }
+}
+
+/// Where in scanned content a pattern matched
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternLocation {
+    /// File the content came from, when the scan was given one
+    pub file: Option<String>,
+    /// Byte offset of the match in that file
+    pub offset: usize,
+    /// 1-based line of the start of the match
+    pub line: usize,
+    /// 1-based column, in characters, of the start of the match
+    pub column: usize,
+}
+
+impl PatternLocation {
+    /// Location of the match starting at byte `offset` of `content`
+    fn new(file: Option<&str>, content: &str, offset: usize) -> Self {
+        let before = &content[..offset];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        Self {
+            file: file.map(str::to_string),
+            offset,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
 }
 
 /// Pattern categories
@@ -32,6 +150,9 @@ pub enum PatternCategory {
     PrivilegeEscalation,
     Persistence,
     AntiAnalysis,
+    DefenseEvasion,
+    /// A rule from a custom ruleset that names no other category
+    Custom,
 }
 
 /// Pattern severity
@@ -44,8 +165,12 @@ pub enum PatternSeverity {
 }
 
 /// Pattern matcher for detecting malicious code
+///
+/// Compiled patterns are shared, so clones are cheap and scan with the same
+/// rule set.
+#[derive(Clone)]
 pub struct PatternMatcher {
-    patterns: Vec<CompiledPattern>,
+    patterns: Arc<[CompiledPattern]>,
 }
 
 /// Compiled pattern with regex
@@ -63,7 +188,9 @@ impl PatternMatcher {
             .map(Self::compile_pattern)
             .collect::<Result<Vec<_>>>()?;
 
-        Ok(Self { patterns: compiled })
+        Ok(Self {
+            patterns: compiled.into(),
+        })
     }
 
     /// Create matcher with custom patterns
@@ -73,7 +200,26 @@ impl PatternMatcher {
             .map(Self::compile_pattern)
             .collect::<Result<Vec<_>>>()?;
 
-        Ok(Self { patterns: compiled })
+        Ok(Self {
+            patterns: compiled.into(),
+        })
+    }
+
+    /// Create matcher with the default patterns followed by `custom` ones
+    pub fn with_custom_patterns(custom: Vec<MaliciousPattern>) -> Result<Self> {
+        let mut patterns = Self::default_patterns();
+        patterns.extend(custom);
+        Self::with_patterns(patterns)
+    }
+
+    /// Number of compiled patterns
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// Whether the matcher has no patterns
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
     }
 
     /// Compile a pattern
@@ -91,12 +237,134 @@ impl PatternMatcher {
     }
 
     /// Scan content for malicious patterns
+    ///
+    /// Encoded blobs handed to decode-and-execute sinks are decoded and their
+    /// innermost layer scanned too, with the encoding chain added as evidence.
     pub fn scan(&self, content: &str, file_path: Option<&str>) -> Vec<MaliciousPattern> {
+        let mut detected = self.scan_plain(content, file_path);
+
+        for payload in decode_payloads(content) {
+            let note = format!(
+                "Decoded {} payload passed to {}{}",
+                payload.chain_description(),
+                payload.sink,
+                if payload.truncated {
+                    " (decoding stopped at limit)"
+                } else {
+                    ""
+                }
+            );
+            for mut pattern in self.scan_plain(&payload.content, None) {
+                pattern.evidence.insert(0, note.clone());
+                // Offsets into the decoded payload point nowhere in the file
+                pattern.forget_positions();
+                match detected
+                    .iter_mut()
+                    .find(|existing| existing.pattern_id == pattern.pattern_id)
+                {
+                    Some(existing) => existing.evidence.extend(pattern.evidence),
+                    None => detected.push(pattern),
+                }
+            }
+        }
+
+        detected
+    }
+
+    /// Scan content for long base64 and hex string literals
+    ///
+    /// Each literal decoding to text is reported as `ENC_001` (base64) or
+    /// `ENC_002` (hex) by its outermost encoding. The decoded text is scanned
+    /// in turn; what it matches is reported at the literal's position, and
+    /// raises the encoded payload to at least high severity.
+    pub fn scan_encoded(&self, content: &str, file_path: Option<&str>) -> Vec<MaliciousPattern> {
+        let mut detected: Vec<MaliciousPattern> = Vec::new();
+
+        for literal in find_encoded_literals(content) {
+            let chain = literal.chain_description();
+            let location = PatternLocation::new(file_path, content, literal.offset);
+            let preview: String = literal.content.chars().take(120).collect();
+            let mut encoded = Self::encoded_payload_pattern(literal.chain[0]);
+            encoded.evidence.push(format!(
+                "{}-character {} literal decodes to {:?}{}",
+                literal.len,
+                chain,
+                preview,
+                if literal.truncated {
+                    " (decoding stopped at limit)"
+                } else {
+                    ""
+                }
+            ));
+            encoded.locations.push(location.clone());
+
+            let mut nested = self.scan(&literal.content, None);
+            for pattern in &mut nested {
+                encoded.evidence.push(format!(
+                    "Decoded content matches {} ({})",
+                    pattern.pattern_id, pattern.pattern_name
+                ));
+                encoded.severity = encoded
+                    .severity
+                    .clone()
+                    .max(pattern.severity.clone())
+                    .max(PatternSeverity::High);
+                pattern.evidence.insert(
+                    0,
+                    format!("Inside {} literal ({})", chain, encoded.pattern_id),
+                );
+                pattern.locations = vec![location.clone()];
+            }
+
+            for mut pattern in std::iter::once(encoded).chain(nested) {
+                pattern.update_position();
+                if let Some(path) = file_path {
+                    pattern.file = PathBuf::from(path);
+                }
+                pattern.merge_into(&mut detected);
+            }
+        }
+
+        detected
+    }
+
+    /// Pattern reported for a long literal whose outermost layer is `encoding`
+    ///
+    /// Matched by [`Self::scan_encoded`] rather than by regex.
+    fn encoded_payload_pattern(encoding: Encoding) -> MaliciousPattern {
+        let (id, name) = match encoding {
+            Encoding::Hex => ("ENC_002", "Hex-encoded payload"),
+            _ => ("ENC_001", "Base64-encoded payload"),
+        };
+        MaliciousPattern {
+            pattern_id: id.to_string(),
+            pattern_name: name.to_string(),
+            description: format!(
+                "Detects long {} string literals that decode to text or compressed data",
+                encoding
+            ),
+            category: PatternCategory::Obfuscation,
+            severity: PatternSeverity::Medium,
+            indicators: vec![encoding.to_string()],
+            regex_patterns: vec![],
+            file_patterns: vec![],
+            attack_techniques: vec!["T1027".to_string(), "T1140".to_string()],
+            evidence: vec![],
+            file: PathBuf::new(),
+            line: None,
+            column: None,
+            locations: vec![],
+        }
+    }
+
+    /// Scan content as-is, without decoding embedded payloads
+    fn scan_plain(&self, content: &str, file_path: Option<&str>) -> Vec<MaliciousPattern> {
         let mut detected = Vec::new();
 
-        for compiled in &self.patterns {
+        for compiled in self.patterns.iter() {
             let mut matches = false;
             let mut evidence = Vec::new();
+            let mut locations = Vec::new();
 
             // Check regex patterns
             for regex in &compiled.regex_matchers {
@@ -107,6 +375,17 @@ impl PatternMatcher {
                         regex.as_str(),
                         m.start()
                     ));
+                    locations.push(PatternLocation::new(file_path, content, m.start()));
+
+                    // Patterns may name what they act on with a `target` capture group
+                    for caps in regex.captures_iter(content) {
+                        if let Some(target) = caps.name("target") {
+                            let entry = format!("Target: {}", target.as_str());
+                            if !evidence.contains(&entry) {
+                                evidence.push(entry);
+                            }
+                        }
+                    }
                 }
             }
 
@@ -123,6 +402,12 @@ impl PatternMatcher {
             if matches {
                 let mut pattern = compiled.pattern.clone();
                 pattern.evidence = evidence;
+                locations.sort_by_key(|location| location.offset);
+                pattern.locations = locations;
+                pattern.update_position();
+                if let Some(path) = file_path {
+                    pattern.file = PathBuf::from(path);
+                }
                 detected.push(pattern);
             }
         }
@@ -131,7 +416,7 @@ impl PatternMatcher {
     }
 
     /// Get default malicious patterns
-    fn default_patterns() -> Vec<MaliciousPattern> {
+    pub(crate) fn default_patterns() -> Vec<MaliciousPattern> {
         vec![
             // Code execution patterns
             MaliciousPattern {
@@ -153,7 +438,12 @@ impl PatternMatcher {
                     r"os\.system\s*\(".to_string(),
                 ],
                 file_patterns: vec![],
+                attack_techniques: vec![],
                 evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
             },
             // Data exfiltration patterns
             MaliciousPattern {
@@ -161,7 +451,9 @@ impl PatternMatcher {
                 pattern_name: "Environment variable access".to_string(),
                 description: "Detects attempts to access environment variables".to_string(),
                 category: PatternCategory::DataExfiltration,
-                severity: PatternSeverity::High,
+                // Telemetry and configuration read the environment too; sending
+                // it anywhere is EXFIL_002
+                severity: PatternSeverity::Medium,
                 indicators: vec!["process.env".to_string(), "os.environ".to_string()],
                 regex_patterns: vec![
                     r"process\.env\.[A-Z_]+".to_string(),
@@ -169,7 +461,62 @@ impl PatternMatcher {
                     r"getenv\s*\(".to_string(),
                 ],
                 file_patterns: vec![],
+                attack_techniques: vec![],
+                evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
+            },
+            MaliciousPattern {
+                pattern_id: "EXFIL_002".to_string(),
+                pattern_name: "Environment variable exfiltration".to_string(),
+                description: "Detects secrets or the whole environment read and sent over the network in the same script".to_string(),
+                category: PatternCategory::DataExfiltration,
+                severity: PatternSeverity::Critical,
+                indicators: vec![
+                    "JSON.stringify(process.env)".to_string(),
+                    "os.environ.copy()".to_string(),
+                ],
+                regex_patterns: vec![format!(
+                    r"(?s)(?:{env}).*?(?:{out})|(?:{out}).*?(?:{env})",
+                    env = SENSITIVE_ENV_READ,
+                    out = OUTBOUND_CALL
+                )],
+                file_patterns: vec![],
+                attack_techniques: vec!["T1552.001".to_string(), "T1041".to_string()],
+                evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
+            },
+            MaliciousPattern {
+                pattern_id: "EXFIL_003".to_string(),
+                pattern_name: "DNS beacon".to_string(),
+                description: "Detects hex-encoded data looked up as DNS subdomains".to_string(),
+                category: PatternCategory::DataExfiltration,
+                severity: PatternSeverity::High,
+                indicators: vec!["dns.lookup".to_string(), "toString('hex')".to_string()],
+                regex_patterns: vec![
+                    format!(
+                        r"(?s)(?:{hex}).{{0,400}}?(?:{dns})|(?:{dns}).{{0,400}}?(?:{hex})",
+                        hex = HEX_ENCODE,
+                        dns = DNS_LOOKUP
+                    ),
+                    // Lookups of a literal name led by a long hex label
+                    format!(
+                        r#"(?:{dns})\s*['"`]?[0-9a-f]{{24,}}\.[a-z0-9-]+\."#,
+                        dns = DNS_LOOKUP
+                    ),
+                ],
+                file_patterns: vec![],
+                attack_techniques: vec!["T1071.004".to_string(), "T1048".to_string()],
                 evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
             },
             // Backdoor patterns
             MaliciousPattern {
@@ -190,7 +537,12 @@ impl PatternMatcher {
                     r"socket\.socket\s*\(".to_string(),
                 ],
                 file_patterns: vec![],
+                attack_techniques: vec![],
                 evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
             },
             // Crypto mining patterns
             MaliciousPattern {
@@ -210,7 +562,12 @@ impl PatternMatcher {
                     r"coinhive|cryptoloot".to_string(),
                 ],
                 file_patterns: vec![],
+                attack_techniques: vec![],
                 evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
             },
             // Obfuscation patterns
             MaliciousPattern {
@@ -226,8 +583,15 @@ impl PatternMatcher {
                     r#"Buffer\.from\([^,]+,\s*['"]base64"#.to_string(),
                 ],
                 file_patterns: vec![],
+                attack_techniques: vec![],
                 evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
             },
+            Self::encoded_payload_pattern(Encoding::Base64),
+            Self::encoded_payload_pattern(Encoding::Hex),
             // Persistence patterns
             MaliciousPattern {
                 pattern_id: "PERS_001".to_string(),
@@ -246,7 +610,141 @@ impl PatternMatcher {
                     r"crontab|systemd|systemctl".to_string(),
                 ],
                 file_patterns: vec![],
+                attack_techniques: vec![],
+                evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
+            },
+            MaliciousPattern {
+                pattern_id: "PERS_002".to_string(),
+                pattern_name: "Windows Run key persistence".to_string(),
+                description: "Detects writes to registry Run keys that start a program at logon"
+                    .to_string(),
+                category: PatternCategory::Persistence,
+                severity: PatternSeverity::Critical,
+                indicators: vec![
+                    "reg add".to_string(),
+                    "CurrentVersion\\Run".to_string(),
+                    "New-ItemProperty".to_string(),
+                ],
+                regex_patterns: vec![
+                    r"(?i)(?P<target>(?:HK(?:CU|LM)|HKEY_(?:CURRENT_USER|LOCAL_MACHINE))?[:\\]*Software\\+Microsoft\\+Windows\\+CurrentVersion\\+Run(?:Once)?)\b"
+                        .to_string(),
+                ],
+                file_patterns: vec![],
+                attack_techniques: vec!["T1547.001".to_string()],
+                evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
+            },
+            MaliciousPattern {
+                pattern_id: "PERS_003".to_string(),
+                pattern_name: "Windows Startup folder persistence".to_string(),
+                description: "Detects files placed in the Windows Startup folder".to_string(),
+                category: PatternCategory::Persistence,
+                severity: PatternSeverity::Critical,
+                indicators: vec!["Start Menu\\Programs\\Startup".to_string(), "shell:startup".to_string()],
+                regex_patterns: vec![
+                    r#"(?i)(?P<target>Start Menu[\\/]+Programs[\\/]+Startup(?:[\\/]+[^\s'"`;)]+)?)"#
+                        .to_string(),
+                    r"(?i)(?P<target>shell:(?:common )?startup)\b".to_string(),
+                ],
+                file_patterns: vec![],
+                attack_techniques: vec!["T1547.001".to_string()],
+                evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
+            },
+            MaliciousPattern {
+                pattern_id: "PERS_004".to_string(),
+                pattern_name: "macOS launch agent persistence".to_string(),
+                description: "Detects launch agents or daemons installed to run at login or boot"
+                    .to_string(),
+                category: PatternCategory::Persistence,
+                severity: PatternSeverity::Critical,
+                indicators: vec![
+                    "LaunchAgents".to_string(),
+                    "LaunchDaemons".to_string(),
+                    "launchctl load".to_string(),
+                ],
+                regex_patterns: vec![
+                    r#"(?P<target>(?:~|\$HOME|/Users/[^/\s'"]+)?/?Library/Launch(?:Agents|Daemons)(?:/[^\s'"`;)]+)?)"#
+                        .to_string(),
+                    r#"['"](?P<target>Launch(?:Agents|Daemons))['"]"#.to_string(),
+                    r#"\blaunchctl\s+(?:load|bootstrap|enable)\s+(?:-\w+\s+)*(?P<target>[^\s'"`;)]+)"#
+                        .to_string(),
+                ],
+                file_patterns: vec![],
+                attack_techniques: vec!["T1543.001".to_string(), "T1543.004".to_string()],
                 evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
+            },
+            MaliciousPattern {
+                pattern_id: "PERS_005".to_string(),
+                pattern_name: "systemd unit persistence".to_string(),
+                description: "Detects systemd system or user units installed or enabled"
+                    .to_string(),
+                category: PatternCategory::Persistence,
+                severity: PatternSeverity::Critical,
+                indicators: vec![
+                    ".config/systemd/user".to_string(),
+                    "/etc/systemd/system".to_string(),
+                    "systemctl enable".to_string(),
+                ],
+                regex_patterns: vec![
+                    r#"(?P<target>(?:~|\$HOME)?/?\.config/systemd/user(?:/[^\s'"`;)]+)?)"#
+                        .to_string(),
+                    r#"(?P<target>/(?:etc|lib|usr/lib)/systemd/system(?:/[^\s'"`;)]+)?)"#
+                        .to_string(),
+                    r"\bsystemctl\s+(?:--user\s+)?enable\s+(?:--now\s+)?(?P<target>[\w@.-]+)"
+                        .to_string(),
+                ],
+                file_patterns: vec![],
+                attack_techniques: vec!["T1543.002".to_string()],
+                evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
+            },
+            MaliciousPattern {
+                pattern_id: "PERS_006".to_string(),
+                pattern_name: "Scheduled task persistence".to_string(),
+                description: "Detects cron jobs and Windows scheduled tasks created at install"
+                    .to_string(),
+                category: PatternCategory::Persistence,
+                severity: PatternSeverity::Critical,
+                indicators: vec![
+                    "crontab -".to_string(),
+                    "schtasks /create".to_string(),
+                    "Register-ScheduledTask".to_string(),
+                ],
+                regex_patterns: vec![
+                    r#"(?P<target>/etc/cron(?:tab|\.d|\.hourly|\.daily|\.weekly|\.monthly)(?:/[^\s'"`;)]+)?)"#
+                        .to_string(),
+                    r#"(?P<target>/var/spool/cron(?:/[^\s'"`;)]+)?)"#.to_string(),
+                    r"\|\s*(?P<target>crontab)\s+-(?:\s|$)".to_string(),
+                    r#"(?i)\bschtasks(?:\.exe)?\s+/create\b[^\n]*?/tn\s+['"]?(?P<target>[^'"\s/]+)"#
+                        .to_string(),
+                    r#"(?i)\bRegister-ScheduledTask\b[^\n]*?-TaskName\s+['"]?(?P<target>[^'"\s]+)"#
+                        .to_string(),
+                ],
+                file_patterns: vec![],
+                attack_techniques: vec!["T1053.003".to_string(), "T1053.005".to_string()],
+                evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
             },
             // Anti-analysis patterns
             MaliciousPattern {
@@ -266,7 +764,12 @@ impl PatternMatcher {
                     r"ptrace.*PTRACE_TRACEME".to_string(),
                 ],
                 file_patterns: vec![],
+                attack_techniques: vec![],
                 evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
             },
             // Network access patterns
             MaliciousPattern {
@@ -286,7 +789,12 @@ impl PatternMatcher {
                     r"curl|wget".to_string(),
                 ],
                 file_patterns: vec![],
+                attack_techniques: vec![],
                 evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
             },
             // File system access patterns
             MaliciousPattern {
@@ -306,7 +814,136 @@ impl PatternMatcher {
                     r"rm\s+-rf|del\s+/[qsf]".to_string(),
                 ],
                 file_patterns: vec![],
+                attack_techniques: vec![],
+                evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
+            },
+            // Defense evasion patterns
+            MaliciousPattern {
+                pattern_id: "EVAS_001".to_string(),
+                pattern_name: "Security tool tampering".to_string(),
+                description: "Detects attempts to kill, stop or uninstall security tooling"
+                    .to_string(),
+                category: PatternCategory::DefenseEvasion,
+                severity: PatternSeverity::Critical,
+                indicators: vec![
+                    "pkill".to_string(),
+                    "taskkill".to_string(),
+                    "uninstall security agent".to_string(),
+                ],
+                regex_patterns: vec![
+                    format!(
+                        r"(?i)\b(?:kill|pkill|killall|taskkill)\b[^;&|\n]*?\b(?P<target>{})\b",
+                        SECURITY_TOOLS
+                    ),
+                    format!(
+                        r"(?i)\b(?:apt(?:-get)?\s+(?:-y\s+)?(?:remove|purge)|yum\s+(?:-y\s+)?(?:remove|erase)|dnf\s+(?:-y\s+)?remove|rpm\s+-e|systemctl\s+(?:stop|disable|mask)|sc(?:\.exe)?\s+(?:stop|delete|config))\s+[^;&|\n]*?\b(?P<target>{})\b",
+                        SECURITY_TOOLS
+                    ),
+                    format!(
+                        r"(?i)\bservice\s+(?P<target>{})\s+stop\b",
+                        SECURITY_TOOLS
+                    ),
+                    r"(?i)Set-MpPreference\s+[^\n]*-(?P<target>Disable\w+)\s+(?:\$true|1)".to_string(),
+                ],
+                file_patterns: vec![],
+                attack_techniques: vec!["T1562.001".to_string()],
+                evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
+            },
+            MaliciousPattern {
+                pattern_id: "EVAS_002".to_string(),
+                pattern_name: "Shell history clearing".to_string(),
+                description: "Detects attempts to clear or disable shell command history"
+                    .to_string(),
+                category: PatternCategory::DefenseEvasion,
+                severity: PatternSeverity::Critical,
+                indicators: vec![
+                    "history -c".to_string(),
+                    "unset HISTFILE".to_string(),
+                    ".bash_history".to_string(),
+                ],
+                regex_patterns: vec![
+                    r"\bhistory\s+-c\b".to_string(),
+                    r"\bunset\s+(?P<target>HISTFILE)\b".to_string(),
+                    r"\b(?P<target>HISTFILE)=/dev/null".to_string(),
+                    r"\b(?P<target>HISTSIZE)=0\b".to_string(),
+                    r"(?:\brm\s+(?:-\w+\s+)*|>\s*|ln\s+-sf\s+/dev/null\s+)[^\s;&|]*\.(?P<target>bash_history|zsh_history|history)\b"
+                        .to_string(),
+                    r"(?i)Clear-History\b|Remove-Item\s+[^\n]*(?P<target>ConsoleHost_history)"
+                        .to_string(),
+                ],
+                file_patterns: vec![],
+                attack_techniques: vec!["T1070.003".to_string()],
+                evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
+            },
+            MaliciousPattern {
+                pattern_id: "EVAS_003".to_string(),
+                pattern_name: "Audit logging disabled".to_string(),
+                description: "Detects attempts to disable or wipe audit and event logging"
+                    .to_string(),
+                category: PatternCategory::DefenseEvasion,
+                severity: PatternSeverity::Critical,
+                indicators: vec![
+                    "auditctl".to_string(),
+                    "wevtutil cl".to_string(),
+                    "stop auditd".to_string(),
+                ],
+                regex_patterns: vec![
+                    r"\b(?P<target>auditctl)\s+-(?:D\b|e\s*0\b)".to_string(),
+                    r"\b(?:systemctl\s+(?:stop|disable|mask)|service)\s+(?P<target>auditd|rsyslog|syslog-ng|systemd-journald)\b"
+                        .to_string(),
+                    r"(?i)\bwevtutil(?:\.exe)?\s+(?:cl|clear-log)\s+(?P<target>\w+)".to_string(),
+                    r"(?i)\bClear-EventLog\b[^\n]*?-LogName\s+(?P<target>\w+)".to_string(),
+                    r"\brm\s+-\w*[rf]\w*\s+(?P<target>/var/log\S*)".to_string(),
+                ],
+                file_patterns: vec![],
+                attack_techniques: vec!["T1562.012".to_string(), "T1562.002".to_string()],
+                evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
+            },
+            MaliciousPattern {
+                pattern_id: "EVAS_004".to_string(),
+                pattern_name: "Firewall modification".to_string(),
+                description: "Detects attempts to disable or flush host firewall rules"
+                    .to_string(),
+                category: PatternCategory::DefenseEvasion,
+                severity: PatternSeverity::Critical,
+                indicators: vec![
+                    "iptables -F".to_string(),
+                    "ufw disable".to_string(),
+                    "netsh advfirewall".to_string(),
+                ],
+                regex_patterns: vec![
+                    r"\b(?P<target>iptables|ip6tables)\s+(?:-F\b|--flush\b|-P\s+\w+\s+ACCEPT\b)"
+                        .to_string(),
+                    r"\b(?P<target>ufw)\s+disable\b".to_string(),
+                    r"\bsystemctl\s+(?:stop|disable|mask)\s+(?P<target>firewalld|ufw|nftables)\b"
+                        .to_string(),
+                    r"(?i)\b(?P<target>netsh)\s+(?:advfirewall\s+set\s+\w+\s+state\s+off|firewall\s+set\s+opmode\s+(?:mode=)?disable)"
+                        .to_string(),
+                    r"(?i)\b(?P<target>Set-NetFirewallProfile)\b[^\n]*-Enabled\s+False".to_string(),
+                ],
+                file_patterns: vec![],
+                attack_techniques: vec!["T1562.004".to_string()],
                 evidence: vec![],
+                file: PathBuf::new(),
+                line: None,
+                column: None,
+                locations: vec![],
             },
         ]
     }
@@ -0,0 +1,195 @@
+//! How tightly direct dependencies pin their versions
+//!
+//! Each declared version specifier is classified as pinned, caret, tilde,
+//! range or wildcard. A specifier is loose when its [`RangeBreadth`] goes past
+//! one release line, as `*`, `latest` or `>=1.0.0` do: every future release
+//! it admits, a compromised one included, installs without review. A lockfile
+//! mitigates a loose specifier by holding the installed version until the
+//! lockfile is next updated.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{DependencyAnalysis, DependencySource};
+use crate::versioning::{range_breadth, RangeBreadth};
+
+/// Loose dependencies listed in [`PinningReport::worst_offenders`]
+const WORST_OFFENDERS: usize = 10;
+
+/// How a version specifier pins a dependency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PinningStyle {
+    /// A single version (`4.17.21`, `==2.0.1`, `=1.0.3` in Cargo)
+    Pinned,
+    /// Updates within a major version (`^1.2.3`, a bare version in Cargo)
+    Caret,
+    /// Updates within a minor version (`~1.2.3`, `~> 5.0`, `~=2.28`)
+    Tilde,
+    /// Any other range (`>=1.2`, `>=1 <4`, `1.x`)
+    Range,
+    /// Any version at all (`*`, `latest`, empty)
+    Wildcard,
+}
+
+impl PinningStyle {
+    /// Classify `spec` as the `ecosystem` package manager reads it
+    ///
+    /// Returns `None` for specifiers that aren't version ranges, such as git
+    /// URLs, local paths or `npm:` aliases.
+    pub fn classify(spec: &str, ecosystem: &str) -> Option<Self> {
+        let breadth = range_breadth(spec)?;
+        let spec = spec.trim();
+        Some(match breadth {
+            RangeBreadth::Any => Self::Wildcard,
+            RangeBreadth::Exact if ecosystem == "cargo" && !spec.starts_with('=') => Self::Caret,
+            RangeBreadth::Exact => Self::Pinned,
+            RangeBreadth::Compatible if spec.starts_with('^') => Self::Caret,
+            RangeBreadth::Compatible if spec.starts_with('~') => Self::Tilde,
+            _ => Self::Range,
+        })
+    }
+}
+
+/// A direct dependency whose specifier admits more than one release line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LooseDependency {
+    pub name: String,
+    pub version_spec: String,
+    pub style: PinningStyle,
+    pub breadth: RangeBreadth,
+    /// Version a lockfile holds it at, if one does
+    pub locked_version: Option<String>,
+}
+
+impl LooseDependency {
+    /// Whether a lockfile holds it at one version
+    pub fn mitigated_by_lockfile(&self) -> bool {
+        self.locked_version.is_some()
+    }
+}
+
+/// Pinning of a package's direct dependencies
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PinningReport {
+    /// Direct dependencies per pinning style
+    pub counts: BTreeMap<PinningStyle, usize>,
+    /// Direct dependencies from git, a path or a URL, or declared as aliases,
+    /// which have no version specifier to classify
+    pub unclassified: usize,
+    /// Direct dependencies with a loose specifier
+    pub loose: usize,
+    /// Loose dependencies a lockfile holds at one version
+    pub mitigated_by_lockfile: usize,
+    /// Loose dependencies, broadest first and those without a lockfile first
+    /// among equals; at most ten
+    pub worst_offenders: Vec<LooseDependency>,
+}
+
+impl PinningReport {
+    /// Classify the direct dependencies of `analysis`, declared in `ecosystem`
+    pub fn from_analysis(analysis: &DependencyAnalysis, ecosystem: &str) -> Self {
+        let mut report = Self::default();
+        let mut offenders = Vec::new();
+        for dep in analysis.dependency_tree.iter().filter(|dep| dep.is_direct) {
+            let from_registry =
+                matches!(dep.source, None | Some(DependencySource::Registry { .. }));
+            let classified = range_breadth(&dep.version_spec)
+                .zip(PinningStyle::classify(&dep.version_spec, ecosystem))
+                .filter(|_| from_registry);
+            let Some((breadth, style)) = classified else {
+                report.unclassified += 1;
+                continue;
+            };
+            *report.counts.entry(style).or_default() += 1;
+            if breadth <= RangeBreadth::Compatible {
+                continue;
+            }
+            report.loose += 1;
+            if dep.resolved_version.is_some() {
+                report.mitigated_by_lockfile += 1;
+            }
+            offenders.push(LooseDependency {
+                name: dep.name.clone(),
+                version_spec: dep.version_spec.clone(),
+                style,
+                breadth,
+                locked_version: dep.resolved_version.clone(),
+            });
+        }
+        offenders.sort_by(|a, b| {
+            b.breadth
+                .cmp(&a.breadth)
+                .then_with(|| a.mitigated_by_lockfile().cmp(&b.mitigated_by_lockfile()))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        offenders.truncate(WORST_OFFENDERS);
+        report.worst_offenders = offenders;
+        report
+    }
+
+    /// Direct dependencies pinned in `style`
+    pub fn count(&self, style: PinningStyle) -> usize {
+        self.counts.get(&style).copied().unwrap_or(0)
+    }
+
+    /// Direct dependencies with a version specifier
+    pub fn classified(&self) -> usize {
+        self.counts.values().sum()
+    }
+
+    /// Share of classified dependencies that are loose, from 0 to 1
+    pub fn loose_share(&self) -> f32 {
+        Self::share(self.loose, self.classified())
+    }
+
+    /// Share of classified dependencies that are loose with no lockfile
+    /// holding them, from 0 to 1
+    pub fn unmitigated_share(&self) -> f32 {
+        Self::share(self.loose - self.mitigated_by_lockfile, self.classified())
+    }
+
+    fn share(part: usize, whole: usize) -> f32 {
+        if whole == 0 {
+            0.0
+        } else {
+            part as f32 / whole as f32
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        use PinningStyle::*;
+        let cases = [
+            ("4.17.21", "npm", Pinned),
+            ("==2.0.1", "python", Pinned),
+            ("=1.0.3", "cargo", Pinned),
+            ("1.0.3", "cargo", Caret),
+            ("^1.2.3", "npm", Caret),
+            ("~1.2.3", "npm", Tilde),
+            ("~> 5.0", "ruby", Tilde),
+            ("~=2.28", "python", Tilde),
+            ("1.x", "npm", Range),
+            (">=1.0.0", "npm", Range),
+            (">=1 <4", "npm", Range),
+            ("*", "npm", Wildcard),
+            ("latest", "npm", Wildcard),
+            ("", "python", Wildcard),
+        ];
+        for (spec, ecosystem, expected) in cases {
+            assert_eq!(
+                PinningStyle::classify(spec, ecosystem),
+                Some(expected),
+                "{}",
+                spec
+            );
+        }
+        assert_eq!(PinningStyle::classify("file:../local", "npm"), None);
+    }
+}
@@ -0,0 +1,262 @@
+//! Detection of install-time payloads gated on the operating system or CPU
+//!
+//! Malware may only act on the platforms it targets, such as Windows
+//! workstations or Linux build hosts, so sandboxes running anything else see
+//! nothing. Installers branching on the platform are also common and
+//! legitimate, for picking a prebuilt binary or a build command, so a gated
+//! branch scores low unless it also looks like a dropper.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::evasion::ACTIONS;
+use super::{rules, Finding, FindingType, RiskLevel, ScanReason};
+
+/// Comparisons of the platform or architecture with a literal, capturing the
+/// literal as `value` and the operator as `op`
+static CHECKS: Lazy<Vec<Regex>> = Lazy::new(|| {
+    [
+        r#"(?:process\.platform|process\.arch|os\.(?:platform|arch|type)\(\))\s*(?P<op>[!=]==?)\s*['"](?P<value>[\w-]+)['"]"#,
+        r#"['"](?P<value>[\w-]+)['"]\s*(?P<op>[!=]==?)\s*(?:process\.platform|process\.arch|os\.(?:platform|arch|type)\(\))"#,
+        r#"(?:sys\.platform|os\.name|platform\.(?:system|machine)\(\))\s*(?P<op>==|!=|\.startswith\()\s*['"](?P<value>[\w-]+)['"]"#,
+        r#"\$\(\s*uname(?:\s+-[sm])?\s*\)"?\s*(?P<op>!?==?)\s*"?(?P<value>[\w-]+)"#,
+    ]
+    .into_iter()
+    .map(|pattern| Regex::new(pattern).unwrap())
+    .collect()
+});
+
+/// Writes to disk and permission changes a branch might perform
+static FILE_WRITES: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"\bfs\.(?:writeFileSync|writeFile|createWriteStream|copyFileSync|chmodSync|chmod)\(|\bopen\([^)\n]*['"][wa]b?['"]|\bshutil\.(?:copy\w*|move)\(|\bchmod\s+\+?[0-7x]+"#,
+    )
+    .unwrap()
+});
+
+/// Traits of a dropper rather than an installer, labelled
+static DROPPER_TRAITS: Lazy<Vec<(&'static str, Regex)>> = Lazy::new(|| {
+    [
+        (
+            "executable file",
+            r#"(?i)['"][^'"\n]*\.(?:exe|dll|scr|bat|cmd|ps1|vbs|hta)['"]"#,
+        ),
+        (
+            "temporary or user profile directory",
+            r"(?i)\bos\.tmpdir\(\)|\btempfile\.|%(?:temp|appdata|localappdata)%|\bprocess\.env\.(?:TEMP|TMP|APPDATA|LOCALAPPDATA)\b|/tmp/|/dev/shm/",
+        ),
+        (
+            "decoded payload",
+            r#"\bBuffer\.from\([^)\n]*['"]base64['"]|\batob\(|\bbase64\.b64decode\(|\bbase64\s+(?:-d|--decode)\b"#,
+        ),
+        (
+            "dynamic code",
+            r"\beval\(|\bnew\s+Function\(|\bexec\(\s*compile\(",
+        ),
+        (
+            "detached process",
+            r"\bdetached\s*:\s*true|\.unref\(\)|\bstart\s+/b\b|\bnohup\b|\bDETACHED_PROCESS\b",
+        ),
+    ]
+    .into_iter()
+    .map(|(label, pattern)| (label, Regex::new(pattern).unwrap()))
+    .collect()
+});
+
+/// A payload that only runs on some platforms
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlatformTargetedPayload {
+    /// Platforms the gated code runs on, such as `win32` or `not linux`
+    pub platforms: Vec<String>,
+    /// Network, execution and file operations inside gated branches
+    pub gated_actions: Vec<String>,
+    /// Dropper traits found in the same branches
+    pub suspicious: Vec<String>,
+}
+
+impl PlatformTargetedPayload {
+    /// Scan script content for platform checks guarding actions
+    pub fn detect(content: &str) -> Option<Self> {
+        let mut platforms = Vec::new();
+        let mut gated_actions = Vec::new();
+        let mut suspicious = Vec::new();
+        for regex in CHECKS.iter() {
+            for captures in regex.captures_iter(content) {
+                let whole = captures.get(0).unwrap();
+                let branch = gated_branch(content, whole.start(), whole.end());
+                let actions: Vec<&str> = ACTIONS
+                    .find_iter(branch)
+                    .chain(FILE_WRITES.find_iter(branch))
+                    .map(|action| action.as_str().trim())
+                    .collect();
+                if actions.is_empty() {
+                    continue;
+                }
+                let platform = if captures["op"].starts_with('!') {
+                    format!("not {}", &captures["value"])
+                } else {
+                    captures["value"].to_string()
+                };
+                push_unique(&mut platforms, platform);
+                for action in actions {
+                    push_unique(&mut gated_actions, action.to_string());
+                }
+                for (label, regex) in DROPPER_TRAITS.iter() {
+                    if let Some(m) = regex.find(branch) {
+                        push_unique(&mut suspicious, format!("{} ({})", label, m.as_str()));
+                    }
+                }
+            }
+        }
+        (!platforms.is_empty()).then_some(Self {
+            platforms,
+            gated_actions,
+            suspicious,
+        })
+    }
+
+    /// Report as a `PlatformTargetedPayload` finding
+    ///
+    /// High when a gated branch also shows dropper traits, such as writing an
+    /// executable to a temporary directory; low otherwise, since installers
+    /// routinely pick binaries or build steps by platform.
+    pub fn to_finding(&self, source: &str) -> Finding {
+        let (severity, description) = if self.suspicious.is_empty() {
+            (
+                RiskLevel::Low,
+                format!(
+                    "{} only performs some actions on {}; usually platform-specific install logic",
+                    source,
+                    self.platforms.join(", ")
+                ),
+            )
+        } else {
+            (
+                RiskLevel::High,
+                format!(
+                    "{} runs a payload only on {}, so analysis on other platforms sees nothing",
+                    source,
+                    self.platforms.join(", ")
+                ),
+            )
+        };
+        let mut finding = Finding::new(
+            FindingType::PlatformTargetedPayload,
+            severity,
+            format!("{} targets specific platforms", source),
+            description,
+        )
+        .with_affected_component(source.to_string())
+        .with_scan_reason(ScanReason::MaliciousPatterns)
+        .with_rule_id(rules::PLATFORM_TARGETED_PAYLOAD);
+        for platform in &self.platforms {
+            finding = finding.with_evidence(format!("Platform: {}", platform));
+        }
+        for action in &self.gated_actions {
+            finding = finding.with_evidence(format!("Gated action: {}", action));
+        }
+        for suspicious in &self.suspicious {
+            finding = finding.with_evidence(format!("Suspicious: {}", suspicious));
+        }
+        finding
+    }
+}
+
+/// Code that runs only when the check between `start` and `end` passes
+///
+/// A brace block opened on the check's line, an indented Python block after
+/// a line ending in `:`, a shell `then` up to `fi`, or else the rest of the
+/// line, which covers ternaries and `&&` chains.
+fn gated_branch(content: &str, start: usize, end: usize) -> &str {
+    let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = content[end..].find('\n').map_or(content.len(), |i| end + i);
+    let rest_of_line = &content[end..line_end];
+
+    if let Some(open) = rest_of_line.find('{') {
+        let body = end + open + 1;
+        let mut depth = 1;
+        for (i, c) in content[body..].char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return &content[body..body + i];
+                    }
+                }
+                _ => {}
+            }
+        }
+        return &content[body..];
+    }
+    if rest_of_line.contains("then") {
+        let body = &content[end..];
+        return match body.find("\nfi").or_else(|| body.find("; fi")) {
+            Some(close) => &body[..close],
+            None => body,
+        };
+    }
+    if rest_of_line.trim_end().ends_with(':') {
+        let indent = indentation(&content[line_start..]);
+        let body = (line_end + 1).min(content.len());
+        let mut close = content.len();
+        let mut offset = body;
+        for line in content[body..].split_inclusive('\n') {
+            if !line.trim().is_empty() && indentation(line) <= indent {
+                close = offset;
+                break;
+            }
+            offset += line.len();
+        }
+        return &content[body..close];
+    }
+    rest_of_line
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start_matches([' ', '\t']).len()
+}
+
+fn push_unique(list: &mut Vec<String>, item: String) {
+    if !list.contains(&item) {
+        list.push(item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_platform_gated_branches() {
+        // Picking a build command per platform is ordinary install logic
+        let installer = r#"
+const { execSync } = require('child_process');
+if (process.platform === 'win32') {
+  execSync('npm run build:win');
+} else {
+  execSync('npm run build');
+}
+"#;
+        let gated = PlatformTargetedPayload::detect(installer).unwrap();
+        assert_eq!(gated.platforms, ["win32"]);
+        assert!(gated.suspicious.is_empty());
+        assert_eq!(gated.to_finding("postinstall").severity, RiskLevel::Low);
+
+        let setup = r#"
+import base64, os, sys
+if sys.platform != "darwin":
+    data = base64.b64decode(PAYLOAD)
+    open("/tmp/.cache", "wb").write(data)
+    os.system("chmod +x /tmp/.cache && /tmp/.cache")
+print("done")
+"#;
+        let gated = PlatformTargetedPayload::detect(setup).unwrap();
+        assert_eq!(gated.platforms, ["not darwin"]);
+        assert_eq!(gated.to_finding("setup.py").severity, RiskLevel::High);
+
+        // Checks that only choose a value gate nothing
+        let path = "const bin = process.platform === 'win32' ? 'tool.exe' : 'tool';";
+        assert!(PlatformTargetedPayload::detect(path).is_none());
+    }
+}
@@ -0,0 +1,123 @@
+//! Progress events of a running analysis
+//!
+//! An analysis run under [`with_progress`] sends a [`ProgressEvent`] down the
+//! given channel as it completes each step, so a caller can drive a progress
+//! bar or time the steps while a package with many dependencies is analyzed.
+//! [`crate::PackageSecurityAnalyzer::analyze_with_progress`] does this for
+//! the facade.
+//!
+//! Events are sent without waiting: a receiver that falls behind only queues
+//! them, and one that has been dropped is ignored. The sender is dropped when
+//! the analysis returns, so the receiver sees the channel close after the
+//! last event, whether the analysis succeeded or not.
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "runtime")]
+use {
+    super::{AnalysisOptions, MaliciousPattern, PackageMetadata},
+    std::future::Future,
+    tokio::sync::mpsc::UnboundedSender,
+};
+
+#[cfg(feature = "runtime")]
+tokio::task_local! {
+    static EVENTS: UnboundedSender<ProgressEvent>;
+}
+
+/// A step of an analysis
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// The package's manifest was read
+    ManifestParsed { name: String, version: String },
+    /// Vulnerability lookups for the dependencies are starting
+    VulnLookupStarted,
+    /// A dependency was resolved and, when enabled, checked for advisories
+    DependencyResolved { name: String },
+    /// The package's files were scanned for malicious patterns
+    PatternScanComplete { patterns: usize },
+}
+
+/// Run `analysis`, sending its progress to `events`
+///
+/// `events` is dropped when `analysis` completes.
+#[cfg(feature = "runtime")]
+pub async fn with_progress<F: Future>(
+    events: UnboundedSender<ProgressEvent>,
+    analysis: F,
+) -> F::Output {
+    EVENTS.scope(events, analysis).await
+}
+
+/// Send an event of the running analysis
+///
+/// Does nothing outside an analysis run by [`with_progress`].
+#[cfg(feature = "runtime")]
+pub(crate) fn emit(event: impl FnOnce() -> ProgressEvent) {
+    let _ = EVENTS.try_with(|events| {
+        let _ = events.send(event());
+    });
+}
+
+/// Note that the manifest describing `metadata` was read
+#[cfg(feature = "runtime")]
+pub(crate) fn manifest_parsed(metadata: &PackageMetadata) {
+    emit(|| ProgressEvent::ManifestParsed {
+        name: metadata.name.clone(),
+        version: metadata.version.clone(),
+    });
+}
+
+/// Note that vulnerability lookups are starting, when `options` enables them
+#[cfg(feature = "runtime")]
+pub(crate) fn vuln_lookup_started(options: &AnalysisOptions) {
+    if options.check_vulnerabilities {
+        emit(|| ProgressEvent::VulnLookupStarted);
+    }
+}
+
+/// Note that the dependency `name` was resolved
+#[cfg(feature = "runtime")]
+pub(crate) fn dependency_resolved(name: &str) {
+    emit(|| ProgressEvent::DependencyResolved {
+        name: name.to_string(),
+    });
+}
+
+/// Note that pattern scanning finished with `patterns` found
+#[cfg(feature = "runtime")]
+pub(crate) fn pattern_scan_complete(patterns: &[MaliciousPattern]) {
+    emit(|| ProgressEvent::PatternScanComplete {
+        patterns: patterns.len(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_events_reach_channel_until_dropped() {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        with_progress(sender, async {
+            dependency_resolved("left-pad");
+            emit(|| ProgressEvent::PatternScanComplete { patterns: 2 });
+        })
+        .await;
+        // Outside an analysis events go nowhere
+        dependency_resolved("ignored");
+
+        assert_eq!(
+            receiver.recv().await,
+            Some(ProgressEvent::DependencyResolved {
+                name: "left-pad".to_string()
+            })
+        );
+        assert_eq!(
+            receiver.recv().await,
+            Some(ProgressEvent::PatternScanComplete { patterns: 2 })
+        );
+        assert_eq!(receiver.recv().await, None);
+    }
+}
@@ -0,0 +1,405 @@
+//! Verification of published build provenance
+//!
+//! Registries attach SLSA provenance attestations to packages built on
+//! trusted CI: an in-toto statement, signed through Sigstore, naming the
+//! published artifact by digest along with the builder and source repository
+//! that produced it. A valid attestation means the package can be traced to
+//! the commit it was built from. One that names a different artifact or a
+//! different repository than the package declares is worse than none.
+//!
+//! Verification checks that the statement is a signed SLSA provenance for
+//! exactly this artifact and that its source matches the declared repository.
+//! The Sigstore certificate chain and transparency log entry are not checked
+//! here; the registry verifies them on publish.
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{rules, Finding, FindingType, RepositoryLink, RiskLevel, ScanReason};
+#[cfg(feature = "runtime")]
+use {anyhow::Result, std::time::Duration};
+
+/// Public npm registry
+pub const NPM_REGISTRY: &str = "https://registry.npmjs.org";
+
+/// Request timeout for registry lookups
+#[cfg(feature = "runtime")]
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Payload type of in-toto statements in a DSSE envelope
+const IN_TOTO_PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
+/// Predicate type prefix of SLSA provenance, any version
+const SLSA_PROVENANCE_PREFIX: &str = "https://slsa.dev/provenance/";
+
+/// Outcome of verifying a package's provenance
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProvenanceStatus {
+    /// A signed SLSA attestation names the published artifact and the declared
+    /// source repository
+    ///
+    /// The attestation's Sigstore signature, certificate chain and
+    /// transparency log entry are not checked; the registry verifies them on
+    /// publish.
+    #[serde(alias = "Verified")]
+    AttestationMatched,
+    /// The package publishes no provenance; informational only
+    Absent,
+    /// The check did not run (offline build or registry unavailable)
+    Skipped(String),
+    /// The package claims provenance that does not hold up
+    Invalid(String),
+}
+
+/// Provenance of a published package
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Provenance {
+    pub status: ProvenanceStatus,
+    /// Builder that produced the artifact, e.g. a CI workflow runner
+    pub builder: Option<String>,
+    /// Repository the artifact was built from
+    pub source_repository: Option<String>,
+    pub predicate_type: Option<String>,
+}
+
+impl Provenance {
+    fn with_status(status: ProvenanceStatus) -> Self {
+        Self {
+            status,
+            builder: None,
+            source_repository: None,
+            predicate_type: None,
+        }
+    }
+
+    /// Whether an attestation for this artifact and repository was found
+    pub fn attestation_matched(&self) -> bool {
+        self.status == ProvenanceStatus::AttestationMatched
+    }
+
+    /// Report claimed provenance that failed verification as `InvalidAttestation`
+    ///
+    /// Missing provenance is not a finding: most packages do not publish any.
+    pub fn to_finding(&self, package_name: &str) -> Option<Finding> {
+        let ProvenanceStatus::Invalid(reason) = &self.status else {
+            return None;
+        };
+
+        let mut finding = Finding::new(
+            FindingType::InvalidAttestation,
+            RiskLevel::High,
+            "Provenance attestation failed verification",
+            "The package claims a transparent build, but its attestation does not match the published artifact or its source; it may be forged",
+        )
+        .with_evidence(reason.clone())
+        .with_affected_component(package_name.to_string())
+        .with_scan_reason(ScanReason::ProvenanceCheck)
+        .with_rule_id(rules::INVALID_ATTESTATION);
+        if let Some(source) = &self.source_repository {
+            finding = finding.with_evidence(format!("Attested source: {}", source));
+        }
+        Some(finding)
+    }
+}
+
+/// Verifies provenance attestations published to the npm registry
+#[cfg(feature = "runtime")]
+pub struct ProvenanceVerifier {
+    client: reqwest::Client,
+    registry: String,
+}
+
+#[cfg(feature = "runtime")]
+impl ProvenanceVerifier {
+    /// Create a verifier for the public npm registry
+    pub fn new() -> Result<Self> {
+        Self::with_registry(NPM_REGISTRY)
+    }
+
+    /// Create a verifier for a registry mirror
+    pub fn with_registry(registry: &str) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .user_agent(concat!(
+                "threatflux-package-security/",
+                env!("CARGO_PKG_VERSION")
+            ))
+            .build()?;
+        Ok(Self {
+            client,
+            registry: registry.trim_end_matches('/').to_string(),
+        })
+    }
+
+    /// Verify the provenance of `name@version`
+    ///
+    /// `claims_provenance` is set when the package itself asks to be published
+    /// with provenance; a missing attestation then fails verification.
+    pub async fn verify_npm(
+        &self,
+        name: &str,
+        version: &str,
+        declared_repository: Option<&str>,
+        claims_provenance: bool,
+    ) -> Provenance {
+        if cfg!(feature = "offline") {
+            return Provenance::with_status(ProvenanceStatus::Skipped("offline build".to_string()));
+        }
+
+        match self
+            .fetch_and_verify(name, version, declared_repository, claims_provenance)
+            .await
+        {
+            Ok(provenance) => provenance,
+            Err(e) => Provenance::with_status(ProvenanceStatus::Skipped(e.to_string())),
+        }
+    }
+
+    async fn fetch_json(&self, url: &str) -> Result<Option<Value>> {
+        let response = self.client.get(url).send().await?;
+        if response.status().as_u16() == 404 {
+            return Ok(None);
+        }
+        let response = response.error_for_status()?;
+        Ok(Some(response.json().await?))
+    }
+
+    async fn fetch_and_verify(
+        &self,
+        name: &str,
+        version: &str,
+        declared_repository: Option<&str>,
+        claims_provenance: bool,
+    ) -> Result<Provenance> {
+        let encoded_name = name.replace('/', "%2F");
+        let Some(manifest) = self
+            .fetch_json(&format!("{}/{}/{}", self.registry, encoded_name, version))
+            .await?
+        else {
+            return Ok(Provenance::with_status(ProvenanceStatus::Skipped(format!(
+                "{}@{} is not published",
+                name, version
+            ))));
+        };
+
+        let dist = &manifest["dist"];
+        let Some(url) = dist["attestations"]["url"].as_str() else {
+            let status = if claims_provenance {
+                ProvenanceStatus::Invalid(
+                    "publishConfig requests provenance, but the registry has no attestation"
+                        .to_string(),
+                )
+            } else {
+                ProvenanceStatus::Absent
+            };
+            return Ok(Provenance::with_status(status));
+        };
+
+        let Some(bundles) = self.fetch_json(url).await? else {
+            return Ok(Provenance::with_status(ProvenanceStatus::Invalid(
+                "The registry lists an attestation it does not serve".to_string(),
+            )));
+        };
+        let integrity = dist["integrity"].as_str().unwrap_or_default();
+        Ok(verify_attestations(
+            &bundles,
+            name,
+            version,
+            integrity,
+            declared_repository,
+        ))
+    }
+}
+
+/// Verify the SLSA provenance among the registry's attestation bundles
+pub fn verify_attestations(
+    bundles: &Value,
+    name: &str,
+    version: &str,
+    integrity: &str,
+    declared_repository: Option<&str>,
+) -> Provenance {
+    let invalid =
+        |reason: &str| Provenance::with_status(ProvenanceStatus::Invalid(reason.to_string()));
+
+    let Some(attestation) = bundles["attestations"].as_array().and_then(|all| {
+        all.iter().find(|a| {
+            a["predicateType"]
+                .as_str()
+                .is_some_and(|t| t.starts_with(SLSA_PROVENANCE_PREFIX))
+        })
+    }) else {
+        return invalid("Attestations contain no SLSA provenance");
+    };
+
+    let envelope = &attestation["bundle"]["dsseEnvelope"];
+    if envelope["payloadType"].as_str() != Some(IN_TOTO_PAYLOAD_TYPE) {
+        return invalid("Attestation payload is not an in-toto statement");
+    }
+    let signed = envelope["signatures"].as_array().is_some_and(|sigs| {
+        sigs.iter()
+            .any(|s| s["sig"].as_str().is_some_and(|s| !s.is_empty()))
+    });
+    if !signed {
+        return invalid("Attestation envelope is not signed");
+    }
+
+    let Some(statement) = envelope["payload"]
+        .as_str()
+        .and_then(|p| base64::engine::general_purpose::STANDARD.decode(p).ok())
+        .and_then(|bytes| serde_json::from_slice::<Value>(&bytes).ok())
+    else {
+        return invalid("Attestation payload cannot be decoded");
+    };
+
+    let predicate_type = statement["predicateType"].as_str().map(String::from);
+    let predicate = &statement["predicate"];
+    // SLSA v1 and v0.2 keep builder and source in different places
+    let builder = predicate["runDetails"]["builder"]["id"]
+        .as_str()
+        .or_else(|| predicate["builder"]["id"].as_str())
+        .map(String::from);
+    let source_repository = predicate["buildDefinition"]["externalParameters"]["workflow"]
+        ["repository"]
+        .as_str()
+        .or_else(|| predicate["invocation"]["configSource"]["uri"].as_str())
+        .map(|uri| uri.split('@').next().unwrap_or(uri).to_string());
+
+    let mut provenance = Provenance {
+        status: ProvenanceStatus::AttestationMatched,
+        builder,
+        source_repository,
+        predicate_type,
+    };
+
+    let subject_name = format!("pkg:npm/{}@{}", name.replace('@', "%40"), version);
+    let expected_digest = integrity
+        .strip_prefix("sha512-")
+        .and_then(|b64| base64::engine::general_purpose::STANDARD.decode(b64).ok())
+        .map(hex::encode);
+    let subject_matches = statement["subject"].as_array().is_some_and(|subjects| {
+        subjects.iter().any(|subject| {
+            subject["name"].as_str() == Some(subject_name.as_str())
+                && expected_digest
+                    .as_deref()
+                    .is_some_and(|digest| subject["digest"]["sha512"].as_str() == Some(digest))
+        })
+    });
+    if !subject_matches {
+        provenance.status = ProvenanceStatus::Invalid(format!(
+            "Attestation does not name {} with the published tarball digest",
+            subject_name
+        ));
+        return provenance;
+    }
+
+    let attested = provenance
+        .source_repository
+        .as_deref()
+        .and_then(RepositoryLink::parse);
+    let declared = declared_repository.and_then(RepositoryLink::parse);
+    if let (Some(attested), Some(declared)) = (attested, declared) {
+        let same = attested.host == declared.host
+            && attested.owner.eq_ignore_ascii_case(&declared.owner)
+            && attested.name.eq_ignore_ascii_case(&declared.name);
+        if !same {
+            provenance.status = ProvenanceStatus::Invalid(format!(
+                "Built from {}, but the package declares {}",
+                attested.url, declared.url
+            ));
+        }
+    }
+    provenance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const INTEGRITY: &str = "sha512-3q2+7w==";
+
+    fn bundles(subject: &str, digest: &str, repository: &str) -> Value {
+        let statement = json!({
+            "_type": "https://in-toto.io/Statement/v1",
+            "subject": [{ "name": subject, "digest": { "sha512": digest } }],
+            "predicateType": "https://slsa.dev/provenance/v1",
+            "predicate": {
+                "buildDefinition": {
+                    "externalParameters": {
+                        "workflow": { "repository": repository, "path": ".github/workflows/publish.yml" }
+                    }
+                },
+                "runDetails": { "builder": { "id": "https://github.com/actions/runner/github-hosted" } }
+            }
+        });
+        let payload = base64::engine::general_purpose::STANDARD.encode(statement.to_string());
+        json!({
+            "attestations": [{
+                "predicateType": "https://slsa.dev/provenance/v1",
+                "bundle": {
+                    "dsseEnvelope": {
+                        "payload": payload,
+                        "payloadType": IN_TOTO_PAYLOAD_TYPE,
+                        "signatures": [{ "sig": "MEUCIQ", "keyid": "" }]
+                    }
+                }
+            }]
+        })
+    }
+
+    #[test]
+    fn test_valid_provenance() {
+        let bundles = bundles(
+            "pkg:npm/%40scope/pkg@1.0.0",
+            "deadbeef",
+            "https://github.com/scope/pkg",
+        );
+        let provenance = verify_attestations(
+            &bundles,
+            "@scope/pkg",
+            "1.0.0",
+            INTEGRITY,
+            Some("git+https://github.com/scope/pkg.git"),
+        );
+        assert!(provenance.attestation_matched(), "{:?}", provenance.status);
+        assert_eq!(
+            provenance.builder.as_deref(),
+            Some("https://github.com/actions/runner/github-hosted")
+        );
+        assert!(provenance.to_finding("@scope/pkg").is_none());
+
+        // Reports written before the rename still load
+        let status: ProvenanceStatus = serde_json::from_str("\"Verified\"").unwrap();
+        assert_eq!(status, ProvenanceStatus::AttestationMatched);
+    }
+
+    #[test]
+    fn test_forged_provenance() {
+        // Attestation lifted from another package
+        let lifted = bundles(
+            "pkg:npm/other@1.0.0",
+            "deadbeef",
+            "https://github.com/o/other",
+        );
+        let provenance = verify_attestations(&lifted, "pkg", "1.0.0", INTEGRITY, None);
+        assert!(matches!(provenance.status, ProvenanceStatus::Invalid(_)));
+
+        // Right artifact, built from a repository the package does not declare
+        let rebuilt = bundles(
+            "pkg:npm/pkg@1.0.0",
+            "deadbeef",
+            "https://github.com/attacker/pkg",
+        );
+        let provenance = verify_attestations(
+            &rebuilt,
+            "pkg",
+            "1.0.0",
+            INTEGRITY,
+            Some("github:owner/pkg"),
+        );
+        let finding = provenance.to_finding("pkg").unwrap();
+        assert_eq!(finding.finding_type, FindingType::InvalidAttestation);
+    }
+}
@@ -0,0 +1,384 @@
+//! Detection of packages whose code does more than their stated purpose needs
+//!
+//! A type definitions package has no runtime code, and a math or color
+//! utility has no reason to open sockets or spawn processes. Malware borrows
+//! such benign descriptions so nobody looks further. Only packages that
+//! clearly declare one of these purposes are checked, and only operations that
+//! purpose rules out are reported.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::{rules, Finding, FindingType, PackageMetadata, RiskLevel, ScanReason};
+
+/// Most JavaScript files read from a package
+const MAX_CODE_FILES: usize = 200;
+
+/// Larger JavaScript files are skipped, as bundles rather than hidden logic
+const MAX_CODE_FILE_SIZE: u64 = 512 * 1024;
+
+/// Directories never read
+const SKIPPED_DIRS: &[&str] = &["node_modules", ".git"];
+
+/// Runtime JavaScript; `.d.ts` and `.ts` files are not executed after install
+const CODE_EXTENSIONS: &[&str] = &["js", "cjs", "mjs"];
+
+/// Descriptions of type-only packages
+static TYPES_DESCRIPTION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:type(?:script)? (?:definitions|declarations)|typings)\b").unwrap()
+});
+
+/// Descriptions and keywords of side-effect-free utilities
+static UTILITY_DESCRIPTION: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\b(?:math(?:ematics|ematical)?|arithmetic|string (?:utilit(?:y|ies)|manipulation)|number formatting|color (?:conversion|manipulation)|css|icons?|constants)\b",
+    )
+    .unwrap()
+});
+
+/// Operations a declared purpose can rule out, with the code that performs them
+static OPERATIONS: Lazy<Vec<(Operation, Regex)>> = Lazy::new(|| {
+    [
+        (
+            Operation::Network,
+            r#"\b(?:require\(\s*|from\s+)['"](?:node:)?(?:https?|net|dgram|dns|tls)['"]|\bhttps?\.(?:get|request)\(|\bfetch\(|\bnew\s+WebSocket\(|\bXMLHttpRequest\b"#,
+        ),
+        (
+            Operation::ProcessExecution,
+            r#"\b(?:require\(\s*|from\s+)['"](?:node:)?child_process['"]|\b(?:execSync|spawnSync|execFile|execFileSync)\(|\bprocess\.binding\("#,
+        ),
+        (
+            Operation::FileSystemWrite,
+            r"\b(?:writeFile|writeFileSync|appendFile|appendFileSync|createWriteStream|unlink|unlinkSync|rmSync|chmodSync)\(",
+        ),
+    ]
+    .into_iter()
+    .map(|(operation, pattern)| (operation, Regex::new(pattern).unwrap()))
+    .collect()
+});
+
+/// What a package says it is, when that rules out active code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DeclaredPurpose {
+    /// TypeScript declarations, such as the `@types` scope
+    TypeDefinitions,
+    /// Side-effect-free helpers: math, string, color, CSS, icons, constants
+    Utility,
+}
+
+impl DeclaredPurpose {
+    /// Purpose a package declares, with what declared it
+    ///
+    /// Only names in the `@types` scope or an explicit description count as
+    /// type definitions; utilities are recognized from the description and
+    /// keywords.
+    pub fn infer(metadata: &PackageMetadata) -> Option<(Self, String)> {
+        if metadata.name.starts_with("@types/") {
+            return Some((
+                Self::TypeDefinitions,
+                "name is in the @types scope".to_string(),
+            ));
+        }
+        let description = metadata.description.as_deref().unwrap_or_default();
+        if TYPES_DESCRIPTION.is_match(description) {
+            return Some((
+                Self::TypeDefinitions,
+                format!("description: \"{}\"", description),
+            ));
+        }
+        if let Some(m) = UTILITY_DESCRIPTION.find(description) {
+            return Some((
+                Self::Utility,
+                format!("description mentions \"{}\"", m.as_str()),
+            ));
+        }
+        metadata
+            .keywords
+            .iter()
+            .find(|keyword| UTILITY_DESCRIPTION.is_match(keyword))
+            .map(|keyword| (Self::Utility, format!("keyword \"{}\"", keyword)))
+    }
+
+    /// Display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::TypeDefinitions => "type definitions",
+            Self::Utility => "a side-effect-free utility",
+        }
+    }
+
+    /// Whether the purpose rules out an operation
+    fn rules_out(&self, operation: Operation) -> bool {
+        match self {
+            Self::TypeDefinitions => true,
+            Self::Utility => matches!(operation, Operation::Network | Operation::ProcessExecution),
+        }
+    }
+}
+
+/// Kind of operation code performs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Operation {
+    /// Runs a script when installed
+    InstallHook,
+    Network,
+    ProcessExecution,
+    FileSystemWrite,
+}
+
+impl Operation {
+    /// Display name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::InstallHook => "runs code at install",
+            Self::Network => "network access",
+            Self::ProcessExecution => "process execution",
+            Self::FileSystemWrite => "file system writes",
+        }
+    }
+}
+
+/// An operation found in the package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObservedOperation {
+    pub operation: Operation,
+    /// Install hook or file the operation is in
+    pub location: String,
+    /// Matched code or command
+    pub evidence: String,
+}
+
+/// Operations a package performs that its declared purpose rules out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PurposeMismatch {
+    pub purpose: DeclaredPurpose,
+    /// What in the metadata declared the purpose
+    pub declared_by: String,
+    pub operations: Vec<ObservedOperation>,
+}
+
+impl PurposeMismatch {
+    /// Compare a package's declared purpose with its code
+    ///
+    /// `install_hooks` are `(hook, source)` pairs: the command together with
+    /// the local scripts it runs. JavaScript files under `root` are only read
+    /// when the metadata declares a purpose.
+    pub fn check(
+        metadata: &PackageMetadata,
+        root: &Path,
+        install_hooks: &[(&str, &str)],
+    ) -> Option<Self> {
+        let (purpose, declared_by) = DeclaredPurpose::infer(metadata)?;
+
+        let mut operations = Vec::new();
+        for (hook, source) in install_hooks {
+            let location = format!("{} script", hook);
+            let found = scan_operations(source, &location);
+            if found.is_empty() && purpose == DeclaredPurpose::TypeDefinitions {
+                operations.push(ObservedOperation {
+                    operation: Operation::InstallHook,
+                    location,
+                    evidence: source.lines().next().unwrap_or_default().to_string(),
+                });
+            }
+            operations.extend(found);
+        }
+        for (file, content) in package_code(root, CODE_EXTENSIONS) {
+            operations.extend(scan_operations(&content, &file));
+        }
+        operations.retain(|observed| {
+            observed.operation == Operation::InstallHook || purpose.rules_out(observed.operation)
+        });
+
+        (!operations.is_empty()).then_some(Self {
+            purpose,
+            declared_by,
+            operations,
+        })
+    }
+
+    /// Report as a `PurposeMismatch` finding
+    ///
+    /// High for type definitions, which should never act; for utilities,
+    /// medium unless the operation runs at install.
+    pub fn to_finding(&self, package: &str) -> Finding {
+        let at_install = self
+            .operations
+            .iter()
+            .any(|observed| observed.location.ends_with(" script"));
+        let severity = match self.purpose {
+            DeclaredPurpose::TypeDefinitions => RiskLevel::High,
+            DeclaredPurpose::Utility if at_install => RiskLevel::High,
+            DeclaredPurpose::Utility => RiskLevel::Medium,
+        };
+        let mut kinds: Vec<&str> = Vec::new();
+        for observed in &self.operations {
+            if !kinds.contains(&observed.operation.name()) {
+                kinds.push(observed.operation.name());
+            }
+        }
+
+        let mut finding = Finding::new(
+            FindingType::PurposeMismatch,
+            severity,
+            format!(
+                "{} is declared as {} but its code performs {}",
+                package,
+                self.purpose.name(),
+                kinds.join(", ")
+            ),
+            format!(
+                "Packages declared as {} have no need for {}; a benign description can be cover for active code",
+                self.purpose.name(),
+                kinds.join(" or ")
+            ),
+        )
+        .with_evidence(format!("Declared purpose: {}", self.declared_by))
+        .with_affected_component(package.to_string())
+        .with_scan_reason(ScanReason::MaliciousPatterns)
+        .with_rule_id(rules::PURPOSE_MISMATCH);
+        for observed in &self.operations {
+            finding = finding.with_evidence(format!(
+                "{}: {} ({})",
+                observed.location,
+                observed.operation.name(),
+                observed.evidence
+            ));
+        }
+        finding
+    }
+}
+
+/// Network, process and file system operations in JavaScript source
+fn scan_operations(source: &str, location: &str) -> Vec<ObservedOperation> {
+    OPERATIONS
+        .iter()
+        .filter_map(|(operation, regex)| {
+            regex.find(source).map(|m| ObservedOperation {
+                operation: *operation,
+                location: location.to_string(),
+                evidence: m.as_str().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Source files under `root` with one of `extensions`, as `(relative path, content)`
+pub(super) fn package_code(root: &Path, extensions: &[&str]) -> Vec<(String, String)> {
+    package_files(root, |relative| {
+        Path::new(relative)
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| extensions.contains(&ext))
+    })
+}
+
+/// Files under `root` whose relative path `keep` accepts, as `(relative path, content)`
+///
+/// Skips `node_modules` and `.git`, files too large or not UTF-8, and files
+/// past the first [`MAX_CODE_FILES`] in path order.
+pub(crate) fn package_files(root: &Path, keep: impl Fn(&str) -> bool) -> Vec<(String, String)> {
+    let relative = |path: &Path| {
+        path.strip_prefix(root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    };
+    let mut files = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                let name = entry.file_name();
+                if !SKIPPED_DIRS.iter().any(|skipped| name == *skipped) {
+                    stack.push(path);
+                }
+                continue;
+            }
+            if file_type.is_file() && keep(&relative(&path)) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+
+    files
+        .into_iter()
+        .filter(|path| std::fs::metadata(path).is_ok_and(|meta| meta.len() <= MAX_CODE_FILE_SIZE))
+        .take(MAX_CODE_FILES)
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            Some((relative(&path), content))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn metadata(name: &str, description: &str) -> PackageMetadata {
+        PackageMetadata {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            description: Some(description.to_string()),
+            author: None,
+            license: None,
+            homepage: None,
+            repository: None,
+            keywords: vec![],
+            publish_date: None,
+        }
+    }
+
+    #[test]
+    fn test_utility_with_network_code() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("lib")).unwrap();
+        fs::write(
+            dir.path().join("lib/index.js"),
+            "const https = require('https');\nexports.add = (a, b) => a + b;",
+        )
+        .unwrap();
+        let math = metadata("tiny-sum", "Simple math helpers");
+
+        let mismatch = PurposeMismatch::check(&math, dir.path(), &[]).unwrap();
+        assert_eq!(mismatch.purpose, DeclaredPurpose::Utility);
+        assert_eq!(mismatch.operations[0].operation, Operation::Network);
+        assert_eq!(mismatch.operations[0].location, "lib/index.js");
+        assert_eq!(mismatch.to_finding("tiny-sum").severity, RiskLevel::Medium);
+
+        // File writes are fine for a utility, and undeclared purposes are not judged
+        fs::write(
+            dir.path().join("lib/index.js"),
+            "fs.writeFileSync(out, css);",
+        )
+        .unwrap();
+        assert!(PurposeMismatch::check(&math, dir.path(), &[]).is_none());
+        fs::write(dir.path().join("lib/index.js"), "require('https')").unwrap();
+        let client = metadata("tiny-client", "HTTP client");
+        assert!(PurposeMismatch::check(&client, dir.path(), &[]).is_none());
+    }
+
+    #[test]
+    fn test_type_definitions_without_code() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("index.d.ts"),
+            "export declare function get(url: string): Promise<Response>;",
+        )
+        .unwrap();
+        let types = metadata("@types/tiny-fetch", "TypeScript definitions for tiny-fetch");
+        assert!(PurposeMismatch::check(&types, dir.path(), &[]).is_none());
+    }
+}
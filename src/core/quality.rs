@@ -0,0 +1,297 @@
+//! Package quality signals
+//!
+//! Looks at the package on disk for signs that it is tested, built in CI and
+//! documented, and at its metadata for signs that it is maintained. These say
+//! nothing about whether the package is safe, but a package without any of
+//! them is less likely to be noticed and fixed when a release goes wrong.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::{PackageMetadata, RepositoryLink, RepositoryStatus, RepositoryValidation};
+
+/// Directories that hold a package's tests; `src/test` for Maven and Gradle,
+/// `templates/tests` for Helm
+const TEST_DIRS: &[&str] = &[
+    "test",
+    "tests",
+    "spec",
+    "__tests__",
+    "src/test",
+    "templates/tests",
+];
+
+/// Files that configure a test runner
+const TEST_CONFIGS: &[&str] = &["pytest.ini", "conftest.py", "tox.ini", ".rspec"];
+
+/// `(file, section)` pairs configuring pytest inside a shared config file
+const TEST_SECTIONS: &[(&str, &str)] = &[
+    ("pyproject.toml", "[tool.pytest.ini_options]"),
+    ("setup.cfg", "[tool:pytest]"),
+];
+
+/// Files that configure a CI pipeline
+const CI_FILES: &[&str] = &[
+    ".gitlab-ci.yml",
+    ".travis.yml",
+    ".circleci/config.yml",
+    "azure-pipelines.yml",
+    "Jenkinsfile",
+];
+
+/// Directories whose files each define a CI workflow
+const CI_DIRS: &[&str] = &[".github/workflows", ".buildkite"];
+
+/// Keywords at which keyword coverage is complete
+const FULL_KEYWORDS: usize = 3;
+
+/// Days since the last publish up to which a package counts as current
+const CURRENT_DAYS: i64 = 365;
+
+/// Days since the last publish from which a package counts as abandoned
+const ABANDONED_DAYS: i64 = 3 * 365;
+
+/// Share of the maintenance score publish recency accounts for, when known
+const RECENCY_WEIGHT: f32 = 0.25;
+
+/// Package quality metrics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityMetrics {
+    pub documentation_score: f32,
+    pub has_tests: bool,
+    pub has_ci_cd: bool,
+    pub maintenance_score: f32,
+}
+
+impl Default for QualityMetrics {
+    fn default() -> Self {
+        Self {
+            documentation_score: 0.5,
+            has_tests: false,
+            has_ci_cd: false,
+            maintenance_score: 0.5,
+        }
+    }
+}
+
+impl QualityMetrics {
+    /// Metrics of the package rooted at `root` and described by `metadata`,
+    /// with publish recency measured up to `now`
+    ///
+    /// `has_test_script` is whether the manifest declares a command running
+    /// the tests, such as npm's `scripts.test`. A `root` that is not a
+    /// directory, such as a jar, has no tests or CI to find.
+    pub fn assess(
+        root: &Path,
+        metadata: &PackageMetadata,
+        has_test_script: bool,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            documentation_score: Self::documentation(root, metadata),
+            has_tests: has_test_script || Self::tests(root),
+            has_ci_cd: Self::ci(root),
+            maintenance_score: Self::maintenance(metadata, now),
+        }
+    }
+
+    /// Lower the maintenance score when checking the repository link found
+    /// it missing, archived or about another project
+    pub fn with_repository_validation(mut self, validation: &RepositoryValidation) -> Self {
+        if matches!(
+            validation.status,
+            RepositoryStatus::NotFound | RepositoryStatus::Archived | RepositoryStatus::Unrelated
+        ) {
+            self.maintenance_score *= 0.5;
+        }
+        self
+    }
+
+    pub fn documentation_score(&self) -> f32 {
+        self.documentation_score
+    }
+
+    pub fn has_tests(&self) -> bool {
+        self.has_tests
+    }
+
+    pub fn has_ci_cd(&self) -> bool {
+        self.has_ci_cd
+    }
+
+    pub fn maintenance_score(&self) -> f32 {
+        self.maintenance_score
+    }
+
+    /// Score from 0 to 1: a repository link that parses counts for 0.4, a
+    /// license and a description for 0.3 each
+    ///
+    /// When the metadata has a publish date, recency makes up a quarter of
+    /// the score: full up to a year since publishing, none from three years.
+    fn maintenance(metadata: &PackageMetadata, now: DateTime<Utc>) -> f32 {
+        let present =
+            |value: &Option<String>| value.as_deref().is_some_and(|v| !v.trim().is_empty());
+        let mut score = 0.0;
+        if metadata
+            .repository
+            .as_deref()
+            .and_then(RepositoryLink::parse)
+            .is_some()
+        {
+            score += 0.4;
+        }
+        if present(&metadata.license) {
+            score += 0.3;
+        }
+        if present(&metadata.description) {
+            score += 0.3;
+        }
+
+        let published = metadata
+            .publish_date
+            .as_deref()
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok());
+        match published {
+            Some(published) => {
+                let age = (now - published.with_timezone(&Utc)).max(Duration::zero());
+                let stale = (age.num_days() - CURRENT_DAYS).clamp(0, ABANDONED_DAYS - CURRENT_DAYS);
+                let recency = 1.0 - stale as f32 / (ABANDONED_DAYS - CURRENT_DAYS) as f32;
+                score * (1.0 - RECENCY_WEIGHT) + recency * RECENCY_WEIGHT
+            }
+            None => score,
+        }
+    }
+
+    /// Score from 0 to 1: a README counts for 0.4, a description and a
+    /// homepage or repository for 0.2 each, and keywords for up to 0.2
+    fn documentation(root: &Path, metadata: &PackageMetadata) -> f32 {
+        let present =
+            |value: &Option<String>| value.as_deref().is_some_and(|v| !v.trim().is_empty());
+        let mut score = 0.0;
+        if Self::readme(root) {
+            score += 0.4;
+        }
+        if present(&metadata.description) {
+            score += 0.2;
+        }
+        if present(&metadata.homepage) || present(&metadata.repository) {
+            score += 0.2;
+        }
+        let keywords = metadata
+            .keywords
+            .iter()
+            .filter(|keyword| !keyword.trim().is_empty())
+            .count();
+        score += 0.2 * keywords.min(FULL_KEYWORDS) as f32 / FULL_KEYWORDS as f32;
+        score
+    }
+
+    /// Whether `root` holds a README in any case and format
+    fn readme(root: &Path) -> bool {
+        std::fs::read_dir(root).is_ok_and(|entries| {
+            entries.flatten().any(|entry| {
+                let name = entry.file_name().to_string_lossy().to_ascii_lowercase();
+                entry.path().is_file() && (name == "readme" || name.starts_with("readme."))
+            })
+        })
+    }
+
+    fn tests(root: &Path) -> bool {
+        TEST_DIRS.iter().any(|dir| root.join(dir).is_dir())
+            || TEST_CONFIGS.iter().any(|file| root.join(file).is_file())
+            || TEST_SECTIONS.iter().any(|(file, section)| {
+                std::fs::read_to_string(root.join(file))
+                    .is_ok_and(|content| content.lines().any(|line| line.trim() == *section))
+            })
+    }
+
+    fn ci(root: &Path) -> bool {
+        CI_FILES.iter().any(|file| root.join(file).is_file())
+            || CI_DIRS.iter().any(|dir| {
+                std::fs::read_dir(root.join(dir))
+                    .is_ok_and(|entries| entries.flatten().any(|entry| entry.path().is_file()))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(keywords: &[&str]) -> PackageMetadata {
+        PackageMetadata {
+            name: "widget".to_string(),
+            version: "1.0.0".to_string(),
+            description: Some("Widgets".to_string()),
+            author: None,
+            license: None,
+            homepage: None,
+            repository: Some("https://github.com/acme/widget".to_string()),
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            publish_date: None,
+        }
+    }
+
+    #[test]
+    fn test_assess() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let now = Utc::now();
+        let bare = QualityMetrics::assess(dir.path(), &metadata(&[]), false, now);
+        assert!(!bare.has_tests);
+        assert!(!bare.has_ci_cd);
+        assert!((bare.documentation_score - 0.4).abs() < 1e-6);
+
+        std::fs::write(dir.path().join("README.md"), "# widget").unwrap();
+        std::fs::write(
+            dir.path().join("pyproject.toml"),
+            "[tool.pytest.ini_options]\naddopts = \"-q\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join(".github/workflows")).unwrap();
+        std::fs::write(dir.path().join(".github/workflows/ci.yml"), "on: push").unwrap();
+        let documented =
+            QualityMetrics::assess(dir.path(), &metadata(&["a", "b", "c", "d"]), false, now);
+        assert!(documented.has_tests);
+        assert!(documented.has_ci_cd);
+        assert!((documented.documentation_score - 1.0).abs() < 1e-6);
+
+        // An archive has nothing on disk to look at
+        let jar = QualityMetrics::assess(&dir.path().join("widget.jar"), &metadata(&[]), true, now);
+        assert!(jar.has_tests);
+        assert!(!jar.has_ci_cd);
+    }
+
+    #[test]
+    fn test_maintenance() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let now = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut metadata = metadata(&[]);
+        let score = |metadata: &PackageMetadata| {
+            QualityMetrics::assess(dir.path(), metadata, false, now).maintenance_score()
+        };
+        // Repository and description, no license or publish date
+        assert!((score(&metadata) - 0.7).abs() < 1e-6);
+
+        metadata.license = Some("MIT".to_string());
+        metadata.publish_date = Some("2025-06-01T00:00:00Z".to_string());
+        assert!((score(&metadata) - 1.0).abs() < 1e-6);
+        metadata.publish_date = Some("2022-01-01T00:00:00Z".to_string());
+        assert!((score(&metadata) - 0.75).abs() < 1e-6);
+
+        metadata.publish_date = None;
+        metadata.repository = Some("not a repository".to_string());
+        assert!((score(&metadata) - 0.6).abs() < 1e-6);
+
+        let archived = RepositoryValidation {
+            declared: "github:acme/widget".to_string(),
+            status: RepositoryStatus::Archived,
+            redirected_to: None,
+        };
+        let metrics = QualityMetrics::assess(dir.path(), &metadata, false, now)
+            .with_repository_validation(&archived);
+        assert!((metrics.maintenance_score() - 0.3).abs() < 1e-6);
+    }
+}
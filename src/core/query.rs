@@ -0,0 +1,149 @@
+//! Typed queries over the findings of an analysis result
+//!
+//! Detailed findings, dependency vulnerabilities and malicious patterns are
+//! surfaced as unified [`Finding`]s and filtered lazily, so a query only
+//! materializes the findings it yields.
+
+use super::{
+    AnalysisResult, Dependency, DependencyAnalysis, Finding, FindingType, MaliciousPattern,
+    RiskAssessment, RiskLevel,
+};
+
+/// Filter over the findings of an [`AnalysisResult`]
+///
+/// Predicates combine with AND; repeated [`category`](Self::category) calls
+/// combine with OR.
+pub struct FindingQuery<'a> {
+    assessment: &'a RiskAssessment,
+    dependencies: &'a DependencyAnalysis,
+    patterns: &'a [MaliciousPattern],
+    min_severity: Option<RiskLevel>,
+    categories: Vec<FindingType>,
+    component: Option<&'a str>,
+    direct_only: bool,
+}
+
+impl<'a> FindingQuery<'a> {
+    /// Query every finding of a result
+    pub fn new<R: AnalysisResult + ?Sized>(result: &'a R) -> Self {
+        Self {
+            assessment: result.risk_assessment(),
+            dependencies: result.dependency_analysis(),
+            patterns: result.malicious_patterns(),
+            min_severity: None,
+            categories: Vec::new(),
+            component: None,
+            direct_only: false,
+        }
+    }
+
+    /// Keep findings at or above a severity
+    pub fn severity_at_least(mut self, severity: RiskLevel) -> Self {
+        self.min_severity = Some(severity);
+        self
+    }
+
+    /// Keep findings of a type; call again to allow further types
+    pub fn category(mut self, finding_type: FindingType) -> Self {
+        if !self.categories.contains(&finding_type) {
+            self.categories.push(finding_type);
+        }
+        self
+    }
+
+    /// Keep findings affecting a named component
+    pub fn component(mut self, component: &'a str) -> Self {
+        self.component = Some(component);
+        self
+    }
+
+    /// Drop findings that only affect transitive dependencies
+    pub fn direct_only(mut self) -> Self {
+        self.direct_only = true;
+        self
+    }
+
+    /// Lazily yield the matching findings
+    pub fn iter(&self) -> impl Iterator<Item = Finding> + '_ {
+        let tree = &self.dependencies.dependency_tree;
+        let detailed = self
+            .assessment
+            .detailed_findings
+            .iter()
+            .filter(move |finding| self.matches(finding, || !only_transitive(finding, tree)))
+            .cloned();
+        let vulnerabilities = TreeWalk::new(tree).flat_map(move |dep| {
+            dep.vulnerabilities.iter().filter_map(move |vuln| {
+                let finding = Finding::from_vulnerability(vuln, &dep.name);
+                self.matches(&finding, || dep.is_direct).then_some(finding)
+            })
+        });
+        let patterns = self
+            .patterns
+            .iter()
+            .map(Finding::from_pattern)
+            .filter(move |finding| self.matches(finding, || true));
+        detailed.chain(vulnerabilities).chain(patterns)
+    }
+
+    /// Collect the matching findings
+    pub fn collect(&self) -> Vec<Finding> {
+        self.iter().collect()
+    }
+
+    /// Number of matching findings
+    pub fn count(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// First matching finding, if any
+    pub fn first(&self) -> Option<Finding> {
+        self.iter().next()
+    }
+
+    fn matches(&self, finding: &Finding, is_direct: impl FnOnce() -> bool) -> bool {
+        self.min_severity.is_none_or(|min| finding.severity >= min)
+            && (self.categories.is_empty() || self.categories.contains(&finding.finding_type))
+            && self.component.is_none_or(|component| {
+                finding
+                    .affected_components
+                    .iter()
+                    .any(|affected| affected == component)
+            })
+            && (!self.direct_only || is_direct())
+    }
+}
+
+/// Whether every affected component of a finding is a transitive-only dependency
+fn only_transitive(finding: &Finding, tree: &[Dependency]) -> bool {
+    !finding.affected_components.is_empty()
+        && finding.affected_components.iter().all(|component| {
+            !tree
+                .iter()
+                .any(|dep| dep.is_direct && &dep.name == component)
+                && TreeWalk::new(tree).any(|dep| !dep.is_direct && &dep.name == component)
+        })
+}
+
+/// Depth-first walk over a dependency tree
+struct TreeWalk<'a> {
+    stack: Vec<&'a Dependency>,
+}
+
+impl<'a> TreeWalk<'a> {
+    fn new(tree: &'a [Dependency]) -> Self {
+        Self {
+            stack: tree.iter().rev().collect(),
+        }
+    }
+}
+
+impl<'a> Iterator for TreeWalk<'a> {
+    type Item = &'a Dependency;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let dep = self.stack.pop()?;
+        self.stack.extend(dep.dependencies.iter().rev());
+        Some(dep)
+    }
+}
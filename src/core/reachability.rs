@@ -0,0 +1,163 @@
+//! Best-effort reachability of vulnerable dependency code
+//!
+//! Some advisories name the functions or classes the flaw is in. When the
+//! scanned package's own source imports the dependency and mentions one of
+//! those symbols, the vulnerability is likely reachable; when it never does,
+//! it likely is not. This is a textual heuristic, not call-graph analysis:
+//! dynamic dispatch, re-exports and renamed imports are missed, and a mention in a
+//! comment counts as use.
+
+use regex::Regex;
+use std::path::Path;
+
+use super::purpose::package_code;
+use super::{Dependency, DependencyAnalysis, ScriptLanguage};
+
+/// Source extensions read for each language
+fn source_extensions(language: ScriptLanguage) -> &'static [&'static str] {
+    match language {
+        ScriptLanguage::JavaScript => &["js", "cjs", "mjs", "ts", "mts", "cts"],
+        ScriptLanguage::Python => &["py"],
+        ScriptLanguage::Shell => &["sh"],
+        ScriptLanguage::PowerShell => &["ps1"],
+        ScriptLanguage::Ruby => &["rb"],
+        ScriptLanguage::Perl => &["pl", "pm"],
+    }
+}
+
+/// The scanned package's own source, searched for dependency symbol use
+#[derive(Debug, Clone)]
+pub struct SymbolUsage {
+    language: ScriptLanguage,
+    /// `(relative path, content)` of each source file
+    sources: Vec<(String, String)>,
+}
+
+impl SymbolUsage {
+    /// Read the package's source files under `root`
+    pub fn scan(root: &Path, language: ScriptLanguage) -> Self {
+        Self {
+            language,
+            sources: package_code(root, source_extensions(language)),
+        }
+    }
+
+    /// Whether the package likely calls any of `symbols` from `package`
+    ///
+    /// A symbol may be qualified with its module, as in `yaml.full_load`,
+    /// which is then the import looked for instead of the package name.
+    /// `None` when there are no symbols to look for or no source to search.
+    pub fn likely_reachable(&self, package: &str, symbols: &[String]) -> Option<bool> {
+        if symbols.is_empty() || self.sources.is_empty() {
+            return None;
+        }
+        Some(symbols.iter().any(|symbol| {
+            let (module, name) = match symbol.rsplit_once('.') {
+                Some((module, name)) => (module.to_string(), name),
+                None => (self.module_name(package), symbol.as_str()),
+            };
+            let Some(import) = self.import_pattern(&module) else {
+                return false;
+            };
+            let Ok(usage) = Regex::new(&format!(r"\b{}\b", regex::escape(name))) else {
+                return false;
+            };
+            self.sources
+                .iter()
+                .any(|(_, source)| import.is_match(source) && usage.is_match(source))
+        }))
+    }
+
+    /// Set `likely_reachable` on every vulnerability in the tree that names
+    /// its affected symbols
+    pub fn annotate(&self, analysis: &mut DependencyAnalysis) {
+        fn walk(usage: &SymbolUsage, dependencies: &mut [Dependency]) {
+            for dependency in dependencies {
+                // Code imports an aliased package by its alias
+                let vulnerabilities = dependency.vulnerabilities.iter_mut().chain(
+                    dependency
+                        .range_vulnerabilities
+                        .iter_mut()
+                        .map(|range| &mut range.vulnerability),
+                );
+                for vulnerability in vulnerabilities {
+                    vulnerability.likely_reachable =
+                        usage.likely_reachable(&dependency.name, &vulnerability.affected_symbols);
+                }
+                walk(usage, &mut dependency.dependencies);
+            }
+        }
+        walk(self, &mut analysis.dependency_tree);
+    }
+
+    /// Module a package is imported as
+    fn module_name(&self, package: &str) -> String {
+        match self.language {
+            ScriptLanguage::Python => package.to_lowercase().replace('-', "_"),
+            _ => package.to_string(),
+        }
+    }
+
+    /// Import of `module` or one of its submodules
+    fn import_pattern(&self, module: &str) -> Option<Regex> {
+        let module = regex::escape(module);
+        let pattern = match self.language {
+            ScriptLanguage::JavaScript => format!(
+                r#"(?:require|import)\s*\(\s*['"]{module}(?:/[^'"]*)?['"]|\bfrom\s+['"]{module}(?:/[^'"]*)?['"]|\bimport\s+['"]{module}(?:/[^'"]*)?['"]"#
+            ),
+            ScriptLanguage::Python => {
+                format!(r"(?m)^\s*(?:import\s+{module}\b|from\s+{module}(?:\.\w+)*\s+import\b)")
+            }
+            _ => return None,
+        };
+        Regex::new(&pattern).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_symbol_usage() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("index.js"),
+            "const _ = require('lodash');\nmodule.exports = (a, b) => _.defaultsDeep(a, b);",
+        )
+        .unwrap();
+        let usage = SymbolUsage::scan(dir.path(), ScriptLanguage::JavaScript);
+        let symbols = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            usage.likely_reachable("lodash", &symbols(&["defaultsDeep"])),
+            Some(true)
+        );
+        assert_eq!(
+            usage.likely_reachable("lodash", &symbols(&["zipObjectDeep"])),
+            Some(false)
+        );
+        // Mentioned, but from a package that is never imported
+        assert_eq!(
+            usage.likely_reachable("lodash-es", &symbols(&["defaultsDeep"])),
+            Some(false)
+        );
+        assert_eq!(usage.likely_reachable("lodash", &[]), None);
+
+        fs::write(
+            dir.path().join("config.py"),
+            "import yaml\n\ndef load(path):\n    return yaml.full_load(open(path))\n",
+        )
+        .unwrap();
+        let usage = SymbolUsage::scan(dir.path(), ScriptLanguage::Python);
+        assert_eq!(
+            usage.likely_reachable("PyYAML", &symbols(&["yaml.full_load"])),
+            Some(true)
+        );
+        assert_eq!(
+            SymbolUsage::scan(&dir.path().join("missing"), ScriptLanguage::Python)
+                .likely_reachable("PyYAML", &symbols(&["yaml.full_load"])),
+            None
+        );
+    }
+}
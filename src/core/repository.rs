@@ -0,0 +1,367 @@
+//! Validation of declared source repository links
+//!
+//! A repository link that is dead or leads to an unrelated project lends a
+//! package credibility it has not earned. The check fetches the repository
+//! page and looks for the package name in the final repository path or the
+//! rendered README. It needs network access and is skipped in offline builds.
+//!
+//! The link comes from the untrusted package, so only repositories on
+//! [`KNOWN_FORGES`] are fetched, over https, following redirects only within
+//! the same host; anything else is skipped rather than requested.
+
+use serde::{Deserialize, Serialize};
+
+use super::{rules, Finding, FindingType, RiskLevel, ScanReason};
+#[cfg(feature = "runtime")]
+use {anyhow::Result, std::time::Duration};
+
+/// Request timeout for repository lookups
+#[cfg(feature = "runtime")]
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Page bytes searched for the package name
+#[cfg(feature = "runtime")]
+const MAX_BODY_SCAN: usize = 512 * 1024;
+
+/// Redirects followed within a forge, as when a repository was renamed
+#[cfg(feature = "runtime")]
+const MAX_REDIRECTS: usize = 10;
+
+/// Hosts whose repository pages are fetched
+pub const KNOWN_FORGES: [&str; 3] = ["github.com", "gitlab.com", "bitbucket.org"];
+
+/// Banner GitHub shows on archived repositories, lowercased
+#[cfg(feature = "runtime")]
+const ARCHIVED_BANNER: &str = "this repository has been archived";
+
+/// Source repository declared by a package, e.g. `github.com/owner/repo`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepositoryLink {
+    pub host: String,
+    pub owner: String,
+    pub name: String,
+    /// Web URL of the repository
+    pub url: String,
+}
+
+impl RepositoryLink {
+    /// Parse the URL and shorthand forms package managers accept
+    ///
+    /// Handles `git+https://host/owner/repo.git`, `git@host:owner/repo`,
+    /// `github:owner/repo` and bare `owner/repo` (GitHub).
+    pub fn parse(link: &str) -> Option<Self> {
+        let link = link.trim();
+        let (scheme, rest) = if let Some((prefix, rest)) = link.split_once("://") {
+            let scheme = if prefix.ends_with("http") {
+                "http"
+            } else {
+                "https"
+            };
+            // Drop credentials such as `git@`
+            let rest = rest.rsplit_once('@').map_or(rest, |(_, host)| host);
+            (scheme, rest.to_string())
+        } else if let Some(rest) = link.strip_prefix("git@") {
+            ("https", rest.replacen(':', "/", 1))
+        } else if let Some((alias, path)) = link.split_once(':') {
+            let host = match alias {
+                "github" => "github.com",
+                "gitlab" => "gitlab.com",
+                "bitbucket" => "bitbucket.org",
+                _ => return None,
+            };
+            ("https", format!("{}/{}", host, path))
+        } else if link.matches('/').count() == 1 {
+            ("https", format!("github.com/{}", link))
+        } else {
+            return None;
+        };
+
+        let rest = rest.split(['#', '?']).next().unwrap_or_default();
+        let mut parts = rest.split('/').filter(|p| !p.is_empty());
+        let host = parts.next()?.to_ascii_lowercase();
+        let owner = parts.next()?.to_string();
+        let name = parts.next()?.trim_end_matches(".git").to_string();
+        if name.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            url: format!("{}://{}/{}/{}", scheme, host, owner, name),
+            host,
+            owner,
+            name,
+        })
+    }
+
+    /// Whether the owner or repository name plausibly belongs to `package_name`
+    #[cfg(feature = "runtime")]
+    fn names_match(&self, package_name: &str) -> bool {
+        let repo = squash(&self.name);
+        let owner = squash(&self.owner);
+        let (scope, unscoped) = match package_name.strip_prefix('@') {
+            Some(scoped) => scoped.split_once('/').unwrap_or(("", scoped)),
+            None => ("", package_name),
+        };
+        let unscoped = squash(unscoped);
+        let scope = squash(scope);
+
+        (!unscoped.is_empty()
+            && !repo.is_empty()
+            && (repo.contains(&unscoped) || unscoped.contains(&repo)))
+            || (!scope.is_empty() && (repo == scope || owner == scope))
+    }
+}
+
+/// Lowercase alphanumerics only, so `node-Foo.js` and `foojs` compare equal-ish
+#[cfg(feature = "runtime")]
+fn squash(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect::<String>()
+        .trim_start_matches("node")
+        .trim_end_matches("js")
+        .to_string()
+}
+
+/// Outcome of validating a repository link
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RepositoryStatus {
+    /// The repository resolves and appears related to the package
+    Verified,
+    /// The repository is related to the package but its owner archived it
+    Archived,
+    /// The check did not run (offline build or disabled)
+    Skipped(String),
+    /// The link is not a recognizable repository URL
+    Unparseable,
+    /// The host could not be reached or returned a server error
+    Unreachable(String),
+    /// The repository does not exist
+    NotFound,
+    /// The repository resolves but does not mention the package
+    Unrelated,
+}
+
+/// Result of validating a package's repository link
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepositoryValidation {
+    /// Link as declared by the package
+    pub declared: String,
+    pub status: RepositoryStatus,
+    /// URL the repository link redirected to, if it differs
+    pub redirected_to: Option<String>,
+}
+
+impl RepositoryValidation {
+    /// Report as a `SuspiciousRepository` finding when the link did not check out
+    ///
+    /// Unreachable hosts may be a transient outage and stay low; a missing
+    /// repository is medium; a live repository about something else is high.
+    pub fn to_finding(&self, package_name: &str) -> Option<Finding> {
+        let (severity, rule_id, title, description) = match &self.status {
+            RepositoryStatus::Verified
+            | RepositoryStatus::Archived
+            | RepositoryStatus::Skipped(_) => return None,
+            RepositoryStatus::Unparseable => (
+                RiskLevel::Low,
+                rules::REPOSITORY_UNREACHABLE,
+                "Repository link is not a valid repository URL",
+                "The declared repository cannot be resolved to a source repository",
+            ),
+            RepositoryStatus::Unreachable(_) => (
+                RiskLevel::Low,
+                rules::REPOSITORY_UNREACHABLE,
+                "Repository link could not be reached",
+                "The declared repository did not respond; this may be transient",
+            ),
+            RepositoryStatus::NotFound => (
+                RiskLevel::Medium,
+                rules::REPOSITORY_NOT_FOUND,
+                "Repository link points to a missing repository",
+                "The declared repository does not exist, so the published code cannot be traced to its source",
+            ),
+            RepositoryStatus::Unrelated => (
+                RiskLevel::High,
+                rules::REPOSITORY_UNRELATED,
+                "Repository link points to an unrelated project",
+                "The declared repository never mentions the package, a sign of borrowing another project's reputation",
+            ),
+        };
+
+        let mut finding = Finding::new(
+            FindingType::SuspiciousRepository,
+            severity,
+            title,
+            description,
+        )
+        .with_evidence(format!("repository = \"{}\"", self.declared))
+        .with_affected_component(package_name.to_string())
+        .with_scan_reason(ScanReason::RepositoryCheck)
+        .with_rule_id(rule_id);
+        if let RepositoryStatus::Unreachable(reason) = &self.status {
+            finding = finding.with_evidence(reason.clone());
+        }
+        if let Some(target) = &self.redirected_to {
+            finding = finding.with_evidence(format!("Redirects to {}", target));
+        }
+        Some(finding)
+    }
+}
+
+/// Checks repository links over HTTP
+#[cfg(feature = "runtime")]
+pub struct RepositoryValidator {
+    client: reqwest::Client,
+    /// Fetches forge pages from here instead of `https://{host}`
+    base_url: Option<String>,
+}
+
+#[cfg(feature = "runtime")]
+impl RepositoryValidator {
+    /// Create a validator with default timeouts
+    pub fn new() -> Result<Self> {
+        Self::build(None)
+    }
+
+    /// Create a validator that fetches repository pages from `base_url`, such
+    /// as a forge mirror, instead of the forge itself
+    pub fn with_base_url(base_url: &str) -> Result<Self> {
+        Self::build(Some(base_url.trim_end_matches('/').to_string()))
+    }
+
+    fn build(base_url: Option<String>) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .user_agent(concat!(
+                "threatflux-package-security/",
+                env!("CARGO_PKG_VERSION")
+            ))
+            .redirect(reqwest::redirect::Policy::custom(|attempt| {
+                let same_origin = attempt
+                    .previous()
+                    .first()
+                    .is_some_and(|first| first.origin() == attempt.url().origin());
+                if same_origin && attempt.previous().len() <= MAX_REDIRECTS {
+                    attempt.follow()
+                } else {
+                    attempt.stop()
+                }
+            }))
+            .build()?;
+        Ok(Self { client, base_url })
+    }
+
+    /// Validate that `declared` resolves to a repository related to `package_name`
+    ///
+    /// Links to hosts other than [`KNOWN_FORGES`] are skipped, and at most
+    /// 512 KiB of the page are read.
+    pub async fn validate(&self, package_name: &str, declared: &str) -> RepositoryValidation {
+        let mut validation = RepositoryValidation {
+            declared: declared.to_string(),
+            status: RepositoryStatus::Skipped("offline build".to_string()),
+            redirected_to: None,
+        };
+        if cfg!(feature = "offline") {
+            return validation;
+        }
+
+        let Some(link) = RepositoryLink::parse(declared) else {
+            validation.status = RepositoryStatus::Unparseable;
+            return validation;
+        };
+
+        if !KNOWN_FORGES
+            .iter()
+            .any(|forge| link.host.eq_ignore_ascii_case(forge))
+        {
+            validation.status =
+                RepositoryStatus::Skipped(format!("{} is not a known forge", link.host));
+            return validation;
+        }
+        let base_url = self
+            .base_url
+            .clone()
+            .unwrap_or_else(|| format!("https://{}", link.host.to_ascii_lowercase()));
+        let url = format!("{}/{}/{}", base_url, link.owner, link.name);
+
+        let mut response = match self.client.get(&url).send().await {
+            Ok(response) => response,
+            Err(e) => {
+                validation.status = RepositoryStatus::Unreachable(e.to_string());
+                return validation;
+            }
+        };
+
+        let status = response.status();
+        let final_url = response.url().to_string();
+        let resolved = RepositoryLink::parse(&final_url).filter(|resolved| {
+            !resolved.owner.eq_ignore_ascii_case(&link.owner)
+                || !resolved.name.eq_ignore_ascii_case(&link.name)
+        });
+        validation.redirected_to = resolved.as_ref().map(|r| r.url.clone());
+
+        validation.status = if status.as_u16() == 404 || status.as_u16() == 410 {
+            RepositoryStatus::NotFound
+        } else if !status.is_success() {
+            RepositoryStatus::Unreachable(format!("HTTP {}", status))
+        } else {
+            let target = resolved.unwrap_or(link);
+            let mut body = Vec::new();
+            while body.len() < MAX_BODY_SCAN {
+                match response.chunk().await {
+                    Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+                    _ => break,
+                }
+            }
+            body.truncate(MAX_BODY_SCAN);
+            let page = String::from_utf8_lossy(&body).to_ascii_lowercase();
+            let mentioned = page.contains(&package_name.to_ascii_lowercase());
+            if !(target.names_match(package_name) || mentioned) {
+                RepositoryStatus::Unrelated
+            } else if page.contains(ARCHIVED_BANNER) {
+                RepositoryStatus::Archived
+            } else {
+                RepositoryStatus::Verified
+            }
+        };
+        validation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_repository_forms() {
+        let expected = "https://github.com/lodash/lodash";
+        for form in [
+            "git+https://github.com/lodash/lodash.git",
+            "git://github.com/lodash/lodash.git",
+            "git@github.com:lodash/lodash.git",
+            "github:lodash/lodash",
+            "lodash/lodash",
+            "https://github.com/lodash/lodash/tree/main#readme",
+        ] {
+            assert_eq!(
+                RepositoryLink::parse(form).unwrap().url,
+                expected,
+                "{}",
+                form
+            );
+        }
+        assert!(RepositoryLink::parse("not a url").is_none());
+    }
+
+    #[test]
+    fn test_names_match() {
+        let babel = RepositoryLink::parse("github:babel/babel").unwrap();
+        assert!(babel.names_match("@babel/core"));
+        let chalk = RepositoryLink::parse("github:chalk/chalk").unwrap();
+        assert!(chalk.names_match("chalk"));
+        assert!(!chalk.names_match("crypto-wallet-helper"));
+        let node = RepositoryLink::parse("github:user/node-foo.js").unwrap();
+        assert!(node.names_match("foo"));
+    }
+}
@@ -1,10 +1,21 @@
 //! Risk assessment and scoring framework
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fmt;
 
-use super::{MaliciousPattern, Vulnerability};
+use super::{
+    rules, AnalysisOptions, DependencyAnalysis, DependencyType, InstallHook, MaliciousPattern,
+    PatternSeverity, PinningReport, ScanContext, Vulnerability, VulnerabilitySeverity,
+};
+
+/// Share of the total taken off a package without install hooks
+const NO_INSTALL_HOOKS_SHARE: f32 = 0.05;
+
+/// Supply chain points when every direct dependency is loosely pinned
+const LOOSE_PINNING_POINTS: f32 = 10.0;
 
 /// Risk level categories
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
@@ -17,15 +28,9 @@ pub enum RiskLevel {
 }
 
 impl RiskLevel {
-    /// Convert from numeric risk score
+    /// Convert from numeric risk score, with the default [`RiskThresholds`]
     pub fn from_score(score: f32) -> Self {
-        match score {
-            s if s >= 80.0 => Self::Critical,
-            s if s >= 60.0 => Self::High,
-            s if s >= 40.0 => Self::Medium,
-            s if s >= 20.0 => Self::Low,
-            _ => Self::Safe,
-        }
+        RiskThresholds::default().level(score)
     }
 
     /// Get color representation for UI
@@ -38,6 +43,29 @@ impl RiskLevel {
             Self::Safe => "#00CC00",     // Green
         }
     }
+
+    /// Named shields.io color
+    pub fn shields_color(&self) -> &'static str {
+        match self {
+            Self::Critical => "red",
+            Self::High => "orange",
+            Self::Medium => "yellow",
+            Self::Low => "green",
+            Self::Safe => "brightgreen",
+        }
+    }
+}
+
+impl From<VulnerabilitySeverity> for RiskLevel {
+    fn from(severity: VulnerabilitySeverity) -> Self {
+        match severity {
+            VulnerabilitySeverity::Critical => Self::Critical,
+            VulnerabilitySeverity::High => Self::High,
+            VulnerabilitySeverity::Medium => Self::Medium,
+            VulnerabilitySeverity::Low => Self::Low,
+            VulnerabilitySeverity::None => Self::Safe,
+        }
+    }
 }
 
 impl fmt::Display for RiskLevel {
@@ -57,14 +85,110 @@ impl fmt::Display for RiskLevel {
 pub struct RiskScore {
     pub total_score: f32,
     pub risk_level: RiskLevel,
+    /// Score of each component after its weight is applied
+    ///
+    /// The components add up to `total_score`, which is capped at 100.
     pub components: HashMap<String, f32>,
     pub factors: Vec<RiskFactor>,
+    /// Scoring the score was computed with, when not the default
+    #[serde(default)]
+    pub scoring: Option<RiskScoringConfig>,
+}
+
+/// Lowest total score rated at each risk level
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskThresholds {
+    pub low: f32,
+    pub medium: f32,
+    pub high: f32,
+    pub critical: f32,
+}
+
+impl RiskThresholds {
+    /// Risk level of a score
+    pub fn level(&self, score: f32) -> RiskLevel {
+        match score {
+            s if s >= self.critical => RiskLevel::Critical,
+            s if s >= self.high => RiskLevel::High,
+            s if s >= self.medium => RiskLevel::Medium,
+            s if s >= self.low => RiskLevel::Low,
+            _ => RiskLevel::Safe,
+        }
+    }
+}
+
+impl Default for RiskThresholds {
+    fn default() -> Self {
+        Self {
+            low: 20.0,
+            medium: 40.0,
+            high: 60.0,
+            critical: 80.0,
+        }
+    }
+}
+
+/// Weight each [`RiskScore`] component is multiplied by
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComponentWeights {
+    pub vulnerabilities: f32,
+    pub malicious_code: f32,
+    pub typosquatting: f32,
+    pub supply_chain: f32,
+    /// Findings reported by the analyzer, see [`RiskCalculator::apply_findings`]
+    pub findings: f32,
+    /// Findings merged from other tools, see [`RiskCalculator::apply_external`]
+    pub external_findings: f32,
+}
+
+impl ComponentWeights {
+    /// Weight of the component named `component`; unknown components weigh 1
+    pub fn get(&self, component: &str) -> f32 {
+        match component {
+            "vulnerabilities" => self.vulnerabilities,
+            "malicious_code" => self.malicious_code,
+            "typosquatting" => self.typosquatting,
+            "supply_chain" => self.supply_chain,
+            "findings" => self.findings,
+            "external_findings" => self.external_findings,
+            _ => 1.0,
+        }
+    }
+}
+
+impl Default for ComponentWeights {
+    fn default() -> Self {
+        Self {
+            vulnerabilities: 1.0,
+            malicious_code: 2.0,
+            typosquatting: 1.5,
+            supply_chain: 1.2,
+            findings: 1.0,
+            external_findings: 1.0,
+        }
+    }
+}
+
+/// Score bands and component weights a [`RiskCalculator`] scores with
+///
+/// Set through [`AnalysisOptions::scoring`] to match an organization's risk
+/// appetite.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskScoringConfig {
+    pub thresholds: RiskThresholds,
+    pub weights: ComponentWeights,
 }
 
 /// Individual risk factor
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskFactor {
     pub category: RiskCategory,
+    /// Key of the [`RiskScore::components`] entry this factor produced
+    #[serde(default)]
+    pub component: String,
     pub description: String,
     pub severity: RiskLevel,
     pub score_contribution: f32,
@@ -72,6 +196,39 @@ pub struct RiskFactor {
     pub mitigation: Option<String>,
 }
 
+impl fmt::Display for RiskFactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} contributed +{}",
+            self.description,
+            (self.score_contribution * 10.0).round() / 10.0
+        )
+    }
+}
+
+impl RiskScore {
+    /// Factors behind each component, largest contribution first
+    ///
+    /// One factor per component: when a component was rescored, as merging
+    /// external findings does, the latest factor is the one that counts.
+    pub fn explanation(&self) -> Vec<RiskFactor> {
+        let mut latest: HashMap<&str, &RiskFactor> = HashMap::new();
+        for factor in &self.factors {
+            if self.components.contains_key(&factor.component) {
+                latest.insert(factor.component.as_str(), factor);
+            }
+        }
+        let mut factors: Vec<RiskFactor> = latest.into_values().cloned().collect();
+        factors.sort_by(|a, b| {
+            b.score_contribution
+                .total_cmp(&a.score_contribution)
+                .then_with(|| a.component.cmp(&b.component))
+        });
+        factors
+    }
+}
+
 /// Risk categories
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum RiskCategory {
@@ -93,6 +250,112 @@ pub struct RiskAssessment {
     pub detailed_findings: Vec<Finding>,
     pub recommendations: Vec<Recommendation>,
     pub security_posture: SecurityPosture,
+    /// Which analysis phases ran and how many findings each produced
+    #[serde(default)]
+    pub scan_phases: Vec<PhaseRecord>,
+    /// Risk of what ships versus the whole development toolchain
+    #[serde(default)]
+    pub scoped_risk: Option<ScopedRisk>,
+    /// Tool and database versions the assessment was produced with
+    #[serde(default)]
+    pub scan_context: Option<ScanContext>,
+}
+
+impl RiskAssessment {
+    /// Risk from runtime dependencies and the package's own code
+    ///
+    /// Falls back to the overall level when no scoped risk was computed.
+    pub fn production_risk_level(&self) -> RiskLevel {
+        self.scoped_risk
+            .map_or(self.risk_score.risk_level, |scoped| scoped.production_level)
+    }
+
+    /// Whether some inputs were skipped because they could not be parsed
+    pub fn is_partial(&self) -> bool {
+        self.detailed_findings
+            .iter()
+            .any(|f| f.finding_type == FindingType::PartialParse)
+    }
+
+    /// Risk from every dependency, including development-only ones
+    pub fn development_risk_level(&self) -> RiskLevel {
+        self.scoped_risk
+            .map_or(self.risk_score.risk_level, |scoped| {
+                scoped.development_level
+            })
+    }
+}
+
+/// Sort key ordering analysis results worst-first
+///
+/// Compares by risk level, then total score, then number of findings, all
+/// descending, and finally by package name and version so that the order is
+/// total and does not depend on the input order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskOrderingKey {
+    pub risk_level: RiskLevel,
+    pub total_score: f32,
+    pub finding_count: usize,
+    pub name: String,
+    pub version: String,
+}
+
+impl RiskOrderingKey {
+    /// Key for an assessment of the package `name` at `version`
+    pub fn new(assessment: &RiskAssessment, name: &str, version: &str) -> Self {
+        Self {
+            risk_level: assessment.risk_score.risk_level,
+            total_score: assessment.risk_score.total_score,
+            finding_count: assessment.detailed_findings.len(),
+            name: name.to_string(),
+            version: version.to_string(),
+        }
+    }
+}
+
+impl PartialEq for RiskOrderingKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for RiskOrderingKey {}
+
+impl PartialOrd for RiskOrderingKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RiskOrderingKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .risk_level
+            .cmp(&self.risk_level)
+            .then_with(|| other.total_score.total_cmp(&self.total_score))
+            .then_with(|| other.finding_count.cmp(&self.finding_count))
+            .then_with(|| self.name.cmp(&other.name))
+            .then_with(|| self.version.cmp(&other.version))
+    }
+}
+
+/// Risk split between production and development scope
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ScopedRisk {
+    pub production_score: f32,
+    pub production_level: RiskLevel,
+    pub development_score: f32,
+    pub development_level: RiskLevel,
+}
+
+impl fmt::Display for ScopedRisk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "production risk {}, development risk {}",
+            self.production_level, self.development_level
+        )
+    }
 }
 
 /// Security finding
@@ -104,6 +367,222 @@ pub struct Finding {
     pub description: String,
     pub evidence: Vec<String>,
     pub affected_components: Vec<String>,
+    /// Phase or source that produced this finding
+    #[serde(default)]
+    pub scan_reason: Option<ScanReason>,
+    /// Stable rule ID of the check that produced this finding (see [`super::rules`])
+    #[serde(default)]
+    pub rule_id: Option<String>,
+    /// For vulnerabilities, whether the package likely calls the affected code
+    ///
+    /// See [`Vulnerability::likely_reachable`]; `None` when unknown.
+    #[serde(default)]
+    pub likely_reachable: Option<bool>,
+}
+
+impl Finding {
+    /// Create a new finding without evidence or affected components
+    pub fn new(
+        finding_type: FindingType,
+        severity: RiskLevel,
+        title: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        Self {
+            finding_type,
+            severity,
+            title: title.into(),
+            description: description.into(),
+            evidence: Vec::new(),
+            affected_components: Vec::new(),
+            scan_reason: None,
+            rule_id: None,
+            likely_reachable: None,
+        }
+    }
+
+    /// Record the stable rule ID of the check that produced this finding
+    pub fn with_rule_id(mut self, rule_id: impl Into<String>) -> Self {
+        self.rule_id = Some(rule_id.into());
+        self
+    }
+
+    /// Record which phase or source produced this finding
+    pub fn with_scan_reason(mut self, reason: ScanReason) -> Self {
+        self.scan_reason = Some(reason);
+        self
+    }
+
+    /// Add a piece of evidence
+    pub fn with_evidence(mut self, evidence: impl Into<String>) -> Self {
+        self.evidence.push(evidence.into());
+        self
+    }
+
+    /// Add an affected component (dependency, module, file)
+    pub fn with_affected_component(mut self, component: impl Into<String>) -> Self {
+        self.affected_components.push(component.into());
+        self
+    }
+
+    /// Stable identity of this finding across scans
+    ///
+    /// Hashes the rule (or finding type), the affected components and what
+    /// the finding is about: the advisory ID for vulnerabilities, the title
+    /// otherwise. Severity, description and evidence are left out, so a
+    /// finding that is re-scored or gains evidence keeps its fingerprint.
+    pub fn fingerprint(&self) -> String {
+        let rule = self
+            .rule_id
+            .clone()
+            .unwrap_or_else(|| format!("{:?}", self.finding_type));
+        let subject = match self.finding_type {
+            FindingType::Vulnerability => self.title.split(": ").next().unwrap_or_default(),
+            _ => self.title.as_str(),
+        };
+        let mut components = self.affected_components.clone();
+        components.sort();
+
+        let mut hasher = Sha256::new();
+        for part in [rule.as_str(), subject]
+            .into_iter()
+            .chain(components.iter().map(String::as_str))
+        {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        hex::encode(&hasher.finalize()[..16])
+    }
+
+    /// Finding for a vulnerability in a dependency
+    pub fn from_vulnerability(vuln: &Vulnerability, component: &str) -> Self {
+        let mut finding = Self::new(
+            FindingType::Vulnerability,
+            RiskLevel::from(vuln.severity.clone()),
+            format!("{}: {}", vuln.id, vuln.title),
+            vuln.description.clone(),
+        )
+        .with_affected_component(component)
+        .with_scan_reason(ScanReason::VulnerabilityCheck)
+        .with_rule_id(rules::VULNERABLE_DEPENDENCY);
+        if !vuln.fixed_versions.is_empty() {
+            finding = finding.with_evidence(format!("Fixed in {}", vuln.fixed_versions.join(", ")));
+        }
+        finding.likely_reachable = vuln.likely_reachable;
+        match vuln.likely_reachable {
+            Some(true) => {
+                finding = finding.with_evidence(format!(
+                    "Likely reachable: code uses {}",
+                    vuln.affected_symbols.join(", ")
+                ))
+            }
+            Some(false) => {
+                finding = finding.with_evidence(format!(
+                    "Likely unreachable: code never uses {}",
+                    vuln.affected_symbols.join(", ")
+                ))
+            }
+            None => {}
+        }
+        finding
+    }
+
+    /// Finding for a matched malicious pattern
+    pub fn from_pattern(pattern: &MaliciousPattern) -> Self {
+        let severity = match pattern.severity {
+            PatternSeverity::Critical => RiskLevel::Critical,
+            PatternSeverity::High => RiskLevel::High,
+            PatternSeverity::Medium => RiskLevel::Medium,
+            PatternSeverity::Low => RiskLevel::Low,
+        };
+        Self {
+            evidence: pattern.evidence.clone(),
+            ..Self::new(
+                FindingType::MaliciousPattern,
+                severity,
+                pattern.pattern_name.clone(),
+                pattern.description.clone(),
+            )
+        }
+        .with_scan_reason(ScanReason::MaliciousPatterns)
+        .with_rule_id(rules::pattern_rule_id(&pattern.pattern_id))
+    }
+}
+
+/// Analysis phase or source that produced a finding
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ScanReason {
+    Manifest,
+    DependencyAnalysis,
+    VulnerabilityCheck,
+    MaliciousPatterns,
+    Typosquatting,
+    Integrity,
+    BinaryInventory,
+    RepositoryCheck,
+    ProvenanceCheck,
+    CustomRule(String),
+    ExternalSource(String),
+}
+
+impl ScanReason {
+    /// `AnalysisOptions` field that turns this phase on or off, if any
+    pub fn option(&self) -> Option<&'static str> {
+        match self {
+            ScanReason::DependencyAnalysis => Some("analyze_dependencies"),
+            ScanReason::VulnerabilityCheck => Some("check_vulnerabilities"),
+            ScanReason::MaliciousPatterns => Some("scan_malicious_patterns"),
+            ScanReason::Typosquatting => Some("detect_typosquatting"),
+            ScanReason::RepositoryCheck => Some("validate_repository"),
+            ScanReason::ProvenanceCheck => Some("verify_provenance"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ScanReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanReason::Manifest => write!(f, "manifest phase"),
+            ScanReason::DependencyAnalysis => write!(f, "dependency analysis phase"),
+            ScanReason::VulnerabilityCheck => write!(f, "vulnerability check phase"),
+            ScanReason::MaliciousPatterns => write!(f, "malicious pattern phase"),
+            ScanReason::Typosquatting => write!(f, "typosquatting phase"),
+            ScanReason::Integrity => write!(f, "integrity phase"),
+            ScanReason::BinaryInventory => write!(f, "binary inventory phase"),
+            ScanReason::RepositoryCheck => write!(f, "repository check phase"),
+            ScanReason::ProvenanceCheck => write!(f, "provenance check phase"),
+            ScanReason::CustomRule(id) => write!(f, "custom rule {}", id),
+            ScanReason::ExternalSource(name) => write!(f, "{} source", name),
+        }
+    }
+}
+
+/// Whether an analysis phase ran during a scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PhaseRecord {
+    pub reason: ScanReason,
+    pub executed: bool,
+    /// Option that disabled the phase when it did not run
+    pub disabled_by: Option<String>,
+    pub findings: usize,
+}
+
+impl PhaseRecord {
+    /// Record a phase that ran when `enabled`, or was skipped by its option
+    pub fn new(reason: ScanReason, enabled: bool, findings: usize) -> Self {
+        let disabled_by = if enabled {
+            None
+        } else {
+            reason.option().map(String::from)
+        };
+        Self {
+            reason,
+            executed: enabled,
+            disabled_by,
+            findings: if enabled { findings } else { 0 },
+        }
+    }
 }
 
 /// Finding types
@@ -114,6 +593,35 @@ pub enum FindingType {
     SuspiciousActivity,
     PolicyViolation,
     QualityIssue,
+    NonRegistrySource,
+    IntegrityMismatch,
+    MissingIntegrity,
+    DependencyCycle,
+    BundledWasm,
+    PrebuiltBinary,
+    UnpinnedDependency,
+    SandboxEvasion,
+    EncryptedArchive,
+    OverbroadCapability,
+    RuntimeRemoteCode,
+    SuspiciousRepository,
+    ResourceExhaustionAttempt,
+    InvalidAttestation,
+    InsecureTransport,
+    SuspiciousName,
+    CrossLanguageExecution,
+    DependencyAlias,
+    PurposeMismatch,
+    RangeAllowsVulnerable,
+    DelayedExecution,
+    PartialParse,
+    DuplicateDeclaration,
+    RegistryExfiltration,
+    SuspiciousArchiveLayout,
+    ImportHijack,
+    MetadataInjection,
+    PlatformTargetedPayload,
+    DependencyConfusion,
 }
 
 /// Recommendation for addressing risks
@@ -167,23 +675,28 @@ pub struct SecurityPosture {
 
 /// Risk calculator
 pub struct RiskCalculator {
-    weights: HashMap<RiskCategory, f32>,
+    config: RiskScoringConfig,
 }
 
 impl RiskCalculator {
     /// Create a new risk calculator with default weights
     pub fn new() -> Self {
-        let mut weights = HashMap::new();
-        weights.insert(RiskCategory::Vulnerability, 1.0);
-        weights.insert(RiskCategory::MaliciousCode, 2.0);
-        weights.insert(RiskCategory::Typosquatting, 1.5);
-        weights.insert(RiskCategory::SupplyChain, 1.2);
-        weights.insert(RiskCategory::Maintenance, 0.5);
-        weights.insert(RiskCategory::License, 0.3);
-        weights.insert(RiskCategory::Privacy, 0.8);
-        weights.insert(RiskCategory::Quality, 0.4);
+        Self::with_config(RiskScoringConfig::default())
+    }
 
-        Self { weights }
+    /// Risk calculator with custom thresholds and weights
+    pub fn with_config(config: RiskScoringConfig) -> Self {
+        Self { config }
+    }
+
+    /// Risk calculator for the scoring `options` ask for
+    pub fn from_options(options: &AnalysisOptions) -> Self {
+        Self::with_config(options.scoring.unwrap_or_default())
+    }
+
+    /// Risk level of a score under this calculator's thresholds
+    pub fn level(&self, score: f32) -> RiskLevel {
+        self.config.thresholds.level(score)
     }
 
     /// Calculate risk score from various inputs
@@ -201,12 +714,13 @@ impl RiskCalculator {
         // Vulnerability score
         let vuln_score = self.calculate_vulnerability_score(vulnerabilities);
         if vuln_score > 0.0 {
-            components.insert("vulnerabilities".to_string(), vuln_score);
+            let weighted = self.insert_component(&mut components, "vulnerabilities", vuln_score);
             factors.push(RiskFactor {
                 category: RiskCategory::Vulnerability,
-                description: format!("{} vulnerabilities found", vulnerabilities.len()),
-                severity: RiskLevel::from_score(vuln_score),
-                score_contribution: vuln_score * self.weights[&RiskCategory::Vulnerability],
+                component: "vulnerabilities".to_string(),
+                description: Self::describe_vulnerabilities(vulnerabilities),
+                severity: self.level(vuln_score),
+                score_contribution: weighted,
                 evidence: vulnerabilities
                     .iter()
                     .map(|v| format!("{}: {}", v.id, v.title))
@@ -218,12 +732,24 @@ impl RiskCalculator {
         // Malicious code score
         let malicious_score = self.calculate_malicious_score(malicious_patterns);
         if malicious_score > 0.0 {
-            components.insert("malicious_code".to_string(), malicious_score);
+            let weighted =
+                self.insert_component(&mut components, "malicious_code", malicious_score);
             factors.push(RiskFactor {
                 category: RiskCategory::MaliciousCode,
-                description: format!("{} malicious patterns detected", malicious_patterns.len()),
+                component: "malicious_code".to_string(),
+                description: format!(
+                    "{} detected: {}",
+                    plural(malicious_patterns.len(), "malicious pattern"),
+                    malicious_patterns
+                        .iter()
+                        .map(|p| p.pattern_name.as_str())
+                        .collect::<BTreeSet<_>>()
+                        .into_iter()
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
                 severity: RiskLevel::Critical,
-                score_contribution: malicious_score * self.weights[&RiskCategory::MaliciousCode],
+                score_contribution: weighted,
                 evidence: malicious_patterns
                     .iter()
                     .map(|p| p.pattern_name.clone())
@@ -235,12 +761,13 @@ impl RiskCalculator {
         // Typosquatting
         if is_typosquatting {
             let typo_score = 60.0;
-            components.insert("typosquatting".to_string(), typo_score);
+            let weighted = self.insert_component(&mut components, "typosquatting", typo_score);
             factors.push(RiskFactor {
                 category: RiskCategory::Typosquatting,
+                component: "typosquatting".to_string(),
                 description: "Package name is suspiciously similar to popular package".to_string(),
                 severity: RiskLevel::High,
-                score_contribution: typo_score * self.weights[&RiskCategory::Typosquatting],
+                score_contribution: weighted,
                 evidence: vec!["Name similarity detected".to_string()],
                 mitigation: Some("Verify correct package name".to_string()),
             });
@@ -248,39 +775,314 @@ impl RiskCalculator {
 
         // Supply chain
         if supply_chain_score > 20.0 {
-            components.insert("supply_chain".to_string(), supply_chain_score);
+            let weighted =
+                self.insert_component(&mut components, "supply_chain", supply_chain_score);
             factors.push(RiskFactor {
                 category: RiskCategory::SupplyChain,
+                component: "supply_chain".to_string(),
                 description: "Supply chain risks detected".to_string(),
-                severity: RiskLevel::from_score(supply_chain_score),
-                score_contribution: supply_chain_score * self.weights[&RiskCategory::SupplyChain],
+                severity: self.level(supply_chain_score),
+                score_contribution: weighted,
                 evidence: vec!["Installation scripts present".to_string()],
                 mitigation: Some("Review installation scripts".to_string()),
             });
         }
 
         // Calculate total
-        let total_score: f32 = components
-            .values()
-            .zip(components.keys().map(|k| match k.as_str() {
-                "vulnerabilities" => self.weights[&RiskCategory::Vulnerability],
-                "malicious_code" => self.weights[&RiskCategory::MaliciousCode],
-                "typosquatting" => self.weights[&RiskCategory::Typosquatting],
-                "supply_chain" => self.weights[&RiskCategory::SupplyChain],
-                _ => 1.0,
-            }))
-            .map(|(score, weight)| score * weight)
-            .sum::<f32>()
-            .min(100.0);
+        let total_score = Self::total(&components);
 
         RiskScore {
             total_score,
-            risk_level: RiskLevel::from_score(total_score),
+            risk_level: self.level(total_score),
             components,
             factors,
+            scoring: (self.config != RiskScoringConfig::default()).then_some(self.config),
         }
     }
 
+    /// Fold analyzer findings into an existing risk score
+    ///
+    /// The most severe finding determines the `findings` component, so a
+    /// single High finding lifts the overall level to at least High.
+    pub fn apply_findings(&self, risk_score: &mut RiskScore, findings: &[Finding]) {
+        let Some(worst) = findings.iter().map(|f| f.severity).max() else {
+            return;
+        };
+
+        let findings_score = Self::findings_score(worst);
+        if findings_score <= 0.0 {
+            return;
+        }
+
+        let findings_score =
+            self.insert_component(&mut risk_score.components, "findings", findings_score);
+        risk_score.factors.push(RiskFactor {
+            category: RiskCategory::SupplyChain,
+            component: "findings".to_string(),
+            description: Self::describe_findings(findings, worst),
+            severity: worst,
+            score_contribution: findings_score,
+            evidence: findings.iter().map(|f| f.title.clone()).collect(),
+            mitigation: Some("Review reported findings".to_string()),
+        });
+
+        risk_score.total_score = Self::total(&risk_score.components);
+        risk_score.risk_level = self.level(risk_score.total_score);
+    }
+
+    /// Fold findings merged from other tools into an existing risk score
+    ///
+    /// Scored like [`Self::apply_findings`] under its own `external_findings`
+    /// component, so merging never lowers the score.
+    pub fn apply_external(&self, risk_score: &mut RiskScore, findings: &[Finding]) {
+        let Some(worst) = findings.iter().map(|f| f.severity).max() else {
+            return;
+        };
+        let previous = risk_score
+            .components
+            .get("external_findings")
+            .copied()
+            .unwrap_or(0.0);
+        let external_score =
+            (Self::findings_score(worst) * self.config.weights.external_findings).max(previous);
+        if external_score <= 0.0 {
+            return;
+        }
+
+        risk_score
+            .components
+            .insert("external_findings".to_string(), external_score);
+        risk_score.factors.push(RiskFactor {
+            category: RiskCategory::SupplyChain,
+            component: "external_findings".to_string(),
+            description: format!(
+                "{}, reported by other tools",
+                Self::describe_findings(findings, worst)
+            ),
+            severity: worst,
+            score_contribution: external_score,
+            evidence: findings.iter().map(|f| f.title.clone()).collect(),
+            mitigation: Some("Review findings from other tools".to_string()),
+        });
+
+        risk_score.total_score = Self::total(&risk_score.components);
+        risk_score.risk_level = self.level(risk_score.total_score);
+    }
+
+    /// Raise the `supply_chain` component for loosely pinned dependencies
+    ///
+    /// In proportion to the share of direct dependencies with a loose
+    /// specifier that no lockfile holds; a lockfile mitigates the rest.
+    pub fn apply_pinning(&self, risk_score: &mut RiskScore, report: &PinningReport) {
+        let points = LOOSE_PINNING_POINTS * report.unmitigated_share();
+        if points <= 0.0 {
+            return;
+        }
+
+        let weighted = points * self.config.weights.get("supply_chain");
+        let supply_chain = risk_score
+            .components
+            .entry("supply_chain".to_string())
+            .or_insert(0.0);
+        *supply_chain += weighted;
+        let evidence = report
+            .worst_offenders
+            .iter()
+            .filter(|dep| !dep.mitigated_by_lockfile())
+            .map(|dep| format!("{} = \"{}\"", dep.name, dep.version_spec));
+        // Folded into the earlier supply chain factor, one factor per component
+        match risk_score
+            .factors
+            .iter_mut()
+            .rfind(|factor| factor.component == "supply_chain")
+        {
+            Some(factor) => {
+                factor.score_contribution = *supply_chain;
+                factor.evidence.extend(evidence);
+            }
+            None => risk_score.factors.push(RiskFactor {
+                category: RiskCategory::SupplyChain,
+                component: "supply_chain".to_string(),
+                description: format!(
+                    "{} of {} direct dependencies loosely pinned without a lockfile",
+                    report.loose - report.mitigated_by_lockfile,
+                    report.classified()
+                ),
+                severity: RiskLevel::Low,
+                score_contribution: *supply_chain,
+                evidence: evidence.collect(),
+                mitigation: Some("Pin exact versions or commit a lockfile".to_string()),
+            }),
+        }
+
+        risk_score.total_score = Self::total(&risk_score.components);
+        risk_score.risk_level = self.level(risk_score.total_score);
+    }
+
+    /// Lower the score slightly for a package that runs nothing at install
+    ///
+    /// Only for ecosystems with install hooks: there, having none removes
+    /// the most common way in. The credit is its own negative
+    /// `no_install_hooks` component, a small share of the total, so it never
+    /// takes the total below zero.
+    pub fn apply_install_hooks(&self, risk_score: &mut RiskScore, hooks: &[InstallHook]) {
+        if !hooks.is_empty() {
+            return;
+        }
+        let Some(credit) = Self::credit_install_hooks(&mut risk_score.components) else {
+            return;
+        };
+        risk_score.factors.push(RiskFactor {
+            category: RiskCategory::SupplyChain,
+            component: "no_install_hooks".to_string(),
+            description: "Nothing runs at install time".to_string(),
+            severity: RiskLevel::Safe,
+            score_contribution: -credit,
+            evidence: vec![],
+            mitigation: None,
+        });
+        risk_score.total_score = Self::total(&risk_score.components);
+        risk_score.risk_level = self.level(risk_score.total_score);
+    }
+
+    /// Insert the `no_install_hooks` credit for `components`, replacing any
+    /// earlier one; `None` when there is nothing to take off
+    fn credit_install_hooks(components: &mut HashMap<String, f32>) -> Option<f32> {
+        components.remove("no_install_hooks");
+        let credit = Self::total(components) * NO_INSTALL_HOOKS_SHARE;
+        if credit <= 0.0 {
+            return None;
+        }
+        components.insert("no_install_hooks".to_string(), -credit);
+        Some(credit)
+    }
+
+    /// Split a final score into production and development scope
+    ///
+    /// Development risk is the full score. Production risk recomputes the
+    /// vulnerability and findings components without top-level dependencies
+    /// outside `production_types` and everything beneath them, or findings
+    /// that only affect those.
+    pub fn scoped_risk(
+        &self,
+        risk_score: &RiskScore,
+        dependencies: &DependencyAnalysis,
+        findings: &[Finding],
+        production_types: &[DependencyType],
+    ) -> ScopedRisk {
+        let mut components = risk_score.components.clone();
+        components.remove("vulnerabilities");
+        components.remove("findings");
+
+        // Transitive dependencies ship when the top-level one they sit under does
+        let mut seen = HashSet::new();
+        let vulnerabilities: Vec<Vulnerability> = dependencies
+            .scoped_dependencies()
+            .into_iter()
+            .filter(|(_, scope)| production_types.contains(scope))
+            .flat_map(|(dep, _)| dep.vulnerabilities.iter().map(move |v| (dep, v)))
+            .filter(|(dep, v)| seen.insert((dep.name.as_str(), v.id.as_str())))
+            .map(|(_, v)| v.clone())
+            .collect();
+        let vuln_score = self.calculate_vulnerability_score(&vulnerabilities);
+        if vuln_score > 0.0 {
+            self.insert_component(&mut components, "vulnerabilities", vuln_score);
+        }
+
+        let worst = findings
+            .iter()
+            .filter(|f| !dependencies.only_affects_non_production(f, production_types))
+            .map(|f| f.severity)
+            .max();
+        if let Some(findings_score) = worst.map(Self::findings_score).filter(|s| *s > 0.0) {
+            self.insert_component(&mut components, "findings", findings_score);
+        }
+
+        if components.remove("no_install_hooks").is_some() {
+            let _ = Self::credit_install_hooks(&mut components);
+        }
+
+        let production_score = Self::total(&components);
+        ScopedRisk {
+            production_score,
+            production_level: self.level(production_score),
+            development_score: risk_score.total_score,
+            development_level: risk_score.risk_level,
+        }
+    }
+
+    /// Vulnerability counts by severity, such as "1 critical and 2 high vulnerabilities"
+    fn describe_vulnerabilities(vulnerabilities: &[Vulnerability]) -> String {
+        let counts: Vec<String> = [
+            VulnerabilitySeverity::Critical,
+            VulnerabilitySeverity::High,
+            VulnerabilitySeverity::Medium,
+            VulnerabilitySeverity::Low,
+        ]
+        .into_iter()
+        .filter_map(|severity| {
+            let count = vulnerabilities
+                .iter()
+                .filter(|v| v.severity == severity)
+                .count();
+            (count > 0).then(|| format!("{} {}", count, severity.to_string().to_lowercase()))
+        })
+        .collect();
+        let rated = vulnerabilities
+            .iter()
+            .filter(|v| v.severity != VulnerabilitySeverity::None)
+            .count();
+        let noun = if rated == 1 {
+            "vulnerability"
+        } else {
+            "vulnerabilities"
+        };
+        match counts.split_last() {
+            Some((last, [])) => format!("{} {}", last, noun),
+            Some((last, rest)) => format!("{} and {} {}", rest.join(", "), last, noun),
+            None => plural(vulnerabilities.len(), "vulnerability"),
+        }
+    }
+
+    /// The most severe finding's title, and how many others there are
+    fn describe_findings(findings: &[Finding], worst: RiskLevel) -> String {
+        let title = findings
+            .iter()
+            .find(|f| f.severity == worst)
+            .map_or("", |f| f.title.as_str());
+        match findings.len() {
+            1 => title.to_string(),
+            n => format!("{} and {}", title, plural(n - 1, "other finding")),
+        }
+    }
+
+    fn findings_score(worst: RiskLevel) -> f32 {
+        match worst {
+            RiskLevel::Critical => 90.0,
+            RiskLevel::High => 65.0,
+            RiskLevel::Medium => 45.0,
+            RiskLevel::Low => 25.0,
+            RiskLevel::Safe => 0.0,
+        }
+    }
+
+    /// Record `score` under `component` with its weight applied, returning
+    /// the weighted score
+    fn insert_component(
+        &self,
+        components: &mut HashMap<String, f32>,
+        component: &str,
+        score: f32,
+    ) -> f32 {
+        let weighted = score * self.config.weights.get(component);
+        components.insert(component.to_string(), weighted);
+        weighted
+    }
+
+    fn total(components: &HashMap<String, f32>) -> f32 {
+        components.values().sum::<f32>().min(100.0)
+    }
+
     fn calculate_vulnerability_score(&self, vulnerabilities: &[Vulnerability]) -> f32 {
         vulnerabilities
             .iter()
@@ -304,3 +1106,12 @@ impl Default for RiskCalculator {
         Self::new()
     }
 }
+
+/// `count` followed by `noun`, with an "s" unless `count` is one
+fn plural(count: usize, noun: &str) -> String {
+    match (count, noun.strip_suffix('y')) {
+        (1, _) => format!("1 {}", noun),
+        (_, Some(stem)) => format!("{} {}ies", count, stem),
+        _ => format!("{} {}s", count, noun),
+    }
+}
@@ -0,0 +1,551 @@
+//! Stable identifiers for built-in detection rules
+//!
+//! Every finding emitted by a built-in check carries a `TFX-<AREA>-<RULE>-<NNN>`
+//! rule ID. IDs are part of the public contract: once released, an ID keeps
+//! its meaning and is never reused, so suppressions, SARIF rule mappings and
+//! cross-version diffs can rely on it. Retired rules keep their entry.
+
+use serde::{Deserialize, Serialize};
+
+use super::{FindingType, PatternMatcher};
+
+/// Version of the rule ID namespace, bumped only if the ID format changes
+pub const RULE_NAMESPACE_VERSION: u32 = 1;
+
+/// Known vulnerability in a dependency
+pub const VULNERABLE_DEPENDENCY: &str = "TFX-VULN-DEP-001";
+/// Direct dependency with a range that admits arbitrary future releases
+pub const UNPINNED_DEPENDENCY: &str = "TFX-DEP-UNPINNED-001";
+/// Packages that depend on each other in a cycle
+pub const DEPENDENCY_CYCLE: &str = "TFX-DEP-CYCLE-001";
+/// Dependency declared as an alias installing a differently named package
+pub const DEPENDENCY_ALIAS: &str = "TFX-DEP-ALIAS-001";
+/// Declared range that permits a known-vulnerable version
+pub const RANGE_ALLOWS_VULNERABLE: &str = "TFX-DEP-RANGE-VULN-001";
+/// Dependency source fetched without transport security
+pub const INSECURE_TRANSPORT: &str = "TFX-TRANSPORT-INSECURE-001";
+/// Dependency fetched from a host the source policy does not allow
+pub const SOURCE_POLICY_VIOLATION: &str = "TFX-POLICY-SOURCE-001";
+/// Internal dependency that may be installed from a public registry
+pub const DEPENDENCY_CONFUSION: &str = "TFX-DEP-CONFUSION-001";
+/// Code that fingerprints its environment before acting
+pub const SANDBOX_EVASION: &str = "TFX-EVASION-FINGERPRINT-001";
+/// Package declared in several dependency sections with different constraints
+pub const DUPLICATE_DECLARATION: &str = "TFX-DEP-DUPLICATE-001";
+/// Manifest or lockfile skipped because it could not be parsed
+pub const PARTIAL_PARSE: &str = "TFX-PARSE-PARTIAL-001";
+/// Code that sleeps or schedules itself for later, outlasting sandbox observation
+pub const DELAYED_EXECUTION: &str = "TFX-EVASION-DELAY-001";
+/// Install code that publishes packages or writes to a registry
+pub const REGISTRY_EXFILTRATION: &str = "TFX-EXFIL-REGISTRY-001";
+/// Code that patches or hooks module resolution
+pub const IMPORT_HIJACK: &str = "TFX-HIJACK-IMPORT-001";
+/// Package metadata containing escape sequences or hidden characters
+pub const METADATA_INJECTION: &str = "TFX-META-INJECTION-001";
+/// Install code whose actions only run on particular operating systems or CPUs
+pub const PLATFORM_TARGETED_PAYLOAD: &str = "TFX-EVASION-PLATFORM-001";
+/// Code that runs another language's interpreter on inline or bundled code
+pub const CROSS_LANGUAGE_EXECUTION: &str = "TFX-EXEC-CROSS-LANGUAGE-001";
+/// Code performing operations its declared purpose rules out
+pub const PURPOSE_MISMATCH: &str = "TFX-PURPOSE-MISMATCH-001";
+/// Archive with password-protected entries
+pub const ENCRYPTED_ARCHIVE: &str = "TFX-ARCHIVE-ENCRYPTED-001";
+/// Bundled WebAssembly module
+pub const NPM_BUNDLED_WASM: &str = "TFX-NPM-BINARY-001";
+/// Bundled prebuilt native binary
+pub const NPM_PREBUILT_BINARY: &str = "TFX-NPM-BINARY-002";
+/// Install script that downloads a binary at install time
+pub const NPM_INSTALL_FETCHES_BINARY: &str = "TFX-NPM-INSTALL-001";
+/// Any install script, reported only when `flag_install_scripts` is set
+pub const NPM_INSTALL_SCRIPT: &str = "TFX-NPM-INSTALL-002";
+/// npm tarball with manifests or entries outside its `package/` directory
+pub const NPM_ARCHIVE_LAYOUT: &str = "TFX-NPM-ARCHIVE-001";
+/// Declared npm dependency missing from the lockfile or locked outside its range
+pub const NPM_LOCKFILE_UNMATCHED: &str = "TFX-NPM-LOCK-001";
+/// Terraform module fetched from outside the registry
+pub const TERRAFORM_NON_REGISTRY_SOURCE: &str = "TFX-TF-SOURCE-001";
+/// Terraform module without a dependency lock file
+pub const TERRAFORM_MISSING_LOCK_FILE: &str = "TFX-TF-LOCK-001";
+/// Terraform provider missing from the lock file
+pub const TERRAFORM_PROVIDER_NOT_LOCKED: &str = "TFX-TF-LOCK-002";
+/// Terraform provider locked without checksums
+pub const TERRAFORM_PROVIDER_NO_CHECKSUMS: &str = "TFX-TF-LOCK-003";
+/// Installed Terraform provider that does not match its lock file hashes
+pub const TERRAFORM_PROVIDER_CHECKSUM_MISMATCH: &str = "TFX-TF-INTEGRITY-001";
+/// Helm chart dependency from a repository alias or unrecognised source
+pub const HELM_UNTRUSTED_REPOSITORY: &str = "TFX-HELM-REPO-001";
+/// Helm chart with remote dependencies and no lock file
+pub const HELM_MISSING_LOCK_FILE: &str = "TFX-HELM-LOCK-001";
+/// Helm chart dependency missing from the lock file
+pub const HELM_DEPENDENCY_NOT_LOCKED: &str = "TFX-HELM-LOCK-002";
+/// Helm install hook that downloads and runs code
+pub const HELM_HOOK_REMOTE_EXEC: &str = "TFX-HELM-HOOK-001";
+/// Container image referenced by a mutable tag, reported only when `flag_mutable_images` is set
+pub const HELM_MUTABLE_IMAGE: &str = "TFX-HELM-IMAGE-001";
+/// Go module without a go.sum file
+pub const GO_MISSING_SUM_FILE: &str = "TFX-GO-SUM-001";
+/// Required Go module with no hash in go.sum
+pub const GO_MODULE_NOT_IN_SUM: &str = "TFX-GO-SUM-002";
+/// Composer package installed from version control or a path, not a registry release
+pub const COMPOSER_NON_REGISTRY_SOURCE: &str = "TFX-COMPOSER-SOURCE-001";
+/// Python requirement installed editable or from a URL or path, not an index
+pub const PYTHON_NON_INDEX_SOURCE: &str = "TFX-PY-SOURCE-001";
+/// Repository link that is malformed or did not respond
+pub const REPOSITORY_UNREACHABLE: &str = "TFX-REPO-LINK-001";
+/// Repository link to a repository that does not exist
+pub const REPOSITORY_NOT_FOUND: &str = "TFX-REPO-LINK-002";
+/// Repository link to a project unrelated to the package
+pub const REPOSITORY_UNRELATED: &str = "TFX-REPO-LINK-003";
+/// Provenance attestation that does not match the package
+pub const INVALID_ATTESTATION: &str = "TFX-PROVENANCE-ATTESTATION-001";
+/// Manifest nested deep enough to overflow recursive parsers
+pub const RESOURCE_NESTING_DEPTH: &str = "TFX-DOS-NESTING-001";
+/// Manifest declaring an excessive number of dependencies
+pub const RESOURCE_DEPENDENCY_COUNT: &str = "TFX-DOS-DEPENDENCIES-001";
+/// Manifest that expands to an excessive number of tokens
+pub const RESOURCE_TOKEN_COUNT: &str = "TFX-DOS-TOKENS-001";
+/// Package name that looks machine-generated
+pub const GENERATED_NAME: &str = "TFX-NAME-GENERATED-001";
+/// Extension with access to every website
+pub const EXTENSION_ALL_HOSTS: &str = "TFX-EXT-HOSTS-001";
+/// Content script injected into every website
+pub const EXTENSION_CONTENT_SCRIPT_ALL_HOSTS: &str = "TFX-EXT-HOSTS-002";
+/// Extension requesting sensitive permissions
+pub const EXTENSION_SENSITIVE_PERMISSIONS: &str = "TFX-EXT-PERMISSION-001";
+/// Extension activating in every workspace
+pub const EXTENSION_EAGER_ACTIVATION: &str = "TFX-EXT-ACTIVATION-001";
+/// Content script loaded from a remote URL
+pub const EXTENSION_REMOTE_CONTENT_SCRIPT: &str = "TFX-EXT-REMOTE-001";
+/// Content security policy permitting remote or evaluated code
+pub const EXTENSION_PERMISSIVE_CSP: &str = "TFX-EXT-REMOTE-002";
+/// Extension source executing code fetched at runtime
+pub const EXTENSION_RUNTIME_REMOTE_CODE: &str = "TFX-EXT-REMOTE-003";
+
+/// Rule ID for a malicious pattern, derived from its pattern ID
+///
+/// `EXEC_001` becomes `TFX-PATTERN-EXEC-001`; custom patterns get IDs in the
+/// same namespace as long as their pattern IDs are unique.
+pub fn pattern_rule_id(pattern_id: &str) -> String {
+    format!(
+        "TFX-PATTERN-{}",
+        pattern_id.to_ascii_uppercase().replace('_', "-")
+    )
+}
+
+/// Description of a built-in rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleDescriptor {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub finding_type: FindingType,
+}
+
+impl RuleDescriptor {
+    fn new(id: &str, name: &str, description: &str, finding_type: FindingType) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            finding_type,
+        }
+    }
+}
+
+/// Registry mapping rule IDs to their descriptors
+pub struct RuleRegistry {
+    rules: Vec<RuleDescriptor>,
+}
+
+impl RuleRegistry {
+    /// Registry of every built-in rule, including the default malicious patterns
+    pub fn builtin() -> Self {
+        let mut rules = vec![
+            RuleDescriptor::new(
+                VULNERABLE_DEPENDENCY,
+                "Vulnerable dependency",
+                "A dependency version is affected by a known vulnerability",
+                FindingType::Vulnerability,
+            ),
+            RuleDescriptor::new(
+                UNPINNED_DEPENDENCY,
+                "Unpinned dependency",
+                "A direct dependency range admits arbitrary future releases",
+                FindingType::UnpinnedDependency,
+            ),
+            RuleDescriptor::new(
+                DEPENDENCY_CYCLE,
+                "Dependency cycle",
+                "Packages depend on each other in a cycle",
+                FindingType::DependencyCycle,
+            ),
+            RuleDescriptor::new(
+                DEPENDENCY_ALIAS,
+                "Dependency alias",
+                "A dependency is declared under one name but installs a different package",
+                FindingType::DependencyAlias,
+            ),
+            RuleDescriptor::new(
+                RANGE_ALLOWS_VULNERABLE,
+                "Range allows vulnerable version",
+                "A dependency's declared range admits a known-vulnerable version, though the resolved version is not affected",
+                FindingType::RangeAllowsVulnerable,
+            ),
+            RuleDescriptor::new(
+                INSECURE_TRANSPORT,
+                "Insecure dependency transport",
+                "A dependency is downloaded over plain HTTP or another unencrypted protocol",
+                FindingType::InsecureTransport,
+            ),
+            RuleDescriptor::new(
+                SOURCE_POLICY_VIOLATION,
+                "Source policy violation",
+                "A dependency is fetched from a host the configured source policy does not allow",
+                FindingType::PolicyViolation,
+            ),
+            RuleDescriptor::new(
+                DEPENDENCY_CONFUSION,
+                "Dependency confusion",
+                "A dependency named as an internal package is resolved from, or published on, a public registry",
+                FindingType::DependencyConfusion,
+            ),
+            RuleDescriptor::new(
+                SANDBOX_EVASION,
+                "Environment fingerprinting",
+                "Code probes its environment, possibly to behave benignly under analysis",
+                FindingType::SandboxEvasion,
+            ),
+            RuleDescriptor::new(
+                DUPLICATE_DECLARATION,
+                "Duplicate dependency declaration",
+                "A package is declared in more than one dependency section with different constraints, leaving which one applies ambiguous",
+                FindingType::DuplicateDeclaration,
+            ),
+            RuleDescriptor::new(
+                PARTIAL_PARSE,
+                "Partially parsed package",
+                "A manifest or lockfile could not be parsed and was skipped, so the analysis may be incomplete",
+                FindingType::PartialParse,
+            ),
+            RuleDescriptor::new(
+                DELAYED_EXECUTION,
+                "Delayed execution",
+                "Code waits or schedules work for later, possibly to act after a sandbox stops watching",
+                FindingType::DelayedExecution,
+            ),
+            RuleDescriptor::new(
+                REGISTRY_EXFILTRATION,
+                "Registry exfiltration",
+                "Code publishes a package or sends authenticated data to a registry, a channel for carrying stolen data out",
+                FindingType::RegistryExfiltration,
+            ),
+            RuleDescriptor::new(
+                IMPORT_HIJACK,
+                "Import hijacking",
+                "Code patches the module loader or installs an import hook, running inside every later require or import",
+                FindingType::ImportHijack,
+            ),
+            RuleDescriptor::new(
+                METADATA_INJECTION,
+                "Metadata injection",
+                "Package metadata contains control characters, terminal escapes or invisible and bidirectional text",
+                FindingType::MetadataInjection,
+            ),
+            RuleDescriptor::new(
+                PLATFORM_TARGETED_PAYLOAD,
+                "Platform-targeted payload",
+                "Install code branches on the operating system or architecture before network access, execution or file writes",
+                FindingType::PlatformTargetedPayload,
+            ),
+            RuleDescriptor::new(
+                CROSS_LANGUAGE_EXECUTION,
+                "Cross-language execution",
+                "Code runs another language's interpreter, moving its payload out of reach of the ecosystem's scanner",
+                FindingType::CrossLanguageExecution,
+            ),
+            RuleDescriptor::new(
+                PURPOSE_MISMATCH,
+                "Purpose mismatch",
+                "A package declared as type definitions or a simple utility performs network, process or install-time operations",
+                FindingType::PurposeMismatch,
+            ),
+            RuleDescriptor::new(
+                ENCRYPTED_ARCHIVE,
+                "Encrypted archive entries",
+                "Archive entries are password-protected and cannot be scanned",
+                FindingType::EncryptedArchive,
+            ),
+            RuleDescriptor::new(
+                NPM_BUNDLED_WASM,
+                "Bundled WebAssembly module",
+                "Package ships a WebAssembly module that source scanning cannot inspect",
+                FindingType::BundledWasm,
+            ),
+            RuleDescriptor::new(
+                NPM_PREBUILT_BINARY,
+                "Bundled prebuilt binary",
+                "Package ships a native binary that source scanning cannot inspect",
+                FindingType::PrebuiltBinary,
+            ),
+            RuleDescriptor::new(
+                NPM_INSTALL_FETCHES_BINARY,
+                "Install script fetches binary",
+                "An install script downloads a binary that is not part of the published package",
+                FindingType::PrebuiltBinary,
+            ),
+            RuleDescriptor::new(
+                NPM_INSTALL_SCRIPT,
+                "Install script",
+                "A lifecycle script runs arbitrary code at install time",
+                FindingType::SuspiciousActivity,
+            ),
+            RuleDescriptor::new(
+                NPM_ARCHIVE_LAYOUT,
+                "Unexpected tarball layout",
+                "An npm tarball has manifests or entries outside the package/ directory npm installs from",
+                FindingType::SuspiciousArchiveLayout,
+            ),
+            RuleDescriptor::new(
+                NPM_LOCKFILE_UNMATCHED,
+                "Dependency not matched by lockfile",
+                "A declared dependency has no lockfile entry, or is locked outside its declared range",
+                FindingType::UnpinnedDependency,
+            ),
+            RuleDescriptor::new(
+                TERRAFORM_NON_REGISTRY_SOURCE,
+                "Non-registry module source",
+                "A Terraform module is fetched from outside the registry",
+                FindingType::NonRegistrySource,
+            ),
+            RuleDescriptor::new(
+                TERRAFORM_MISSING_LOCK_FILE,
+                "Missing dependency lock file",
+                "Provider versions and checksums are not pinned by a lock file",
+                FindingType::MissingIntegrity,
+            ),
+            RuleDescriptor::new(
+                TERRAFORM_PROVIDER_NOT_LOCKED,
+                "Provider not locked",
+                "A required provider has no entry in the lock file",
+                FindingType::MissingIntegrity,
+            ),
+            RuleDescriptor::new(
+                TERRAFORM_PROVIDER_NO_CHECKSUMS,
+                "Provider without checksums",
+                "A locked provider lists no hashes to verify its binary against",
+                FindingType::MissingIntegrity,
+            ),
+            RuleDescriptor::new(
+                TERRAFORM_PROVIDER_CHECKSUM_MISMATCH,
+                "Provider checksum mismatch",
+                "An installed provider does not match any hash in the lock file",
+                FindingType::IntegrityMismatch,
+            ),
+            RuleDescriptor::new(
+                HELM_UNTRUSTED_REPOSITORY,
+                "Untrusted chart repository",
+                "A chart dependency resolves through a local repository alias or an unrecognised source",
+                FindingType::NonRegistrySource,
+            ),
+            RuleDescriptor::new(
+                HELM_MISSING_LOCK_FILE,
+                "Missing chart lock file",
+                "Chart dependency versions and digests are not pinned by a lock file",
+                FindingType::MissingIntegrity,
+            ),
+            RuleDescriptor::new(
+                HELM_DEPENDENCY_NOT_LOCKED,
+                "Chart dependency not locked",
+                "A chart dependency has no entry in the lock file",
+                FindingType::MissingIntegrity,
+            ),
+            RuleDescriptor::new(
+                HELM_HOOK_REMOTE_EXEC,
+                "Hook runs downloaded code",
+                "An install or upgrade hook downloads a script and runs it in the cluster",
+                FindingType::SuspiciousActivity,
+            ),
+            RuleDescriptor::new(
+                HELM_MUTABLE_IMAGE,
+                "Mutable image tag",
+                "A container image is referenced by a tag that can move to different content",
+                FindingType::UnpinnedDependency,
+            ),
+            RuleDescriptor::new(
+                GO_MISSING_SUM_FILE,
+                "Missing go.sum",
+                "A Go module has requirements but no go.sum pinning their content",
+                FindingType::MissingIntegrity,
+            ),
+            RuleDescriptor::new(
+                GO_MODULE_NOT_IN_SUM,
+                "Module not in go.sum",
+                "A required Go module has no hash recorded in go.sum",
+                FindingType::MissingIntegrity,
+            ),
+            RuleDescriptor::new(
+                COMPOSER_NON_REGISTRY_SOURCE,
+                "Non-registry Composer package",
+                "A Composer package is installed from version control or a local path instead of a registry release",
+                FindingType::NonRegistrySource,
+            ),
+            RuleDescriptor::new(
+                PYTHON_NON_INDEX_SOURCE,
+                "Non-index Python requirement",
+                "A Python requirement is an editable install or comes from a URL or local path instead of a package index",
+                FindingType::NonRegistrySource,
+            ),
+            RuleDescriptor::new(
+                REPOSITORY_UNREACHABLE,
+                "Unreachable repository link",
+                "The declared repository link is malformed or did not respond",
+                FindingType::SuspiciousRepository,
+            ),
+            RuleDescriptor::new(
+                REPOSITORY_NOT_FOUND,
+                "Missing repository",
+                "The declared repository does not exist",
+                FindingType::SuspiciousRepository,
+            ),
+            RuleDescriptor::new(
+                REPOSITORY_UNRELATED,
+                "Unrelated repository",
+                "The declared repository belongs to a project unrelated to the package",
+                FindingType::SuspiciousRepository,
+            ),
+            RuleDescriptor::new(
+                INVALID_ATTESTATION,
+                "Invalid provenance attestation",
+                "The package claims provenance that does not match the published artifact or its source",
+                FindingType::InvalidAttestation,
+            ),
+            RuleDescriptor::new(
+                RESOURCE_NESTING_DEPTH,
+                "Excessive nesting",
+                "A manifest is nested deeper than any legitimate package needs",
+                FindingType::ResourceExhaustionAttempt,
+            ),
+            RuleDescriptor::new(
+                RESOURCE_DEPENDENCY_COUNT,
+                "Excessive dependency count",
+                "A manifest declares enough dependencies to stall analysis",
+                FindingType::ResourceExhaustionAttempt,
+            ),
+            RuleDescriptor::new(
+                RESOURCE_TOKEN_COUNT,
+                "Excessive manifest size",
+                "A manifest expands to more content than any legitimate package needs",
+                FindingType::ResourceExhaustionAttempt,
+            ),
+            RuleDescriptor::new(
+                GENERATED_NAME,
+                "Machine-generated package name",
+                "The package name looks random, as is typical of malware published in bulk",
+                FindingType::SuspiciousName,
+            ),
+            RuleDescriptor::new(
+                EXTENSION_ALL_HOSTS,
+                "Access to every website",
+                "An extension can read and modify every page the user visits",
+                FindingType::OverbroadCapability,
+            ),
+            RuleDescriptor::new(
+                EXTENSION_CONTENT_SCRIPT_ALL_HOSTS,
+                "Content script on every website",
+                "A content script is injected into every page the user visits",
+                FindingType::OverbroadCapability,
+            ),
+            RuleDescriptor::new(
+                EXTENSION_SENSITIVE_PERMISSIONS,
+                "Sensitive permissions",
+                "An extension requests permissions that expose user data or browser control",
+                FindingType::OverbroadCapability,
+            ),
+            RuleDescriptor::new(
+                EXTENSION_EAGER_ACTIVATION,
+                "Eager activation",
+                "An extension runs on startup regardless of the workspace",
+                FindingType::OverbroadCapability,
+            ),
+            RuleDescriptor::new(
+                EXTENSION_REMOTE_CONTENT_SCRIPT,
+                "Remote content script",
+                "A content script is loaded from a remote URL",
+                FindingType::RuntimeRemoteCode,
+            ),
+            RuleDescriptor::new(
+                EXTENSION_PERMISSIVE_CSP,
+                "Permissive content security policy",
+                "The content security policy allows remote or evaluated code",
+                FindingType::RuntimeRemoteCode,
+            ),
+            RuleDescriptor::new(
+                EXTENSION_RUNTIME_REMOTE_CODE,
+                "Runtime remote code",
+                "Extension source executes code fetched after installation",
+                FindingType::RuntimeRemoteCode,
+            ),
+        ];
+
+        rules.extend(
+            PatternMatcher::default_patterns()
+                .into_iter()
+                .map(|pattern| RuleDescriptor {
+                    id: pattern_rule_id(&pattern.pattern_id),
+                    name: pattern.pattern_name,
+                    description: pattern.description,
+                    finding_type: FindingType::MaliciousPattern,
+                }),
+        );
+
+        Self { rules }
+    }
+
+    /// Look up a rule by ID
+    pub fn get(&self, id: &str) -> Option<&RuleDescriptor> {
+        self.rules.iter().find(|rule| rule.id == id)
+    }
+
+    /// All registered rules in registration order
+    pub fn rules(&self) -> &[RuleDescriptor] {
+        &self.rules
+    }
+}
+
+impl Default for RuleRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_builtin_rule_ids_are_unique_and_non_empty() {
+        let registry = RuleRegistry::builtin();
+        let mut seen = HashSet::new();
+        for rule in registry.rules() {
+            assert!(!rule.id.is_empty());
+            assert!(rule.id.starts_with("TFX-"), "{}", rule.id);
+            assert!(!rule.name.is_empty(), "{}", rule.id);
+            assert!(
+                seen.insert(rule.id.clone()),
+                "duplicate rule id {}",
+                rule.id
+            );
+        }
+    }
+
+    #[test]
+    fn test_pattern_rule_id() {
+        assert_eq!(pattern_rule_id("EXEC_001"), "TFX-PATTERN-EXEC-001");
+        let registry = RuleRegistry::builtin();
+        assert_eq!(
+            registry.get("TFX-PATTERN-EVAS-002").unwrap().finding_type,
+            FindingType::MaliciousPattern
+        );
+    }
+}
@@ -0,0 +1,274 @@
+//! Organization policy on which hosts dependencies may come from
+//!
+//! Unlike the transport and alias heuristics, this enforces an explicit rule:
+//! an organization that mirrors packages internally can require every
+//! dependency to come from the mirror and forbid the public registry. Only
+//! sources that name a host are checked: URLs in manifests, lockfile
+//! `resolved` URLs, git shorthands and index options. A bare version range
+//! resolves from whatever registry the package manager is configured with,
+//! which is not visible here.
+
+use serde::{Deserialize, Serialize};
+
+use super::{rules, Finding, FindingType, RiskLevel, ScanReason};
+
+/// Git shorthand prefixes npm expands, with the host they fetch from
+const GIT_SHORTHANDS: &[(&str, &str)] = &[
+    ("github:", "github.com"),
+    ("gist:", "gist.github.com"),
+    ("gitlab:", "gitlab.com"),
+    ("bitbucket:", "bitbucket.org"),
+];
+
+/// Hosts dependencies may or may not be fetched from
+///
+/// Entries are host names, matching that host exactly, or `*.domain`,
+/// matching any subdomain of `domain`. Comparison ignores case and ports.
+///
+/// When both lists match a host, the more specific entry wins: an exact host
+/// over a wildcard, and a longer domain over a shorter one, so
+/// `git.corp.example` can be allowed while `*.corp.example` is denied. On a
+/// tie the denylist wins. A host neither list matches is a violation only
+/// when the allowlist is non-empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourcePolicy {
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+    #[serde(default)]
+    pub denied_hosts: Vec<String>,
+}
+
+/// A dependency fetched from a host the policy does not allow
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceViolation {
+    pub dependency: String,
+    pub url: String,
+    pub host: String,
+    /// Denylist entry that matched, `None` when the host is just not allowed
+    pub denied_by: Option<String>,
+    /// Manifest or lockfile declaring the source
+    pub declared_in: String,
+}
+
+impl SourcePolicy {
+    /// Allow a host or `*.domain`
+    pub fn allow(mut self, host: impl Into<String>) -> Self {
+        self.allowed_hosts.push(host.into());
+        self
+    }
+
+    /// Deny a host or `*.domain`
+    pub fn deny(mut self, host: impl Into<String>) -> Self {
+        self.denied_hosts.push(host.into());
+        self
+    }
+
+    /// Whether the policy restricts anything
+    pub fn is_empty(&self) -> bool {
+        self.allowed_hosts.is_empty() && self.denied_hosts.is_empty()
+    }
+
+    /// Check a dependency source, returning the violation if its host is not allowed
+    pub fn check(
+        &self,
+        dependency: &str,
+        source: &str,
+        declared_in: &str,
+    ) -> Option<SourceViolation> {
+        if self.is_empty() {
+            return None;
+        }
+        let host = source_host(source)?;
+        let allowed = most_specific(&self.allowed_hosts, &host);
+        let denied = most_specific(&self.denied_hosts, &host);
+        let denied_by = match (allowed, denied) {
+            (Some((allowed, _)), Some((denied, _))) if allowed > denied => return None,
+            (_, Some((_, entry))) => Some(entry.to_string()),
+            (Some(_), None) => return None,
+            (None, None) if self.allowed_hosts.is_empty() => return None,
+            (None, None) => None,
+        };
+
+        Some(SourceViolation {
+            dependency: dependency.to_string(),
+            url: source.trim().to_string(),
+            host,
+            denied_by,
+            declared_in: declared_in.to_string(),
+        })
+    }
+
+    /// Findings for every `(dependency, source)` the policy rejects, in order
+    pub fn findings<'a>(
+        &self,
+        sources: impl IntoIterator<Item = (&'a str, &'a str)>,
+        declared_in: &str,
+    ) -> Vec<Finding> {
+        sources
+            .into_iter()
+            .filter_map(|(dependency, source)| self.check(dependency, source, declared_in))
+            .map(|violation| violation.to_finding())
+            .collect()
+    }
+}
+
+impl SourceViolation {
+    /// Report as a `PolicyViolation` finding
+    pub fn to_finding(&self) -> Finding {
+        let reason = match &self.denied_by {
+            Some(entry) => format!("Host '{}' is denied by '{}'", self.host, entry),
+            None => format!("Host '{}' is not an allowed source", self.host),
+        };
+        Finding::new(
+            FindingType::PolicyViolation,
+            RiskLevel::High,
+            format!(
+                "Dependency '{}' comes from a source the policy does not allow",
+                self.dependency
+            ),
+            "The source policy restricts which hosts dependencies may be fetched from; this one bypasses the approved sources",
+        )
+        .with_evidence(format!("{}: {}", self.declared_in, self.url))
+        .with_evidence(reason)
+        .with_affected_component(self.dependency.clone())
+        .with_scan_reason(ScanReason::DependencyAnalysis)
+        .with_rule_id(rules::SOURCE_POLICY_VIOLATION)
+    }
+}
+
+/// Specificity and entry of the list entry matching `host` most specifically
+///
+/// An exact entry outranks a wildcard for the same domain.
+fn most_specific<'a>(entries: &'a [String], host: &str) -> Option<(usize, &'a str)> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let pattern = entry.trim().to_ascii_lowercase();
+            let specificity = match pattern.strip_prefix("*.") {
+                Some(domain) => host
+                    .strip_suffix(domain)
+                    .is_some_and(|sub| sub.ends_with('.'))
+                    .then_some(domain.len() * 2),
+                None => (pattern == host).then_some(pattern.len() * 2 + 1),
+            }?;
+            Some((specificity, entry.as_str()))
+        })
+        .max_by_key(|(specificity, _)| *specificity)
+}
+
+/// Lowercased host a dependency source is fetched from, if it names one
+///
+/// Understands URLs, scp-style git remotes (`git@host:owner/repo`) and npm git
+/// shorthands (`github:owner/repo`, `owner/repo`). IPv6 hosts keep their
+/// brackets; ports and credentials are dropped.
+pub fn source_host(source: &str) -> Option<String> {
+    let source = source.trim().to_ascii_lowercase();
+    if let Some((_, rest)) = source.split_once("://") {
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let host = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        let host = if host.starts_with('[') {
+            host.split_inclusive(']').next().unwrap_or(host)
+        } else {
+            host.split(':').next().unwrap_or(host)
+        };
+        return (!host.is_empty()).then(|| host.to_string());
+    }
+    if let Some((_, host)) = GIT_SHORTHANDS
+        .iter()
+        .find(|(prefix, _)| source.starts_with(prefix))
+    {
+        return Some(host.to_string());
+    }
+    // scp-style remote: the user and host come before the first `:`
+    if let Some((user_host, _)) = source.split_once(':') {
+        return user_host
+            .split_once('@')
+            .map(|(_, host)| host)
+            .filter(|host| !host.is_empty() && !host.contains('/'))
+            .map(str::to_string);
+    }
+    // `owner/repo` is npm's shorthand for a GitHub repository
+    let repo = source.split('#').next().unwrap_or_default();
+    let is_github_shorthand = repo.split_once('/').is_some_and(|(owner, name)| {
+        !owner.is_empty()
+            && !name.is_empty()
+            && !name.contains('/')
+            && !owner.starts_with(['.', '~', '@'])
+            && repo
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "/._-".contains(c))
+    });
+    is_github_shorthand.then(|| "github.com".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_hosts() {
+        let cases = [
+            (
+                "https://registry.npmjs.org/a/-/a-1.0.0.tgz",
+                Some("registry.npmjs.org"),
+            ),
+            (
+                "git+ssh://git@Git.Corp.Example:2222/a.git",
+                Some("git.corp.example"),
+            ),
+            ("http://[::1]:8080/a.tgz", Some("[::1]")),
+            ("git@github.com:owner/a.git", Some("github.com")),
+            ("gitlab:owner/a", Some("gitlab.com")),
+            ("owner/a#v1.0.0", Some("github.com")),
+            ("^1.2.3", None),
+            ("npm:other@1.0.0", None),
+            ("file:../a", None),
+            ("./vendor/a", None),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(source_host(source).as_deref(), expected, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_precedence() {
+        let policy = SourcePolicy::default()
+            .allow("npm.corp.example")
+            .allow("git.corp.example")
+            .deny("*.corp.example")
+            .deny("registry.npmjs.org");
+
+        // Exact allow beats the wildcard deny
+        assert!(policy
+            .check("a", "https://npm.corp.example/a.tgz", "package-lock.json")
+            .is_none());
+        let denied = policy
+            .check("a", "https://ci.corp.example/a.tgz", "package-lock.json")
+            .unwrap();
+        assert_eq!(denied.denied_by.as_deref(), Some("*.corp.example"));
+        let denied = policy
+            .check("a", "https://registry.npmjs.org/a.tgz", "package-lock.json")
+            .unwrap();
+        assert_eq!(denied.denied_by.as_deref(), Some("registry.npmjs.org"));
+
+        // With an allowlist, unlisted hosts are violations too
+        let unlisted = policy.check("a", "github:owner/a", "package.json").unwrap();
+        assert_eq!(unlisted.host, "github.com");
+        assert_eq!(unlisted.denied_by, None);
+
+        // A denylist alone leaves other hosts alone; a tie goes to the denylist
+        let deny_only = SourcePolicy::default().deny("github.com");
+        assert!(deny_only
+            .check("a", "https://gitlab.com/a.git", "package.json")
+            .is_none());
+        let tie = SourcePolicy::default()
+            .allow("github.com")
+            .deny("github.com");
+        assert!(tie.check("a", "owner/a", "package.json").is_some());
+        assert!(SourcePolicy::default()
+            .check("a", "https://anything.example/a.tgz", "package.json")
+            .is_none());
+    }
+}
@@ -0,0 +1,113 @@
+//! Dependency sources fetched without transport security
+//!
+//! A dependency resolved from a plain `http://` or `git://` URL can be
+//! swapped in transit, and a lockfile integrity hash recorded from such a
+//! download only proves what the attacker served. This looks at the source
+//! URLs of individual dependencies in manifests and lockfiles; it needs no
+//! network access.
+
+use serde::{Deserialize, Serialize};
+
+use super::source_policy::source_host;
+use super::{rules, Finding, FindingType, RiskLevel, ScanReason};
+
+/// URL schemes that carry no transport security
+const INSECURE_SCHEMES: &[&str] = &["http://", "git://", "git+http://", "ftp://"];
+
+/// Hosts on the local machine, where there is no network to intercept
+const LOOPBACK_HOSTS: &[&str] = &["localhost", "127.0.0.1", "[::1]"];
+
+/// A dependency whose source is fetched without TLS
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InsecureSource {
+    pub dependency: String,
+    pub url: String,
+    /// Manifest or lockfile declaring the source
+    pub declared_in: String,
+}
+
+impl InsecureSource {
+    /// Check a dependency source, returning it if its transport is insecure
+    pub fn check(dependency: &str, source: &str, declared_in: &str) -> Option<Self> {
+        let source = source.trim();
+        let lower = source.to_ascii_lowercase();
+        INSECURE_SCHEMES.iter().find(|s| lower.starts_with(*s))?;
+        let host = source_host(source).unwrap_or_default();
+        if LOOPBACK_HOSTS.contains(&host.as_str()) {
+            return None;
+        }
+
+        Some(Self {
+            dependency: dependency.to_string(),
+            url: source.to_string(),
+            declared_in: declared_in.to_string(),
+        })
+    }
+
+    /// Report as an `InsecureTransport` finding
+    pub fn to_finding(&self) -> Finding {
+        Finding::new(
+            FindingType::InsecureTransport,
+            RiskLevel::High,
+            format!("Dependency '{}' is fetched over insecure transport", self.dependency),
+            "The source is downloaded without TLS, so anyone on the network path can replace it and any recorded integrity hash only covers what they served",
+        )
+        .with_evidence(format!("{}: {}", self.declared_in, self.url))
+        .with_affected_component(self.dependency.clone())
+        .with_scan_reason(ScanReason::DependencyAnalysis)
+        .with_rule_id(rules::INSECURE_TRANSPORT)
+    }
+}
+
+/// Findings for every `(dependency, source)` fetched without TLS, in order
+pub fn insecure_transport_findings<'a>(
+    sources: impl IntoIterator<Item = (&'a str, &'a str)>,
+    declared_in: &str,
+) -> Vec<Finding> {
+    sources
+        .into_iter()
+        .filter_map(|(dependency, source)| InsecureSource::check(dependency, source, declared_in))
+        .map(|source| source.to_finding())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insecure_schemes() {
+        for url in [
+            "http://registry.example.com/pkg/-/pkg-1.0.0.tgz",
+            "git://github.com/owner/pkg.git",
+            "git+http://user@git.example.com/pkg.git#v1",
+            "HTTP://EXAMPLE.COM/pkg.tgz",
+        ] {
+            assert!(
+                InsecureSource::check("pkg", url, "package-lock.json").is_some(),
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn test_secure_and_local_sources() {
+        for source in [
+            "https://registry.npmjs.org/pkg/-/pkg-1.0.0.tgz",
+            "git+https://github.com/owner/pkg.git",
+            "git+ssh://git@github.com/owner/pkg.git",
+            "^1.2.3",
+            "file:../pkg",
+            "http://localhost:4873/pkg/-/pkg-1.0.0.tgz",
+            "http://127.0.0.1/pkg.tgz",
+            "http://[::1]:8080/pkg.tgz",
+        ] {
+            assert!(
+                InsecureSource::check("pkg", source, "package.json").is_none(),
+                "{}",
+                source
+            );
+        }
+    }
+}
@@ -7,6 +7,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt;
 
+use crate::versioning::{common_version, evaluates, PrereleasePolicy, VersionScheme};
+
 /// Vulnerability information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Vulnerability {
@@ -24,12 +26,43 @@ pub struct Vulnerability {
     pub cwe_ids: Vec<String>,
     pub exploit_available: bool,
     pub patch_available: bool,
+    /// Severity as the advisory source rated it, before normalization
+    #[serde(default)]
+    pub source_severity: Option<SourceSeverity>,
+    /// Functions or classes the flaw is in, when the advisory names them
+    ///
+    /// Qualified as `module.symbol` when the module differs from the package name.
+    #[serde(default)]
+    pub affected_symbols: Vec<String>,
+    /// Whether the scanned package appears to use `affected_symbols`
+    ///
+    /// Set only by reachability analysis (see [`super::reachability`]), and
+    /// `None` without symbol data. A heuristic, not a guarantee.
+    #[serde(default)]
+    pub likely_reachable: Option<bool>,
+    /// Other IDs of the same advisory, such as the CVE of a GHSA advisory
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+/// A severity rating as the advisory source expressed it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceSeverity {
+    /// Source name, such as `ghsa`, `osv` or `nvd`
+    pub source: String,
+    /// The rating verbatim: a label, a CVSS score or a CVSS vector
+    pub value: String,
 }
 
 impl Vulnerability {
-    /// Get CVE ID (convenience method)
+    /// Get CVE ID, from the aliases when the advisory has its own ID
+    ///
+    /// Falls back to the advisory ID when no CVE is known.
     pub fn cve_id(&self) -> &str {
-        &self.id
+        std::iter::once(&self.id)
+            .chain(&self.aliases)
+            .find(|id| id.starts_with("CVE-"))
+            .unwrap_or(&self.id)
     }
 
     /// Get advisory ID (alias for id)
@@ -37,6 +70,14 @@ impl Vulnerability {
         &self.id
     }
 
+    /// Whether the advisory is for malicious code rather than a flaw
+    ///
+    /// OSV malicious-package advisories use `MAL-` IDs; others are tagged with
+    /// CWE-506, embedded malicious code.
+    pub fn is_malicious_code(&self) -> bool {
+        self.id.starts_with("MAL-") || self.cwe_ids.iter().any(|cwe| cwe == "CWE-506")
+    }
+
     /// Get severity score as f32
     pub fn severity_score(&self) -> f32 {
         self.cvss_score.unwrap_or(match self.severity {
@@ -47,6 +88,88 @@ impl Vulnerability {
             VulnerabilitySeverity::None => 0.0,
         })
     }
+
+    /// Check whether a version falls in any affected range
+    ///
+    /// A declared range, such as `^4.17.21` or `>=2.31.0`, is affected when it
+    /// admits an affected release. Anything the scheme can neither parse nor
+    /// evaluate as a range (`latest`, a URL) cannot be ruled out and is
+    /// treated as affected, as are advisory ranges the scheme fails to
+    /// evaluate. Pre-releases are matched by precedence alone.
+    pub fn affects_version(&self, version: &str, scheme: &dyn VersionScheme) -> bool {
+        self.affects_version_with(version, scheme, PrereleasePolicy::Include)
+    }
+
+    /// Check whether a version falls in any affected range under a pre-release policy
+    pub fn affects_version_with(
+        &self,
+        version: &str,
+        scheme: &dyn VersionScheme,
+        policy: PrereleasePolicy,
+    ) -> bool {
+        if self.affected_versions.is_empty() {
+            return true;
+        }
+        if scheme.parse(version).is_err() {
+            return !evaluates(scheme, version)
+                || self
+                    .affected_version_in_range(version, scheme, policy)
+                    .is_some();
+        }
+        self.affected_versions
+            .iter()
+            .any(|range| policy.satisfies(scheme, version, range).unwrap_or(true))
+    }
+
+    /// Lowest release permitted by `range` that falls in an affected range
+    ///
+    /// Unlike [`affects_version_with`](Self::affects_version_with), nothing
+    /// unknown counts as affected: an advisory without affected ranges, or a
+    /// range the scheme cannot evaluate, gives `None`.
+    pub fn affected_version_in_range(
+        &self,
+        range: &str,
+        scheme: &dyn VersionScheme,
+        policy: PrereleasePolicy,
+    ) -> Option<String> {
+        common_version(scheme, policy, range, &self.affected_versions)
+    }
+
+    /// Lowest fixed version newer than `current`
+    ///
+    /// Stable fixes are preferred; a pre-release fix is only offered when the
+    /// current version is itself a pre-release or no stable fix exists.
+    pub fn lowest_fixed_version(
+        &self,
+        current: &str,
+        scheme: &dyn VersionScheme,
+    ) -> Option<String> {
+        self.lowest_fixed_version_with(current, scheme, false)
+            .or_else(|| self.lowest_fixed_version_with(current, scheme, true))
+    }
+
+    /// Lowest fixed version newer than `current`, optionally including pre-release fixes
+    pub fn lowest_fixed_version_with(
+        &self,
+        current: &str,
+        scheme: &dyn VersionScheme,
+        allow_prereleases: bool,
+    ) -> Option<String> {
+        let allow_prereleases =
+            PrereleasePolicy::allows_prerelease_fix(scheme, current, allow_prereleases);
+        let newer = |fixed: &&String| match scheme.compare(fixed, current) {
+            Ok(ord) => ord == std::cmp::Ordering::Greater,
+            Err(_) => scheme.parse(current).is_err(),
+        };
+        self.fixed_versions
+            .iter()
+            .filter(newer)
+            .filter(|fixed| {
+                allow_prereleases || !scheme.parse(fixed).is_ok_and(|v| v.is_prerelease)
+            })
+            .min_by(|a, b| scheme.compare(a, b).unwrap_or(std::cmp::Ordering::Equal))
+            .cloned()
+    }
 }
 
 /// Vulnerability severity levels
@@ -106,6 +229,21 @@ pub trait VulnerabilityDatabase: Send + Sync {
         package_type: &str,
     ) -> Result<Vec<Vulnerability>>;
 
+    /// Check vulnerabilities for a package, matching pre-release versions per `policy`
+    ///
+    /// Databases that don't evaluate version ranges themselves fall back to
+    /// [`check_package`](Self::check_package).
+    async fn check_package_with_policy(
+        &self,
+        package_name: &str,
+        version: &str,
+        package_type: &str,
+        _policy: PrereleasePolicy,
+    ) -> Result<Vec<Vulnerability>> {
+        self.check_package(package_name, version, package_type)
+            .await
+    }
+
     /// Update the vulnerability database
     async fn update(&mut self) -> Result<UpdateResult>;
 
@@ -154,6 +292,7 @@ pub struct EmbeddedVulnerability {
     pub package_name: String,
     pub package_type: String,
     pub vulnerability: Vulnerability,
+    #[serde(default)]
     pub version_ranges: Vec<VersionRange>,
 }
 
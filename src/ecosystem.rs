@@ -0,0 +1,216 @@
+//! Package ecosystem detection
+//!
+//! Decides which analyzer a file or directory belongs to from file names and,
+//! for extensions, the manifest's keys, without running any analysis. This is
+//! the routing [`crate::PackageSecurityAnalyzer::analyze`] uses.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "runtime")]
+use std::path::Path;
+
+#[cfg(feature = "runtime")]
+use crate::analyzers::{
+    cargo::CargoAnalyzer, composer::ComposerAnalyzer, extension::ExtensionAnalyzer,
+    go::GoModuleAnalyzer, helm::HelmAnalyzer, maven::MavenAnalyzer, ruby::RubyGemAnalyzer,
+    terraform::TerraformAnalyzer,
+};
+
+/// Package ecosystem
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Ecosystem {
+    Npm,
+    Python,
+    /// Java archive
+    Java,
+    /// Maven or Gradle project
+    Maven,
+    Terraform,
+    Helm,
+    /// VS Code or browser extension
+    Extension,
+    /// Rust crate or Cargo workspace
+    Cargo,
+    /// Go module
+    Go,
+    /// Ruby gem or Bundler project
+    Ruby,
+    /// PHP package or project managed by Composer
+    Composer,
+    #[default]
+    Unknown,
+}
+
+impl Ecosystem {
+    /// Ecosystem for a name such as `npm`, `pypi` or `maven`
+    pub fn from_name(name: &str) -> Self {
+        match name.to_ascii_lowercase().as_str() {
+            "npm" | "node" | "javascript" => Self::Npm,
+            "python" | "pypi" | "pip" => Self::Python,
+            "java" => Self::Java,
+            "maven" | "gradle" => Self::Maven,
+            "terraform" | "opentofu" => Self::Terraform,
+            "helm" => Self::Helm,
+            "extension" | "vscode" | "browser" => Self::Extension,
+            "cargo" | "rust" | "crates.io" => Self::Cargo,
+            "go" | "golang" => Self::Go,
+            "ruby" | "rubygems" | "gem" | "bundler" => Self::Ruby,
+            "composer" | "packagist" | "php" => Self::Composer,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Ecosystem [`crate::PackageSecurityAnalyzer::analyze`] would analyze `path` as
+#[cfg(feature = "runtime")]
+pub fn detect_ecosystem(path: &Path) -> Option<Ecosystem> {
+    detect_ecosystems(path).into_iter().next()
+}
+
+/// Every ecosystem `path` could be analyzed as, in the order `analyze` tries them
+///
+/// A directory holding several kinds of package, such as a `package.json`
+/// next to a `pyproject.toml`, matches each of them. Extensions come first
+/// and also match npm, since they are npm packages too.
+#[cfg(feature = "runtime")]
+pub fn detect_ecosystems(path: &Path) -> Vec<Ecosystem> {
+    [
+        (
+            Ecosystem::Extension,
+            ExtensionAnalyzer::manifest_path(path).is_some(),
+        ),
+        (Ecosystem::Npm, is_npm_package(path)),
+        (Ecosystem::Python, is_python_package(path)),
+        (Ecosystem::Java, is_java_package(path)),
+        (Ecosystem::Maven, MavenAnalyzer::is_project(path)),
+        (Ecosystem::Terraform, TerraformAnalyzer::is_module(path)),
+        (Ecosystem::Helm, HelmAnalyzer::is_chart(path)),
+        (Ecosystem::Cargo, CargoAnalyzer::is_crate(path)),
+        (Ecosystem::Go, GoModuleAnalyzer::is_module(path)),
+        (Ecosystem::Ruby, RubyGemAnalyzer::is_project(path)),
+        (Ecosystem::Composer, ComposerAnalyzer::is_project(path)),
+    ]
+    .into_iter()
+    .filter_map(|(ecosystem, matched)| matched.then_some(ecosystem))
+    .collect()
+}
+
+/// Ecosystem a single manifest, lockfile or archive belongs to
+///
+/// Only the file name is needed, except for `package.json` and
+/// `manifest.json`, whose `contents` tell extensions apart from npm packages;
+/// pass an empty slice when they are not at hand.
+pub fn detect_ecosystem_from_file(file_name: &str, contents: &[u8]) -> Option<Ecosystem> {
+    let file_name = file_name.rsplit(['/', '\\']).next().unwrap_or(file_name);
+    let manifest = || serde_json::from_slice::<Value>(contents).ok();
+    let ecosystem = match file_name {
+        "package.json" => match manifest() {
+            Some(json) if is_vscode_manifest(&json) => Ecosystem::Extension,
+            _ => Ecosystem::Npm,
+        },
+        "manifest.json" => manifest()
+            .filter(|json| json.get("manifest_version").is_some())
+            .map(|_| Ecosystem::Extension)?,
+        "package-lock.json" | "npm-shrinkwrap.json" | "yarn.lock" => Ecosystem::Npm,
+        "setup.py" | "setup.cfg" | "pyproject.toml" | "requirements.txt" => Ecosystem::Python,
+        "pom.xml" | "build.gradle" | "build.gradle.kts" | "gradle.lockfile" => Ecosystem::Maven,
+        "Chart.yaml" | "Chart.lock" => Ecosystem::Helm,
+        "Cargo.toml" | "Cargo.lock" => Ecosystem::Cargo,
+        "go.mod" | "go.sum" => Ecosystem::Go,
+        "Gemfile" | "Gemfile.lock" => Ecosystem::Ruby,
+        "composer.json" | "composer.lock" => Ecosystem::Composer,
+        _ if file_name.ends_with(".tar.gz") => Ecosystem::Python,
+        _ => match file_name.rsplit_once('.')?.1 {
+            "tgz" => Ecosystem::Npm,
+            "whl" | "egg" => Ecosystem::Python,
+            "jar" | "war" | "ear" | "apk" | "aar" => Ecosystem::Java,
+            "tf" | "tofu" => Ecosystem::Terraform,
+            "gemspec" => Ecosystem::Ruby,
+            _ => return None,
+        },
+    };
+    Some(ecosystem)
+}
+
+/// Whether a `package.json` describes a VS Code extension
+pub(crate) fn is_vscode_manifest(json: &Value) -> bool {
+    json.get("engines").and_then(|e| e.get("vscode")).is_some()
+        || json.get("contributes").is_some()
+        || json.get("activationEvents").is_some()
+}
+
+#[cfg(feature = "runtime")]
+fn is_npm_package(path: &Path) -> bool {
+    if path.is_dir() {
+        path.join("package.json").exists()
+    } else if let Some(ext) = path.extension() {
+        ext == "tgz" || (ext == "gz" && path.to_string_lossy().contains("npm"))
+    } else {
+        false
+    }
+}
+
+#[cfg(feature = "runtime")]
+fn is_python_package(path: &Path) -> bool {
+    if path.is_dir() {
+        path.join("setup.py").exists()
+            || path.join("pyproject.toml").exists()
+            || path.join("setup.cfg").exists()
+    } else if let Some(ext) = path.extension() {
+        ext == "whl"
+            || ext == "egg"
+            || (ext == "gz" && path.to_string_lossy().contains(".tar.gz"))
+            || (ext == "zip" && !is_java_package(path))
+    } else {
+        false
+    }
+}
+
+#[cfg(feature = "runtime")]
+fn is_java_package(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("jar" | "war" | "ear" | "apk" | "aar")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detection_by_name() {
+        assert_eq!(
+            detect_ecosystem(Path::new("package.tgz")),
+            Some(Ecosystem::Npm)
+        );
+        assert_eq!(
+            detect_ecosystem(Path::new("package.whl")),
+            Some(Ecosystem::Python)
+        );
+        assert_eq!(
+            detect_ecosystem(Path::new("app.jar")),
+            Some(Ecosystem::Java)
+        );
+        assert_eq!(detect_ecosystem(Path::new("README.md")), None);
+
+        let file = detect_ecosystem_from_file;
+        assert_eq!(file("app/package.json", b"{}"), Some(Ecosystem::Npm));
+        assert_eq!(
+            file("package.json", br#"{"engines": {"vscode": "^1.80.0"}}"#),
+            Some(Ecosystem::Extension)
+        );
+        assert_eq!(file("manifest.json", b"{}"), None);
+        assert_eq!(file("requests-2.31.0.tar.gz", b""), Some(Ecosystem::Python));
+        assert_eq!(file("main.tf", b""), Some(Ecosystem::Terraform));
+        // Terraform is analyzed from its configuration, not the lock file
+        assert_eq!(file(".terraform.lock.hcl", b""), None);
+        assert_eq!(file("Chart.yaml", b""), Some(Ecosystem::Helm));
+        assert_eq!(file("crates/core/Cargo.lock", b""), Some(Ecosystem::Cargo));
+        assert_eq!(file("ledger.gemspec", b""), Some(Ecosystem::Ruby));
+        assert_eq!(file("notes.txt", b""), None);
+    }
+}
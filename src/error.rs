@@ -0,0 +1,134 @@
+//! Errors analyses fail with
+//!
+//! The analyze methods of [`crate::PackageSecurityAnalyzer`] return a
+//! [`PackageSecurityError`] to match on. Analyzers used on their own return
+//! `anyhow::Error`; the causes below can be recovered from it with
+//! `anyhow::Error::downcast_ref`, or the whole error classified with
+//! `PackageSecurityError::from`.
+
+use std::path::PathBuf;
+
+use crate::core::{AnalysisTimeout, MemoryLimitExceeded, ResourceLimitExceeded};
+#[cfg(feature = "runtime")]
+use crate::vulnerability_db::DatabaseLoadError;
+
+/// Why an analysis failed
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum PackageSecurityError {
+    /// The path to analyze does not exist
+    #[error("{} does not exist", path.display())]
+    PathNotFound { path: PathBuf },
+
+    /// A directory without a manifest any analyzer recognizes
+    #[error("no package manifest found in {}", path.display())]
+    NoPackageManifest { path: PathBuf },
+
+    /// A file no analyzer handles
+    #[error("unsupported package type: {}", path.display())]
+    Unsupported { path: PathBuf },
+
+    /// A manifest or lockfile is not valid JSON, TOML or YAML
+    #[error("failed to parse manifest: {message}")]
+    ManifestParse {
+        /// The parse error with the context it was reported in
+        message: String,
+    },
+
+    /// A request to a registry or advisory service failed
+    #[error("network request failed: {message}")]
+    Network { message: String },
+
+    /// The analysis ran past `AnalysisOptions::timeout_seconds`
+    #[error(transparent)]
+    Timeout(#[from] AnalysisTimeout),
+
+    /// A manifest exceeded `AnalysisOptions::limits`
+    #[error(transparent)]
+    ResourceLimit(#[from] ResourceLimitExceeded),
+
+    /// The analysis exceeded `AnalysisOptions::max_memory_bytes`
+    #[error(transparent)]
+    MemoryLimit(#[from] MemoryLimitExceeded),
+
+    /// A vulnerability database could not be loaded
+    #[cfg(feature = "runtime")]
+    #[error(transparent)]
+    Database(#[from] DatabaseLoadError),
+
+    /// An archive unpacked past `AnalysisOptions::max_decompressed_bytes` or
+    /// `AnalysisOptions::max_compression_ratio`; extraction was aborted
+    #[error("{archive} looks like a decompression bomb: {decompressed} bytes unpacked from {compressed} compressed ({ratio:.0}:1)")]
+    DecompressionBomb {
+        archive: String,
+        /// Unpacked to compressed size when extraction stopped
+        ratio: f64,
+        decompressed: u64,
+        compressed: u64,
+    },
+
+    /// An archive entry is absolute, or its path or link target leads outside
+    /// the directory it is unpacked into; nothing was unpacked past it
+    #[error("archive entry {entry} leads outside the extraction root")]
+    UnsafePath {
+        /// The entry's path, followed by ` -> ` and its target for links
+        entry: String,
+    },
+
+    /// The ruleset `AnalysisOptions::custom_pattern_rules` names cannot be
+    /// used; no analysis runs with it
+    #[error("custom pattern rules {}: {reason}", file.display())]
+    InvalidRuleset { file: PathBuf, reason: String },
+
+    /// Any other failure, with its chain of causes
+    #[error("{0}")]
+    Analysis(String),
+}
+
+impl From<anyhow::Error> for PackageSecurityError {
+    /// Classify an analyzer's error by the causes in its chain
+    fn from(error: anyhow::Error) -> Self {
+        if let Some(error) = error.downcast_ref::<PackageSecurityError>() {
+            return error.clone();
+        }
+        if let Some(timeout) = error.downcast_ref::<AnalysisTimeout>() {
+            return Self::Timeout(timeout.clone());
+        }
+        if let Some(exceeded) = error.downcast_ref::<ResourceLimitExceeded>() {
+            return Self::ResourceLimit(exceeded.clone());
+        }
+        if let Some(exceeded) = error.downcast_ref::<MemoryLimitExceeded>() {
+            return Self::MemoryLimit(exceeded.clone());
+        }
+        #[cfg(feature = "runtime")]
+        if let Some(load) = error.downcast_ref::<DatabaseLoadError>() {
+            return Self::Database(load.clone());
+        }
+
+        let message = format!("{:#}", error);
+        if error.chain().any(|cause| {
+            cause.is::<serde_json::Error>()
+                || cause.is::<serde_yaml::Error>()
+                || cause.is::<toml::de::Error>()
+        }) {
+            Self::ManifestParse { message }
+        } else if Self::is_network(&error) {
+            Self::Network { message }
+        } else {
+            Self::Analysis(message)
+        }
+    }
+}
+
+impl PackageSecurityError {
+    /// Whether a request failed somewhere in `error`'s chain
+    #[cfg(feature = "runtime")]
+    fn is_network(error: &anyhow::Error) -> bool {
+        error.chain().any(|cause| cause.is::<reqwest::Error>())
+    }
+
+    /// Without the runtime no requests are made
+    #[cfg(not(feature = "runtime"))]
+    fn is_network(_: &anyhow::Error) -> bool {
+        false
+    }
+}
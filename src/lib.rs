@@ -1,123 +1,625 @@
 //! ThreatFlux Package Security Library
 //!
 //! A unified framework for analyzing package security across multiple package managers
-//! including npm, Python (PyPI), Java (Maven), and more.
+//! including npm, Python (PyPI), Java (Maven), Cargo, Go modules, RubyGems,
+//! Composer, Terraform modules, Helm charts, and more.
+//!
+//! The `runtime` feature, on by default, brings in the analyzers, vulnerability
+//! databases and [`PackageSecurityAnalyzer`], which need an async runtime, the
+//! network and the filesystem. Without it only the synchronous
+//! [`analyze_manifest`] path and the core types are built, as for WebAssembly.
 
+#[cfg(feature = "runtime")]
 pub mod analyzers;
+#[cfg(feature = "runtime")]
+pub mod benchmark;
+#[cfg(feature = "runtime")]
+pub mod context;
 pub mod core;
+pub mod ecosystem;
+pub mod error;
+pub mod manifest;
+pub mod report;
 pub mod utils;
+pub mod versioning;
+#[cfg(feature = "runtime")]
 pub mod vulnerability_db;
 
 pub use core::{
-    AnalysisResult, MaliciousPattern, PackageAnalyzer, PackageInfo, RiskLevel, RiskScore,
+    diff, AnalysisDiff, AnalysisOptions, AnalysisResult, DynPackageAnalyzer, ExternalFinding,
+    MaliciousPattern, PackageAnalyzer, PackageInfo, ProgressEvent, RiskLevel, RiskScore,
     TyposquattingRisk, Vulnerability, VulnerabilitySeverity,
 };
 
-pub use analyzers::{java::JavaAnalyzer, npm::NpmAnalyzer, python::PythonAnalyzer};
+#[cfg(feature = "runtime")]
+pub use analyzers::{
+    cargo::CargoAnalyzer, composer::ComposerAnalyzer, extension::ExtensionAnalyzer,
+    go::GoModuleAnalyzer, helm::HelmAnalyzer, java::JavaAnalyzer, maven::MavenAnalyzer,
+    npm::NpmAnalyzer, python::PythonAnalyzer, ruby::RubyGemAnalyzer, terraform::TerraformAnalyzer,
+};
 
-pub use vulnerability_db::VulnerabilityDatabase;
+#[cfg(feature = "runtime")]
+pub use context::AnalyzerContext;
+#[cfg(feature = "runtime")]
+pub use ecosystem::{detect_ecosystem, detect_ecosystems};
+pub use ecosystem::{detect_ecosystem_from_file, Ecosystem};
+pub use error::PackageSecurityError;
+pub use manifest::{analyze_manifest, analyze_manifest_with_options, ManifestAnalysisResult};
+pub use utils::typosquatting::{
+    PopularPackages, SimilarPackage, TyposquattingTargetProvider, TyposquattingTechnique,
+};
+pub use versioning::{VersionScheme, VersionSchemeRegistry};
+#[cfg(feature = "runtime")]
+pub use vulnerability_db::{
+    CacheStats, DatabaseMetadata, DatabaseSource, LookupCache, VulnerabilityDatabase,
+};
 
-use anyhow::Result;
-use std::path::Path;
+#[cfg(feature = "runtime")]
+use {
+    anyhow::Result,
+    core::ErasedAnalyzer,
+    report::AsyncNdjsonWriter,
+    std::collections::HashMap,
+    std::path::{Path, PathBuf},
+    std::sync::Arc,
+    tokio::io::AsyncWrite,
+    tokio::sync::mpsc::UnboundedSender,
+    tokio::sync::Semaphore,
+    tokio::task::{self, JoinSet},
+};
+
+/// Outcome of analyzing one package of a batch
+#[cfg(feature = "runtime")]
+type BatchResult = std::result::Result<Box<dyn AnalysisResult>, PackageSecurityError>;
+
+/// Running batch analyses, with the input index of each task
+#[cfg(feature = "runtime")]
+type BatchTasks = (JoinSet<BatchResult>, HashMap<task::Id, usize>);
 
 /// Main entry point for package security analysis
+#[cfg(feature = "runtime")]
 pub struct PackageSecurityAnalyzer {
     npm_analyzer: NpmAnalyzer,
     python_analyzer: PythonAnalyzer,
     java_analyzer: JavaAnalyzer,
+    maven_analyzer: MavenAnalyzer,
+    terraform_analyzer: TerraformAnalyzer,
+    helm_analyzer: HelmAnalyzer,
+    extension_analyzer: ExtensionAnalyzer,
+    cargo_analyzer: CargoAnalyzer,
+    go_analyzer: GoModuleAnalyzer,
+    ruby_analyzer: RubyGemAnalyzer,
+    composer_analyzer: ComposerAnalyzer,
+    /// Registered analyzers, consulted before the built-in ones
+    custom_analyzers: Vec<Arc<dyn DynPackageAnalyzer>>,
+    database: DatabaseMetadata,
+    options: AnalysisOptions,
+    /// Context the analyzers were built from, without the lookup cache, to
+    /// build them again
+    context: AnalyzerContext,
+    vuln_cache: Arc<LookupCache>,
+    typosquatting_providers: Vec<Arc<dyn TyposquattingTargetProvider>>,
+    concurrency: usize,
 }
 
+#[cfg(feature = "runtime")]
 impl PackageSecurityAnalyzer {
     /// Create a new package security analyzer with default settings
     pub fn new() -> Result<Self> {
-        Ok(Self {
-            npm_analyzer: NpmAnalyzer::new()?,
-            python_analyzer: PythonAnalyzer::new()?,
-            java_analyzer: JavaAnalyzer::new()?,
-        })
+        Self::from_context(&AnalyzerContext::new()?)
     }
 
     /// Create analyzer with custom vulnerability database path
     pub fn with_db_path(db_path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_context(&AnalyzerContext::with_db_path(db_path)?)
+    }
+
+    /// Create analyzer sharing the compiled rules and databases of `context`
+    ///
+    /// Much cheaper than [`Self::new`], which compiles and loads them afresh;
+    /// build one context and create an analyzer from it per request.
+    pub fn from_context(context: &AnalyzerContext) -> Result<Self> {
+        Self::with_cache(context, Arc::default())
+    }
+
+    /// Analyzers from `context` that look vulnerabilities up through `cache`
+    fn with_cache(context: &AnalyzerContext, vuln_cache: Arc<LookupCache>) -> Result<Self> {
+        let shared = context;
+        let context = &context.clone().with_lookup_cache(vuln_cache.clone());
         Ok(Self {
-            npm_analyzer: NpmAnalyzer::with_db_path(db_path.as_ref())?,
-            python_analyzer: PythonAnalyzer::with_db_path(db_path.as_ref())?,
-            java_analyzer: JavaAnalyzer::with_db_path(db_path.as_ref())?,
+            npm_analyzer: NpmAnalyzer::from_context(context)?,
+            python_analyzer: PythonAnalyzer::from_context(context),
+            java_analyzer: JavaAnalyzer::from_context(context),
+            maven_analyzer: MavenAnalyzer::from_context(context),
+            terraform_analyzer: TerraformAnalyzer::from_context(context),
+            helm_analyzer: HelmAnalyzer::from_context(context),
+            extension_analyzer: ExtensionAnalyzer::from_context(context)?,
+            cargo_analyzer: CargoAnalyzer::from_context(context),
+            go_analyzer: GoModuleAnalyzer::from_context(context),
+            ruby_analyzer: RubyGemAnalyzer::from_context(context),
+            composer_analyzer: ComposerAnalyzer::from_context(context),
+            custom_analyzers: Vec::new(),
+            database: context.database().clone(),
+            options: AnalysisOptions::default(),
+            context: shared.clone(),
+            vuln_cache,
+            typosquatting_providers: Vec::new(),
+            concurrency: std::thread::available_parallelism().map_or(1, |n| n.get()),
         })
     }
 
-    /// Analyze a package file or directory
-    pub async fn analyze(&self, path: impl AsRef<Path>) -> Result<Box<dyn AnalysisResult>> {
+    /// Builder for an analyzer with custom analyzers registered
+    pub fn builder() -> PackageSecurityAnalyzerBuilder {
+        PackageSecurityAnalyzerBuilder::default()
+    }
+
+    /// Create analyzer with a vulnerability database loaded from `source`
+    ///
+    /// A snapshot from a path, URL or custom loader replaces the bundled
+    /// databases for every ecosystem. It is validated before use; a snapshot
+    /// that is unreadable, corrupt or in another format version fails with a
+    /// [`vulnerability_db::DatabaseLoadError`].
+    pub async fn with_database(source: DatabaseSource) -> Result<Self> {
+        Self::from_context(&AnalyzerContext::with_database(source).await?)
+    }
+
+    /// Analyze with `options` wherever no options are given per call
+    ///
+    /// Applies to [`Self::analyze`] and the methods built on it.
+    pub fn with_options(mut self, options: AnalysisOptions) -> Self {
+        self.vuln_cache.set_ttl(options.vuln_cache_ttl_secs);
+        self.options = options;
+        self
+    }
+
+    /// Options [`Self::analyze`] uses
+    pub fn options(&self) -> &AnalysisOptions {
+        &self.options
+    }
+
+    /// Also compare package names against candidates from `provider`
+    ///
+    /// Candidates are scored alongside the bundled popular names for npm,
+    /// Python and extension packages. Providers are skipped in `offline`
+    /// builds.
+    pub fn with_typosquatting_provider(
+        mut self,
+        provider: Arc<dyn TyposquattingTargetProvider>,
+    ) -> Self {
+        self.npm_analyzer = self
+            .npm_analyzer
+            .with_typosquatting_provider(provider.clone());
+        self.python_analyzer = self
+            .python_analyzer
+            .with_typosquatting_provider(provider.clone());
+        self.extension_analyzer = self
+            .extension_analyzer
+            .with_typosquatting_provider(provider.clone());
+        self.typosquatting_providers.push(provider);
+        self
+    }
+
+    /// Analyze up to `concurrency` packages at once in [`Self::analyze_many`]
+    ///
+    /// Defaults to the available parallelism; zero is treated as one.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Keep cached vulnerability lookups in `file` as well as in memory
+    ///
+    /// Answers in the file that are still fresh are used right away; new ones
+    /// are written back after each analysis. Replaces the analyzer's cache.
+    pub fn with_vuln_cache_file(self, file: impl AsRef<Path>) -> Result<Self> {
+        let cache = LookupCache::open(file, self.options.vuln_cache_ttl_secs)?;
+        self.rebuilt(Arc::new(cache))
+    }
+
+    /// Hits and misses of the vulnerability lookup cache
+    ///
+    /// The cache is shared by every call on this analyzer and kept for
+    /// [`AnalysisOptions::vuln_cache_ttl_secs`].
+    pub fn cache_stats(&self) -> CacheStats {
+        self.vuln_cache.stats()
+    }
+
+    /// Forget cached vulnerability lookups
+    pub fn clear_cache(&self) {
+        self.vuln_cache.clear();
+    }
+
+    /// Version and size of the vulnerability database in use
+    pub fn database(&self) -> &DatabaseMetadata {
+        &self.database
+    }
+
+    /// Register a version scheme for an ecosystem in the process-wide registry
+    ///
+    /// This is [`VersionSchemeRegistry::global`], not state of any one
+    /// analyzer: the scheme replaces any earlier one for `ecosystem`, built-in
+    /// or registered, for every analyzer in the process. The vulnerability
+    /// databases consult it when matching affected version ranges.
+    pub fn register_global_version_scheme(ecosystem: &str, scheme: impl VersionScheme + 'static) {
+        VersionSchemeRegistry::global().register(ecosystem, scheme);
+    }
+
+    /// Analyze a package file or directory with the analyzer's options
+    ///
+    /// These are [`AnalysisOptions::default`] unless set with
+    /// [`Self::with_options`]. Analyzers registered with
+    /// [`PackageSecurityAnalyzerBuilder::register_analyzer`] are asked first,
+    /// in the order they were registered, and the first whose `can_analyze`
+    /// accepts the path analyzes it. Otherwise the ecosystem
+    /// [`detect_ecosystem`] finds picks a built-in analyzer. Fails with a [`PackageSecurityError`] saying
+    /// why, such as [`PackageSecurityError::PathNotFound`] or
+    /// [`PackageSecurityError::ManifestParse`].
+    pub async fn analyze(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Box<dyn AnalysisResult>, PackageSecurityError> {
+        self.analyze_with_options(path, &self.options).await
+    }
+
+    /// Analyze a package file or directory with `options` for this call only
+    pub async fn analyze_with_options(
+        &self,
+        path: impl AsRef<Path>,
+        options: &AnalysisOptions,
+    ) -> Result<Box<dyn AnalysisResult>, PackageSecurityError> {
+        let result = self.analyze_unsaved(path, options).await;
+        self.save_cache();
+        result
+    }
+
+    /// Analyze a package, sending a [`ProgressEvent`] to `events` as each
+    /// step of the analysis completes
+    ///
+    /// `events` is dropped when the analysis returns, so the receiver sees the
+    /// channel close once the last event is delivered. See [`core::progress`].
+    pub async fn analyze_with_progress(
+        &self,
+        path: impl AsRef<Path>,
+        events: UnboundedSender<ProgressEvent>,
+    ) -> Result<Box<dyn AnalysisResult>, PackageSecurityError> {
+        core::progress::with_progress(events, self.analyze_with_options(path, &self.options)).await
+    }
+
+    /// Analyze without writing the lookup cache file
+    async fn analyze_unsaved(
+        &self,
+        path: impl AsRef<Path>,
+        options: &AnalysisOptions,
+    ) -> Result<Box<dyn AnalysisResult>, PackageSecurityError> {
         let path = path.as_ref();
+        self.vuln_cache.set_ttl(options.vuln_cache_ttl_secs);
+        if !path.exists() {
+            return Err(PackageSecurityError::PathNotFound {
+                path: path.to_path_buf(),
+            });
+        }
+        if let Some(custom) = self
+            .custom_analyzers
+            .iter()
+            .find(|analyzer| analyzer.can_analyze(path))
+        {
+            return Ok(custom.analyze_with_options(path, options).await?);
+        }
+
+        // Detect package type based on file extension or contents; extensions
+        // are checked first since VS Code extensions are also npm packages
+        match detect_ecosystem(path) {
+            Some(Ecosystem::Extension) => Ok(Box::new(
+                self.extension_analyzer
+                    .analyze_with_options(path, options)
+                    .await?,
+            )),
+            Some(Ecosystem::Npm) => Ok(Box::new(
+                self.npm_analyzer
+                    .analyze_with_options(path, options)
+                    .await?,
+            )),
+            Some(Ecosystem::Python) => Ok(Box::new(
+                self.python_analyzer
+                    .analyze_with_options(path, options)
+                    .await?,
+            )),
+            Some(Ecosystem::Java) => Ok(Box::new(
+                self.java_analyzer
+                    .analyze_with_options(path, options)
+                    .await?,
+            )),
+            Some(Ecosystem::Maven) => Ok(Box::new(
+                self.maven_analyzer
+                    .analyze_with_options(path, options)
+                    .await?,
+            )),
+            Some(Ecosystem::Terraform) => Ok(Box::new(
+                self.terraform_analyzer
+                    .analyze_with_options(path, options)
+                    .await?,
+            )),
+            Some(Ecosystem::Helm) => Ok(Box::new(
+                self.helm_analyzer
+                    .analyze_with_options(path, options)
+                    .await?,
+            )),
+            Some(Ecosystem::Cargo) => Ok(Box::new(
+                self.cargo_analyzer
+                    .analyze_with_options(path, options)
+                    .await?,
+            )),
+            Some(Ecosystem::Go) => Ok(Box::new(
+                self.go_analyzer.analyze_with_options(path, options).await?,
+            )),
+            Some(Ecosystem::Ruby) => Ok(Box::new(
+                self.ruby_analyzer
+                    .analyze_with_options(path, options)
+                    .await?,
+            )),
+            Some(Ecosystem::Composer) => Ok(Box::new(
+                self.composer_analyzer
+                    .analyze_with_options(path, options)
+                    .await?,
+            )),
+            Some(Ecosystem::Unknown) | None if path.is_dir() => {
+                Err(PackageSecurityError::NoPackageManifest {
+                    path: path.to_path_buf(),
+                })
+            }
+            Some(Ecosystem::Unknown) | None => Err(PackageSecurityError::Unsupported {
+                path: path.to_path_buf(),
+            }),
+        }
+    }
+
+    /// Analyze a package and merge findings other tools reported for it
+    ///
+    /// External findings are tagged with their source, deduped against native
+    /// findings by fingerprint, and count toward the overall risk level; see
+    /// [`core::RiskAssessment::merge_external`].
+    pub async fn analyze_with_external(
+        &self,
+        path: impl AsRef<Path>,
+        external: Vec<ExternalFinding>,
+    ) -> Result<Box<dyn AnalysisResult>, PackageSecurityError> {
+        let mut result = self.analyze(path).await?;
+        result.merge_external(&external);
+        Ok(result)
+    }
 
-        // Detect package type based on file extension or contents
-        if self.is_npm_package(path) {
-            Ok(Box::new(self.npm_analyzer.analyze(path).await?))
-        } else if self.is_python_package(path) {
-            Ok(Box::new(self.python_analyzer.analyze(path).await?))
-        } else if self.is_java_package(path) {
-            Ok(Box::new(self.java_analyzer.analyze(path).await?))
-        } else {
-            anyhow::bail!("Unknown package type for path: {}", path.display())
+    /// Analyze many packages concurrently with the analyzer's options
+    ///
+    /// Runs up to [`Self::with_concurrency`] analyses at once, each on its
+    /// own task. Vulnerability lookups go through the analyzer's cache, so a
+    /// dependency common to many packages is looked up once (see
+    /// [`Self::cache_stats`]). Results are returned in input order, each with
+    /// its path; one package failing does not stop the rest.
+    pub async fn analyze_many(
+        &self,
+        paths: impl IntoIterator<Item = PathBuf>,
+    ) -> Vec<(
+        PathBuf,
+        Result<Box<dyn AnalysisResult>, PackageSecurityError>,
+    )> {
+        let paths: Vec<PathBuf> = paths.into_iter().collect();
+        let (mut tasks, indexes) = match self.spawn_many(&paths) {
+            Ok(spawned) => spawned,
+            Err(error) => {
+                return paths
+                    .into_iter()
+                    .map(|path| (path, Err(error.clone())))
+                    .collect();
+            }
+        };
+
+        let mut results: Vec<_> = paths.iter().map(|_| None).collect();
+        while let Some(joined) = tasks.join_next_with_id().await {
+            let (index, result) = Self::batch_outcome(&paths, &indexes, joined);
+            results[index] = Some(result);
         }
+        self.save_cache();
+        paths
+            .into_iter()
+            .zip(results)
+            .map(|(path, result)| (path, result.expect("every batch task is joined")))
+            .collect()
     }
 
-    /// Check if path is an npm package
-    fn is_npm_package(&self, path: &Path) -> bool {
-        if path.is_dir() {
-            path.join("package.json").exists()
-        } else if let Some(ext) = path.extension() {
-            ext == "tgz" || (ext == "gz" && path.to_string_lossy().contains("npm"))
-        } else {
-            false
+    /// Start analyzing each of `paths` on its own task, up to
+    /// [`Self::with_concurrency`] at once, with the input index of each task
+    fn spawn_many(&self, paths: &[PathBuf]) -> Result<BatchTasks, PackageSecurityError> {
+        let batch = Arc::new(self.rebuilt(self.vuln_cache.clone())?);
+        let permits = Arc::new(Semaphore::new(self.concurrency));
+        let mut tasks = JoinSet::new();
+        let mut indexes = HashMap::new();
+        for (index, path) in paths.iter().enumerate() {
+            let batch = batch.clone();
+            let permits = permits.clone();
+            let path = path.clone();
+            let task = tasks.spawn(async move {
+                let _permit = permits
+                    .acquire_owned()
+                    .await
+                    .map_err(|error| PackageSecurityError::Analysis(error.to_string()))?;
+                batch.analyze_unsaved(&path, &batch.options).await
+            });
+            indexes.insert(task.id(), index);
         }
+        Ok((tasks, indexes))
     }
 
-    /// Check if path is a Python package
-    fn is_python_package(&self, path: &Path) -> bool {
-        if path.is_dir() {
-            path.join("setup.py").exists()
-                || path.join("pyproject.toml").exists()
-                || path.join("setup.cfg").exists()
-        } else if let Some(ext) = path.extension() {
-            ext == "whl"
-                || ext == "egg"
-                || (ext == "gz" && path.to_string_lossy().contains(".tar.gz"))
-                || (ext == "zip" && !self.is_java_package(path))
-        } else {
-            false
+    /// Input index and result of a finished batch task
+    fn batch_outcome(
+        paths: &[PathBuf],
+        indexes: &HashMap<task::Id, usize>,
+        joined: std::result::Result<(task::Id, BatchResult), task::JoinError>,
+    ) -> (usize, BatchResult) {
+        match joined {
+            Ok((id, result)) => (indexes[&id], result),
+            Err(error) => {
+                let index = indexes[&error.id()];
+                let error = PackageSecurityError::Analysis(format!(
+                    "analysis of {} panicked: {}",
+                    paths[index].display(),
+                    error
+                ));
+                (index, Err(error))
+            }
         }
     }
 
-    /// Check if path is a Java package
-    fn is_java_package(&self, path: &Path) -> bool {
-        if let Some(ext) = path.extension() {
-            matches!(
-                ext.to_str(),
-                Some("jar") | Some("war") | Some("ear") | Some("apk") | Some("aar")
-            )
-        } else {
-            false
+    /// Copy of this analyzer looking vulnerabilities up through `vuln_cache`
+    fn rebuilt(&self, vuln_cache: Arc<LookupCache>) -> Result<Self> {
+        let mut analyzer = Self::with_cache(&self.context, vuln_cache)?
+            .with_options(self.options.clone())
+            .with_concurrency(self.concurrency);
+        for provider in &self.typosquatting_providers {
+            analyzer = analyzer.with_typosquatting_provider(provider.clone());
         }
+        analyzer.custom_analyzers = self.custom_analyzers.clone();
+        Ok(analyzer)
+    }
+
+    /// Write the lookup cache file, if any; failing to only loses the cache
+    fn save_cache(&self) {
+        if let Err(error) = self.vuln_cache.save() {
+            tracing::warn!("Failed to save the vulnerability cache: {:#}", error);
+        }
+    }
+
+    /// Analyze many packages concurrently, streaming each result as an NDJSON
+    /// line as soon as its analysis completes
+    ///
+    /// Runs like [`Self::analyze_many`], but lines come in completion order
+    /// and no result is held once written. With `per_finding` set, each
+    /// finding, vulnerability and malicious pattern gets its own line instead
+    /// of one line per package. A package that fails to analyze gets an
+    /// `error` line with its path. Returns the number of lines written; only
+    /// failing to write stops the batch.
+    pub async fn analyze_to_ndjson<P, W>(
+        &self,
+        paths: impl IntoIterator<Item = P>,
+        writer: W,
+        per_finding: bool,
+    ) -> Result<usize>
+    where
+        P: AsRef<Path>,
+        W: AsyncWrite + Unpin + Send,
+    {
+        let paths: Vec<PathBuf> = paths
+            .into_iter()
+            .map(|path| path.as_ref().to_path_buf())
+            .collect();
+        let mut writer = AsyncNdjsonWriter::new(writer);
+        let (mut tasks, indexes) = match self.spawn_many(&paths) {
+            Ok(spawned) => spawned,
+            Err(error) => {
+                for path in &paths {
+                    writer.write_error(path, &error).await?;
+                }
+                return Ok(writer.lines_written());
+            }
+        };
+
+        while let Some(joined) = tasks.join_next_with_id().await {
+            match Self::batch_outcome(&paths, &indexes, joined) {
+                (_, Ok(result)) if per_finding => {
+                    writer.write_findings(result.as_ref()).await?;
+                }
+                (_, Ok(result)) => writer.write_result(result.as_ref()).await?,
+                (index, Err(error)) => writer.write_error(&paths[index], &error).await?,
+            }
+        }
+        self.save_cache();
+        Ok(writer.lines_written())
     }
 }
 
+/// Builds a [`PackageSecurityAnalyzer`] with custom analyzers alongside the
+/// built-in ones
+///
+/// Registered analyzers take precedence over the built-in ones, in the order
+/// they are registered; see [`PackageSecurityAnalyzer::analyze`].
+#[cfg(feature = "runtime")]
+#[derive(Default)]
+pub struct PackageSecurityAnalyzerBuilder {
+    context: Option<AnalyzerContext>,
+    options: Option<AnalysisOptions>,
+    custom_analyzers: Vec<Arc<dyn DynPackageAnalyzer>>,
+}
+
+#[cfg(feature = "runtime")]
+impl PackageSecurityAnalyzerBuilder {
+    /// Share the compiled rules and databases of `context`
+    ///
+    /// Without one, [`Self::build`] creates a default context.
+    pub fn context(mut self, context: AnalyzerContext) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    /// Analyze with `options`; see [`PackageSecurityAnalyzer::with_options`]
+    pub fn options(mut self, options: AnalysisOptions) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    /// Try `analyzer` after the analyzers registered before it
+    pub fn register_analyzer<A>(self, analyzer: A) -> Self
+    where
+        A: PackageAnalyzer + 'static,
+        A::Analysis: 'static,
+    {
+        self.register_dyn_analyzer(Box::new(ErasedAnalyzer(analyzer)))
+    }
+
+    /// Try an analyzer implementing [`DynPackageAnalyzer`] directly, after
+    /// the analyzers registered before it
+    pub fn register_dyn_analyzer(mut self, analyzer: Box<dyn DynPackageAnalyzer>) -> Self {
+        self.custom_analyzers.push(Arc::from(analyzer));
+        self
+    }
+
+    /// Create the analyzer
+    pub fn build(self) -> Result<PackageSecurityAnalyzer> {
+        let context = match self.context {
+            Some(context) => context,
+            None => AnalyzerContext::new()?,
+        };
+        let mut analyzer = PackageSecurityAnalyzer::from_context(&context)?;
+        if let Some(options) = self.options {
+            analyzer = analyzer.with_options(options);
+        }
+        analyzer.custom_analyzers = self.custom_analyzers;
+        Ok(analyzer)
+    }
+}
+
+#[cfg(feature = "runtime")]
 impl Default for PackageSecurityAnalyzer {
     fn default() -> Self {
         Self::new().expect("Failed to create default analyzer")
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "runtime"))]
 mod tests {
     use super::*;
 
     #[test]
     fn test_package_detection() {
-        let analyzer = PackageSecurityAnalyzer::new().unwrap();
-
-        assert!(analyzer.is_npm_package(Path::new("package.tgz")));
-        assert!(analyzer.is_python_package(Path::new("package.whl")));
-        assert!(analyzer.is_java_package(Path::new("app.jar")));
+        assert_eq!(
+            detect_ecosystem(Path::new("package.tgz")),
+            Some(Ecosystem::Npm)
+        );
+        assert_eq!(
+            detect_ecosystem(Path::new("package.whl")),
+            Some(Ecosystem::Python)
+        );
+        assert_eq!(
+            detect_ecosystem(Path::new("app.jar")),
+            Some(Ecosystem::Java)
+        );
     }
 }
@@ -0,0 +1,251 @@
+//! `Cargo.toml` parsing
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::core::{DependencyType, InstallHook, PackageInfo, PackageMetadata, PartialParse};
+
+/// Dependency tables of a manifest and the kind of dependency each declares
+const DEPENDENCY_TABLES: &[(&str, DependencyType)] = &[
+    ("dependencies", DependencyType::Runtime),
+    ("build-dependencies", DependencyType::Build),
+    ("dev-dependencies", DependencyType::Development),
+];
+
+/// Rust crate or workspace information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CargoPackage {
+    pub metadata: PackageMetadata,
+    pub edition: Option<String>,
+    pub rust_version: Option<String>,
+    /// Build script compiled and run before the crate, relative to its root
+    pub build_script: Option<String>,
+    /// Whether the crate is a procedural macro, run inside the compiler
+    pub is_proc_macro: bool,
+    /// Member paths, for a workspace root
+    pub workspace_members: Vec<String>,
+    /// Dependencies as declared in `Cargo.toml`
+    pub declared_dependencies: Vec<DeclaredDependency>,
+    pub has_lock_file: bool,
+    /// Lock file left out because it could not be parsed
+    #[serde(default)]
+    pub skipped_files: Vec<PartialParse>,
+}
+
+impl CargoPackage {
+    /// The build script, compiled and run on every machine building the crate
+    pub fn install_hooks(&self) -> Vec<InstallHook> {
+        self.build_script
+            .iter()
+            .map(|script| InstallHook::new("build", script, "Cargo.toml"))
+            .collect()
+    }
+}
+
+/// A dependency declared in `Cargo.toml`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeclaredDependency {
+    /// Crate name, which differs from the key when the dependency is renamed
+    pub name: String,
+    /// Version requirement, `*` when none is given
+    pub version_spec: String,
+    pub dependency_type: DependencyType,
+    pub optional: bool,
+    /// Git URL or local path the crate is taken from instead of a registry
+    pub source: Option<String>,
+}
+
+impl PackageInfo for CargoPackage {
+    fn metadata(&self) -> &PackageMetadata {
+        &self.metadata
+    }
+
+    fn package_type(&self) -> &str {
+        "cargo"
+    }
+
+    fn custom_attributes(&self) -> HashMap<String, serde_json::Value> {
+        let mut attrs = HashMap::new();
+        attrs.insert("edition".to_string(), serde_json::json!(self.edition));
+        attrs.insert(
+            "rust_version".to_string(),
+            serde_json::json!(self.rust_version),
+        );
+        attrs.insert(
+            "build_script".to_string(),
+            serde_json::json!(self.build_script),
+        );
+        attrs.insert(
+            "is_proc_macro".to_string(),
+            serde_json::json!(self.is_proc_macro),
+        );
+        attrs.insert(
+            "workspace_members".to_string(),
+            serde_json::json!(self.workspace_members),
+        );
+        attrs.insert(
+            "has_lock_file".to_string(),
+            serde_json::json!(self.has_lock_file),
+        );
+        attrs
+    }
+}
+
+/// Parse `Cargo.toml`
+///
+/// Fields inherited with `workspace = true` are taken from the manifest's
+/// own `[workspace.package]` and `[workspace.dependencies]`. A virtual
+/// workspace manifest is named after its directory. Without a `root` the
+/// filesystem is not consulted, so an implicit `build.rs` goes unseen.
+pub(crate) fn parse_manifest(root: Option<&Path>, content: &str) -> Result<CargoPackage> {
+    let manifest: toml::Value = toml::from_str(content).context("Invalid Cargo.toml")?;
+    let workspace = manifest.get("workspace");
+    let inherited_package = workspace.and_then(|w| w.get("package"));
+    let inherited_dependencies = workspace.and_then(|w| w.get("dependencies"));
+    let package = manifest.get("package");
+    if package.is_none() && workspace.is_none() {
+        anyhow::bail!("Cargo.toml has neither [package] nor [workspace]");
+    }
+
+    let field = |key: &str| -> Option<&toml::Value> {
+        let value = package?.get(key)?;
+        if value.get("workspace").and_then(|w| w.as_bool()) == Some(true) {
+            inherited_package?.get(key)
+        } else {
+            Some(value)
+        }
+    };
+    let string = |key: &str| field(key).and_then(|v| v.as_str()).map(String::from);
+    let strings = |key: &str| -> Vec<String> {
+        field(key)
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    let name = match package {
+        Some(_) => string("name").ok_or_else(|| anyhow::anyhow!("Missing package name"))?,
+        None => root
+            .and_then(|root| root.canonicalize().ok())
+            .and_then(|root| root.file_name()?.to_str().map(String::from))
+            .unwrap_or_else(|| "workspace".to_string()),
+    };
+    let version = match package {
+        Some(_) => string("version"),
+        None => inherited_package
+            .and_then(|p| p.get("version"))
+            .and_then(|v| v.as_str())
+            .map(String::from),
+    };
+    let authors = strings("authors");
+    let metadata = PackageMetadata {
+        name,
+        version: version.unwrap_or_else(|| "unknown".to_string()),
+        description: string("description"),
+        author: (!authors.is_empty()).then(|| authors.join(", ")),
+        license: string("license").or_else(|| string("license-file")),
+        homepage: string("homepage"),
+        repository: string("repository"),
+        keywords: strings("keywords"),
+        publish_date: None,
+    };
+
+    // A build.rs next to the manifest is built unless `build = false`
+    let build_script = match field("build") {
+        Some(toml::Value::String(path)) => Some(path.clone()),
+        Some(toml::Value::Boolean(false)) => None,
+        _ if package.is_some() && root.is_some_and(|root| root.join("build.rs").is_file()) => {
+            Some("build.rs".to_string())
+        }
+        _ => None,
+    };
+    let is_proc_macro = manifest
+        .get("lib")
+        .and_then(|lib| lib.get("proc-macro"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    // Target-specific tables declare dependencies the same way
+    let mut tables: Vec<(&toml::Value, &DependencyType)> = Vec::new();
+    let scopes = std::iter::once(&manifest).chain(
+        manifest
+            .get("target")
+            .and_then(|t| t.as_table())
+            .into_iter()
+            .flat_map(|targets| targets.values()),
+    );
+    for scope in scopes {
+        for (table, dependency_type) in DEPENDENCY_TABLES {
+            if let Some(deps) = scope.get(*table) {
+                tables.push((deps, dependency_type));
+            }
+        }
+    }
+    if package.is_none() {
+        tables.extend(inherited_dependencies.map(|deps| (deps, &DependencyType::Runtime)));
+    }
+    let declared_dependencies = tables
+        .into_iter()
+        .filter_map(|(deps, dependency_type)| {
+            Some(deps.as_table()?.iter().map(move |(key, spec)| {
+                let spec = match spec.get("workspace").and_then(|w| w.as_bool()) {
+                    Some(true) => inherited_dependencies
+                        .and_then(|deps| deps.get(key))
+                        .unwrap_or(spec),
+                    _ => spec,
+                };
+                declared_dependency(key, spec, dependency_type.clone())
+            }))
+        })
+        .flatten()
+        .collect();
+
+    Ok(CargoPackage {
+        metadata,
+        edition: string("edition"),
+        rust_version: string("rust-version"),
+        build_script,
+        is_proc_macro,
+        workspace_members: workspace
+            .and_then(|w| w.get("members"))
+            .and_then(|m| m.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        declared_dependencies,
+        has_lock_file: false,
+        skipped_files: vec![],
+    })
+}
+
+/// Read one dependency declaration, either `"1.0"` or a table
+fn declared_dependency(
+    key: &str,
+    spec: &toml::Value,
+    dependency_type: DependencyType,
+) -> DeclaredDependency {
+    let get = |field: &str| spec.get(field).and_then(|v| v.as_str());
+    DeclaredDependency {
+        name: get("package").unwrap_or(key).to_string(),
+        version_spec: spec
+            .as_str()
+            .or_else(|| get("version"))
+            .unwrap_or("*")
+            .to_string(),
+        dependency_type,
+        optional: spec
+            .get("optional")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        source: get("git").or_else(|| get("path")).map(String::from),
+    }
+}
@@ -0,0 +1,305 @@
+//! Synchronous analysis of a single manifest held in memory
+//!
+//! [`analyze_manifest`] is for hosts without an async runtime or a filesystem,
+//! such as a browser running the crate as WebAssembly, and is what remains
+//! with the `runtime` feature off. It parses one manifest from bytes and
+//! reports only what the manifest itself shows: its metadata, install hooks,
+//! typosquatting and generated names, and malicious patterns in its text.
+//! Nothing is read from disk and no request is made, so there are no
+//! vulnerability lookups, lockfiles or dependency trees; use
+//! [`crate::PackageSecurityAnalyzer::analyze`] for a full analysis.
+
+pub(crate) mod cargo;
+pub(crate) mod npm;
+pub(crate) mod python;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use self::npm::SCOPE_CONFUSION_CONFIDENCE;
+use crate::core::{
+    AnalysisOptions, AnalysisResult, DependencyAnalysis, InstallHook, MaliciousPattern,
+    MetadataInjection, PackageInfo, PackageMetadata, PatternMatcher, PhaseRecord, RiskAssessment,
+    RiskCalculator, ScanContext, ScanReason, SecurityPosture, TyposquattingRisk, Vulnerability,
+};
+use crate::ecosystem::Ecosystem;
+use crate::error::PackageSecurityError;
+use crate::utils::generated_names::GeneratedNameDetector;
+use crate::utils::typosquatting::{
+    SimilarPackage, TyposquattingDetector, TyposquattingReason, TyposquattingTechnique,
+};
+
+/// Supply chain component of a manifest that runs code at install time
+const INSTALL_HOOK_SCORE: f32 = 40.0;
+
+/// Package described by a manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestPackage {
+    pub metadata: PackageMetadata,
+    /// Ecosystem name, as the matching analyzer reports it
+    pub package_type: String,
+    /// File name of the manifest, such as `package.json`
+    pub manifest: String,
+}
+
+impl PackageInfo for ManifestPackage {
+    fn metadata(&self) -> &PackageMetadata {
+        &self.metadata
+    }
+
+    fn package_type(&self) -> &str {
+        &self.package_type
+    }
+
+    fn custom_attributes(&self) -> HashMap<String, serde_json::Value> {
+        let mut attrs = HashMap::new();
+        attrs.insert("manifest".to_string(), serde_json::json!(self.manifest));
+        attrs
+    }
+}
+
+/// Result of [`analyze_manifest`]
+///
+/// Vulnerabilities and the dependency analysis are always empty.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestAnalysisResult {
+    pub package: ManifestPackage,
+    pub risk_assessment: RiskAssessment,
+    pub dependency_analysis: DependencyAnalysis,
+    pub malicious_patterns: Vec<MaliciousPattern>,
+    pub typosquatting_risk: Option<TyposquattingRisk>,
+    pub install_hooks: Vec<InstallHook>,
+}
+
+impl AnalysisResult for ManifestAnalysisResult {
+    fn package_info(&self) -> &dyn PackageInfo {
+        &self.package
+    }
+
+    fn risk_assessment(&self) -> &RiskAssessment {
+        &self.risk_assessment
+    }
+
+    fn risk_assessment_mut(&mut self) -> &mut RiskAssessment {
+        &mut self.risk_assessment
+    }
+
+    fn dependency_analysis(&self) -> &DependencyAnalysis {
+        &self.dependency_analysis
+    }
+
+    fn vulnerabilities(&self) -> &[Vulnerability] {
+        &[]
+    }
+
+    fn malicious_patterns(&self) -> &[MaliciousPattern] {
+        &self.malicious_patterns
+    }
+
+    fn to_json(&self) -> anyhow::Result<serde_json::Value> {
+        Ok(serde_json::to_value(self)?)
+    }
+
+    fn typosquatting_risk(&self) -> Option<TyposquattingRisk> {
+        self.typosquatting_risk.clone()
+    }
+
+    fn install_hooks(&self) -> Vec<InstallHook> {
+        self.install_hooks.clone()
+    }
+}
+
+/// Analyze a manifest with the default options
+///
+/// `package_type` is an ecosystem name as [`Ecosystem::from_name`] accepts
+/// it; `npm` takes a `package.json`, `python` a `setup.py` and `cargo` a
+/// `Cargo.toml`. Other ecosystems are [`PackageSecurityError::Unsupported`].
+pub fn analyze_manifest(
+    package_type: &str,
+    bytes: &[u8],
+) -> Result<ManifestAnalysisResult, PackageSecurityError> {
+    analyze_manifest_with_options(package_type, bytes, &AnalysisOptions::default())
+}
+
+/// Analyze a manifest with custom options
+///
+/// Options that need the filesystem or network, such as custom pattern rules,
+/// typosquatting target files or repository validation, are ignored.
+pub fn analyze_manifest_with_options(
+    package_type: &str,
+    bytes: &[u8],
+    options: &AnalysisOptions,
+) -> Result<ManifestAnalysisResult, PackageSecurityError> {
+    let ecosystem = Ecosystem::from_name(package_type);
+    let manifest = match ecosystem {
+        Ecosystem::Npm => "package.json",
+        Ecosystem::Python => "setup.py",
+        Ecosystem::Cargo => "Cargo.toml",
+        _ => {
+            return Err(PackageSecurityError::Unsupported {
+                path: PathBuf::from(package_type),
+            })
+        }
+    };
+    let content =
+        std::str::from_utf8(bytes).map_err(|error| PackageSecurityError::ManifestParse {
+            message: format!("{} is not UTF-8: {}", manifest, error),
+        })?;
+
+    let (metadata, package_type, install_hooks) = match ecosystem {
+        Ecosystem::Npm => {
+            options.limits.check_json(manifest, content)?;
+            let package = npm::parse_package_json(content)?;
+            let hooks = npm::install_hooks(&package.scripts);
+            (package.metadata, "npm", hooks)
+        }
+        Ecosystem::Python => {
+            let metadata = python::parse_setup_py(content)?;
+            let hooks = python::analyze_setup(content).install_hooks();
+            (metadata, "python", hooks)
+        }
+        _ => {
+            let package = cargo::parse_manifest(None, content)?;
+            let hooks = package.install_hooks();
+            (package.metadata, "cargo", hooks)
+        }
+    };
+    let mut package = ManifestPackage {
+        metadata,
+        package_type: package_type.to_string(),
+        manifest: manifest.to_string(),
+    };
+
+    let malicious_patterns = if options.scan_malicious_patterns {
+        let matcher = PatternMatcher::new()?;
+        let mut patterns = matcher.scan(content, Some(manifest));
+        for pattern in matcher.scan_encoded(content, Some(manifest)) {
+            pattern.merge_into(&mut patterns);
+        }
+        patterns
+    } else {
+        vec![]
+    };
+
+    let typosquatting_risk = options
+        .detect_typosquatting
+        .then(|| typosquatting_risk(ecosystem, package_type, &package.metadata.name, options))
+        .flatten();
+
+    // Random-looking names, more telling when the package also runs at install
+    let first_hook = install_hooks.first().map(|hook| hook.hook.as_str());
+    let mut findings: Vec<_> = GeneratedNameDetector::new()
+        .check(&package.metadata, first_hook, options)
+        .into_iter()
+        .collect();
+    let name_findings = findings.len();
+    findings.extend(MetadataInjection::apply(
+        &mut package.metadata,
+        options.sanitize_metadata,
+    ));
+
+    let risk_calculator = RiskCalculator::from_options(options);
+    let mut risk_score = risk_calculator.calculate(
+        &[],
+        &malicious_patterns,
+        typosquatting_risk.is_some(),
+        if install_hooks.is_empty() {
+            0.0
+        } else {
+            INSTALL_HOOK_SCORE
+        },
+        50.0, // Default maintenance score
+    );
+    risk_calculator.apply_findings(&mut risk_score, &findings);
+    risk_calculator.apply_install_hooks(&mut risk_score, &install_hooks);
+
+    let scan_phases = vec![
+        PhaseRecord::new(ScanReason::DependencyAnalysis, false, 0),
+        PhaseRecord::new(ScanReason::VulnerabilityCheck, false, 0),
+        PhaseRecord::new(
+            ScanReason::MaliciousPatterns,
+            options.scan_malicious_patterns,
+            malicious_patterns.len(),
+        ),
+        PhaseRecord::new(
+            ScanReason::Typosquatting,
+            options.detect_typosquatting,
+            usize::from(typosquatting_risk.is_some()) + name_findings,
+        ),
+    ];
+    let risk_assessment = RiskAssessment {
+        risk_score: risk_score.clone(),
+        summary: format!(
+            "{} '{}' has {} risk with {} malicious patterns; dependencies and vulnerabilities were not checked",
+            manifest,
+            package.metadata.name,
+            risk_score.risk_level,
+            malicious_patterns.len()
+        ),
+        detailed_findings: findings,
+        recommendations: vec![],
+        security_posture: SecurityPosture {
+            vulnerabilities_present: false,
+            malicious_code_detected: !malicious_patterns.is_empty(),
+            supply_chain_risks: !install_hooks.is_empty(),
+            actively_maintained: true,
+            trusted_publisher: false,
+            security_practices_score: 50.0,
+        },
+        scan_phases,
+        scoped_risk: None,
+        scan_context: Some(ScanContext::current(None)),
+    };
+
+    Ok(ManifestAnalysisResult {
+        package,
+        risk_assessment,
+        dependency_analysis: DependencyAnalysis::default(),
+        malicious_patterns,
+        typosquatting_risk,
+        install_hooks,
+    })
+}
+
+/// Popular package `name` may impersonate, scope confusion first on npm
+fn typosquatting_risk(
+    ecosystem: Ecosystem,
+    package_type: &str,
+    name: &str,
+    options: &AnalysisOptions,
+) -> Option<TyposquattingRisk> {
+    let detector = TyposquattingDetector::for_ecosystem(package_type);
+    let scope_confusion = (ecosystem == Ecosystem::Npm)
+        .then(|| detector.scope_confusion(name))
+        .flatten()
+        .filter(|_| SCOPE_CONFUSION_CONFIDENCE >= options.typosquatting_min_confidence);
+    let (similar, reason) = match scope_confusion {
+        Some((canonical, reason)) => (
+            vec![SimilarPackage::new(
+                name,
+                &canonical,
+                SCOPE_CONFUSION_CONFIDENCE,
+                vec![TyposquattingTechnique::ScopeConfusion],
+            )],
+            reason,
+        ),
+        None => (
+            detector.similar_within(
+                name,
+                options.typosquatting_max_distance,
+                options.typosquatting_min_confidence,
+            ),
+            TyposquattingReason::SimilarName,
+        ),
+    };
+    let best = similar.first()?;
+    Some(TyposquattingRisk {
+        is_potential_typosquatting: true,
+        confidence_score: best.confidence,
+        techniques: best.techniques.clone(),
+        similar_packages: similar,
+        reason,
+    })
+}
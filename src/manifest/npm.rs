@@ -0,0 +1,169 @@
+//! `package.json` parsing
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::core::{InstallHook, PackageInfo, PackageMetadata, Provenance, RepositoryValidation};
+
+/// Confidence that a scope confusion impersonates the package it names
+pub(crate) const SCOPE_CONFUSION_CONFIDENCE: f32 = 0.9;
+
+/// Lifecycle scripts npm runs automatically on install
+pub(crate) const INSTALL_HOOKS: &[&str] = &["preinstall", "install", "postinstall", "prepare"];
+
+/// NPM package information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NpmPackage {
+    pub metadata: PackageMetadata,
+    pub main: Option<String>,
+    pub scripts: HashMap<String, String>,
+    pub engines: HashMap<String, String>,
+    pub files: Vec<String>,
+    pub private: bool,
+    /// Outcome of checking the `repository` link, when the check ran
+    #[serde(default)]
+    pub repository_validation: Option<RepositoryValidation>,
+    /// Outcome of verifying published provenance, when the check ran
+    #[serde(default)]
+    pub provenance: Option<Provenance>,
+}
+
+impl PackageInfo for NpmPackage {
+    fn metadata(&self) -> &PackageMetadata {
+        &self.metadata
+    }
+
+    fn package_type(&self) -> &str {
+        "npm"
+    }
+
+    fn custom_attributes(&self) -> HashMap<String, serde_json::Value> {
+        let mut attrs = HashMap::new();
+        attrs.insert("main".to_string(), serde_json::json!(self.main));
+        attrs.insert("scripts".to_string(), serde_json::json!(self.scripts));
+        attrs.insert("engines".to_string(), serde_json::json!(self.engines));
+        attrs.insert("private".to_string(), serde_json::json!(self.private));
+        if let Some(validation) = &self.repository_validation {
+            attrs.insert(
+                "repository_validation".to_string(),
+                serde_json::json!(validation),
+            );
+        }
+        if let Some(provenance) = &self.provenance {
+            attrs.insert("provenance".to_string(), serde_json::json!(provenance));
+        }
+        attrs
+    }
+}
+
+/// Parse package.json file
+pub(crate) fn parse_package_json(content: &str) -> Result<NpmPackage> {
+    let json: Value = serde_json::from_str(content).context("Failed to parse package.json")?;
+
+    let obj = json
+        .as_object()
+        .ok_or_else(|| anyhow::anyhow!("package.json is not an object"))?;
+
+    let metadata = PackageMetadata {
+        name: obj
+            .get("name")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string(),
+        version: obj
+            .get("version")
+            .and_then(|v| v.as_str())
+            .unwrap_or("0.0.0")
+            .to_string(),
+        description: obj
+            .get("description")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        author: obj.get("author").and_then(|v| v.as_str()).map(String::from),
+        license: obj
+            .get("license")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        homepage: obj
+            .get("homepage")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        repository: obj.get("repository").and_then(|v| {
+            if let Some(s) = v.as_str() {
+                Some(s.to_string())
+            } else if let Some(obj) = v.as_object() {
+                obj.get("url").and_then(|u| u.as_str()).map(String::from)
+            } else {
+                None
+            }
+        }),
+        keywords: obj
+            .get("keywords")
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        publish_date: None,
+    };
+
+    let scripts = obj
+        .get("scripts")
+        .and_then(|v| v.as_object())
+        .map(|obj| {
+            obj.iter()
+                .map(|(k, v)| (k.clone(), v.as_str().unwrap_or("").to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let engines = obj
+        .get("engines")
+        .and_then(|v| v.as_object())
+        .map(|obj| {
+            obj.iter()
+                .map(|(k, v)| (k.clone(), v.as_str().unwrap_or("").to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let files = obj
+        .get("files")
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(NpmPackage {
+        metadata,
+        main: obj.get("main").and_then(|v| v.as_str()).map(String::from),
+        scripts,
+        engines,
+        files,
+        private: obj
+            .get("private")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        repository_validation: None,
+        provenance: None,
+    })
+}
+
+/// Install hooks among `scripts`, in the order npm runs them
+pub(crate) fn install_hooks(scripts: &HashMap<String, String>) -> Vec<InstallHook> {
+    INSTALL_HOOKS
+        .iter()
+        .filter_map(|hook| {
+            scripts
+                .get(*hook)
+                .map(|command| InstallHook::new(*hook, command, "package.json"))
+        })
+        .collect()
+}
@@ -0,0 +1,140 @@
+//! `setup.py` parsing
+
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::core::{InstallHook, PackageMetadata};
+
+/// `cmdclass` in a `setup()` call: a dict literal, a `dict(...)` call or a name
+static CMDCLASS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"cmdclass\s*=\s*(\{[^}]*\}|dict\([^)]*\)|[A-Za-z_][\w.]*)").unwrap());
+
+/// `'command': Class` or `command=Class` inside a `cmdclass` mapping
+static CMDCLASS_ENTRY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"['"]?([A-Za-z_][\w-]*)['"]?\s*[:=]\s*([A-Za-z_][\w.]*)"#).unwrap());
+
+/// Python setup.py analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupAnalysis {
+    pub has_setup_py: bool,
+    pub has_custom_commands: bool,
+    pub dangerous_operations: Vec<String>,
+    pub external_downloads: Vec<String>,
+    pub code_execution_risk: bool,
+    /// Setup commands `cmdclass` overrides, with the class run for each;
+    /// keyed `cmdclass` when the mapping is built elsewhere
+    #[serde(default)]
+    pub custom_commands: BTreeMap<String, String>,
+}
+
+impl SetupAnalysis {
+    /// Commands `setup.py` replaces with its own code, which runs when the
+    /// package is built from source
+    pub fn install_hooks(&self) -> Vec<InstallHook> {
+        self.custom_commands
+            .iter()
+            .map(|(command, class)| InstallHook::new(command, class, "setup.py"))
+            .collect()
+    }
+}
+
+/// Parse setup.py file
+pub(crate) fn parse_setup_py(content: &str) -> Result<PackageMetadata> {
+    // Simple regex-based extraction
+    let name = extract_setup_field(content, "name")?;
+    let version = extract_setup_field(content, "version")?;
+
+    Ok(PackageMetadata {
+        name,
+        version,
+        description: extract_setup_field(content, "description").ok(),
+        author: extract_setup_field(content, "author").ok(),
+        license: extract_setup_field(content, "license").ok(),
+        homepage: extract_setup_field(content, "url").ok(),
+        repository: None,
+        keywords: vec![], // TODO: Parse keywords list
+        publish_date: None,
+    })
+}
+
+/// Extract field from setup.py using regex
+fn extract_setup_field(content: &str, field: &str) -> Result<String> {
+    let pattern = format!(r#"{}\s*=\s*["']([^"']+)["']"#, field);
+    let re = regex::Regex::new(&pattern)?;
+
+    re.captures(content)
+        .and_then(|cap| cap.get(1))
+        .map(|m| m.as_str().to_string())
+        .ok_or_else(|| anyhow::anyhow!("Field '{}' not found", field))
+}
+
+/// Analyze setup.py for dangerous operations
+pub(crate) fn analyze_setup(content: &str) -> SetupAnalysis {
+    let mut analysis = SetupAnalysis {
+        has_setup_py: true,
+        has_custom_commands: false,
+        dangerous_operations: vec![],
+        external_downloads: vec![],
+        code_execution_risk: false,
+        custom_commands: BTreeMap::new(),
+    };
+
+    // Check for custom commands
+    if content.contains("cmdclass") {
+        analysis.has_custom_commands = true;
+        // A mapping passed by name is usually built further up the file
+        let mut names = Vec::new();
+        for mapping in CMDCLASS.captures_iter(content) {
+            let mapping = &mapping[1];
+            if mapping.starts_with('{') || mapping.starts_with("dict(") {
+                for entry in CMDCLASS_ENTRY.captures_iter(mapping) {
+                    analysis
+                        .custom_commands
+                        .insert(entry[1].to_string(), entry[2].to_string());
+                }
+            } else {
+                names.push(mapping.to_string());
+            }
+        }
+        if analysis.custom_commands.is_empty() {
+            if let Some(name) = names.pop() {
+                analysis
+                    .custom_commands
+                    .insert("cmdclass".to_string(), name);
+            }
+        }
+        analysis
+            .dangerous_operations
+            .push("Custom setup commands detected".to_string());
+    }
+
+    // Check for dangerous operations
+    let dangerous_patterns = [
+        ("subprocess", "Process execution"),
+        ("os.system", "System command execution"),
+        ("exec", "Dynamic code execution"),
+        ("eval", "Code evaluation"),
+        ("__import__", "Dynamic imports"),
+        ("urllib", "Network access"),
+        ("requests", "HTTP requests"),
+    ];
+
+    for (pattern, description) in &dangerous_patterns {
+        if content.contains(pattern) {
+            analysis.dangerous_operations.push(description.to_string());
+            analysis.code_execution_risk = true;
+        }
+    }
+
+    // Check for external downloads
+    if content.contains("urlopen") || content.contains("requests.get") {
+        analysis
+            .external_downloads
+            .push("External download detected".to_string());
+    }
+
+    analysis
+}
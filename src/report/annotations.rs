@@ -0,0 +1,232 @@
+//! GitHub Actions annotations for findings new since a baseline
+//!
+//! Each finding becomes a workflow command such as
+//! `::error file=package.json,line=12,title=...::message`, which GitHub shows
+//! inline on the pull request diff. Findings carry no source positions, so an
+//! annotation goes on the first manifest or lockfile line naming the finding's
+//! affected component or target, and on line 1 of the manifest otherwise.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::io::Write;
+
+use crate::core::{AnalysisResult, Finding, RiskLevel};
+
+/// Findings already present before a change, by [`Finding::fingerprint`]
+///
+/// Counted rather than deduplicated, so a second finding with the fingerprint
+/// of a baseline one still counts as new, as in [`crate::core::ResultDiff`].
+#[derive(Debug, Clone, Default)]
+pub struct FindingBaseline {
+    fingerprints: HashMap<String, usize>,
+}
+
+impl FindingBaseline {
+    /// Baseline of every finding in a previous result
+    pub fn from_result(result: &dyn AnalysisResult) -> Self {
+        Self::from_fingerprints(result.query().iter().map(|finding| finding.fingerprint()))
+    }
+
+    /// Baseline from stored fingerprints
+    pub fn from_fingerprints<I, S>(fingerprints: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut baseline = Self::default();
+        for fingerprint in fingerprints {
+            *baseline.fingerprints.entry(fingerprint.into()).or_default() += 1;
+        }
+        baseline
+    }
+
+    /// Findings of `result` not accounted for by the baseline
+    pub fn new_findings(&self, result: &dyn AnalysisResult) -> Vec<Finding> {
+        let mut remaining = self.fingerprints.clone();
+        result
+            .query()
+            .iter()
+            .filter(|finding| match remaining.get_mut(&finding.fingerprint()) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            })
+            .collect()
+    }
+}
+
+/// Formatter for GitHub Actions annotation commands
+pub struct GithubAnnotations {
+    /// Files annotations may point at, as `(path, content)`; the first is the manifest
+    sources: Vec<(String, String)>,
+    baseline: Option<FindingBaseline>,
+    min_severity: Option<RiskLevel>,
+}
+
+impl GithubAnnotations {
+    /// Annotate against a manifest, given by its path relative to the repository root
+    pub fn new(manifest_path: impl Into<String>, manifest: impl Into<String>) -> Self {
+        Self {
+            sources: vec![(manifest_path.into(), manifest.into())],
+            baseline: None,
+            min_severity: None,
+        }
+    }
+
+    /// Also place annotations on a lockfile, searched after the manifest
+    pub fn with_lockfile(mut self, path: impl Into<String>, content: impl Into<String>) -> Self {
+        self.sources.push((path.into(), content.into()));
+        self
+    }
+
+    /// Only annotate findings not in the baseline
+    pub fn with_baseline(mut self, baseline: FindingBaseline) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+
+    /// Only annotate findings at or above a severity
+    pub fn min_severity(mut self, severity: RiskLevel) -> Self {
+        self.min_severity = Some(severity);
+        self
+    }
+
+    /// One annotation command per finding to report
+    pub fn annotations(&self, result: &dyn AnalysisResult) -> Vec<String> {
+        let findings = match &self.baseline {
+            Some(baseline) => baseline.new_findings(result),
+            None => result.query().collect(),
+        };
+        findings
+            .iter()
+            .filter(|finding| self.min_severity.is_none_or(|min| finding.severity >= min))
+            .map(|finding| self.annotate(finding))
+            .collect()
+    }
+
+    /// Write the annotations one per line, returning how many were written
+    pub fn write<W: Write>(&self, writer: &mut W, result: &dyn AnalysisResult) -> Result<usize> {
+        let annotations = self.annotations(result);
+        for annotation in &annotations {
+            writeln!(writer, "{}", annotation)?;
+        }
+        writer.flush()?;
+        Ok(annotations.len())
+    }
+
+    /// Format one finding as an annotation command
+    pub fn annotate(&self, finding: &Finding) -> String {
+        let level = match finding.severity {
+            RiskLevel::Critical | RiskLevel::High => "error",
+            RiskLevel::Medium => "warning",
+            RiskLevel::Low | RiskLevel::Safe => "notice",
+        };
+        let (file, line) = self.locate(finding);
+        let title = match &finding.rule_id {
+            Some(rule_id) => format!("{}: {}", rule_id, finding.title),
+            None => finding.title.clone(),
+        };
+        format!(
+            "::{} file={},line={},title={}::{}",
+            level,
+            escape_property(file),
+            line,
+            escape_property(&title),
+            escape_data(&finding.description)
+        )
+    }
+
+    /// Where to annotate a finding, falling back to the manifest's first line
+    fn locate(&self, finding: &Finding) -> (&str, usize) {
+        locate_finding(finding, &self.sources).unwrap_or((&self.sources[0].0, 1))
+    }
+}
+
+/// First line of `sources`, given as `(path, content)`, naming the finding's
+/// affected components or targets
+pub(crate) fn locate_finding<'a>(
+    finding: &Finding,
+    sources: &'a [(String, String)],
+) -> Option<(&'a str, usize)> {
+    let targets = finding
+        .evidence
+        .iter()
+        .filter_map(|evidence| evidence.strip_prefix("Target: "));
+    let needles: Vec<&str> = finding
+        .affected_components
+        .iter()
+        .map(String::as_str)
+        .chain(targets)
+        .filter(|needle| !needle.is_empty())
+        .collect();
+
+    // A quoted name is a JSON key or value; fall back to any mention
+    for quoted in [true, false] {
+        for needle in &needles {
+            let needle = if quoted {
+                format!("\"{}\"", needle)
+            } else {
+                needle.to_string()
+            };
+            for (path, content) in sources {
+                if let Some(index) = content.lines().position(|line| line.contains(&needle)) {
+                    return Some((path, index + 1));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Escape a command message
+fn escape_data(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escape a command property, which also may not contain `:` or `,`
+fn escape_property(value: &str) -> String {
+    escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::FindingType;
+
+    #[test]
+    fn test_annotation_format() {
+        let manifest =
+            "{\n  \"name\": \"acme\",\n  \"dependencies\": {\n    \"chalk\": \"latest\"\n  }\n}";
+        let annotations = GithubAnnotations::new("web/package.json", manifest);
+
+        let finding = Finding::new(
+            FindingType::UnpinnedDependency,
+            RiskLevel::High,
+            "Unpinned dependency: chalk",
+            "Version 'latest' resolves to 100% of\nnew releases",
+        )
+        .with_affected_component("chalk")
+        .with_rule_id("TFX-DEP-001");
+        assert_eq!(
+            annotations.annotate(&finding),
+            "::error file=web/package.json,line=4,title=TFX-DEP-001%3A Unpinned dependency%3A chalk::Version 'latest' resolves to 100%25 of%0Anew releases"
+        );
+
+        // Nothing to point at lands on the manifest's first line
+        let finding = Finding::new(
+            FindingType::QualityIssue,
+            RiskLevel::Medium,
+            "No tests",
+            "None",
+        );
+        assert_eq!(
+            annotations.annotate(&finding),
+            "::warning file=web/package.json,line=1,title=No tests::None"
+        );
+    }
+}
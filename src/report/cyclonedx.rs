@@ -0,0 +1,199 @@
+//! CycloneDX 1.5 SBOM documents for analysis results
+//!
+//! The analyzed package is the document's `metadata.component`; every entry of
+//! the dependency tree becomes a component identified by its package URL
+//! (`pkg:npm/lodash@4.17.21`), which also serves as its `bom-ref`. A package
+//! found at several places in the tree is listed once, and the `dependencies`
+//! section records the edges between them. Each advisory is listed once under
+//! `vulnerabilities`, by CVE when it has one, and names the components it
+//! affects.
+//!
+//! A dependency known only by a version range gets a package URL without a
+//! version. Ecosystems without a registered package URL type (Terraform,
+//! Helm, extensions) use `pkg:generic`; see [`super::sbom::purl`].
+
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+
+use super::sbom::{is_spdx_expression, PackageGraph, SbomPackage};
+use crate::core::{AnalysisResult, Vulnerability, VulnerabilitySeverity};
+
+/// CycloneDX specification version of the documents produced
+pub const SPEC_VERSION: &str = "1.5";
+
+/// CycloneDX JSON document for `result`
+pub fn to_cyclonedx<R: AnalysisResult + ?Sized>(result: &R) -> Result<Value> {
+    let metadata = result.package_info().metadata();
+    let graph = PackageGraph::from_result(result);
+
+    let mut root = component(&graph.root);
+    if let Some(description) = &metadata.description {
+        root["description"] = json!(description);
+    }
+    if let Some(root) = root.as_object_mut() {
+        root.remove("scope");
+    }
+
+    let components: Vec<Value> = graph.packages.iter().map(component).collect();
+    let dependencies: Vec<Value> = graph
+        .edges
+        .iter()
+        .map(|(reference, depends_on)| json!({ "ref": reference, "dependsOn": depends_on }))
+        .collect();
+
+    Ok(json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": SPEC_VERSION,
+        "version": 1,
+        "metadata": {
+            "tools": {
+                "components": [{
+                    "type": "application",
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                }]
+            },
+            "component": root,
+        },
+        "components": components,
+        "dependencies": dependencies,
+        "vulnerabilities": graph
+            .vulnerabilities
+            .iter()
+            .map(|(vulnerability, affects)| vulnerability_entry(vulnerability, affects))
+            .collect::<Vec<_>>(),
+    }))
+}
+
+/// Component entry for `package`
+///
+/// A license that is not a valid SPDX expression is given by name instead.
+fn component(package: &SbomPackage) -> Value {
+    let mut component = json!({
+        "type": "library",
+        "bom-ref": package.reference,
+        "name": package.name,
+        "purl": package.reference,
+        "scope": if package.is_dev { "excluded" } else { "required" },
+    });
+    if let Some(version) = &package.version {
+        component["version"] = json!(version);
+    }
+    if let Some(license) = &package.license {
+        component["licenses"] = if is_spdx_expression(license) {
+            json!([{ "expression": license }])
+        } else {
+            json!([{ "license": { "name": license } }])
+        };
+    }
+    component
+}
+
+/// Entry in the `vulnerabilities` section
+fn vulnerability_entry(vulnerability: &Vulnerability, affects: &[String]) -> Value {
+    let id = vulnerability.cve_id();
+    let mut entry = Map::new();
+    entry.insert("bom-ref".into(), json!(vulnerability.id));
+    entry.insert("id".into(), json!(id));
+    entry.insert("source".into(), source(id));
+
+    let other_ids: Vec<Value> = std::iter::once(&vulnerability.id)
+        .chain(&vulnerability.aliases)
+        .filter(|other| other.as_str() != id)
+        .map(|other| json!({ "id": other, "source": source(other) }))
+        .collect();
+    if !other_ids.is_empty() {
+        entry.insert("references".into(), json!(other_ids));
+    }
+
+    let severity = match vulnerability.severity {
+        VulnerabilitySeverity::Critical => "critical",
+        VulnerabilitySeverity::High => "high",
+        VulnerabilitySeverity::Medium => "medium",
+        VulnerabilitySeverity::Low => "low",
+        VulnerabilitySeverity::None => "none",
+    };
+    let mut rating = json!({ "severity": severity });
+    match vulnerability.cvss_score {
+        Some(score) => {
+            rating["score"] = json!(score);
+            rating["method"] = json!(cvss_method(vulnerability.cvss_vector.as_deref()));
+            if let Some(vector) = &vulnerability.cvss_vector {
+                rating["vector"] = json!(vector);
+            }
+        }
+        None => rating["method"] = json!("other"),
+    }
+    entry.insert("ratings".into(), json!([rating]));
+
+    let cwes: Vec<u32> = vulnerability
+        .cwe_ids
+        .iter()
+        .filter_map(|cwe| cwe.strip_prefix("CWE-")?.parse().ok())
+        .collect();
+    if !cwes.is_empty() {
+        entry.insert("cwes".into(), json!(cwes));
+    }
+    if !vulnerability.title.is_empty() && vulnerability.title != vulnerability.id {
+        entry.insert("description".into(), json!(vulnerability.title));
+    }
+    if !vulnerability.description.is_empty() {
+        entry.insert("detail".into(), json!(vulnerability.description));
+    }
+    if !vulnerability.fixed_versions.is_empty() {
+        entry.insert(
+            "recommendation".into(),
+            json!(format!(
+                "Upgrade to {}",
+                vulnerability.fixed_versions.join(" or ")
+            )),
+        );
+    }
+    if !vulnerability.references.is_empty() {
+        let advisories: Vec<Value> = vulnerability
+            .references
+            .iter()
+            .map(|url| json!({ "url": url }))
+            .collect();
+        entry.insert("advisories".into(), json!(advisories));
+    }
+    if let Some(published) = vulnerability.published_date {
+        entry.insert("published".into(), json!(published.to_rfc3339()));
+    }
+    if let Some(updated) = vulnerability.updated_date {
+        entry.insert("updated".into(), json!(updated.to_rfc3339()));
+    }
+    let affects: Vec<Value> = affects
+        .iter()
+        .map(|reference| json!({ "ref": reference }))
+        .collect();
+    entry.insert("affects".into(), json!(affects));
+    Value::Object(entry)
+}
+
+/// Database an advisory ID comes from
+fn source(id: &str) -> Value {
+    if id.starts_with("CVE-") {
+        json!({ "name": "NVD", "url": format!("https://nvd.nist.gov/vuln/detail/{}", id) })
+    } else if id.starts_with("GHSA-") {
+        json!({ "name": "GitHub", "url": format!("https://github.com/advisories/{}", id) })
+    } else if ["PYSEC-", "RUSTSEC-", "GO-", "MAL-", "OSV-"]
+        .iter()
+        .any(|prefix| id.starts_with(prefix))
+    {
+        json!({ "name": "OSV", "url": format!("https://osv.dev/vulnerability/{}", id) })
+    } else {
+        json!({ "name": "ThreatFlux" })
+    }
+}
+
+/// CycloneDX scoring method for a CVSS vector
+fn cvss_method(vector: Option<&str>) -> &'static str {
+    match vector {
+        Some(v) if v.starts_with("CVSS:4") => "CVSSv4",
+        Some(v) if v.starts_with("CVSS:3.1") => "CVSSv31",
+        Some(v) if v.starts_with("CVSS:3") => "CVSSv3",
+        Some(_) => "CVSSv2",
+        None => "CVSSv3",
+    }
+}
@@ -0,0 +1,183 @@
+//! Newline-delimited JSON output for streaming ingestion
+//!
+//! Every line is a self-contained JSON object carrying the package identity,
+//! so lines can be shipped, filtered and parsed independently. Writers flush
+//! after each record so long batch scans stream instead of buffering. A
+//! package that fails to analyze gets an `error` line naming its path, so a
+//! batch's output accounts for every input.
+
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::fmt::Display;
+use std::io::Write;
+use std::path::Path;
+#[cfg(feature = "runtime")]
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::core::AnalysisResult;
+
+/// Identity of the package a line belongs to
+fn package_identity(result: &dyn AnalysisResult) -> Value {
+    let info = result.package_info();
+    json!({
+        "name": info.metadata().name,
+        "version": info.metadata().version,
+        "ecosystem": info.package_type(),
+    })
+}
+
+/// One record holding the full result
+fn result_record(result: &dyn AnalysisResult) -> Result<Value> {
+    Ok(json!({
+        "kind": "result",
+        "package": package_identity(result),
+        "result": result.to_json()?,
+    }))
+}
+
+/// One record for a package that could not be analyzed
+fn error_record(path: &Path, error: impl Display) -> Value {
+    json!({
+        "kind": "error",
+        "path": path.display().to_string(),
+        "error": error.to_string(),
+    })
+}
+
+/// One record per finding, vulnerability and malicious pattern
+fn finding_records(result: &dyn AnalysisResult) -> Result<Vec<Value>> {
+    let package = package_identity(result);
+    let mut records = Vec::new();
+    for finding in &result.risk_assessment().detailed_findings {
+        records.push(json!({
+            "kind": "finding",
+            "package": package,
+            "finding": serde_json::to_value(finding)?,
+        }));
+    }
+    for vulnerability in result.vulnerabilities() {
+        records.push(json!({
+            "kind": "vulnerability",
+            "package": package,
+            "vulnerability": serde_json::to_value(vulnerability)?,
+        }));
+    }
+    for pattern in result.malicious_patterns() {
+        records.push(json!({
+            "kind": "malicious_pattern",
+            "package": package,
+            "malicious_pattern": serde_json::to_value(pattern)?,
+        }));
+    }
+    Ok(records)
+}
+
+fn encode_line(record: &Value) -> Result<Vec<u8>> {
+    // Compact serde_json output never contains a raw newline
+    let mut line = serde_json::to_vec(record)?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+/// NDJSON writer over a blocking `Write`
+pub struct NdjsonWriter<W: Write> {
+    writer: W,
+    lines: usize,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    /// Wrap a writer
+    pub fn new(writer: W) -> Self {
+        Self { writer, lines: 0 }
+    }
+
+    /// Write the whole result as one line
+    pub fn write_result(&mut self, result: &dyn AnalysisResult) -> Result<()> {
+        self.write_record(&result_record(result)?)
+    }
+
+    /// Write one line per finding, returning the number of lines written
+    pub fn write_findings(&mut self, result: &dyn AnalysisResult) -> Result<usize> {
+        let records = finding_records(result)?;
+        for record in &records {
+            self.write_record(record)?;
+        }
+        Ok(records.len())
+    }
+
+    /// Write the error analyzing the package at `path` as one line
+    pub fn write_error(&mut self, path: &Path, error: impl Display) -> Result<()> {
+        self.write_record(&error_record(path, error))
+    }
+
+    /// Write an arbitrary record as one line and flush
+    pub fn write_record(&mut self, record: &Value) -> Result<()> {
+        self.writer.write_all(&encode_line(record)?)?;
+        self.writer.flush()?;
+        self.lines += 1;
+        Ok(())
+    }
+
+    /// Number of lines written so far
+    pub fn lines_written(&self) -> usize {
+        self.lines
+    }
+
+    /// Recover the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// NDJSON writer over a Tokio `AsyncWrite`
+#[cfg(feature = "runtime")]
+pub struct AsyncNdjsonWriter<W: AsyncWrite + Unpin + Send> {
+    writer: W,
+    lines: usize,
+}
+
+#[cfg(feature = "runtime")]
+impl<W: AsyncWrite + Unpin + Send> AsyncNdjsonWriter<W> {
+    /// Wrap a writer
+    pub fn new(writer: W) -> Self {
+        Self { writer, lines: 0 }
+    }
+
+    /// Write the whole result as one line
+    pub async fn write_result(&mut self, result: &dyn AnalysisResult) -> Result<()> {
+        let record = result_record(result)?;
+        self.write_record(&record).await
+    }
+
+    /// Write one line per finding, returning the number of lines written
+    pub async fn write_findings(&mut self, result: &dyn AnalysisResult) -> Result<usize> {
+        let records = finding_records(result)?;
+        for record in &records {
+            self.write_record(record).await?;
+        }
+        Ok(records.len())
+    }
+
+    /// Write the error analyzing the package at `path` as one line
+    pub async fn write_error(&mut self, path: &Path, error: impl Display) -> Result<()> {
+        self.write_record(&error_record(path, error)).await
+    }
+
+    /// Write an arbitrary record as one line and flush
+    pub async fn write_record(&mut self, record: &Value) -> Result<()> {
+        self.writer.write_all(&encode_line(record)?).await?;
+        self.writer.flush().await?;
+        self.lines += 1;
+        Ok(())
+    }
+
+    /// Number of lines written so far
+    pub fn lines_written(&self) -> usize {
+        self.lines
+    }
+
+    /// Recover the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
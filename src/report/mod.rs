@@ -0,0 +1,15 @@
+//! Output formats for analysis results
+
+pub mod annotations;
+pub mod cyclonedx;
+pub mod jsonl;
+pub mod sarif;
+pub mod sbom;
+pub mod spdx;
+pub mod text;
+
+pub use annotations::{FindingBaseline, GithubAnnotations};
+#[cfg(feature = "runtime")]
+pub use jsonl::AsyncNdjsonWriter;
+pub use jsonl::NdjsonWriter;
+pub use sarif::SarifReport;
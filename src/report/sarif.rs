@@ -0,0 +1,268 @@
+//! SARIF 2.1.0 logs for GitHub code scanning
+//!
+//! Each malicious pattern and each dependency vulnerability becomes a
+//! `result` under the finding's rule ID, with a `level` following its
+//! [`RiskLevel`] and [`Finding::fingerprint`] as its partial fingerprint, so
+//! code scanning tracks an alert across runs. Vulnerabilities point at the
+//! manifest, on the first manifest or lockfile line naming the affected
+//! package when their content is given. Patterns point at the line, column and
+//! byte offset of each match in its file, and at the manifest when the match
+//! has no file.
+//! The driver describes every rule the results use.
+//!
+//! Files the analyzers report are relative to the package root, which is taken
+//! to be the manifest's directory.
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use crate::core::{
+    AnalysisResult, Dependency, Finding, MaliciousPattern, RiskLevel, RuleDescriptor, RuleRegistry,
+};
+
+/// SARIF version of the logs produced
+pub const SARIF_VERSION: &str = "2.1.0";
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Key of [`Finding::fingerprint`] among a result's partial fingerprints
+const FINGERPRINT_KEY: &str = "threatfluxFinding/v1";
+
+/// SARIF log for `result`, located against the ecosystem's usual manifest name
+///
+/// No file contents are at hand, so vulnerabilities go on line 1 of the
+/// manifest; use [`SarifReport`] to supply the manifest and lockfile.
+pub fn to_sarif<R: AnalysisResult + ?Sized>(result: &R) -> Result<Value> {
+    let manifest = match result.package_info().package_type() {
+        "python" => "pyproject.toml",
+        "java" | "maven" => "pom.xml",
+        "terraform" => "main.tf",
+        "helm" => "Chart.yaml",
+        "cargo" => "Cargo.toml",
+        "go" => "go.mod",
+        "rubygems" => "Gemfile",
+        "composer" => "composer.json",
+        "browser-extension" => "manifest.json",
+        _ => "package.json",
+    };
+    SarifReport::new(manifest, "").report(result)
+}
+
+/// Formatter for SARIF logs
+pub struct SarifReport {
+    /// Files results may point at, as `(path, content)`; the first is the manifest
+    sources: Vec<(String, String)>,
+}
+
+impl SarifReport {
+    /// Report against a manifest, given by its path relative to the repository root
+    ///
+    /// Pass an empty `manifest` when its content is not at hand; results then
+    /// go on its line 1, as annotations do.
+    pub fn new(manifest_path: impl Into<String>, manifest: impl Into<String>) -> Self {
+        Self {
+            sources: vec![(manifest_path.into(), manifest.into())],
+        }
+    }
+
+    /// Also locate results in a lockfile, given by its path relative to the
+    /// repository root
+    pub fn with_file(mut self, path: impl Into<String>, content: impl Into<String>) -> Self {
+        self.sources.push((path.into(), content.into()));
+        self
+    }
+
+    /// SARIF log with one run for `result`
+    pub fn report<R: AnalysisResult + ?Sized>(&self, result: &R) -> Result<Value> {
+        let registry = RuleRegistry::builtin();
+        let mut rules: Vec<RuleDescriptor> = Vec::new();
+        let mut results = Vec::new();
+
+        let mut vulnerabilities: Vec<Finding> = Vec::new();
+        let mut advisories: Vec<&str> = Vec::new();
+        let mut stack: Vec<&Dependency> = result
+            .dependency_analysis()
+            .dependency_tree
+            .iter()
+            .rev()
+            .collect();
+        while let Some(dependency) = stack.pop() {
+            for vulnerability in &dependency.vulnerabilities {
+                vulnerabilities.push(Finding::from_vulnerability(vulnerability, &dependency.name));
+                advisories.push(&vulnerability.id);
+            }
+            stack.extend(dependency.dependencies.iter().rev());
+        }
+        // Advisories for the package itself rather than any one dependency
+        let package = result.package_info().name();
+        for vulnerability in result.vulnerabilities() {
+            if !advisories.contains(&vulnerability.id.as_str()) {
+                vulnerabilities.push(Finding::from_vulnerability(vulnerability, package));
+            }
+        }
+        let mut fingerprints = Vec::new();
+        for finding in vulnerabilities {
+            let fingerprint = finding.fingerprint();
+            if fingerprints.contains(&fingerprint) {
+                continue;
+            }
+            let (path, line) = self.locate(&finding);
+            let location = physical_location(path, json!({ "startLine": line }));
+            let rule_index = rule_index(&mut rules, &registry, &finding, None);
+            results.push(result_entry(&finding, rule_index, vec![location]));
+            fingerprints.push(fingerprint);
+        }
+
+        for pattern in result.malicious_patterns() {
+            let finding = Finding::from_pattern(pattern);
+            let mut locations: Vec<Value> = pattern
+                .locations
+                .iter()
+                .filter_map(|location| {
+                    let path = self.resolve(location.file.as_deref()?);
+                    let region = json!({
+                        "startLine": location.line,
+                        "startColumn": location.column,
+                        "byteOffset": location.offset,
+                    });
+                    Some(physical_location(&path, region))
+                })
+                .collect();
+            locations.dedup();
+            if locations.is_empty() {
+                let (path, line) = self.locate(&finding);
+                locations.push(physical_location(path, json!({ "startLine": line })));
+            }
+            let rule_index = rule_index(&mut rules, &registry, &finding, Some(pattern));
+            let mut entry = result_entry(&finding, rule_index, locations);
+            entry["properties"]["category"] = json!(format!("{:?}", pattern.category));
+            if !pattern.attack_techniques.is_empty() {
+                entry["properties"]["attackTechniques"] = json!(pattern.attack_techniques);
+            }
+            results.push(entry);
+        }
+
+        let rules: Vec<Value> = rules
+            .iter()
+            .map(|rule| {
+                json!({
+                    "id": rule.id,
+                    "name": rule.name,
+                    "shortDescription": { "text": rule.name },
+                    "fullDescription": { "text": rule.description },
+                    "properties": {
+                        "tags": ["security", format!("{:?}", rule.finding_type)],
+                    },
+                })
+            })
+            .collect();
+
+        Ok(json!({
+            "$schema": SARIF_SCHEMA,
+            "version": SARIF_VERSION,
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                        "rules": rules,
+                    }
+                },
+                "results": results,
+            }],
+        }))
+    }
+
+    /// File and line to report a finding at, falling back to the manifest's first line
+    fn locate(&self, finding: &Finding) -> (&str, usize) {
+        crate::report::annotations::locate_finding(finding, &self.sources)
+            .unwrap_or((&self.sources[0].0, 1))
+    }
+
+    /// Repository path of a file given relative to the package root
+    fn resolve(&self, file: &str) -> String {
+        let file = file.trim_start_matches("./");
+        if let Some((path, _)) = self
+            .sources
+            .iter()
+            .find(|(path, _)| path == file || path.ends_with(&format!("/{}", file)))
+        {
+            return path.clone();
+        }
+        match self.sources[0].0.rsplit_once('/') {
+            Some((directory, _)) => format!("{}/{}", directory, file),
+            None => file.to_string(),
+        }
+    }
+}
+
+/// SARIF level for a severity
+fn level(severity: RiskLevel) -> &'static str {
+    match severity {
+        RiskLevel::Critical | RiskLevel::High => "error",
+        RiskLevel::Medium => "warning",
+        RiskLevel::Low | RiskLevel::Safe => "note",
+    }
+}
+
+/// Index of the finding's rule in `rules`, adding it on first use
+///
+/// Rules missing from the registry, such as those of custom patterns, are
+/// described by the pattern or the finding itself.
+fn rule_index(
+    rules: &mut Vec<RuleDescriptor>,
+    registry: &RuleRegistry,
+    finding: &Finding,
+    pattern: Option<&MaliciousPattern>,
+) -> usize {
+    let id = finding
+        .rule_id
+        .clone()
+        .unwrap_or_else(|| format!("{:?}", finding.finding_type));
+    if let Some(index) = rules.iter().position(|rule| rule.id == id) {
+        return index;
+    }
+    let rule = registry
+        .get(&id)
+        .cloned()
+        .unwrap_or_else(|| RuleDescriptor {
+            name: pattern.map_or_else(|| finding.title.clone(), |p| p.pattern_name.clone()),
+            description: pattern
+                .map_or_else(|| finding.description.clone(), |p| p.description.clone()),
+            finding_type: finding.finding_type.clone(),
+            id,
+        });
+    rules.push(rule);
+    rules.len() - 1
+}
+
+/// Entry in a run's `results`
+fn result_entry(finding: &Finding, rule_index: usize, locations: Vec<Value>) -> Value {
+    let message = if finding.description.is_empty() {
+        finding.title.clone()
+    } else {
+        format!("{}\n{}", finding.title, finding.description)
+    };
+    json!({
+        "ruleId": finding.rule_id.clone().unwrap_or_else(|| format!("{:?}", finding.finding_type)),
+        "ruleIndex": rule_index,
+        "level": level(finding.severity),
+        "message": { "text": message },
+        "locations": locations,
+        "partialFingerprints": { FINGERPRINT_KEY: finding.fingerprint() },
+        "properties": {
+            "affectedComponents": finding.affected_components,
+            "evidence": finding.evidence,
+        },
+    })
+}
+
+fn physical_location(path: &str, region: Value) -> Value {
+    json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": path },
+            "region": region,
+        }
+    })
+}
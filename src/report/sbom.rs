@@ -0,0 +1,348 @@
+//! Package graph and identifiers shared by the SBOM formats
+//!
+//! [`super::cyclonedx`] and [`super::spdx`] describe the same thing: the
+//! analyzed package, every package in its dependency tree listed once under its
+//! package URL, the edges between them, and which packages each advisory
+//! affects.
+
+use std::collections::HashMap;
+
+use crate::core::{AnalysisResult, Dependency, Vulnerability};
+use crate::utils::names::normalize_name;
+use crate::versioning::VersionSchemeRegistry;
+
+/// SPDX license identifiers accepted in license expressions
+///
+/// The licenses packages commonly declare; expressions naming others fall
+/// back to "no assertion" rather than risk an invalid document. Custom
+/// licenses can always be given as `LicenseRef-...`.
+const SPDX_LICENSE_IDS: &[&str] = &[
+    "0BSD",
+    "AFL-3.0",
+    "AGPL-3.0",
+    "AGPL-3.0-only",
+    "AGPL-3.0-or-later",
+    "Apache-1.1",
+    "Apache-2.0",
+    "Artistic-2.0",
+    "BlueOak-1.0.0",
+    "BSD-1-Clause",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "BSD-3-Clause-Clear",
+    "BSL-1.0",
+    "CC-BY-3.0",
+    "CC-BY-4.0",
+    "CC-BY-SA-4.0",
+    "CC0-1.0",
+    "CDDL-1.0",
+    "CDDL-1.1",
+    "CPL-1.0",
+    "EPL-1.0",
+    "EPL-2.0",
+    "EUPL-1.2",
+    "GPL-2.0",
+    "GPL-2.0-only",
+    "GPL-2.0-or-later",
+    "GPL-3.0",
+    "GPL-3.0-only",
+    "GPL-3.0-or-later",
+    "ISC",
+    "LGPL-2.1",
+    "LGPL-2.1-only",
+    "LGPL-2.1-or-later",
+    "LGPL-3.0",
+    "LGPL-3.0-only",
+    "LGPL-3.0-or-later",
+    "MIT",
+    "MIT-0",
+    "MPL-1.1",
+    "MPL-2.0",
+    "MS-PL",
+    "NCSA",
+    "OFL-1.1",
+    "OpenSSL",
+    "PostgreSQL",
+    "PSF-2.0",
+    "Python-2.0",
+    "Ruby",
+    "Unicode-3.0",
+    "Unicode-DFS-2016",
+    "Unlicense",
+    "UPL-1.0",
+    "W3C",
+    "WTFPL",
+    "X11",
+    "Zlib",
+    "ZPL-2.1",
+];
+
+/// SPDX license exceptions accepted after `WITH`
+const SPDX_EXCEPTION_IDS: &[&str] = &[
+    "Classpath-exception-2.0",
+    "GCC-exception-3.1",
+    "LLVM-exception",
+    "OpenJDK-assembly-exception-1.0",
+];
+
+/// A package as the SBOM formats list it
+#[derive(Debug, Clone)]
+pub(crate) struct SbomPackage {
+    /// Package URL, unique within the graph
+    pub(crate) reference: String,
+    pub(crate) name: String,
+    pub(crate) version: Option<String>,
+    pub(crate) license: Option<String>,
+    pub(crate) is_dev: bool,
+}
+
+/// The analyzed package and its dependency tree with each package listed once
+#[derive(Debug, Clone)]
+pub(crate) struct PackageGraph {
+    pub(crate) root: SbomPackage,
+    /// Dependencies in first-seen order
+    pub(crate) packages: Vec<SbomPackage>,
+    /// Direct dependencies of the root, then of each package in `packages`
+    pub(crate) edges: Vec<(String, Vec<String>)>,
+    /// Each advisory once, with the references of the packages it affects
+    pub(crate) vulnerabilities: Vec<(Vulnerability, Vec<String>)>,
+}
+
+impl PackageGraph {
+    /// Graph of `result`
+    pub(crate) fn from_result<R: AnalysisResult + ?Sized>(result: &R) -> Self {
+        let package = result.package_info();
+        let package_type = package.package_type();
+        let metadata = package.metadata();
+        let root = SbomPackage {
+            reference: purl(package_type, &metadata.name, Some(&metadata.version)),
+            name: metadata.name.clone(),
+            version: Some(metadata.version.clone()),
+            license: metadata.license.clone(),
+            is_dev: false,
+        };
+
+        let mut graph = Self {
+            edges: vec![(root.reference.clone(), Vec::new())],
+            root,
+            packages: Vec::new(),
+            vulnerabilities: Vec::new(),
+        };
+        let mut seen = HashMap::new();
+        let ecosystem = dependency_ecosystem(package_type);
+        for dependency in &result.dependency_analysis().dependency_tree {
+            let reference = graph.add(ecosystem, dependency, &mut seen);
+            push_unique(&mut graph.edges[0].1, reference);
+        }
+        // Advisories for the package itself rather than any one dependency
+        for vulnerability in result.vulnerabilities() {
+            if !graph
+                .vulnerabilities
+                .iter()
+                .any(|(v, _)| v.id == vulnerability.id)
+            {
+                let root = graph.root.reference.clone();
+                graph.affect(vulnerability, &root);
+            }
+        }
+        graph
+    }
+
+    /// Add `dependency` and everything beneath it, returning its reference
+    fn add(
+        &mut self,
+        ecosystem: &str,
+        dependency: &Dependency,
+        seen: &mut HashMap<String, usize>,
+    ) -> String {
+        let name = dependency
+            .alias_target
+            .as_deref()
+            .unwrap_or(&dependency.name);
+        let version = dependency
+            .resolved_version
+            .as_deref()
+            .or_else(|| exact_version(ecosystem, &dependency.version_spec));
+        let reference = purl(ecosystem, name, version);
+
+        let children: Vec<String> = dependency
+            .dependencies
+            .iter()
+            .map(|child| self.add(ecosystem, child, seen))
+            .collect();
+        // Edge 0 is the root's, so package `i` has edge `i + 1`
+        let index = match seen.get(&reference) {
+            Some(&index) => index,
+            None => {
+                seen.insert(reference.clone(), self.packages.len());
+                self.packages.push(SbomPackage {
+                    reference: reference.clone(),
+                    name: name.to_string(),
+                    version: version.map(str::to_string),
+                    license: dependency.license.clone(),
+                    is_dev: dependency.is_dev,
+                });
+                self.edges.push((reference.clone(), Vec::new()));
+                self.packages.len() - 1
+            }
+        };
+        for child in children {
+            push_unique(&mut self.edges[index + 1].1, child);
+        }
+        for vulnerability in &dependency.vulnerabilities {
+            self.affect(vulnerability, &reference);
+        }
+        reference
+    }
+
+    /// Record that `vulnerability` affects the package `reference`
+    fn affect(&mut self, vulnerability: &Vulnerability, reference: &str) {
+        let index = match self
+            .vulnerabilities
+            .iter()
+            .position(|(v, _)| v.id == vulnerability.id)
+        {
+            Some(index) => index,
+            None => {
+                self.vulnerabilities
+                    .push((vulnerability.clone(), Vec::new()));
+                self.vulnerabilities.len() - 1
+            }
+        };
+        push_unique(&mut self.vulnerabilities[index].1, reference.to_string());
+    }
+}
+
+fn push_unique(references: &mut Vec<String>, reference: String) {
+    if !references.contains(&reference) {
+        references.push(reference);
+    }
+}
+
+/// `version_spec` when it names one concrete version rather than a range
+fn exact_version<'a>(ecosystem: &str, version_spec: &'a str) -> Option<&'a str> {
+    let version = version_spec.trim().trim_start_matches("==").trim();
+    let scheme = VersionSchemeRegistry::global().get_or_default(ecosystem);
+    scheme.parse(version).is_ok().then_some(version)
+}
+
+/// Ecosystem the dependencies of a `package_type` package come from
+fn dependency_ecosystem(package_type: &str) -> &str {
+    match package_type {
+        // Extensions bundle npm packages
+        "vscode-extension" | "browser-extension" => "npm",
+        other => other,
+    }
+}
+
+/// Package URL for a package of `package_type`
+///
+/// Ecosystems without a registered package URL type (Terraform, Helm,
+/// extensions) use `pkg:generic`. See <https://github.com/package-url/purl-spec>.
+pub fn purl(package_type: &str, name: &str, version: Option<&str>) -> String {
+    let (kind, path) = match package_type {
+        "npm" => ("npm", encode_path(name)),
+        "python" => ("pypi", encode(&normalize_name(name, "python"))),
+        "cargo" => ("cargo", encode(name)),
+        "go" => ("golang", encode_path(name)),
+        "rubygems" => ("gem", encode(name)),
+        "composer" => ("composer", encode_path(&name.to_lowercase())),
+        "java" | "maven" => ("maven", encode_path(&name.replace(':', "/"))),
+        _ => ("generic", encode(name)),
+    };
+    match version {
+        Some(version) => format!("pkg:{}/{}@{}", kind, path, encode(version)),
+        None => format!("pkg:{}/{}", kind, path),
+    }
+}
+
+/// Percent-encode each `/`-separated segment of a name
+fn encode_path(name: &str) -> String {
+    name.split('/').map(encode).collect::<Vec<_>>().join("/")
+}
+
+/// Percent-encode everything outside the characters a purl leaves as is
+fn encode(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'.' | b'-' | b'_' | b'~' | b'+' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Whether `expression` is an SPDX license expression this crate can vouch for
+///
+/// Follows the SPDX 2.3 expression grammar: license identifiers, optionally
+/// with `+`, combined with `AND` and `OR`, `WITH` an exception, and grouped
+/// in parentheses. Identifiers must be among the common licenses this crate
+/// knows or `LicenseRef-` references. Operators are case-sensitive, license
+/// identifiers are not.
+pub fn is_spdx_expression(expression: &str) -> bool {
+    let spaced = expression.replace('(', " ( ").replace(')', " ) ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+    let mut position = 0;
+    !tokens.is_empty() && parse_compound(&tokens, &mut position) && position == tokens.len()
+}
+
+/// `compound := term (("AND" | "OR") term)*`
+fn parse_compound(tokens: &[&str], position: &mut usize) -> bool {
+    if !parse_term(tokens, position) {
+        return false;
+    }
+    while matches!(tokens.get(*position), Some(&"AND") | Some(&"OR")) {
+        *position += 1;
+        if !parse_term(tokens, position) {
+            return false;
+        }
+    }
+    true
+}
+
+/// `term := "(" compound ")" | license ["WITH" exception]`
+fn parse_term(tokens: &[&str], position: &mut usize) -> bool {
+    let Some(&token) = tokens.get(*position) else {
+        return false;
+    };
+    *position += 1;
+    if token == "(" {
+        let inner = parse_compound(tokens, position);
+        let closed = tokens.get(*position) == Some(&")");
+        *position += 1;
+        return inner && closed;
+    }
+    if !is_license_id(token) {
+        return false;
+    }
+    if tokens.get(*position) == Some(&"WITH") {
+        *position += 1;
+        let exception = tokens.get(*position);
+        *position += 1;
+        return exception.is_some_and(|exception| {
+            SPDX_EXCEPTION_IDS
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(exception))
+        });
+    }
+    true
+}
+
+fn is_license_id(token: &str) -> bool {
+    let id_chars = |id: &str| {
+        !id.is_empty()
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+    };
+    if let Some(reference) = token.strip_prefix("LicenseRef-") {
+        return id_chars(reference);
+    }
+    let id = token.strip_suffix('+').unwrap_or(token);
+    SPDX_LICENSE_IDS
+        .iter()
+        .any(|known| known.eq_ignore_ascii_case(id))
+}
@@ -0,0 +1,134 @@
+//! SPDX 2.3 documents for analysis results
+//!
+//! The document `DESCRIBES` the analyzed package; every package in the
+//! dependency tree is listed once, with its package URL as an external
+//! reference, and each dependency edge becomes a `DEPENDS_ON` relationship.
+//! `licenseDeclared` carries the license the package declares when it is a
+//! valid SPDX expression and `NOASSERTION` otherwise, so free-form license
+//! strings never make the document invalid.
+
+use anyhow::Result;
+use chrono::Utc;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+
+use super::sbom::{is_spdx_expression, PackageGraph, SbomPackage};
+use crate::core::AnalysisResult;
+
+/// SPDX specification version of the documents produced
+pub const SPEC_VERSION: &str = "SPDX-2.3";
+
+const NOASSERTION: &str = "NOASSERTION";
+
+/// SPDX JSON document for `result`
+pub fn to_spdx<R: AnalysisResult + ?Sized>(result: &R) -> Result<Value> {
+    let graph = PackageGraph::from_result(result);
+    let created = Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    let mut ids = HashMap::new();
+    let mut taken = HashSet::new();
+    for package in std::iter::once(&graph.root).chain(&graph.packages) {
+        let id = spdx_id(package, &mut taken);
+        ids.insert(package.reference.as_str(), id);
+    }
+
+    let packages: Vec<Value> = std::iter::once(&graph.root)
+        .chain(&graph.packages)
+        .map(|package| package_entry(package, &ids[package.reference.as_str()]))
+        .collect();
+
+    let mut relationships = vec![json!({
+        "spdxElementId": "SPDXRef-DOCUMENT",
+        "relationshipType": "DESCRIBES",
+        "relatedSpdxElement": ids[graph.root.reference.as_str()],
+    })];
+    for (reference, depends_on) in &graph.edges {
+        for target in depends_on {
+            relationships.push(json!({
+                "spdxElementId": ids[reference.as_str()],
+                "relationshipType": "DEPENDS_ON",
+                "relatedSpdxElement": ids[target.as_str()],
+            }));
+        }
+    }
+
+    let digest = Sha256::digest(format!("{}\n{}", graph.root.reference, created));
+    Ok(json!({
+        "spdxVersion": SPEC_VERSION,
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": graph.root.reference,
+        "documentNamespace": format!(
+            "https://spdx.org/spdxdocs/{}-{}",
+            graph.root.name.replace(['/', ':', '@'], "-"),
+            hex::encode(&digest[..16])
+        ),
+        "creationInfo": {
+            "created": created,
+            "creators": [format!(
+                "Tool: {}-{}",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION")
+            )],
+        },
+        "documentDescribes": [ids[graph.root.reference.as_str()]],
+        "packages": packages,
+        "relationships": relationships,
+    }))
+}
+
+/// Entry in the `packages` section
+fn package_entry(package: &SbomPackage, id: &str) -> Value {
+    let license = package
+        .license
+        .as_deref()
+        .filter(|license| is_spdx_expression(license))
+        .unwrap_or(NOASSERTION);
+    let mut entry = json!({
+        "SPDXID": id,
+        "name": package.name,
+        "downloadLocation": NOASSERTION,
+        "filesAnalyzed": false,
+        "licenseConcluded": NOASSERTION,
+        "licenseDeclared": license,
+        "copyrightText": NOASSERTION,
+        "externalRefs": [{
+            "referenceCategory": "PACKAGE-MANAGER",
+            "referenceType": "purl",
+            "referenceLocator": package.reference,
+        }],
+    });
+    if let Some(version) = &package.version {
+        entry["versionInfo"] = json!(version);
+    }
+    entry
+}
+
+/// `SPDXRef-Package-<name>-<version>`, made unique among `taken`
+///
+/// SPDX IDs allow only letters, digits, `.` and `-`; anything else becomes `-`.
+fn spdx_id(package: &SbomPackage, taken: &mut HashSet<String>) -> String {
+    let label = match &package.version {
+        Some(version) => format!("{}-{}", package.name, version),
+        None => package.name.clone(),
+    };
+    let sanitized: String = label
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let base = format!("SPDXRef-Package-{}", sanitized);
+    let mut id = base.clone();
+    let mut suffix = 2;
+    while !taken.insert(id.clone()) {
+        id = format!("{}-{}", base, suffix);
+        suffix += 1;
+    }
+    id
+}
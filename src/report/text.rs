@@ -0,0 +1,259 @@
+//! Human-readable report for terminals and CI logs
+//!
+//! Lays out the package, its overall risk level, a table of vulnerabilities,
+//! the most severe malicious indicators and the supply chain score. With color
+//! on, risk levels and severities get ANSI colors; with it off the report is
+//! plain ASCII. Text taken from packages, such as descriptions and evidence,
+//! has control characters replaced either way, so a package cannot write
+//! escape sequences of its own to the terminal.
+
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::io::IsTerminal;
+
+use crate::core::{AnalysisResult, Dependency, PatternSeverity, RiskLevel, Vulnerability};
+
+/// Malicious patterns listed, most severe first
+const TOP_INDICATORS: usize = 5;
+
+/// Evidence lines listed per malicious pattern
+const EVIDENCE_LINES: usize = 2;
+
+/// Characters kept of text taken from a package, such as a description
+const MAX_TEXT: usize = 100;
+
+const RESET: &str = "\x1b[0m";
+
+/// Whether standard output is a terminal that should get color
+///
+/// False when output is redirected, `NO_COLOR` is set or `TERM` is `dumb`.
+/// Pass the result to [`AnalysisResult::to_text_report`] to degrade to plain
+/// text exactly when the report is not read on a terminal.
+pub fn color_supported() -> bool {
+    std::io::stdout().is_terminal()
+        && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        && std::env::var("TERM").map_or(true, |term| term != "dumb")
+}
+
+/// Text report of `result`; see [`AnalysisResult::to_text_report`]
+pub fn to_text_report<R: AnalysisResult + ?Sized>(result: &R, color: bool) -> String {
+    let paint = Paint { color };
+    let metadata = result.package_info().metadata();
+    let mut report = String::new();
+
+    let _ = writeln!(
+        report,
+        "Package: {}@{} ({})",
+        paint.text(&metadata.name),
+        paint.text(&metadata.version),
+        result.package_info().package_type()
+    );
+    if let Some(description) = metadata.description.as_deref() {
+        let _ = writeln!(report, "  {}", paint.text(description));
+    }
+    let level = result.overall_risk_level();
+    let _ = writeln!(
+        report,
+        "Risk level: {}",
+        paint.level(&level.to_string().to_uppercase(), level)
+    );
+    let _ = writeln!(
+        report,
+        "Supply chain score: {:.2}",
+        result.supply_chain_risk_score()
+    );
+
+    let vulnerabilities = vulnerability_rows(result);
+    report.push('\n');
+    if vulnerabilities.is_empty() {
+        report.push_str("No known vulnerabilities\n");
+    } else {
+        let _ = writeln!(report, "Vulnerabilities ({})", vulnerabilities.len());
+        let header = ["PACKAGE", "ID", "SEVERITY", "FIXED IN"];
+        let cells: Vec<[String; 4]> = vulnerabilities
+            .iter()
+            .map(|(package, vulnerability)| {
+                [
+                    paint.text(package),
+                    paint.text(&vulnerability.id),
+                    vulnerability.severity.to_string(),
+                    paint.text(
+                        &vulnerability
+                            .fixed_versions
+                            .first()
+                            .cloned()
+                            .unwrap_or_else(|| "-".to_string()),
+                    ),
+                ]
+            })
+            .collect();
+        let widths: Vec<usize> = (0..header.len())
+            .map(|column| {
+                cells
+                    .iter()
+                    .map(|row| row[column].chars().count())
+                    .chain([header[column].len()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let _ = writeln!(
+            report,
+            "  {:<w0$}  {:<w1$}  {:<w2$}  {}",
+            header[0],
+            header[1],
+            header[2],
+            header[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+        );
+        for (row, (_, vulnerability)) in cells.iter().zip(&vulnerabilities) {
+            // Padded before coloring, as escape codes would count toward the width
+            let severity = format!("{:<width$}", row[2], width = widths[2]);
+            let _ = writeln!(
+                report,
+                "  {:<w0$}  {:<w1$}  {}  {}",
+                row[0],
+                row[1],
+                paint.level(&severity, vulnerability.severity.clone().into()),
+                row[3],
+                w0 = widths[0],
+                w1 = widths[1],
+            );
+        }
+    }
+
+    let mut patterns: Vec<_> = result.malicious_indicators().iter().collect();
+    patterns.sort_by(|a, b| b.severity.cmp(&a.severity));
+    report.push('\n');
+    if patterns.is_empty() {
+        report.push_str("No malicious indicators\n");
+    } else {
+        let _ = writeln!(
+            report,
+            "Malicious indicators ({} of {})",
+            patterns.len().min(TOP_INDICATORS),
+            patterns.len()
+        );
+        for pattern in patterns.iter().take(TOP_INDICATORS) {
+            let severity = format!("[{:?}]", pattern.severity);
+            let _ = writeln!(
+                report,
+                "  {} {}: {}",
+                paint.level(&severity, pattern_level(&pattern.severity)),
+                paint.text(&pattern.pattern_name),
+                paint.text(&pattern.description)
+            );
+            for evidence in pattern.evidence.iter().take(EVIDENCE_LINES) {
+                let _ = writeln!(report, "      {}", paint.text(evidence));
+            }
+        }
+    }
+    report
+}
+
+/// Every vulnerability with the package it is in, worst first
+///
+/// Dependency advisories come first in tree order; advisories of the package
+/// itself follow unless a dependency already listed them.
+fn vulnerability_rows<R: AnalysisResult + ?Sized>(result: &R) -> Vec<(String, &Vulnerability)> {
+    let mut rows = Vec::new();
+    let mut seen = HashSet::new();
+    let mut stack: Vec<&Dependency> = result
+        .dependency_analysis()
+        .dependency_tree
+        .iter()
+        .rev()
+        .collect();
+    while let Some(dependency) = stack.pop() {
+        for vulnerability in &dependency.vulnerabilities {
+            if seen.insert((dependency.name.as_str(), vulnerability.id.as_str())) {
+                rows.push((dependency.name.clone(), vulnerability));
+            }
+        }
+        stack.extend(dependency.dependencies.iter().rev());
+    }
+    let advisories: HashSet<&str> = seen.iter().map(|(_, id)| *id).collect();
+    let package = result.package_info().name();
+    for vulnerability in result.vulnerabilities() {
+        if !advisories.contains(vulnerability.id.as_str()) {
+            rows.push((package.to_string(), vulnerability));
+        }
+    }
+    rows.sort_by(|(_, a), (_, b)| b.severity.cmp(&a.severity));
+    rows
+}
+
+fn pattern_level(severity: &PatternSeverity) -> RiskLevel {
+    match severity {
+        PatternSeverity::Low => RiskLevel::Low,
+        PatternSeverity::Medium => RiskLevel::Medium,
+        PatternSeverity::High => RiskLevel::High,
+        PatternSeverity::Critical => RiskLevel::Critical,
+    }
+}
+
+/// Applies colors, or leaves text plain
+struct Paint {
+    color: bool,
+}
+
+impl Paint {
+    /// `text` in the color of `level`
+    fn level(&self, text: &str, level: RiskLevel) -> String {
+        if !self.color {
+            return text.to_string();
+        }
+        let code = match level {
+            RiskLevel::Critical => "\x1b[1;31m",
+            RiskLevel::High => "\x1b[31m",
+            RiskLevel::Medium => "\x1b[33m",
+            RiskLevel::Low => "\x1b[36m",
+            RiskLevel::Safe => "\x1b[32m",
+        };
+        format!("{}{}{}", code, text, RESET)
+    }
+
+    /// Text from a package, cut to [`MAX_TEXT`] characters
+    ///
+    /// Control characters become `?`, and so does anything outside ASCII
+    /// when color is off.
+    fn text(&self, text: &str) -> String {
+        let mut clean: String = text
+            .chars()
+            .take(MAX_TEXT)
+            .map(|c| {
+                if c.is_control() || (!self.color && !c.is_ascii()) {
+                    '?'
+                } else {
+                    c
+                }
+            })
+            .collect();
+        if text.chars().nth(MAX_TEXT).is_some() {
+            clean.push_str("...");
+        }
+        clean
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paint() {
+        let plain = Paint { color: false };
+        assert_eq!(plain.level("HIGH", RiskLevel::High), "HIGH");
+        assert_eq!(plain.text("caf\u{e9}\x1b[2J"), "caf??[2J");
+
+        let colored = Paint { color: true };
+        assert_eq!(
+            colored.level("HIGH", RiskLevel::High),
+            "\x1b[31mHIGH\x1b[0m"
+        );
+        assert_eq!(colored.text("caf\u{e9}\x1b[2J"), "caf\u{e9}?[2J");
+        assert!(plain.text(&"a".repeat(150)).ends_with("a..."));
+    }
+}
@@ -0,0 +1,429 @@
+//! Inventory of WebAssembly modules and prebuilt native binaries bundled in a package
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::Path;
+
+use crate::core::PartialParse;
+
+/// Modules larger than this are inventoried but not parsed
+const MAX_WASM_INSPECT_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Directories never descended into while inventorying
+const SKIPPED_DIRS: &[&str] = &["node_modules", ".git"];
+
+/// Files whose presence suggests binaries are built locally rather than dropped in
+const BUILD_CONFIG_FILES: &[&str] = &[
+    "binding.gyp",
+    "CMakeLists.txt",
+    "Makefile",
+    "Cargo.toml",
+    "asconfig.json",
+    "build.zig",
+];
+
+/// Source extensions from which a bundled binary could have been compiled
+const SOURCE_EXTENSIONS: &[&str] = &["c", "cc", "cpp", "cxx", "rs", "wat", "zig", "go"];
+
+/// Host import names that give a module process, network or code-loading capabilities
+const DANGEROUS_IMPORT_MARKERS: &[&str] = &[
+    "exec",
+    "spawn",
+    "system",
+    "eval",
+    "run_script",
+    "child_process",
+    "require",
+    "socket",
+    "sock_",
+    "connect",
+    "dlopen",
+];
+
+/// Kind of bundled binary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BinaryKind {
+    Wasm,
+    /// Node.js native addon (`.node`)
+    NodeAddon,
+    SharedLibrary,
+    Executable,
+}
+
+impl BinaryKind {
+    /// Classify a file by extension, falling back to its magic bytes
+    pub fn detect(path: &Path, magic: &[u8]) -> Option<Self> {
+        let by_extension = match path.extension().and_then(|e| e.to_str()) {
+            Some("wasm") => Some(BinaryKind::Wasm),
+            Some("node") => Some(BinaryKind::NodeAddon),
+            Some("so" | "dylib" | "dll") => Some(BinaryKind::SharedLibrary),
+            Some("exe") => Some(BinaryKind::Executable),
+            _ => None,
+        };
+        if by_extension.is_some() {
+            return by_extension;
+        }
+
+        match magic {
+            [0x00, b'a', b's', b'm', ..] => Some(BinaryKind::Wasm),
+            [0x7f, b'E', b'L', b'F', ..] => Some(BinaryKind::Executable),
+            [0xcf, 0xfa, 0xed, 0xfe, ..] | [0xce, 0xfa, 0xed, 0xfe, ..] => {
+                Some(BinaryKind::Executable)
+            }
+            [b'M', b'Z', ..] if path.extension().is_none() => Some(BinaryKind::Executable),
+            _ => None,
+        }
+    }
+}
+
+/// An import declared by a WebAssembly module
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WasmImport {
+    pub module: String,
+    pub name: String,
+    /// `func`, `table`, `memory`, `global` or `tag`
+    pub kind: String,
+}
+
+impl WasmImport {
+    /// Whether this import hands the module a dangerous host capability
+    pub fn is_dangerous(&self) -> bool {
+        if self.kind != "func" {
+            return false;
+        }
+        let name = self.name.to_lowercase();
+        DANGEROUS_IMPORT_MARKERS
+            .iter()
+            .any(|marker| name.contains(marker))
+    }
+}
+
+/// A binary found in a package
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundledBinary {
+    /// Slash-separated path relative to the package root
+    pub path: String,
+    pub kind: BinaryKind,
+    pub size: u64,
+    /// Install hooks whose command references this file
+    pub referenced_by_scripts: Vec<String>,
+    /// Whether build configuration or source that could produce it ships alongside
+    pub has_build_config: bool,
+    /// Imports of a WebAssembly module, when inspected
+    pub wasm_imports: Vec<WasmImport>,
+}
+
+impl BundledBinary {
+    /// Imports that grant dangerous host capabilities
+    pub fn dangerous_imports(&self) -> Vec<&WasmImport> {
+        self.wasm_imports
+            .iter()
+            .filter(|i| i.is_dangerous())
+            .collect()
+    }
+}
+
+/// Binaries found in a package
+#[derive(Debug, Clone, Default)]
+pub struct Inventory {
+    pub binaries: Vec<BundledBinary>,
+    /// Directories that could not be read, which may hold further binaries
+    pub unreadable: Vec<PartialParse>,
+}
+
+/// Inventory binaries under `root`
+///
+/// `install_scripts` are `(hook, command)` pairs used to attribute binaries to the
+/// scripts that run them. WebAssembly imports are parsed when `inspect_wasm` is set.
+/// Symlinks are not followed, so a link back up the tree cannot loop the walk,
+/// and a directory that cannot be read is skipped and reported.
+pub fn inventory(root: &Path, install_scripts: &[(&str, &str)], inspect_wasm: bool) -> Inventory {
+    let mut files = Vec::new();
+    let mut unreadable = Vec::new();
+    let mut has_build_config = false;
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let entries = match std::fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(error) => {
+                unreadable.push(PartialParse::new(relative_dir(root, &dir), error));
+                continue;
+            }
+        };
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(error) => {
+                    unreadable.push(PartialParse::new(relative_dir(root, &dir), error));
+                    continue;
+                }
+            };
+            // The entry itself, not what a symlink points to
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let path = entry.path();
+            let file_name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            if metadata.is_dir() {
+                if !SKIPPED_DIRS.contains(&file_name) {
+                    stack.push(path);
+                }
+                continue;
+            }
+            if !metadata.is_file() {
+                continue;
+            }
+            let is_source = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|ext| SOURCE_EXTENSIONS.contains(&ext))
+                .unwrap_or(false);
+            if is_source || BUILD_CONFIG_FILES.contains(&file_name) {
+                has_build_config = true;
+            }
+            files.push((path, metadata.len()));
+        }
+    }
+    files.sort();
+
+    let mut binaries = Vec::new();
+    for (path, size) in files {
+        let Some(kind) = BinaryKind::detect(&path, &read_magic(&path)) else {
+            continue;
+        };
+        let relative = relative_path(root, &path);
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+
+        let referenced_by_scripts = install_scripts
+            .iter()
+            .filter(|(_, command)| command.contains(&relative) || command.contains(file_name))
+            .map(|(hook, _)| hook.to_string())
+            .collect();
+
+        let wasm_imports =
+            if kind == BinaryKind::Wasm && inspect_wasm && size <= MAX_WASM_INSPECT_SIZE {
+                // A module that fails to read or parse is still inventoried, just without imports
+                std::fs::read(&path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|bytes| parse_wasm_imports(&bytes))
+                    .unwrap_or_default()
+            } else {
+                vec![]
+            };
+
+        binaries.push(BundledBinary {
+            path: relative,
+            kind,
+            size,
+            referenced_by_scripts,
+            has_build_config,
+            wasm_imports,
+        });
+    }
+
+    Inventory {
+        binaries,
+        unreadable,
+    }
+}
+
+/// Parse the import section of a WebAssembly module
+pub fn parse_wasm_imports(bytes: &[u8]) -> Result<Vec<WasmImport>> {
+    let mut reader = WasmReader { bytes, pos: 0 };
+    if reader.take(4)? != b"\0asm" {
+        return Err(anyhow!("Not a WebAssembly module"));
+    }
+    reader.take(4)?; // version
+
+    while reader.pos < bytes.len() {
+        let id = reader.byte()?;
+        let size = reader.leb()? as usize;
+        if id != 2 {
+            reader.take(size)?;
+            continue;
+        }
+
+        let mut section = WasmReader {
+            bytes: reader.take(size)?,
+            pos: 0,
+        };
+        let count = section.leb()?;
+        let mut imports = Vec::new();
+        for _ in 0..count {
+            let module = section.name()?;
+            let name = section.name()?;
+            let kind = match section.byte()? {
+                0 => {
+                    section.leb()?;
+                    "func"
+                }
+                1 => {
+                    section.byte()?;
+                    section.limits()?;
+                    "table"
+                }
+                2 => {
+                    section.limits()?;
+                    "memory"
+                }
+                3 => {
+                    section.take(2)?;
+                    "global"
+                }
+                4 => {
+                    section.byte()?;
+                    section.leb()?;
+                    "tag"
+                }
+                other => return Err(anyhow!("Unknown import kind {}", other)),
+            };
+            imports.push(WasmImport {
+                module,
+                name,
+                kind: kind.to_string(),
+            });
+        }
+        return Ok(imports);
+    }
+
+    Ok(vec![])
+}
+
+struct WasmReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> WasmReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| anyhow!("Truncated WebAssembly module"))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Unsigned LEB128
+    fn leb(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(anyhow!("LEB128 value too long"))
+    }
+
+    fn name(&mut self) -> Result<String> {
+        let len = self.leb()? as usize;
+        Ok(String::from_utf8_lossy(self.take(len)?).into_owned())
+    }
+
+    fn limits(&mut self) -> Result<()> {
+        let flags = self.byte()?;
+        self.leb()?;
+        if flags & 1 != 0 {
+            self.leb()?;
+        }
+        Ok(())
+    }
+}
+
+fn read_magic(path: &Path) -> Vec<u8> {
+    let mut magic = Vec::with_capacity(4);
+    if let Ok(file) = std::fs::File::open(path) {
+        let _ = file.take(4).read_to_end(&mut magic);
+    }
+    magic
+}
+
+/// Relative path of a directory, `.` for the root itself
+fn relative_dir(root: &Path, dir: &Path) -> String {
+    let relative = relative_path(root, dir);
+    if relative.is_empty() {
+        ".".to_string()
+    } else {
+        relative
+    }
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_wasm_imports() {
+        // Module importing env.system (func) and env.memory (memory, min 1)
+        let mut module = b"\0asm\x01\0\0\0".to_vec();
+        let section = [
+            &[0x02][..], // two imports
+            &[0x03],
+            b"env",
+            &[0x06],
+            b"system",
+            &[0x00, 0x00],
+            &[0x03],
+            b"env",
+            &[0x06],
+            b"memory",
+            &[0x02, 0x00, 0x01],
+        ]
+        .concat();
+        module.push(0x02);
+        module.push(section.len() as u8);
+        module.extend(section);
+
+        let imports = parse_wasm_imports(&module).unwrap();
+        assert_eq!(imports.len(), 2);
+        assert_eq!(imports[0].name, "system");
+        assert!(imports[0].is_dangerous());
+        assert_eq!(imports[1].kind, "memory");
+        assert!(!imports[1].is_dangerous());
+
+        assert!(parse_wasm_imports(b"\0asm\x01\0\0\0\x02\x05\x01").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_inventory_skips_symlinked_directories() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("lib")).unwrap();
+        std::fs::write(dir.path().join("lib/hash.wasm"), b"\0asm\x01\0\0\0").unwrap();
+        std::os::unix::fs::symlink(".", dir.path().join("self")).unwrap();
+        std::os::unix::fs::symlink("..", dir.path().join("lib/up")).unwrap();
+
+        let found = inventory(dir.path(), &[], true);
+        let paths: Vec<_> = found.binaries.iter().map(|b| b.path.as_str()).collect();
+        assert_eq!(paths, vec!["lib/hash.wasm"]);
+        assert!(found.unreadable.is_empty());
+
+        let missing = inventory(&dir.path().join("missing"), &[], true);
+        assert_eq!(missing.unreadable.len(), 1);
+        assert_eq!(missing.unreadable[0].file, ".");
+    }
+}
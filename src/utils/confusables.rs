@@ -0,0 +1,109 @@
+//! Characters that render like ASCII letters and digits
+//!
+//! A name spelled with a Cyrillic `а` or a fullwidth `ｕ` looks identical to
+//! the ASCII name it imitates but is a different package. Mapping each such
+//! character to the ASCII one it imitates gives the name's skeleton, which is
+//! compared instead, in the spirit of Unicode TR39 confusable detection. The
+//! table covers the Cyrillic, Greek and Latin lookalikes seen in package
+//! names and the fullwidth forms, not the whole of `confusables.txt`.
+
+use crate::core::HiddenCharacter;
+
+/// ASCII character `c` imitates, if any
+pub fn ascii_lookalike(c: char) -> Option<char> {
+    let ascii = match c {
+        // Cyrillic
+        'а' => 'a',
+        'в' => 'b',
+        'е' | 'ё' => 'e',
+        'һ' => 'h',
+        'і' => 'i',
+        'ј' => 'j',
+        'к' => 'k',
+        'м' => 'm',
+        'о' => 'o',
+        'р' => 'p',
+        'с' => 'c',
+        'ѕ' => 's',
+        'т' => 't',
+        'у' => 'y',
+        'х' => 'x',
+        'ԁ' => 'd',
+        'ԛ' => 'q',
+        'ԝ' => 'w',
+        'А' => 'A',
+        'В' => 'B',
+        'Е' => 'E',
+        'К' => 'K',
+        'М' => 'M',
+        'Н' => 'H',
+        'О' => 'O',
+        'Р' => 'P',
+        'С' => 'C',
+        'Т' => 'T',
+        'Х' => 'X',
+        // Greek
+        'α' => 'a',
+        'ε' => 'e',
+        'ι' => 'i',
+        'κ' => 'k',
+        'ν' => 'v',
+        'ο' => 'o',
+        'ρ' => 'p',
+        'υ' => 'u',
+        'χ' => 'x',
+        'Α' => 'A',
+        'Β' => 'B',
+        'Ε' => 'E',
+        'Ι' => 'I',
+        'Κ' => 'K',
+        'Μ' => 'M',
+        'Ν' => 'N',
+        'Ο' => 'O',
+        'Ρ' => 'P',
+        'Τ' => 'T',
+        'Χ' => 'X',
+        // Latin lookalikes
+        'ı' | 'ɩ' => 'i',
+        'ȷ' => 'j',
+        'ɡ' => 'g',
+        'ʏ' => 'y',
+        'ℓ' => 'l',
+        // Fullwidth forms of printable ASCII
+        '\u{ff01}'..='\u{ff5e}' => char::from_u32(c as u32 - 0xfee0)?,
+        _ => return None,
+    };
+    Some(ascii)
+}
+
+/// `name` with lookalikes replaced by the ASCII they imitate and zero-width
+/// characters removed
+pub fn skeleton(name: &str) -> String {
+    name.chars()
+        .filter(|c| HiddenCharacter::of(*c) != Some(HiddenCharacter::ZeroWidth))
+        .map(|c| ascii_lookalike(c).unwrap_or(c))
+        .collect()
+}
+
+/// Whether `name` holds a lookalike or zero-width character
+pub fn has_confusables(name: &str) -> bool {
+    name.chars().any(|c| {
+        ascii_lookalike(c).is_some() || HiddenCharacter::of(c) == Some(HiddenCharacter::ZeroWidth)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skeleton() {
+        assert_eq!(skeleton("lоdash"), "lodash");
+        assert_eq!(skeleton("reqｕest"), "request");
+        assert_eq!(skeleton("ехрress"), "express");
+        assert_eq!(skeleton("react\u{200b}"), "react");
+        assert!(has_confusables("lоdash"));
+        assert!(!has_confusables("lodash"));
+        assert!(!has_confusables("naïve"));
+    }
+}
@@ -0,0 +1,136 @@
+//! Content digests of package inputs
+
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Directories never part of a package's own inputs
+const SKIPPED_DIRS: &[&str] = &["node_modules", ".git"];
+
+/// Bytes of each file hashed; past this only the file's length is
+const MAX_HASHED_FILE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// What a path under the root contributes to the digest
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Input {
+    File { path: PathBuf, len: u64 },
+    Symlink { target: PathBuf },
+    Unreadable,
+}
+
+/// SHA-256 over every file under `root`, except `excluded` relative paths
+///
+/// Paths and contents are hashed in sorted path order, so the digest only
+/// changes when a file is added, removed, renamed or edited. `salt` is mixed
+/// in first, e.g. serialized analysis options. Symlinks are hashed by their
+/// target rather than followed, files are streamed up to
+/// `MAX_HASHED_FILE_SIZE` bytes, and an unreadable directory is hashed by
+/// its path alone.
+pub fn tree_digest(root: &Path, excluded: &[&str], salt: &[u8]) -> Result<String> {
+    let mut inputs = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            inputs.push((relative_path(root, &dir), Input::Unreadable));
+            continue;
+        };
+        for entry in entries {
+            let entry = entry?;
+            // The entry itself, not what a symlink points to
+            let file_type = entry.file_type()?;
+            let path = entry.path();
+            let file_name = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default();
+            if file_type.is_dir() {
+                if !SKIPPED_DIRS.contains(&file_name) {
+                    stack.push(path);
+                }
+                continue;
+            }
+            let relative = relative_path(root, &path);
+            if excluded.contains(&relative.as_str()) {
+                continue;
+            }
+            let input = if file_type.is_symlink() {
+                Input::Symlink {
+                    target: std::fs::read_link(&path)?,
+                }
+            } else {
+                Input::File {
+                    len: entry.metadata()?.len(),
+                    path,
+                }
+            };
+            inputs.push((relative, input));
+        }
+    }
+    inputs.sort();
+
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    for (relative, input) in inputs {
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        match input {
+            Input::File { path, len } => {
+                hasher.update(len.to_le_bytes());
+                let file = std::fs::File::open(&path)?;
+                std::io::copy(&mut file.take(MAX_HASHED_FILE_SIZE), &mut hasher)?;
+            }
+            Input::Symlink { target } => {
+                hasher.update(b"->");
+                hasher.update(target.to_string_lossy().as_bytes());
+            }
+            Input::Unreadable => hasher.update(b"unreadable"),
+        }
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_excluded_files_do_not_change_digest() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("package-lock.json"), "{\"v\":1}").unwrap();
+        let before = tree_digest(dir.path(), &["package-lock.json"], b"").unwrap();
+
+        std::fs::write(dir.path().join("package-lock.json"), "{\"v\":2}").unwrap();
+        assert_eq!(
+            tree_digest(dir.path(), &["package-lock.json"], b"").unwrap(),
+            before
+        );
+
+        std::fs::write(dir.path().join("package.json"), "{ }").unwrap();
+        assert_ne!(
+            tree_digest(dir.path(), &["package-lock.json"], b"").unwrap(),
+            before
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_hashed_by_target() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+        std::os::unix::fs::symlink(".", dir.path().join("self")).unwrap();
+        let before = tree_digest(dir.path(), &[], b"").unwrap();
+
+        std::fs::remove_file(dir.path().join("self")).unwrap();
+        std::os::unix::fs::symlink("..", dir.path().join("self")).unwrap();
+        assert_ne!(tree_digest(dir.path(), &[], b"").unwrap(), before);
+    }
+}
@@ -0,0 +1,549 @@
+//! Detection of machine-generated package names
+//!
+//! Bulk-published malware campaigns register names such as `xkqzvbwtrplm`:
+//! no dictionary words, almost no vowels, long consonant runs and letters
+//! interleaved with digits. The heuristic only looks at name segments of five
+//! or more letters, so acronyms (`hmac-drbg`, `nwsapi`) and short names are
+//! never judged, and any dictionary word in the name sharply lowers the score.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    rules, AnalysisOptions, Finding, FindingType, PackageMetadata, RiskLevel, ScanReason,
+};
+
+/// Segments shorter than this are treated as acronyms and ignored
+const MIN_SEGMENT_LETTERS: usize = 5;
+
+/// Names with fewer analyzed letters are never judged
+const MIN_ANALYZED_LETTERS: usize = 8;
+
+/// Packages published within this many days count as new
+const RECENT_PUBLISH_DAYS: i64 = 30;
+
+/// Words common in legitimate package names; covering letters lowers the score
+const DICTIONARY: &[&str] = &[
+    "access",
+    "action",
+    "adapter",
+    "admin",
+    "agent",
+    "alert",
+    "analy",
+    "angular",
+    "array",
+    "assert",
+    "async",
+    "audio",
+    "auth",
+    "babel",
+    "backend",
+    "base",
+    "batch",
+    "binary",
+    "bind",
+    "bridge",
+    "browser",
+    "buffer",
+    "build",
+    "bundle",
+    "cache",
+    "canvas",
+    "chain",
+    "chart",
+    "check",
+    "child",
+    "class",
+    "clean",
+    "client",
+    "cloud",
+    "cluster",
+    "color",
+    "command",
+    "common",
+    "compile",
+    "component",
+    "compress",
+    "config",
+    "connect",
+    "console",
+    "context",
+    "convert",
+    "cookie",
+    "core",
+    "crypt",
+    "data",
+    "date",
+    "debug",
+    "decode",
+    "deep",
+    "default",
+    "define",
+    "deploy",
+    "detect",
+    "device",
+    "diff",
+    "docker",
+    "document",
+    "domain",
+    "driver",
+    "element",
+    "email",
+    "encode",
+    "engine",
+    "entry",
+    "error",
+    "event",
+    "express",
+    "extend",
+    "extract",
+    "factory",
+    "fetch",
+    "field",
+    "file",
+    "filter",
+    "flow",
+    "format",
+    "frame",
+    "function",
+    "gateway",
+    "generat",
+    "global",
+    "graph",
+    "handler",
+    "hash",
+    "header",
+    "helper",
+    "hook",
+    "html",
+    "http",
+    "icon",
+    "image",
+    "import",
+    "index",
+    "inject",
+    "input",
+    "inspect",
+    "install",
+    "interface",
+    "internal",
+    "json",
+    "kernel",
+    "layout",
+    "lexer",
+    "light",
+    "limit",
+    "link",
+    "lint",
+    "list",
+    "load",
+    "local",
+    "lock",
+    "logger",
+    "manager",
+    "match",
+    "media",
+    "merge",
+    "message",
+    "meta",
+    "method",
+    "middleware",
+    "migrat",
+    "mobile",
+    "mock",
+    "model",
+    "module",
+    "monitor",
+    "native",
+    "network",
+    "node",
+    "notif",
+    "number",
+    "object",
+    "option",
+    "order",
+    "output",
+    "package",
+    "page",
+    "parse",
+    "parser",
+    "patch",
+    "path",
+    "plugin",
+    "point",
+    "policy",
+    "polyfill",
+    "pool",
+    "port",
+    "preset",
+    "print",
+    "process",
+    "promise",
+    "protocol",
+    "provider",
+    "proxy",
+    "query",
+    "queue",
+    "react",
+    "reader",
+    "record",
+    "redis",
+    "redux",
+    "regex",
+    "register",
+    "render",
+    "report",
+    "request",
+    "resolve",
+    "resource",
+    "response",
+    "result",
+    "retry",
+    "route",
+    "router",
+    "runtime",
+    "sanit",
+    "schema",
+    "script",
+    "search",
+    "secret",
+    "secure",
+    "select",
+    "sender",
+    "serial",
+    "server",
+    "service",
+    "session",
+    "shared",
+    "shell",
+    "signal",
+    "simple",
+    "socket",
+    "source",
+    "spawn",
+    "stack",
+    "state",
+    "static",
+    "storage",
+    "store",
+    "stream",
+    "string",
+    "struct",
+    "style",
+    "support",
+    "switch",
+    "sync",
+    "system",
+    "table",
+    "task",
+    "template",
+    "terminal",
+    "test",
+    "text",
+    "theme",
+    "thread",
+    "time",
+    "token",
+    "tool",
+    "track",
+    "transform",
+    "tree",
+    "type",
+    "upload",
+    "utils",
+    "valid",
+    "value",
+    "vendor",
+    "version",
+    "view",
+    "watch",
+    "webpack",
+    "widget",
+    "window",
+    "worker",
+    "wrap",
+    "write",
+];
+
+/// Why a name looks generated
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeneratedName {
+    /// Generatedness from 0.0 (natural) to 1.0 (random)
+    pub score: f32,
+    /// Human-readable signals that contributed to the score
+    pub signals: Vec<String>,
+}
+
+impl GeneratedName {
+    /// Report as a `SuspiciousName` finding
+    ///
+    /// Low on its own; medium when the package also runs install scripts or
+    /// was published recently, the pattern of spray-and-pray campaigns.
+    pub fn to_finding(&self, package_name: &str, corroborated_by: Option<&str>) -> Finding {
+        let severity = if corroborated_by.is_some() {
+            RiskLevel::Medium
+        } else {
+            RiskLevel::Low
+        };
+        let mut finding = Finding::new(
+            FindingType::SuspiciousName,
+            severity,
+            format!("Package name '{}' looks machine-generated", package_name),
+            "Random-looking names are typical of malware published in bulk",
+        )
+        .with_evidence(format!("Generatedness score {:.2}", self.score))
+        .with_affected_component(package_name.to_string())
+        .with_scan_reason(ScanReason::Typosquatting)
+        .with_rule_id(rules::GENERATED_NAME);
+        for signal in &self.signals {
+            finding = finding.with_evidence(signal.clone());
+        }
+        if let Some(reason) = corroborated_by {
+            finding = finding.with_evidence(reason.to_string());
+        }
+        finding
+    }
+}
+
+/// Scores how machine-generated a package name looks
+#[derive(Debug, Clone)]
+pub struct GeneratedNameDetector {
+    threshold: f32,
+}
+
+impl Default for GeneratedNameDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GeneratedNameDetector {
+    /// Score at or above which a name counts as generated
+    pub const DEFAULT_THRESHOLD: f32 = 0.6;
+
+    /// Create a detector with the default, conservative threshold
+    pub fn new() -> Self {
+        Self::with_threshold(Self::DEFAULT_THRESHOLD)
+    }
+
+    /// Create a detector flagging names scoring at least `threshold`
+    pub fn with_threshold(threshold: f32) -> Self {
+        Self { threshold }
+    }
+
+    /// Assess a name, returning the signals if it looks generated
+    pub fn assess(&self, package_name: &str) -> Option<GeneratedName> {
+        let assessment = self.score(package_name)?;
+        (assessment.score >= self.threshold).then_some(assessment)
+    }
+
+    /// Check a package, reporting its name when it looks generated
+    ///
+    /// `install_hook` names the script that runs at install time, if any. Names
+    /// in `options.name_allowlist` are never reported.
+    pub fn check(
+        &self,
+        metadata: &PackageMetadata,
+        install_hook: Option<&str>,
+        options: &AnalysisOptions,
+    ) -> Option<Finding> {
+        if !options.detect_typosquatting
+            || options
+                .name_allowlist
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(&metadata.name))
+        {
+            return None;
+        }
+        let assessment = self.assess(&metadata.name)?;
+
+        let published = metadata
+            .publish_date
+            .as_deref()
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+            .filter(|date| {
+                options.now() - date.with_timezone(&Utc) < Duration::days(RECENT_PUBLISH_DAYS)
+            });
+        let corroboration = match (install_hook, published) {
+            (Some(hook), _) => Some(format!("Runs a {} script", hook)),
+            (None, Some(date)) => Some(format!("Published recently ({})", date.to_rfc3339())),
+            (None, None) => None,
+        };
+        Some(assessment.to_finding(&metadata.name, corroboration.as_deref()))
+    }
+
+    /// Score a name regardless of the threshold; `None` if it is too short to judge
+    pub fn score(&self, package_name: &str) -> Option<GeneratedName> {
+        // The scope is chosen by the publisher once, not per package
+        let name = package_name
+            .rsplit('/')
+            .next()
+            .unwrap_or(package_name)
+            .to_ascii_lowercase();
+        let segments: Vec<&str> = name
+            .split(['-', '_', '.'])
+            .filter(|s| {
+                s.chars().filter(|c| c.is_ascii_alphabetic()).count() >= MIN_SEGMENT_LETTERS
+            })
+            .collect();
+        let letters: String = segments
+            .iter()
+            .flat_map(|s| s.chars())
+            .filter(|c| c.is_ascii_alphabetic())
+            .collect();
+        if letters.len() < MIN_ANALYZED_LETTERS {
+            return None;
+        }
+
+        let mut score = 0.0;
+        let mut signals = Vec::new();
+
+        let vowels = letters.chars().filter(|c| "aeiou".contains(*c)).count();
+        let vowel_ratio = vowels as f32 / letters.len() as f32;
+        if vowel_ratio < 0.2 {
+            score += 0.35;
+            signals.push(format!("{:.0}% vowels", vowel_ratio * 100.0));
+        } else if vowel_ratio < 0.28 {
+            score += 0.175;
+            signals.push(format!("{:.0}% vowels", vowel_ratio * 100.0));
+        }
+
+        let consonant_run = segments
+            .iter()
+            .flat_map(|s| s.split(|c: char| "aeiou".contains(c) || !c.is_ascii_alphabetic()))
+            .map(str::len)
+            .max()
+            .unwrap_or(0);
+        // English allows five in a row (`strengths`), rarely more
+        if consonant_run >= 6 {
+            score += 0.3;
+            signals.push(format!("{} consonants in a row", consonant_run));
+        } else if consonant_run == 5 {
+            score += 0.15;
+        }
+
+        let entropy = shannon_entropy(&letters);
+        if letters.len() >= 12 && entropy >= 3.5 {
+            score += 0.15;
+            signals.push(format!("{:.2} bits of entropy per letter", entropy));
+        }
+
+        // `x7k2q`: digits inside a word rather than a version suffix like `es2015`
+        let interleaved = segments
+            .iter()
+            .map(|s| {
+                let chars: Vec<char> = s.chars().collect();
+                chars
+                    .windows(2)
+                    .filter(|w| w[0].is_ascii_digit() && w[1].is_ascii_alphabetic())
+                    .count()
+            })
+            .sum::<usize>();
+        if interleaved >= 2 {
+            score += 0.2;
+            signals.push("Digits interleaved with letters".to_string());
+        }
+
+        let coverage = dictionary_coverage(&letters);
+        if coverage > 0.0 {
+            score *= 1.0 - coverage;
+        } else {
+            signals.push("No dictionary words".to_string());
+        }
+
+        Some(GeneratedName { score, signals })
+    }
+}
+
+/// Shannon entropy of the character distribution, in bits per character
+fn shannon_entropy(text: &str) -> f32 {
+    let mut counts = [0usize; 256];
+    for byte in text.bytes() {
+        counts[byte as usize] += 1;
+    }
+    let len = text.len() as f32;
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f32 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Fraction of letters covered by dictionary words
+fn dictionary_coverage(letters: &str) -> f32 {
+    let mut covered = vec![false; letters.len()];
+    for word in DICTIONARY {
+        for (start, _) in letters.match_indices(word) {
+            covered[start..start + word.len()].fill(true);
+        }
+    }
+    covered.iter().filter(|&&c| c).count() as f32 / letters.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_names_flagged() {
+        let detector = GeneratedNameDetector::new();
+        for name in ["xkqzvbwtrplm", "@evil/qzxwvtrbnmkl", "hjyvzfvtqwkxn-sdk"] {
+            assert!(detector.assess(name).is_some(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_legitimate_names_not_flagged() {
+        let detector = GeneratedNameDetector::new();
+        for name in [
+            "lodash",
+            "express",
+            "hmac-drbg",
+            "nwsapi",
+            "cross-spawn",
+            "webpack-bundle-analyzer",
+            "cjs-module-lexer",
+            "strengths",
+            "babel-plugin-transform-es2015-arrow-functions",
+            "@typescript-eslint/parser",
+            "scikit-learn",
+            "zxcvbn",
+        ] {
+            let score = detector.score(name).map_or(0.0, |s| s.score);
+            assert!(detector.assess(name).is_none(), "{} scored {}", name, score);
+        }
+    }
+
+    #[test]
+    fn test_recent_publish_escalates() {
+        let detector = GeneratedNameDetector::new();
+        let options = AnalysisOptions::default();
+        let mut metadata = PackageMetadata {
+            name: "xkqzvbwtrplm".to_string(),
+            version: "1.0.0".to_string(),
+            description: None,
+            author: None,
+            license: None,
+            homepage: None,
+            repository: None,
+            keywords: vec![],
+            publish_date: Some("2019-01-01T00:00:00Z".to_string()),
+        };
+        let finding = detector.check(&metadata, None, &options).unwrap();
+        assert_eq!(finding.severity, RiskLevel::Low);
+
+        metadata.publish_date = Some(Utc::now().to_rfc3339());
+        let finding = detector.check(&metadata, None, &options).unwrap();
+        assert_eq!(finding.severity, RiskLevel::Medium);
+
+        let options = AnalysisOptions {
+            name_allowlist: vec!["xkqzvbwtrplm".to_string()],
+            ..AnalysisOptions::default()
+        };
+        assert!(detector.check(&metadata, None, &options).is_none());
+    }
+}
@@ -0,0 +1,611 @@
+//! Minimal HCL parsing utilities
+//!
+//! Supports the subset of HCL needed to read Terraform/OpenTofu configuration
+//! and lock files: blocks with labels, attributes, strings, heredocs, lists and
+//! objects. Anything more complex (expressions, function calls, conditionals)
+//! is preserved as raw text.
+//!
+//! Blocks, lists and objects are parsed by recursive descent, so their
+//! nesting is bounded by `ManifestLimits::max_depth` before it can exhaust
+//! the stack.
+
+use anyhow::{anyhow, Result};
+
+use crate::core::{LimitKind, ManifestLimits, ResourceLimitExceeded};
+
+/// Parsed HCL value
+#[derive(Debug, Clone, PartialEq)]
+pub enum HclValue {
+    String(String),
+    List(Vec<HclValue>),
+    Object(Vec<(String, HclValue)>),
+    Raw(String),
+}
+
+impl HclValue {
+    /// Get the value as a string (quoted strings only)
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get the value as a list
+    pub fn as_list(&self) -> Option<&[HclValue]> {
+        match self {
+            Self::List(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Look up a key in an object value
+    pub fn get(&self, key: &str) -> Option<&HclValue> {
+        match self {
+            Self::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Get the textual form of the value (strings and raw expressions)
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::String(s) | Self::Raw(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// HCL block definition
+#[derive(Debug, Clone, PartialEq)]
+pub struct HclBlock {
+    pub block_type: String,
+    pub labels: Vec<String>,
+    pub body: HclBody,
+}
+
+/// HCL body containing attributes and nested blocks
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HclBody {
+    pub attributes: Vec<(String, HclValue)>,
+    pub blocks: Vec<HclBlock>,
+}
+
+impl HclBody {
+    /// Get an attribute by name
+    pub fn attribute(&self, name: &str) -> Option<&HclValue> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v)
+    }
+
+    /// Get all nested blocks of the given type
+    pub fn blocks_of<'a>(&'a self, block_type: &'a str) -> impl Iterator<Item = &'a HclBlock> {
+        self.blocks
+            .iter()
+            .filter(move |b| b.block_type == block_type)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    LBrace,
+    RBrace,
+    LBracket,
+    RBracket,
+    LParen,
+    RParen,
+    Eq,
+    Colon,
+    Comma,
+    Newline,
+    Other(char),
+}
+
+impl Token {
+    fn text(&self) -> String {
+        match self {
+            Self::Ident(s) => s.clone(),
+            Self::Str(s) => format!("\"{}\"", s),
+            Self::LBrace => "{".to_string(),
+            Self::RBrace => "}".to_string(),
+            Self::LBracket => "[".to_string(),
+            Self::RBracket => "]".to_string(),
+            Self::LParen => "(".to_string(),
+            Self::RParen => ")".to_string(),
+            Self::Eq => "=".to_string(),
+            Self::Colon => ":".to_string(),
+            Self::Comma => ",".to_string(),
+            Self::Newline => "\n".to_string(),
+            Self::Other(c) => c.to_string(),
+        }
+    }
+}
+
+/// Parse HCL source into a body under the default [`ManifestLimits`]
+pub fn parse(input: &str) -> Result<HclBody> {
+    parse_with_limits(input, "HCL", &ManifestLimits::default())
+}
+
+/// Parse `manifest`, HCL source, into a body
+///
+/// Fails with [`ResourceLimitExceeded`] when blocks, lists or objects nest
+/// deeper than `limits.max_depth`.
+pub fn parse_with_limits(input: &str, manifest: &str, limits: &ManifestLimits) -> Result<HclBody> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        depth: 0,
+        max_depth: limits.max_depth,
+        manifest,
+    };
+    let body = parser.parse_body(false)?;
+    Ok(body)
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '*')
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\n' => {
+                tokens.push(Token::Newline);
+                i += 1;
+            }
+            c if c.is_whitespace() => i += 1,
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i + 1 < chars.len() && !(chars[i] == '*' && chars[i + 1] == '/') {
+                    i += 1;
+                }
+                i += 2;
+            }
+            '"' => {
+                let (value, next) = read_string(&chars, i + 1)?;
+                tokens.push(Token::Str(value));
+                i = next;
+            }
+            '<' if chars.get(i + 1) == Some(&'<') => {
+                let (value, next) = read_heredoc(&chars, i + 2)?;
+                tokens.push(Token::Str(value));
+                i = next;
+            }
+            '{' => {
+                tokens.push(Token::LBrace);
+                i += 1;
+            }
+            '}' => {
+                tokens.push(Token::RBrace);
+                i += 1;
+            }
+            '[' => {
+                tokens.push(Token::LBracket);
+                i += 1;
+            }
+            ']' => {
+                tokens.push(Token::RBracket);
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '=' if chars.get(i + 1) != Some(&'=') => {
+                tokens.push(Token::Eq);
+                i += 1;
+            }
+            ':' => {
+                tokens.push(Token::Colon);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            c if is_ident_char(c) => {
+                let start = i;
+                while i < chars.len() && is_ident_char(chars[i]) {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            other => {
+                tokens.push(Token::Other(other));
+                i += 1;
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Read a quoted string, keeping `${...}` interpolations verbatim
+fn read_string(chars: &[char], mut i: usize) -> Result<(String, usize)> {
+    let mut value = String::new();
+    let mut interpolation_depth = 0usize;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if interpolation_depth == 0 {
+            match c {
+                '"' => return Ok((value, i + 1)),
+                '\\' => {
+                    let escaped = chars
+                        .get(i + 1)
+                        .ok_or_else(|| anyhow!("Unterminated escape sequence"))?;
+                    value.push(match escaped {
+                        'n' => '\n',
+                        't' => '\t',
+                        other => *other,
+                    });
+                    i += 2;
+                    continue;
+                }
+                '\n' => return Err(anyhow!("Unterminated string literal")),
+                '$' if chars.get(i + 1) == Some(&'{') => {
+                    interpolation_depth = 1;
+                    value.push_str("${");
+                    i += 2;
+                    continue;
+                }
+                _ => {}
+            }
+        } else {
+            match c {
+                '{' => interpolation_depth += 1,
+                '}' => interpolation_depth -= 1,
+                _ => {}
+            }
+        }
+        value.push(c);
+        i += 1;
+    }
+
+    Err(anyhow!("Unterminated string literal"))
+}
+
+/// Read a heredoc (`<<EOF` or `<<-EOF`) body
+fn read_heredoc(chars: &[char], mut i: usize) -> Result<(String, usize)> {
+    let indented = chars.get(i) == Some(&'-');
+    if indented {
+        i += 1;
+    }
+
+    let start = i;
+    while i < chars.len() && chars[i] != '\n' {
+        i += 1;
+    }
+    let marker: String = chars[start..i]
+        .iter()
+        .collect::<String>()
+        .trim()
+        .to_string();
+    if marker.is_empty() {
+        return Err(anyhow!("Missing heredoc marker"));
+    }
+
+    let mut lines = Vec::new();
+    i += 1;
+    while i < chars.len() {
+        let line_start = i;
+        while i < chars.len() && chars[i] != '\n' {
+            i += 1;
+        }
+        let line: String = chars[line_start..i].iter().collect();
+        if line.trim() == marker {
+            return Ok((lines.join("\n"), i));
+        }
+        lines.push(if indented {
+            line.trim_start().to_string()
+        } else {
+            line
+        });
+        i += 1;
+    }
+
+    Err(anyhow!("Unterminated heredoc '{}'", marker))
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// Blocks, lists and objects currently open
+    depth: usize,
+    max_depth: usize,
+    manifest: &'a str,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// Run `parse` one level deeper, failing past `max_depth`
+    fn nested<T>(&mut self, parse: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.depth += 1;
+        if self.depth > self.max_depth {
+            return Err(ResourceLimitExceeded {
+                manifest: self.manifest.to_string(),
+                kind: LimitKind::NestingDepth,
+                limit: self.max_depth,
+                observed: self.depth,
+            }
+            .into());
+        }
+        let parsed = parse(self);
+        self.depth -= 1;
+        parsed
+    }
+
+    fn skip_newlines(&mut self) {
+        while matches!(self.peek(), Some(Token::Newline)) {
+            self.pos += 1;
+        }
+    }
+
+    fn parse_body(&mut self, nested: bool) -> Result<HclBody> {
+        let mut body = HclBody::default();
+
+        loop {
+            self.skip_newlines();
+            match self.next() {
+                None if nested => return Err(anyhow!("Unexpected end of input in block")),
+                None => return Ok(body),
+                Some(Token::RBrace) if nested => return Ok(body),
+                Some(Token::Ident(name)) => {
+                    if matches!(self.peek(), Some(Token::Eq)) {
+                        self.pos += 1;
+                        let value = self.parse_expression()?;
+                        body.attributes.push((name, value));
+                    } else {
+                        let mut labels = Vec::new();
+                        loop {
+                            match self.next() {
+                                Some(Token::Str(label)) | Some(Token::Ident(label)) => {
+                                    labels.push(label)
+                                }
+                                Some(Token::LBrace) => break,
+                                other => {
+                                    return Err(anyhow!(
+                                        "Unexpected token {:?} in block header '{}'",
+                                        other,
+                                        name
+                                    ))
+                                }
+                            }
+                        }
+                        let block_body = self.nested(|parser| parser.parse_body(true))?;
+                        body.blocks.push(HclBlock {
+                            block_type: name,
+                            labels,
+                            body: block_body,
+                        });
+                    }
+                }
+                Some(other) => return Err(anyhow!("Unexpected token {:?}", other)),
+            }
+        }
+    }
+
+    fn parse_expression(&mut self) -> Result<HclValue> {
+        let value = match self.next() {
+            Some(Token::Str(s)) => HclValue::String(s),
+            Some(Token::LBracket) => self.nested(Self::parse_list)?,
+            Some(Token::LBrace) => self.nested(Self::parse_object)?,
+            Some(Token::Ident(_) | Token::Other(_) | Token::LParen) => {
+                // Re-read the token as part of a raw expression below
+                self.pos -= 1;
+                HclValue::Raw(String::new())
+            }
+            other => return Err(anyhow!("Unexpected token {:?} in expression", other)),
+        };
+
+        // Anything that continues the expression (operators, calls, indexing)
+        // turns the whole expression into raw text
+        let mut rest = String::new();
+        let mut depth = 0usize;
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Newline | Token::Comma | Token::RBrace | Token::RBracket | Token::RParen
+                    if depth == 0 =>
+                {
+                    break
+                }
+                Token::LParen | Token::LBracket | Token::LBrace => depth += 1,
+                Token::RParen | Token::RBracket | Token::RBrace => depth -= 1,
+                _ => {}
+            }
+            if !rest.is_empty() {
+                rest.push(' ');
+            }
+            rest.push_str(&token.text());
+            self.pos += 1;
+        }
+
+        if rest.is_empty() {
+            return Ok(value);
+        }
+
+        let prefix = match &value {
+            HclValue::String(s) => format!("\"{}\" ", s),
+            HclValue::Raw(s) if !s.is_empty() => format!("{} ", s),
+            _ => String::new(),
+        };
+        Ok(HclValue::Raw(format!("{}{}", prefix, rest)))
+    }
+
+    fn parse_list(&mut self) -> Result<HclValue> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_newlines();
+            match self.peek() {
+                Some(Token::RBracket) => {
+                    self.pos += 1;
+                    return Ok(HclValue::List(items));
+                }
+                Some(Token::Comma) => self.pos += 1,
+                None => return Err(anyhow!("Unterminated list")),
+                _ => items.push(self.parse_expression()?),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<HclValue> {
+        let mut entries = Vec::new();
+        loop {
+            self.skip_newlines();
+            match self.next() {
+                Some(Token::RBrace) => return Ok(HclValue::Object(entries)),
+                Some(Token::Comma) => {}
+                Some(Token::Ident(key)) | Some(Token::Str(key)) => {
+                    match self.next() {
+                        Some(Token::Eq) | Some(Token::Colon) => {}
+                        other => {
+                            return Err(anyhow!(
+                                "Expected '=' after object key '{}', found {:?}",
+                                key,
+                                other
+                            ))
+                        }
+                    }
+                    let value = self.parse_expression()?;
+                    entries.push((key, value));
+                }
+                None => return Err(anyhow!("Unterminated object")),
+                Some(other) => return Err(anyhow!("Unexpected token {:?} in object", other)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nesting_depth_bounded() {
+        let limits = ManifestLimits::default();
+        let nested = "a {\n".repeat(limits.max_depth + 1);
+        let err = parse_with_limits(&nested, "main.tf", &limits).unwrap_err();
+        let exceeded = err.downcast_ref::<ResourceLimitExceeded>().unwrap();
+        assert_eq!(exceeded.observed, limits.max_depth + 1);
+
+        // Lists and objects count towards the same depth
+        let value = format!("x = {}", "[{ y = ".repeat(limits.max_depth));
+        assert!(parse_with_limits(&value, "main.tf", &limits)
+            .unwrap_err()
+            .is::<ResourceLimitExceeded>());
+    }
+
+    #[test]
+    fn test_parse_blocks_and_attributes() {
+        let body = parse(
+            r#"
+# Network module
+module "vpc" {
+  source  = "terraform-aws-modules/vpc/aws"
+  version = "~> 5.0"
+  azs     = ["eu-west-1a", "eu-west-1b"]
+}
+
+terraform {
+  required_providers {
+    aws = {
+      source  = "hashicorp/aws"
+      version = ">= 4.0"
+    }
+  }
+}
+"#,
+        )
+        .unwrap();
+
+        let module = body.blocks_of("module").next().unwrap();
+        assert_eq!(module.labels, vec!["vpc".to_string()]);
+        assert_eq!(
+            module.body.attribute("source").and_then(|v| v.as_str()),
+            Some("terraform-aws-modules/vpc/aws")
+        );
+        assert_eq!(
+            module
+                .body
+                .attribute("azs")
+                .and_then(|v| v.as_list())
+                .map(|l| l.len()),
+            Some(2)
+        );
+
+        let providers = body
+            .blocks_of("terraform")
+            .next()
+            .and_then(|t| t.body.blocks_of("required_providers").next())
+            .unwrap();
+        let aws = providers.body.attribute("aws").unwrap();
+        assert_eq!(
+            aws.get("source").and_then(|v| v.as_str()),
+            Some("hashicorp/aws")
+        );
+    }
+
+    #[test]
+    fn test_parse_expressions_and_heredoc() {
+        let body = parse(
+            r#"
+resource "null_resource" "setup" {
+  count = var.enabled ? 1 : 0
+  provisioner "local-exec" {
+    command = <<-EOT
+      curl -s https://example.com/install.sh | sh
+    EOT
+  }
+}
+"#,
+        )
+        .unwrap();
+
+        let resource = body.blocks_of("resource").next().unwrap();
+        assert!(matches!(
+            resource.body.attribute("count"),
+            Some(HclValue::Raw(_))
+        ));
+        let provisioner = resource.body.blocks_of("provisioner").next().unwrap();
+        assert_eq!(
+            provisioner
+                .body
+                .attribute("command")
+                .and_then(|v| v.as_str()),
+            Some("curl -s https://example.com/install.sh | sh")
+        );
+    }
+}
@@ -1,5 +1,19 @@
 //! Utility modules
 
+pub mod binary;
+pub mod confusables;
+pub mod digest;
+pub mod generated_names;
+pub mod hcl;
+pub mod names;
+#[cfg(feature = "runtime")]
+pub mod npm_tarball;
 pub mod pattern_matcher;
+pub mod pep508;
+#[cfg(feature = "runtime")]
+pub mod python_archive;
+#[cfg(feature = "runtime")]
+pub mod requirements_txt;
 pub mod typosquatting;
 pub mod version_parser;
+pub mod yarn_lock;
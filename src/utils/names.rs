@@ -0,0 +1,100 @@
+//! Per-ecosystem normalization of package names for matching
+
+use serde::{Deserialize, Serialize};
+
+/// How an ecosystem decides whether two package names are the same package
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NameNormalization {
+    /// Names compare exactly (npm, Maven, Go, RubyGems)
+    Exact,
+    /// Names compare case-insensitively (Terraform, Composer, Debian, NuGet)
+    Lowercase,
+    /// Case-insensitive with `-` and `_` equivalent (crates.io)
+    Cargo,
+    /// PEP 503: case-insensitive with runs of `-`, `_` and `.` equivalent (PyPI)
+    Pep503,
+}
+
+impl NameNormalization {
+    /// Normalization used by an ecosystem; unknown ecosystems compare exactly
+    pub fn for_ecosystem(ecosystem: &str) -> Self {
+        match ecosystem.to_lowercase().as_str() {
+            "python" | "pypi" => Self::Pep503,
+            "cargo" | "rust" | "crates.io" => Self::Cargo,
+            "terraform" | "composer" | "php" | "debian" | "nuget" => Self::Lowercase,
+            _ => Self::Exact,
+        }
+    }
+
+    /// Normalize a name as written in a manifest
+    ///
+    /// Requirement decorations such as extras (`Flask[async]`) and environment
+    /// markers (`; python_version < "3.8"`) are stripped for PEP 503 names.
+    pub fn normalize(&self, name: &str) -> String {
+        let name = name.trim();
+        match self {
+            Self::Exact => name.to_string(),
+            Self::Lowercase => name.to_lowercase(),
+            Self::Cargo => name.to_lowercase().replace('_', "-"),
+            Self::Pep503 => {
+                let name = name.split(';').next().unwrap_or_default();
+                let name = name.split('[').next().unwrap_or_default().trim();
+                let mut normalized = String::with_capacity(name.len());
+                let mut in_separator = false;
+                for c in name.chars() {
+                    if matches!(c, '-' | '_' | '.') {
+                        in_separator = true;
+                        continue;
+                    }
+                    if in_separator && !normalized.is_empty() {
+                        normalized.push('-');
+                    }
+                    in_separator = false;
+                    normalized.extend(c.to_lowercase());
+                }
+                normalized
+            }
+        }
+    }
+}
+
+/// Normalize a package name under its ecosystem's rules
+pub fn normalize_name(name: &str, ecosystem: &str) -> String {
+    NameNormalization::for_ecosystem(ecosystem).normalize(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pep503_normalization() {
+        for name in [
+            "Flask",
+            "flask",
+            "FLASK",
+            "Flask[async]",
+            "flask[async,dotenv]",
+            " Flask ; python_version >= \"3.8\"",
+        ] {
+            assert_eq!(normalize_name(name, "python"), "flask", "{}", name);
+        }
+        for name in ["Foo_Bar", "foo-bar", "foo.bar", "FOO__BAR", "foo-_.bar"] {
+            assert_eq!(normalize_name(name, "python"), "foo-bar", "{}", name);
+        }
+        // Separators at the edges are dropped rather than kept
+        assert_eq!(normalize_name("_private_", "python"), "private");
+        assert_eq!(normalize_name("zope.interface", "pypi"), "zope-interface");
+    }
+
+    #[test]
+    fn test_other_ecosystems() {
+        // npm names are case-sensitive and scopes are part of the name
+        assert_eq!(normalize_name("@Babel/Core", "npm"), "@Babel/Core");
+        assert_eq!(normalize_name("Serde_JSON", "cargo"), "serde-json");
+        assert_eq!(
+            normalize_name("HashiCorp/AWS", "terraform"),
+            "hashicorp/aws"
+        );
+    }
+}
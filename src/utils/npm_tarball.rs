@@ -0,0 +1,437 @@
+//! npm package tarballs
+//!
+//! `npm pack` puts every file under a single `package/` directory, and npm
+//! installs from there. A tarball with manifests elsewhere, or entries beside
+//! that directory, was not produced by npm: a second `package.json` can make
+//! a scanner or a reviewer read a different manifest from the one npm
+//! installs. The canonical root is picked the way npm does, and every
+//! deviation is recorded.
+//!
+//! Tarballs are streamed through gzip and tar readers straight to disk. The
+//! unpacked stream is held to the decompression limits of the options (see
+//! [`crate::core::archive`]) and each entry is capped. An entry whose path, or
+//! whose link target, leads out of the archive fails the extraction before
+//! anything is written.
+
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use std::collections::BTreeSet;
+use std::path::{Component, Path, PathBuf};
+use tempfile::TempDir;
+
+use crate::core::{
+    rules, AnalysisOptions, DecompressionGuard, Finding, FindingType, RiskLevel, ScanReason,
+};
+use crate::error::PackageSecurityError;
+
+/// Directory `npm pack` puts the package in
+pub const CANONICAL_ROOT: &str = "package";
+
+/// Most bytes unpacked from one tarball entry
+pub const MAX_ENTRY_SIZE: u64 = 128 * 1024 * 1024;
+
+/// Where the manifests of a tarball sit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TarballLayout {
+    /// Directory analyzed as the package, relative to the archive root; empty
+    /// when the manifest is at the top level
+    pub root: String,
+    /// Manifests outside `root`
+    pub stray_manifests: Vec<String>,
+    /// Manifests inside `root` other than its own, excluding `node_modules`
+    pub nested_manifests: Vec<String>,
+    /// Top-level entries other than `root`
+    pub extra_entries: Vec<String>,
+}
+
+impl TarballLayout {
+    /// Layout of a tarball from its entry paths, `None` without any manifest
+    ///
+    /// The root is `package/` when it holds a manifest, as npm expects;
+    /// otherwise the directory of the shallowest manifest, the first by name
+    /// among equally shallow ones.
+    pub fn from_paths<S: AsRef<str>>(paths: &[S]) -> Option<Self> {
+        let paths: BTreeSet<&str> = paths
+            .iter()
+            .map(|path| path.as_ref().trim_start_matches("./").trim_end_matches('/'))
+            .filter(|path| !path.is_empty())
+            .collect();
+        let manifests: Vec<&str> = paths
+            .iter()
+            .copied()
+            .filter(|path| *path == "package.json" || path.ends_with("/package.json"))
+            .collect();
+
+        let canonical = format!("{}/package.json", CANONICAL_ROOT);
+        let root_manifest = if manifests.contains(&canonical.as_str()) {
+            canonical.as_str()
+        } else {
+            manifests
+                .iter()
+                .copied()
+                .min_by_key(|path| (path.matches('/').count(), *path))?
+        };
+        let root = root_manifest
+            .strip_suffix("package.json")
+            .unwrap_or_default()
+            .trim_end_matches('/')
+            .to_string();
+        let in_root = |path: &str| root.is_empty() || path.starts_with(&format!("{}/", root));
+
+        let (nested_manifests, stray_manifests): (Vec<_>, Vec<_>) = manifests
+            .iter()
+            .filter(|path| **path != root_manifest)
+            .map(|path| path.to_string())
+            .partition(|path| in_root(path));
+        let nested_manifests = nested_manifests
+            .into_iter()
+            .filter(|path| !path.split('/').any(|part| part == "node_modules"))
+            .collect();
+
+        let top_level = root.split('/').next().unwrap_or_default();
+        let extra_entries = if root.is_empty() {
+            Vec::new()
+        } else {
+            paths
+                .iter()
+                .map(|path| path.split('/').next().unwrap_or_default())
+                .filter(|entry| *entry != top_level)
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .map(str::to_string)
+                .collect()
+        };
+
+        Some(Self {
+            root,
+            stray_manifests,
+            nested_manifests,
+            extra_entries,
+        })
+    }
+
+    /// Whether the tarball looks like `npm pack` output
+    pub fn is_conventional(&self) -> bool {
+        self.root == CANONICAL_ROOT
+            && self.stray_manifests.is_empty()
+            && self.nested_manifests.is_empty()
+            && self.extra_entries.is_empty()
+    }
+
+    /// Report deviations as a `SuspiciousArchiveLayout` finding
+    ///
+    /// High when a manifest sits outside the analyzed root, medium for a
+    /// non-standard root or extra top-level entries, low for manifests nested
+    /// inside the package.
+    pub fn to_finding(&self) -> Option<Finding> {
+        if self.is_conventional() {
+            return None;
+        }
+        let severity = if !self.stray_manifests.is_empty() {
+            RiskLevel::High
+        } else if self.root != CANONICAL_ROOT || !self.extra_entries.is_empty() {
+            RiskLevel::Medium
+        } else {
+            RiskLevel::Low
+        };
+        let root = if self.root.is_empty() {
+            "the archive root"
+        } else {
+            self.root.as_str()
+        };
+        let mut finding = Finding::new(
+            FindingType::SuspiciousArchiveLayout,
+            severity,
+            "npm tarball has an unexpected layout",
+            format!(
+                "npm packs a package into a single {}/ directory; this tarball deviates from that, so other manifests may be mistaken for the one npm installs. {} was analyzed",
+                CANONICAL_ROOT, root
+            ),
+        )
+        .with_evidence(format!("Analyzed root: {}", root))
+        .with_scan_reason(ScanReason::Manifest)
+        .with_rule_id(rules::NPM_ARCHIVE_LAYOUT);
+        for manifest in &self.stray_manifests {
+            finding = finding.with_evidence(format!("Manifest outside the root: {}", manifest));
+        }
+        for manifest in &self.nested_manifests {
+            finding = finding.with_evidence(format!("Nested manifest: {}", manifest));
+        }
+        for entry in &self.extra_entries {
+            finding = finding.with_evidence(format!("Extra top-level entry: {}", entry));
+        }
+        Some(finding)
+    }
+}
+
+/// A tarball unpacked into a temporary directory, removed on drop
+#[derive(Debug)]
+pub struct ExtractedTarball {
+    dir: TempDir,
+    pub layout: TarballLayout,
+}
+
+impl ExtractedTarball {
+    /// Directory of the package to analyze
+    pub fn root(&self) -> std::path::PathBuf {
+        self.dir.path().join(&self.layout.root)
+    }
+}
+
+/// Unpack a gzipped npm tarball with the default decompression limits
+pub fn extract(path: &Path) -> Result<ExtractedTarball> {
+    extract_with_options(path, &AnalysisOptions::default())
+}
+
+/// Unpack a gzipped npm tarball
+///
+/// Only regular files and directories are unpacked; links are skipped. Fails
+/// with [`crate::PackageSecurityError::UnsafePath`] when an entry's path is
+/// absolute or its normalized path, or a link's target, leaves the archive
+/// root. Fails when the tarball holds no `package.json` or an entry is larger
+/// than [`MAX_ENTRY_SIZE`], and with
+/// [`crate::PackageSecurityError::DecompressionBomb`] when the tar stream
+/// passes `options.max_decompressed_bytes` or `options.max_compression_ratio`.
+pub fn extract_with_options(path: &Path, options: &AnalysisOptions) -> Result<ExtractedTarball> {
+    extract_within(path, options, MAX_ENTRY_SIZE)
+}
+
+fn extract_within(
+    path: &Path,
+    options: &AnalysisOptions,
+    max_entry: u64,
+) -> Result<ExtractedTarball> {
+    let file =
+        std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let dir = TempDir::new().context("Failed to create a directory to unpack into")?;
+    // Entries are resolved lexically against the canonical root; no link is
+    // ever written, so nothing inside it can redirect them
+    let root = dir
+        .path()
+        .canonicalize()
+        .context("Failed to resolve the directory to unpack into")?;
+    let guard = DecompressionGuard::new(path.display().to_string(), options);
+    let mut archive = tar::Archive::new(guard.decompressed(GzDecoder::new(guard.compressed(file))));
+
+    // The tar and gzip readers wrap the guard's error; report the limit instead
+    let paths =
+        unpack_entries(path, &mut archive, &root, max_entry).map_err(|e| guard.explain(e))?;
+
+    let layout = TarballLayout::from_paths(&paths)
+        .with_context(|| format!("{} contains no package.json", path.display()))?;
+    Ok(ExtractedTarball { dir, layout })
+}
+
+/// Unpack the entries of `archive` into `root`, returning the paths unpacked
+pub(crate) fn unpack_entries<R: std::io::Read>(
+    path: &Path,
+    archive: &mut tar::Archive<R>,
+    root: &Path,
+    max_entry: u64,
+) -> Result<Vec<String>> {
+    let mut paths = Vec::new();
+    for entry in archive.entries().context("Not a gzipped tarball")? {
+        let mut entry = entry.context("Corrupt tarball entry")?;
+        let entry_path = entry.path()?.into_owned();
+        let name = entry_path.to_string_lossy().replace('\\', "/");
+        if normalize(&entry_path).is_none() {
+            return Err(PackageSecurityError::UnsafePath { entry: name }.into());
+        }
+        let kind = entry.header().entry_type();
+        if kind.is_symlink() || kind.is_hard_link() {
+            if let Some(target) = entry.link_name()? {
+                // Symlinks resolve from their own directory, hard links from
+                // the archive root
+                let resolved = if kind.is_symlink() {
+                    entry_path.parent().unwrap_or(Path::new("")).join(&target)
+                } else {
+                    target.to_path_buf()
+                };
+                if target.is_absolute() || normalize(&resolved).is_none() {
+                    return Err(PackageSecurityError::UnsafePath {
+                        entry: format!("{} -> {}", name, target.display()),
+                    }
+                    .into());
+                }
+            }
+        }
+        if !(kind.is_file() || kind.is_dir()) {
+            continue;
+        }
+        // Checked before any of the entry is read, so a header claiming a
+        // huge size costs nothing
+        if entry.size() > max_entry {
+            bail!(
+                "{} holds an entry larger than {} bytes: {}",
+                path.display(),
+                max_entry,
+                name
+            );
+        }
+        if entry.unpack_in(root)? {
+            paths.push(name);
+        }
+    }
+    Ok(paths)
+}
+
+/// Entry path with `.` and `..` resolved, `None` when it leads out of the
+/// archive root or is absolute
+pub(crate) fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return None;
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout() {
+        let packed = TarballLayout::from_paths(&[
+            "package/package.json",
+            "package/index.js",
+            "package/node_modules/dep/package.json",
+        ])
+        .unwrap();
+        assert!(packed.is_conventional());
+        assert!(packed.to_finding().is_none());
+
+        let smuggled = TarballLayout::from_paths(&[
+            "package.json",
+            "package/package.json",
+            "package/lib/package.json",
+        ])
+        .unwrap();
+        assert_eq!(smuggled.root, "package");
+        assert_eq!(smuggled.stray_manifests, ["package.json"]);
+        assert_eq!(smuggled.nested_manifests, ["package/lib/package.json"]);
+        assert_eq!(smuggled.to_finding().unwrap().severity, RiskLevel::High);
+
+        // Tarballs not made by npm pack, such as some @types publishes
+        let renamed = TarballLayout::from_paths(&["node/package.json", "node/index.d.ts"]).unwrap();
+        assert_eq!(renamed.root, "node");
+        assert_eq!(renamed.to_finding().unwrap().severity, RiskLevel::Medium);
+
+        assert!(TarballLayout::from_paths(&["README.md"]).is_none());
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize(Path::new("package/./lib/../index.js")),
+            Some(PathBuf::from("package/index.js"))
+        );
+        assert_eq!(normalize(Path::new("package/../../etc/passwd")), None);
+        assert_eq!(normalize(Path::new("/etc/passwd")), None);
+    }
+
+    #[test]
+    fn test_escaping_links() {
+        let dir = TempDir::new().unwrap();
+        let pack = |name: &str, links: &[(&str, tar::EntryType, &str)]| {
+            let archive = dir.path().join(name);
+            let file = std::fs::File::create(&archive).unwrap();
+            let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+                file,
+                flate2::Compression::default(),
+            ));
+            let manifest = br#"{"name": "links"}"#;
+            let mut header = tar::Header::new_gnu();
+            header.set_size(manifest.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, "package/package.json", &manifest[..])
+                .unwrap();
+            for (path, kind, target) in links {
+                let mut header = tar::Header::new_gnu();
+                header.set_entry_type(*kind);
+                header.set_size(0);
+                header.set_link_name_literal(target).unwrap();
+                header.set_cksum();
+                builder
+                    .append_data(&mut header, path, std::io::empty())
+                    .unwrap();
+            }
+            builder.into_inner().unwrap().finish().unwrap();
+            archive
+        };
+
+        // Links staying inside are skipped, not refused
+        let inside = pack(
+            "inside.tgz",
+            &[(
+                "package/lib/index.js",
+                tar::EntryType::Symlink,
+                "../main.js",
+            )],
+        );
+        assert!(extract(&inside).is_ok());
+
+        for (path, kind, target) in [
+            ("package/lib/etc", tar::EntryType::Symlink, "../../../etc"),
+            ("package/passwd", tar::EntryType::Symlink, "/etc/passwd"),
+            ("package/shadow", tar::EntryType::Link, "../etc/shadow"),
+        ] {
+            let archive = pack("escaping.tgz", &[(path, kind, target)]);
+            let error = extract(&archive).unwrap_err();
+            assert_eq!(
+                error.downcast_ref::<PackageSecurityError>(),
+                Some(&PackageSecurityError::UnsafePath {
+                    entry: format!("{} -> {}", path, target)
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_size_caps() {
+        let dir = TempDir::new().unwrap();
+        let archive = dir.path().join("big.tgz");
+        let file = std::fs::File::create(&archive).unwrap();
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            file,
+            flate2::Compression::default(),
+        ));
+        for name in ["package/package.json", "package/a.js", "package/b.js"] {
+            let content = [b' '; 100];
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, &content[..])
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let options = AnalysisOptions::default();
+        assert!(extract_within(&archive, &options, 1000).is_ok());
+        let error = extract_within(&archive, &options, 99).unwrap_err();
+        assert!(error.to_string().contains("entry larger than 99 bytes"));
+
+        // The limit counts the tar stream, headers included
+        let capped = AnalysisOptions {
+            max_decompressed_bytes: 1000,
+            ..options
+        };
+        let error = extract_within(&archive, &capped, 1000).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<crate::PackageSecurityError>(),
+            Some(crate::PackageSecurityError::DecompressionBomb { .. })
+        ));
+    }
+}
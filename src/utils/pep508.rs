@@ -0,0 +1,335 @@
+//! PEP 508 requirement strings and environment markers
+//!
+//! A requirement names a distribution with optional extras, then either a
+//! version specifier or a direct URL, then an environment marker limiting
+//! where it is installed:
+//! `requests[socks] (>=2.0,<3); python_version < "3.8"`.
+//!
+//! Markers are evaluated against a map of marker variables such as
+//! `python_version` or `sys_platform`. A comparison with a variable the map
+//! does not hold counts as true, so a partial environment only rules out
+//! requirements it contradicts.
+
+use std::collections::HashMap;
+
+use crate::versioning::VersionSchemeRegistry;
+
+/// Marker variables compared as PEP 440 versions rather than strings
+const VERSION_VARIABLES: &[&str] = &[
+    "python_version",
+    "python_full_version",
+    "implementation_version",
+];
+
+/// A parsed requirement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Requirement {
+    pub name: String,
+    pub extras: Vec<String>,
+    /// Version specifier, `*` when there is none or the requirement is a URL
+    pub version_spec: String,
+    /// Direct reference of a `name @ url` requirement
+    pub url: Option<String>,
+    /// Environment marker, without the leading `;`
+    pub marker: Option<String>,
+}
+
+/// Parse a requirement, `None` when it does not start with a name
+pub fn parse(requirement: &str) -> Option<Requirement> {
+    let requirement = requirement.trim();
+    let end = requirement
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(requirement.len());
+    let name = &requirement[..end];
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut rest = requirement[end..].trim_start();
+    let mut extras = Vec::new();
+    if let Some(inner) = rest.strip_prefix('[') {
+        let (list, after) = inner.split_once(']').unwrap_or((inner, ""));
+        extras = list
+            .split(',')
+            .map(str::trim)
+            .filter(|extra| !extra.is_empty())
+            .map(str::to_string)
+            .collect();
+        rest = after.trim_start();
+    }
+
+    // A URL may contain `;`, so its marker must follow whitespace
+    let (spec, url, marker) = if let Some(reference) = rest.strip_prefix('@') {
+        let reference = reference.trim_start();
+        let (url, marker) = match reference.find(char::is_whitespace) {
+            Some(pos) => (&reference[..pos], reference[pos..].trim_start()),
+            None => (reference, ""),
+        };
+        let marker = marker.strip_prefix(';').map(str::trim);
+        ("", Some(url.to_string()), marker)
+    } else {
+        let (spec, marker) = match rest.split_once(';') {
+            Some((spec, marker)) => (spec, Some(marker.trim())),
+            None => (rest, None),
+        };
+        let spec = spec.trim();
+        let spec = spec
+            .strip_prefix('(')
+            .and_then(|spec| spec.strip_suffix(')'))
+            .unwrap_or(spec)
+            .trim();
+        (spec, None, marker)
+    };
+
+    Some(Requirement {
+        name: name.to_string(),
+        extras,
+        version_spec: if spec.is_empty() { "*" } else { spec }.to_string(),
+        url,
+        marker: marker
+            .filter(|marker| !marker.is_empty())
+            .map(str::to_string),
+    })
+}
+
+/// Whether `marker` holds in the environment `env` describes
+///
+/// An empty environment, or a marker that does not parse, matches.
+pub fn marker_matches(marker: &str, env: &HashMap<String, String>) -> bool {
+    if env.is_empty() {
+        return true;
+    }
+    let tokens = tokenize(marker);
+    let mut parser = MarkerParser {
+        tokens: &tokens,
+        pos: 0,
+        env,
+    };
+    match parser.or_expr() {
+        Some(matches) if parser.pos == tokens.len() => matches,
+        _ => true,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(String),
+    Variable(String),
+    Op(String),
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+fn tokenize(marker: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = marker.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            '\'' | '"' => {
+                chars.next();
+                let mut literal = String::new();
+                for (_, ch) in chars.by_ref() {
+                    if ch == c {
+                        break;
+                    }
+                    literal.push(ch);
+                }
+                tokens.push(Token::Literal(literal));
+            }
+            '=' | '!' | '<' | '>' | '~' => {
+                let mut op = String::new();
+                while let Some(&(_, ch)) = chars.peek() {
+                    if !matches!(ch, '=' | '!' | '<' | '>' | '~') {
+                        break;
+                    }
+                    op.push(ch);
+                    chars.next();
+                }
+                tokens.push(Token::Op(op));
+            }
+            _ => {
+                let mut end = start;
+                while let Some(&(i, ch)) = chars.peek() {
+                    if !(ch.is_ascii_alphanumeric() || matches!(ch, '_' | '.')) {
+                        break;
+                    }
+                    end = i + ch.len_utf8();
+                    chars.next();
+                }
+                if end == start {
+                    // Not part of any marker; leave it to fail the parse
+                    chars.next();
+                    tokens.push(Token::Op(c.to_string()));
+                    continue;
+                }
+                let word = &marker[start..end];
+                tokens.push(match word {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "in" => Token::Op("in".to_string()),
+                    "not" => Token::Op("not".to_string()),
+                    _ => Token::Variable(word.to_string()),
+                });
+            }
+        }
+    }
+    tokens
+}
+
+struct MarkerParser<'a> {
+    tokens: &'a [Token],
+    pos: usize,
+    env: &'a HashMap<String, String>,
+}
+
+impl MarkerParser<'_> {
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.pos)?;
+        self.pos += 1;
+        Some(token)
+    }
+
+    fn or_expr(&mut self) -> Option<bool> {
+        let mut matches = self.and_expr()?;
+        while self.tokens.get(self.pos) == Some(&Token::Or) {
+            self.pos += 1;
+            matches |= self.and_expr()?;
+        }
+        Some(matches)
+    }
+
+    fn and_expr(&mut self) -> Option<bool> {
+        let mut matches = self.atom()?;
+        while self.tokens.get(self.pos) == Some(&Token::And) {
+            self.pos += 1;
+            matches &= self.atom()?;
+        }
+        Some(matches)
+    }
+
+    fn atom(&mut self) -> Option<bool> {
+        if self.tokens.get(self.pos) == Some(&Token::Open) {
+            self.pos += 1;
+            let matches = self.or_expr()?;
+            return (self.next()? == &Token::Close).then_some(matches);
+        }
+        let left = self.next()?.clone();
+        let mut op = match self.next()? {
+            Token::Op(op) => op.clone(),
+            _ => return None,
+        };
+        if op == "not" {
+            match self.next()? {
+                Token::Op(next) if next == "in" => op = "not in".to_string(),
+                _ => return None,
+            }
+        }
+        let right = self.next()?.clone();
+        compare(&left, &op, &right, self.env)
+    }
+}
+
+/// Evaluate one comparison; `true` when it names an unknown variable
+fn compare(left: &Token, op: &str, right: &Token, env: &HashMap<String, String>) -> Option<bool> {
+    let value = |token: &Token| match token {
+        Token::Literal(literal) => Some(Some(literal.clone())),
+        Token::Variable(variable) => Some(env.get(variable).cloned()),
+        _ => None,
+    };
+    let (Some(lhs), Some(rhs)) = (value(left)?, value(right)?) else {
+        return Some(true);
+    };
+
+    let is_version = [left, right].iter().any(
+        |token| matches!(token, Token::Variable(v) if VERSION_VARIABLES.contains(&v.as_str())),
+    );
+    if is_version && !matches!(op, "in" | "not in") {
+        // `'3.8' > python_version` reads as `python_version < '3.8'`
+        let (version, op, target) = match (left, op) {
+            (Token::Literal(_), ">") => (&rhs, "<", &lhs),
+            (Token::Literal(_), "<") => (&rhs, ">", &lhs),
+            (Token::Literal(_), ">=") => (&rhs, "<=", &lhs),
+            (Token::Literal(_), "<=") => (&rhs, ">=", &lhs),
+            _ => (&lhs, op, &rhs),
+        };
+        let scheme = VersionSchemeRegistry::global().get_or_default("python");
+        if let Ok(matches) = scheme.satisfies(version, &format!("{}{}", op, target)) {
+            return Some(matches);
+        }
+    }
+
+    Some(match op {
+        "==" | "===" => lhs == rhs,
+        "!=" => lhs != rhs,
+        "in" => rhs.contains(lhs.as_str()),
+        "not in" => !rhs.contains(lhs.as_str()),
+        "<" => lhs < rhs,
+        "<=" => lhs <= rhs,
+        ">" => lhs > rhs,
+        ">=" => lhs >= rhs,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let requirement =
+            parse("requests[socks, security] (>=2.0,<3) ; python_version < '3.8'").unwrap();
+        assert_eq!(requirement.name, "requests");
+        assert_eq!(requirement.extras, ["socks", "security"]);
+        assert_eq!(requirement.version_spec, ">=2.0,<3");
+        assert_eq!(
+            requirement.marker.as_deref(),
+            Some("python_version < '3.8'")
+        );
+
+        let requirement =
+            parse("tool @ https://example.com/tool.tar.gz;v=1 ; sys_platform == 'linux'").unwrap();
+        assert_eq!(requirement.version_spec, "*");
+        assert_eq!(
+            requirement.url.as_deref(),
+            Some("https://example.com/tool.tar.gz;v=1")
+        );
+        assert_eq!(
+            requirement.marker.as_deref(),
+            Some("sys_platform == 'linux'")
+        );
+
+        assert_eq!(parse("zope.interface>=5").unwrap().version_spec, ">=5");
+        assert_eq!(parse("; extra"), None);
+    }
+
+    #[test]
+    fn test_marker_matches() {
+        let env: HashMap<String, String> = [("python_version", "3.11"), ("sys_platform", "linux")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        assert!(!marker_matches("python_version < '3.8'", &env));
+        // Compared as versions, not strings
+        assert!(marker_matches("python_version >= '3.9'", &env));
+        assert!(marker_matches("'3.8' < python_version", &env));
+        assert!(!marker_matches(
+            "sys_platform == 'win32' or (python_version < '3.8' and sys_platform == 'linux')",
+            &env
+        ));
+        assert!(marker_matches("sys_platform in 'linux darwin'", &env));
+        assert!(!marker_matches("sys_platform not in 'linux darwin'", &env));
+        // Unknown variables and an empty environment rule nothing out
+        assert!(marker_matches("platform_machine == 'arm64'", &env));
+        assert!(marker_matches("python_version < '3.8'", &HashMap::new()));
+    }
+}
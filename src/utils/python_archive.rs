@@ -0,0 +1,393 @@
+//! Python wheels and source distributions
+//!
+//! A wheel is a zip laid out as it installs, with its metadata in
+//! `{name}-{version}.dist-info/METADATA`. A source distribution is a gzipped
+//! tarball holding one `{name}-{version}/` directory with `PKG-INFO` and,
+//! usually, a `setup.py` that pip runs at install. Both carry core metadata:
+//! email-style headers followed by the long description.
+//!
+//! Archives are unpacked to a temporary directory under the same limits as
+//! npm tarballs (see [`super::npm_tarball`]): the stream is held to the
+//! decompression limits of the options, each entry is capped at
+//! [`MAX_ENTRY_SIZE`], and an entry or link leading out of the archive fails
+//! the extraction.
+
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use zip::ZipArchive;
+
+use super::npm_tarball::{normalize, unpack_entries, MAX_ENTRY_SIZE};
+use crate::core::{archive, AnalysisOptions, DecompressionGuard, Finding};
+use crate::error::PackageSecurityError;
+
+/// Kind of distribution archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DistributionKind {
+    /// A built `.whl`
+    Wheel,
+    /// A `.tar.gz` source distribution
+    Sdist,
+}
+
+impl DistributionKind {
+    /// Kind of archive `path` names, by extension
+    pub fn of(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_ascii_lowercase();
+        if name.ends_with(".whl") {
+            Some(Self::Wheel)
+        } else if name.ends_with(".tar.gz") {
+            Some(Self::Sdist)
+        } else {
+            None
+        }
+    }
+}
+
+/// Core metadata from `METADATA` or `PKG-INFO`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoreMetadata {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub summary: Option<String>,
+    pub author: Option<String>,
+    pub author_email: Option<String>,
+    pub maintainer: Option<String>,
+    pub maintainer_email: Option<String>,
+    pub license: Option<String>,
+    pub home_page: Option<String>,
+    pub keywords: Vec<String>,
+    pub requires_python: Option<String>,
+    pub classifiers: Vec<String>,
+    /// `Project-URL` entries by label
+    pub project_urls: HashMap<String, String>,
+    /// `Requires-Dist` requirements, environment markers included
+    pub requires_dist: Vec<String>,
+}
+
+impl CoreMetadata {
+    /// Parse the headers of a metadata file; the description body is skipped
+    pub fn parse(content: &str) -> Self {
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for line in content.lines() {
+            if line.trim().is_empty() {
+                break;
+            }
+            // Folded header lines continue the previous value
+            if line.starts_with([' ', '\t']) {
+                if let Some((_, value)) = headers.last_mut() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+                continue;
+            }
+            if let Some((key, value)) = line.split_once(':') {
+                headers.push((key.trim().to_ascii_lowercase(), value.trim().to_string()));
+            }
+        }
+
+        let mut metadata = Self::default();
+        for (key, value) in headers {
+            if value.is_empty() || value == "UNKNOWN" {
+                continue;
+            }
+            match key.as_str() {
+                "name" => metadata.name = Some(value),
+                "version" => metadata.version = Some(value),
+                "summary" => metadata.summary = Some(value),
+                "author" => metadata.author = Some(value),
+                "author-email" => metadata.author_email = Some(value),
+                "maintainer" => metadata.maintainer = Some(value),
+                "maintainer-email" => metadata.maintainer_email = Some(value),
+                "license" => metadata.license = Some(value),
+                "home-page" => metadata.home_page = Some(value),
+                "keywords" => metadata.keywords.extend(
+                    value
+                        .split([',', ' '])
+                        .map(str::trim)
+                        .filter(|keyword| !keyword.is_empty())
+                        .map(str::to_string),
+                ),
+                "requires-python" => metadata.requires_python = Some(value),
+                "classifier" => metadata.classifiers.push(value),
+                "project-url" => {
+                    if let Some((label, url)) = value.split_once(',') {
+                        metadata
+                            .project_urls
+                            .insert(label.trim().to_string(), url.trim().to_string());
+                    }
+                }
+                "requires-dist" => metadata.requires_dist.push(value),
+                _ => {}
+            }
+        }
+        metadata
+    }
+}
+
+/// A distribution unpacked into a temporary directory, removed on drop
+#[derive(Debug)]
+pub struct ExtractedDistribution {
+    dir: TempDir,
+    pub kind: DistributionKind,
+    /// Directory analyzed as the package, relative to the archive root
+    pub root: String,
+    /// `METADATA` or `PKG-INFO`, relative to the archive root
+    pub metadata_file: Option<String>,
+    /// Wheel entries that are password-protected and were not unpacked
+    pub encrypted_entries: Vec<String>,
+    /// Total number of entries in the archive
+    pub total_entries: usize,
+}
+
+impl ExtractedDistribution {
+    /// Directory of the package to analyze
+    pub fn root(&self) -> PathBuf {
+        self.dir.path().join(&self.root)
+    }
+
+    /// Core metadata of the distribution, `None` without a metadata file
+    pub fn metadata(&self) -> Result<Option<CoreMetadata>> {
+        let Some(file) = &self.metadata_file else {
+            return Ok(None);
+        };
+        let content = std::fs::read_to_string(self.dir.path().join(file))
+            .with_context(|| format!("Failed to read {}", file))?;
+        Ok(Some(CoreMetadata::parse(&content)))
+    }
+
+    /// Report the entries left packed because they are encrypted
+    pub fn encrypted_archive_finding(&self) -> Option<Finding> {
+        archive::encrypted_entries_finding(&self.encrypted_entries, self.total_entries)
+    }
+}
+
+/// Unpack a wheel or source distribution
+///
+/// Fails with [`crate::PackageSecurityError::UnsafePath`] when an entry, or a
+/// link's target, leads out of the archive, with
+/// [`crate::PackageSecurityError::DecompressionBomb`] past the decompression
+/// limits of `options`, and when an entry is larger than [`MAX_ENTRY_SIZE`]
+/// or the archive holds no package.
+pub fn extract_with_options(
+    path: &Path,
+    options: &AnalysisOptions,
+) -> Result<ExtractedDistribution> {
+    let kind = DistributionKind::of(path)
+        .with_context(|| format!("{} is not a wheel or source distribution", path.display()))?;
+    let dir = TempDir::new().context("Failed to create a directory to unpack into")?;
+    let root = dir
+        .path()
+        .canonicalize()
+        .context("Failed to resolve the directory to unpack into")?;
+    let guard = DecompressionGuard::new(path.display().to_string(), options);
+
+    let Unpacked {
+        paths,
+        encrypted_entries,
+        total_entries,
+    } = match kind {
+        DistributionKind::Wheel => unpack_wheel(path, &guard, &root),
+        DistributionKind::Sdist => {
+            let file = std::fs::File::open(path)
+                .with_context(|| format!("Failed to open {}", path.display()))?;
+            let mut archive =
+                tar::Archive::new(guard.decompressed(GzDecoder::new(guard.compressed(file))));
+            unpack_entries(path, &mut archive, &root, MAX_ENTRY_SIZE).map(|paths| Unpacked {
+                total_entries: paths.len(),
+                paths,
+                encrypted_entries: vec![],
+            })
+        }
+    }
+    .map_err(|e| guard.explain(e))?;
+
+    let (package_root, metadata_file) = match kind {
+        // The wheel's own `.dist-info` sits at the top level
+        DistributionKind::Wheel => {
+            let metadata = paths
+                .iter()
+                .filter(|path| {
+                    path.split('/').count() == 2 && path.ends_with(".dist-info/METADATA")
+                })
+                .min()
+                .cloned();
+            if metadata.is_none() {
+                if let Some(encrypted) = encrypted_entries
+                    .iter()
+                    .find(|entry| entry.ends_with(".dist-info/METADATA"))
+                {
+                    bail!("{} has an encrypted {}", path.display(), encrypted);
+                }
+                bail!("{} contains no .dist-info/METADATA", path.display());
+            }
+            (String::new(), metadata)
+        }
+        // The shallowest directory with PKG-INFO, or else with a build script
+        DistributionKind::Sdist => {
+            let shallowest = |file: &str| {
+                paths
+                    .iter()
+                    .filter(|path| path.as_str() == file || path.ends_with(&format!("/{}", file)))
+                    .min_by_key(|path| (path.matches('/').count(), path.as_str()))
+                    .cloned()
+            };
+            let metadata = shallowest("PKG-INFO");
+            let manifest = metadata
+                .clone()
+                .or_else(|| shallowest("setup.py"))
+                .or_else(|| shallowest("pyproject.toml"))
+                .with_context(|| {
+                    format!(
+                        "{} contains no PKG-INFO, setup.py or pyproject.toml",
+                        path.display()
+                    )
+                })?;
+            let root = manifest
+                .rsplit_once('/')
+                .map_or(String::new(), |(dir, _)| dir.to_string());
+            (root, metadata)
+        }
+    };
+
+    Ok(ExtractedDistribution {
+        dir,
+        kind,
+        root: package_root,
+        metadata_file,
+        encrypted_entries,
+        total_entries,
+    })
+}
+
+/// Entries unpacked from a distribution
+struct Unpacked {
+    paths: Vec<String>,
+    encrypted_entries: Vec<String>,
+    total_entries: usize,
+}
+
+/// Unpack the entries of a wheel into `root`
+///
+/// Encrypted entries can't be read without their password; they are skipped
+/// and listed rather than failing the extraction.
+fn unpack_wheel(path: &Path, guard: &DecompressionGuard, root: &Path) -> Result<Unpacked> {
+    let file =
+        std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut archive = ZipArchive::new(file).context("Not a zip archive")?;
+    let mut paths = Vec::new();
+    let mut encrypted_entries = Vec::new();
+    for i in 0..archive.len() {
+        // Read raw so an entry needing a password is listed, not an error
+        let raw = archive.by_index_raw(i).context("Corrupt wheel entry")?;
+        if raw.encrypted() {
+            encrypted_entries.push(raw.name().replace('\\', "/"));
+            continue;
+        }
+        drop(raw);
+        let mut entry = archive.by_index(i).context("Corrupt wheel entry")?;
+        let name = entry.name().replace('\\', "/");
+        let Some(relative) = normalize(Path::new(&name)) else {
+            return Err(PackageSecurityError::UnsafePath { entry: name }.into());
+        };
+        if entry.is_symlink() {
+            // A zip link's target is its content; links are never written
+            let mut target = String::new();
+            (&mut entry)
+                .take(4096)
+                .read_to_string(&mut target)
+                .context("Corrupt wheel link")?;
+            let resolved = relative.parent().unwrap_or(Path::new("")).join(&target);
+            if Path::new(&target).is_absolute() || normalize(&resolved).is_none() {
+                return Err(PackageSecurityError::UnsafePath {
+                    entry: format!("{} -> {}", name, target),
+                }
+                .into());
+            }
+            continue;
+        }
+        if entry.is_dir() || relative.as_os_str().is_empty() {
+            continue;
+        }
+        // Checked before any of the entry is read, so a header claiming a
+        // huge size costs nothing
+        if entry.size() > MAX_ENTRY_SIZE {
+            bail!(
+                "{} holds an entry larger than {} bytes: {}",
+                path.display(),
+                MAX_ENTRY_SIZE,
+                name
+            );
+        }
+
+        guard.add_compressed(entry.compressed_size());
+        let target = root.join(&relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut out = std::fs::File::create(&target)?;
+        std::io::copy(
+            &mut guard.decompressed((&mut entry).take(MAX_ENTRY_SIZE)),
+            &mut out,
+        )?;
+        paths.push(relative.to_string_lossy().replace('\\', "/"));
+    }
+    Ok(Unpacked {
+        paths,
+        encrypted_entries,
+        total_entries: archive.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_core_metadata() {
+        let metadata = CoreMetadata::parse(
+            "Metadata-Version: 2.1\n\
+             Name: fast-json-tools\n\
+             Version: 0.1.0\n\
+             Summary: Faster JSON\n\
+             Author-email: Dev <dev@example.com>\n\
+             License: MIT\n\
+             Keywords: json,speed\n\
+             Classifier: Programming Language :: Python :: 3\n\
+             Classifier: License :: OSI Approved :: MIT License\n\
+             Project-URL: Source, https://github.com/example/fast-json-tools\n\
+             Requires-Python: >=3.8\n\
+             Requires-Dist: requests (>=2.0)\n\
+             Description: first line\n\
+             \x20       continued\n\
+             \n\
+             Name: not-a-header\n",
+        );
+        assert_eq!(metadata.name.as_deref(), Some("fast-json-tools"));
+        assert_eq!(metadata.version.as_deref(), Some("0.1.0"));
+        assert_eq!(metadata.keywords, ["json", "speed"]);
+        assert_eq!(metadata.classifiers.len(), 2);
+        assert_eq!(
+            metadata.project_urls["Source"],
+            "https://github.com/example/fast-json-tools"
+        );
+        assert_eq!(metadata.requires_python.as_deref(), Some(">=3.8"));
+        assert_eq!(metadata.requires_dist, ["requests (>=2.0)"]);
+    }
+
+    #[test]
+    fn test_kind() {
+        assert_eq!(
+            DistributionKind::of(Path::new("requests-2.31.0-py3-none-any.whl")),
+            Some(DistributionKind::Wheel)
+        );
+        assert_eq!(
+            DistributionKind::of(Path::new("requests-2.31.0.tar.gz")),
+            Some(DistributionKind::Sdist)
+        );
+        assert_eq!(DistributionKind::of(Path::new("requests.zip")), None);
+    }
+}
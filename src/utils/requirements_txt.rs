@@ -0,0 +1,365 @@
+//! pip requirements file parsing
+//!
+//! Reads requirements files the way pip does: a trailing `\` continues a
+//! line, `#` at the start of a line or after whitespace starts a comment, and
+//! `-r`/`--requirement` includes another file, relative to the one including
+//! it. Each requirement keeps its extras, environment marker and `--hash`
+//! digests, and whether it is an editable install (`-e`) or comes from a URL
+//! or path instead of an index. Includes leading out of the project, back to
+//! a file already read, or to a file that does not exist are skipped.
+
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use super::npm_tarball::normalize;
+use super::pep508;
+
+/// Options that point pip at a package index
+const INDEX_OPTIONS: &[&str] = &["--index-url", "--extra-index-url", "-i"];
+
+/// One requirement of a requirements file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequirementLine {
+    pub name: String,
+    pub extras: Vec<String>,
+    /// Version specifier, `*` when there is none
+    pub version_spec: String,
+    /// Environment marker, without the leading `;`
+    pub marker: Option<String>,
+    /// `--hash` digests such as `sha256:…`
+    pub hashes: Vec<String>,
+    /// URL or path installed from instead of an index, as written
+    pub location: Option<String>,
+    /// Installed with `-e`/`--editable`
+    pub editable: bool,
+    /// File listing it, relative to the project
+    pub file: String,
+    /// Line it starts on, from 1
+    pub line: usize,
+}
+
+/// A package index pip is pointed at, such as `--index-url URL`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexOption {
+    pub option: String,
+    pub url: String,
+    /// File setting it, relative to the project
+    pub file: String,
+}
+
+/// Requirements of a requirements file and the files it includes
+#[derive(Debug, Clone, Default)]
+pub struct Requirements {
+    pub requirements: Vec<RequirementLine>,
+    pub indexes: Vec<IndexOption>,
+    /// Files read, relative to the project, the top-level file first
+    pub files: Vec<String>,
+    /// Bytes read across all files
+    pub bytes: usize,
+}
+
+/// Read `file`, relative to `root`, and every file it includes
+pub fn read(root: &Path, file: &str) -> Result<Requirements> {
+    let mut requirements = Requirements::default();
+    let canonical_root = root
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", root.display()))?;
+    let mut pending = vec![PathBuf::from(file)];
+    let mut seen = HashSet::new();
+    while let Some(relative) = pending.pop() {
+        let name = relative.to_string_lossy().replace('\\', "/");
+        if !seen.insert(name.clone()) {
+            continue;
+        }
+        let path = root.join(&relative);
+        // A link may still lead out of the project
+        let inside = path
+            .canonicalize()
+            .is_ok_and(|path| path.starts_with(&canonical_root));
+        if !inside {
+            continue;
+        }
+        let content =
+            std::fs::read_to_string(&path).with_context(|| format!("Failed to read {}", name))?;
+        requirements.bytes += content.len();
+        requirements.files.push(name.clone());
+
+        let parent = relative.parent().unwrap_or(Path::new(""));
+        let includes = parse_into(&content, &name, &mut requirements);
+        // Depth first, in the order the includes are listed
+        for include in includes.into_iter().rev() {
+            if Path::new(&include).is_absolute() {
+                continue;
+            }
+            if let Some(included) = normalize(&parent.join(&include)) {
+                pending.push(included);
+            }
+        }
+    }
+    Ok(requirements)
+}
+
+/// Add the requirements and indexes of one file, returning the files it
+/// includes
+fn parse_into(content: &str, file: &str, requirements: &mut Requirements) -> Vec<String> {
+    let mut includes = Vec::new();
+    for (line_number, line) in logical_lines(content) {
+        let mut tokens = line.split_whitespace();
+        let Some(first) = tokens.next() else {
+            continue;
+        };
+
+        if let Some((option, value)) = option_value(first, &mut tokens) {
+            match option.as_str() {
+                "-r" | "--requirement" => includes.push(value),
+                "-e" | "--editable" => {
+                    let mut requirement = location_requirement(&value);
+                    requirement.editable = true;
+                    requirement.file = file.to_string();
+                    requirement.line = line_number;
+                    requirements.requirements.push(requirement);
+                }
+                option if INDEX_OPTIONS.contains(&option) && !value.is_empty() => {
+                    requirements.indexes.push(IndexOption {
+                        option: option.to_string(),
+                        url: value,
+                        file: file.to_string(),
+                    });
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        // Options such as `--hash` follow the requirement
+        let (spec, options) = match line.find(" --").or_else(|| line.find("\t--")) {
+            Some(pos) => (line[..pos].trim(), &line[pos..]),
+            None => (line.trim(), ""),
+        };
+        let mut hashes = Vec::new();
+        let mut options = options.split_whitespace();
+        while let Some(token) = options.next() {
+            if let Some((option, value)) = option_value(token, &mut options) {
+                if option == "--hash" {
+                    hashes.push(value);
+                }
+            }
+        }
+
+        let requirement = if is_location(spec) {
+            Some(location_requirement(spec))
+        } else {
+            pep508::parse(spec).map(|parsed| RequirementLine {
+                name: parsed.name,
+                extras: parsed.extras,
+                version_spec: parsed.version_spec,
+                marker: parsed.marker,
+                location: parsed.url,
+                ..RequirementLine::default()
+            })
+        };
+        if let Some(mut requirement) = requirement {
+            requirement.hashes = hashes;
+            requirement.file = file.to_string();
+            requirement.line = line_number;
+            requirements.requirements.push(requirement);
+        }
+    }
+    includes
+}
+
+/// Lines with continuations joined and comments removed, with the number
+/// of the line each starts on
+fn logical_lines(content: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    for (index, line) in content.lines().enumerate() {
+        let (start, mut joined) = current.take().unwrap_or((index + 1, String::new()));
+        match line.strip_suffix('\\') {
+            Some(continued) => {
+                joined.push_str(continued);
+                joined.push(' ');
+                current = Some((start, joined));
+            }
+            None => {
+                joined.push_str(line);
+                lines.push((start, joined));
+            }
+        }
+    }
+    lines.extend(current);
+
+    lines
+        .into_iter()
+        .filter_map(|(number, line)| {
+            let line = strip_comment(&line).trim();
+            (!line.is_empty()).then(|| (number, line.to_string()))
+        })
+        .collect()
+}
+
+fn strip_comment(line: &str) -> &str {
+    if line.trim_start().starts_with('#') {
+        return "";
+    }
+    line.find(" #")
+        .or_else(|| line.find("\t#"))
+        .map_or(line, |pos| &line[..pos])
+}
+
+/// `(option, value)` of an option token, taking the value from the next
+/// token when it is not attached with `=` or, for short options, directly
+fn option_value<'a>(
+    token: &str,
+    rest: &mut impl Iterator<Item = &'a str>,
+) -> Option<(String, String)> {
+    if !token.starts_with('-') {
+        return None;
+    }
+    if let Some((option, value)) = token.split_once('=').filter(|_| token.starts_with("--")) {
+        return Some((option.to_string(), value.to_string()));
+    }
+    if !token.starts_with("--") && token.len() > 2 {
+        // A short option is ASCII; anything else is an option pip doesn't know
+        return Some(match (token.get(..2), token.get(2..)) {
+            (Some(option), Some(value)) => (option.to_string(), value.to_string()),
+            _ => (token.to_string(), String::new()),
+        });
+    }
+    let value = rest.next().unwrap_or_default();
+    Some((token.to_string(), value.to_string()))
+}
+
+/// Whether a requirement is a bare URL or path rather than a name
+fn is_location(spec: &str) -> bool {
+    let first = spec.split_whitespace().next().unwrap_or_default();
+    first.contains("://")
+        || first.starts_with('.')
+        || first.starts_with('/')
+        || first.starts_with("file:")
+}
+
+/// Requirement for a bare URL or path, named by its `#egg=` fragment
+///
+/// pip finds the name of other locations only by building them, so those
+/// are named by the location itself.
+fn location_requirement(spec: &str) -> RequirementLine {
+    let (location, marker) = match spec.split_once(" ;").or_else(|| spec.split_once("; ")) {
+        Some((location, marker)) => (location.trim(), Some(marker.trim().to_string())),
+        None => (spec.trim(), None),
+    };
+    let name = location
+        .split_once("#egg=")
+        .map(|(_, egg)| egg.split('&').next().unwrap_or(egg))
+        .unwrap_or(location);
+    let (name, extras) = match pep508::parse(name) {
+        Some(parsed) if location.contains("#egg=") => (parsed.name, parsed.extras),
+        _ => (name.to_string(), Vec::new()),
+    };
+    RequirementLine {
+        name,
+        extras,
+        version_spec: "*".to_string(),
+        marker: marker.filter(|marker| !marker.is_empty()),
+        location: Some(location.to_string()),
+        ..RequirementLine::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_requirements() {
+        let mut requirements = Requirements::default();
+        let includes = parse_into(
+            "# pinned\n\
+             -r base.txt\n\
+             --index-url=https://pypi.org/simple\n\
+             requests[security]==2.31.0 ; python_version < \"3.8\" \\\n    \
+             --hash=sha256:aaa \\\n    \
+             --hash sha256:bbb\n\
+             -e git+https://github.com/o/tool.git@v1.0#egg=tool\n\
+             helper @ https://files.example.com/helper-1.0.tar.gz  # vendored\n\
+             ./local-plugin\n\
+             -édition\n",
+            "requirements.txt",
+            &mut requirements,
+        );
+        assert_eq!(includes, ["base.txt"]);
+        assert_eq!(
+            requirements.indexes,
+            [IndexOption {
+                option: "--index-url".to_string(),
+                url: "https://pypi.org/simple".to_string(),
+                file: "requirements.txt".to_string(),
+            }]
+        );
+
+        let found = &requirements.requirements;
+        assert_eq!(found.len(), 4);
+        assert_eq!(found[0].name, "requests");
+        assert_eq!(found[0].extras, ["security"]);
+        assert_eq!(found[0].version_spec, "==2.31.0");
+        assert_eq!(found[0].marker.as_deref(), Some("python_version < \"3.8\""));
+        assert_eq!(found[0].hashes, ["sha256:aaa", "sha256:bbb"]);
+        assert_eq!(found[0].line, 4);
+
+        assert_eq!(found[1].name, "tool");
+        assert!(found[1].editable);
+        assert_eq!(
+            found[1].location.as_deref(),
+            Some("git+https://github.com/o/tool.git@v1.0#egg=tool")
+        );
+        assert_eq!(found[2].name, "helper");
+        assert_eq!(
+            found[2].location.as_deref(),
+            Some("https://files.example.com/helper-1.0.tar.gz")
+        );
+        assert_eq!(found[3].location.as_deref(), Some("./local-plugin"));
+        assert!(!found[3].editable);
+    }
+
+    #[test]
+    fn test_read_includes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("reqs")).unwrap();
+        std::fs::write(
+            dir.path().join("requirements.txt"),
+            "-r reqs/prod.txt\nflask==2.0.0\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("reqs/prod.txt"),
+            "--requirement=common.txt\n-r ../../outside.txt\ngunicorn==21.2.0\n",
+        )
+        .unwrap();
+        // Includes its includer; read once
+        std::fs::write(
+            dir.path().join("reqs/common.txt"),
+            "-r ../requirements.txt\nrequests==2.31.0\n",
+        )
+        .unwrap();
+
+        let requirements = read(dir.path(), "requirements.txt").unwrap();
+        assert_eq!(
+            requirements.files,
+            ["requirements.txt", "reqs/prod.txt", "reqs/common.txt"]
+        );
+        let names: Vec<(&str, &str)> = requirements
+            .requirements
+            .iter()
+            .map(|r| (r.name.as_str(), r.file.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("flask", "requirements.txt"),
+                ("gunicorn", "reqs/prod.txt"),
+                ("requests", "reqs/common.txt"),
+            ]
+        );
+    }
+}
@@ -1,64 +1,368 @@
 //! Typosquatting detection utilities
 
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
 use strsim::levenshtein;
 
+use super::confusables;
+use super::names::NameNormalization;
+use crate::core::AnalysisOptions;
+pub use crate::ecosystem::Ecosystem;
+
+/// Why a name was taken for typosquatting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TyposquattingReason {
+    /// Within a few edits of a popular name, or a popular name with a decoy affix
+    #[default]
+    SimilarName,
+    /// Unscoped name built from a popular scoped package's member (`material-core`
+    /// for `@angular/material`)
+    ScopedMemberUnscoped,
+    /// Near-miss spelling of a popular scope (`@angu1ar/core`)
+    LookalikeScope,
+    /// Popular unscoped package republished under a scope named after it
+    /// (`@lodash/lodash`)
+    UnscopedUnderScope,
+}
+
+/// Source of extra names to compare packages against
+///
+/// Providers let an organization protect its own package names, which the
+/// bundled popularity lists cannot know about. Candidates are merged with
+/// the bundled names before distance scoring; a provider that has nothing
+/// to offer, or fails, returns an empty list. Closures taking the package
+/// name and ecosystem implement this trait.
+pub trait TyposquattingTargetProvider: Send + Sync {
+    /// Names `name` should be compared against in `ecosystem`
+    fn candidates(&self, name: &str, ecosystem: Ecosystem) -> Vec<String>;
+}
+
+impl<F> TyposquattingTargetProvider for F
+where
+    F: Fn(&str, Ecosystem) -> Vec<String> + Send + Sync,
+{
+    fn candidates(&self, name: &str, ecosystem: Ecosystem) -> Vec<String> {
+        self(name, ecosystem)
+    }
+}
+
+/// Legitimate names of an ecosystem, used in place of the bundled popularity lists
+///
+/// Lets an organization whose internal packages are named like public ones
+/// compare against its own corpus, so its packages are no longer flagged and
+/// lookalikes of them are.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PopularPackages {
+    /// Names given inline
+    Names(Vec<String>),
+    /// File with one name per line; blank lines and `#` comments are skipped
+    File(PathBuf),
+}
+
+impl PopularPackages {
+    /// The names, reading the file if there is one
+    pub fn load(&self) -> Result<Vec<String>> {
+        match self {
+            Self::Names(names) => Ok(names.clone()),
+            Self::File(path) => {
+                let content = std::fs::read_to_string(path).with_context(|| {
+                    format!(
+                        "Failed to read popular package names from {}",
+                        path.display()
+                    )
+                })?;
+                Ok(content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string)
+                    .collect())
+            }
+        }
+    }
+}
+
+/// How a name imitates a popular one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TyposquattingTechnique {
+    /// Characters inserted, removed or replaced
+    CharacterEdit,
+    /// Characters replaced by neighbouring keys on a QWERTY keyboard (`reqiest`)
+    KeyboardAdjacency,
+    /// Lookalike Unicode characters in place of ASCII ones (Cyrillic `о` in `lоdash`)
+    Homoglyph,
+    /// A popular name with a decoy prefix or suffix (`lodash-js`)
+    DecoyAffix,
+    /// Confusion between scoped and unscoped npm names
+    ScopeConfusion,
+}
+
+/// Popular name a package may impersonate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimilarPackage {
+    pub name: String,
+    /// Edits between the package's normalized name and this one
+    pub distance: u32,
+    /// How likely the package impersonates this one, from 0 to 1
+    pub confidence: f32,
+    /// How the package's name imitates this one
+    #[serde(default)]
+    pub techniques: Vec<TyposquattingTechnique>,
+}
+
+impl SimilarPackage {
+    /// Candidate `name` for a package named `package_name`
+    pub fn new(
+        package_name: &str,
+        name: &str,
+        confidence: f32,
+        techniques: Vec<TyposquattingTechnique>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            distance: levenshtein(package_name, name) as u32,
+            confidence,
+            techniques,
+        }
+    }
+}
+
 /// Typosquatting detector
+#[derive(Clone)]
 pub struct TyposquattingDetector {
     popular_packages: HashSet<String>,
+    /// Popular npm scopes with their popular members, in list order
+    popular_scopes: Vec<(String, Vec<String>)>,
+    normalization: NameNormalization,
+    ecosystem: Ecosystem,
+    providers: Vec<Arc<dyn TyposquattingTargetProvider>>,
 }
 
 impl TyposquattingDetector {
-    /// Create a new typosquatting detector
+    /// Edit distance to a popular name treated as typosquatting by default
+    pub const DEFAULT_MAX_DISTANCE: usize = 2;
+
+    /// Confidence that a popular name with a decoy affix impersonates it
+    pub const AFFIX_CONFIDENCE: f32 = 0.8;
+
+    /// Confidence that a name reading the same as a popular one once
+    /// lookalikes are mapped to ASCII impersonates it
+    pub const HOMOGLYPH_CONFIDENCE: f32 = 0.95;
+
+    /// Cost of replacing a character by a neighbouring key, against 1 for any
+    /// other edit, when scoring similarity
+    pub const ADJACENT_KEY_COST: f32 = 0.75;
+
+    /// Create a new typosquatting detector comparing names exactly
     pub fn new() -> Self {
+        Self::with_normalization(NameNormalization::Exact)
+    }
+
+    /// Create a detector that compares names under an ecosystem's normalization rules
+    pub fn for_ecosystem(ecosystem: &str) -> Self {
+        Self {
+            ecosystem: Ecosystem::from_name(ecosystem),
+            ..Self::with_normalization(NameNormalization::for_ecosystem(ecosystem))
+        }
+    }
+
+    /// Create a detector using the given name normalization
+    pub fn with_normalization(normalization: NameNormalization) -> Self {
         let mut popular_packages = HashSet::new();
 
         // Add popular npm packages
         for pkg in NPM_POPULAR_PACKAGES {
-            popular_packages.insert(pkg.to_string());
+            popular_packages.insert(normalization.normalize(pkg));
         }
 
         // Add popular Python packages
         for pkg in PYTHON_POPULAR_PACKAGES {
-            popular_packages.insert(pkg.to_string());
+            popular_packages.insert(normalization.normalize(pkg));
         }
 
         // Add popular Java packages
         for pkg in JAVA_POPULAR_PACKAGES {
-            popular_packages.insert(pkg.to_string());
+            popular_packages.insert(normalization.normalize(pkg));
+        }
+
+        let mut popular_scopes: Vec<(String, Vec<String>)> = Vec::new();
+        for pkg in NPM_POPULAR_SCOPED_PACKAGES {
+            let Some((scope, member)) = split_scoped(pkg) else {
+                continue;
+            };
+            match popular_scopes.iter_mut().find(|(known, _)| known == scope) {
+                Some((_, members)) => members.push(member.to_string()),
+                None => popular_scopes.push((scope.to_string(), vec![member.to_string()])),
+            }
         }
 
-        Self { popular_packages }
+        Self {
+            popular_packages,
+            popular_scopes,
+            normalization,
+            ecosystem: Ecosystem::Unknown,
+            providers: Vec::new(),
+        }
+    }
+
+    /// Compare names against `names` instead of the bundled popularity lists
+    ///
+    /// The names replace the bundled ones for edit distance and decoy affix
+    /// checks; scope confusion still uses the bundled npm scopes.
+    pub fn with_popular_packages<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.popular_packages = names
+            .into_iter()
+            .map(|name| self.normalization.normalize(name.as_ref()))
+            .filter(|name| !name.is_empty())
+            .collect();
+        self
+    }
+
+    /// This detector, or one comparing against the names `options` gives for
+    /// its ecosystem in `popular_packages`
+    pub fn for_options(&self, options: &AnalysisOptions) -> Result<Cow<'_, Self>> {
+        match options.popular_packages.get(&self.ecosystem) {
+            Some(popular) => Ok(Cow::Owned(
+                self.clone().with_popular_packages(popular.load()?),
+            )),
+            None => Ok(Cow::Borrowed(self)),
+        }
+    }
+
+    /// Also compare names against candidates from `provider`
+    ///
+    /// Providers are not consulted in `offline` builds, which compare against
+    /// the bundled names only.
+    pub fn with_target_provider(mut self, provider: Arc<dyn TyposquattingTargetProvider>) -> Self {
+        self.providers.push(provider);
+        self
     }
 
     /// Check if a package name is likely typosquatting
     pub fn is_typosquatting(&self, package_name: &str) -> bool {
-        // Check for common typosquatting patterns
-        if self.has_suspicious_suffix(package_name) || self.has_suspicious_prefix(package_name) {
-            return true;
-        }
+        self.is_typosquatting_within(package_name, Self::DEFAULT_MAX_DISTANCE)
+    }
 
-        // Check similarity to popular packages
-        for popular in &self.popular_packages {
-            let distance = levenshtein(package_name, popular);
-            if distance > 0 && distance <= 2 {
-                return true;
+    /// Check for typosquatting, allowing up to `max_distance` edits from a popular name
+    pub fn is_typosquatting_within(&self, package_name: &str, max_distance: usize) -> bool {
+        !self
+            .similar_within(package_name, max_distance, 0.0)
+            .is_empty()
+    }
+
+    /// Popular names `package_name` may impersonate, most likely first
+    ///
+    /// Names are compared by their skeletons, with lookalike Unicode
+    /// characters mapped to the ASCII they imitate (see
+    /// [`super::confusables`]). A popular name is a candidate when:
+    /// - the skeleton reads the same as it (`lоdash` with a Cyrillic `о`), at
+    ///   [`Self::HOMOGLYPH_CONFIDENCE`];
+    /// - the skeleton is it with a decoy affix (`lodash-js`, `fake-lodash`),
+    ///   at [`Self::AFFIX_CONFIDENCE`];
+    /// - the skeleton is within `max_distance` edits of it, at a confidence
+    ///   falling with the share of characters edited, where replacing a
+    ///   character by a neighbouring key counts [`Self::ADJACENT_KEY_COST`]:
+    ///   one edit to `ms` is far weaker evidence than one edit to `express`.
+    ///
+    /// Only candidates at `min_confidence` or above are kept, and a name that
+    /// is itself popular, such as `@babel/core`, has none.
+    pub fn similar_within(
+        &self,
+        package_name: &str,
+        max_distance: usize,
+        min_confidence: f32,
+    ) -> Vec<SimilarPackage> {
+        let package_name = self.normalization.normalize(package_name);
+        let package_name = package_name.as_str();
+        if self.is_popular(package_name) {
+            return Vec::new();
+        }
+        let skeleton = confusables::skeleton(package_name);
+        let with_homoglyphs = |mut techniques: Vec<TyposquattingTechnique>| {
+            if skeleton != package_name {
+                techniques.push(TyposquattingTechnique::Homoglyph);
             }
+            techniques
+        };
+        let mut similar: Vec<SimilarPackage> = Vec::new();
+
+        for popular in [
+            self.decoy_suffix_target(&skeleton),
+            self.decoy_prefix_target(&skeleton),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            similar.push(SimilarPackage::new(
+                package_name,
+                popular,
+                Self::AFFIX_CONFIDENCE,
+                with_homoglyphs(vec![TyposquattingTechnique::DecoyAffix]),
+            ));
+        }
 
-            // Check for character substitution
-            if self.is_character_substitution(package_name, popular) {
-                return true;
+        let provided = self.provided_targets(package_name);
+        for popular in self.popular_packages.iter().chain(&provided) {
+            if popular == package_name || similar.iter().any(|known| known.name == *popular) {
+                continue;
+            }
+            let distance = levenshtein(&skeleton, popular);
+            if distance == 0 {
+                similar.push(SimilarPackage::new(
+                    package_name,
+                    popular,
+                    Self::HOMOGLYPH_CONFIDENCE,
+                    vec![TyposquattingTechnique::Homoglyph],
+                ));
+                continue;
             }
+            if distance > max_distance {
+                continue;
+            }
+            let length = skeleton.chars().count().max(popular.chars().count());
+            let confidence = 1.0 - keyboard_distance(&skeleton, popular) / length as f32;
+            let technique = if is_adjacent_substitution(&skeleton, popular) {
+                TyposquattingTechnique::KeyboardAdjacency
+            } else {
+                TyposquattingTechnique::CharacterEdit
+            };
+            similar.push(SimilarPackage::new(
+                package_name,
+                popular,
+                confidence,
+                with_homoglyphs(vec![technique]),
+            ));
         }
 
-        false
+        similar.retain(|candidate| candidate.confidence >= min_confidence);
+        similar.sort_by(|a, b| {
+            b.confidence
+                .total_cmp(&a.confidence)
+                .then(a.distance.cmp(&b.distance))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        similar
     }
 
     /// Find similar popular packages
     pub fn find_similar(&self, package_name: &str) -> Vec<String> {
+        let package_name = self.normalization.normalize(package_name);
+        let package_name = package_name.as_str();
         let mut similar = Vec::new();
 
-        for popular in &self.popular_packages {
+        let provided = self.provided_targets(package_name);
+        for popular in self.popular_packages.iter().chain(&provided) {
             let distance = levenshtein(package_name, popular);
             if distance > 0 && distance <= 3 {
                 similar.push(popular.clone());
@@ -68,8 +372,124 @@ impl TyposquattingDetector {
         similar
     }
 
-    /// Check for suspicious suffixes
-    fn has_suspicious_suffix(&self, name: &str) -> bool {
+    /// Check for confusion between scoped and unscoped npm names
+    ///
+    /// Returns the canonical name being impersonated and how:
+    /// - an unscoped name made of a popular scope's member and the scope or
+    ///   another of its members (`material-core`, `angular-material`) points
+    ///   at the scoped package;
+    /// - a scope within two edits of a popular one, five or more characters
+    ///   long, points at the same member under the real scope;
+    /// - a popular unscoped package republished under a scope named after it
+    ///   (`@lodash/lodash`, `@reactjs/react`) points at the unscoped package.
+    pub fn scope_confusion(&self, package_name: &str) -> Option<(String, TyposquattingReason)> {
+        let name = package_name.trim().to_lowercase();
+        match split_scoped(&name) {
+            Some((scope, member)) => self.scoped_confusion(scope, member),
+            None => self
+                .scoped_member_unscoped(&name)
+                .map(|canonical| (canonical, TyposquattingReason::ScopedMemberUnscoped)),
+        }
+    }
+
+    /// Provider candidates for `package_name` not among the bundled names
+    fn provided_targets(&self, package_name: &str) -> HashSet<String> {
+        if cfg!(feature = "offline") {
+            return HashSet::new();
+        }
+        self.providers
+            .iter()
+            .flat_map(|provider| provider.candidates(package_name, self.ecosystem))
+            .map(|candidate| self.normalization.normalize(&candidate))
+            .filter(|candidate| !candidate.is_empty() && !self.popular_packages.contains(candidate))
+            .collect()
+    }
+
+    /// Whether a normalized name is one of the popular names, scoped or not
+    fn is_popular(&self, name: &str) -> bool {
+        self.popular_packages.contains(name)
+            || split_scoped(name).is_some_and(|(scope, member)| {
+                self.popular_scopes
+                    .iter()
+                    .any(|(known, members)| known == scope && members.iter().any(|m| m == member))
+            })
+    }
+
+    fn scoped_member_unscoped(&self, name: &str) -> Option<String> {
+        if NPM_POPULAR_PACKAGES.contains(&name) || name.starts_with('@') {
+            return None;
+        }
+        let distinctive = |member: &str| !GENERIC_SCOPE_MEMBERS.contains(&member);
+
+        for (scope, members) in &self.popular_scopes {
+            let is_member = |part: &str| members.iter().any(|m| m == part);
+            let canonical = |member: &str| format!("@{}/{}", scope, member);
+
+            if is_member(name) && distinctive(name) {
+                return Some(canonical(name));
+            }
+            if let Some(member) = name.strip_prefix(scope.as_str()) {
+                if is_member(member) {
+                    return Some(canonical(member));
+                }
+            }
+            for (split, _) in name.match_indices('-') {
+                let (left, right) = (&name[..split], &name[split + 1..]);
+                if left == scope && is_member(right) {
+                    return Some(canonical(right));
+                }
+                for (member, other) in [(left, right), (right, left)] {
+                    if is_member(member)
+                        && distinctive(member)
+                        && (other == scope || is_member(other))
+                    {
+                        return Some(canonical(member));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    fn scoped_confusion(&self, scope: &str, member: &str) -> Option<(String, TyposquattingReason)> {
+        if self.popular_scopes.iter().any(|(known, _)| known == scope) {
+            return None;
+        }
+
+        // Scopes as short as `@vue` or `@mui` are an edit away from too many
+        // unrelated ones
+        for (known, _) in self
+            .popular_scopes
+            .iter()
+            .filter(|(known, _)| known.len() >= 5)
+        {
+            let distance = levenshtein(scope, known);
+            if distance > 0 && distance <= 2 {
+                return Some((
+                    format!("@{}/{}", known, member),
+                    TyposquattingReason::LookalikeScope,
+                ));
+            }
+        }
+
+        let bare_scope: String = scope
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect();
+        let bare_member: String = member
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect();
+        if NPM_POPULAR_PACKAGES.contains(&member) && bare_scope.starts_with(&bare_member) {
+            return Some((member.to_string(), TyposquattingReason::UnscopedUnderScope));
+        }
+
+        None
+    }
+
+    /// Popular name `name` is with a decoy suffix added
+    fn decoy_suffix_target(&self, name: &str) -> Option<&str> {
         const SUSPICIOUS_SUFFIXES: &[&str] = &[
             "-dev",
             "-test",
@@ -85,79 +505,96 @@ impl TyposquattingDetector {
             "-new",
         ];
 
-        for suffix in SUSPICIOUS_SUFFIXES {
-            if let Some(base) = name.strip_suffix(suffix) {
-                // Check if removing suffix matches a popular package
-                if self.popular_packages.contains(base) {
-                    return true;
-                }
-            }
-        }
-
-        false
+        SUSPICIOUS_SUFFIXES
+            .iter()
+            .filter_map(|suffix| name.strip_suffix(suffix))
+            .find_map(|base| self.popular_packages.get(base))
+            .map(String::as_str)
     }
 
-    /// Check for suspicious prefixes
-    fn has_suspicious_prefix(&self, name: &str) -> bool {
+    /// Popular name `name` is with a decoy prefix added
+    fn decoy_prefix_target(&self, name: &str) -> Option<&str> {
         const SUSPICIOUS_PREFIXES: &[&str] =
             &["fake-", "test-", "my-", "new-", "real-", "official-"];
 
-        for prefix in SUSPICIOUS_PREFIXES {
-            if let Some(base) = name.strip_prefix(prefix) {
-                if self.popular_packages.contains(base) {
-                    return true;
-                }
-            }
-        }
-
-        false
+        SUSPICIOUS_PREFIXES
+            .iter()
+            .filter_map(|prefix| name.strip_prefix(prefix))
+            .find_map(|base| self.popular_packages.get(base))
+            .map(String::as_str)
     }
+}
 
-    /// Check for single character substitution
-    fn is_character_substitution(&self, name1: &str, name2: &str) -> bool {
-        if name1.len() != name2.len() {
-            return false;
-        }
-
-        let chars1: Vec<char> = name1.chars().collect();
-        let chars2: Vec<char> = name2.chars().collect();
-        let mut differences = 0;
+/// QWERTY rows, each shifted half a key right of the one above
+const KEYBOARD_ROWS: [&str; 4] = ["1234567890-", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
 
-        for (c1, c2) in chars1.iter().zip(chars2.iter()) {
-            if c1 != c2 {
-                differences += 1;
-                if differences > 1 {
-                    return false;
-                }
+/// Row and column of a key
+fn key_position(c: char) -> Option<(usize, usize)> {
+    let c = c.to_ascii_lowercase();
+    KEYBOARD_ROWS
+        .iter()
+        .enumerate()
+        .find_map(|(row, keys)| keys.find(c).map(|column| (row, column)))
+}
 
-                // Check for common visual confusions
-                if !self.is_visual_confusion(*c1, *c2) {
-                    return true;
-                }
-            }
+/// Whether two keys touch on a QWERTY keyboard
+fn keys_adjacent(a: char, b: char) -> bool {
+    let (Some((row_a, column_a)), Some((row_b, column_b))) = (key_position(a), key_position(b))
+    else {
+        return false;
+    };
+    match row_a.abs_diff(row_b) {
+        0 => column_a.abs_diff(column_b) == 1,
+        // A key touches the two keys above it at its own column and the next
+        1 => {
+            let (lower, upper) = if row_a > row_b {
+                (column_a, column_b)
+            } else {
+                (column_b, column_a)
+            };
+            upper == lower || upper == lower + 1
         }
-
-        differences == 1
+        _ => false,
     }
+}
+
+/// Whether `a` is `b` with only characters replaced, each by a neighbouring key
+fn is_adjacent_substitution(a: &str, b: &str) -> bool {
+    a.chars().count() == b.chars().count()
+        && a.chars()
+            .zip(b.chars())
+            .all(|(x, y)| x == y || keys_adjacent(x, y))
+}
 
-    /// Check for visually similar characters
-    fn is_visual_confusion(&self, c1: char, c2: char) -> bool {
-        matches!(
-            (c1, c2),
-            ('0', 'o')
-                | ('o', '0')
-                | ('0', 'O')
-                | ('O', '0')
-                | ('1', 'l')
-                | ('l', '1')
-                | ('1', 'I')
-                | ('I', '1')
-                | ('5', 's')
-                | ('s', '5')
-                | ('5', 'S')
-                | ('S', '5')
-        )
+/// Levenshtein distance where replacing a character by a neighbouring key
+/// costs [`TyposquattingDetector::ADJACENT_KEY_COST`]
+fn keyboard_distance(a: &str, b: &str) -> f32 {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<f32> = (0..=b.len()).map(|j| j as f32).collect();
+    for (i, x) in a.chars().enumerate() {
+        let mut current = vec![(i + 1) as f32; b.len() + 1];
+        for (j, y) in b.iter().enumerate() {
+            let substitution = if x == *y {
+                0.0
+            } else if keys_adjacent(x, *y) {
+                TyposquattingDetector::ADJACENT_KEY_COST
+            } else {
+                1.0
+            };
+            current[j + 1] = (previous[j] + substitution)
+                .min(previous[j + 1] + 1.0)
+                .min(current[j] + 1.0);
+        }
+        previous = current;
     }
+    previous[b.len()]
+}
+
+/// Split `@scope/member` into its scope (without `@`) and member
+fn split_scoped(name: &str) -> Option<(&str, &str)> {
+    name.strip_prefix('@')?
+        .split_once('/')
+        .filter(|(scope, member)| !scope.is_empty() && !member.is_empty())
 }
 
 impl Default for TyposquattingDetector {
@@ -191,6 +628,42 @@ const NPM_POPULAR_PACKAGES: &[&str] = &[
     "commander",
 ];
 
+// Popular scoped NPM packages
+const NPM_POPULAR_SCOPED_PACKAGES: &[&str] = &[
+    "@angular/core",
+    "@angular/common",
+    "@angular/material",
+    "@angular/cdk",
+    "@angular/router",
+    "@angular/forms",
+    "@angular/cli",
+    "@babel/core",
+    "@babel/preset-env",
+    "@babel/runtime",
+    "@vue/cli",
+    "@vue/compiler-sfc",
+    "@nestjs/core",
+    "@nestjs/common",
+    "@aws-sdk/client-s3",
+    "@testing-library/react",
+    "@testing-library/jest-dom",
+    "@mui/material",
+    "@mui/icons-material",
+    "@emotion/react",
+    "@emotion/styled",
+    "@reduxjs/toolkit",
+    "@typescript-eslint/parser",
+    "@typescript-eslint/eslint-plugin",
+    "@sentry/node",
+    "@sentry/browser",
+];
+
+// Scoped package members too generic to impersonate a scope on their own
+const GENERIC_SCOPE_MEMBERS: &[&str] = &[
+    "core", "common", "cdk", "router", "forms", "cli", "runtime", "react", "styled", "toolkit",
+    "parser", "node", "browser",
+];
+
 // Popular Python packages
 const PYTHON_POPULAR_PACKAGES: &[&str] = &[
     "numpy",
@@ -234,3 +707,181 @@ const JAVA_POPULAR_PACKAGES: &[&str] = &[
     "jetty",
     "tomcat",
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(feature = "offline"))]
+    #[test]
+    fn test_target_provider_candidates() {
+        let provider = |_: &str, ecosystem: Ecosystem| match ecosystem {
+            Ecosystem::Python => vec!["Acme.Billing".to_string()],
+            _ => Vec::new(),
+        };
+        let detector = TyposquattingDetector::for_ecosystem("python");
+        assert!(!detector.is_typosquatting("acme_billing"));
+
+        let detector = detector.with_target_provider(Arc::new(provider));
+        // Candidates are normalized like the names checked against them
+        assert!(detector.find_similar("acme_billing").is_empty());
+        assert!(!detector.is_typosquatting("acme_billing"));
+        assert!(detector.is_typosquatting("acme_biling"));
+        assert_eq!(detector.find_similar("acme_biling"), ["acme-billing"]);
+    }
+
+    #[test]
+    fn test_popular_packages() {
+        let detector = TyposquattingDetector::for_ecosystem("npm")
+            .with_popular_packages(["acme-utils", "acme-ui"]);
+        // Internal names shadowing public ones are no longer compared against them
+        assert!(!detector.is_typosquatting("reacts"));
+        assert!(detector.is_typosquatting("acme-utlis"));
+        assert!(detector.is_typosquatting("acme-utils-js"));
+        assert!(!detector.is_typosquatting("acme-utils"));
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let file = dir.path().join("popular.txt");
+        std::fs::write(&file, "# internal\nacme-utils\n\n  acme-ui  \n").unwrap();
+        assert_eq!(
+            PopularPackages::File(file).load().unwrap(),
+            ["acme-utils", "acme-ui"]
+        );
+    }
+
+    #[test]
+    fn test_popular_names_are_never_flagged() {
+        for (ecosystem, names) in [
+            ("npm", NPM_POPULAR_PACKAGES),
+            ("npm", NPM_POPULAR_SCOPED_PACKAGES),
+            ("python", PYTHON_POPULAR_PACKAGES),
+            ("java", JAVA_POPULAR_PACKAGES),
+        ] {
+            let detector = TyposquattingDetector::for_ecosystem(ecosystem);
+            for name in names {
+                assert!(
+                    detector.similar_within(name, 3, 0.0).is_empty(),
+                    "{} flagged",
+                    name
+                );
+            }
+        }
+        let detector = TyposquattingDetector::for_ecosystem("npm");
+        assert!(!detector.is_typosquatting("@babel/core"));
+        assert!(detector.is_typosquatting("babel-core-js"));
+    }
+
+    #[test]
+    fn test_similar_confidence() {
+        let detector =
+            TyposquattingDetector::for_ecosystem("npm").with_popular_packages(["ms", "express"]);
+
+        let short = detector.similar_within("mz", 2, 0.0);
+        assert_eq!(short.len(), 1);
+        assert_eq!((short[0].name.as_str(), short[0].distance), ("ms", 1));
+        assert_eq!(short[0].confidence, 0.625);
+        assert!(detector.similar_within("mz", 2, 0.7).is_empty());
+        assert!(detector.similar_within("mz", 0, 0.0).is_empty());
+
+        let long = detector.similar_within("expres", 2, 0.7);
+        assert_eq!(long[0].name, "express");
+        assert!(long[0].confidence > 0.8);
+
+        // Decoy affixes are not bound by the distance
+        let affixed = detector.similar_within("express-official", 2, 0.7);
+        assert_eq!(affixed[0].name, "express");
+        assert_eq!(affixed[0].distance, 9);
+        assert_eq!(
+            affixed[0].confidence,
+            TyposquattingDetector::AFFIX_CONFIDENCE
+        );
+    }
+
+    #[test]
+    fn test_keyboard_and_homoglyph_techniques() {
+        let detector = TyposquattingDetector::for_ecosystem("npm");
+
+        // `w` sits next to `e`, `p` does not
+        let adjacent = detector.similar_within("exprwss", 2, 0.0);
+        let express = adjacent.iter().find(|s| s.name == "express").unwrap();
+        assert_eq!(
+            express.techniques,
+            [TyposquattingTechnique::KeyboardAdjacency]
+        );
+        let distant = detector.similar_within("exprpss", 2, 0.0);
+        let distant = distant.iter().find(|s| s.name == "express").unwrap();
+        assert_eq!(distant.techniques, [TyposquattingTechnique::CharacterEdit]);
+        assert!(express.confidence > distant.confidence);
+
+        for name in ["lоdash", "reqｕests"] {
+            let similar = detector.similar_within(name, 2, 0.9);
+            assert_eq!(similar[0].techniques, [TyposquattingTechnique::Homoglyph]);
+            assert_eq!(
+                similar[0].confidence,
+                TyposquattingDetector::HOMOGLYPH_CONFIDENCE
+            );
+        }
+
+        // Lookalikes on top of an edit
+        let mixed = detector.similar_within("lоdahs", 2, 0.0);
+        let lodash = mixed.iter().find(|s| s.name == "lodash").unwrap();
+        assert_eq!(
+            lodash.techniques,
+            [
+                TyposquattingTechnique::CharacterEdit,
+                TyposquattingTechnique::Homoglyph
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scope_confusion() {
+        let detector = TyposquattingDetector::for_ecosystem("npm");
+        let confusion = |name: &str| detector.scope_confusion(name);
+
+        assert_eq!(
+            confusion("material-core"),
+            Some((
+                "@angular/material".to_string(),
+                TyposquattingReason::ScopedMemberUnscoped
+            ))
+        );
+        assert_eq!(
+            confusion("angular-forms").map(|(name, _)| name).as_deref(),
+            Some("@angular/forms")
+        );
+        assert_eq!(
+            confusion("aws-sdk-client-s3")
+                .map(|(name, _)| name)
+                .as_deref(),
+            Some("@aws-sdk/client-s3")
+        );
+        assert_eq!(
+            confusion("@angu1ar/material"),
+            Some((
+                "@angular/material".to_string(),
+                TyposquattingReason::LookalikeScope
+            ))
+        );
+        assert_eq!(
+            confusion("@lodash/lodash"),
+            Some((
+                "lodash".to_string(),
+                TyposquattingReason::UnscopedUnderScope
+            ))
+        );
+
+        // The real packages, generic names and unrelated scopes are left alone
+        for name in [
+            "@angular/material",
+            "@types/react",
+            "@jest/core",
+            "react",
+            "core",
+            "router-utils",
+            "lodash",
+        ] {
+            assert_eq!(confusion(name), None, "{name}");
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! Minimal yarn.lock parsing
+//!
+//! Reads the entries of both the classic (v1) format and the YAML format of
+//! Yarn 2+: each entry lists the specs it satisfies (`lodash@^4.17.0`), the
+//! version it locks, the ranges it depends on and, for v1, the URL it was
+//! resolved from. Other nested blocks such as `peerDependenciesMeta` are
+//! skipped.
+
+use anyhow::{anyhow, Result};
+
+/// Locked package in a yarn.lock
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YarnEntry {
+    /// Specs resolved to this entry, such as `lodash@^4.17.0` or `lodash@npm:^4.17.0`
+    pub specs: Vec<String>,
+    pub version: String,
+    /// Tarball URL, recorded by v1 lockfiles only
+    pub resolved: Option<String>,
+    /// `(name, range)` of its dependencies and optional dependencies
+    pub dependencies: Vec<(String, String)>,
+}
+
+impl YarnEntry {
+    /// Package name, taken from the first spec
+    pub fn name(&self) -> &str {
+        self.specs
+            .first()
+            .map_or("", |spec| spec_name(spec).unwrap_or(spec))
+    }
+
+    /// Whether this entry is what `name` declared with `range` resolves to
+    pub fn satisfies(&self, name: &str, range: &str) -> bool {
+        self.specs.iter().any(|spec| {
+            spec.strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix('@'))
+                .is_some_and(|rest| rest == range || rest.strip_prefix("npm:") == Some(range))
+        })
+    }
+}
+
+/// Entry whose fields are still being read
+struct PendingEntry {
+    line: usize,
+    specs: Vec<String>,
+    version: Option<String>,
+    resolved: Option<String>,
+    dependencies: Vec<(String, String)>,
+    /// Whether the nested block being read lists dependencies
+    in_dependencies: bool,
+}
+
+/// Parse a yarn.lock, failing on lines that fit neither format
+pub fn parse(content: &str) -> Result<Vec<YarnEntry>> {
+    let mut entries = Vec::new();
+    let mut current: Option<PendingEntry> = None;
+
+    for (index, line) in content.lines().enumerate() {
+        let number = index + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        if !line.starts_with(' ') {
+            if let Some(entry) = current.take() {
+                entries.extend(finish(entry)?);
+            }
+            let header = trimmed.strip_suffix(':').ok_or_else(|| {
+                anyhow!("line {}: expected an entry header ending in ':'", number)
+            })?;
+            let specs: Vec<String> = header
+                .split(',')
+                .map(|spec| spec.trim().trim_matches('"').to_string())
+                .collect();
+            if let Some(spec) = specs
+                .iter()
+                .find(|spec| *spec != "__metadata" && spec_name(spec).is_none())
+            {
+                return Err(anyhow!("line {}: '{}' is not a package spec", number, spec));
+            }
+            current = Some(PendingEntry {
+                line: number,
+                specs,
+                version: None,
+                resolved: None,
+                dependencies: Vec::new(),
+                in_dependencies: false,
+            });
+            continue;
+        }
+
+        let Some(entry) = current.as_mut() else {
+            return Err(anyhow!("line {}: field outside of an entry", number));
+        };
+        let (key, value) = trimmed
+            .split_once(": ")
+            .or_else(|| trimmed.split_once(' '))
+            .unwrap_or((trimmed.trim_end_matches(':'), ""));
+        // Fields of the entry itself are indented by exactly two spaces
+        if line.starts_with("   ") {
+            if entry.in_dependencies {
+                entry
+                    .dependencies
+                    .push((unquote(key).to_string(), unquote(value.trim()).to_string()));
+            }
+            continue;
+        }
+        entry.in_dependencies = matches!(key, "dependencies" | "optionalDependencies");
+        match key {
+            "version" => entry.version = Some(unquote(value.trim()).to_string()),
+            "resolved" => entry.resolved = Some(unquote(value.trim()).to_string()),
+            _ => {}
+        }
+    }
+    if let Some(entry) = current.take() {
+        entries.extend(finish(entry)?);
+    }
+    Ok(entries)
+}
+
+/// Complete an entry, dropping the Yarn 2+ `__metadata` block
+fn finish(entry: PendingEntry) -> Result<Option<YarnEntry>> {
+    let PendingEntry {
+        line,
+        specs,
+        version,
+        resolved,
+        dependencies,
+        ..
+    } = entry;
+    if specs.iter().any(|spec| spec == "__metadata") {
+        return Ok(None);
+    }
+    let version =
+        version.ok_or_else(|| anyhow!("line {}: entry '{}' has no version", line, specs[0]))?;
+    Ok(Some(YarnEntry {
+        specs,
+        version,
+        resolved,
+        dependencies,
+    }))
+}
+
+/// Name part of `name@range`, keeping the `@` of a scope
+fn spec_name(spec: &str) -> Option<&str> {
+    let at = spec.get(1..)?.find('@')? + 1;
+    Some(&spec[..at])
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_v1_and_berry() {
+        let v1 = r#"# yarn lockfile v1
+
+"@babel/core@^7.0.0", "@babel/core@^7.1.0":
+  version "7.24.0"
+  resolved "https://registry.yarnpkg.com/@babel/core/-/core-7.24.0.tgz"
+  dependencies:
+    debug "^4.1.0"
+
+lodash@^4.17.0:
+  version "4.17.21"
+"#;
+        let entries = parse(v1).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name(), "@babel/core");
+        assert_eq!(entries[0].version, "7.24.0");
+        assert!(entries[0].satisfies("@babel/core", "^7.1.0"));
+        assert!(entries[0].resolved.is_some());
+        assert_eq!(
+            entries[0].dependencies,
+            [("debug".to_string(), "^4.1.0".to_string())]
+        );
+
+        let berry = r#"__metadata:
+  version: 6
+  cacheKey: 8
+
+"lodash@npm:^4.17.0":
+  version: 4.17.21
+  resolution: "lodash@npm:4.17.21"
+  dependencies:
+    "@types/node": "npm:*"
+  checksum: 0123abcd
+"#;
+        let entries = parse(berry).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].satisfies("lodash", "^4.17.0"));
+        assert_eq!(entries[0].version, "4.17.21");
+        assert_eq!(
+            entries[0].dependencies,
+            [("@types/node".to_string(), "npm:*".to_string())]
+        );
+
+        assert!(parse("lodash@^4.17.0:\n  resolved \"x\"\n").is_err());
+        assert!(parse("<<<<<<< HEAD\nlodash@^4.17.0:\n").is_err());
+        assert!(parse("  version \"1.0.0\"\n").is_err());
+    }
+}
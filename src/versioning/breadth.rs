@@ -0,0 +1,166 @@
+//! How much of a package's future a declared version range lets in
+
+use serde::{Deserialize, Serialize};
+
+/// Breadth of a declared version range, from pinned to anything goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RangeBreadth {
+    /// A single version
+    Exact,
+    /// Compatible updates within one release line (`^1.2`, `~1.2`, `~=1.2`, `1.x`)
+    Compatible,
+    /// Bounded, but across more than one major version (`>=1 <4`)
+    MultiMajor,
+    /// A lower bound with no upper bound (`>=1.2`)
+    Unbounded,
+    /// Any version at all (`*`, `latest`, `>=0`, empty)
+    Any,
+}
+
+/// Classify a version specifier
+///
+/// Returns `None` for specifiers that aren't version ranges, such as git URLs,
+/// local paths or `npm:` aliases.
+pub fn range_breadth(spec: &str) -> Option<RangeBreadth> {
+    let spec = spec.trim();
+    if spec.contains("://")
+        || spec.starts_with("git")
+        || (spec.contains(':') && !spec.starts_with(|c: char| c.is_ascii_digit()))
+        || spec.starts_with('.')
+        || spec.starts_with('/')
+    {
+        return None;
+    }
+
+    Some(
+        spec.split("||")
+            .map(alternative_breadth)
+            .max()
+            .unwrap_or(RangeBreadth::Any),
+    )
+}
+
+/// Breadth of one `||` alternative
+fn alternative_breadth(alternative: &str) -> RangeBreadth {
+    let alternative = alternative.trim();
+    if alternative.is_empty() || is_wildcard(alternative) {
+        return RangeBreadth::Any;
+    }
+    // Dist-tags such as `latest` or `next` float with every publish
+    if alternative.chars().all(|c| c.is_ascii_alphabetic()) {
+        return RangeBreadth::Any;
+    }
+
+    if let Some((low, high)) = alternative.split_once(" - ") {
+        return span(major(low), major(high));
+    }
+
+    // Re-attach operators written with a space, as in `>= 1.2` or `~> 5.0`
+    let mut comparators: Vec<String> = Vec::new();
+    for token in alternative
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|t| !t.is_empty())
+    {
+        match comparators.last_mut() {
+            Some(last) if last.chars().all(|c| "<>=~^!".contains(c)) => last.push_str(token),
+            _ => comparators.push(token.to_string()),
+        }
+    }
+    let mut lower = None;
+    let mut upper = None;
+    let mut compatible = false;
+    let mut exact = false;
+    for comparator in &comparators {
+        let operator_len = comparator
+            .find(|c: char| c.is_ascii_alphanumeric() || c == '*')
+            .unwrap_or(comparator.len());
+        let (operator, version) = comparator.split_at(operator_len);
+        match operator {
+            ">" | ">=" => lower = Some(version),
+            "<" | "<=" => upper = Some(version),
+            "^" | "~" | "~>" | "~=" => compatible = true,
+            "!=" => {}
+            "" | "=" | "==" | "===" if is_wildcard(version) => return RangeBreadth::Any,
+            "" | "=" | "==" | "===" if version.contains(['x', 'X', '*']) => compatible = true,
+            "" | "=" | "==" | "===" => exact = true,
+            _ => {}
+        }
+    }
+
+    match (lower, upper) {
+        (Some(low), None) if !compatible && !exact => {
+            if major(low) == Some(0) && is_zero(low) {
+                RangeBreadth::Any
+            } else {
+                RangeBreadth::Unbounded
+            }
+        }
+        (Some(low), Some(high)) => span(major(low), major(high)),
+        _ if compatible => RangeBreadth::Compatible,
+        _ => RangeBreadth::Exact,
+    }
+}
+
+fn span(low: Option<u64>, high: Option<u64>) -> RangeBreadth {
+    match (low, high) {
+        (Some(low), Some(high)) if high > low + 1 => RangeBreadth::MultiMajor,
+        _ => RangeBreadth::Compatible,
+    }
+}
+
+fn is_wildcard(version: &str) -> bool {
+    matches!(version, "*" | "x" | "X" | "*.*" | "x.x" | "*.*.*" | "x.x.x")
+}
+
+fn major(version: &str) -> Option<u64> {
+    version
+        .trim()
+        .trim_start_matches(['v', '='])
+        .split('.')
+        .next()?
+        .parse()
+        .ok()
+}
+
+fn is_zero(version: &str) -> bool {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .all(|part| part == "0")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_range_breadth() {
+        use RangeBreadth::*;
+        let cases = [
+            ("*", Any),
+            ("", Any),
+            ("latest", Any),
+            (">=0", Any),
+            (">=0.0.0", Any),
+            ("1.x || *", Any),
+            (">=1.2.0", Unbounded),
+            ("> 2", Unbounded),
+            (">=1.0 <5.0", MultiMajor),
+            ("1.0.0 - 4.0.0", MultiMajor),
+            (">=2.0, <3.0", Compatible),
+            ("^1.2.3", Compatible),
+            ("~> 5.0", Compatible),
+            ("~=2.28", Compatible),
+            ("1.x", Compatible),
+            ("4.17.21", Exact),
+            ("==2.0.1", Exact),
+        ];
+        for (spec, expected) in cases {
+            assert_eq!(range_breadth(spec), Some(expected), "{}", spec);
+        }
+        assert_eq!(range_breadth("git+https://github.com/a/b.git"), None);
+        assert_eq!(range_breadth("file:../local"), None);
+        assert_eq!(range_breadth("npm:other@^1.0.0"), None);
+    }
+}
@@ -0,0 +1,277 @@
+//! Composer versions (Packagist ordering and constraint syntax)
+
+use anyhow::{anyhow, Result};
+use std::cmp::Ordering;
+
+use super::{split_comparators, ParsedVersion, VersionScheme};
+
+/// Stability flags in ascending order; `-dev` sorts before every release
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Stability {
+    Dev,
+    Alpha,
+    Beta,
+    Rc,
+    Stable,
+    Patch,
+}
+
+/// Parsed Composer version, padded to four numeric components
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct ComposerVersion {
+    release: [u64; 4],
+    stability: Stability,
+    number: Vec<u64>,
+}
+
+impl ComposerVersion {
+    /// Parse a version such as `v5.4.31`, `1.0.0-beta2` or `2.1.0.3-RC1`,
+    /// returning it with the number of components given
+    fn parse(version: &str) -> Result<(Self, usize)> {
+        let version = version.trim().to_ascii_lowercase();
+        let input = version.trim_start_matches('v');
+        let input = input.split('+').next().unwrap_or_default();
+        let split = input
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(input.len());
+        let (numbers, suffix) = input.split_at(split);
+
+        let components = numbers
+            .split('.')
+            .map(|n| n.parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| anyhow!("Not a Composer version: {}", version))?;
+        if components.len() > 4 {
+            return Err(anyhow!("Too many version components in {}", version));
+        }
+        let mut release = [0; 4];
+        release[..components.len()].copy_from_slice(&components);
+
+        let suffix = suffix.trim_start_matches(['-', '_', '.']);
+        let name_end = suffix
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(suffix.len());
+        let (name, number) = suffix.split_at(name_end);
+        let stability = match name {
+            "dev" => Stability::Dev,
+            "alpha" | "a" => Stability::Alpha,
+            "beta" | "b" => Stability::Beta,
+            "rc" => Stability::Rc,
+            "" | "stable" => Stability::Stable,
+            "patch" | "pl" | "p" => Stability::Patch,
+            other => {
+                return Err(anyhow!(
+                    "Unknown stability '{}' in Composer version {}",
+                    other,
+                    version
+                ))
+            }
+        };
+        let number = number
+            .split(['.', '-', '_'])
+            .filter(|n| !n.is_empty())
+            .map(|n| n.parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| anyhow!("Not a Composer version: {}", version))?;
+
+        Ok((
+            Self {
+                release,
+                stability,
+                number,
+            },
+            components.len(),
+        ))
+    }
+
+    /// Lowest version of the release line `components`, such as `2.0.0.0-dev`
+    fn floor(components: &[u64]) -> Self {
+        let mut release = [0; 4];
+        release[..components.len()].copy_from_slice(components);
+        Self {
+            release,
+            stability: Stability::Dev,
+            number: vec![],
+        }
+    }
+}
+
+/// Whether a version names its stability, as `1.0-beta1` does but `v1.0` not
+fn has_stability(version: &str) -> bool {
+    version
+        .trim()
+        .trim_start_matches(['v', 'V'])
+        .contains(|c: char| c.is_ascii_alphabetic())
+}
+
+fn compare_versions(a: &str, b: &str) -> Result<Ordering> {
+    Ok(ComposerVersion::parse(a)?
+        .0
+        .cmp(&ComposerVersion::parse(b)?.0))
+}
+
+/// Composer scheme
+#[derive(Debug, Clone, Default)]
+pub struct ComposerScheme;
+
+impl ComposerScheme {
+    /// Whether `version` meets one constraint such as `^1.2`, `~1.2.3`,
+    /// `1.2.*` or `>=5.0.0`
+    fn meets(version: &ComposerVersion, op: &str, target: &str) -> Result<bool> {
+        if matches!(target, "*" | "x") {
+            return Ok(true);
+        }
+        if let Some(prefix) = target
+            .strip_suffix(".*")
+            .or_else(|| target.strip_suffix(".x"))
+        {
+            let (low, given) = ComposerVersion::parse(prefix)?;
+            let ceiling = Self::bump(&low.release[..given], given - 1);
+            return Ok(
+                *version >= ComposerVersion::floor(&low.release[..given]) && *version < ceiling
+            );
+        }
+
+        let (target_version, given) = ComposerVersion::parse(target)?;
+        // Unqualified bounds include the prereleases of the version they name
+        let floor = if has_stability(target) {
+            target_version.clone()
+        } else {
+            ComposerVersion::floor(&target_version.release)
+        };
+        let ok = match op {
+            "^" => {
+                let significant = target_version.release[..given]
+                    .iter()
+                    .position(|n| *n != 0)
+                    .unwrap_or(given - 1);
+                *version >= floor
+                    && *version < Self::bump(&target_version.release, significant.min(2))
+            }
+            "~" => {
+                let position = given.saturating_sub(2);
+                *version >= floor && *version < Self::bump(&target_version.release, position)
+            }
+            ">=" => *version >= floor,
+            "<" => *version < floor,
+            ">" => *version > target_version,
+            "<=" => *version <= target_version,
+            "!=" | "<>" => *version != target_version,
+            "" | "=" | "==" => *version == target_version,
+            other => return Err(anyhow!("Unsupported Composer constraint: {}", other)),
+        };
+        Ok(ok)
+    }
+
+    /// Lowest version after the release line ending at `position`
+    fn bump(release: &[u64], position: usize) -> ComposerVersion {
+        let mut ceiling = release[..=position].to_vec();
+        ceiling[position] += 1;
+        ComposerVersion::floor(&ceiling)
+    }
+}
+
+impl VersionScheme for ComposerScheme {
+    fn name(&self) -> &str {
+        "composer"
+    }
+
+    fn parse(&self, version: &str) -> Result<ParsedVersion> {
+        let (parsed, _) = ComposerVersion::parse(version)?;
+        Ok(ParsedVersion {
+            original: version.to_string(),
+            normalized: version.trim().trim_start_matches(['v', 'V']).to_lowercase(),
+            is_prerelease: parsed.stability < Stability::Stable,
+        })
+    }
+
+    fn compare(&self, a: &str, b: &str) -> Result<Ordering> {
+        compare_versions(a, b)
+    }
+
+    /// Alternatives are separated by `||` or `|`, constraints within one by
+    /// commas or whitespace, and `1.0 - 2.0` is an inclusive hyphen range
+    fn satisfies(&self, version: &str, range: &str) -> Result<bool> {
+        const OPERATORS: [&str; 10] = [">=", "<=", "!=", "<>", "==", ">", "<", "=", "^", "~"];
+
+        let (version, _) = ComposerVersion::parse(version)?;
+        for alternative in range.split('|').filter(|a| !a.trim().is_empty()) {
+            if let Some((low, high)) = alternative.split_once(" - ") {
+                let (high_version, given) = ComposerVersion::parse(high)?;
+                let below_high = if given < 4 && !has_stability(high) {
+                    version < Self::bump(&high_version.release, given - 1)
+                } else {
+                    version <= high_version
+                };
+                if Self::meets(&version, ">=", low.trim())? && below_high {
+                    return Ok(true);
+                }
+                continue;
+            }
+
+            let mut all = true;
+            for (op, target) in split_comparators(alternative, &OPERATORS) {
+                if !Self::meets(&version, &op, &target)? {
+                    all = false;
+                    break;
+                }
+            }
+            if all {
+                return Ok(true);
+            }
+        }
+        Ok(range.trim().is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_composer_ordering() {
+        let ordered = [
+            "1.0.0-dev",
+            "1.0.0-alpha1",
+            "1.0.0-beta2",
+            "1.0.0-RC1",
+            "1.0.0",
+            "1.0.0-patch1",
+            "1.0.0.1",
+            "v1.0.1",
+        ];
+        let scheme = ComposerScheme;
+        for pair in ordered.windows(2) {
+            assert_eq!(
+                scheme.compare(pair[0], pair[1]).unwrap(),
+                Ordering::Less,
+                "{} < {}",
+                pair[0],
+                pair[1]
+            );
+        }
+        assert_eq!(scheme.compare("v2.1", "2.1.0.0").unwrap(), Ordering::Equal);
+        assert!(scheme.parse("1.0.0-beta.2").unwrap().is_prerelease);
+        assert!(scheme.parse("1.2.3.4.5").is_err());
+    }
+
+    #[test]
+    fn test_composer_constraints() {
+        let scheme = ComposerScheme;
+        let advisory = ">=5.0.0,<5.4.31|>=6.0.0,<6.2.8";
+        assert!(scheme.satisfies("5.4.30", advisory).unwrap());
+        assert!(!scheme.satisfies("5.4.31", advisory).unwrap());
+        assert!(scheme.satisfies("6.2.7.1", advisory).unwrap());
+        assert!(!scheme.satisfies("6.2.8", advisory).unwrap());
+
+        assert!(scheme.satisfies("1.9.9", "^1.2").unwrap());
+        assert!(!scheme.satisfies("2.0.0-beta1", "^1.2").unwrap());
+        assert!(!scheme.satisfies("0.4.0", "^0.3").unwrap());
+        assert!(scheme.satisfies("1.9", "~1.2").unwrap());
+        assert!(!scheme.satisfies("1.3.0", "~1.2.3").unwrap());
+        assert!(scheme.satisfies("1.2.9", "1.2.*").unwrap());
+        assert!(scheme.satisfies("2.0.5", "1.0 - 2.0").unwrap());
+        assert!(!scheme.satisfies("2.1.0", "1.0 - 2.0").unwrap());
+        assert!(scheme.satisfies("3.1", ">=1.0 <2.0 || >=3.0").unwrap());
+    }
+}
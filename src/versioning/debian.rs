@@ -0,0 +1,188 @@
+//! Debian package versions (`[epoch:]upstream[-revision]`)
+
+use anyhow::{anyhow, Result};
+use std::cmp::Ordering;
+
+use super::{ParsedVersion, VersionScheme};
+
+/// Parsed Debian version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebianVersion {
+    pub epoch: u64,
+    pub upstream: String,
+    pub revision: String,
+}
+
+impl DebianVersion {
+    /// Parse a Debian version string
+    pub fn parse(version: &str) -> Result<Self> {
+        let version = version.trim();
+        let (epoch, rest) = match version.split_once(':') {
+            Some((epoch, rest)) => (
+                epoch
+                    .parse::<u64>()
+                    .map_err(|_| anyhow!("Invalid epoch in Debian version: {}", version))?,
+                rest,
+            ),
+            None => (0, version),
+        };
+        let (upstream, revision) = match rest.rsplit_once('-') {
+            Some((upstream, revision)) => (upstream, revision),
+            None => (rest, ""),
+        };
+
+        if !upstream.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(anyhow!(
+                "Debian upstream version must start with a digit: {}",
+                version
+            ));
+        }
+        let valid = |s: &str, extra: &[char]| {
+            s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+.~".contains(c) || extra.contains(&c))
+        };
+        if !valid(upstream, &['-', ':']) || !valid(revision, &[]) {
+            return Err(anyhow!("Invalid characters in Debian version: {}", version));
+        }
+
+        Ok(Self {
+            epoch,
+            upstream: upstream.to_string(),
+            revision: revision.to_string(),
+        })
+    }
+}
+
+impl PartialOrd for DebianVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DebianVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| compare_fragment(&self.upstream, &other.upstream))
+            .then_with(|| compare_fragment(&self.revision, &other.revision))
+    }
+}
+
+/// Character weight used by dpkg: `~` sorts before everything, even the end
+fn weight(c: Option<u8>) -> i32 {
+    match c {
+        None => 0,
+        Some(b'~') => -1,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => c as i32,
+        Some(c) => c as i32 + 256,
+    }
+}
+
+/// dpkg's `verrevcmp`: alternate non-digit and digit runs
+fn compare_fragment(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() || j < b.len() {
+        while (i < a.len() && !a[i].is_ascii_digit()) || (j < b.len() && !b[j].is_ascii_digit()) {
+            let (wa, wb) = (weight(a.get(i).copied()), weight(b.get(j).copied()));
+            if wa != wb {
+                return wa.cmp(&wb);
+            }
+            i += 1;
+            j += 1;
+        }
+
+        while i < a.len() && a[i] == b'0' {
+            i += 1;
+        }
+        while j < b.len() && b[j] == b'0' {
+            j += 1;
+        }
+
+        let mut first_diff = Ordering::Equal;
+        while i < a.len() && a[i].is_ascii_digit() && j < b.len() && b[j].is_ascii_digit() {
+            if first_diff == Ordering::Equal {
+                first_diff = a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+        if i < a.len() && a[i].is_ascii_digit() {
+            return Ordering::Greater;
+        }
+        if j < b.len() && b[j].is_ascii_digit() {
+            return Ordering::Less;
+        }
+        if first_diff != Ordering::Equal {
+            return first_diff;
+        }
+    }
+
+    Ordering::Equal
+}
+
+/// Debian/dpkg scheme
+#[derive(Debug, Clone, Default)]
+pub struct DebianScheme;
+
+impl VersionScheme for DebianScheme {
+    fn name(&self) -> &str {
+        "debian"
+    }
+
+    fn parse(&self, version: &str) -> Result<ParsedVersion> {
+        let parsed = DebianVersion::parse(version)?;
+        Ok(ParsedVersion {
+            original: version.to_string(),
+            normalized: version.trim().to_string(),
+            is_prerelease: parsed.upstream.contains('~'),
+        })
+    }
+
+    fn compare(&self, a: &str, b: &str) -> Result<Ordering> {
+        Ok(DebianVersion::parse(a)?.cmp(&DebianVersion::parse(b)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epoch_and_tilde_ordering() {
+        let scheme = DebianScheme;
+        let less = |a: &str, b: &str| scheme.compare(a, b).unwrap() == Ordering::Less;
+
+        // Epoch dominates everything else
+        assert!(less("9.9-9", "1:0.1-1"));
+        assert!(less("1:2.0", "2:1.0"));
+        // Missing epoch is epoch 0
+        assert_eq!(scheme.compare("0:1.0-1", "1.0-1").unwrap(), Ordering::Equal);
+
+        // Tilde sorts before anything, including the end of the string
+        assert!(less("1.0~rc1", "1.0"));
+        assert!(less("1.0~~", "1.0~"));
+        assert!(less("1.0~", "1.0"));
+        assert!(less("1.0~rc1-1", "1.0-1"));
+        assert!(less("1.0", "1.0a"));
+        assert!(less("1.0+dfsg", "1.0.1"));
+
+        // Revision comparison and numeric runs
+        assert!(less("2.30-1", "2.30-1ubuntu1"));
+        assert!(less("1.2.9", "1.2.10"));
+        assert_eq!(scheme.compare("1.001", "1.1").unwrap(), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_relations() {
+        let scheme = DebianScheme;
+        assert!(scheme
+            .satisfies("2.36-9+deb12u3", "<< 2.36-9+deb12u4")
+            .unwrap());
+        assert!(scheme.satisfies("1:1.2-1", ">= 1:1.0").unwrap());
+        assert!(!scheme.satisfies("1.2-1", ">= 1:1.0").unwrap());
+        assert!(scheme.satisfies("1.0~beta1", "<< 1.0").unwrap());
+    }
+}
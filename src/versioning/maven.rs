@@ -0,0 +1,257 @@
+//! Maven versions (`ComparableVersion` ordering and range syntax)
+
+use anyhow::{anyhow, Result};
+use std::cmp::Ordering;
+
+use super::{satisfies_comparators, ParsedVersion, VersionScheme};
+
+/// Item of a tokenized Maven version
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Item {
+    Number(u64),
+    Qualifier(String),
+}
+
+impl Item {
+    /// Qualifier rank; unknown qualifiers sort after `sp`, lexically
+    fn rank(qualifier: &str) -> (u8, &str) {
+        match qualifier {
+            "alpha" => (0, ""),
+            "beta" => (1, ""),
+            "milestone" => (2, ""),
+            "rc" => (3, ""),
+            "snapshot" => (4, ""),
+            "" | "ga" | "final" | "release" => (5, ""),
+            "sp" => (6, ""),
+            other => (7, other),
+        }
+    }
+
+    fn is_null(&self) -> bool {
+        match self {
+            Item::Number(n) => *n == 0,
+            Item::Qualifier(q) => Self::rank(q).0 == 5,
+        }
+    }
+
+    fn compare(a: Option<&Item>, b: Option<&Item>) -> Ordering {
+        match (a, b) {
+            (None, None) => Ordering::Equal,
+            (Some(x), None) => Self::compare(Some(x), Some(&x.padding())),
+            (None, Some(y)) => Self::compare(Some(&y.padding()), Some(y)),
+            (Some(Item::Number(x)), Some(Item::Number(y))) => x.cmp(y),
+            (Some(Item::Number(_)), Some(Item::Qualifier(_))) => Ordering::Greater,
+            (Some(Item::Qualifier(_)), Some(Item::Number(_))) => Ordering::Less,
+            (Some(Item::Qualifier(x)), Some(Item::Qualifier(y))) => {
+                Self::rank(x).cmp(&Self::rank(y))
+            }
+        }
+    }
+
+    /// Value a shorter version is padded with when compared against this item
+    fn padding(&self) -> Item {
+        match self {
+            Item::Number(_) => Item::Number(0),
+            Item::Qualifier(_) => Item::Qualifier(String::new()),
+        }
+    }
+}
+
+/// Tokenize a Maven version into numbers and normalized qualifiers
+fn tokenize(version: &str) -> Result<Vec<Item>> {
+    let version = version.trim().to_lowercase();
+    if version.is_empty() {
+        return Err(anyhow!("Empty Maven version"));
+    }
+
+    let mut raw = Vec::new();
+    let mut current = String::new();
+    for c in version.chars() {
+        let boundary = match current.chars().last() {
+            Some(last) => last.is_ascii_digit() != c.is_ascii_digit(),
+            None => false,
+        };
+        if c == '.' || c == '-' || c == '_' {
+            raw.push(std::mem::take(&mut current));
+        } else {
+            if boundary {
+                raw.push(std::mem::take(&mut current));
+            }
+            current.push(c);
+        }
+    }
+    raw.push(current);
+
+    let mut items = Vec::new();
+    for (i, token) in raw.iter().enumerate() {
+        let followed_by_number = raw
+            .get(i + 1)
+            .map(|n| n.starts_with(|c: char| c.is_ascii_digit()))
+            .unwrap_or(false);
+        let item = match token.parse::<u64>() {
+            Ok(n) => Item::Number(n),
+            Err(_) => Item::Qualifier(
+                match token.as_str() {
+                    "a" if followed_by_number => "alpha",
+                    "b" if followed_by_number => "beta",
+                    "m" if followed_by_number => "milestone",
+                    "cr" => "rc",
+                    other => other,
+                }
+                .to_string(),
+            ),
+        };
+        items.push(item);
+    }
+
+    while items.last().map(Item::is_null).unwrap_or(false) && items.len() > 1 {
+        items.pop();
+    }
+    Ok(items)
+}
+
+fn compare_versions(a: &str, b: &str) -> Result<Ordering> {
+    let (a, b) = (tokenize(a)?, tokenize(b)?);
+    for i in 0..a.len().max(b.len()) {
+        let ord = Item::compare(a.get(i), b.get(i));
+        if ord != Ordering::Equal {
+            return Ok(ord);
+        }
+    }
+    Ok(Ordering::Equal)
+}
+
+/// Maven scheme
+#[derive(Debug, Clone, Default)]
+pub struct MavenScheme;
+
+impl MavenScheme {
+    /// Evaluate one bracketed range such as `[1.0,2.0)` or `[1.5]`
+    fn in_bracket_range(version: &str, range: &str) -> Result<bool> {
+        let inclusive_low = range.starts_with('[');
+        let inclusive_high = range.ends_with(']');
+        let inner = &range[1..range.len() - 1];
+
+        let Some((low, high)) = inner.split_once(',') else {
+            // `[1.5]` pins an exact version
+            return Ok(compare_versions(version, inner)? == Ordering::Equal);
+        };
+
+        let (low, high) = (low.trim(), high.trim());
+        if !low.is_empty() {
+            let ord = compare_versions(version, low)?;
+            if ord == Ordering::Less || (ord == Ordering::Equal && !inclusive_low) {
+                return Ok(false);
+            }
+        }
+        if !high.is_empty() {
+            let ord = compare_versions(version, high)?;
+            if ord == Ordering::Greater || (ord == Ordering::Equal && !inclusive_high) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl VersionScheme for MavenScheme {
+    fn name(&self) -> &str {
+        "maven"
+    }
+
+    fn parse(&self, version: &str) -> Result<ParsedVersion> {
+        let items = tokenize(version)?;
+        if !matches!(items.first(), Some(Item::Number(_))) {
+            return Err(anyhow!("Not a Maven version: {}", version));
+        }
+        let is_prerelease = items.iter().any(|item| match item {
+            Item::Qualifier(q) => Item::rank(q).0 < 5,
+            Item::Number(_) => false,
+        });
+        Ok(ParsedVersion {
+            original: version.to_string(),
+            normalized: version.trim().to_lowercase(),
+            is_prerelease,
+        })
+    }
+
+    fn compare(&self, a: &str, b: &str) -> Result<Ordering> {
+        compare_versions(a, b)
+    }
+
+    fn satisfies(&self, version: &str, range: &str) -> Result<bool> {
+        let range = range.trim();
+
+        if range.starts_with('[') || range.starts_with('(') {
+            // Unions such as `[1.0,2.0),[3.0,4.0)`
+            let mut start = 0;
+            let mut depth_open = false;
+            for (i, c) in range.char_indices() {
+                match c {
+                    '[' | '(' if !depth_open => {
+                        start = i;
+                        depth_open = true;
+                    }
+                    ']' | ')' if depth_open => {
+                        depth_open = false;
+                        if Self::in_bracket_range(version, &range[start..=i])? {
+                            return Ok(true);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            if depth_open {
+                return Err(anyhow!("Unterminated Maven version range: {}", range));
+            }
+            return Ok(false);
+        }
+
+        // Advisory style `2.0-beta9 to 2.14.1`, inclusive on both ends
+        if let Some((low, high)) = range.split_once(" to ") {
+            return Ok(compare_versions(version, low)? != Ordering::Less
+                && compare_versions(version, high)? != Ordering::Greater);
+        }
+
+        satisfies_comparators(version, range, compare_versions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maven_ordering_and_ranges() {
+        let ordered = [
+            "1.0-alpha-1",
+            "1.0-beta1",
+            "1.0-M2",
+            "1.0-rc1",
+            "1.0-SNAPSHOT",
+            "1.0",
+            "1.0-sp1",
+            "1.0.1",
+        ];
+        let scheme = MavenScheme;
+        for pair in ordered.windows(2) {
+            assert_eq!(
+                scheme.compare(pair[0], pair[1]).unwrap(),
+                Ordering::Less,
+                "{} < {}",
+                pair[0],
+                pair[1]
+            );
+        }
+        assert_eq!(
+            scheme.compare("1.0.0", "1.0-final").unwrap(),
+            Ordering::Equal
+        );
+
+        assert!(scheme.satisfies("2.14.1", "2.0-beta9 to 2.14.1").unwrap());
+        assert!(!scheme.satisfies("2.15.0", "2.0-beta9 to 2.14.1").unwrap());
+        assert!(scheme.satisfies("1.5", "[1.0,2.0)").unwrap());
+        assert!(!scheme.satisfies("2.0", "[1.0,2.0)").unwrap());
+        assert!(scheme.satisfies("3.1", "(,1.0],[3.0,)").unwrap());
+    }
+}
@@ -0,0 +1,272 @@
+//! Ecosystem-specific version schemes
+//!
+//! Each ecosystem orders versions and expresses ranges differently. A
+//! [`VersionScheme`] encapsulates those rules, and the [`VersionSchemeRegistry`]
+//! maps ecosystem names (the analyzers' package types) to schemes so that
+//! vulnerability matching and remediation use the right comparison.
+
+pub mod breadth;
+pub mod composer;
+pub mod debian;
+pub mod maven;
+pub mod overlap;
+pub mod pep440;
+pub mod prerelease;
+pub mod rubygems;
+pub mod semver;
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+pub use breadth::{range_breadth, RangeBreadth};
+pub use composer::ComposerScheme;
+pub use debian::DebianScheme;
+pub use maven::MavenScheme;
+pub use overlap::{common_version, evaluates};
+pub use pep440::Pep440Scheme;
+pub use prerelease::PrereleasePolicy;
+pub use rubygems::RubyGemsScheme;
+pub use semver::SemverScheme;
+
+/// A version validated by a scheme
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParsedVersion {
+    pub original: String,
+    pub normalized: String,
+    pub is_prerelease: bool,
+}
+
+/// Version parsing, ordering and range rules for an ecosystem
+pub trait VersionScheme: Send + Sync {
+    /// Scheme name
+    fn name(&self) -> &str;
+
+    /// Parse and normalize a concrete version
+    fn parse(&self, version: &str) -> Result<ParsedVersion>;
+
+    /// Compare two concrete versions
+    fn compare(&self, a: &str, b: &str) -> Result<Ordering>;
+
+    /// Check whether a concrete version falls within a range expression
+    fn satisfies(&self, version: &str, range: &str) -> Result<bool> {
+        satisfies_comparators(version, range, |a, b| self.compare(a, b))
+    }
+}
+
+static GLOBAL_REGISTRY: Lazy<VersionSchemeRegistry> = Lazy::new(VersionSchemeRegistry::new);
+
+/// Registry of version schemes keyed by ecosystem
+pub struct VersionSchemeRegistry {
+    schemes: RwLock<HashMap<String, Arc<dyn VersionScheme>>>,
+}
+
+impl VersionSchemeRegistry {
+    /// Create a registry with the built-in schemes
+    pub fn new() -> Self {
+        let registry = Self::empty();
+        let semver: Arc<dyn VersionScheme> = Arc::new(SemverScheme::new());
+        for ecosystem in ["npm", "go", "terraform"] {
+            registry.register_arc(ecosystem, semver.clone());
+        }
+        registry.register("cargo", SemverScheme::cargo());
+        registry.register("python", Pep440Scheme);
+        registry.register("java", MavenScheme);
+        registry.register("maven", MavenScheme);
+        registry.register("debian", DebianScheme);
+        registry.register("rubygems", RubyGemsScheme);
+        registry.register("composer", ComposerScheme);
+        registry
+    }
+
+    /// Create a registry without any schemes
+    pub fn empty() -> Self {
+        Self {
+            schemes: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Process-wide registry consulted by the built-in vulnerability databases
+    pub fn global() -> &'static VersionSchemeRegistry {
+        &GLOBAL_REGISTRY
+    }
+
+    /// Register a scheme for an ecosystem, replacing any existing one
+    pub fn register(&self, ecosystem: &str, scheme: impl VersionScheme + 'static) {
+        self.register_arc(ecosystem, Arc::new(scheme));
+    }
+
+    /// Register a shared scheme for an ecosystem
+    pub fn register_arc(&self, ecosystem: &str, scheme: Arc<dyn VersionScheme>) {
+        self.schemes
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(ecosystem.to_lowercase(), scheme);
+    }
+
+    /// Get the scheme for an ecosystem
+    pub fn get(&self, ecosystem: &str) -> Option<Arc<dyn VersionScheme>> {
+        self.schemes
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&ecosystem.to_lowercase())
+            .cloned()
+    }
+
+    /// Get the scheme for an ecosystem, falling back to semver
+    pub fn get_or_default(&self, ecosystem: &str) -> Arc<dyn VersionScheme> {
+        self.get(ecosystem)
+            .unwrap_or_else(|| Arc::new(SemverScheme::new()))
+    }
+
+    /// List registered ecosystems
+    pub fn ecosystems(&self) -> Vec<String> {
+        let mut names: Vec<_> = self
+            .schemes
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .keys()
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+impl Default for VersionSchemeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Comparison operators shared by most range syntaxes
+const OPERATORS: [&str; 10] = ["===", "==", "!=", ">=", "<=", ">>", "<<", ">", "<", "="];
+
+/// Evaluate a comparator range such as `>= 1.0, < 2.0 || = 3.0`
+///
+/// Alternatives are separated by `||`; comparators within an alternative by
+/// commas or whitespace. `<<`/`>>` are accepted as strict comparisons and a
+/// bare version means equality.
+pub fn satisfies_comparators(
+    version: &str,
+    range: &str,
+    compare: impl Fn(&str, &str) -> Result<Ordering>,
+) -> Result<bool> {
+    for alternative in range.split("||") {
+        let comparators = split_comparators(alternative, &OPERATORS);
+        if comparators.is_empty() {
+            return Ok(true);
+        }
+
+        let mut all = true;
+        for (op, target) in comparators {
+            if target == "*" {
+                continue;
+            }
+            let ord = compare(version, &target)?;
+            let ok = match op.as_str() {
+                ">=" => ord != Ordering::Less,
+                "<=" => ord != Ordering::Greater,
+                ">" | ">>" => ord == Ordering::Greater,
+                "<" | "<<" => ord == Ordering::Less,
+                "!=" => ord != Ordering::Equal,
+                "" | "=" | "==" | "===" => ord == Ordering::Equal,
+                other => return Err(anyhow!("Unsupported range operator: {}", other)),
+            };
+            if !ok {
+                all = false;
+                break;
+            }
+        }
+        if all {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Split one range alternative into `(operator, version)` pairs
+///
+/// `operators` must list longer operators before their prefixes.
+pub(crate) fn split_comparators(range: &str, operators: &[&str]) -> Vec<(String, String)> {
+    let mut result = Vec::new();
+    let mut pending_op: Option<String> = None;
+
+    for token in range
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|t| !t.is_empty())
+    {
+        let op = operators
+            .iter()
+            .find(|op| token.starts_with(**op))
+            .map(|op| op.to_string())
+            .unwrap_or_default();
+        let rest = &token[op.len()..];
+
+        match (pending_op.take(), op.is_empty(), rest.is_empty()) {
+            // Operator separated from its version by whitespace
+            (None, false, true) => pending_op = Some(op),
+            (Some(prev), true, _) => result.push((prev, token.to_string())),
+            (_, _, _) => result.push((op, rest.to_string())),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_comparators() {
+        assert_eq!(
+            split_comparators(">= 1.0, <2.0", &OPERATORS),
+            vec![
+                (">=".to_string(), "1.0".to_string()),
+                ("<".to_string(), "2.0".to_string())
+            ]
+        );
+        assert_eq!(
+            split_comparators("1.2.3", &OPERATORS),
+            vec![("".to_string(), "1.2.3".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_registry_custom_scheme() {
+        struct Calendar;
+        impl VersionScheme for Calendar {
+            fn name(&self) -> &str {
+                "calendar"
+            }
+            fn parse(&self, version: &str) -> Result<ParsedVersion> {
+                if version.len() == 8 && version.chars().all(|c| c.is_ascii_digit()) {
+                    Ok(ParsedVersion {
+                        original: version.to_string(),
+                        normalized: version.to_string(),
+                        is_prerelease: false,
+                    })
+                } else {
+                    Err(anyhow!("Invalid calendar version: {}", version))
+                }
+            }
+            fn compare(&self, a: &str, b: &str) -> Result<Ordering> {
+                Ok(self.parse(a)?.normalized.cmp(&self.parse(b)?.normalized))
+            }
+        }
+
+        let registry = VersionSchemeRegistry::new();
+        registry.register("Internal", Calendar);
+        let scheme = registry.get("internal").unwrap();
+        assert_eq!(scheme.name(), "calendar");
+        assert!(scheme
+            .satisfies("20240105", ">=20240101, <20250101")
+            .unwrap());
+        assert!(!scheme.satisfies("20250105", "<20250101").unwrap());
+        assert_eq!(registry.get("python").unwrap().name(), "pep440");
+    }
+}
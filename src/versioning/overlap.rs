@@ -0,0 +1,146 @@
+//! Whether two ranges admit a common version
+//!
+//! Schemes only answer whether a concrete version satisfies a range, so
+//! overlap is decided by probing: every version a range names, plus its
+//! neighbours one step up and down in each numeric component, is tested
+//! against both sides. Intervals that share at least one release (rather than
+//! only pre-releases between two adjacent releases) always contain one of
+//! these probes.
+
+use std::cmp::Ordering;
+
+use super::{split_comparators, PrereleasePolicy, VersionScheme};
+
+/// Operators stripped from range comparators to find the versions they name
+const RANGE_OPERATORS: [&str; 14] = [
+    "===", "~>", "~=", ">=", "<=", "!=", "==", ">>", "<<", ">", "<", "=", "^", "~",
+];
+
+/// Lowest release satisfying `range` and at least one of `others`
+///
+/// Returns `None` when no probe satisfies both, including when either side
+/// cannot be evaluated by the scheme. Pre-releases are never returned.
+pub fn common_version(
+    scheme: &dyn VersionScheme,
+    policy: PrereleasePolicy,
+    range: &str,
+    others: &[String],
+) -> Option<String> {
+    let mut probes: Vec<String> = std::iter::once(range)
+        .chain(others.iter().map(String::as_str))
+        .flat_map(named_versions)
+        .flat_map(|version| neighbours(&version))
+        .filter(|probe| {
+            scheme
+                .parse(probe)
+                .is_ok_and(|parsed| !parsed.is_prerelease)
+        })
+        .collect();
+    probes.sort_by(|a, b| scheme.compare(a, b).unwrap_or(Ordering::Equal));
+    probes.dedup_by(|a, b| scheme.compare(a, b).is_ok_and(|ord| ord == Ordering::Equal));
+
+    let satisfies =
+        |version: &str, range: &str| policy.satisfies(scheme, version, range).unwrap_or(false);
+    probes
+        .into_iter()
+        .find(|probe| satisfies(probe, range) && others.iter().any(|other| satisfies(probe, other)))
+}
+
+/// Whether `scheme` can evaluate `range`, judged on the versions it names
+///
+/// A range naming no version, such as a dist-tag, cannot be evaluated.
+pub fn evaluates(scheme: &dyn VersionScheme, range: &str) -> bool {
+    named_versions(range)
+        .iter()
+        .filter(|version| scheme.parse(version).is_ok())
+        .any(|version| scheme.satisfies(version, range).is_ok())
+}
+
+/// Versions a range expression names, without operators
+fn named_versions(range: &str) -> Vec<String> {
+    range
+        .split("||")
+        .flat_map(|alternative| alternative.split(" - "))
+        .flat_map(|part| split_comparators(part, &RANGE_OPERATORS))
+        .map(|(_, version)| version.trim_start_matches(['v', '=']).to_string())
+        .filter(|version| !version.is_empty() && version != "*")
+        .collect()
+}
+
+/// A version with each numeric component stepped up and down
+///
+/// `1.4.2` gives itself, `2.0.0`, `1.5.0`, `1.4.3`, `0.0.0`, `1.3.0` and
+/// `1.4.1`. Later components reset to zero, keeping the count of components.
+fn neighbours(version: &str) -> Vec<String> {
+    let numeric: String = version
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    let parts: Vec<u64> = numeric
+        .split('.')
+        .map_while(|part| part.parse().ok())
+        .collect();
+
+    let mut result = vec![version.to_string()];
+    let step = |index: usize, value: u64| {
+        parts
+            .iter()
+            .enumerate()
+            .map(|(i, part)| match i.cmp(&index) {
+                Ordering::Less => part.to_string(),
+                Ordering::Equal => value.to_string(),
+                Ordering::Greater => "0".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    };
+    for (index, part) in parts.iter().enumerate() {
+        result.push(step(index, part + 1));
+        if *part > 0 {
+            result.push(step(index, part - 1));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::versioning::{Pep440Scheme, SemverScheme};
+
+    fn affected(ranges: &[&str]) -> Vec<String> {
+        ranges.iter().map(|r| r.to_string()).collect()
+    }
+
+    #[test]
+    fn test_semver_overlap() {
+        let scheme = SemverScheme::new();
+        let policy = PrereleasePolicy::for_ecosystem("npm");
+        let common = |range: &str, vulnerable: &[&str]| {
+            common_version(&scheme, policy, range, &affected(vulnerable))
+        };
+
+        assert_eq!(common(">=1.0.0", &["< 1.2.2"]).as_deref(), Some("1.0.0"));
+        assert_eq!(
+            common("^1.5.0", &["3.3.4", ">=1.7.0 <1.8.1"]).as_deref(),
+            Some("1.7.0")
+        );
+        assert_eq!(common("^3.3.0", &["3.3.4"]).as_deref(), Some("3.3.4"));
+        assert_eq!(common("<2.0.0", &["<1.2.0"]).as_deref(), Some("0.0.0"));
+        assert_eq!(common("^1.2.2", &["< 1.2.2"]), None);
+        assert_eq!(common("~4.17.21", &["< 4.17.12"]), None);
+        assert_eq!(common(">1.0.0 <1.5.0", &[">=1.5.0"]), None);
+    }
+
+    #[test]
+    fn test_pep440_overlap() {
+        let scheme = Pep440Scheme;
+        let policy = PrereleasePolicy::for_ecosystem("python");
+        let vulnerable = affected(&[">=2.0, <2.31.0"]);
+        assert_eq!(
+            common_version(&scheme, policy, ">=2.25", &vulnerable).as_deref(),
+            Some("2.25")
+        );
+        assert_eq!(common_version(&scheme, policy, "~=2.31", &vulnerable), None);
+    }
+}
@@ -0,0 +1,395 @@
+//! PEP 440 versions and specifiers (Python)
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::cmp::Ordering;
+use std::fmt;
+
+use super::{ParsedVersion, VersionScheme};
+
+static VERSION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?ix)^\s*v?
+        (?:(?P<epoch>[0-9]+)!)?
+        (?P<release>[0-9]+(?:\.[0-9]+)*)
+        (?P<pre>[-_.]?(?P<pre_l>alpha|a|beta|b|preview|pre|c|rc)[-_.]?(?P<pre_n>[0-9]+)?)?
+        (?P<post>(?:-(?P<post_n1>[0-9]+))|(?:[-_.]?(?P<post_l>post|rev|r)[-_.]?(?P<post_n2>[0-9]+)?))?
+        (?P<dev>[-_.]?dev[-_.]?(?P<dev_n>[0-9]+)?)?
+        (?:\+(?P<local>[a-z0-9]+(?:[-_.][a-z0-9]+)*))?
+        \s*$",
+    )
+    .expect("valid PEP 440 regex")
+});
+
+/// Pre-release phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PreRelease {
+    Alpha,
+    Beta,
+    ReleaseCandidate,
+}
+
+/// Segment of a local version label; numeric segments sort after strings
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LocalSegment {
+    Text(String),
+    Number(u64),
+}
+
+/// Parsed PEP 440 version
+#[derive(Debug, Clone)]
+pub struct Pep440Version {
+    pub epoch: u64,
+    pub release: Vec<u64>,
+    pub pre: Option<(PreRelease, u64)>,
+    pub post: Option<u64>,
+    pub dev: Option<u64>,
+    pub local: Vec<LocalSegment>,
+}
+
+/// Sort key for the pre-release slot
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum PreKey {
+    /// `1.0.dev0` sorts before every pre-release of `1.0`
+    DevOnly,
+    Pre(PreRelease, u64),
+    Final,
+}
+
+impl Pep440Version {
+    /// Parse a PEP 440 version
+    pub fn parse(version: &str) -> Result<Self> {
+        let caps = VERSION_RE
+            .captures(version)
+            .ok_or_else(|| anyhow!("Invalid PEP 440 version: {}", version))?;
+        let number = |name: &str| -> Result<Option<u64>> {
+            caps.name(name)
+                .map(|m| m.as_str().parse::<u64>())
+                .transpose()
+                .map_err(Into::into)
+        };
+
+        let pre = match caps.name("pre_l") {
+            Some(label) => {
+                let kind = match label.as_str().to_lowercase().as_str() {
+                    "a" | "alpha" => PreRelease::Alpha,
+                    "b" | "beta" => PreRelease::Beta,
+                    _ => PreRelease::ReleaseCandidate,
+                };
+                Some((kind, number("pre_n")?.unwrap_or(0)))
+            }
+            None => None,
+        };
+
+        let post = if caps.name("post").is_some() {
+            Some(number("post_n1")?.or(number("post_n2")?).unwrap_or(0))
+        } else {
+            None
+        };
+
+        let dev = if caps.name("dev").is_some() {
+            Some(number("dev_n")?.unwrap_or(0))
+        } else {
+            None
+        };
+
+        let local = caps
+            .name("local")
+            .map(|m| {
+                m.as_str()
+                    .split(['-', '_', '.'])
+                    .map(|s| match s.parse::<u64>() {
+                        Ok(n) => LocalSegment::Number(n),
+                        Err(_) => LocalSegment::Text(s.to_lowercase()),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            epoch: number("epoch")?.unwrap_or(0),
+            release: caps["release"]
+                .split('.')
+                .map(|p| p.parse::<u64>())
+                .collect::<Result<_, _>>()?,
+            pre,
+            post,
+            dev,
+            local,
+        })
+    }
+
+    /// Whether this is a pre-release or development release
+    pub fn is_prerelease(&self) -> bool {
+        self.pre.is_some() || self.dev.is_some()
+    }
+
+    /// The version without its local label
+    pub fn public(&self) -> Self {
+        Self {
+            local: vec![],
+            ..self.clone()
+        }
+    }
+
+    fn trimmed_release(&self) -> &[u64] {
+        let end = self
+            .release
+            .iter()
+            .rposition(|&n| n != 0)
+            .map_or(0, |i| i + 1);
+        &self.release[..end]
+    }
+
+    fn pre_key(&self) -> PreKey {
+        match (self.pre, self.post, self.dev) {
+            (None, None, Some(_)) => PreKey::DevOnly,
+            (Some((kind, n)), _, _) => PreKey::Pre(kind, n),
+            (None, _, _) => PreKey::Final,
+        }
+    }
+
+    /// Same epoch and release numbers, ignoring trailing zeros
+    fn same_base(&self, other: &Self) -> bool {
+        self.epoch == other.epoch && self.trimmed_release() == other.trimmed_release()
+    }
+
+    /// Whether the release starts with `prefix`, padding with zeros
+    fn matches_prefix(&self, prefix: &Self) -> bool {
+        self.epoch == prefix.epoch
+            && prefix
+                .release
+                .iter()
+                .enumerate()
+                .all(|(i, n)| self.release.get(i).copied().unwrap_or(0) == *n)
+    }
+}
+
+impl PartialEq for Pep440Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Pep440Version {}
+
+impl PartialOrd for Pep440Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Pep440Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| self.trimmed_release().cmp(other.trimmed_release()))
+            .then_with(|| self.pre_key().cmp(&other.pre_key()))
+            .then_with(|| self.post.cmp(&other.post))
+            // Development releases sort before the release they precede
+            .then_with(|| (self.dev.is_none(), self.dev).cmp(&(other.dev.is_none(), other.dev)))
+            .then_with(|| self.local.cmp(&other.local))
+    }
+}
+
+impl fmt::Display for Pep440Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.epoch != 0 {
+            write!(f, "{}!", self.epoch)?;
+        }
+        let release: Vec<_> = self.release.iter().map(|n| n.to_string()).collect();
+        write!(f, "{}", release.join("."))?;
+        if let Some((kind, n)) = self.pre {
+            let label = match kind {
+                PreRelease::Alpha => "a",
+                PreRelease::Beta => "b",
+                PreRelease::ReleaseCandidate => "rc",
+            };
+            write!(f, "{}{}", label, n)?;
+        }
+        if let Some(n) = self.post {
+            write!(f, ".post{}", n)?;
+        }
+        if let Some(n) = self.dev {
+            write!(f, ".dev{}", n)?;
+        }
+        if !self.local.is_empty() {
+            let local: Vec<_> = self
+                .local
+                .iter()
+                .map(|s| match s {
+                    LocalSegment::Text(t) => t.clone(),
+                    LocalSegment::Number(n) => n.to_string(),
+                })
+                .collect();
+            write!(f, "+{}", local.join("."))?;
+        }
+        Ok(())
+    }
+}
+
+/// PEP 440 scheme
+#[derive(Debug, Clone, Default)]
+pub struct Pep440Scheme;
+
+impl Pep440Scheme {
+    /// Evaluate a single specifier clause such as `~=1.4.2`
+    fn matches_clause(version: &Pep440Version, raw: &str, clause: &str) -> Result<bool> {
+        let op = ["~=", "===", "==", "!=", "<=", ">=", "<", ">"]
+            .iter()
+            .find(|op| clause.starts_with(**op))
+            .copied()
+            .unwrap_or("==");
+        let target = clause.strip_prefix(op).unwrap_or(clause).trim();
+
+        if op == "===" {
+            return Ok(raw.trim().eq_ignore_ascii_case(target));
+        }
+
+        if let Some(prefix) = target.strip_suffix(".*") {
+            let prefix = Pep440Version::parse(prefix)?;
+            let matched = version.matches_prefix(&prefix);
+            return match op {
+                "==" => Ok(matched),
+                "!=" => Ok(!matched),
+                _ => Err(anyhow!("Wildcards are only allowed with == and !=")),
+            };
+        }
+
+        let spec = Pep440Version::parse(target)?;
+        // Local labels only matter for exact matches that name one
+        let candidate = if op == "==" && !spec.local.is_empty() {
+            version.clone()
+        } else {
+            version.public()
+        };
+
+        Ok(match op {
+            "==" => candidate == spec,
+            "!=" => candidate != spec,
+            "<=" => candidate <= spec,
+            ">=" => candidate >= spec,
+            "<" => {
+                candidate < spec
+                    && (spec.is_prerelease()
+                        || !(candidate.is_prerelease() && candidate.same_base(&spec)))
+            }
+            ">" => {
+                candidate > spec
+                    && (spec.post.is_some()
+                        || !(candidate.post.is_some() && candidate.same_base(&spec)))
+            }
+            "~=" => {
+                if spec.release.len() < 2 {
+                    return Err(anyhow!("~= requires at least two release segments"));
+                }
+                let prefix = Pep440Version {
+                    release: spec.release[..spec.release.len() - 1].to_vec(),
+                    pre: None,
+                    post: None,
+                    dev: None,
+                    local: vec![],
+                    ..spec.clone()
+                };
+                candidate >= spec && candidate.matches_prefix(&prefix)
+            }
+            _ => unreachable!("operator list is exhaustive"),
+        })
+    }
+}
+
+impl VersionScheme for Pep440Scheme {
+    fn name(&self) -> &str {
+        "pep440"
+    }
+
+    fn parse(&self, version: &str) -> Result<ParsedVersion> {
+        let parsed = Pep440Version::parse(version)?;
+        Ok(ParsedVersion {
+            original: version.to_string(),
+            normalized: parsed.to_string(),
+            is_prerelease: parsed.is_prerelease(),
+        })
+    }
+
+    fn compare(&self, a: &str, b: &str) -> Result<Ordering> {
+        Ok(Pep440Version::parse(a)?.cmp(&Pep440Version::parse(b)?))
+    }
+
+    fn satisfies(&self, version: &str, range: &str) -> Result<bool> {
+        let parsed = Pep440Version::parse(version)?;
+        for clause in range.split(',').map(str::trim).filter(|c| !c.is_empty()) {
+            if clause == "*" {
+                continue;
+            }
+            if !Self::matches_clause(&parsed, version, &clause.replace(' ', ""))? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pre_release_ordering() {
+        // Ordering example from PEP 440
+        let ordered = [
+            "1.0.dev456",
+            "1.0a1",
+            "1.0a2.dev456",
+            "1.0a12.dev456",
+            "1.0a12",
+            "1.0b1.dev456",
+            "1.0b2",
+            "1.0b2.post345.dev456",
+            "1.0b2.post345",
+            "1.0rc1.dev456",
+            "1.0rc1",
+            "1.0",
+            "1.0+abc.5",
+            "1.0+abc.7",
+            "1.0+5",
+            "1.0.post456.dev34",
+            "1.0.post456",
+            "1.0.15",
+            "1.1.dev1",
+        ];
+        let scheme = Pep440Scheme;
+        for pair in ordered.windows(2) {
+            assert_eq!(
+                scheme.compare(pair[0], pair[1]).unwrap(),
+                Ordering::Less,
+                "{} < {}",
+                pair[0],
+                pair[1]
+            );
+        }
+        assert_eq!(scheme.compare("1.0", "1.0.0").unwrap(), Ordering::Equal);
+        assert_eq!(scheme.compare("1!0.1", "2.0").unwrap(), Ordering::Greater);
+        assert_eq!(
+            scheme.parse("1.0-ALPHA.1_dev2").unwrap().normalized,
+            "1.0a1.dev2"
+        );
+    }
+
+    #[test]
+    fn test_specifiers() {
+        let scheme = Pep440Scheme;
+        assert!(scheme.satisfies("2.2.5", ">=2.0, <3.0").unwrap());
+        assert!(scheme.satisfies("3.2.1", "< 3.2.22").unwrap());
+        assert!(scheme.satisfies("1.4.5", "~=1.4.2").unwrap());
+        assert!(!scheme.satisfies("1.5.0", "~=1.4.2").unwrap());
+        assert!(scheme.satisfies("1.1.post1", "==1.1.*").unwrap());
+        assert!(scheme.satisfies("1.0+local", "==1.0").unwrap());
+        assert!(!scheme.satisfies("1.0+local", "==1.0+other").unwrap());
+        // Exclusive comparisons skip pre/post releases of the boundary itself
+        assert!(!scheme.satisfies("3.0rc1", "<3.0").unwrap());
+        assert!(scheme.satisfies("3.0rc1", "<3.0rc2").unwrap());
+        assert!(!scheme.satisfies("1.7.post1", ">1.7").unwrap());
+    }
+}
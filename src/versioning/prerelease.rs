@@ -0,0 +1,220 @@
+//! Whether pre-release versions take part in range matching
+//!
+//! Ecosystems disagree on whether `1.0.0-rc.1` satisfies a range. Plain
+//! precedence puts it inside `<1.0.0`; node-semver and Cargo only let a
+//! pre-release match a range that names a pre-release of the same release;
+//! pip leaves pre-releases out unless a specifier asks for one. Build metadata
+//! (`1.0.0+build.5`) never affects matching in any scheme: it is ignored when
+//! comparing, so `1.0.0+build.5` matches exactly where `1.0.0` does.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::VersionScheme;
+
+/// How pre-release versions are treated when checking them against a range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrereleasePolicy {
+    /// Match pre-releases by precedence alone, as OSV ranges do
+    Include,
+    /// A pre-release only matches a `||` alternative containing a pre-release
+    /// comparator on the same release (`>=1.0.0-rc.1 <1.0.0`), as node-semver
+    /// and Cargo do
+    SameRelease,
+    /// A pre-release only matches a range that names some pre-release, as pip
+    /// does for candidate versions
+    Exclude,
+}
+
+impl PrereleasePolicy {
+    /// The policy matching an ecosystem's own resolver
+    ///
+    /// Semver ecosystems use [`SameRelease`](Self::SameRelease). Python uses
+    /// [`Include`](Self::Include): an installed pre-release is "already
+    /// present" under PEP 440, and its scheme already keeps `1.0rc1` out of
+    /// `<1.0`. Everything else matches by precedence.
+    pub fn for_ecosystem(ecosystem: &str) -> Self {
+        match ecosystem {
+            "npm" | "cargo" | "go" | "terraform" => Self::SameRelease,
+            _ => Self::Include,
+        }
+    }
+
+    /// Check whether `version` falls within `range` under this policy
+    pub fn satisfies(self, scheme: &dyn VersionScheme, version: &str, range: &str) -> Result<bool> {
+        if self == Self::Include || !scheme.parse(version)?.is_prerelease {
+            return scheme.satisfies(version, range);
+        }
+
+        match self {
+            Self::Include => unreachable!("handled above"),
+            Self::Exclude => {
+                let names_prerelease = range
+                    .split("||")
+                    .any(|alternative| !prerelease_comparators(scheme, alternative).is_empty());
+                Ok(names_prerelease && scheme.satisfies(version, range)?)
+            }
+            Self::SameRelease => {
+                for alternative in range.split("||") {
+                    let same_release = prerelease_comparators(scheme, alternative)
+                        .iter()
+                        .any(|target| same_release(scheme, version, target));
+                    if same_release && scheme.satisfies(version, alternative)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+        }
+    }
+
+    /// Whether a pre-release fix may be recommended as an upgrade target
+    ///
+    /// Users already on a pre-release are offered pre-release fixes; everyone
+    /// else only when `allow` is set.
+    pub fn allows_prerelease_fix(scheme: &dyn VersionScheme, current: &str, allow: bool) -> bool {
+        allow || scheme.parse(current).is_ok_and(|v| v.is_prerelease)
+    }
+}
+
+/// Versions named in one range alternative that are pre-releases
+fn prerelease_comparators<'a>(scheme: &dyn VersionScheme, alternative: &'a str) -> Vec<&'a str> {
+    alternative
+        .split(|c: char| c == ',' || c.is_whitespace() || "[]()".contains(c))
+        .map(|token| token.trim_start_matches(['<', '>', '=', '!', '~', '^']))
+        .filter(|token| scheme.parse(token).is_ok_and(|v| v.is_prerelease))
+        .collect()
+}
+
+/// Whether two versions share their release numbers, ignoring pre-release tags
+fn same_release(scheme: &dyn VersionScheme, a: &str, b: &str) -> bool {
+    scheme
+        .compare(release_part(a), release_part(b))
+        .is_ok_and(|ord| ord.is_eq())
+}
+
+/// The leading dotted numbers of a version: `1.0.0` for `1.0.0-rc.1` or `1.0a1`
+fn release_part(version: &str) -> &str {
+    let version = version.trim().trim_start_matches('v');
+    let end = version
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(version.len());
+    version[..end].trim_end_matches('.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{Vulnerability, VulnerabilitySeverity};
+    use crate::versioning::{Pep440Scheme, SemverScheme};
+
+    #[test]
+    fn test_semver_prerelease_policies() {
+        let npm = SemverScheme::new();
+        let check = |policy: PrereleasePolicy, version: &str, range: &str| {
+            policy.satisfies(&npm, version, range).unwrap()
+        };
+
+        // The release candidate of the fixing release sorts below it
+        assert!(check(PrereleasePolicy::Include, "1.0.0-rc.1", "<1.0.0"));
+        assert!(!check(
+            PrereleasePolicy::SameRelease,
+            "1.0.0-rc.1",
+            "<1.0.0"
+        ));
+        assert!(!check(PrereleasePolicy::Exclude, "1.0.0-rc.1", "<1.0.0"));
+
+        // A range naming a pre-release of the same release lets it in
+        let range = ">=1.0.0-alpha.1 <1.0.0";
+        assert!(check(PrereleasePolicy::SameRelease, "1.0.0-rc.1", range));
+        assert!(!check(PrereleasePolicy::SameRelease, "0.9.0-rc.1", range));
+        assert!(check(PrereleasePolicy::Exclude, "1.0.0-rc.1", range));
+
+        // Only the alternative that names the pre-release counts
+        let range = "<0.5.0 || >=1.0.0-beta.1 <1.0.1";
+        assert!(check(PrereleasePolicy::SameRelease, "1.0.0-rc.1", range));
+        assert!(!check(PrereleasePolicy::SameRelease, "0.4.0-rc.1", range));
+
+        // Stable versions are unaffected, and build metadata is ignored
+        assert!(check(PrereleasePolicy::Exclude, "0.9.0", "<1.0.0"));
+        assert!(check(
+            PrereleasePolicy::SameRelease,
+            "1.0.0+build.5",
+            "=1.0.0"
+        ));
+        assert!(!check(PrereleasePolicy::Include, "1.0.0+build.5", "<1.0.0"));
+    }
+
+    #[test]
+    fn test_pep440_prerelease_policies() {
+        let python = Pep440Scheme;
+        let check = |policy: PrereleasePolicy, version: &str, range: &str| {
+            policy.satisfies(&python, version, range).unwrap()
+        };
+
+        // PEP 440 keeps pre-releases of 0.12.3 out of `<0.12.3` on its own
+        assert!(!check(PrereleasePolicy::Include, "0.12.3rc1", "<0.12.3"));
+        assert!(check(PrereleasePolicy::Include, "0.12.0a1", "<0.12.3"));
+        assert!(!check(PrereleasePolicy::Exclude, "0.12.0a1", "<0.12.3"));
+        assert!(check(
+            PrereleasePolicy::Exclude,
+            "0.12.0a1",
+            ">=0.12.0a1,<0.12.3"
+        ));
+        assert!(check(PrereleasePolicy::Exclude, "0.12.0", "<0.12.3"));
+
+        assert_eq!(
+            PrereleasePolicy::for_ecosystem("python"),
+            PrereleasePolicy::Include
+        );
+        assert_eq!(
+            PrereleasePolicy::for_ecosystem("npm"),
+            PrereleasePolicy::SameRelease
+        );
+    }
+
+    #[test]
+    fn test_prerelease_fix_recommendations() {
+        let npm = SemverScheme::new();
+        let vuln = Vulnerability {
+            id: "GHSA-test".to_string(),
+            title: "Test".to_string(),
+            description: String::new(),
+            severity: VulnerabilitySeverity::High,
+            cvss_score: None,
+            cvss_vector: None,
+            affected_versions: vec!["<2.0.1".to_string()],
+            fixed_versions: vec!["2.0.0-rc.2".to_string(), "2.0.1".to_string()],
+            published_date: None,
+            updated_date: None,
+            references: vec![],
+            cwe_ids: vec![],
+            exploit_available: false,
+            patch_available: true,
+            source_severity: None,
+            affected_symbols: vec![],
+            likely_reachable: None,
+            aliases: vec![],
+        };
+
+        assert_eq!(
+            vuln.lowest_fixed_version("1.9.0", &npm).as_deref(),
+            Some("2.0.1")
+        );
+        assert_eq!(
+            vuln.lowest_fixed_version_with("1.9.0", &npm, true)
+                .as_deref(),
+            Some("2.0.0-rc.2")
+        );
+        // Already on a pre-release, the next pre-release fix is fine
+        assert_eq!(
+            vuln.lowest_fixed_version("2.0.0-rc.1", &npm).as_deref(),
+            Some("2.0.0-rc.2")
+        );
+
+        // The fixing release's own candidate isn't flagged under npm rules
+        assert!(vuln.affects_version("2.0.1-rc.1", &npm));
+        assert!(!vuln.affects_version_with("2.0.1-rc.1", &npm, PrereleasePolicy::SameRelease));
+    }
+}
@@ -0,0 +1,216 @@
+//! RubyGems versions (`Gem::Version` ordering and `~>` requirements)
+
+use anyhow::{anyhow, Result};
+use std::cmp::Ordering;
+
+use super::{satisfies_comparators, split_comparators, ParsedVersion, VersionScheme};
+
+/// Segment of a gem version: a run of digits or of letters
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Number(u64),
+    Text(String),
+}
+
+impl Segment {
+    fn is_zero(&self) -> bool {
+        *self == Segment::Number(0)
+    }
+
+    /// Letters sort before numbers, so `1.0.a` precedes `1.0`
+    fn compare(a: &Segment, b: &Segment) -> Ordering {
+        match (a, b) {
+            (Segment::Number(x), Segment::Number(y)) => x.cmp(y),
+            (Segment::Text(x), Segment::Text(y)) => x.cmp(y),
+            (Segment::Text(_), Segment::Number(_)) => Ordering::Less,
+            (Segment::Number(_), Segment::Text(_)) => Ordering::Greater,
+        }
+    }
+}
+
+/// Split a version into segments; a `-` starts a prerelease as in `1.0-rc1`
+fn segments(version: &str) -> Result<Vec<Segment>> {
+    let version = version.trim();
+    let valid = version.starts_with(|c: char| c.is_ascii_digit())
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    if !valid {
+        return Err(anyhow!("Not a RubyGems version: {}", version));
+    }
+
+    let version = version.replace('-', ".pre.");
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    for c in version.chars().chain(std::iter::once('.')) {
+        let boundary = match current.chars().last() {
+            Some(last) => c == '.' || last.is_ascii_digit() != c.is_ascii_digit(),
+            None => false,
+        };
+        if boundary {
+            let run = std::mem::take(&mut current);
+            segments.push(match run.parse::<u64>() {
+                Ok(n) => Segment::Number(n),
+                Err(_) if run.starts_with(|c: char| c.is_ascii_digit()) => {
+                    return Err(anyhow!("Version segment too large in {}", version))
+                }
+                Err(_) => Segment::Text(run),
+            });
+        }
+        if c != '.' {
+            current.push(c);
+        }
+    }
+    Ok(segments)
+}
+
+/// Drop trailing zeros from the release and prerelease parts, so that `1.0`
+/// equals `1` and `1.0.a.0` equals `1.a`
+fn canonical(segments: Vec<Segment>) -> Vec<Segment> {
+    let split = segments
+        .iter()
+        .position(|s| matches!(s, Segment::Text(_)))
+        .unwrap_or(segments.len());
+    let (release, prerelease) = segments.split_at(split);
+    let trim = |part: &[Segment]| {
+        let end = part.iter().rposition(|s| !s.is_zero()).map_or(0, |i| i + 1);
+        part[..end].to_vec()
+    };
+    let mut canonical = trim(release);
+    canonical.extend(trim(prerelease));
+    canonical
+}
+
+fn compare_versions(a: &str, b: &str) -> Result<Ordering> {
+    let (a, b) = (canonical(segments(a)?), canonical(segments(b)?));
+    let zero = Segment::Number(0);
+    for i in 0..a.len().max(b.len()) {
+        let ord = Segment::compare(a.get(i).unwrap_or(&zero), b.get(i).unwrap_or(&zero));
+        if ord != Ordering::Equal {
+            return Ok(ord);
+        }
+    }
+    Ok(Ordering::Equal)
+}
+
+/// Release part of a version, without any prerelease segments
+fn release(version: &str) -> Result<Vec<u64>> {
+    Ok(segments(version)?
+        .into_iter()
+        .map_while(|s| match s {
+            Segment::Number(n) => Some(n),
+            Segment::Text(_) => None,
+        })
+        .collect())
+}
+
+fn join(release: &[u64]) -> String {
+    release
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// RubyGems scheme
+///
+/// Every segment is significant, so `7.0.4.2` sorts before `7.0.4.3`.
+#[derive(Debug, Clone, Default)]
+pub struct RubyGemsScheme;
+
+impl RubyGemsScheme {
+    /// Whether `version` meets a pessimistic requirement: `~> 2.2` allows
+    /// `>= 2.2, < 3` and `~> 2.2.1` allows `>= 2.2.1, < 2.3`
+    fn pessimistic(version: &str, target: &str) -> Result<bool> {
+        let mut ceiling = release(target)?;
+        if ceiling.len() > 1 {
+            ceiling.pop();
+        }
+        if let Some(last) = ceiling.last_mut() {
+            *last += 1;
+        }
+        Ok(compare_versions(version, target)? != Ordering::Less
+            && compare_versions(&join(&release(version)?), &join(&ceiling))? == Ordering::Less)
+    }
+}
+
+impl VersionScheme for RubyGemsScheme {
+    fn name(&self) -> &str {
+        "rubygems"
+    }
+
+    fn parse(&self, version: &str) -> Result<ParsedVersion> {
+        let segments = segments(version)?;
+        Ok(ParsedVersion {
+            original: version.to_string(),
+            normalized: version.trim().to_string(),
+            is_prerelease: segments.iter().any(|s| matches!(s, Segment::Text(_))),
+        })
+    }
+
+    fn compare(&self, a: &str, b: &str) -> Result<Ordering> {
+        compare_versions(a, b)
+    }
+
+    fn satisfies(&self, version: &str, range: &str) -> Result<bool> {
+        const OPERATORS: [&str; 7] = ["~>", ">=", "<=", "!=", ">", "<", "="];
+
+        for alternative in range.split("||") {
+            let mut all = true;
+            for (op, target) in split_comparators(alternative, &OPERATORS) {
+                let ok = if op == "~>" {
+                    Self::pessimistic(version, &target)?
+                } else {
+                    satisfies_comparators(version, &format!("{}{}", op, target), compare_versions)?
+                };
+                if !ok {
+                    all = false;
+                    break;
+                }
+            }
+            if all {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gem_ordering() {
+        let ordered = [
+            "1.0.a", "1.0.b1", "1.0-rc1", "1.0", "1.0.0.1", "1.0.1", "7.0.4.2", "7.0.4.3", "7.0.10",
+        ];
+        let scheme = RubyGemsScheme;
+        for pair in ordered.windows(2) {
+            assert_eq!(
+                scheme.compare(pair[0], pair[1]).unwrap(),
+                Ordering::Less,
+                "{} < {}",
+                pair[0],
+                pair[1]
+            );
+        }
+        assert_eq!(scheme.compare("1.0.0", "1").unwrap(), Ordering::Equal);
+        assert!(scheme.parse("1.0.rc1").unwrap().is_prerelease);
+        assert!(scheme.parse("v1.0").is_err());
+    }
+
+    #[test]
+    fn test_gem_requirements() {
+        let scheme = RubyGemsScheme;
+        assert!(scheme.satisfies("7.0.4.2", "< 7.0.4.3").unwrap());
+        assert!(!scheme.satisfies("7.0.4.3", ">= 7.0.0, < 7.0.4.3").unwrap());
+        assert!(scheme.satisfies("5.2.8", "< 5.2.8.1").unwrap());
+        assert!(scheme.satisfies("2.9.9", "~> 2.2").unwrap());
+        assert!(!scheme.satisfies("3.0", "~> 2.2").unwrap());
+        assert!(scheme.satisfies("2.2.9", "~> 2.2.1").unwrap());
+        assert!(!scheme.satisfies("2.3.0", "~> 2.2.1").unwrap());
+        assert!(!scheme.satisfies("2.2.0", "~> 2.2.1").unwrap());
+        assert!(scheme.satisfies("1.1", "< 1.0 || >= 1.1").unwrap());
+    }
+}
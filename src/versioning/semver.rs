@@ -0,0 +1,272 @@
+//! Semantic versioning (npm, Cargo, Go, Terraform)
+
+use anyhow::{anyhow, Result};
+use std::cmp::Ordering;
+
+use super::{split_comparators, ParsedVersion, VersionScheme};
+use crate::utils::version_parser::Version;
+
+/// SemVer 2.0 scheme with npm/Cargo/Terraform range syntax
+#[derive(Debug, Clone, Default)]
+pub struct SemverScheme {
+    bare_is_caret: bool,
+}
+
+/// Partially specified version such as `1.2` or `1.x`
+#[derive(Debug, Clone)]
+struct Partial {
+    major: Option<u64>,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    suffix: String,
+}
+
+impl Partial {
+    fn parse(input: &str) -> Result<Self> {
+        let input = input.trim_start_matches('v');
+        let split = input.find(['-', '+']).unwrap_or(input.len());
+        let (core, suffix) = input.split_at(split);
+
+        let mut parts = core.split('.').map(|p| match p {
+            "x" | "X" | "*" | "" => Ok(None),
+            n => n
+                .parse::<u64>()
+                .map(Some)
+                .map_err(|_| anyhow!("Invalid version component '{}' in {}", n, input)),
+        });
+        let major = parts.next().transpose()?.flatten();
+        let minor = parts.next().transpose()?.flatten();
+        let patch = parts.next().transpose()?.flatten();
+        if parts.next().is_some() {
+            return Err(anyhow!("Too many version components in {}", input));
+        }
+
+        Ok(Self {
+            major,
+            minor: major.and(minor),
+            patch: major.and(minor).and(patch),
+            suffix: suffix.to_string(),
+        })
+    }
+
+    fn is_full(&self) -> bool {
+        self.patch.is_some()
+    }
+
+    fn specified(&self) -> usize {
+        [self.major, self.minor, self.patch]
+            .iter()
+            .filter(|p| p.is_some())
+            .count()
+    }
+
+    fn floor(&self) -> Result<Version> {
+        let suffix = if self.is_full() { &self.suffix } else { "" };
+        Version::parse(&format!(
+            "{}.{}.{}{}",
+            self.major.unwrap_or(0),
+            self.minor.unwrap_or(0),
+            self.patch.unwrap_or(0),
+            suffix
+        ))
+    }
+
+    fn bump(major: u64, minor: u64, patch: u64) -> Result<Version> {
+        Version::parse(&format!("{}.{}.{}", major, minor, patch))
+    }
+
+    /// Exclusive upper bound of an x-range (`1.2` → `1.3.0`)
+    fn x_ceiling(&self) -> Result<Option<Version>> {
+        match (self.major, self.minor) {
+            (None, _) => Ok(None),
+            (Some(major), None) => Self::bump(major + 1, 0, 0).map(Some),
+            (Some(major), Some(minor)) => Self::bump(major, minor + 1, 0).map(Some),
+        }
+    }
+}
+
+/// Primitive comparison produced by desugaring a range
+type Bound = (Ordering, bool, Version);
+
+impl SemverScheme {
+    /// npm-style scheme where a bare version means an exact match
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cargo-style scheme where a bare version means a caret requirement
+    pub fn cargo() -> Self {
+        Self {
+            bare_is_caret: true,
+        }
+    }
+
+    /// Parse a version, accepting a leading `v`/`=` and missing minor/patch
+    pub fn parse_version(version: &str) -> Result<Version> {
+        let partial = Partial::parse(version.trim().trim_start_matches('='))?;
+        if partial.major.is_none() {
+            return Err(anyhow!("Not a concrete version: {}", version));
+        }
+        Partial {
+            patch: Some(partial.patch.unwrap_or(0)),
+            minor: Some(partial.minor.unwrap_or(0)),
+            ..partial
+        }
+        .floor()
+    }
+
+    /// Convert one comparator into primitive bounds
+    fn desugar(&self, op: &str, target: &str) -> Result<Vec<Bound>> {
+        let partial = Partial::parse(target)?;
+        let Some(major) = partial.major else {
+            // `*`, `x` and friends match everything
+            return Ok(vec![]);
+        };
+        let minor = partial.minor.unwrap_or(0);
+        let patch = partial.patch.unwrap_or(0);
+        let floor = partial.floor()?;
+
+        let op = if op.is_empty() && self.bare_is_caret {
+            "^"
+        } else {
+            op
+        };
+
+        let ge = (Ordering::Greater, true, floor.clone());
+        let bounds = match op {
+            "" | "=" | "==" => match partial.x_ceiling()? {
+                Some(ceiling) if !partial.is_full() => {
+                    vec![ge, (Ordering::Less, false, ceiling)]
+                }
+                _ => vec![(Ordering::Equal, true, floor)],
+            },
+            "^" => {
+                let ceiling = if major > 0 || partial.minor.is_none() {
+                    Partial::bump(major + 1, 0, 0)?
+                } else if minor > 0 || partial.patch.is_none() {
+                    Partial::bump(0, minor + 1, 0)?
+                } else {
+                    Partial::bump(0, 0, patch + 1)?
+                };
+                vec![ge, (Ordering::Less, false, ceiling)]
+            }
+            "~" => {
+                let ceiling = match partial.minor {
+                    None => Partial::bump(major + 1, 0, 0)?,
+                    Some(_) => Partial::bump(major, minor + 1, 0)?,
+                };
+                vec![ge, (Ordering::Less, false, ceiling)]
+            }
+            "~>" => {
+                // Pessimistic constraint: only the rightmost component may increase
+                let ceiling = match partial.specified() {
+                    1 | 2 => Partial::bump(major + 1, 0, 0)?,
+                    _ => Partial::bump(major, minor + 1, 0)?,
+                };
+                vec![ge, (Ordering::Less, false, ceiling)]
+            }
+            ">=" => vec![ge],
+            ">" => match partial.x_ceiling()? {
+                Some(ceiling) if !partial.is_full() => vec![(Ordering::Greater, true, ceiling)],
+                _ => vec![(Ordering::Greater, false, floor)],
+            },
+            "<" => vec![(Ordering::Less, false, floor)],
+            "<=" => match partial.x_ceiling()? {
+                Some(ceiling) if !partial.is_full() => vec![(Ordering::Less, false, ceiling)],
+                _ => vec![(Ordering::Less, true, floor)],
+            },
+            "!=" => vec![(Ordering::Equal, false, floor)],
+            other => return Err(anyhow!("Unsupported semver operator: {}", other)),
+        };
+        Ok(bounds)
+    }
+
+    /// Desugar one `||` alternative into primitive bounds
+    fn alternative_bounds(&self, alternative: &str) -> Result<Vec<Bound>> {
+        // Hyphen ranges: `1.2.3 - 2.3.4`
+        if let Some((low, high)) = alternative.split_once(" - ") {
+            let mut bounds = self.desugar(">=", low.trim())?;
+            bounds.extend(self.desugar("<=", high.trim())?);
+            return Ok(bounds);
+        }
+
+        let mut bounds = Vec::new();
+        for (op, target) in split_comparators(alternative, &SEMVER_OPERATORS) {
+            bounds.extend(self.desugar(&op, &target)?);
+        }
+        Ok(bounds)
+    }
+}
+
+/// Operators accepted in npm, Cargo and Terraform ranges
+const SEMVER_OPERATORS: [&str; 10] = ["~>", ">=", "<=", "!=", "==", ">", "<", "=", "^", "~"];
+
+impl VersionScheme for SemverScheme {
+    fn name(&self) -> &str {
+        "semver"
+    }
+
+    fn parse(&self, version: &str) -> Result<ParsedVersion> {
+        let parsed = Self::parse_version(version)?;
+        let mut normalized = format!("{}.{}.{}", parsed.major, parsed.minor, parsed.patch);
+        if let Some(pre) = &parsed.pre_release {
+            normalized.push('-');
+            normalized.push_str(pre);
+        }
+        Ok(ParsedVersion {
+            original: version.to_string(),
+            normalized,
+            is_prerelease: parsed.pre_release.is_some(),
+        })
+    }
+
+    fn compare(&self, a: &str, b: &str) -> Result<Ordering> {
+        Ok(Self::parse_version(a)?.cmp(&Self::parse_version(b)?))
+    }
+
+    fn satisfies(&self, version: &str, range: &str) -> Result<bool> {
+        let version = Self::parse_version(version)?;
+
+        for alternative in range.split("||") {
+            let bounds = self.alternative_bounds(alternative)?;
+            let ok = bounds.iter().all(|(ord, inclusive, target)| {
+                let actual = version.cmp(target);
+                match (ord, inclusive) {
+                    (Ordering::Equal, true) => actual == Ordering::Equal,
+                    (Ordering::Equal, false) => actual != Ordering::Equal,
+                    (ord, true) => actual == *ord || actual == Ordering::Equal,
+                    (ord, false) => actual == *ord,
+                }
+            });
+            if ok {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_semver_ranges() {
+        let npm = SemverScheme::new();
+        assert!(npm.satisfies("4.17.10", "< 4.17.12").unwrap());
+        assert!(npm.satisfies("1.2.9", "^1.2.3").unwrap());
+        assert!(!npm.satisfies("2.0.0", "^1.2.3").unwrap());
+        assert!(!npm.satisfies("0.3.0", "^0.2.3").unwrap());
+        assert!(npm.satisfies("1.2.7", "~1.2.3").unwrap());
+        assert!(npm.satisfies("1.9.0", "1.x || >=3.0.0").unwrap());
+        assert!(npm.satisfies("2.3.4", "1.2.3 - 2.3.4").unwrap());
+        assert!(!npm.satisfies("1.2.4", "1.2.3").unwrap());
+
+        // Terraform pessimistic constraints
+        assert!(npm.satisfies("5.31.0", "~> 5.0").unwrap());
+        assert!(!npm.satisfies("1.3.0", "~> 1.2.0").unwrap());
+
+        // Cargo treats bare versions as caret requirements
+        assert!(SemverScheme::cargo().satisfies("1.4.0", "1.2").unwrap());
+    }
+}
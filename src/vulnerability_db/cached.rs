@@ -0,0 +1,443 @@
+//! Vulnerability lookups shared between analyses
+//!
+//! Packages scanned together, or the same dependency set scanned again,
+//! mostly depend on the same few hundred libraries. A [`LookupCache`]
+//! remembers what a database answered for each package and version for
+//! `AnalysisOptions::vuln_cache_ttl_secs`, and [`CachedDb`] consults it before
+//! the database it wraps; lookups of one package that run concurrently wait
+//! for the first instead of repeating it. Failed lookups are not remembered.
+//!
+//! A cache can be backed by a JSON file, read when opened and written by
+//! [`LookupCache::save`], so answers also outlive the process.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::OnceCell;
+
+use crate::core::{DatabaseStatistics, UpdateResult, Vulnerability, VulnerabilityDatabase};
+use crate::versioning::PrereleasePolicy;
+
+/// Default time answers are kept, one hour
+pub const DEFAULT_TTL_SECS: u64 = 60 * 60;
+
+/// `(ecosystem, name, version, policy)` of a lookup
+type LookupKey = (String, String, String, Option<PrereleasePolicy>);
+
+/// Answer to each lookup, set once the first caller gets it
+type Lookups = HashMap<LookupKey, Arc<OnceCell<CachedLookup>>>;
+
+/// A database answer and when it was given
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedLookup {
+    fetched_at: DateTime<Utc>,
+    vulnerabilities: Vec<Vulnerability>,
+}
+
+/// A cached lookup as stored in a cache file
+#[derive(Serialize, Deserialize)]
+struct StoredLookup {
+    ecosystem: String,
+    name: String,
+    version: String,
+    #[serde(default)]
+    policy: Option<PrereleasePolicy>,
+    #[serde(flatten)]
+    lookup: CachedLookup,
+}
+
+/// Lookups answered from a [`LookupCache`] and passed on to the database
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Answers held, fresh or not
+    pub entries: usize,
+}
+
+/// Database answers kept for a time, shared by every [`CachedDb`] given it
+#[derive(Debug)]
+pub struct LookupCache {
+    lookups: Mutex<Lookups>,
+    ttl_secs: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    file: Option<PathBuf>,
+    /// Whether answers changed since the file was read or written
+    dirty: AtomicBool,
+}
+
+impl Default for LookupCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_TTL_SECS)
+    }
+}
+
+impl LookupCache {
+    /// In-memory cache keeping answers for `ttl_secs`; zero disables it
+    pub fn new(ttl_secs: u64) -> Self {
+        Self {
+            lookups: Mutex::new(HashMap::new()),
+            ttl_secs: AtomicU64::new(ttl_secs),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            file: None,
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// Cache backed by `file`, starting with the answers it holds
+    ///
+    /// A missing file starts the cache empty; it is created on the first
+    /// [`Self::save`].
+    pub fn open(file: impl AsRef<Path>, ttl_secs: u64) -> Result<Self> {
+        let file = file.as_ref();
+        let mut cache = Self::new(ttl_secs);
+        if file.exists() {
+            let content = std::fs::read_to_string(file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let stored: Vec<StoredLookup> = serde_json::from_str(&content)
+                .with_context(|| format!("Invalid vulnerability cache {}", file.display()))?;
+            let lookups = cache
+                .lookups
+                .get_mut()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            for entry in stored {
+                let key = (entry.ecosystem, entry.name, entry.version, entry.policy);
+                lookups.insert(key, Arc::new(OnceCell::new_with(Some(entry.lookup))));
+            }
+        }
+        cache.file = Some(file.to_path_buf());
+        Ok(cache)
+    }
+
+    /// Keep answers for `ttl_secs` from now on; zero disables the cache
+    pub fn set_ttl(&self, ttl_secs: u64) {
+        self.ttl_secs.store(ttl_secs, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self
+                .lookups()
+                .values()
+                .filter(|cell| cell.initialized())
+                .count(),
+        }
+    }
+
+    /// Forget every answer and reset the hit and miss counts
+    pub fn clear(&self) {
+        self.lookups().clear();
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+        self.dirty.store(self.file.is_some(), Ordering::Relaxed);
+    }
+
+    /// Write fresh answers to the cache file, if there is one and they changed
+    pub fn save(&self) -> Result<()> {
+        let Some(file) = &self.file else {
+            return Ok(());
+        };
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+        let now = Utc::now();
+        let stored: Vec<StoredLookup> = self
+            .lookups()
+            .iter()
+            .filter_map(|((ecosystem, name, version, policy), cell)| {
+                let lookup = cell.get().filter(|lookup| !self.expired(lookup, now))?;
+                Some(StoredLookup {
+                    ecosystem: ecosystem.clone(),
+                    name: name.clone(),
+                    version: version.clone(),
+                    policy: *policy,
+                    lookup: lookup.clone(),
+                })
+            })
+            .collect();
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(file, serde_json::to_vec(&stored)?)
+            .with_context(|| format!("Failed to write {}", file.display()))
+    }
+
+    /// `lookup`'s answer, from the cache while fresh
+    async fn get_or_fetch<F, Fut>(&self, key: LookupKey, lookup: F) -> Result<Vec<Vulnerability>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<Vec<Vulnerability>>>,
+    {
+        if self.ttl_secs.load(Ordering::Relaxed) == 0 {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return lookup().await;
+        }
+
+        let cell = {
+            let now = Utc::now();
+            let mut lookups = self.lookups();
+            let cell = lookups.entry(key).or_default();
+            if cell.get().is_some_and(|cached| self.expired(cached, now)) {
+                *cell = Arc::default();
+            }
+            cell.clone()
+        };
+        let mut fetched = false;
+        let cached = cell
+            .get_or_try_init(|| {
+                fetched = true;
+                async {
+                    Ok::<_, anyhow::Error>(CachedLookup {
+                        vulnerabilities: lookup().await?,
+                        fetched_at: Utc::now(),
+                    })
+                }
+            })
+            .await?;
+        if fetched {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            self.dirty.store(true, Ordering::Relaxed);
+        } else {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(cached.vulnerabilities.clone())
+    }
+
+    fn expired(&self, lookup: &CachedLookup, now: DateTime<Utc>) -> bool {
+        let ttl = self.ttl_secs.load(Ordering::Relaxed);
+        let ttl = Duration::seconds(i64::try_from(ttl).unwrap_or(i64::MAX / 1000));
+        lookup
+            .fetched_at
+            .checked_add_signed(ttl)
+            .is_some_and(|expiry| expiry <= now)
+    }
+
+    fn lookups(&self) -> MutexGuard<'_, Lookups> {
+        self.lookups
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Database that answers from a [`LookupCache`] first
+pub struct CachedDb {
+    inner: Arc<dyn VulnerabilityDatabase>,
+    cache: Arc<LookupCache>,
+}
+
+impl CachedDb {
+    pub fn new(inner: Arc<dyn VulnerabilityDatabase>, cache: Arc<LookupCache>) -> Self {
+        Self { inner, cache }
+    }
+
+    pub fn cache(&self) -> &Arc<LookupCache> {
+        &self.cache
+    }
+}
+
+#[async_trait]
+impl VulnerabilityDatabase for CachedDb {
+    async fn check_package(
+        &self,
+        package_name: &str,
+        version: &str,
+        package_type: &str,
+    ) -> Result<Vec<Vulnerability>> {
+        let key = (
+            package_type.to_string(),
+            package_name.to_string(),
+            version.to_string(),
+            None,
+        );
+        self.cache
+            .get_or_fetch(key, || {
+                self.inner
+                    .check_package(package_name, version, package_type)
+            })
+            .await
+    }
+
+    async fn check_package_with_policy(
+        &self,
+        package_name: &str,
+        version: &str,
+        package_type: &str,
+        policy: PrereleasePolicy,
+    ) -> Result<Vec<Vulnerability>> {
+        let key = (
+            package_type.to_string(),
+            package_name.to_string(),
+            version.to_string(),
+            Some(policy),
+        );
+        self.cache
+            .get_or_fetch(key, || {
+                self.inner
+                    .check_package_with_policy(package_name, version, package_type, policy)
+            })
+            .await
+    }
+
+    /// Updates the wrapped database when nothing else holds it, then clears
+    /// the cache
+    async fn update(&mut self) -> Result<UpdateResult> {
+        let inner = Arc::get_mut(&mut self.inner).ok_or_else(|| {
+            anyhow::anyhow!("the cached database is shared and cannot be updated")
+        })?;
+        let result = inner.update().await?;
+        self.cache.clear();
+        Ok(result)
+    }
+
+    fn last_updated(&self) -> Option<DateTime<Utc>> {
+        self.inner.last_updated()
+    }
+
+    fn statistics(&self) -> DatabaseStatistics {
+        self.inner.statistics()
+    }
+
+    async fn get_by_cve(&self, cve_id: &str) -> Result<Option<Vulnerability>> {
+        self.inner.get_by_cve(cve_id).await
+    }
+
+    async fn get_all_for_package(
+        &self,
+        package_name: &str,
+        package_type: &str,
+    ) -> Result<Vec<Vulnerability>> {
+        self.inner
+            .get_all_for_package(package_name, package_type)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    /// Counts the lookups that reach it
+    #[derive(Default)]
+    struct CountingDb {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl VulnerabilityDatabase for CountingDb {
+        async fn check_package(&self, _: &str, _: &str, _: &str) -> Result<Vec<Vulnerability>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            Ok(vec![])
+        }
+
+        async fn update(&mut self) -> Result<UpdateResult> {
+            unimplemented!()
+        }
+
+        fn last_updated(&self) -> Option<DateTime<Utc>> {
+            None
+        }
+
+        fn statistics(&self) -> DatabaseStatistics {
+            unimplemented!()
+        }
+
+        async fn get_by_cve(&self, _: &str) -> Result<Option<Vulnerability>> {
+            Ok(None)
+        }
+
+        async fn get_all_for_package(&self, _: &str, _: &str) -> Result<Vec<Vulnerability>> {
+            Ok(vec![])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lookups_deduplicated() {
+        let counting = Arc::new(CountingDb::default());
+        let cached = Arc::new(CachedDb::new(counting.clone(), Arc::default()));
+
+        let mut tasks = Vec::new();
+        for _ in 0..8 {
+            let cached = cached.clone();
+            tasks.push(tokio::spawn(async move {
+                cached.check_package("lodash", "4.17.20", "npm").await
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+        cached
+            .check_package("lodash", "4.17.21", "npm")
+            .await
+            .unwrap();
+
+        assert_eq!(counting.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            cached.cache().stats(),
+            CacheStats {
+                hits: 7,
+                misses: 2,
+                entries: 2
+            }
+        );
+
+        cached.cache().clear();
+        cached
+            .check_package("lodash", "4.17.21", "npm")
+            .await
+            .unwrap();
+        assert_eq!(counting.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_ttl_and_file() {
+        let counting = Arc::new(CountingDb::default());
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("vulns.json");
+
+        let cache = Arc::new(LookupCache::open(&file, 60).unwrap());
+        let cached = CachedDb::new(counting.clone(), cache.clone());
+        cached
+            .check_package("left-pad", "1.3.0", "npm")
+            .await
+            .unwrap();
+        cache.save().unwrap();
+
+        // Answers outlive the cache through its file
+        let cache = Arc::new(LookupCache::open(&file, 60).unwrap());
+        let cached = CachedDb::new(counting.clone(), cache.clone());
+        cached
+            .check_package("left-pad", "1.3.0", "npm")
+            .await
+            .unwrap();
+        assert_eq!(counting.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.stats().hits, 1);
+
+        // Until they expire
+        cache.set_ttl(1);
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        cached
+            .check_package("left-pad", "1.3.0", "npm")
+            .await
+            .unwrap();
+        assert_eq!(counting.calls.load(Ordering::SeqCst), 2);
+
+        // A zero TTL passes every lookup on
+        cache.set_ttl(0);
+        cached
+            .check_package("left-pad", "1.3.0", "npm")
+            .await
+            .unwrap();
+        assert_eq!(counting.calls.load(Ordering::SeqCst), 3);
+    }
+}
@@ -9,6 +9,8 @@ use std::path::{Path, PathBuf};
 use crate::core::{
     DatabaseStatistics, UpdateResult, Vulnerability, VulnerabilityDatabase, VulnerabilitySeverity,
 };
+use crate::utils::names::normalize_name;
+use crate::versioning::PrereleasePolicy;
 
 /// Java vulnerability database
 pub struct JavaVulnerabilityDb {
@@ -55,7 +57,7 @@ impl JavaVulnerabilityDb {
     /// Load embedded vulnerabilities
     fn load_embedded(&mut self) {
         // Log4j vulnerability
-        self.add_vulnerability("log4j", Vulnerability {
+        self.add_vulnerability("log4j-core", Vulnerability {
             id: "CVE-2021-44228".to_string(),
             title: "Log4Shell - Remote Code Execution in Log4j".to_string(),
             description: "Apache Log4j2 JNDI features used in configuration, log messages, and parameters do not protect against attacker controlled LDAP and other JNDI related endpoints".to_string(),
@@ -70,6 +72,10 @@ impl JavaVulnerabilityDb {
             cwe_ids: vec!["CWE-502".to_string(), "CWE-400".to_string()],
             exploit_available: true,
             patch_available: true,
+            source_severity: None,
+            affected_symbols: vec![],
+            likely_reachable: None,
+            aliases: vec![],
         });
 
         // Spring Framework vulnerability
@@ -88,12 +94,27 @@ impl JavaVulnerabilityDb {
             cwe_ids: vec!["CWE-94".to_string()],
             exploit_available: true,
             patch_available: true,
+            source_severity: None,
+            affected_symbols: vec![],
+            likely_reachable: None,
+            aliases: vec![],
         });
     }
 
+    /// Advisories for an artifact, named by its artifact ID or its
+    /// `groupId:artifactId` coordinate
+    fn entries(&self, package_name: &str) -> Vec<Vulnerability> {
+        let artifact_id = package_name.rsplit(':').next().unwrap_or(package_name);
+        self.cache
+            .get(&normalize_name(package_name, "java"))
+            .or_else(|| self.cache.get(&normalize_name(artifact_id, "java")))
+            .cloned()
+            .unwrap_or_default()
+    }
+
     fn add_vulnerability(&mut self, package: &str, vuln: Vulnerability) {
         self.cache
-            .entry(package.to_string())
+            .entry(normalize_name(package, "java"))
             .or_default()
             .push(vuln);
     }
@@ -104,14 +125,36 @@ impl VulnerabilityDatabase for JavaVulnerabilityDb {
     async fn check_package(
         &self,
         package_name: &str,
-        _version: &str,
+        version: &str,
+        package_type: &str,
+    ) -> Result<Vec<Vulnerability>> {
+        self.check_package_with_policy(
+            package_name,
+            version,
+            package_type,
+            PrereleasePolicy::for_ecosystem(package_type),
+        )
+        .await
+    }
+
+    async fn check_package_with_policy(
+        &self,
+        package_name: &str,
+        version: &str,
         package_type: &str,
+        policy: PrereleasePolicy,
     ) -> Result<Vec<Vulnerability>> {
-        if package_type != "java" {
+        if !matches!(package_type, "java" | "maven") {
             return Ok(vec![]);
         }
 
-        Ok(self.cache.get(package_name).cloned().unwrap_or_default())
+        let package_vulns = self.entries(package_name);
+        Ok(super::filter_affected(
+            package_vulns,
+            version,
+            "java",
+            policy,
+        ))
     }
 
     async fn update(&mut self) -> Result<UpdateResult> {
@@ -180,10 +223,10 @@ impl VulnerabilityDatabase for JavaVulnerabilityDb {
         package_name: &str,
         package_type: &str,
     ) -> Result<Vec<Vulnerability>> {
-        if package_type != "java" {
+        if !matches!(package_type, "java" | "maven") {
             return Ok(vec![]);
         }
 
-        Ok(self.cache.get(package_name).cloned().unwrap_or_default())
+        Ok(self.entries(package_name))
     }
 }
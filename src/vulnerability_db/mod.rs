@@ -1,14 +1,62 @@
 //! Vulnerability database implementation
 
+pub mod cached;
 pub mod java_db;
 pub mod npm_db;
+#[cfg(feature = "osv")]
+pub mod osv;
+pub mod osv_dump;
+mod osv_record;
 pub mod python_db;
+pub mod severity;
+pub mod snapshot;
+pub mod terraform_db;
 pub mod updater;
 
 use anyhow::Result;
 use std::path::Path;
+use std::sync::Arc;
+
+use crate::core::{AnalysisOptions, Vulnerability};
+use crate::versioning::{PrereleasePolicy, VersionSchemeRegistry};
 
 pub use crate::core::VulnerabilityDatabase;
+pub use cached::{CacheStats, CachedDb, LookupCache};
+#[cfg(feature = "osv")]
+pub use osv::{OsvClient, OsvLookup};
+pub use osv_dump::OsvDumpDb;
+pub use severity::SeverityMapper;
+pub use snapshot::{
+    DatabaseLoadError, DatabaseLoader, DatabaseMetadata, DatabaseSource, SnapshotDb,
+    VulnerabilitySnapshot,
+};
+
+/// Database `options` puts in place of an analyzer's own
+///
+/// `Some` when [`AnalysisOptions::offline_vuln_db`] names an OSV dump, which is
+/// read on first use and shared by later analyses.
+pub(crate) fn database_override(
+    options: &AnalysisOptions,
+) -> Result<Option<Arc<dyn VulnerabilityDatabase>>> {
+    match &options.offline_vuln_db {
+        Some(dir) => Ok(Some(OsvDumpDb::shared(dir)?)),
+        None => Ok(None),
+    }
+}
+
+/// Keep only vulnerabilities affecting `version` under the ecosystem's version scheme
+pub(crate) fn filter_affected(
+    vulns: Vec<Vulnerability>,
+    version: &str,
+    ecosystem: &str,
+    policy: PrereleasePolicy,
+) -> Vec<Vulnerability> {
+    let scheme = VersionSchemeRegistry::global().get_or_default(ecosystem);
+    vulns
+        .into_iter()
+        .filter(|v| v.affects_version_with(version, scheme.as_ref(), policy))
+        .collect()
+}
 
 /// Create NPM vulnerability database
 pub fn create_npm_database() -> Result<Box<dyn VulnerabilityDatabase>> {
@@ -39,3 +87,15 @@ pub fn create_java_database() -> Result<Box<dyn VulnerabilityDatabase>> {
 pub fn create_java_database_with_path(path: &Path) -> Result<Box<dyn VulnerabilityDatabase>> {
     Ok(Box::new(java_db::JavaVulnerabilityDb::with_path(path)?))
 }
+
+/// Create Terraform vulnerability database
+pub fn create_terraform_database() -> Result<Box<dyn VulnerabilityDatabase>> {
+    Ok(Box::new(terraform_db::TerraformVulnerabilityDb::new()?))
+}
+
+/// Create Terraform vulnerability database with custom path
+pub fn create_terraform_database_with_path(path: &Path) -> Result<Box<dyn VulnerabilityDatabase>> {
+    Ok(Box::new(terraform_db::TerraformVulnerabilityDb::with_path(
+        path,
+    )?))
+}
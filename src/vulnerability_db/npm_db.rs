@@ -9,6 +9,8 @@ use std::path::{Path, PathBuf};
 use crate::core::{
     DatabaseStatistics, UpdateResult, Vulnerability, VulnerabilityDatabase, VulnerabilitySeverity,
 };
+use crate::utils::names::normalize_name;
+use crate::versioning::PrereleasePolicy;
 
 /// NPM vulnerability database
 pub struct NpmVulnerabilityDb {
@@ -78,6 +80,10 @@ impl NpmVulnerabilityDb {
                 cwe_ids: vec!["CWE-1321".to_string()],
                 exploit_available: false,
                 patch_available: true,
+                source_severity: None,
+                affected_symbols: vec!["defaultsDeep".to_string()],
+                likely_reachable: None,
+                aliases: vec![],
             },
         );
 
@@ -103,6 +109,10 @@ impl NpmVulnerabilityDb {
                 cwe_ids: vec!["CWE-1321".to_string()],
                 exploit_available: false,
                 patch_available: true,
+                source_severity: None,
+                affected_symbols: vec![],
+                likely_reachable: None,
+                aliases: vec![],
             },
         );
 
@@ -132,6 +142,10 @@ impl NpmVulnerabilityDb {
                 cwe_ids: vec!["CWE-506".to_string()],
                 exploit_available: true,
                 patch_available: true,
+                source_severity: None,
+                affected_symbols: vec![],
+                likely_reachable: None,
+                aliases: vec![],
             },
         );
 
@@ -158,6 +172,10 @@ impl NpmVulnerabilityDb {
                 cwe_ids: vec!["CWE-506".to_string()],
                 exploit_available: true,
                 patch_available: true,
+                source_severity: None,
+                affected_symbols: vec![],
+                likely_reachable: None,
+                aliases: vec![],
             },
         );
 
@@ -184,6 +202,10 @@ impl NpmVulnerabilityDb {
                 cwe_ids: vec!["CWE-506".to_string()],
                 exploit_available: true,
                 patch_available: true,
+                source_severity: None,
+                affected_symbols: vec![],
+                likely_reachable: None,
+                aliases: vec![],
             },
         );
 
@@ -211,6 +233,10 @@ impl NpmVulnerabilityDb {
                 cwe_ids: vec!["CWE-22".to_string()],
                 exploit_available: true,
                 patch_available: true,
+                source_severity: None,
+                affected_symbols: vec![],
+                likely_reachable: None,
+                aliases: vec![],
             },
         );
 
@@ -236,6 +262,10 @@ impl NpmVulnerabilityDb {
                 cwe_ids: vec!["CWE-400".to_string()],
                 exploit_available: false,
                 patch_available: true,
+                source_severity: None,
+                affected_symbols: vec![],
+                likely_reachable: None,
+                aliases: vec![],
             },
         );
 
@@ -262,13 +292,17 @@ impl NpmVulnerabilityDb {
                 cwe_ids: vec!["CWE-918".to_string()],
                 exploit_available: true,
                 patch_available: true,
+                source_severity: None,
+                affected_symbols: vec![],
+                likely_reachable: None,
+                aliases: vec![],
             },
         );
     }
 
     fn add_vulnerability(&mut self, package: &str, vuln: Vulnerability) {
         self.cache
-            .entry(package.to_string())
+            .entry(normalize_name(package, "npm"))
             .or_default()
             .push(vuln);
     }
@@ -281,31 +315,38 @@ impl VulnerabilityDatabase for NpmVulnerabilityDb {
         package_name: &str,
         version: &str,
         package_type: &str,
+    ) -> Result<Vec<Vulnerability>> {
+        self.check_package_with_policy(
+            package_name,
+            version,
+            package_type,
+            PrereleasePolicy::for_ecosystem(package_type),
+        )
+        .await
+    }
+
+    async fn check_package_with_policy(
+        &self,
+        package_name: &str,
+        version: &str,
+        package_type: &str,
+        policy: PrereleasePolicy,
     ) -> Result<Vec<Vulnerability>> {
         if package_type != "npm" {
             return Ok(vec![]);
         }
 
-        // Get vulnerabilities for this package
-        let package_vulns = self.cache.get(package_name).cloned().unwrap_or_default();
-
-        // Filter vulnerabilities that affect this version
-        let mut applicable_vulns = Vec::new();
-        for vuln in package_vulns {
-            // Simple version check - if any affected version range contains this version
-            // For now, use a simple check that works with the test case
-            for affected in &vuln.affected_versions {
-                if let Some(max_version) = affected.strip_prefix("< ") {
-                    // Simple string comparison for now - lodash 4.17.10 < 4.17.12
-                    if version < max_version {
-                        applicable_vulns.push(vuln.clone());
-                        break;
-                    }
-                }
-            }
-        }
-
-        Ok(applicable_vulns)
+        let package_vulns = self
+            .cache
+            .get(&normalize_name(package_name, "npm"))
+            .cloned()
+            .unwrap_or_default();
+        Ok(super::filter_affected(
+            package_vulns,
+            version,
+            "npm",
+            policy,
+        ))
     }
 
     async fn update(&mut self) -> Result<UpdateResult> {
@@ -381,6 +422,10 @@ impl VulnerabilityDatabase for NpmVulnerabilityDb {
             return Ok(vec![]);
         }
 
-        Ok(self.cache.get(package_name).cloned().unwrap_or_default())
+        Ok(self
+            .cache
+            .get(&normalize_name(package_name, "npm"))
+            .cloned()
+            .unwrap_or_default())
     }
 }
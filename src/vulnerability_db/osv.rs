@@ -0,0 +1,368 @@
+//! Live vulnerability lookups against the OSV.dev API
+//!
+//! Available with the `osv` feature, so builds that must stay offline never
+//! make these requests. Packages are looked up in batches; each advisory
+//! found is then fetched once for its details. When the API cannot be
+//! reached, lookups log a warning and report no advisories instead of
+//! failing the analysis.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::core::{DatabaseStatistics, UpdateResult, Vulnerability, VulnerabilityDatabase};
+use crate::ecosystem::Ecosystem;
+
+use super::osv_record::{osv_version, OsvRecord};
+use super::SeverityMapper;
+
+/// Public OSV.dev API
+pub const OSV_API: &str = "https://api.osv.dev";
+
+/// How long one request may take
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Queries per batch request, the most the API accepts
+const BATCH_SIZE: usize = 1000;
+
+/// Outcome of looking packages up
+#[derive(Debug, Clone, Default)]
+pub struct OsvLookup {
+    /// Advisories affecting each queried package, in query order
+    pub vulnerabilities: Vec<Vec<Vulnerability>>,
+    /// Why the lookup did not complete; every package then has no advisories
+    pub warning: Option<String>,
+}
+
+/// Client for the OSV.dev vulnerability API
+pub struct OsvClient {
+    client: reqwest::Client,
+    api: String,
+    severity_mapper: SeverityMapper,
+}
+
+impl OsvClient {
+    /// Create a client for the public API
+    pub fn new() -> Result<Self> {
+        Self::with_api(OSV_API)
+    }
+
+    /// Create a client for a mirror of the API
+    pub fn with_api(api: &str) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .user_agent(concat!(
+                "threatflux-package-security/",
+                env!("CARGO_PKG_VERSION")
+            ))
+            .build()?;
+        Ok(Self {
+            client,
+            api: api.trim_end_matches('/').to_string(),
+            severity_mapper: SeverityMapper::new(),
+        })
+    }
+
+    /// Map OSV and GHSA severity ratings with `mapper`
+    pub fn with_severity_mapper(mut self, mapper: SeverityMapper) -> Self {
+        self.severity_mapper = mapper;
+        self
+    }
+
+    /// Advisories affecting each `(ecosystem, name, version)`
+    ///
+    /// Ecosystems are named as elsewhere in the crate (`npm`, `pypi`,
+    /// `cargo`, `go`, `maven`); packages of ecosystems OSV does not cover get
+    /// no advisories. An unreachable API is logged and reported in
+    /// [`OsvLookup::warning`].
+    pub async fn query(&self, packages: &[(&str, &str, &str)]) -> OsvLookup {
+        let unchecked = || vec![Vec::new(); packages.len()];
+        if cfg!(feature = "offline") {
+            return OsvLookup {
+                vulnerabilities: unchecked(),
+                warning: Some("OSV lookups are unavailable in an offline build".to_string()),
+            };
+        }
+        match self.try_query(packages).await {
+            Ok(vulnerabilities) => OsvLookup {
+                vulnerabilities,
+                warning: None,
+            },
+            Err(error) => {
+                let warning = format!("OSV lookup failed: {:#}", error);
+                tracing::warn!("{}", warning);
+                OsvLookup {
+                    vulnerabilities: unchecked(),
+                    warning: Some(warning),
+                }
+            }
+        }
+    }
+
+    async fn try_query(&self, packages: &[(&str, &str, &str)]) -> Result<Vec<Vec<Vulnerability>>> {
+        let mut pending: Vec<(usize, Value)> = packages
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (ecosystem, name, version))| {
+                let ecosystem = osv_ecosystem(ecosystem)?;
+                Some((
+                    index,
+                    json!({
+                        "package": { "name": name, "ecosystem": ecosystem },
+                        "version": osv_version(ecosystem, version),
+                    }),
+                ))
+            })
+            .collect();
+
+        // A result with more advisories than fit one page carries a token for the next
+        let mut ids: Vec<Vec<String>> = vec![Vec::new(); packages.len()];
+        while !pending.is_empty() {
+            let mut next = Vec::new();
+            for chunk in pending.chunks(BATCH_SIZE) {
+                let queries: Vec<&Value> = chunk.iter().map(|(_, query)| query).collect();
+                let response: BatchResponse = self
+                    .client
+                    .post(format!("{}/v1/querybatch", self.api))
+                    .json(&json!({ "queries": queries }))
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+                for ((index, query), result) in chunk.iter().zip(response.results) {
+                    ids[*index].extend(result.vulns.into_iter().map(|vuln| vuln.id));
+                    if let Some(token) = result.next_page_token {
+                        let mut query = query.clone();
+                        query["page_token"] = json!(token);
+                        next.push((*index, query));
+                    }
+                }
+            }
+            pending = next;
+        }
+
+        let mut records: HashMap<String, OsvRecord> = HashMap::new();
+        for id in ids.iter().flatten() {
+            if !records.contains_key(id) {
+                let record = self
+                    .fetch_record(id)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("advisory {} is listed but not served", id))?;
+                records.insert(id.clone(), record);
+            }
+        }
+        Ok(ids
+            .iter()
+            .zip(packages)
+            .map(|(ids, (_, name, _))| {
+                ids.iter()
+                    .map(|id| self.to_vulnerability(&records[id], Some(name)))
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Fetch one advisory, `None` when the API does not know it
+    async fn fetch_record(&self, id: &str) -> Result<Option<OsvRecord>> {
+        let response = self
+            .client
+            .get(format!("{}/v1/vulns/{}", self.api, id))
+            .send()
+            .await?;
+        if response.status().as_u16() == 404 {
+            return Ok(None);
+        }
+        Ok(Some(response.error_for_status()?.json().await?))
+    }
+
+    /// Every advisory for a package, whatever the version
+    async fn fetch_package(&self, ecosystem: &str, name: &str) -> Result<Vec<OsvRecord>> {
+        let mut records = Vec::new();
+        let mut query = json!({ "package": { "name": name, "ecosystem": ecosystem } });
+        loop {
+            let response: QueryResponse = self
+                .client
+                .post(format!("{}/v1/query", self.api))
+                .json(&query)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            records.extend(response.vulns);
+            match response.next_page_token {
+                Some(token) => query["page_token"] = json!(token),
+                None => return Ok(records),
+            }
+        }
+    }
+
+    /// Convert an OSV record with this client's severity mapping
+    fn to_vulnerability(&self, record: &OsvRecord, package: Option<&str>) -> Vulnerability {
+        record.to_vulnerability(package, &self.severity_mapper)
+    }
+}
+
+#[async_trait]
+impl VulnerabilityDatabase for OsvClient {
+    async fn check_package(
+        &self,
+        package_name: &str,
+        version: &str,
+        package_type: &str,
+    ) -> Result<Vec<Vulnerability>> {
+        let mut lookup = self.query(&[(package_type, package_name, version)]).await;
+        Ok(lookup.vulnerabilities.pop().unwrap_or_default())
+    }
+
+    /// Nothing to update; every lookup asks the API
+    async fn update(&mut self) -> Result<UpdateResult> {
+        Ok(UpdateResult {
+            success: true,
+            new_vulnerabilities: 0,
+            updated_vulnerabilities: 0,
+            removed_vulnerabilities: 0,
+            duration_seconds: 0,
+            errors: vec![],
+        })
+    }
+
+    fn last_updated(&self) -> Option<DateTime<Utc>> {
+        None
+    }
+
+    fn statistics(&self) -> DatabaseStatistics {
+        DatabaseStatistics {
+            total_vulnerabilities: 0,
+            packages_covered: 0,
+            last_updated: None,
+            database_version: "osv.dev".to_string(),
+            vulnerabilities_by_severity: HashMap::new(),
+            vulnerabilities_by_year: HashMap::new(),
+        }
+    }
+
+    async fn get_by_cve(&self, cve_id: &str) -> Result<Option<Vulnerability>> {
+        if cfg!(feature = "offline") {
+            return Ok(None);
+        }
+        match self.fetch_record(cve_id).await {
+            Ok(record) => Ok(record.map(|record| self.to_vulnerability(&record, None))),
+            Err(error) => {
+                tracing::warn!("OSV lookup of {} failed: {:#}", cve_id, error);
+                Ok(None)
+            }
+        }
+    }
+
+    async fn get_all_for_package(
+        &self,
+        package_name: &str,
+        package_type: &str,
+    ) -> Result<Vec<Vulnerability>> {
+        let Some(ecosystem) = osv_ecosystem(package_type).filter(|_| !cfg!(feature = "offline"))
+        else {
+            return Ok(vec![]);
+        };
+        match self.fetch_package(ecosystem, package_name).await {
+            Ok(records) => Ok(records
+                .iter()
+                .map(|record| self.to_vulnerability(record, Some(package_name)))
+                .collect()),
+            Err(error) => {
+                tracing::warn!("OSV lookup of {} failed: {:#}", package_name, error);
+                Ok(vec![])
+            }
+        }
+    }
+}
+
+/// OSV's name for an ecosystem, `None` for ecosystems it does not cover
+fn osv_ecosystem(package_type: &str) -> Option<&'static str> {
+    match Ecosystem::from_name(package_type) {
+        Ecosystem::Npm | Ecosystem::Extension => Some("npm"),
+        Ecosystem::Python => Some("PyPI"),
+        Ecosystem::Java | Ecosystem::Maven => Some("Maven"),
+        Ecosystem::Cargo => Some("crates.io"),
+        Ecosystem::Go => Some("Go"),
+        Ecosystem::Ruby => Some("RubyGems"),
+        Ecosystem::Composer => Some("Packagist"),
+        Ecosystem::Terraform | Ecosystem::Helm | Ecosystem::Unknown => None,
+    }
+}
+
+#[derive(Deserialize)]
+struct BatchResponse {
+    #[serde(default)]
+    results: Vec<BatchResult>,
+}
+
+#[derive(Deserialize)]
+struct BatchResult {
+    #[serde(default)]
+    vulns: Vec<BatchVuln>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct BatchVuln {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct QueryResponse {
+    #[serde(default)]
+    vulns: Vec<OsvRecord>,
+    next_page_token: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::VulnerabilitySeverity;
+
+    #[test]
+    fn test_record_conversion() {
+        let record: OsvRecord = serde_json::from_value(json!({
+            "id": "GHSA-p6mc-m468-83gw",
+            "summary": "Prototype Pollution in lodash",
+            "details": "Versions of lodash prior to 4.17.19 are vulnerable to prototype pollution.",
+            "aliases": ["CVE-2020-8203"],
+            "modified": "2024-02-16T08:00:00Z",
+            "severity": [{"type": "CVSS_V3", "score": "CVSS:3.1/AV:N/AC:H/PR:N/UI:N/S:U/C:N/I:H/A:H"}],
+            "affected": [{
+                "package": {"ecosystem": "npm", "name": "lodash"},
+                "ranges": [{"type": "SEMVER", "events": [
+                    {"introduced": "3.7.0"}, {"fixed": "4.17.19"}
+                ]}]
+            }, {
+                "package": {"ecosystem": "npm", "name": "lodash-es"},
+                "ranges": [{"type": "SEMVER", "events": [{"introduced": "0"}, {"fixed": "4.17.20"}]}]
+            }],
+            "references": [{"type": "ADVISORY", "url": "https://nvd.nist.gov/vuln/detail/CVE-2020-8203"}],
+            "database_specific": {"cwe_ids": ["CWE-1321"], "severity": "HIGH"}
+        }))
+        .unwrap();
+
+        let vuln = OsvClient::new()
+            .unwrap()
+            .to_vulnerability(&record, Some("lodash"));
+        assert_eq!(vuln.advisory_id(), "GHSA-p6mc-m468-83gw");
+        assert_eq!(vuln.cve_id(), "CVE-2020-8203");
+        assert_eq!(vuln.severity_score(), 7.4);
+        assert_eq!(vuln.severity, VulnerabilitySeverity::High);
+        assert_eq!(vuln.affected_versions, [">= 3.7.0, < 4.17.19"]);
+        assert_eq!(vuln.fixed_versions, ["4.17.19"]);
+        assert_eq!(vuln.cwe_ids, ["CWE-1321"]);
+
+        assert_eq!(osv_ecosystem("cargo"), Some("crates.io"));
+        assert_eq!(osv_ecosystem("maven"), Some("Maven"));
+        assert_eq!(osv_version("go", "v1.4.0"), "1.4.0");
+        assert_eq!(osv_ecosystem("helm"), None);
+    }
+}
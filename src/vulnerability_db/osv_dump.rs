@@ -0,0 +1,289 @@
+//! Offline vulnerability database read from a local OSV dump
+//!
+//! OSV publishes every advisory of an ecosystem as one JSON file per
+//! advisory (the `all.zip` archive of each ecosystem). Unpacked into a
+//! directory, a dump is read once, indexed by ecosystem and package name, and
+//! answers every lookup without network access. Versions are matched with
+//! OSV's own range rules rather than the range strings other databases use,
+//! so `SEMVER` and `ECOSYSTEM` ranges mean exactly what the advisory says.
+//!
+//! The dump's date is that of its most recently modified advisory; results
+//! analyzed against it record that date in their scan context.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Utc};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::core::{DatabaseStatistics, UpdateResult, Vulnerability, VulnerabilityDatabase};
+use crate::utils::names::normalize_name;
+use crate::versioning::{PrereleasePolicy, SemverScheme, VersionSchemeRegistry};
+
+use super::osv_record::{osv_version, OsvAffected, OsvRecord};
+use super::snapshot::canonical_ecosystem;
+use super::SeverityMapper;
+
+/// Dumps already read in this process, by directory
+static LOADED: Lazy<Mutex<HashMap<PathBuf, Arc<OsvDumpDb>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// An advisory as it applies to one package
+struct DumpAdvisory {
+    vulnerability: Vulnerability,
+    /// The advisory's `affected` entries for the package
+    affected: Vec<OsvAffected>,
+}
+
+/// Vulnerability database read from a directory of OSV advisories
+pub struct OsvDumpDb {
+    root: PathBuf,
+    /// Advisories by canonical ecosystem, then normalized package name
+    packages: HashMap<&'static str, HashMap<String, Vec<DumpAdvisory>>>,
+    advisory_count: usize,
+    snapshot_date: Option<DateTime<Utc>>,
+}
+
+impl OsvDumpDb {
+    /// Read every `.json` advisory under `dir`
+    ///
+    /// Fails when a file is not an OSV advisory or the directory holds none,
+    /// so a broken dump never passes for a clean result. Withdrawn advisories
+    /// and packages of ecosystems the crate does not analyze are skipped.
+    pub fn load(dir: &Path) -> Result<Self> {
+        Self::load_with_severity_mapper(dir, &SeverityMapper::new())
+    }
+
+    /// Read a dump, mapping OSV and GHSA severity ratings with `mapper`
+    pub fn load_with_severity_mapper(dir: &Path, mapper: &SeverityMapper) -> Result<Self> {
+        let mut files = Vec::new();
+        collect_json_files(dir, &mut files)
+            .with_context(|| format!("cannot read OSV dump {}", dir.display()))?;
+        if files.is_empty() {
+            anyhow::bail!("OSV dump {} holds no advisories", dir.display());
+        }
+        files.sort();
+
+        let mut db = Self {
+            root: dir.to_path_buf(),
+            packages: HashMap::new(),
+            advisory_count: 0,
+            snapshot_date: None,
+        };
+        for file in files {
+            let content = std::fs::read(&file)
+                .with_context(|| format!("cannot read OSV advisory {}", file.display()))?;
+            let record: OsvRecord = serde_json::from_slice(&content)
+                .with_context(|| format!("{} is not an OSV advisory", file.display()))?;
+            db.insert(record, mapper);
+        }
+        Ok(db)
+    }
+
+    /// The dump in `dir`, read on first use and shared afterwards
+    ///
+    /// Changes to the directory are not seen until the process restarts.
+    pub fn shared(dir: &Path) -> Result<Arc<Self>> {
+        let mut loaded = LOADED
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(db) = loaded.get(dir) {
+            return Ok(db.clone());
+        }
+        let db = Arc::new(Self::load(dir)?);
+        loaded.insert(dir.to_path_buf(), db.clone());
+        Ok(db)
+    }
+
+    /// Directory the dump was read from
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn insert(&mut self, record: OsvRecord, mapper: &SeverityMapper) {
+        if record.withdrawn.is_some() {
+            return;
+        }
+        // One advisory may cover several packages, each with several entries
+        let mut by_package: HashMap<(&'static str, String), (String, Vec<OsvAffected>)> =
+            HashMap::new();
+        for entry in &record.affected {
+            let Some(package) = &entry.package else {
+                continue;
+            };
+            let Some(ecosystem) = canonical_ecosystem(&package.ecosystem) else {
+                continue;
+            };
+            by_package
+                .entry((ecosystem, normalize_name(&package.name, ecosystem)))
+                .or_insert_with(|| (package.name.clone(), Vec::new()))
+                .1
+                .push(entry.clone());
+        }
+        if by_package.is_empty() {
+            return;
+        }
+
+        self.advisory_count += 1;
+        if record.modified > self.snapshot_date {
+            self.snapshot_date = record.modified;
+        }
+        for ((ecosystem, name), (package, affected)) in by_package {
+            self.packages
+                .entry(ecosystem)
+                .or_default()
+                .entry(name)
+                .or_default()
+                .push(DumpAdvisory {
+                    vulnerability: record.to_vulnerability(Some(&package), mapper),
+                    affected,
+                });
+        }
+    }
+
+    fn advisories(&self, package_name: &str, package_type: &str) -> &[DumpAdvisory] {
+        canonical_ecosystem(package_type)
+            .and_then(|ecosystem| {
+                self.packages
+                    .get(ecosystem)?
+                    .get(&normalize_name(package_name, ecosystem))
+            })
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Gather the `.json` files under `dir`, descending into subdirectories
+fn collect_json_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_json_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl VulnerabilityDatabase for OsvDumpDb {
+    async fn check_package(
+        &self,
+        package_name: &str,
+        version: &str,
+        package_type: &str,
+    ) -> Result<Vec<Vulnerability>> {
+        self.check_package_with_policy(
+            package_name,
+            version,
+            package_type,
+            PrereleasePolicy::for_ecosystem(package_type),
+        )
+        .await
+    }
+
+    /// A concrete version is matched with OSV's range rules, which order
+    /// pre-releases themselves; a version range, as declared without a lock
+    /// file, falls back to the advisories' range strings and `policy`.
+    async fn check_package_with_policy(
+        &self,
+        package_name: &str,
+        version: &str,
+        package_type: &str,
+        policy: PrereleasePolicy,
+    ) -> Result<Vec<Vulnerability>> {
+        let Some(ecosystem) = canonical_ecosystem(package_type) else {
+            return Ok(vec![]);
+        };
+        let version = osv_version(package_type, version);
+        let semver = SemverScheme::new();
+        let scheme = VersionSchemeRegistry::global().get_or_default(ecosystem);
+        if scheme.parse(version).is_err() {
+            let all = self.get_all_for_package(package_name, package_type).await?;
+            return Ok(super::filter_affected(all, version, ecosystem, policy));
+        }
+        Ok(self
+            .advisories(package_name, package_type)
+            .iter()
+            .filter(|advisory| {
+                advisory
+                    .affected
+                    .iter()
+                    .any(|entry| entry.affects(version, &semver, scheme.as_ref()))
+            })
+            .map(|advisory| advisory.vulnerability.clone())
+            .collect())
+    }
+
+    /// A dump is fixed once read; unpack a newer one to update
+    async fn update(&mut self) -> Result<UpdateResult> {
+        Ok(UpdateResult {
+            success: true,
+            new_vulnerabilities: 0,
+            updated_vulnerabilities: 0,
+            removed_vulnerabilities: 0,
+            duration_seconds: 0,
+            errors: vec![],
+        })
+    }
+
+    fn last_updated(&self) -> Option<DateTime<Utc>> {
+        self.snapshot_date
+    }
+
+    fn statistics(&self) -> DatabaseStatistics {
+        let mut stats = DatabaseStatistics {
+            total_vulnerabilities: self.advisory_count,
+            packages_covered: self.packages.values().map(HashMap::len).sum(),
+            last_updated: self.snapshot_date,
+            database_version: format!("osv dump {}", self.root.display()),
+            vulnerabilities_by_severity: HashMap::new(),
+            vulnerabilities_by_year: HashMap::new(),
+        };
+        let vulns = self
+            .packages
+            .values()
+            .flat_map(HashMap::values)
+            .flatten()
+            .map(|advisory| &advisory.vulnerability);
+        for vuln in vulns {
+            *stats
+                .vulnerabilities_by_severity
+                .entry(vuln.severity.clone())
+                .or_insert(0) += 1;
+            if let Some(date) = vuln.published_date {
+                *stats
+                    .vulnerabilities_by_year
+                    .entry(date.year())
+                    .or_insert(0) += 1;
+            }
+        }
+        stats
+    }
+
+    /// Look an advisory up by its OSV ID or any of its aliases
+    async fn get_by_cve(&self, cve_id: &str) -> Result<Option<Vulnerability>> {
+        Ok(self
+            .packages
+            .values()
+            .flat_map(HashMap::values)
+            .flatten()
+            .map(|advisory| &advisory.vulnerability)
+            .find(|vuln| vuln.id == cve_id || vuln.aliases.iter().any(|alias| alias == cve_id))
+            .cloned())
+    }
+
+    async fn get_all_for_package(
+        &self,
+        package_name: &str,
+        package_type: &str,
+    ) -> Result<Vec<Vulnerability>> {
+        Ok(self
+            .advisories(package_name, package_type)
+            .iter()
+            .map(|advisory| advisory.vulnerability.clone())
+            .collect())
+    }
+}
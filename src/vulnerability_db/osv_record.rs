@@ -0,0 +1,318 @@
+//! Advisories in the OSV schema, shared by the OSV.dev client and local OSV dumps
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+
+use crate::core::{Vulnerability, VulnerabilitySeverity};
+use crate::ecosystem::Ecosystem;
+use crate::versioning::VersionScheme;
+
+use super::SeverityMapper;
+
+/// Version as OSV records it; Go versions drop their leading `v`
+pub(crate) fn osv_version<'a>(package_type: &str, version: &'a str) -> &'a str {
+    match Ecosystem::from_name(package_type) {
+        Ecosystem::Go => version.strip_prefix('v').unwrap_or(version),
+        _ => version,
+    }
+}
+
+/// An advisory in the OSV schema, reduced to the fields used here
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct OsvRecord {
+    pub(crate) id: String,
+    pub(crate) summary: Option<String>,
+    pub(crate) details: Option<String>,
+    #[serde(default)]
+    pub(crate) aliases: Vec<String>,
+    pub(crate) published: Option<DateTime<Utc>>,
+    pub(crate) modified: Option<DateTime<Utc>>,
+    pub(crate) withdrawn: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub(crate) severity: Vec<OsvSeverity>,
+    #[serde(default)]
+    pub(crate) affected: Vec<OsvAffected>,
+    #[serde(default)]
+    pub(crate) references: Vec<OsvReference>,
+    #[serde(default)]
+    pub(crate) database_specific: serde_json::Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct OsvSeverity {
+    #[serde(rename = "type")]
+    pub(crate) kind: String,
+    pub(crate) score: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct OsvAffected {
+    pub(crate) package: Option<OsvPackage>,
+    #[serde(default)]
+    pub(crate) ranges: Vec<OsvRange>,
+    #[serde(default)]
+    pub(crate) versions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct OsvPackage {
+    #[serde(default)]
+    pub(crate) ecosystem: String,
+    pub(crate) name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct OsvRange {
+    #[serde(rename = "type")]
+    pub(crate) kind: String,
+    #[serde(default)]
+    pub(crate) events: Vec<OsvEvent>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct OsvEvent {
+    pub(crate) introduced: Option<String>,
+    pub(crate) fixed: Option<String>,
+    pub(crate) last_affected: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct OsvReference {
+    pub(crate) url: String,
+}
+
+impl OsvRecord {
+    /// Convert the record, taking affected ranges from the entries for `package`
+    ///
+    /// The ID is OSV's, such as a GHSA ID, with the CVE among the aliases.
+    /// Severity comes from the CVSS vector when there is one, otherwise from
+    /// the GHSA rating in `database_specific`.
+    pub(crate) fn to_vulnerability(
+        &self,
+        package: Option<&str>,
+        severity_mapper: &SeverityMapper,
+    ) -> Vulnerability {
+        let affected: Vec<&OsvAffected> = self
+            .affected
+            .iter()
+            .filter(|entry| {
+                package
+                    .is_none_or(|package| entry.package.as_ref().is_none_or(|p| p.name == package))
+            })
+            .collect();
+        let mut affected_versions = Vec::new();
+        let mut fixed_versions = Vec::new();
+        for entry in &affected {
+            let ranges: Vec<&OsvRange> = entry
+                .ranges
+                .iter()
+                .filter(|range| range.kind != "GIT")
+                .collect();
+            if ranges.is_empty() {
+                affected_versions.extend(entry.versions.iter().cloned());
+            }
+            for range in ranges {
+                let mut introduced: Option<&str> = None;
+                for event in &range.events {
+                    if let Some(version) = &event.introduced {
+                        introduced = Some(version);
+                    }
+                    let upper = match (&event.fixed, &event.last_affected) {
+                        (Some(fixed), _) => {
+                            fixed_versions.push(fixed.clone());
+                            format!("< {}", fixed)
+                        }
+                        (None, Some(last)) => format!("<= {}", last),
+                        (None, None) => continue,
+                    };
+                    affected_versions.push(match introduced.take() {
+                        Some("0") | None => upper,
+                        Some(lower) => format!(">= {}, {}", lower, upper),
+                    });
+                }
+                if let Some(lower) = introduced {
+                    affected_versions.push(match lower {
+                        "0" => "*".to_string(),
+                        lower => format!(">= {}", lower),
+                    });
+                }
+            }
+        }
+
+        let mut vulnerability = Vulnerability {
+            id: self.id.clone(),
+            title: self.summary.clone().unwrap_or_else(|| self.id.clone()),
+            description: self
+                .details
+                .clone()
+                .or_else(|| self.summary.clone())
+                .unwrap_or_default(),
+            severity: VulnerabilitySeverity::Medium,
+            cvss_score: None,
+            cvss_vector: None,
+            patch_available: !fixed_versions.is_empty(),
+            affected_versions,
+            fixed_versions,
+            published_date: self.published,
+            updated_date: self.modified,
+            references: self.references.iter().map(|r| r.url.clone()).collect(),
+            cwe_ids: self
+                .database_specific
+                .get("cwe_ids")
+                .and_then(|ids| serde_json::from_value(ids.clone()).ok())
+                .unwrap_or_default(),
+            exploit_available: false,
+            source_severity: None,
+            affected_symbols: vec![],
+            likely_reachable: None,
+            aliases: self.aliases.clone(),
+        };
+        let vector = self
+            .severity
+            .iter()
+            .find(|s| s.kind == "CVSS_V3")
+            .or_else(|| self.severity.first());
+        let rating = self
+            .database_specific
+            .get("severity")
+            .and_then(Value::as_str);
+        let mapped = vector
+            .is_some_and(|vector| severity_mapper.apply(&mut vulnerability, "osv", &vector.score));
+        if let (false, Some(rating)) = (mapped, rating) {
+            severity_mapper.apply(&mut vulnerability, "ghsa", rating);
+        }
+        vulnerability
+    }
+}
+
+impl OsvAffected {
+    /// Whether the entry lists `version` or one of its ranges holds it
+    ///
+    /// `SEMVER` ranges are compared as semantic versions and `ECOSYSTEM`
+    /// ranges with `scheme`; `GIT` ranges name commits and never match.
+    pub(crate) fn affects(
+        &self,
+        version: &str,
+        semver: &dyn VersionScheme,
+        scheme: &dyn VersionScheme,
+    ) -> bool {
+        self.versions.iter().any(|listed| listed == version)
+            || self.ranges.iter().any(|range| match range.kind.as_str() {
+                "SEMVER" => range.affects(version, semver),
+                "ECOSYSTEM" => range.affects(version, scheme),
+                _ => false,
+            })
+    }
+}
+
+impl OsvRange {
+    /// OSV's range evaluation
+    ///
+    /// The highest event at or below `version` decides: an `introduced`
+    /// enters the range, a `fixed` at or below it or a `last_affected` below
+    /// it leaves. An `introduced` of `0` precedes every version. Events whose
+    /// versions the scheme cannot compare are ignored.
+    fn affects(&self, version: &str, scheme: &dyn VersionScheme) -> bool {
+        if scheme.parse(version).is_err() {
+            return false;
+        }
+        let compare = |a: &str, b: &str| match (a, b) {
+            ("0", "0") => Some(Ordering::Equal),
+            ("0", _) => Some(Ordering::Less),
+            (_, "0") => Some(Ordering::Greater),
+            (a, b) => scheme.compare(a, b).ok(),
+        };
+
+        let mut deciding: Option<(&str, bool)> = None;
+        for event in &self.events {
+            let (bound, enters, reached) =
+                match (&event.introduced, &event.fixed, &event.last_affected) {
+                    (Some(bound), _, _) => (
+                        bound,
+                        true,
+                        compare(bound, version).is_some_and(Ordering::is_le),
+                    ),
+                    (None, Some(bound), _) => (
+                        bound,
+                        false,
+                        compare(bound, version).is_some_and(Ordering::is_le),
+                    ),
+                    (None, None, Some(bound)) => (
+                        bound,
+                        false,
+                        compare(bound, version).is_some_and(Ordering::is_lt),
+                    ),
+                    (None, None, None) => continue,
+                };
+            // Of events at the same version, the later one listed wins
+            let later = deciding
+                .is_none_or(|(current, _)| compare(bound, current).is_some_and(Ordering::is_ge));
+            if reached && later {
+                deciding = Some((bound, enters));
+            }
+        }
+        deciding.is_some_and(|(_, enters)| enters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::versioning::{Pep440Scheme, SemverScheme};
+
+    fn range(kind: &str, events: Value) -> OsvAffected {
+        serde_json::from_value(serde_json::json!({
+            "ranges": [{"type": kind, "events": events}]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_range_semantics() {
+        let semver = SemverScheme::new();
+        let pep440 = Pep440Scheme;
+
+        // Two disjoint ranges, listed out of order
+        let entry = range(
+            "SEMVER",
+            serde_json::json!([
+                {"introduced": "2.0.0"}, {"fixed": "2.3.1"},
+                {"introduced": "0"}, {"fixed": "1.4.0"}
+            ]),
+        );
+        for (version, affected) in [
+            ("0.1.0", true),
+            ("1.3.9", true),
+            ("1.4.0", false),
+            ("1.9.0", false),
+            ("2.0.0", true),
+            ("2.3.1", false),
+            ("not-a-version", false),
+        ] {
+            assert_eq!(
+                entry.affects(version, &semver, &semver),
+                affected,
+                "{}",
+                version
+            );
+        }
+
+        // `last_affected` includes its own version; ECOSYSTEM uses the scheme given
+        let entry = range(
+            "ECOSYSTEM",
+            serde_json::json!([{"introduced": "1.0"}, {"last_affected": "1.2.post1"}]),
+        );
+        assert!(entry.affects("1.2.post1", &semver, &pep440));
+        assert!(entry.affects("1.1", &semver, &pep440));
+        assert!(!entry.affects("1.3", &semver, &pep440));
+        assert!(!entry.affects("0.9", &semver, &pep440));
+
+        // Still open: every version from `introduced` on
+        let entry = range("SEMVER", serde_json::json!([{"introduced": "3.1.0"}]));
+        assert!(entry.affects("9.0.0", &semver, &semver));
+        assert!(!range("GIT", serde_json::json!([{"introduced": "0"}]))
+            .affects("1.0.0", &semver, &semver));
+    }
+}
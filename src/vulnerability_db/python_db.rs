@@ -9,6 +9,8 @@ use std::path::{Path, PathBuf};
 use crate::core::{
     DatabaseStatistics, UpdateResult, Vulnerability, VulnerabilityDatabase, VulnerabilitySeverity,
 };
+use crate::utils::names::normalize_name;
+use crate::versioning::PrereleasePolicy;
 
 /// Python vulnerability database
 pub struct PythonVulnerabilityDb {
@@ -81,6 +83,10 @@ impl PythonVulnerabilityDb {
                 cwe_ids: vec!["CWE-400".to_string()],
                 exploit_available: false,
                 patch_available: true,
+                source_severity: None,
+                affected_symbols: vec![],
+                likely_reachable: None,
+                aliases: vec![],
             },
         );
 
@@ -100,6 +106,14 @@ impl PythonVulnerabilityDb {
             cwe_ids: vec!["CWE-502".to_string()],
             exploit_available: true,
             patch_available: true,
+            source_severity: None,
+            affected_symbols: vec![
+                "yaml.full_load".to_string(),
+                "yaml.full_load_all".to_string(),
+                "yaml.FullLoader".to_string(),
+            ],
+            likely_reachable: None,
+            aliases: vec![],
         });
 
         // Flask (security header vulnerability)
@@ -118,6 +132,10 @@ impl PythonVulnerabilityDb {
             cwe_ids: vec!["CWE-400".to_string()],
             exploit_available: false,
             patch_available: true,
+            source_severity: None,
+            affected_symbols: vec![],
+            likely_reachable: None,
+            aliases: vec![],
         });
 
         // requests (proxy header leakage)
@@ -136,6 +154,10 @@ impl PythonVulnerabilityDb {
             cwe_ids: vec!["CWE-200".to_string()],
             exploit_available: false,
             patch_available: true,
+            source_severity: None,
+            affected_symbols: vec![],
+            likely_reachable: None,
+            aliases: vec![],
         });
 
         // Pillow (uncontrolled resource consumption)
@@ -154,6 +176,10 @@ impl PythonVulnerabilityDb {
             cwe_ids: vec!["CWE-125".to_string()],
             exploit_available: false,
             patch_available: true,
+            source_severity: None,
+            affected_symbols: vec![],
+            likely_reachable: None,
+            aliases: vec![],
         });
 
         // NumPy (NULL pointer dereference)
@@ -172,6 +198,10 @@ impl PythonVulnerabilityDb {
             cwe_ids: vec!["CWE-120".to_string()],
             exploit_available: false,
             patch_available: true,
+            source_severity: None,
+            affected_symbols: vec![],
+            likely_reachable: None,
+            aliases: vec![],
         });
 
         // urllib3 (cookie header leakage)
@@ -190,12 +220,16 @@ impl PythonVulnerabilityDb {
             cwe_ids: vec!["CWE-1333".to_string()],
             exploit_available: false,
             patch_available: true,
+            source_severity: None,
+            affected_symbols: vec![],
+            likely_reachable: None,
+            aliases: vec![],
         });
     }
 
     fn add_vulnerability(&mut self, package: &str, vuln: Vulnerability) {
         self.cache
-            .entry(package.to_string())
+            .entry(normalize_name(package, "python"))
             .or_default()
             .push(vuln);
     }
@@ -206,14 +240,40 @@ impl VulnerabilityDatabase for PythonVulnerabilityDb {
     async fn check_package(
         &self,
         package_name: &str,
-        _version: &str,
+        version: &str,
         package_type: &str,
+    ) -> Result<Vec<Vulnerability>> {
+        self.check_package_with_policy(
+            package_name,
+            version,
+            package_type,
+            PrereleasePolicy::for_ecosystem(package_type),
+        )
+        .await
+    }
+
+    async fn check_package_with_policy(
+        &self,
+        package_name: &str,
+        version: &str,
+        package_type: &str,
+        policy: PrereleasePolicy,
     ) -> Result<Vec<Vulnerability>> {
         if package_type != "python" {
             return Ok(vec![]);
         }
 
-        Ok(self.cache.get(package_name).cloned().unwrap_or_default())
+        let package_vulns = self
+            .cache
+            .get(&normalize_name(package_name, "python"))
+            .cloned()
+            .unwrap_or_default();
+        Ok(super::filter_affected(
+            package_vulns,
+            version,
+            "python",
+            policy,
+        ))
     }
 
     async fn update(&mut self) -> Result<UpdateResult> {
@@ -286,6 +346,10 @@ impl VulnerabilityDatabase for PythonVulnerabilityDb {
             return Ok(vec![]);
         }
 
-        Ok(self.cache.get(package_name).cloned().unwrap_or_default())
+        Ok(self
+            .cache
+            .get(&normalize_name(package_name, "python"))
+            .cloned()
+            .unwrap_or_default())
     }
 }
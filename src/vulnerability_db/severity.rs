@@ -0,0 +1,372 @@
+//! Normalizing severity ratings from different advisory sources
+//!
+//! GHSA rates advisories LOW/MODERATE/HIGH/CRITICAL, NVD uses CVSS scores,
+//! OSV carries CVSS vectors, and distribution trackers have scales of their
+//! own. [`SeverityMapper`] maps each onto [`VulnerabilitySeverity`] so that
+//! advisories from several sources compare, deduplicate and score alike. The
+//! rating as the source gave it is kept in [`Vulnerability::source_severity`].
+
+use std::collections::HashMap;
+
+use crate::core::{SourceSeverity, Vulnerability, VulnerabilitySeverity};
+
+/// Table used for sources without one of their own
+const GENERIC: &str = "*";
+
+/// Maps source severity ratings onto [`VulnerabilitySeverity`]
+///
+/// A rating is looked up in the source's label table first, so overrides win,
+/// then read as a CVSS v3 vector or a numeric CVSS score, and finally looked
+/// up among labels most sources share (`moderate`, `important`, ...).
+#[derive(Debug, Clone)]
+pub struct SeverityMapper {
+    labels: HashMap<String, HashMap<String, VulnerabilitySeverity>>,
+}
+
+impl SeverityMapper {
+    /// Mapper with the built-in tables
+    pub fn new() -> Self {
+        use VulnerabilitySeverity::*;
+        let tables: [(&str, &[(&str, VulnerabilitySeverity)]); 7] = [
+            (
+                "ghsa",
+                &[
+                    ("low", Low),
+                    ("moderate", Medium),
+                    ("high", High),
+                    ("critical", Critical),
+                ],
+            ),
+            (
+                "npm",
+                &[
+                    ("info", None),
+                    ("low", Low),
+                    ("moderate", Medium),
+                    ("high", High),
+                    ("critical", Critical),
+                ],
+            ),
+            (
+                "nvd",
+                &[
+                    ("none", None),
+                    ("low", Low),
+                    ("medium", Medium),
+                    ("high", High),
+                    ("critical", Critical),
+                ],
+            ),
+            // Red Hat calls the level below critical "important"
+            (
+                "redhat",
+                &[
+                    ("low", Low),
+                    ("moderate", Medium),
+                    ("important", High),
+                    ("critical", Critical),
+                ],
+            ),
+            (
+                "ubuntu",
+                &[
+                    ("negligible", None),
+                    ("low", Low),
+                    ("medium", Medium),
+                    ("high", High),
+                    ("critical", Critical),
+                ],
+            ),
+            // Debian rates urgency and has no critical level
+            (
+                "debian",
+                &[
+                    ("unimportant", None),
+                    ("low", Low),
+                    ("medium", Medium),
+                    ("high", High),
+                ],
+            ),
+            (
+                GENERIC,
+                &[
+                    ("none", None),
+                    ("info", None),
+                    ("informational", None),
+                    ("negligible", None),
+                    ("low", Low),
+                    ("medium", Medium),
+                    ("moderate", Medium),
+                    ("high", High),
+                    ("important", High),
+                    ("critical", Critical),
+                ],
+            ),
+        ];
+
+        let mut mapper = Self {
+            labels: HashMap::new(),
+        };
+        for (source, labels) in tables {
+            for (label, severity) in labels {
+                mapper = mapper.with_mapping(source, label, severity.clone());
+            }
+        }
+        mapper
+    }
+
+    /// Map a source's label to a severity, replacing any built-in mapping
+    ///
+    /// Source names and labels are case-insensitive.
+    pub fn with_mapping(
+        mut self,
+        source: &str,
+        label: &str,
+        severity: VulnerabilitySeverity,
+    ) -> Self {
+        self.labels
+            .entry(source.to_lowercase())
+            .or_default()
+            .insert(label.trim().to_lowercase(), severity);
+        self
+    }
+
+    /// Normalize a rating, with the CVSS score it implies when it carries one
+    pub fn normalize(
+        &self,
+        source: &str,
+        value: &str,
+    ) -> Option<(VulnerabilitySeverity, Option<f32>)> {
+        let label = value.trim().to_lowercase();
+        if let Some(severity) = self
+            .labels
+            .get(&source.to_lowercase())
+            .and_then(|table| table.get(&label))
+        {
+            return Some((severity.clone(), None));
+        }
+
+        let score = if label.starts_with("cvss:3") {
+            cvss_v3_base_score(value)
+        } else {
+            label
+                .parse::<f32>()
+                .ok()
+                .filter(|s| (0.0..=10.0).contains(s))
+        };
+        if let Some(score) = score {
+            return Some((VulnerabilitySeverity::from_cvss_v3(score), Some(score)));
+        }
+
+        self.labels
+            .get(GENERIC)
+            .and_then(|table| table.get(&label))
+            .map(|severity| (severity.clone(), None))
+    }
+
+    /// Set a vulnerability's severity from a source rating
+    ///
+    /// The rating is always recorded in `source_severity`. Severity is only
+    /// changed when the rating could be mapped; a CVSS vector or score also
+    /// fills in `cvss_score` and `cvss_vector`. Returns whether it was mapped.
+    pub fn apply(&self, vuln: &mut Vulnerability, source: &str, value: &str) -> bool {
+        vuln.source_severity = Some(SourceSeverity {
+            source: source.to_string(),
+            value: value.to_string(),
+        });
+        let Some((severity, score)) = self.normalize(source, value) else {
+            return false;
+        };
+        vuln.severity = severity;
+        if let Some(score) = score {
+            vuln.cvss_score = Some(score);
+            if value.trim().to_lowercase().starts_with("cvss:") {
+                vuln.cvss_vector = Some(value.trim().to_string());
+            }
+        }
+        true
+    }
+}
+
+impl Default for SeverityMapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// CVSS v3.0/v3.1 base score of a vector such as `CVSS:3.1/AV:N/AC:L/...`
+///
+/// Returns `None` when a base metric is missing or has an unknown value.
+pub fn cvss_v3_base_score(vector: &str) -> Option<f32> {
+    let metrics: HashMap<&str, &str> = vector
+        .trim()
+        .split('/')
+        .skip(1)
+        .filter_map(|metric| metric.split_once(':'))
+        .collect();
+    let metric = |name: &str| metrics.get(name).copied();
+
+    let changed = match metric("S")? {
+        "U" => false,
+        "C" => true,
+        _ => return None,
+    };
+    let attack_vector = match metric("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        "P" => 0.2,
+        _ => return None,
+    };
+    let attack_complexity = match metric("AC")? {
+        "L" => 0.77,
+        "H" => 0.44,
+        _ => return None,
+    };
+    let privileges = match (metric("PR")?, changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        ("H", false) => 0.27,
+        ("H", true) => 0.5,
+        _ => return None,
+    };
+    let interaction = match metric("UI")? {
+        "N" => 0.85,
+        "R" => 0.62,
+        _ => return None,
+    };
+    let impact_of = |name: &str| match metric(name)? {
+        "H" => Some(0.56),
+        "L" => Some(0.22),
+        "N" => Some(0.0),
+        _ => None,
+    };
+    let (c, i, a): (f64, f64, f64) = (impact_of("C")?, impact_of("I")?, impact_of("A")?);
+
+    let base_impact = 1.0 - (1.0 - c) * (1.0 - i) * (1.0 - a);
+    let impact = if changed {
+        7.52 * (base_impact - 0.029) - 3.25 * (base_impact - 0.02).powi(15)
+    } else {
+        6.42 * base_impact
+    };
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+    let exploitability = 8.22 * attack_vector * attack_complexity * privileges * interaction;
+    let score = if changed {
+        1.08 * (impact + exploitability)
+    } else {
+        impact + exploitability
+    };
+    Some(round_up(score.min(10.0)) as f32)
+}
+
+/// Round up to one decimal as CVSS v3.1 specifies, avoiding float artifacts
+fn round_up(value: f64) -> f64 {
+    let scaled = (value * 100_000.0).round() as i64;
+    if scaled % 10_000 == 0 {
+        scaled as f64 / 100_000.0
+    } else {
+        (scaled / 10_000 + 1) as f64 / 10.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use VulnerabilitySeverity::*;
+
+    fn mapped(mapper: &SeverityMapper, source: &str, value: &str) -> Option<VulnerabilitySeverity> {
+        mapper
+            .normalize(source, value)
+            .map(|(severity, _)| severity)
+    }
+
+    #[test]
+    fn test_source_label_tables() {
+        let mapper = SeverityMapper::new();
+        let cases = [
+            ("ghsa", "MODERATE", Medium),
+            ("GHSA", "critical", Critical),
+            ("npm", "info", None),
+            ("npm", "moderate", Medium),
+            ("nvd", "MEDIUM", Medium),
+            ("redhat", "Important", High),
+            ("ubuntu", "negligible", None),
+            ("debian", "unimportant", None),
+            ("debian", "high", High),
+            // Sources without a table fall back to the shared labels
+            ("internal", "Moderate", Medium),
+            ("osv", "HIGH", High),
+        ];
+        for (source, label, expected) in cases {
+            assert_eq!(
+                mapped(&mapper, source, label),
+                Some(expected),
+                "{source} {label}"
+            );
+        }
+        assert_eq!(mapped(&mapper, "internal", "P1"), Option::None);
+    }
+
+    #[test]
+    fn test_cvss_scores_and_vectors() {
+        let mapper = SeverityMapper::new();
+        assert_eq!(mapper.normalize("nvd", "7.5"), Some((High, Some(7.5))));
+        assert_eq!(
+            mapper.normalize("osv", "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"),
+            Some((Critical, Some(9.8)))
+        );
+        assert_eq!(
+            cvss_v3_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N"),
+            Some(6.1)
+        );
+        assert_eq!(
+            cvss_v3_base_score("CVSS:3.0/AV:L/AC:H/PR:H/UI:R/S:U/C:N/I:N/A:N"),
+            Some(0.0)
+        );
+        assert_eq!(cvss_v3_base_score("CVSS:3.1/AV:N/AC:L"), Option::None);
+    }
+
+    #[test]
+    fn test_override_and_preserve_original() {
+        let mapper = SeverityMapper::new().with_mapping("ghsa", "MODERATE", High);
+        let mut vuln = Vulnerability {
+            id: "GHSA-xxxx-yyyy-zzzz".to_string(),
+            title: "Prototype pollution".to_string(),
+            description: String::new(),
+            severity: Low,
+            cvss_score: Option::None,
+            cvss_vector: Option::None,
+            affected_versions: vec![],
+            fixed_versions: vec![],
+            published_date: Option::None,
+            updated_date: Option::None,
+            references: vec![],
+            cwe_ids: vec![],
+            exploit_available: false,
+            patch_available: false,
+            source_severity: Option::None,
+            affected_symbols: vec![],
+            likely_reachable: Option::None,
+            aliases: vec![],
+        };
+
+        assert!(mapper.apply(&mut vuln, "ghsa", "MODERATE"));
+        assert_eq!(vuln.severity, High);
+        assert_eq!(vuln.severity_score(), 8.0);
+        assert_eq!(
+            vuln.source_severity,
+            Some(SourceSeverity {
+                source: "ghsa".to_string(),
+                value: "MODERATE".to_string(),
+            })
+        );
+
+        // Unknown ratings are recorded but leave the severity alone
+        assert!(!mapper.apply(&mut vuln, "internal", "P1"));
+        assert_eq!(vuln.severity, High);
+        assert_eq!(vuln.source_severity.unwrap().value, "P1");
+    }
+}
@@ -0,0 +1,516 @@
+//! Vulnerability database snapshots loaded from outside the crate
+//!
+//! The bundled databases only change with crate releases. An organization that
+//! maintains its own advisory feed can publish it as a snapshot file and point
+//! the analyzers at it with a [`DatabaseSource`]: a path on disk, a URL, or a
+//! [`DatabaseLoader`] of its own for object storage and the like. A snapshot
+//! is validated when it is loaded and rejected as a whole if any part of it is
+//! unusable, so a corrupt download never silently yields an empty database.
+//!
+//! The format is JSON:
+//!
+//! ```json
+//! {
+//!   "format_version": 1,
+//!   "version": "2026.10.14",
+//!   "generated_at": "2026-10-14T06:00:00Z",
+//!   "entries": [
+//!     { "package_name": "lodash", "package_type": "npm", "vulnerability": { ... } }
+//!   ]
+//! }
+//! ```
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::core::{
+    vulnerability::EmbeddedVulnerability, DatabaseStatistics, UpdateResult, Vulnerability,
+    VulnerabilityDatabase,
+};
+use crate::utils::names::normalize_name;
+use crate::versioning::PrereleasePolicy;
+
+/// Snapshot format this build reads
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// How long a snapshot download may take
+const FETCH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Ecosystems a snapshot may hold, with the aliases accepted for them
+const ECOSYSTEMS: &[(&str, &[&str])] = &[
+    ("npm", &["npm"]),
+    ("python", &["python", "pypi"]),
+    ("java", &["java", "maven"]),
+    ("terraform", &["terraform"]),
+    ("cargo", &["cargo", "crates.io", "rust"]),
+    ("go", &["go", "golang"]),
+    ("rubygems", &["rubygems", "ruby", "gem"]),
+    ("composer", &["composer", "packagist", "php"]),
+];
+
+/// Fetches the raw bytes of a snapshot from storage the crate knows nothing about
+#[async_trait]
+pub trait DatabaseLoader: Send + Sync {
+    /// Read the snapshot
+    async fn load(&self) -> Result<Vec<u8>>;
+
+    /// Where the snapshot comes from, for metadata and errors
+    fn describe(&self) -> String;
+}
+
+/// Where the vulnerability database is loaded from
+#[derive(Clone, Default)]
+pub enum DatabaseSource {
+    /// The databases compiled into the crate
+    #[default]
+    Bundled,
+    /// A snapshot file on disk
+    Path(PathBuf),
+    /// A snapshot served over HTTP(S)
+    ///
+    /// Fetched through the proxy in `HTTPS_PROXY`/`HTTP_PROXY`, honoring
+    /// `NO_PROXY`; unavailable in builds with the `offline` feature.
+    Url(String),
+    /// A snapshot read by a custom loader
+    Custom(Arc<dyn DatabaseLoader>),
+}
+
+impl DatabaseSource {
+    /// Description of the source, for metadata and errors
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Bundled => "bundled".to_string(),
+            Self::Path(path) => path.display().to_string(),
+            Self::Url(url) => url.clone(),
+            Self::Custom(loader) => loader.describe(),
+        }
+    }
+
+    /// Load the snapshot, or `None` for the bundled databases
+    pub async fn load(&self) -> Result<Option<VulnerabilitySnapshot>, DatabaseLoadError> {
+        let source = self.describe();
+        let unavailable = |err: anyhow::Error| DatabaseLoadError::Unavailable {
+            location: source.clone(),
+            reason: format!("{:#}", err),
+        };
+        let bytes = match self {
+            Self::Bundled => return Ok(None),
+            Self::Path(path) => tokio::fs::read(path)
+                .await
+                .map_err(|err| unavailable(err.into()))?,
+            Self::Url(url) if cfg!(feature = "offline") => {
+                return Err(DatabaseLoadError::Offline(url.clone()))
+            }
+            Self::Url(url) => fetch(url).await.map_err(unavailable)?,
+            Self::Custom(loader) => loader.load().await.map_err(unavailable)?,
+        };
+        VulnerabilitySnapshot::parse(&bytes, &source).map(Some)
+    }
+}
+
+impl fmt::Debug for DatabaseSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bundled => write!(f, "Bundled"),
+            Self::Path(path) => f.debug_tuple("Path").field(path).finish(),
+            Self::Url(url) => f.debug_tuple("Url").field(url).finish(),
+            Self::Custom(loader) => f.debug_tuple("Custom").field(&loader.describe()).finish(),
+        }
+    }
+}
+
+/// A vulnerability database that could not be loaded
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DatabaseLoadError {
+    /// The snapshot could not be read or fetched
+    #[error("vulnerability database {location} is unavailable: {reason}")]
+    Unavailable { location: String, reason: String },
+    /// The build cannot fetch a remote snapshot
+    #[error("vulnerability database {0} cannot be fetched in an offline build")]
+    Offline(String),
+    /// The snapshot is in a format this build does not read
+    #[error("vulnerability database {location} has format version {found}, this build reads version {supported}")]
+    UnsupportedFormat {
+        location: String,
+        found: u32,
+        supported: u32,
+    },
+    /// The snapshot is malformed or has invalid entries
+    #[error("vulnerability database {location} is corrupt: {reason}")]
+    Corrupt { location: String, reason: String },
+}
+
+/// Version and size of a loaded vulnerability database
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatabaseMetadata {
+    /// Where the database was loaded from; `bundled` for the built-in one
+    pub source: String,
+    /// Snapshot version, or the crate version for the bundled databases
+    pub version: String,
+    pub format_version: u32,
+    /// When the snapshot was generated, if it says
+    pub generated_at: Option<DateTime<Utc>>,
+    /// Number of advisories, counted once per affected package
+    pub entry_count: usize,
+    pub packages_covered: usize,
+}
+
+impl DatabaseMetadata {
+    /// Metadata of the databases compiled into the crate
+    pub fn bundled() -> Result<Self> {
+        let databases = [
+            super::create_npm_database()?,
+            super::create_python_database()?,
+            super::create_java_database()?,
+            super::create_terraform_database()?,
+        ];
+        Ok(Self::bundled_from(databases.iter().map(|db| db.as_ref())))
+    }
+
+    /// Metadata of already loaded per-ecosystem databases
+    pub(crate) fn bundled_from<'a>(
+        databases: impl IntoIterator<Item = &'a dyn VulnerabilityDatabase>,
+    ) -> Self {
+        let (entry_count, packages_covered) =
+            databases
+                .into_iter()
+                .fold((0, 0), |(entries, packages), db| {
+                    let stats = db.statistics();
+                    (
+                        entries + stats.total_vulnerabilities,
+                        packages + stats.packages_covered,
+                    )
+                });
+        Self {
+            source: DatabaseSource::Bundled.describe(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            generated_at: None,
+            entry_count,
+            packages_covered,
+        }
+    }
+}
+
+/// Snapshot file layout
+#[derive(Deserialize)]
+struct SnapshotFile {
+    version: String,
+    #[serde(default)]
+    generated_at: Option<DateTime<Utc>>,
+    entries: Vec<EmbeddedVulnerability>,
+}
+
+/// A validated snapshot, shared by the analyzers of every ecosystem
+#[derive(Debug)]
+pub struct VulnerabilitySnapshot {
+    metadata: DatabaseMetadata,
+    /// Advisories by ecosystem, then by normalized package name
+    packages: HashMap<String, HashMap<String, Vec<Vulnerability>>>,
+}
+
+impl VulnerabilitySnapshot {
+    /// Parse and validate a snapshot read from `source`
+    pub fn parse(bytes: &[u8], source: &str) -> Result<Self, DatabaseLoadError> {
+        let corrupt = |reason: String| DatabaseLoadError::Corrupt {
+            location: source.to_string(),
+            reason,
+        };
+
+        // Check the format version before the layout, which may have changed
+        let header: serde_json::Value =
+            serde_json::from_slice(bytes).map_err(|err| corrupt(err.to_string()))?;
+        let found = header
+            .get("format_version")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| corrupt("missing format_version".to_string()))?;
+        if found != u64::from(SNAPSHOT_FORMAT_VERSION) {
+            return Err(DatabaseLoadError::UnsupportedFormat {
+                location: source.to_string(),
+                found: u32::try_from(found).unwrap_or(u32::MAX),
+                supported: SNAPSHOT_FORMAT_VERSION,
+            });
+        }
+        let file: SnapshotFile =
+            serde_json::from_value(header).map_err(|err| corrupt(err.to_string()))?;
+        if file.version.trim().is_empty() {
+            return Err(corrupt("empty version".to_string()));
+        }
+
+        let mut packages: HashMap<String, HashMap<String, Vec<Vulnerability>>> = HashMap::new();
+        for (index, entry) in file.entries.into_iter().enumerate() {
+            let ecosystem = canonical_ecosystem(&entry.package_type).ok_or_else(|| {
+                corrupt(format!(
+                    "entry {} has unknown package type '{}'",
+                    index, entry.package_type
+                ))
+            })?;
+            if entry.package_name.trim().is_empty() {
+                return Err(corrupt(format!("entry {} has no package name", index)));
+            }
+            if entry.vulnerability.id.trim().is_empty() {
+                return Err(corrupt(format!(
+                    "entry {} ({}) has no advisory ID",
+                    index, entry.package_name
+                )));
+            }
+            packages
+                .entry(ecosystem.to_string())
+                .or_default()
+                .entry(normalize_name(&entry.package_name, ecosystem))
+                .or_default()
+                .push(entry.vulnerability);
+        }
+
+        let by_package = packages.values().flat_map(HashMap::values);
+        let metadata = DatabaseMetadata {
+            source: source.to_string(),
+            version: file.version,
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            generated_at: file.generated_at,
+            entry_count: by_package.clone().map(Vec::len).sum(),
+            packages_covered: by_package.count(),
+        };
+        Ok(Self { metadata, packages })
+    }
+
+    /// Version and size of the snapshot
+    pub fn metadata(&self) -> &DatabaseMetadata {
+        &self.metadata
+    }
+}
+
+/// A [`VulnerabilitySnapshot`] serving lookups for every ecosystem it holds
+#[derive(Debug, Clone)]
+pub struct SnapshotDb {
+    snapshot: Arc<VulnerabilitySnapshot>,
+}
+
+impl SnapshotDb {
+    pub fn new(snapshot: Arc<VulnerabilitySnapshot>) -> Self {
+        Self { snapshot }
+    }
+
+    fn advisories(&self, package_name: &str, package_type: &str) -> Vec<Vulnerability> {
+        let Some(ecosystem) = canonical_ecosystem(package_type) else {
+            return vec![];
+        };
+        self.snapshot
+            .packages
+            .get(ecosystem)
+            .and_then(|packages| packages.get(&normalize_name(package_name, ecosystem)))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl VulnerabilityDatabase for SnapshotDb {
+    async fn check_package(
+        &self,
+        package_name: &str,
+        version: &str,
+        package_type: &str,
+    ) -> Result<Vec<Vulnerability>> {
+        self.check_package_with_policy(
+            package_name,
+            version,
+            package_type,
+            PrereleasePolicy::for_ecosystem(package_type),
+        )
+        .await
+    }
+
+    async fn check_package_with_policy(
+        &self,
+        package_name: &str,
+        version: &str,
+        package_type: &str,
+        policy: PrereleasePolicy,
+    ) -> Result<Vec<Vulnerability>> {
+        Ok(super::filter_affected(
+            self.advisories(package_name, package_type),
+            version,
+            package_type,
+            policy,
+        ))
+    }
+
+    /// Snapshots are immutable; load a newer one to update
+    async fn update(&mut self) -> Result<UpdateResult> {
+        Ok(UpdateResult {
+            success: true,
+            new_vulnerabilities: 0,
+            updated_vulnerabilities: 0,
+            removed_vulnerabilities: 0,
+            duration_seconds: 0,
+            errors: vec![],
+        })
+    }
+
+    fn last_updated(&self) -> Option<DateTime<Utc>> {
+        self.snapshot.metadata.generated_at
+    }
+
+    fn statistics(&self) -> DatabaseStatistics {
+        let metadata = &self.snapshot.metadata;
+        let mut stats = DatabaseStatistics {
+            total_vulnerabilities: metadata.entry_count,
+            packages_covered: metadata.packages_covered,
+            last_updated: metadata.generated_at,
+            database_version: metadata.version.clone(),
+            vulnerabilities_by_severity: HashMap::new(),
+            vulnerabilities_by_year: HashMap::new(),
+        };
+        let vulns = self
+            .snapshot
+            .packages
+            .values()
+            .flat_map(HashMap::values)
+            .flatten();
+        for vuln in vulns {
+            *stats
+                .vulnerabilities_by_severity
+                .entry(vuln.severity.clone())
+                .or_insert(0) += 1;
+            if let Some(date) = vuln.published_date {
+                *stats
+                    .vulnerabilities_by_year
+                    .entry(date.year())
+                    .or_insert(0) += 1;
+            }
+        }
+        stats
+    }
+
+    async fn get_by_cve(&self, cve_id: &str) -> Result<Option<Vulnerability>> {
+        Ok(self
+            .snapshot
+            .packages
+            .values()
+            .flat_map(HashMap::values)
+            .flatten()
+            .find(|vuln| vuln.id == cve_id)
+            .cloned())
+    }
+
+    async fn get_all_for_package(
+        &self,
+        package_name: &str,
+        package_type: &str,
+    ) -> Result<Vec<Vulnerability>> {
+        Ok(self.advisories(package_name, package_type))
+    }
+}
+
+/// Canonical name of an ecosystem a snapshot may hold
+pub(crate) fn canonical_ecosystem(package_type: &str) -> Option<&'static str> {
+    let package_type = package_type.trim().to_lowercase();
+    ECOSYSTEMS
+        .iter()
+        .find(|(_, aliases)| aliases.contains(&package_type.as_str()))
+        .map(|(ecosystem, _)| *ecosystem)
+}
+
+/// Download a snapshot
+async fn fetch(url: &str) -> Result<Vec<u8>> {
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!(
+            "threatflux-package-security/",
+            env!("CARGO_PKG_VERSION")
+        ))
+        .build()?;
+    let response = client.get(url).send().await?.error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(format_version: u32, package_type: &str) -> String {
+        format!(
+            r#"{{
+                "format_version": {},
+                "version": "2026.10.14",
+                "entries": [{{
+                    "package_name": "Left_Pad",
+                    "package_type": "{}",
+                    "version_ranges": [],
+                    "vulnerability": {{
+                        "id": "INTERNAL-2026-001",
+                        "title": "Credential theft",
+                        "description": "Exfiltrates tokens",
+                        "severity": "Critical",
+                        "cvss_score": null,
+                        "cvss_vector": null,
+                        "affected_versions": ["< 2.0.0"],
+                        "fixed_versions": ["2.0.0"],
+                        "published_date": null,
+                        "updated_date": null,
+                        "references": [],
+                        "cwe_ids": ["CWE-506"],
+                        "exploit_available": true,
+                        "patch_available": true
+                    }}
+                }}]
+            }}"#,
+            format_version, package_type
+        )
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_lookup() {
+        let parsed = VulnerabilitySnapshot::parse(snapshot(1, "pypi").as_bytes(), "test").unwrap();
+        assert_eq!(parsed.metadata().version, "2026.10.14");
+        assert_eq!(parsed.metadata().entry_count, 1);
+
+        // Names are matched the way the ecosystem compares them
+        let db = SnapshotDb::new(Arc::new(parsed));
+        let found = db
+            .check_package("left-pad", "1.0.0", "python")
+            .await
+            .unwrap();
+        assert_eq!(found[0].id, "INTERNAL-2026-001");
+        assert!(db
+            .check_package("left-pad", "2.0.0", "python")
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(db
+            .check_package("left-pad", "1.0.0", "npm")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_validation() {
+        let err =
+            VulnerabilitySnapshot::parse(snapshot(2, "npm").as_bytes(), "db.json").unwrap_err();
+        assert_eq!(
+            err,
+            DatabaseLoadError::UnsupportedFormat {
+                location: "db.json".to_string(),
+                found: 2,
+                supported: 1,
+            }
+        );
+        let err =
+            VulnerabilitySnapshot::parse(snapshot(1, "cobol").as_bytes(), "db.json").unwrap_err();
+        assert!(err.to_string().contains("unknown package type 'cobol'"));
+        let truncated = &snapshot(1, "npm")[..200];
+        assert!(matches!(
+            VulnerabilitySnapshot::parse(truncated.as_bytes(), "db.json"),
+            Err(DatabaseLoadError::Corrupt { .. })
+        ));
+    }
+}
@@ -0,0 +1,184 @@
+//! Terraform provider/module vulnerability database implementation
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Utc};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::core::{DatabaseStatistics, UpdateResult, Vulnerability, VulnerabilityDatabase};
+use crate::utils::names::normalize_name;
+use crate::versioning::PrereleasePolicy;
+
+/// Terraform vulnerability database
+///
+/// Entries are keyed by registry address without the hostname, e.g.
+/// `hashicorp/aws` for providers or `terraform-aws-modules/vpc/aws` for
+/// modules, so that `registry.terraform.io` and `registry.opentofu.org`
+/// sources resolve to the same advisories.
+pub struct TerraformVulnerabilityDb {
+    #[allow(dead_code)]
+    path: PathBuf,
+    cache: HashMap<String, Vec<Vulnerability>>,
+    last_updated: Option<DateTime<Utc>>,
+}
+
+impl TerraformVulnerabilityDb {
+    /// Create new Terraform vulnerability database
+    pub fn new() -> Result<Self> {
+        let path = dirs::cache_dir()
+            .ok_or_else(|| anyhow::anyhow!("Cannot find cache directory"))?
+            .join("threatflux")
+            .join("terraform_vulns.db");
+
+        Ok(Self {
+            path,
+            cache: HashMap::new(),
+            last_updated: None,
+        })
+    }
+
+    /// Create with custom path
+    pub fn with_path(path: &Path) -> Result<Self> {
+        Ok(Self {
+            path: path.to_path_buf(),
+            cache: HashMap::new(),
+            last_updated: None,
+        })
+    }
+
+    /// Add a vulnerability for a registry address
+    pub fn add_vulnerability(&mut self, address: &str, vuln: Vulnerability) {
+        self.cache
+            .entry(Self::registry_key(address))
+            .or_default()
+            .push(vuln);
+    }
+
+    /// Strip the registry hostname from a provider or module address
+    pub fn registry_key(address: &str) -> String {
+        let parts: Vec<&str> = address.split('/').collect();
+        let is_hostname = parts.len() > 2 && parts[0].contains('.');
+        let address = if is_hostname {
+            parts[1..].join("/")
+        } else {
+            address.to_string()
+        };
+        normalize_name(&address, "terraform")
+    }
+}
+
+#[async_trait]
+impl VulnerabilityDatabase for TerraformVulnerabilityDb {
+    async fn check_package(
+        &self,
+        package_name: &str,
+        version: &str,
+        package_type: &str,
+    ) -> Result<Vec<Vulnerability>> {
+        self.check_package_with_policy(
+            package_name,
+            version,
+            package_type,
+            PrereleasePolicy::for_ecosystem(package_type),
+        )
+        .await
+    }
+
+    async fn check_package_with_policy(
+        &self,
+        package_name: &str,
+        version: &str,
+        package_type: &str,
+        policy: PrereleasePolicy,
+    ) -> Result<Vec<Vulnerability>> {
+        if package_type != "terraform" {
+            return Ok(vec![]);
+        }
+
+        let package_vulns = self
+            .cache
+            .get(&Self::registry_key(package_name))
+            .cloned()
+            .unwrap_or_default();
+        Ok(super::filter_affected(
+            package_vulns,
+            version,
+            "terraform",
+            policy,
+        ))
+    }
+
+    async fn update(&mut self) -> Result<UpdateResult> {
+        self.last_updated = Some(Utc::now());
+
+        Ok(UpdateResult {
+            success: true,
+            new_vulnerabilities: self.cache.values().map(|v| v.len()).sum(),
+            updated_vulnerabilities: 0,
+            removed_vulnerabilities: 0,
+            duration_seconds: 0,
+            errors: vec![],
+        })
+    }
+
+    fn last_updated(&self) -> Option<DateTime<Utc>> {
+        self.last_updated
+    }
+
+    fn statistics(&self) -> DatabaseStatistics {
+        let mut stats = DatabaseStatistics {
+            total_vulnerabilities: self.cache.values().map(|v| v.len()).sum(),
+            packages_covered: self.cache.len(),
+            last_updated: self.last_updated,
+            database_version: "1.0.0".to_string(),
+            vulnerabilities_by_severity: HashMap::new(),
+            vulnerabilities_by_year: HashMap::new(),
+        };
+
+        for vulns in self.cache.values() {
+            for vuln in vulns {
+                *stats
+                    .vulnerabilities_by_severity
+                    .entry(vuln.severity.clone())
+                    .or_insert(0) += 1;
+
+                if let Some(date) = &vuln.published_date {
+                    *stats
+                        .vulnerabilities_by_year
+                        .entry(date.year())
+                        .or_insert(0) += 1;
+                }
+            }
+        }
+
+        stats
+    }
+
+    async fn get_by_cve(&self, cve_id: &str) -> Result<Option<Vulnerability>> {
+        for vulns in self.cache.values() {
+            for vuln in vulns {
+                if vuln.id == cve_id {
+                    return Ok(Some(vuln.clone()));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    async fn get_all_for_package(
+        &self,
+        package_name: &str,
+        package_type: &str,
+    ) -> Result<Vec<Vulnerability>> {
+        if package_type != "terraform" {
+            return Ok(vec![]);
+        }
+
+        Ok(self
+            .cache
+            .get(&Self::registry_key(package_name))
+            .cloned()
+            .unwrap_or_default())
+    }
+}
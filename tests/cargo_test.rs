@@ -0,0 +1,172 @@
+//! Integration tests for Cargo crate analysis
+
+use std::fs;
+use tempfile::TempDir;
+use threatflux_package_security::core::DependencyType;
+use threatflux_package_security::vulnerability_db::DatabaseSource;
+use threatflux_package_security::{
+    detect_ecosystem, AnalysisResult, CargoAnalyzer, Ecosystem, PackageAnalyzer,
+    PackageSecurityAnalyzer,
+};
+
+const CARGO_TOML: &str = r#"
+[package]
+name = "ledger-sync"
+version = "0.3.1"
+edition = "2021"
+authors = ["Ledger Team <dev@ledger.example>"]
+license = "MIT OR Apache-2.0"
+repository = "https://github.com/ledger-example/ledger-sync"
+
+[dependencies]
+smallvec = "1.6"
+time = { version = "0.1", optional = true }
+
+[dev-dependencies]
+tempfile = "3"
+"#;
+
+const CARGO_LOCK: &str = r#"
+version = 3
+
+[[package]]
+name = "ledger-sync"
+version = "0.3.1"
+dependencies = [
+ "smallvec",
+ "tempfile",
+ "time",
+]
+
+[[package]]
+name = "libc"
+version = "0.2.150"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "smallvec"
+version = "1.6.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "tempfile"
+version = "3.8.1"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = [
+ "libc",
+]
+
+[[package]]
+name = "time"
+version = "0.1.45"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = [
+ "libc",
+]
+"#;
+
+fn write_crate(dir: &std::path::Path) {
+    fs::write(dir.join("Cargo.toml"), CARGO_TOML).unwrap();
+    fs::write(dir.join("Cargo.lock"), CARGO_LOCK).unwrap();
+}
+
+#[tokio::test]
+async fn test_cargo_locked_dependencies() {
+    let temp_dir = TempDir::new().unwrap();
+    write_crate(temp_dir.path());
+
+    let analyzer = CargoAnalyzer::new().unwrap();
+    assert!(analyzer.can_analyze(temp_dir.path()));
+    assert!(analyzer.supported_extensions().contains(&"toml"));
+    assert_eq!(detect_ecosystem(temp_dir.path()), Some(Ecosystem::Cargo));
+
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let info = result.package_info();
+    assert_eq!(info.package_type(), "cargo");
+    assert_eq!(info.metadata().name, "ledger-sync");
+    assert_eq!(info.metadata().version, "0.3.1");
+    assert_eq!(
+        info.metadata().license.as_deref(),
+        Some("MIT OR Apache-2.0")
+    );
+    assert_eq!(
+        info.metadata().repository.as_deref(),
+        Some("https://github.com/ledger-example/ledger-sync")
+    );
+
+    let analysis = result.dependency_analysis();
+    assert_eq!(analysis.direct_dependencies, 3);
+    assert_eq!(analysis.transitive_dependencies, 1);
+    let smallvec = analysis
+        .dependency_tree
+        .iter()
+        .find(|dep| dep.name == "smallvec")
+        .unwrap();
+    assert_eq!(smallvec.version_spec, "1.6");
+    assert_eq!(smallvec.resolved_version.as_deref(), Some("1.6.0"));
+
+    // libc is shared, and listed once under the first crate reaching it
+    let tempfile = analysis
+        .dependency_tree
+        .iter()
+        .find(|dep| dep.name == "tempfile")
+        .unwrap();
+    assert_eq!(tempfile.dependency_type, DependencyType::Development);
+    assert_eq!(tempfile.dependencies[0].name, "libc");
+    assert!(tempfile.dependencies[0].is_dev);
+}
+
+#[tokio::test]
+async fn test_cargo_vulnerabilities_use_locked_versions() {
+    let temp_dir = TempDir::new().unwrap();
+    write_crate(temp_dir.path());
+    let advisory = |crate_name: &str, id: &str, affected: &str, fixed: &str| {
+        serde_json::json!({
+            "package_name": crate_name,
+            "package_type": "crates.io",
+            "vulnerability": {
+                "id": id,
+                "title": format!("{} advisory", crate_name),
+                "description": "",
+                "severity": "High",
+                "cvss_score": null,
+                "cvss_vector": null,
+                "affected_versions": [affected],
+                "fixed_versions": [fixed],
+                "published_date": null,
+                "updated_date": null,
+                "references": [],
+                "cwe_ids": [],
+                "exploit_available": false,
+                "patch_available": true
+            }
+        })
+    };
+    let snapshot = temp_dir.path().join("advisories.json");
+    fs::write(
+        &snapshot,
+        serde_json::json!({
+            "format_version": 1,
+            "version": "2026.10.15",
+            "generated_at": "2026-10-15T00:00:00Z",
+            "entries": [
+                advisory("smallvec", "RUSTSEC-2021-0003", "< 1.6.1", "1.6.1"),
+                // The manifest allows 0.1.0, but the lock file pins 0.1.45
+                advisory("time", "RUSTSEC-2020-0071", "< 0.1.10", "0.1.10"),
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let analyzer = PackageSecurityAnalyzer::with_database(DatabaseSource::Path(snapshot))
+        .await
+        .unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let ids: Vec<_> = result
+        .vulnerabilities()
+        .iter()
+        .map(|v| v.id.as_str())
+        .collect();
+    assert_eq!(ids, ["RUSTSEC-2021-0003"]);
+}
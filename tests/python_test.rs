@@ -0,0 +1,245 @@
+//! Integration tests for Python project analysis
+
+use std::fs;
+use tempfile::TempDir;
+use threatflux_package_security::core::{
+    AnalysisOptions, DependencySource, DependencyType, FindingType, RiskLevel,
+};
+use threatflux_package_security::{AnalysisResult, PackageAnalyzer, PythonAnalyzer};
+
+const POETRY_PROJECT: &str = r#"
+[tool.poetry]
+name = "acme-api"
+version = "2.1.0"
+description = "ACME API client"
+authors = ["Dev Team <dev@acme.test>"]
+license = "MIT"
+repository = "https://github.com/acme/acme-api"
+
+[tool.poetry.dependencies]
+python = "^3.9"
+requests = "^2.25"
+
+[tool.poetry.dev-dependencies]
+pytest = "^7.0"
+"#;
+
+const POETRY_LOCK: &str = r#"
+[[package]]
+name = "certifi"
+version = "2023.7.22"
+description = "Python package for providing Mozilla's CA Bundle."
+optional = false
+python-versions = ">=3.6"
+
+[[package]]
+name = "pytest"
+version = "7.4.0"
+optional = false
+python-versions = ">=3.7"
+
+[[package]]
+name = "requests"
+version = "2.31.0"
+optional = false
+python-versions = ">=3.7"
+
+[metadata]
+lock-version = "2.0"
+python-versions = "^3.9"
+content-hash = "0f3c"
+"#;
+
+#[tokio::test]
+async fn test_poetry_project_with_lockfile() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("pyproject.toml"), POETRY_PROJECT).unwrap();
+    fs::write(temp_dir.path().join("poetry.lock"), POETRY_LOCK).unwrap();
+
+    let analyzer = PythonAnalyzer::new().unwrap();
+    assert!(analyzer.can_analyze(temp_dir.path()));
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let info = result.package_info();
+    assert_eq!(info.metadata().name, "acme-api");
+    assert_eq!(info.metadata().version, "2.1.0");
+    assert_eq!(
+        info.metadata().author.as_deref(),
+        Some("Dev Team <dev@acme.test>")
+    );
+    assert_eq!(
+        info.metadata().repository.as_deref(),
+        Some("https://github.com/acme/acme-api")
+    );
+    assert_eq!(info.custom_attributes()["python_requires"], "^3.9");
+
+    let analysis = result.dependency_analysis();
+    assert_eq!(analysis.direct_dependencies, 2);
+    assert_eq!(analysis.transitive_dependencies, 1);
+    let resolved: Vec<(&str, Option<&str>, bool)> = analysis
+        .dependency_tree
+        .iter()
+        .map(|dep| {
+            (
+                dep.name.as_str(),
+                dep.resolved_version.as_deref(),
+                dep.is_direct,
+            )
+        })
+        .collect();
+    assert_eq!(
+        resolved,
+        [
+            ("requests", Some("2.31.0"), true),
+            ("pytest", Some("7.4.0"), true),
+            ("certifi", Some("2023.7.22"), false),
+        ]
+    );
+    assert_eq!(
+        analysis.dependency_tree[1].dependency_type,
+        DependencyType::Development
+    );
+}
+
+#[tokio::test]
+async fn test_pyproject_preferred_over_setup_py() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("pyproject.toml"),
+        r#"
+[project]
+name = "modern-name"
+dynamic = ["version"]
+authors = [{ name = "Jane Dev", email = "jane@example.com" }]
+dependencies = ["httpx>=0.24", "rich[jupyter] ; python_version >= '3.8'"]
+
+[project.optional-dependencies]
+cli = ["click==8.1.7"]
+
+[project.urls]
+Homepage = "https://example.com"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("setup.py"),
+        "from setuptools import setup\nsetup(name='legacy-name', version='0.9.0')\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("pdm.lock"),
+        "[[package]]\nname = \"httpx\"\nversion = \"0.24.1\"\n",
+    )
+    .unwrap();
+
+    let analyzer = PythonAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let metadata = result.package_info().metadata();
+    assert_eq!(metadata.name, "modern-name");
+    // The dynamic version is filled in from setup.py
+    assert_eq!(metadata.version, "0.9.0");
+    assert_eq!(metadata.author.as_deref(), Some("Jane Dev"));
+    assert_eq!(metadata.homepage.as_deref(), Some("https://example.com"));
+
+    let deps: Vec<(&str, &str, Option<&str>, DependencyType)> = result
+        .dependency_analysis()
+        .dependency_tree
+        .iter()
+        .map(|dep| {
+            (
+                dep.name.as_str(),
+                dep.version_spec.as_str(),
+                dep.resolved_version.as_deref(),
+                dep.dependency_type.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        deps,
+        [
+            ("httpx", ">=0.24", Some("0.24.1"), DependencyType::Runtime),
+            ("rich", "*", None, DependencyType::Runtime),
+            ("click", "==8.1.7", None, DependencyType::Optional),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_requirements_with_includes_hashes_and_markers() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("setup.py"),
+        "from setuptools import setup\nsetup(name='acme-worker', version='1.0.0')\n",
+    )
+    .unwrap();
+    fs::create_dir(temp_dir.path().join("requirements")).unwrap();
+    fs::write(
+        temp_dir.path().join("requirements.txt"),
+        "-r requirements/base.txt\n\
+         requests[security]==2.31.0 \\\n    \
+         --hash=sha256:58cd2187c01e70e6e26505bca751777aa9f2ee0b7f4300988b709f44e013003f\n\
+         importlib-metadata==6.8.0 ; python_version < \"3.8\"\n\
+         -e git+https://github.com/acme/acme-tools.git@v2.0#egg=acme-tools\n",
+    )
+    .unwrap();
+    fs::write(
+        temp_dir.path().join("requirements/base.txt"),
+        "helper @ https://files.acme.test/helper-1.0.tar.gz\npyyaml==6.0.1\n",
+    )
+    .unwrap();
+
+    let options = AnalysisOptions {
+        python_env_markers: [("python_version", "3.11"), ("sys_platform", "linux")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        ..AnalysisOptions::default()
+    };
+    let analyzer = PythonAnalyzer::new().unwrap();
+    let result = analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+
+    let deps = &result.dependency_analysis().dependency_tree;
+    let names: Vec<&str> = deps.iter().map(|dep| dep.name.as_str()).collect();
+    // importlib-metadata is only installed before Python 3.8
+    assert_eq!(names, ["requests", "acme-tools", "helper", "pyyaml"]);
+    assert_eq!(
+        deps[0].hashes,
+        ["sha256:58cd2187c01e70e6e26505bca751777aa9f2ee0b7f4300988b709f44e013003f"]
+    );
+    assert_eq!(
+        deps[1].source,
+        Some(DependencySource::Git {
+            url: "https://github.com/acme/acme-tools.git".to_string(),
+            revision: Some("v2.0".to_string()),
+        })
+    );
+
+    // The editable install ranks above the archive URL
+    let non_index: Vec<(&str, RiskLevel, &str)> = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .filter(|f| f.finding_type == FindingType::NonRegistrySource)
+        .map(|f| {
+            (
+                f.affected_components[0].as_str(),
+                f.severity,
+                f.evidence[1].as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        non_index,
+        [
+            ("acme-tools", RiskLevel::High, "requirements.txt:5"),
+            ("helper", RiskLevel::Medium, "requirements/base.txt:1"),
+        ]
+    );
+
+    // Without an environment every requirement is kept
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert_eq!(result.dependency_analysis().dependency_tree.len(), 5);
+}
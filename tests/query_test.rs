@@ -0,0 +1,189 @@
+//! Integration tests for querying findings across an analysis result
+
+use std::fs;
+use tempfile::TempDir;
+use threatflux_package_security::analyzers::npm::NpmAnalysisResult;
+use threatflux_package_security::core::{
+    AnalysisOptions, Dependency, DependencyType, Finding, FindingType, PackageAnalyzer,
+};
+use threatflux_package_security::{
+    AnalysisResult, NpmAnalyzer, RiskLevel, Vulnerability, VulnerabilitySeverity,
+};
+
+/// `app -> lodash@4.17.10 -> vuln-helper` with an unpinned direct dependency
+async fn analyzed_fixture() -> NpmAnalysisResult {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "query-fixture",
+        "version": "1.0.0",
+        "dependencies": {
+            "lodash": "4.17.10",
+            "chalk": "*"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let mut result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    let lodash = result
+        .dependency_analysis
+        .dependency_tree
+        .iter_mut()
+        .find(|dep| dep.name == "lodash")
+        .unwrap();
+    assert!(lodash.is_direct);
+    assert!(!lodash.vulnerabilities.is_empty());
+    let mut advisory: Vulnerability = lodash.vulnerabilities[0].clone();
+    advisory.id = "TEST-0001".to_string();
+    advisory.severity = VulnerabilitySeverity::Critical;
+    lodash.dependencies.push(Dependency {
+        name: "vuln-helper".to_string(),
+        version_spec: "1.0.0".to_string(),
+        resolved_version: Some("1.0.0".to_string()),
+        dependency_type: DependencyType::Runtime,
+        is_direct: false,
+        is_dev: false,
+        vulnerabilities: vec![advisory],
+        license: None,
+        dependencies: vec![],
+        alias_target: None,
+        range_vulnerabilities: vec![],
+        shadowed_declarations: vec![],
+        source: None,
+        platform: None,
+        hashes: vec![],
+        marker: None,
+    });
+    result.risk_assessment.detailed_findings.push(
+        Finding::new(
+            FindingType::PolicyViolation,
+            RiskLevel::High,
+            "Helper fails policy",
+            "Transitive helper violates policy",
+        )
+        .with_affected_component("vuln-helper"),
+    );
+    result
+}
+
+#[tokio::test]
+async fn test_query_combines_predicates() {
+    let result = analyzed_fixture().await;
+
+    let vulns = result
+        .query()
+        .category(FindingType::Vulnerability)
+        .collect();
+    assert!(vulns
+        .iter()
+        .all(|f| f.finding_type == FindingType::Vulnerability));
+    assert!(vulns
+        .iter()
+        .any(|f| f.affected_components == ["vuln-helper"]));
+
+    // The critical advisory sits on a transitive dependency, which direct_only drops
+    let critical = result
+        .query()
+        .severity_at_least(RiskLevel::Critical)
+        .category(FindingType::Vulnerability);
+    assert!(critical.iter().any(|f| f.title.starts_with("TEST-0001")));
+    let direct = result
+        .query()
+        .severity_at_least(RiskLevel::High)
+        .category(FindingType::Vulnerability)
+        .direct_only()
+        .collect();
+    assert!(direct
+        .iter()
+        .all(|f| f.affected_components == ["lodash"] && f.severity >= RiskLevel::High));
+
+    // Categories are alternatives; detailed findings on transitive deps are dropped too
+    let either = result
+        .query()
+        .category(FindingType::PolicyViolation)
+        .category(FindingType::UnpinnedDependency);
+    assert_eq!(either.count(), 2);
+    let direct_either = result
+        .query()
+        .category(FindingType::PolicyViolation)
+        .category(FindingType::UnpinnedDependency)
+        .direct_only()
+        .collect();
+    assert_eq!(direct_either.len(), 1);
+    assert_eq!(direct_either[0].affected_components, ["chalk"]);
+
+    let helper = result.query().component("vuln-helper").collect();
+    assert_eq!(helper.len(), 2);
+    assert!(result
+        .query()
+        .component("vuln-helper")
+        .direct_only()
+        .first()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_query_through_trait_object() {
+    let result: Box<dyn AnalysisResult> = Box::new(analyzed_fixture().await);
+    let all = result.query().count();
+    let findings = result.risk_assessment().detailed_findings.len();
+    assert!(all >= findings + result.vulnerabilities().len());
+    assert_eq!(
+        result.query().severity_at_least(RiskLevel::Safe).count(),
+        all
+    );
+}
+
+#[tokio::test]
+async fn test_vulnerability_findings_tagged_with_reachability() {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "config-merge",
+        "version": "1.0.0",
+        "main": "index.js",
+        "dependencies": { "lodash": "4.17.10" }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    fs::write(
+        temp_dir.path().join("index.js"),
+        "const _ = require('lodash');\nmodule.exports = (user) => _.defaultsDeep(user, {});\n",
+    )
+    .unwrap();
+
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let reachability = |result: &NpmAnalysisResult| {
+        result
+            .query()
+            .category(FindingType::Vulnerability)
+            .collect()
+            .into_iter()
+            .map(|finding| (finding.title, finding.likely_reachable))
+            .collect::<Vec<_>>()
+    };
+
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert!(reachability(&result)
+        .iter()
+        .all(|(_, reachable)| reachable.is_none()));
+
+    let options = AnalysisOptions {
+        analyze_reachability: true,
+        ..AnalysisOptions::default()
+    };
+    let result = analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+    let findings = reachability(&result);
+    let (_, reachable) = findings
+        .iter()
+        .find(|(title, _)| title.starts_with("CVE-2019-10744"))
+        .expect("defaultsDeep advisory reported");
+    assert_eq!(*reachable, Some(true));
+    // Advisories without symbol data stay unknown
+    assert!(findings
+        .iter()
+        .filter(|(title, _)| !title.starts_with("CVE-2019-10744"))
+        .all(|(_, reachable)| reachable.is_none()));
+}
@@ -0,0 +1,555 @@
+//! Integration tests for report output formats
+
+use std::fs;
+use tempfile::TempDir;
+use threatflux_package_security::analyzers::npm::NpmAnalysisResult;
+use threatflux_package_security::core::Finding;
+use threatflux_package_security::report::{FindingBaseline, GithubAnnotations, NdjsonWriter};
+use threatflux_package_security::{
+    AnalysisResult, NpmAnalyzer, PackageAnalyzer, PackageSecurityAnalyzer,
+};
+
+fn npm_package(dir: &TempDir, name: &str, dependencies: &str) -> std::path::PathBuf {
+    let path = dir.path().join(name);
+    fs::create_dir(&path).unwrap();
+    fs::write(
+        path.join("package.json"),
+        format!(
+            r#"{{"name": "{}", "version": "1.0.0", "dependencies": {{{}}}}}"#,
+            name, dependencies
+        ),
+    )
+    .unwrap();
+    path
+}
+
+#[tokio::test]
+async fn test_ndjson_streams_one_line_per_package() {
+    let temp_dir = TempDir::new().unwrap();
+    let paths = [
+        npm_package(&temp_dir, "acme-first-service", r#""lodash": "4.17.10""#),
+        npm_package(&temp_dir, "acme-second-service", ""),
+    ];
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let mut output = Vec::new();
+    let lines = analyzer
+        .analyze_to_ndjson(&paths, &mut output, false)
+        .await
+        .unwrap();
+    assert_eq!(lines, 2);
+
+    let output = String::from_utf8(output).unwrap();
+    let mut records: Vec<serde_json::Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(records.len(), 2);
+    // Lines come in completion order
+    records.sort_by_key(|record| record["package"]["name"].to_string());
+    assert_eq!(records[0]["kind"], "result");
+    assert_eq!(records[0]["package"]["name"], "acme-first-service");
+    assert_eq!(records[0]["package"]["ecosystem"], "npm");
+    assert_eq!(records[1]["package"]["name"], "acme-second-service");
+}
+
+#[tokio::test]
+async fn test_ndjson_reports_failed_packages() {
+    let temp_dir = TempDir::new().unwrap();
+    let missing = temp_dir.path().join("acme-missing-service");
+    let paths = [
+        npm_package(&temp_dir, "acme-fourth-service", ""),
+        missing.clone(),
+    ];
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let mut output = Vec::new();
+    let lines = analyzer
+        .analyze_to_ndjson(&paths, &mut output, false)
+        .await
+        .unwrap();
+    assert_eq!(lines, 2);
+
+    let output = String::from_utf8(output).unwrap();
+    let records: Vec<serde_json::Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert!(records
+        .iter()
+        .any(|r| r["kind"] == "result" && r["package"]["name"] == "acme-fourth-service"));
+    let error = records.iter().find(|r| r["kind"] == "error").unwrap();
+    assert_eq!(error["path"], missing.display().to_string());
+    assert!(!error["error"].as_str().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_ndjson_per_finding_lines_carry_identity() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = npm_package(&temp_dir, "acme-third-service", r#""lodash": "4.17.10""#);
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer.analyze(&path).await.unwrap();
+
+    let mut writer = NdjsonWriter::new(Vec::new());
+    let written = writer.write_findings(result.as_ref()).unwrap();
+    assert!(written >= result.vulnerabilities().len());
+    assert_eq!(writer.lines_written(), written);
+
+    let output = String::from_utf8(writer.into_inner()).unwrap();
+    for line in output.lines() {
+        let record: serde_json::Value = serde_json::from_str(line).unwrap();
+        assert_eq!(record["package"]["name"], "acme-third-service");
+        assert_eq!(record["package"]["version"], "1.0.0");
+    }
+    assert!(output
+        .lines()
+        .any(|l| l.contains(r#""kind":"vulnerability""#)));
+}
+
+#[tokio::test]
+async fn test_results_sort_worst_first() {
+    let temp_dir = TempDir::new().unwrap();
+    let paths = [
+        npm_package(&temp_dir, "acme-clean-b", ""),
+        npm_package(&temp_dir, "acme-vulnerable", r#""lodash": "4.17.10""#),
+        npm_package(&temp_dir, "acme-clean-a", ""),
+        npm_package(&temp_dir, "acme-unpinned", r#""left-pad": "*""#),
+    ];
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let mut batch = Vec::new();
+    for path in &paths {
+        batch.push(analyzer.analyze(path).await.unwrap());
+    }
+    let mut reversed: Vec<_> = Vec::new();
+    for path in paths.iter().rev() {
+        reversed.push(analyzer.analyze(path).await.unwrap());
+    }
+
+    batch.sort();
+    reversed.sort();
+    let names = |results: &[Box<dyn AnalysisResult>]| -> Vec<String> {
+        results
+            .iter()
+            .map(|r| r.package_info().metadata().name.clone())
+            .collect()
+    };
+    assert_eq!(names(&batch), names(&reversed));
+
+    // Levels never increase along the sorted batch
+    let keys: Vec<_> = batch.iter().map(|r| r.risk_ordering_key()).collect();
+    assert!(keys.windows(2).all(|w| w[0].risk_level >= w[1].risk_level));
+    assert!(keys[0].finding_count > 0);
+
+    // Equally risky packages fall back to name order
+    assert_eq!(names(&batch)[2..], ["acme-clean-a", "acme-clean-b"]);
+}
+
+#[tokio::test]
+async fn test_diff_persisted_results_by_fingerprint() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = npm_package(
+        &temp_dir,
+        "acme-history",
+        r#""lodash": "4.17.10", "left-pad": "*""#,
+    );
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let stored = serde_json::to_string(&analyzer.analyze(&path).await.unwrap()).unwrap();
+
+    // The lodash upgrade lands and a new unpinned dependency comes in
+    fs::write(
+        path.join("package.json"),
+        r#"{"name": "acme-history", "version": "1.1.0", "dependencies": {"left-pad": "*", "chalk": "latest"}}"#,
+    )
+    .unwrap();
+    let current = analyzer.analyze(&path).await.unwrap();
+    drop(temp_dir);
+
+    let mut baseline: NpmAnalysisResult = serde_json::from_str(&stored).unwrap();
+    let diff = baseline.diff(&current);
+    let components = |findings: &[Finding]| -> Vec<String> {
+        findings
+            .iter()
+            .flat_map(|f| f.affected_components.clone())
+            .collect()
+    };
+    assert!(!diff.removed.is_empty());
+    assert!(components(&diff.removed).iter().all(|c| c == "lodash"));
+    assert_eq!(components(&diff.added), ["chalk"]);
+    assert_eq!(components(&diff.unchanged), ["left-pad"]);
+    assert!(diff.score_delta < 0.0);
+    assert!(diff.risk_level_delta() <= 0);
+    assert!(!diff.tooling_changed);
+    assert!(diff.notes.is_empty());
+
+    // A baseline from another tool version is still compared, with a note
+    baseline
+        .risk_assessment
+        .scan_context
+        .as_mut()
+        .unwrap()
+        .tool_version = "0.0.1".to_string();
+    let diff = baseline.diff(&current);
+    assert!(diff.tooling_changed);
+    assert!(diff.notes[0].contains("0.0.1"));
+    assert_eq!(components(&diff.unchanged), ["left-pad"]);
+}
+
+#[tokio::test]
+async fn test_diff_vulnerabilities_and_dependencies() {
+    use threatflux_package_security::core::DependencyChange;
+    use threatflux_package_security::VulnerabilitySeverity;
+
+    let temp_dir = TempDir::new().unwrap();
+    let path = npm_package(
+        &temp_dir,
+        "acme-gate",
+        r#""lodash": "4.17.10", "left-pad": "1.3.0""#,
+    );
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let baseline = analyzer.analyze(&path).await.unwrap();
+
+    // lodash is bumped past its advisories, left-pad goes and minimist comes
+    fs::write(
+        path.join("package.json"),
+        r#"{"name": "acme-gate", "version": "1.1.0", "dependencies": {"lodash": "4.17.21", "minimist": "1.2.0"}}"#,
+    )
+    .unwrap();
+    let current = analyzer.analyze(&path).await.unwrap();
+
+    let diff = threatflux_package_security::diff(baseline.as_ref(), current.as_ref());
+    let ids: Vec<&str> = diff
+        .added_vulnerabilities
+        .iter()
+        .map(|v| v.id.as_str())
+        .collect();
+    assert_eq!(ids, ["CVE-2020-7598"]);
+    assert!(diff
+        .resolved_vulnerabilities
+        .iter()
+        .any(|v| v.id == "CVE-2019-10744"));
+    assert_eq!(
+        diff.changed_dependencies,
+        [DependencyChange {
+            name: "lodash".to_string(),
+            baseline_versions: vec!["4.17.10".to_string()],
+            versions: vec!["4.17.21".to_string()],
+        }]
+    );
+    assert_eq!(diff.added_dependencies[0].name, "minimist");
+    assert_eq!(diff.removed_dependencies[0].name, "left-pad");
+    assert!(diff.adds_vulnerability_at_least(VulnerabilitySeverity::Medium));
+    assert!(!diff.adds_vulnerability_at_least(VulnerabilitySeverity::Critical));
+
+    let unchanged = threatflux_package_security::diff(current.as_ref(), current.as_ref());
+    assert!(unchanged.is_empty());
+}
+
+#[tokio::test]
+async fn test_annotate_only_findings_new_since_baseline() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = npm_package(&temp_dir, "acme-checks", r#""left-pad": "*""#);
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let baseline = analyzer.analyze(&path).await.unwrap();
+
+    // The pull request adds another unpinned dependency
+    let manifest = r#"{
+  "name": "acme-checks",
+  "version": "1.0.0",
+  "dependencies": {
+    "left-pad": "*",
+    "chalk": "latest"
+  }
+}"#;
+    fs::write(path.join("package.json"), manifest).unwrap();
+    let current = analyzer.analyze(&path).await.unwrap();
+
+    let annotations = GithubAnnotations::new("services/checks/package.json", manifest)
+        .with_baseline(FindingBaseline::from_result(&baseline));
+    let lines = annotations.annotations(&current);
+    assert_eq!(lines.len(), 1, "{lines:?}");
+    assert_eq!(
+        lines[0],
+        "::error file=services/checks/package.json,line=6,\
+         title=TFX-DEP-UNPINNED-001%3A Dependency 'chalk' accepts any version::\
+         Every future release in the declared range, including a compromised one, will be installed"
+    );
+
+    // The same baseline given as stored fingerprints
+    let fingerprints: Vec<String> = baseline.query().iter().map(|f| f.fingerprint()).collect();
+    let mut out = Vec::new();
+    let written = GithubAnnotations::new("services/checks/package.json", manifest)
+        .with_baseline(FindingBaseline::from_fingerprints(fingerprints))
+        .write(&mut out, &current)
+        .unwrap();
+    assert_eq!(written, 1);
+    assert_eq!(String::from_utf8(out).unwrap(), format!("{}\n", lines[0]));
+}
+
+#[tokio::test]
+async fn test_cyclonedx_sbom() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = npm_package(
+        &temp_dir,
+        "acme-sbom-service",
+        r#""lodash": "4.17.10", "@babel/core": "^7.0.0""#,
+    );
+    fs::write(
+        path.join("package-lock.json"),
+        r#"{"lockfileVersion": 3, "packages": {
+            "": {"dependencies": {"lodash": "4.17.10", "@babel/core": "^7.0.0"}},
+            "node_modules/lodash": {"version": "4.17.10"},
+            "node_modules/@babel/core": {"version": "7.24.0", "dependencies": {"lodash": "^4.17.0"}}
+        }}"#,
+    )
+    .unwrap();
+    let result = NpmAnalyzer::new().unwrap().analyze(&path).await.unwrap();
+    let bom = result.to_cyclonedx().unwrap();
+
+    assert_eq!(bom["bomFormat"], "CycloneDX");
+    assert_eq!(bom["specVersion"], "1.5");
+    assert_eq!(
+        bom["metadata"]["component"]["purl"],
+        "pkg:npm/acme-sbom-service@1.0.0"
+    );
+    let components = bom["components"].as_array().unwrap();
+    let refs: Vec<&str> = components
+        .iter()
+        .map(|c| c["bom-ref"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        refs,
+        ["pkg:npm/%40babel/core@7.24.0", "pkg:npm/lodash@4.17.10"]
+    );
+
+    // Every reference points at a component in the document
+    let known = |reference: &str| {
+        reference == "pkg:npm/acme-sbom-service@1.0.0" || refs.contains(&reference)
+    };
+    for edge in bom["dependencies"].as_array().unwrap() {
+        assert!(known(edge["ref"].as_str().unwrap()));
+        for target in edge["dependsOn"].as_array().unwrap() {
+            assert!(known(target.as_str().unwrap()), "{}", target);
+        }
+    }
+    let vulnerabilities = bom["vulnerabilities"].as_array().unwrap();
+    assert!(!vulnerabilities.is_empty());
+    for vulnerability in vulnerabilities {
+        assert!(vulnerability["id"].as_str().unwrap().starts_with("CVE-"));
+        assert!(vulnerability["ratings"][0]["severity"].is_string());
+        for affected in vulnerability["affects"].as_array().unwrap() {
+            assert!(known(affected["ref"].as_str().unwrap()));
+        }
+    }
+    assert!(vulnerabilities.iter().any(|v| {
+        v["affects"][0]["ref"] == "pkg:npm/lodash@4.17.10" && v["ratings"][0]["score"].is_number()
+    }));
+}
+
+#[test]
+fn test_package_urls() {
+    use threatflux_package_security::report::sbom::purl;
+
+    assert_eq!(
+        purl("python", "Flask_Login", Some("0.6.3")),
+        "pkg:pypi/flask-login@0.6.3"
+    );
+    assert_eq!(
+        purl(
+            "java",
+            "org.apache.logging.log4j:log4j-core",
+            Some("2.14.1")
+        ),
+        "pkg:maven/org.apache.logging.log4j/log4j-core@2.14.1"
+    );
+    assert_eq!(
+        purl("go", "github.com/gorilla/websocket", Some("v1.4.0")),
+        "pkg:golang/github.com/gorilla/websocket@v1.4.0"
+    );
+    assert_eq!(purl("cargo", "serde", None), "pkg:cargo/serde");
+    assert_eq!(
+        purl("helm", "nginx", Some("1.0.0")),
+        "pkg:generic/nginx@1.0.0"
+    );
+}
+
+#[tokio::test]
+async fn test_spdx_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("acme-spdx-service");
+    fs::create_dir(&path).unwrap();
+    fs::write(
+        path.join("package.json"),
+        r#"{"name": "acme-spdx-service", "version": "2.1.0",
+            "license": "SEE LICENSE IN LICENSE.txt",
+            "dependencies": {"lodash": "4.17.10", "@babel/core": "^7.0.0"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        path.join("package-lock.json"),
+        r#"{"lockfileVersion": 3, "packages": {
+            "": {"dependencies": {"lodash": "4.17.10", "@babel/core": "^7.0.0"}},
+            "node_modules/lodash": {"version": "4.17.10", "license": "MIT"},
+            "node_modules/@babel/core": {"version": "7.24.0", "dependencies": {"lodash": "^4.17.0"}}
+        }}"#,
+    )
+    .unwrap();
+    let result = NpmAnalyzer::new().unwrap().analyze(&path).await.unwrap();
+    let emitted = serde_json::to_string(&result.to_spdx().unwrap()).unwrap();
+    let document: serde_json::Value = serde_json::from_str(&emitted).unwrap();
+
+    assert_eq!(document["spdxVersion"], "SPDX-2.3");
+    assert_eq!(document["SPDXID"], "SPDXRef-DOCUMENT");
+    let packages = document["packages"].as_array().unwrap();
+    assert_eq!(packages.len(), 3);
+    let ids: Vec<&str> = packages
+        .iter()
+        .map(|p| p["SPDXID"].as_str().unwrap())
+        .collect();
+    let root = &packages[0];
+    assert_eq!(root["name"], "acme-spdx-service");
+    // Not an SPDX expression, so no license is asserted
+    assert_eq!(root["licenseDeclared"], "NOASSERTION");
+
+    // Root -> lodash and root -> @babel/core, plus the document DESCRIBES the root
+    let relationships = document["relationships"].as_array().unwrap();
+    let depends_on: Vec<_> = relationships
+        .iter()
+        .filter(|r| r["relationshipType"] == "DEPENDS_ON")
+        .collect();
+    assert_eq!(depends_on.len(), 2);
+    assert_eq!(relationships.len(), 3);
+    for relationship in relationships {
+        assert!(ids.contains(&relationship["relatedSpdxElement"].as_str().unwrap()));
+    }
+    assert!(packages
+        .iter()
+        .all(|p| p["externalRefs"][0]["referenceLocator"]
+            .as_str()
+            .unwrap()
+            .starts_with("pkg:npm/")));
+}
+
+#[test]
+fn test_spdx_expressions() {
+    use threatflux_package_security::report::sbom::is_spdx_expression;
+
+    assert!(is_spdx_expression("MIT"));
+    assert!(is_spdx_expression("(MIT OR Apache-2.0) AND BSD-3-Clause"));
+    assert!(is_spdx_expression(
+        "GPL-2.0-or-later WITH Classpath-exception-2.0"
+    ));
+    assert!(is_spdx_expression("LicenseRef-acme-proprietary"));
+    assert!(!is_spdx_expression("SEE LICENSE IN LICENSE.txt"));
+    assert!(!is_spdx_expression("MIT OR"));
+    assert!(!is_spdx_expression("(MIT"));
+    assert!(!is_spdx_expression(""));
+}
+
+#[tokio::test]
+async fn test_sarif_locations() {
+    use threatflux_package_security::report::SarifReport;
+
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("acme-agent");
+    fs::create_dir_all(path.join("scripts")).unwrap();
+    let manifest = r#"{
+  "name": "acme-agent",
+  "version": "1.0.0",
+  "scripts": {"postinstall": "node scripts/setup.js"},
+  "dependencies": {
+    "lodash": "4.17.10"
+  }
+}"#;
+    let script = "const { execSync } = require('child_process');\n\
+                  // keep the agent running\n\
+                  execSync('systemctl --user enable acme-agent.service');\n";
+    fs::write(path.join("package.json"), manifest).unwrap();
+    fs::write(path.join("scripts/setup.js"), script).unwrap();
+    let result = NpmAnalyzer::new().unwrap().analyze(&path).await.unwrap();
+
+    let log = SarifReport::new("services/agent/package.json", manifest)
+        .report(&result)
+        .unwrap();
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+    let results = run["results"].as_array().unwrap();
+
+    // Every result's rule is described by the driver
+    for result in results {
+        let rule = &rules[result["ruleIndex"].as_u64().unwrap() as usize];
+        assert_eq!(rule["id"], result["ruleId"]);
+        assert!(result["partialFingerprints"]["threatfluxFinding/v1"].is_string());
+    }
+
+    let vulnerability = results
+        .iter()
+        .find(|r| r["ruleId"] == "TFX-VULN-DEP-001")
+        .unwrap();
+    let location = &vulnerability["locations"][0]["physicalLocation"];
+    assert_eq!(
+        location["artifactLocation"]["uri"],
+        "services/agent/package.json"
+    );
+    assert_eq!(location["region"]["startLine"], 6);
+
+    // The persistence pattern points into the install script
+    let persistence = results
+        .iter()
+        .find(|r| r["ruleId"] == "TFX-PATTERN-PERS-005")
+        .unwrap();
+    assert_eq!(persistence["level"], "error");
+    let location = &persistence["locations"][0]["physicalLocation"];
+    assert_eq!(
+        location["artifactLocation"]["uri"],
+        "services/agent/scripts/setup.js"
+    );
+    assert_eq!(location["region"]["startLine"], 3);
+    assert_eq!(location["region"]["startColumn"], 11);
+    assert_eq!(
+        location["region"]["byteOffset"],
+        script.find("systemctl").unwrap()
+    );
+
+    // Without file contents the manifest name is still known
+    let log = result.to_sarif().unwrap();
+    assert!(log["runs"][0]["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(
+            |r| r["locations"][0]["physicalLocation"]["artifactLocation"]["uri"] == "package.json"
+        ));
+}
+
+#[tokio::test]
+async fn test_text_report() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = npm_package(&temp_dir, "acme-text-service", r#""lodash": "4.17.10""#);
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer.analyze(&path).await.unwrap();
+
+    let plain = result.to_text_report(false);
+    assert!(plain.is_ascii());
+    assert!(!plain.contains('\x1b'));
+    assert!(plain.starts_with("Package: acme-text-service@1.0.0 (npm)\n"));
+    let level = result.overall_risk_level().to_string().to_uppercase();
+    assert!(plain.contains(&format!("Risk level: {}\n", level)));
+    assert!(plain.contains("Supply chain score: "));
+    assert!(plain.contains("  PACKAGE"));
+    let vulnerability = &result.dependency_analysis().dependency_tree[0].vulnerabilities[0];
+    assert!(plain
+        .lines()
+        .any(|line| line.trim_start().starts_with("lodash") && line.contains(&vulnerability.id)));
+
+    let colored = result.to_text_report(true);
+    assert!(colored.contains("\x1b["));
+    assert_eq!(
+        colored.lines().count(),
+        plain.lines().count(),
+        "color changes nothing but the escape codes"
+    );
+}
@@ -0,0 +1,117 @@
+//! Integration tests for incremental lockfile re-scans
+
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+use threatflux_package_security::analyzers::npm::NpmAnalysisResult;
+use threatflux_package_security::core::{AnalysisOptions, PackageAnalyzer};
+use threatflux_package_security::{AnalysisResult, NpmAnalyzer};
+
+fn write_lockfile(dir: &Path, lodash: &str) {
+    let lockfile = format!(
+        r#"{{
+        "name": "rescan-fixture",
+        "version": "1.0.0",
+        "lockfileVersion": 3,
+        "packages": {{
+            "": {{ "name": "rescan-fixture", "version": "1.0.0" }},
+            "node_modules/lodash": {{ "version": "{}" }},
+            "node_modules/chalk": {{ "version": "5.3.0" }},
+            "node_modules/chalk/node_modules/lodash": {{ "version": "1.0.0" }}
+        }}
+    }}"#,
+        lodash
+    );
+    fs::write(dir.join("package-lock.json"), lockfile).unwrap();
+}
+
+fn fixture() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let package_json = r#"{
+        "name": "rescan-fixture",
+        "version": "1.0.0",
+        "dependencies": {
+            "lodash": "^4.17.0",
+            "chalk": "^5.0.0"
+        }
+    }"#;
+    fs::write(temp_dir.path().join("package.json"), package_json).unwrap();
+    write_lockfile(temp_dir.path(), "4.17.10");
+    temp_dir
+}
+
+#[tokio::test]
+async fn test_lockfile_bump_reanalyzes_only_changed_dependency() {
+    let temp_dir = fixture();
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let options = AnalysisOptions::default();
+    let previous = analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+
+    let lodash = |result: &NpmAnalysisResult| {
+        result
+            .dependency_analysis
+            .dependency_tree
+            .iter()
+            .find(|dep| dep.name == "lodash")
+            .cloned()
+            .unwrap()
+    };
+    assert_eq!(
+        lodash(&previous).resolved_version.as_deref(),
+        Some("4.17.10")
+    );
+    assert!(!lodash(&previous).vulnerabilities.is_empty());
+
+    write_lockfile(temp_dir.path(), "4.17.21");
+    let (rescanned, stats) = analyzer
+        .rescan_lockfile(temp_dir.path(), &previous, &options)
+        .await
+        .unwrap();
+    assert!(!stats.full_scan);
+    assert_eq!(stats.reanalyzed, 1);
+    assert_eq!(stats.reused, 1);
+    assert_eq!(
+        lodash(&rescanned).resolved_version.as_deref(),
+        Some("4.17.21")
+    );
+    assert!(lodash(&rescanned).vulnerabilities.is_empty());
+
+    let full = analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+    assert_eq!(rescanned.to_json().unwrap(), full.to_json().unwrap());
+}
+
+#[tokio::test]
+async fn test_manifest_change_forces_full_scan() {
+    let temp_dir = fixture();
+    let analyzer = NpmAnalyzer::new().unwrap();
+    let options = AnalysisOptions::default();
+    let previous = analyzer
+        .analyze_with_options(temp_dir.path(), &options)
+        .await
+        .unwrap();
+
+    fs::write(
+        temp_dir.path().join("index.js"),
+        "module.exports = () => eval(process.argv[2]);",
+    )
+    .unwrap();
+    let (_, stats) = analyzer
+        .rescan_lockfile(temp_dir.path(), &previous, &options)
+        .await
+        .unwrap();
+    assert!(stats.full_scan);
+    assert_eq!(stats.reused, 0);
+
+    // Options are part of the digest too
+    let (_, stats) = analyzer
+        .rescan_lockfile(temp_dir.path(), &previous, &AnalysisOptions::relaxed())
+        .await
+        .unwrap();
+    assert!(stats.full_scan);
+}
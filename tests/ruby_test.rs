@@ -0,0 +1,265 @@
+//! Integration tests for Ruby gem analysis
+
+use std::fs;
+use tempfile::TempDir;
+use threatflux_package_security::core::{DependencySource, DependencyType, FindingType};
+use threatflux_package_security::vulnerability_db::DatabaseSource;
+use threatflux_package_security::{
+    detect_ecosystem, AnalysisResult, Ecosystem, PackageAnalyzer, PackageSecurityAnalyzer,
+    RubyGemAnalyzer,
+};
+
+const GEMSPEC: &str = r#"
+Gem::Specification.new do |spec|
+  spec.name        = "ledger"
+  spec.version     = "1.4.0"
+  spec.summary     = "Double-entry bookkeeping"
+  spec.authors     = ["Ana"]
+  spec.license     = "MIT"
+
+  spec.add_dependency "nokogiri", "~> 1.15"
+  spec.add_development_dependency "rake", "~> 13.0"
+end
+"#;
+
+const GEMFILE: &str = r#"
+source "https://rubygems.org"
+
+gemspec
+
+gem "auth", git: "https://git.example.com/auth.git", tag: "v2.0"
+"#;
+
+const LOCK_FILE: &str = r#"GIT
+  remote: https://git.example.com/auth.git
+  revision: 3f2a9c1d
+  tag: v2.0
+  specs:
+    auth (2.0.0)
+      jwt (>= 2.0)
+
+PATH
+  remote: .
+  specs:
+    ledger (1.4.0)
+      nokogiri (~> 1.15)
+
+GEM
+  remote: https://rubygems.org/
+  specs:
+    jwt (2.7.1)
+    nokogiri (1.15.4-arm64-darwin)
+      racc (~> 1.4)
+    nokogiri (1.15.4-x86_64-linux)
+      racc (~> 1.4)
+    racc (1.7.3)
+    rake (13.1.0)
+
+PLATFORMS
+  arm64-darwin
+  x86_64-linux
+
+DEPENDENCIES
+  auth!
+  ledger!
+  rake (~> 13.0)
+
+BUNDLED WITH
+   2.5.3
+"#;
+
+fn write_project(temp_dir: &TempDir) {
+    fs::write(temp_dir.path().join("ledger.gemspec"), GEMSPEC).unwrap();
+    fs::write(temp_dir.path().join("Gemfile"), GEMFILE).unwrap();
+    fs::write(temp_dir.path().join("Gemfile.lock"), LOCK_FILE).unwrap();
+}
+
+#[tokio::test]
+async fn test_ruby_lockfile_sources_and_platforms() {
+    let temp_dir = TempDir::new().unwrap();
+    write_project(&temp_dir);
+
+    let analyzer = RubyGemAnalyzer::new().unwrap();
+    assert!(analyzer.can_analyze(temp_dir.path()));
+    assert_eq!(detect_ecosystem(temp_dir.path()), Some(Ecosystem::Ruby));
+
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let info = result.package_info();
+    assert_eq!(info.package_type(), "rubygems");
+    assert_eq!(info.metadata().name, "ledger");
+    assert_eq!(info.metadata().version, "1.4.0");
+    assert_eq!(result.package.bundler_version.as_deref(), Some("2.5.3"));
+
+    // The project's own gem is not a dependency of itself
+    let analysis = result.dependency_analysis();
+    let direct: Vec<(&str, Option<&str>)> = analysis
+        .dependency_tree
+        .iter()
+        .map(|dep| (dep.name.as_str(), dep.platform.as_deref()))
+        .collect();
+    assert_eq!(
+        direct,
+        [
+            ("auth", None),
+            ("rake", None),
+            ("nokogiri", Some("arm64-darwin")),
+            ("nokogiri", Some("x86_64-linux")),
+        ]
+    );
+
+    let auth = &analysis.dependency_tree[0];
+    assert_eq!(
+        auth.source,
+        Some(DependencySource::Git {
+            url: "https://git.example.com/auth.git".to_string(),
+            revision: Some("3f2a9c1d".to_string()),
+        })
+    );
+    assert_eq!(auth.dependencies[0].name, "jwt");
+    assert_eq!(
+        auth.dependencies[0].source.as_ref().and_then(|s| s.url()),
+        Some("https://rubygems.org/")
+    );
+    assert_eq!(
+        analysis.dependency_tree[1].dependency_type,
+        DependencyType::Development
+    );
+    let nokogiri = &analysis.dependency_tree[2];
+    assert_eq!(nokogiri.version_spec, "~> 1.15");
+    assert_eq!(nokogiri.resolved_version.as_deref(), Some("1.15.4"));
+    assert_eq!(nokogiri.dependencies[0].name, "racc");
+    assert!(result.risk_assessment().security_posture.supply_chain_risks);
+}
+
+#[tokio::test]
+async fn test_ruby_application_without_lockfile() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("Gemfile"),
+        "source \"http://rubygems.org\"\n\ngem \"sinatra\", \"~> 4.0\"\n",
+    )
+    .unwrap();
+
+    let result = PackageSecurityAnalyzer::new()
+        .unwrap()
+        .analyze(temp_dir.path())
+        .await
+        .unwrap();
+    assert_eq!(result.package_info().package_type(), "rubygems");
+    let analysis = result.dependency_analysis();
+    assert_eq!(analysis.dependency_tree.len(), 1);
+    assert_eq!(analysis.dependency_tree[0].resolved_version, None);
+
+    // The plain-HTTP registry is reported against the Gemfile
+    let insecure: Vec<_> = result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .filter(|f| f.finding_type == FindingType::InsecureTransport)
+        .collect();
+    assert_eq!(insecure.len(), 1);
+}
+
+/// Write a snapshot holding one advisory for `package`
+fn write_snapshot(
+    temp_dir: &TempDir,
+    package: &str,
+    id: &str,
+    affected: &str,
+    fixed: &str,
+) -> std::path::PathBuf {
+    let snapshot = temp_dir.path().join("advisories.json");
+    fs::write(
+        &snapshot,
+        serde_json::json!({
+            "format_version": 1,
+            "version": "2026.10.15",
+            "generated_at": "2026-10-15T00:00:00Z",
+            "entries": [{
+                "package_name": package,
+                "package_type": "rubygems",
+                "vulnerability": {
+                    "id": id,
+                    "title": id,
+                    "description": "",
+                    "severity": "High",
+                    "cvss_score": null,
+                    "cvss_vector": null,
+                    "affected_versions": [affected],
+                    "fixed_versions": [fixed],
+                    "published_date": null,
+                    "updated_date": null,
+                    "references": [],
+                    "cwe_ids": [],
+                    "exploit_available": false,
+                    "patch_available": true
+                }
+            }]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    snapshot
+}
+
+#[tokio::test]
+async fn test_ruby_vulnerabilities_across_platforms() {
+    let temp_dir = TempDir::new().unwrap();
+    write_project(&temp_dir);
+    let snapshot = write_snapshot(
+        &temp_dir,
+        "nokogiri",
+        "GHSA-xc9x-jj77-9p9j",
+        "< 1.16.5",
+        "1.16.5",
+    );
+
+    let analyzer = PackageSecurityAnalyzer::with_database(DatabaseSource::Path(snapshot))
+        .await
+        .unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    let ids: Vec<_> = result
+        .vulnerabilities()
+        .iter()
+        .map(|v| v.id.as_str())
+        .collect();
+    assert_eq!(ids, ["GHSA-xc9x-jj77-9p9j"]);
+}
+
+#[tokio::test]
+async fn test_ruby_four_component_versions() {
+    for (version, vulnerable) in [("7.0.4.2", true), ("7.0.4.3", false), ("7.0.10", false)] {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("Gemfile"),
+            format!("source \"https://rubygems.org\"\n\ngem \"rails\", \"{version}\"\n"),
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("Gemfile.lock"),
+            format!(
+                "GEM\n  remote: https://rubygems.org/\n  specs:\n    rails ({version})\n\n\
+                 PLATFORMS\n  ruby\n\nDEPENDENCIES\n  rails (= {version})\n"
+            ),
+        )
+        .unwrap();
+        let snapshot = write_snapshot(
+            &temp_dir,
+            "rails",
+            "GHSA-pj73-v5mw-pm9j",
+            ">= 7.0.0, < 7.0.4.3",
+            "7.0.4.3",
+        );
+
+        let analyzer = PackageSecurityAnalyzer::with_database(DatabaseSource::Path(snapshot))
+            .await
+            .unwrap();
+        let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+        assert_eq!(
+            !result.vulnerabilities().is_empty(),
+            vulnerable,
+            "{}",
+            version
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Integration tests for Terraform/OpenTofu module analysis
+
+use std::fs;
+use tempfile::TempDir;
+use threatflux_package_security::core::FindingType;
+use threatflux_package_security::{
+    detect_ecosystem, Ecosystem, PackageAnalyzer, PackageSecurityAnalyzer, TerraformAnalyzer,
+};
+
+const MAIN_TF: &str = r#"
+terraform {
+  required_version = ">= 1.5"
+
+  required_providers {
+    aws = {
+      source  = "hashicorp/aws"
+      version = "~> 5.0"
+    }
+  }
+}
+
+module "vpc" {
+  source  = "terraform-aws-modules/vpc/aws"
+  version = "5.1.0"
+}
+
+module "network" {
+  source = "git::https://example.com/infra/network.git"
+}
+
+module "local" {
+  source = "./modules/local"
+}
+"#;
+
+fn write_lock(dir: &std::path::Path, hash: &str) {
+    let lock = format!(
+        r#"
+provider "registry.terraform.io/hashicorp/aws" {{
+  version     = "5.31.0"
+  constraints = "~> 5.0"
+  hashes = [
+    "{}",
+  ]
+}}
+"#,
+        hash
+    );
+    fs::write(dir.join(".terraform.lock.hcl"), lock).unwrap();
+}
+
+fn install_provider(dir: &std::path::Path, content: &str) -> std::path::PathBuf {
+    let platform_dir =
+        dir.join(".terraform/providers/registry.terraform.io/hashicorp/aws/5.31.0/linux_amd64");
+    fs::create_dir_all(&platform_dir).unwrap();
+    fs::write(
+        platform_dir.join("terraform-provider-aws_v5.31.0_x5"),
+        content,
+    )
+    .unwrap();
+    platform_dir
+}
+
+#[tokio::test]
+async fn test_terraform_module_sources() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("main.tf"), MAIN_TF).unwrap();
+    write_lock(temp_dir.path(), "h1:bogus");
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    assert_eq!(result.package_info().package_type(), "terraform");
+
+    // Registry module, git module and the aws provider; local modules are skipped
+    let deps = &result.dependency_analysis().dependency_tree;
+    assert_eq!(deps.len(), 3);
+    let aws = deps.iter().find(|d| d.name == "hashicorp/aws").unwrap();
+    assert_eq!(aws.resolved_version.as_deref(), Some("5.31.0"));
+
+    let findings = &result.risk_assessment().detailed_findings;
+    let non_registry: Vec<_> = findings
+        .iter()
+        .filter(|f| f.finding_type == FindingType::NonRegistrySource)
+        .collect();
+    assert_eq!(non_registry.len(), 1);
+    assert!(non_registry[0]
+        .affected_components
+        .contains(&"network".to_string()));
+}
+
+#[tokio::test]
+async fn test_terraform_broken_file_skipped() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("main.tf"), MAIN_TF).unwrap();
+    fs::write(
+        temp_dir.path().join("broken.tf"),
+        "resource \"aws_s3_bucket\" \"logs\" {\n  bucket = \"logs\n}\n",
+    )
+    .unwrap();
+    write_lock(temp_dir.path(), "h1:bogus");
+
+    let analyzer = TerraformAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+
+    assert_eq!(result.package.module_calls.len(), 3);
+    assert_eq!(result.package.skipped_files.len(), 1);
+    assert_eq!(result.package.skipped_files[0].file, "broken.tf");
+    assert!(result.risk_assessment.is_partial());
+
+    // With nothing parseable left, the analysis still fails
+    fs::remove_file(temp_dir.path().join("main.tf")).unwrap();
+    assert!(analyzer.analyze(temp_dir.path()).await.is_err());
+}
+
+#[tokio::test]
+async fn test_terraform_lock_file_alone_is_not_a_module() {
+    let temp_dir = TempDir::new().unwrap();
+    write_lock(temp_dir.path(), "h1:bogus");
+
+    assert!(!TerraformAnalyzer::new()
+        .unwrap()
+        .can_analyze(temp_dir.path()));
+    assert_eq!(detect_ecosystem(temp_dir.path()), None);
+
+    fs::write(temp_dir.path().join("main.tf"), MAIN_TF).unwrap();
+    assert_eq!(
+        detect_ecosystem(temp_dir.path()),
+        Some(Ecosystem::Terraform)
+    );
+}
+
+#[tokio::test]
+async fn test_terraform_provider_hash_verification() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("main.tf"), MAIN_TF).unwrap();
+    let platform_dir = install_provider(temp_dir.path(), "original provider binary");
+    let hash = TerraformAnalyzer::provider_hash(&platform_dir).unwrap();
+    write_lock(temp_dir.path(), &hash);
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert!(!result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .any(|f| f.finding_type == FindingType::IntegrityMismatch));
+
+    // Tamper with the installed provider binary
+    install_provider(temp_dir.path(), "tampered provider binary");
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert!(result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .any(|f| f.finding_type == FindingType::IntegrityMismatch));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_terraform_provider_symlinks_not_followed() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("main.tf"), MAIN_TF).unwrap();
+    let platform_dir = install_provider(temp_dir.path(), "original provider binary");
+    std::os::unix::fs::symlink("loop", platform_dir.join("loop")).unwrap();
+    std::os::unix::fs::symlink("..", platform_dir.join("up")).unwrap();
+    let hash = TerraformAnalyzer::provider_hash(&platform_dir).unwrap();
+    write_lock(temp_dir.path(), &hash);
+
+    let analyzer = PackageSecurityAnalyzer::new().unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert!(!result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .any(|f| f.finding_type == FindingType::IntegrityMismatch));
+
+    // Repointing a link changes the hash
+    fs::remove_file(platform_dir.join("up")).unwrap();
+    std::os::unix::fs::symlink("/usr/bin", platform_dir.join("up")).unwrap();
+    let result = analyzer.analyze(temp_dir.path()).await.unwrap();
+    assert!(result
+        .risk_assessment()
+        .detailed_findings
+        .iter()
+        .any(|f| f.finding_type == FindingType::IntegrityMismatch));
+}
+
+#[test]
+fn test_provider_hash_matches_terraform_dirhash() {
+    // h1 = base64(sha256("<sha256(file)>  <path>\n" for each file in sorted order))
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("a"), "").unwrap();
+    let hash = TerraformAnalyzer::provider_hash(temp_dir.path()).unwrap();
+    assert_eq!(hash, "h1:+6GTBXDPLTOioF/0Ok/qUyOCYR9Dh2mI/Jwx/ZzhAtE=");
+}